@@ -31,6 +31,8 @@
 //! curl -v -x http://127.0.0.1:62017 --proxy-user 'john:secret' http://www.example.com/
 //! curl -k -v -x http://127.0.0.1:62017 --proxy-user 'john:secret' https://www.example.com/
 //! ```
+//!
+//! WebSocket connections are relayed as well, with all relayed messages written to stdout.
 
 use rama::{
     Layer, Service,
@@ -45,11 +47,15 @@ use rama::{
             remove_header::{RemoveRequestHeaderLayer, RemoveResponseHeaderLayer},
             required_header::AddRequiredRequestHeadersLayer,
             trace::TraceLayer,
-            traffic_writer::{self, RequestWriterInspector},
+            traffic_writer::{self, BidirectionalWriter, RequestWriterInspector, WriterMode},
             upgrade::{UpgradeLayer, Upgraded},
         },
         matcher::MethodMatcher,
         server::HttpServer,
+        ws::{
+            handshake::is_websocket_upgrade_request,
+            relay::{ForwardMessage, RelayMessageWriterLayer, WebSocketRelay},
+        },
     },
     layer::ConsumeErrLayer,
     net::{
@@ -209,10 +215,6 @@ fn new_http_mitm_proxy(
         UserAgentEmulateLayer::new(ctx.state().ua_db.clone())
             .try_auto_detect_user_agent(true)
             .optional(true),
-        RemoveResponseHeaderLayer::hop_by_hop(),
-        RemoveRequestHeaderLayer::hop_by_hop(),
-        CompressAdaptLayer::default(),
-        AddRequiredRequestHeadersLayer::new(),
     )
         .into_layer(service_fn(http_mitm_proxy))
 }
@@ -221,6 +223,12 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
     // This function will receive all requests going through this proxy,
     // be it sent via HTTP or HTTPS, both are equally visible. Hence... MITM
 
+    // WebSocket upgrade requests are relayed prior to removing
+    // the hop-by-hop headers, as these are required for the upgrade
+    if is_websocket_upgrade_request(&req) {
+        return websocket_mitm_proxy(ctx, req).await;
+    }
+
     // NOTE: use a custom connector (layers) in case you wish to add custom features,
     // such as upstream proxies or other configurations
     let mut client = EasyHttpWebClient::default()
@@ -239,6 +247,56 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
             ),
         ));
 
+    let tls_client_config = new_tls_client_config(
+        &ctx,
+        vec![ApplicationProtocol::HTTP_2, ApplicationProtocol::HTTP_11],
+    );
+    client.set_tls_connector_config(TlsConnectorConfig::Boring(Some(tls_client_config)));
+
+    let client = (
+        RemoveResponseHeaderLayer::hop_by_hop(),
+        RemoveRequestHeaderLayer::hop_by_hop(),
+        CompressAdaptLayer::default(),
+        AddRequiredRequestHeadersLayer::new(),
+    )
+        .into_layer(client);
+
+    match client.serve(ctx, req).await {
+        Ok(resp) => Ok(resp.map(Body::new)),
+        Err(err) => {
+            tracing::error!(error = ?err, "error in client request");
+            Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .unwrap())
+        }
+    }
+}
+
+async fn websocket_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infallible> {
+    let mut client = EasyHttpWebClient::default();
+
+    // WebSockets are only supported over HTTP/1.1
+    let tls_client_config = new_tls_client_config(&ctx, vec![ApplicationProtocol::HTTP_11]);
+    client.set_tls_connector_config(TlsConnectorConfig::Boring(Some(tls_client_config)));
+
+    // this writer is for example purposes only,
+    // best not to print messages like this in production...
+    let writer = BidirectionalWriter::stdout_unbounded(
+        ctx.executor(),
+        Some(WriterMode::All),
+        Some(WriterMode::All),
+    );
+
+    // use your own message handler instead of the [`ForwardMessage`] handler
+    // in case you wish to modify, drop or inject messages
+    WebSocketRelay::new(client)
+        .with_handler(RelayMessageWriterLayer::new(writer).into_layer(ForwardMessage::new()))
+        .serve(ctx, req)
+        .await
+}
+
+fn new_tls_client_config(ctx: &Context, alpn: Vec<ApplicationProtocol>) -> ClientConfig {
     let mut base_tls_cfg = ctx
         .get::<SecureTransport>()
         .and_then(|st| st.client_hello())
@@ -246,10 +304,7 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
         .map(Into::into)
         .unwrap_or_else(|| ClientConfig {
             extensions: Some(vec![
-                ClientHelloExtension::ApplicationLayerProtocolNegotiation(vec![
-                    ApplicationProtocol::HTTP_2,
-                    ApplicationProtocol::HTTP_11,
-                ]),
+                ClientHelloExtension::ApplicationLayerProtocolNegotiation(alpn.clone()),
             ]),
             ..Default::default()
         });
@@ -257,7 +312,7 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
 
     // TODO: this tls stack API needs to be easier and more performant; but especially less messy
 
-    let tls_client_config = match extract_client_config_from_ctx(&ctx) {
+    let mut tls_client_config = match extract_client_config_from_ctx(ctx) {
         Some(chain) => {
             let mut cfg = base_tls_cfg;
             for other_cfg in chain.iter() {
@@ -268,18 +323,18 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
         None => base_tls_cfg,
     };
 
-    client.set_tls_connector_config(TlsConnectorConfig::Boring(Some(tls_client_config)));
-
-    match client.serve(ctx, req).await {
-        Ok(resp) => Ok(resp),
-        Err(err) => {
-            tracing::error!(error = ?err, "error in client request");
-            Ok(Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::empty())
-                .unwrap())
+    // only offer the supported application protocols,
+    // even if the (mirrored) client offered others
+    if let Some(extensions) = tls_client_config.extensions.as_mut() {
+        for extension in extensions.iter_mut() {
+            if let ClientHelloExtension::ApplicationLayerProtocolNegotiation(protocols) = extension
+            {
+                protocols.retain(|protocol| alpn.contains(protocol));
+            }
         }
     }
+
+    tls_client_config
 }
 
 // NOTE: for a production service you ideally use
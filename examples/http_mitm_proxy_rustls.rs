@@ -31,6 +31,8 @@
 //! curl -v -x http://127.0.0.1:62019 --proxy-user 'john:secret' http://www.example.com/
 //! curl -k -v -x http://127.0.0.1:62019 --proxy-user 'john:secret' https://www.example.com/
 //! ```
+//!
+//! WebSocket connections are relayed as well, with all relayed messages written to stdout.
 
 use rama::{
     Layer, Service,
@@ -45,14 +47,21 @@ use rama::{
             remove_header::{RemoveRequestHeaderLayer, RemoveResponseHeaderLayer},
             required_header::AddRequiredRequestHeadersLayer,
             trace::TraceLayer,
+            traffic_writer::{BidirectionalWriter, WriterMode},
             upgrade::{UpgradeLayer, Upgraded},
         },
         matcher::MethodMatcher,
         server::HttpServer,
+        ws::{
+            handshake::is_websocket_upgrade_request,
+            relay::{ForwardMessage, RelayMessageWriterLayer, WebSocketRelay},
+        },
     },
     layer::ConsumeErrLayer,
     net::{
-        http::RequestContext, stream::layer::http::BodyLimitLayer, tls::server::SelfSignedData,
+        http::RequestContext,
+        stream::layer::http::BodyLimitLayer,
+        tls::{ApplicationProtocol, server::SelfSignedData},
         user::Basic,
     },
     rt::Executor,
@@ -188,10 +197,6 @@ fn new_http_mitm_proxy() -> impl Service<State, Request, Response = Response, Er
         MapResponseBodyLayer::new(Body::new),
         TraceLayer::new_for_http(),
         ConsumeErrLayer::default(),
-        RemoveResponseHeaderLayer::hop_by_hop(),
-        RemoveRequestHeaderLayer::hop_by_hop(),
-        CompressAdaptLayer::default(),
-        AddRequiredRequestHeadersLayer::new(),
     )
         .into_layer(service_fn(http_mitm_proxy))
 }
@@ -200,6 +205,12 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
     // This function will receive all requests going through this proxy,
     // be it sent via HTTP or HTTPS, both are equally visible. Hence... MITM
 
+    // WebSocket upgrade requests are relayed prior to removing
+    // the hop-by-hop headers, as these are required for the upgrade
+    if is_websocket_upgrade_request(&req) {
+        return websocket_mitm_proxy(ctx, req).await;
+    }
+
     // NOTE: use a custom connector (layers) in case you wish to add custom features,
     // such as upstream proxies or other configurations
     let mut client = EasyHttpWebClient::default();
@@ -213,8 +224,16 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
 
     client.set_tls_connector_config(TlsConnectorConfig::Rustls(Some(data)));
 
+    let client = (
+        RemoveResponseHeaderLayer::hop_by_hop(),
+        RemoveRequestHeaderLayer::hop_by_hop(),
+        CompressAdaptLayer::default(),
+        AddRequiredRequestHeadersLayer::new(),
+    )
+        .into_layer(client);
+
     match client.serve(ctx, req).await {
-        Ok(resp) => Ok(resp),
+        Ok(resp) => Ok(resp.map(Body::new)),
         Err(err) => {
             tracing::error!(error = ?err, "error in client request");
             Ok(Response::builder()
@@ -225,6 +244,35 @@ async fn http_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infalli
     }
 }
 
+async fn websocket_mitm_proxy(ctx: Context, req: Request) -> Result<Response, Infallible> {
+    let mut client = EasyHttpWebClient::default();
+
+    // WebSockets are only supported over HTTP/1.1
+    let data = TlsConnectorDataBuilder::new()
+        .with_no_cert_verifier()
+        .with_alpn_protocols(&[ApplicationProtocol::HTTP_11])
+        .with_env_key_logger()
+        .expect("with env key logger")
+        .build();
+
+    client.set_tls_connector_config(TlsConnectorConfig::Rustls(Some(data)));
+
+    // this writer is for example purposes only,
+    // best not to print messages like this in production...
+    let writer = BidirectionalWriter::stdout_unbounded(
+        ctx.executor(),
+        Some(WriterMode::All),
+        Some(WriterMode::All),
+    );
+
+    // use your own message handler instead of the [`ForwardMessage`] handler
+    // in case you wish to modify, drop or inject messages
+    WebSocketRelay::new(client)
+        .with_handler(RelayMessageWriterLayer::new(writer).into_layer(ForwardMessage::new()))
+        .serve(ctx, req)
+        .await
+}
+
 // NOTE: for a production service you ideally use
 // an issued TLS cert (if possible via ACME). Or at the very least
 // load it in from memory/file, so that your clients can install the certificate for trust.
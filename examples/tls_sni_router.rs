@@ -0,0 +1,92 @@
+//! An example to show how to route TLS connections based on their ClientHello,
+//! without terminating the TLS connection (also known as TLS passthrough).
+//!
+//! The ClientHello is peeked from the incoming stream, after which the
+//! (still encrypted) stream is forwarded to the backend matched by
+//! the server name (SNI) or offered application protocols (ALPN).
+//!
+//! # Run the example
+//!
+//! ```sh
+//! cargo run --example tls_sni_router --features=tcp,tls
+//! ```
+//!
+//! # Expected output
+//!
+//! The server will start and listen on `:62502`, forwarding connections:
+//!
+//! - for `*.example.com` to `127.0.0.1:62503`;
+//! - offering `h2` to `127.0.0.1:62504`;
+//! - all other connections to `127.0.0.1:62505`.
+//!
+//! Run for example the `tls_rustls_termination` example, adapted to listen on these ports,
+//! and use `curl` to connect via the router:
+//!
+//! ```sh
+//! curl -vk --http1.1 --resolve www.example.com:62502:127.0.0.1 https://www.example.com:62502
+//! ```
+
+use rama::{
+    Layer,
+    layer::{TimeoutLayer, TraceErrLayer},
+    matcher::MatcherRouter,
+    net::{
+        address::Domain,
+        tls::{
+            ApplicationProtocol,
+            server::{AlpnMatcher, PeekClientHelloLayer, SniMatcher},
+        },
+    },
+    tcp::{client::service::Forwarder, server::TcpListener},
+};
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(fmt::layer())
+        .with(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::DEBUG.into())
+                .from_env_lossy(),
+        )
+        .init();
+
+    let graceful = rama::graceful::Shutdown::default();
+
+    let router = MatcherRouter((
+        (
+            SniMatcher::sub(Domain::from_static("example.com")),
+            Forwarder::new(([127, 0, 0, 1], 62503)),
+        ),
+        (
+            AlpnMatcher::new(ApplicationProtocol::HTTP_2),
+            Forwarder::new(([127, 0, 0, 1], 62504)),
+        ),
+        Forwarder::new(([127, 0, 0, 1], 62505)),
+    ));
+
+    graceful.spawn_task_fn(async |guard| {
+        TcpListener::bind("0.0.0.0:62502")
+            .await
+            .expect("bind TCP Listener")
+            .serve_graceful(
+                guard,
+                (
+                    TraceErrLayer::new(),
+                    // do not wait forever on clients which never send a ClientHello
+                    TimeoutLayer::new(Duration::from_secs(60)),
+                    PeekClientHelloLayer::new(),
+                )
+                    .into_layer(router),
+            )
+            .await;
+    });
+
+    graceful
+        .shutdown_with_limit(Duration::from_secs(30))
+        .await
+        .expect("graceful shutdown");
+}
@@ -3,26 +3,17 @@
 use clap::Args;
 use rama::{
     Service,
-    cli::{ForwardKind, service::serve::ServeServiceBuilder},
+    cli::{ForwardKind, SniCertArg, service::serve::ServeServiceBuilder},
     error::{BoxError, ErrorContext, OpaqueError},
     http::{
         IntoResponse, Request, Response, matcher::HttpMatcher, service::fs::DirectoryServeMode,
     },
     layer::HijackLayer,
-    net::{
-        address::SocketAddress,
-        tls::{
-            ApplicationProtocol, DataEncoding,
-            server::{SelfSignedData, ServerAuth, ServerAuthData, ServerConfig},
-        },
-    },
+    net::address::SocketAddress,
     rt::Executor,
     tcp::server::TcpListener,
 };
 
-use base64::Engine;
-use base64::engine::general_purpose::STANDARD as ENGINE;
-
 use std::{convert::Infallible, path::PathBuf, time::Duration};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
@@ -70,6 +61,19 @@ pub struct CliCommandServe {
     /// run serve service in secure mode (enable TLS)
     secure: bool,
 
+    #[arg(long = "cert", value_name = "HOST=CERT,KEY")]
+    /// serve the PEM-encoded certificate chain and private key for the given host (SNI)
+    ///
+    /// Can be repeated, with hosts such as '*.example.com' matching any direct subdomain.
+    /// The first certificate is served to clients with no or an unknown server name.
+    ///
+    /// Implies secure mode.
+    certs: Vec<SniCertArg>,
+
+    #[arg(long, requires = "certs")]
+    /// reject clients which sent a server name (SNI) not matching any of the certificates
+    reject_unknown_sni: bool,
+
     #[arg(long, default_value_t = DirectoryServeMode::HtmlFileList)]
     /// define how to serve directories
     ///
@@ -92,48 +96,8 @@ pub async fn run(cfg: CliCommandServe) -> Result<(), BoxError> {
         )
         .init();
 
-    let maybe_tls_server_config = cfg.secure.then(|| {
-        let tls_key_pem_raw = match std::env::var("RAMA_TLS_KEY") {
-            Ok(raw) => raw,
-            Err(_) => {
-                return ServerConfig {
-                    application_layer_protocol_negotiation: Some(vec![
-                        ApplicationProtocol::HTTP_2,
-                        ApplicationProtocol::HTTP_11,
-                    ]),
-                    ..ServerConfig::new(ServerAuth::SelfSigned(SelfSignedData::default()))
-                };
-            }
-        };
-        let tls_key_pem_raw = std::str::from_utf8(
-            &ENGINE
-                .decode(tls_key_pem_raw)
-                .expect("base64 decode RAMA_TLS_KEY")[..],
-        )
-        .expect("base64-decoded RAMA_TLS_KEY valid utf-8")
-        .try_into()
-        .expect("tls_key_pem_raw => NonEmptyStr (RAMA_TLS_KEY)");
-        let tls_crt_pem_raw = std::env::var("RAMA_TLS_CRT").expect("RAMA_TLS_CRT");
-        let tls_crt_pem_raw = std::str::from_utf8(
-            &ENGINE
-                .decode(tls_crt_pem_raw)
-                .expect("base64 decode RAMA_TLS_CRT")[..],
-        )
-        .expect("base64-decoded RAMA_TLS_CRT valid utf-8")
-        .try_into()
-        .expect("tls_crt_pem_raw => NonEmptyStr (RAMA_TLS_CRT)");
-        ServerConfig {
-            application_layer_protocol_negotiation: Some(vec![
-                ApplicationProtocol::HTTP_2,
-                ApplicationProtocol::HTTP_11,
-            ]),
-            ..ServerConfig::new(ServerAuth::Single(ServerAuthData {
-                private_key: DataEncoding::Pem(tls_key_pem_raw),
-                cert_chain: DataEncoding::Pem(tls_crt_pem_raw),
-                ocsp: None,
-            }))
-        }
-    });
+    let maybe_tls_server_config =
+        crate::utils::tls::try_new_server_config(cfg.secure, cfg.certs, cfg.reject_unknown_sni)?;
 
     let maybe_acme_service = std::env::var("RAMA_ACME_DATA")
         .map(|data| {
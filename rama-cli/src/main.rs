@@ -12,6 +12,8 @@ use cmd::{echo, fp, http, ip, proxy, serve, tls};
 
 pub mod error;
 
+pub mod utils;
+
 #[cfg(all(not(feature = "mimalloc"), feature = "jemalloc"))]
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
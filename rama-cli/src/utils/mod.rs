@@ -0,0 +1,3 @@
+//! utilities shared by the rama cli subcommands
+
+pub mod tls;
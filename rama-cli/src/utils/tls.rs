@@ -0,0 +1,73 @@
+//! tls utilities shared by the rama cli server subcommands
+
+use rama::{
+    cli::SniCertArg,
+    error::{ErrorContext, OpaqueError},
+    net::tls::{
+        ApplicationProtocol, DataEncoding,
+        server::{SelfSignedData, ServerAuth, ServerAuthData, ServerConfig},
+    },
+};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as ENGINE;
+
+/// Create the tls [`ServerConfig`] of a server subcommand, if tls is enabled.
+///
+/// The certificates given per server name (SNI) are loaded and served if any.
+/// Otherwise, in secure mode, the certificate chain and private key are read from the
+/// base64-encoded `RAMA_TLS_CRT` and `RAMA_TLS_KEY` environment variables,
+/// falling back to a self-signed certificate in case no key is defined.
+pub fn try_new_server_config(
+    secure: bool,
+    certs: Vec<SniCertArg>,
+    reject_unknown_sni: bool,
+) -> Result<Option<ServerConfig>, OpaqueError> {
+    let server_auth = if !certs.is_empty() {
+        let sni_map = SniCertArg::try_into_sni_map(certs)
+            .context("load sni certs")?
+            .with_reject_unknown_server_name(reject_unknown_sni);
+        ServerAuth::Sni(sni_map)
+    } else if secure {
+        server_auth_from_env()
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(ServerConfig {
+        application_layer_protocol_negotiation: Some(vec![
+            ApplicationProtocol::HTTP_2,
+            ApplicationProtocol::HTTP_11,
+        ]),
+        ..ServerConfig::new(server_auth)
+    }))
+}
+
+fn server_auth_from_env() -> ServerAuth {
+    let tls_key_pem_raw = match std::env::var("RAMA_TLS_KEY") {
+        Ok(raw) => raw,
+        Err(_) => return ServerAuth::SelfSigned(SelfSignedData::default()),
+    };
+    let tls_key_pem_raw = std::str::from_utf8(
+        &ENGINE
+            .decode(tls_key_pem_raw)
+            .expect("base64 decode RAMA_TLS_KEY")[..],
+    )
+    .expect("base64-decoded RAMA_TLS_KEY valid utf-8")
+    .try_into()
+    .expect("tls_key_pem_raw => NonEmptyStr (RAMA_TLS_KEY)");
+    let tls_crt_pem_raw = std::env::var("RAMA_TLS_CRT").expect("RAMA_TLS_CRT");
+    let tls_crt_pem_raw = std::str::from_utf8(
+        &ENGINE
+            .decode(tls_crt_pem_raw)
+            .expect("base64 decode RAMA_TLS_CRT")[..],
+    )
+    .expect("base64-decoded RAMA_TLS_CRT valid utf-8")
+    .try_into()
+    .expect("tls_crt_pem_raw => NonEmptyStr (RAMA_TLS_CRT)");
+    ServerAuth::Single(ServerAuthData {
+        private_key: DataEncoding::Pem(tls_key_pem_raw),
+        cert_chain: DataEncoding::Pem(tls_crt_pem_raw),
+        ocsp: None,
+    })
+}
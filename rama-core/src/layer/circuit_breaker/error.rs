@@ -0,0 +1,37 @@
+//! Error type for the CircuitBreaker middleware.
+
+use std::{error, fmt, time::Duration};
+
+/// The request was rejected because the circuit breaker is open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitOpen {
+    retry_after: Duration,
+}
+
+impl CircuitOpen {
+    /// Construct a new [`CircuitOpen`] error.
+    pub(crate) const fn new(retry_after: Duration) -> Self {
+        Self { retry_after }
+    }
+
+    /// The duration after which the circuit breaker
+    /// will allow trial requests again.
+    ///
+    /// This is zero in case the circuit breaker is half-open,
+    /// but all trial requests are in flight.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "request rejected by open circuit breaker (retry after {:?})",
+            self.retry_after
+        )
+    }
+}
+
+impl error::Error for CircuitOpen {}
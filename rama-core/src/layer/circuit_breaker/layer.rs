@@ -0,0 +1,209 @@
+use super::{Breaker, CircuitBreaker, CircuitBreakerConfig, ErrorsAsFailures, new_breaker_cache};
+use crate::Layer;
+use crate::layer::limit::policy::KeyExtractor;
+use moka::sync::Cache;
+use std::{sync::Arc, time::Duration};
+
+/// A [`Layer`] that produces [`CircuitBreaker`] services.
+///
+/// All services created by the same layer share their circuit breakers.
+///
+/// See the [module docs](super) for more information.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerLayer<K: KeyExtractor = (), P = ErrorsAsFailures, H = ()> {
+    extractor: K,
+    predicate: P,
+    hook: H,
+    config: CircuitBreakerConfig,
+    max_keys: u64,
+    idle_timeout: Duration,
+    breakers: Cache<K::Key, Arc<Breaker>>,
+}
+
+impl CircuitBreakerLayer {
+    /// Default maximum amount of keys for which a circuit breaker is kept.
+    pub const DEFAULT_MAX_KEYS: u64 = 10_000;
+
+    /// Default duration after which the circuit breaker of an idle key is evicted.
+    pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    /// Create a new [`CircuitBreakerLayer`], using a single circuit breaker
+    /// for all requests, which considers all errors as failures.
+    ///
+    /// The circuit breaker opens after 5 consecutive failures, for 30 seconds,
+    /// after which a single successful trial request closes it again.
+    pub fn new() -> Self {
+        Self {
+            extractor: (),
+            predicate: ErrorsAsFailures::new(),
+            hook: (),
+            config: CircuitBreakerConfig::default(),
+            max_keys: Self::DEFAULT_MAX_KEYS,
+            idle_timeout: Self::DEFAULT_IDLE_TIMEOUT,
+            breakers: new_breaker_cache(Self::DEFAULT_MAX_KEYS, Self::DEFAULT_IDLE_TIMEOUT),
+        }
+    }
+}
+
+impl Default for CircuitBreakerLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: KeyExtractor, P, H> CircuitBreakerLayer<K, P, H> {
+    /// Set the amount of consecutive failures after which the circuit breaker opens.
+    ///
+    /// A threshold of `0` is treated as a threshold of `1`.
+    pub fn set_failure_threshold(&mut self, threshold: u32) -> &mut Self {
+        self.config.failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Same as [`Self::set_failure_threshold`] but consuming self.
+    pub fn with_failure_threshold(mut self, threshold: u32) -> Self {
+        self.set_failure_threshold(threshold);
+        self
+    }
+
+    /// Set the amount of successful trial requests required,
+    /// while half-open, for the circuit breaker to close.
+    ///
+    /// This is also the maximum amount of trial requests in flight.
+    /// A threshold of `0` is treated as a threshold of `1`.
+    pub fn set_success_threshold(&mut self, threshold: u32) -> &mut Self {
+        self.config.success_threshold = threshold.max(1);
+        self
+    }
+
+    /// Same as [`Self::set_success_threshold`] but consuming self.
+    pub fn with_success_threshold(mut self, threshold: u32) -> Self {
+        self.set_success_threshold(threshold);
+        self
+    }
+
+    /// Set the duration for which the circuit breaker stays open,
+    /// before it allows trial requests.
+    pub fn set_open_duration(&mut self, duration: Duration) -> &mut Self {
+        self.config.open_duration = duration;
+        self
+    }
+
+    /// Same as [`Self::set_open_duration`] but consuming self.
+    pub fn with_open_duration(mut self, duration: Duration) -> Self {
+        self.set_open_duration(duration);
+        self
+    }
+
+    /// Set the maximum amount of keys for which a circuit breaker is kept,
+    /// evicting the least recently used ones when exceeded.
+    ///
+    /// Circuit breakers which were already created are dropped.
+    pub fn set_max_keys(&mut self, max_keys: u64) -> &mut Self {
+        self.max_keys = max_keys;
+        self.breakers = new_breaker_cache(self.max_keys, self.idle_timeout);
+        self
+    }
+
+    /// Same as [`Self::set_max_keys`] but consuming self.
+    pub fn with_max_keys(mut self, max_keys: u64) -> Self {
+        self.set_max_keys(max_keys);
+        self
+    }
+
+    /// Set the duration after which the circuit breaker of a key
+    /// which had no requests is evicted.
+    ///
+    /// Circuit breakers which were already created are dropped.
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) -> &mut Self {
+        self.idle_timeout = idle_timeout;
+        self.breakers = new_breaker_cache(self.max_keys, self.idle_timeout);
+        self
+    }
+
+    /// Same as [`Self::set_idle_timeout`] but consuming self.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.set_idle_timeout(idle_timeout);
+        self
+    }
+
+    /// Use a separate circuit breaker per key extracted by the given key extractor,
+    /// e.g. per upstream authority.
+    ///
+    /// Requests for which no key is extracted bypass the circuit breakers.
+    pub fn with_key_extractor<K2: KeyExtractor>(
+        self,
+        extractor: K2,
+    ) -> CircuitBreakerLayer<K2, P, H> {
+        CircuitBreakerLayer {
+            extractor,
+            predicate: self.predicate,
+            hook: self.hook,
+            config: self.config,
+            max_keys: self.max_keys,
+            idle_timeout: self.idle_timeout,
+            breakers: new_breaker_cache(self.max_keys, self.idle_timeout),
+        }
+    }
+
+    /// Use the given [`FailurePredicate`] to decide whether a request failed.
+    ///
+    /// [`FailurePredicate`]: super::FailurePredicate
+    pub fn with_failure_predicate<P2>(self, predicate: P2) -> CircuitBreakerLayer<K, P2, H> {
+        CircuitBreakerLayer {
+            extractor: self.extractor,
+            predicate,
+            hook: self.hook,
+            config: self.config,
+            max_keys: self.max_keys,
+            idle_timeout: self.idle_timeout,
+            breakers: self.breakers,
+        }
+    }
+
+    /// Call the given [`OnStateChange`] hook when a circuit breaker changed state.
+    ///
+    /// [`OnStateChange`]: super::OnStateChange
+    pub fn with_state_change_hook<H2>(self, hook: H2) -> CircuitBreakerLayer<K, P, H2> {
+        CircuitBreakerLayer {
+            extractor: self.extractor,
+            predicate: self.predicate,
+            hook,
+            config: self.config,
+            max_keys: self.max_keys,
+            idle_timeout: self.idle_timeout,
+            breakers: self.breakers,
+        }
+    }
+}
+
+impl<S, K, P, H> Layer<S> for CircuitBreakerLayer<K, P, H>
+where
+    K: KeyExtractor + Clone,
+    P: Clone,
+    H: Clone,
+{
+    type Service = CircuitBreaker<S, K, P, H>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreaker {
+            inner,
+            extractor: self.extractor.clone(),
+            predicate: self.predicate.clone(),
+            hook: self.hook.clone(),
+            config: self.config,
+            breakers: self.breakers.clone(),
+        }
+    }
+
+    fn into_layer(self, inner: S) -> Self::Service {
+        CircuitBreaker {
+            inner,
+            extractor: self.extractor,
+            predicate: self.predicate,
+            hook: self.hook,
+            config: self.config,
+            breakers: self.breakers,
+        }
+    }
+}
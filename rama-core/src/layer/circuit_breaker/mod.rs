@@ -0,0 +1,513 @@
+//! Middleware that stops sending requests to a failing service.
+//!
+//! A circuit breaker starts [`Closed`], allowing all requests.
+//! Once the configured amount of consecutive requests failed, it [`Open`]s,
+//! rejecting all requests with a [`CircuitOpen`] error, giving the inner service
+//! (e.g. an upstream server) time to recover. After the open duration elapsed
+//! it becomes [`HalfOpen`], allowing a limited amount of trial requests.
+//! It closes again once enough trial requests succeeded, and re-opens
+//! as soon as a trial request failed.
+//!
+//! Whether or not a request failed is decided by a [`FailurePredicate`],
+//! which by default considers all errors as failures. In rama-http the
+//! response classifiers can be used as predicate, e.g. to also
+//! consider `5xx` responses as failures.
+//!
+//! By default a single circuit breaker is used for all requests. Using a key
+//! extractor (see [`ExtractKey`]) a separate circuit breaker can be used per key,
+//! e.g. per upstream authority.
+//!
+//! See [`CircuitBreaker`] and [`CircuitBreakerLayer`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::circuit_breaker::{CircuitBreakerLayer, CircuitOpen};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Layer, Service};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = CircuitBreakerLayer::new()
+//!     .with_failure_threshold(2)
+//!     .into_layer(service_fn(async |_, _: ()| Err::<(), _>("upstream down")));
+//!
+//! assert!(service.serve(Context::default(), ()).await.is_err());
+//! assert!(service.serve(Context::default(), ()).await.is_err());
+//! let err = service.serve(Context::default(), ()).await.unwrap_err();
+//! assert!(err.downcast_ref::<CircuitOpen>().is_some());
+//! # }
+//! ```
+//!
+//! [`Closed`]: CircuitState::Closed
+//! [`Open`]: CircuitState::Open
+//! [`HalfOpen`]: CircuitState::HalfOpen
+//! [`ExtractKey`]: crate::layer::limit::policy::ExtractKey
+
+use crate::error::BoxError;
+use crate::layer::limit::policy::{ExtractKey, KeyExtractor};
+use crate::{Context, Service};
+use moka::sync::Cache;
+use parking_lot::Mutex;
+use rama_utils::macros::define_inner_service_accessors;
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+mod error;
+#[doc(inline)]
+pub use error::CircuitOpen;
+
+mod layer;
+#[doc(inline)]
+pub use layer::CircuitBreakerLayer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The state of a circuit breaker.
+pub enum CircuitState {
+    /// All requests are allowed.
+    Closed,
+    /// All requests are rejected.
+    Open,
+    /// A limited amount of trial requests is allowed.
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => write!(f, "closed"),
+            Self::Open => write!(f, "open"),
+            Self::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// Decides whether the result of a request is a failure,
+/// counting towards opening the circuit breaker.
+pub trait FailurePredicate<Response, Error>: Send + Sync + 'static {
+    /// Returns `true` if the given result is a failure.
+    fn is_failure(&self, result: &Result<Response, Error>) -> bool;
+}
+
+impl<F, Response, Error> FailurePredicate<Response, Error> for F
+where
+    F: Fn(&Result<Response, Error>) -> bool + Send + Sync + 'static,
+{
+    fn is_failure(&self, result: &Result<Response, Error>) -> bool {
+        (self)(result)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// A [`FailurePredicate`] which considers all errors, and only errors, as failures.
+pub struct ErrorsAsFailures;
+
+impl ErrorsAsFailures {
+    /// Create a new [`ErrorsAsFailures`].
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl<Response, Error> FailurePredicate<Response, Error> for ErrorsAsFailures {
+    fn is_failure(&self, result: &Result<Response, Error>) -> bool {
+        result.is_err()
+    }
+}
+
+/// A hook called when the state of a circuit breaker changed,
+/// e.g. to record metrics or log the change.
+pub trait OnStateChange<Key>: Send + Sync + 'static {
+    /// Called when the circuit breaker of the given key changed state.
+    fn on_state_change(&self, key: &Key, from: CircuitState, to: CircuitState);
+}
+
+impl<Key> OnStateChange<Key> for () {
+    fn on_state_change(&self, _key: &Key, _from: CircuitState, _to: CircuitState) {}
+}
+
+impl<F, Key> OnStateChange<Key> for F
+where
+    F: Fn(&Key, CircuitState, CircuitState) + Send + Sync + 'static,
+{
+    fn on_state_change(&self, key: &Key, from: CircuitState, to: CircuitState) {
+        (self)(key, from, to)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CircuitBreakerConfig {
+    failure_threshold: u32,
+    success_threshold: u32,
+    open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            success_threshold: 1,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+type StateChange = (CircuitState, CircuitState);
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { in_flight: u32, successes: u32 },
+}
+
+impl BreakerState {
+    fn state(&self) -> CircuitState {
+        match self {
+            Self::Closed { .. } => CircuitState::Closed,
+            Self::Open { .. } => CircuitState::Open,
+            Self::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: Mutex<BreakerState>,
+}
+
+impl Breaker {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(BreakerState::Closed { failures: 0 }),
+        }
+    }
+
+    /// Try to acquire permission to serve a request.
+    fn try_acquire(
+        self: &Arc<Self>,
+        config: &CircuitBreakerConfig,
+        now: Instant,
+    ) -> Result<(Permit, Option<StateChange>), CircuitOpen> {
+        let mut state = self.state.lock();
+        let (trial, change) = match &mut *state {
+            BreakerState::Closed { .. } => (false, None),
+            BreakerState::Open { until } if *until > now => {
+                return Err(CircuitOpen::new(*until - now));
+            }
+            BreakerState::Open { .. } => {
+                *state = BreakerState::HalfOpen {
+                    in_flight: 1,
+                    successes: 0,
+                };
+                (true, Some((CircuitState::Open, CircuitState::HalfOpen)))
+            }
+            BreakerState::HalfOpen {
+                in_flight,
+                successes,
+            } => {
+                if *in_flight + *successes >= config.success_threshold {
+                    return Err(CircuitOpen::new(Duration::ZERO));
+                }
+                *in_flight += 1;
+                (true, None)
+            }
+        };
+        Ok((
+            Permit {
+                breaker: Some(self.clone()),
+                trial,
+            },
+            change,
+        ))
+    }
+}
+
+/// Permission to serve a request,
+/// which releases its trial slot if dropped without recording a result.
+struct Permit {
+    breaker: Option<Arc<Breaker>>,
+    trial: bool,
+}
+
+impl Permit {
+    fn record(
+        mut self,
+        failure: bool,
+        config: &CircuitBreakerConfig,
+        now: Instant,
+    ) -> Option<StateChange> {
+        let breaker = self.breaker.take()?;
+        let mut state = breaker.state.lock();
+        let from = state.state();
+
+        match &mut *state {
+            BreakerState::Closed { failures } => {
+                if !failure {
+                    *failures = 0;
+                } else {
+                    *failures += 1;
+                    if *failures >= config.failure_threshold {
+                        *state = BreakerState::Open {
+                            until: now + config.open_duration,
+                        };
+                    }
+                }
+            }
+            BreakerState::HalfOpen {
+                in_flight,
+                successes,
+            } if self.trial => {
+                *in_flight = in_flight.saturating_sub(1);
+                if failure {
+                    *state = BreakerState::Open {
+                        until: now + config.open_duration,
+                    };
+                } else {
+                    *successes += 1;
+                    if *successes >= config.success_threshold {
+                        *state = BreakerState::Closed { failures: 0 };
+                    }
+                }
+            }
+            // results of requests allowed prior to the last state change
+            BreakerState::HalfOpen { .. } | BreakerState::Open { .. } => (),
+        }
+
+        let to = state.state();
+        (from != to).then_some((from, to))
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if !self.trial {
+            return;
+        }
+        if let Some(breaker) = self.breaker.take() {
+            if let BreakerState::HalfOpen { in_flight, .. } = &mut *breaker.state.lock() {
+                *in_flight = in_flight.saturating_sub(1);
+            }
+        }
+    }
+}
+
+fn new_breaker_cache<Key>(max_keys: u64, idle_timeout: Duration) -> Cache<Key, Arc<Breaker>>
+where
+    Key: Clone + Eq + std::hash::Hash + Send + Sync + 'static,
+{
+    Cache::builder()
+        .max_capacity(max_keys)
+        .time_to_idle(idle_timeout)
+        .build()
+}
+
+/// Middleware which rejects requests while the inner service is failing.
+///
+/// See the [module docs](self) for more information.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker<S, K: KeyExtractor, P, H> {
+    inner: S,
+    extractor: K,
+    predicate: P,
+    hook: H,
+    config: CircuitBreakerConfig,
+    breakers: Cache<K::Key, Arc<Breaker>>,
+}
+
+impl<S, K: KeyExtractor, P, H> CircuitBreaker<S, K, P, H> {
+    define_inner_service_accessors!();
+
+    /// The current state of the circuit breaker of the given key.
+    ///
+    /// Keys for which no circuit breaker exists are [`CircuitState::Closed`].
+    pub fn state(&self, key: &K::Key) -> CircuitState {
+        self.breakers
+            .get(key)
+            .map(|breaker| breaker.state.lock().state())
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    fn notify(&self, key: &K::Key, change: Option<StateChange>)
+    where
+        H: OnStateChange<K::Key>,
+    {
+        if let Some((from, to)) = change {
+            tracing::debug!(%from, %to, "circuit breaker state changed");
+            self.hook.on_state_change(key, from, to);
+        }
+    }
+}
+
+impl<S, K, P, H, State, Request> Service<State, Request> for CircuitBreaker<S, K, P, H>
+where
+    S: Service<State, Request, Error: Into<BoxError>>,
+    K: ExtractKey<State, Request>,
+    P: FailurePredicate<S::Response, S::Error>,
+    H: OnStateChange<K::Key>,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let Some(key) = self.extractor.extract_key(&ctx, &req) else {
+            return self.inner.serve(ctx, req).await.map_err(Into::into);
+        };
+
+        let breaker = self
+            .breakers
+            .get_with_by_ref(&key, || Arc::new(Breaker::new()));
+        let (permit, change) = breaker.try_acquire(&self.config, Instant::now())?;
+        self.notify(&key, change);
+
+        let result = self.inner.serve(ctx, req).await;
+
+        let failure = self.predicate.is_failure(&result);
+        let change = permit.record(failure, &self.config, Instant::now());
+        self.notify(&key, change);
+
+        result.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Layer;
+    use crate::layer::limit::policy::KeyFn;
+    use crate::service::service_fn;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            success_threshold: 2,
+            open_duration: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn test_breaker_state_machine() {
+        let config = config();
+        let breaker = Arc::new(Breaker::new());
+        let now = Instant::now();
+
+        let (permit, change) = breaker.try_acquire(&config, now).unwrap();
+        assert!(change.is_none());
+        assert!(permit.record(true, &config, now).is_none());
+
+        // a success resets the consecutive failures
+        let (permit, _) = breaker.try_acquire(&config, now).unwrap();
+        assert!(permit.record(false, &config, now).is_none());
+
+        let (permit, _) = breaker.try_acquire(&config, now).unwrap();
+        assert!(permit.record(true, &config, now).is_none());
+        let (permit, _) = breaker.try_acquire(&config, now).unwrap();
+        assert_eq!(
+            permit.record(true, &config, now),
+            Some((CircuitState::Closed, CircuitState::Open))
+        );
+
+        let err = breaker
+            .try_acquire(&config, now + Duration::from_secs(4))
+            .err()
+            .unwrap();
+        assert_eq!(err.retry_after(), Duration::from_secs(6));
+
+        // half-open allows as many trial requests as successes are required
+        let now = now + Duration::from_secs(10);
+        let (trial_a, change) = breaker.try_acquire(&config, now).unwrap();
+        assert_eq!(change, Some((CircuitState::Open, CircuitState::HalfOpen)));
+        let (trial_b, change) = breaker.try_acquire(&config, now).unwrap();
+        assert!(change.is_none());
+        assert!(breaker.try_acquire(&config, now).is_err());
+
+        // a dropped trial request releases its slot
+        drop(trial_b);
+        let (trial_b, _) = breaker.try_acquire(&config, now).unwrap();
+
+        assert!(trial_a.record(false, &config, now).is_none());
+        assert_eq!(
+            trial_b.record(false, &config, now),
+            Some((CircuitState::HalfOpen, CircuitState::Closed))
+        );
+    }
+
+    #[test]
+    fn test_breaker_half_open_failure() {
+        let config = config();
+        let breaker = Arc::new(Breaker::new());
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            let (permit, _) = breaker.try_acquire(&config, now).unwrap();
+            permit.record(true, &config, now);
+        }
+
+        let now = now + Duration::from_secs(10);
+        let (trial, _) = breaker.try_acquire(&config, now).unwrap();
+        assert_eq!(
+            trial.record(true, &config, now),
+            Some((CircuitState::HalfOpen, CircuitState::Open))
+        );
+        assert!(breaker.try_acquire(&config, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_per_key() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let hook_changes = changes.clone();
+
+        let service = CircuitBreakerLayer::new()
+            .with_failure_threshold(1)
+            .with_key_extractor(KeyFn::new(|_: &Context<()>, req: &&'static str| Some(*req)))
+            .with_state_change_hook(
+                move |key: &&'static str, from: CircuitState, to: CircuitState| {
+                    hook_changes.lock().push((*key, from, to));
+                },
+            )
+            .into_layer(service_fn(async |_, req: &'static str| {
+                if req == "down" {
+                    Err("upstream down")
+                } else {
+                    Ok(())
+                }
+            }));
+
+        let err = service.serve(Context::default(), "down").await.unwrap_err();
+        assert!(err.downcast_ref::<CircuitOpen>().is_none());
+        let err = service.serve(Context::default(), "down").await.unwrap_err();
+        assert!(err.downcast_ref::<CircuitOpen>().is_some());
+
+        assert!(service.serve(Context::default(), "up").await.is_ok());
+
+        assert_eq!(service.state(&"down"), CircuitState::Open);
+        assert_eq!(service.state(&"up"), CircuitState::Closed);
+        assert_eq!(
+            changes.lock().as_slice(),
+            &[("down", CircuitState::Closed, CircuitState::Open)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_failure_predicate() {
+        let service = CircuitBreakerLayer::new()
+            .with_failure_threshold(1)
+            .with_failure_predicate(|result: &Result<u16, BoxError>| {
+                result.as_ref().map(|status| *status >= 500).unwrap_or(true)
+            })
+            .into_layer(service_fn(async |_, status: u16| Ok::<_, BoxError>(status)));
+
+        assert_eq!(service.serve(Context::default(), 404).await.unwrap(), 404);
+        assert_eq!(service.serve(Context::default(), 503).await.unwrap(), 503);
+        let err = service.serve(Context::default(), 200).await.unwrap_err();
+        assert!(err.downcast_ref::<CircuitOpen>().is_some());
+    }
+}
@@ -0,0 +1,551 @@
+//! A [`Policy`] that applies a separate limit per key,
+//! such as per client, per user or per route.
+//!
+//! See [`KeyedPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::limit::{Limit, policy::{ConcurrentPolicy, KeyFn, KeyedPolicy}};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! # use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = service_fn(async |_, _: &'static str| {
+//!     Ok::<_, Infallible>(())
+//! });
+//! let service = Limit::new(
+//!     service,
+//!     KeyedPolicy::new(
+//!         KeyFn::new(|_: &Context<()>, req: &&'static str| Some(*req)),
+//!         || ConcurrentPolicy::max(1),
+//!     ),
+//! );
+//!
+//! assert!(service.serve(Context::default(), "alice").await.is_ok());
+//! assert!(service.serve(Context::default(), "bob").await.is_ok());
+//! # }
+//! ```
+
+use super::{Policy, PolicyOutput, PolicyResult};
+use crate::Context;
+use moka::notification::RemovalCause;
+use moka::sync::Cache;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+/// The key type of a key extractor, used by a [`KeyedPolicy`]
+/// to decide which limit applies to a request.
+///
+/// The key is extracted using the [`ExtractKey`] trait,
+/// which is to be implemented for the states and requests
+/// supported by the extractor.
+///
+/// The unit type `()` is an extractor which extracts the same key
+/// for all requests, such that they all share a single limit.
+pub trait KeyExtractor: Send + Sync + 'static {
+    /// The key which identifies the limit to be applied.
+    type Key: Clone + Eq + Hash + Send + Sync + 'static;
+}
+
+/// Extract the key from a [`Context`] and request,
+/// for a [`KeyedPolicy`] to decide which limit applies to the request.
+pub trait ExtractKey<State, Request>: KeyExtractor {
+    /// Extract the key from the given [`Context`] and request.
+    ///
+    /// Returning `None` means that no limit applies to the request.
+    fn extract_key(&self, ctx: &Context<State>, req: &Request) -> Option<Self::Key>;
+}
+
+/// Extracts the same key for all requests,
+/// such that a single instance is shared by all of them.
+impl KeyExtractor for () {
+    type Key = ();
+}
+
+impl<State, Request> ExtractKey<State, Request> for () {
+    fn extract_key(&self, _ctx: &Context<State>, _req: &Request) -> Option<()> {
+        Some(())
+    }
+}
+
+impl<K: KeyExtractor> KeyExtractor for Arc<K> {
+    type Key = K::Key;
+}
+
+impl<K, State, Request> ExtractKey<State, Request> for Arc<K>
+where
+    K: ExtractKey<State, Request>,
+{
+    fn extract_key(&self, ctx: &Context<State>, req: &Request) -> Option<Self::Key> {
+        (**self).extract_key(ctx, req)
+    }
+}
+
+/// A key extractor created from a function.
+///
+/// See [`KeyFn::new`].
+pub struct KeyFn<F, K> {
+    f: F,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<F, K> KeyFn<F, K> {
+    /// Create a new [`KeyFn`] from the given function,
+    /// which extracts the key from a [`Context`] and request.
+    pub const fn new<State, Request>(f: F) -> Self
+    where
+        F: Fn(&Context<State>, &Request) -> Option<K>,
+    {
+        Self {
+            f,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<F, K> fmt::Debug for KeyFn<F, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyFn")
+            .field("f", &std::any::type_name::<F>())
+            .field("key", &std::any::type_name::<K>())
+            .finish()
+    }
+}
+
+impl<F: Clone, K> Clone for KeyFn<F, K> {
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<F, K> KeyExtractor for KeyFn<F, K>
+where
+    F: Send + Sync + 'static,
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    type Key = K;
+}
+
+impl<F, K, State, Request> ExtractKey<State, Request> for KeyFn<F, K>
+where
+    F: Fn(&Context<State>, &Request) -> Option<K> + Send + Sync + 'static,
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    fn extract_key(&self, ctx: &Context<State>, req: &Request) -> Option<K> {
+        (self.f)(ctx, req)
+    }
+}
+
+/// A [`Policy`] that applies a separate instance of a [`Policy`] per key.
+///
+/// The key is extracted from the [`Context`] and request using an [`ExtractKey`]
+/// implementation, e.g. to limit requests per peer ip, per authenticated user
+/// or per target authority. Requests for which no key is extracted are not limited.
+///
+/// The policy of a key is created using the given function when it is first seen,
+/// and the policies are kept in a bounded cache, such that the policies
+/// of idle keys are evicted. Policies evicted while guards of their requests
+/// are still alive are kept aside until the last of these guards is dropped,
+/// and are reused for new requests of their key in the meantime.
+/// This way stateful policies such as the [`ConcurrentPolicy`] keep counting
+/// the requests in flight, even when their key is pushed out of the cache
+/// by (a flood of) requests for other keys.
+///
+/// Cloning a [`KeyedPolicy`] shares the policies of all keys.
+///
+/// [`ConcurrentPolicy`]: super::ConcurrentPolicy
+pub struct KeyedPolicy<K: KeyExtractor, P> {
+    extractor: K,
+    make_policy: Arc<dyn Fn() -> P + Send + Sync + 'static>,
+    policies: Cache<K::Key, Arc<P>>,
+    evicted: Arc<Mutex<EvictedPolicies<K::Key, P>>>,
+    max_keys: u64,
+    idle_timeout: Duration,
+}
+
+/// Policies evicted from the cache while guards of their requests were still alive.
+struct EvictedPolicies<Key, P> {
+    policies: HashMap<Key, Weak<P>>,
+    /// amount of policies at which the dropped policies are removed
+    sweep_at: usize,
+}
+
+impl<Key: Eq + Hash, P> EvictedPolicies<Key, P> {
+    const MIN_SWEEP_AT: usize = 64;
+
+    fn new() -> Self {
+        Self {
+            policies: HashMap::new(),
+            sweep_at: Self::MIN_SWEEP_AT,
+        }
+    }
+
+    fn insert(&mut self, key: Key, policy: Weak<P>) {
+        self.policies.insert(key, policy);
+        if self.policies.len() >= self.sweep_at {
+            self.policies.retain(|_, policy| policy.strong_count() > 0);
+            self.sweep_at = (self.policies.len() * 2).max(Self::MIN_SWEEP_AT);
+        }
+    }
+
+    fn get(&self, key: &Key) -> Option<Arc<P>> {
+        self.policies.get(key).and_then(Weak::upgrade)
+    }
+
+    fn remove(&mut self, key: &Key) -> Option<Arc<P>> {
+        self.policies
+            .remove(key)
+            .and_then(|policy| policy.upgrade())
+    }
+}
+
+/// The guard of a [`KeyedPolicy`], wrapping the guard of the policy of its key,
+/// which keeps that policy alive for as long as the guard is not dropped.
+pub struct KeyedPolicyGuard<G, P> {
+    guard: G,
+    _policy: Arc<P>,
+}
+
+impl<G, P> KeyedPolicyGuard<G, P> {
+    /// The guard of the policy of the key.
+    pub fn guard(&self) -> &G {
+        &self.guard
+    }
+}
+
+impl<G: fmt::Debug, P> fmt::Debug for KeyedPolicyGuard<G, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedPolicyGuard")
+            .field("guard", &self.guard)
+            .finish()
+    }
+}
+
+impl<K, P> fmt::Debug for KeyedPolicy<K, P>
+where
+    K: KeyExtractor + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedPolicy")
+            .field("extractor", &self.extractor)
+            .field("keys", &self.policies.entry_count())
+            .field("evicted_keys", &self.evicted.lock().policies.len())
+            .field("max_keys", &self.max_keys)
+            .field("idle_timeout", &self.idle_timeout)
+            .finish()
+    }
+}
+
+impl<K, P> Clone for KeyedPolicy<K, P>
+where
+    K: KeyExtractor + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            extractor: self.extractor.clone(),
+            make_policy: self.make_policy.clone(),
+            policies: self.policies.clone(),
+            evicted: self.evicted.clone(),
+            max_keys: self.max_keys,
+            idle_timeout: self.idle_timeout,
+        }
+    }
+}
+
+impl<K, P> KeyedPolicy<K, P>
+where
+    K: KeyExtractor,
+    P: Clone + Send + Sync + 'static,
+{
+    /// Default maximum amount of keys for which a policy is kept.
+    pub const DEFAULT_MAX_KEYS: u64 = 10_000;
+
+    /// Default duration after which the policy of an idle key is evicted.
+    pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    /// Create a new [`KeyedPolicy`] using the given key extractor,
+    /// creating the policy of a new key using the given function.
+    ///
+    /// The policies returned by the function should not share their state,
+    /// as is for example the case for clones of a [`ConcurrentPolicy`].
+    ///
+    /// [`ConcurrentPolicy`]: super::ConcurrentPolicy
+    pub fn new<F>(extractor: K, make_policy: F) -> Self
+    where
+        F: Fn() -> P + Send + Sync + 'static,
+    {
+        let evicted = Arc::new(Mutex::new(EvictedPolicies::new()));
+        Self {
+            extractor,
+            make_policy: Arc::new(make_policy),
+            policies: new_policy_cache(
+                Self::DEFAULT_MAX_KEYS,
+                Self::DEFAULT_IDLE_TIMEOUT,
+                evicted.clone(),
+            ),
+            evicted,
+            max_keys: Self::DEFAULT_MAX_KEYS,
+            idle_timeout: Self::DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+
+    /// Set the maximum amount of keys for which a policy is kept,
+    /// evicting the least recently used ones when exceeded.
+    ///
+    /// Policies which were already created are dropped.
+    pub fn set_max_keys(&mut self, max_keys: u64) -> &mut Self {
+        self.max_keys = max_keys;
+        self.reset_policies();
+        self
+    }
+
+    /// Same as [`Self::set_max_keys`] but consuming self.
+    pub fn with_max_keys(mut self, max_keys: u64) -> Self {
+        self.set_max_keys(max_keys);
+        self
+    }
+
+    /// Set the duration after which the policy of a key
+    /// which had no requests is evicted.
+    ///
+    /// Policies which were already created are dropped.
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) -> &mut Self {
+        self.idle_timeout = idle_timeout;
+        self.reset_policies();
+        self
+    }
+
+    /// Same as [`Self::set_idle_timeout`] but consuming self.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.set_idle_timeout(idle_timeout);
+        self
+    }
+
+    /// The key extractor used by this [`KeyedPolicy`].
+    pub fn extractor(&self) -> &K {
+        &self.extractor
+    }
+
+    /// The policy currently kept for the given key, if any.
+    pub fn policy(&self, key: &K::Key) -> Option<P> {
+        self.policies
+            .get(key)
+            .or_else(|| self.evicted.lock().get(key))
+            .map(|policy| P::clone(&policy))
+    }
+
+    fn reset_policies(&mut self) {
+        self.evicted = Arc::new(Mutex::new(EvictedPolicies::new()));
+        self.policies = new_policy_cache(self.max_keys, self.idle_timeout, self.evicted.clone());
+    }
+
+    /// Get the policy of the given key, reusing the policy evicted
+    /// while still in use, or creating a new one if there is none.
+    fn get_or_create_policy(&self, key: K::Key) -> Arc<P> {
+        self.policies.get_with_by_ref(&key, || {
+            self.evicted
+                .lock()
+                .remove(&key)
+                .unwrap_or_else(|| Arc::new((self.make_policy)()))
+        })
+    }
+}
+
+fn new_policy_cache<Key, P>(
+    max_keys: u64,
+    idle_timeout: Duration,
+    evicted: Arc<Mutex<EvictedPolicies<Key, P>>>,
+) -> Cache<Key, Arc<P>>
+where
+    Key: Clone + Eq + Hash + Send + Sync + 'static,
+    P: Send + Sync + 'static,
+{
+    Cache::builder()
+        .max_capacity(max_keys)
+        .time_to_idle(idle_timeout)
+        .eviction_listener(move |key: Arc<Key>, policy: Arc<P>, cause| {
+            // the policy is still used by guards (or requests being checked)
+            // in case the cache does not hold the only reference to it
+            if matches!(cause, RemovalCause::Expired | RemovalCause::Size)
+                && Arc::strong_count(&policy) > 1
+            {
+                evicted
+                    .lock()
+                    .insert(Key::clone(&key), Arc::downgrade(&policy));
+            }
+        })
+        .build()
+}
+
+impl<K, P, State, Request> Policy<State, Request> for KeyedPolicy<K, P>
+where
+    K: ExtractKey<State, Request>,
+    P: Policy<State, Request> + Clone,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Guard = Option<KeyedPolicyGuard<P::Guard, P>>;
+    type Error = P::Error;
+
+    async fn check(
+        &self,
+        ctx: Context<State>,
+        request: Request,
+    ) -> PolicyResult<State, Request, Self::Guard, Self::Error> {
+        let Some(key) = self.extractor.extract_key(&ctx, &request) else {
+            return PolicyResult {
+                ctx,
+                request,
+                output: PolicyOutput::Ready(None),
+            };
+        };
+
+        let policy = self.get_or_create_policy(key);
+        let result = policy.check(ctx, request).await;
+        let output = match result.output {
+            PolicyOutput::Ready(guard) => PolicyOutput::Ready(Some(KeyedPolicyGuard {
+                guard,
+                _policy: policy,
+            })),
+            PolicyOutput::Abort(err) => PolicyOutput::Abort(err),
+            PolicyOutput::Retry => PolicyOutput::Retry,
+        };
+        PolicyResult {
+            ctx: result.ctx,
+            request: result.request,
+            output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::limit::policy::{ConcurrentPolicy, LimitReached};
+
+    type TestKeyFn = KeyFn<fn(&Context<()>, &&'static str) -> Option<&'static str>, &'static str>;
+
+    fn test_key_fn() -> TestKeyFn {
+        KeyFn::new(|_, req| (!req.is_empty()).then_some(*req))
+    }
+
+    async fn assert_ready<P>(policy: &P, req: &'static str) -> P::Guard
+    where
+        P: Policy<(), &'static str>,
+    {
+        let result = policy.check(Context::default(), req).await;
+        match result.output {
+            PolicyOutput::Ready(guard) => guard,
+            _ => panic!("unexpected output, expected ready"),
+        }
+    }
+
+    async fn assert_abort<P>(policy: &P, req: &'static str)
+    where
+        P: Policy<(), &'static str, Error = LimitReached>,
+    {
+        let result = policy.check(Context::default(), req).await;
+        match result.output {
+            PolicyOutput::Abort(_) => (),
+            _ => panic!("unexpected output, expected abort"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keyed_policy_per_key() {
+        let policy = KeyedPolicy::new(test_key_fn(), || ConcurrentPolicy::max(1));
+
+        let alice = assert_ready(&policy, "alice").await;
+        assert!(alice.is_some());
+        assert_abort(&policy, "alice").await;
+
+        let bob = assert_ready(&policy, "bob").await;
+        assert!(bob.is_some());
+        assert_abort(&policy.clone(), "bob").await;
+
+        drop(alice);
+        let _alice = assert_ready(&policy, "alice").await;
+        assert_abort(&policy, "bob").await;
+    }
+
+    #[tokio::test]
+    async fn test_keyed_policy_no_key() {
+        let policy = KeyedPolicy::new(test_key_fn(), || ConcurrentPolicy::max(0));
+
+        let guard = assert_ready(&policy, "").await;
+        assert!(guard.is_none());
+        assert_abort(&policy, "alice").await;
+    }
+
+    #[tokio::test]
+    async fn test_keyed_policy_unit_key() {
+        let policy = KeyedPolicy::new((), || ConcurrentPolicy::max(1));
+
+        let guard = assert_ready(&policy, "alice").await;
+        assert!(guard.is_some());
+        assert_abort(&policy, "bob").await;
+
+        drop(guard);
+        let _guard = assert_ready(&policy, "bob").await;
+    }
+
+    #[tokio::test]
+    async fn test_keyed_policy_bounded() {
+        let policy = KeyedPolicy::new(test_key_fn(), || ConcurrentPolicy::max(1)).with_max_keys(1);
+
+        for key in ["a", "b", "c", "d"] {
+            let _guard = assert_ready(&policy, key).await;
+        }
+        policy.policies.run_pending_tasks();
+        assert!(policy.policies.entry_count() <= 1);
+    }
+
+    #[tokio::test]
+    async fn test_keyed_policy_evicted_while_guarded() {
+        let policy = KeyedPolicy::new(test_key_fn(), || ConcurrentPolicy::max(1))
+            .with_idle_timeout(Duration::from_millis(10));
+
+        let alice = assert_ready(&policy, "alice").await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        policy.policies.run_pending_tasks();
+        assert!(!policy.policies.contains_key(&"alice"));
+
+        // the evicted policy is reused as long as the guard is alive
+        assert_abort(&policy, "alice").await;
+        assert!(policy.policy(&"alice").is_some());
+
+        drop(alice);
+        let _alice = assert_ready(&policy, "alice").await;
+    }
+
+    #[tokio::test]
+    async fn test_keyed_policy_flooded_while_guarded() {
+        let policy = KeyedPolicy::new(test_key_fn(), || ConcurrentPolicy::max(1)).with_max_keys(2);
+
+        let alice = assert_ready(&policy, "alice").await;
+        let keys: Vec<&'static str> = (0..256)
+            .map(|i| &*Box::leak(format!("key-{i}").into_boxed_str()))
+            .collect();
+        for key in keys {
+            let _guard = assert_ready(&policy, key).await;
+        }
+        policy.policies.run_pending_tasks();
+        assert!(policy.policies.entry_count() <= 2);
+
+        assert_abort(&policy, "alice").await;
+        drop(alice);
+        let _alice = assert_ready(&policy, "alice").await;
+    }
+}
@@ -35,6 +35,21 @@ mod concurrent;
 #[doc(inline)]
 pub use concurrent::{ConcurrentCounter, ConcurrentPolicy, ConcurrentTracker, LimitReached};
 
+mod keyed;
+#[doc(inline)]
+pub use keyed::{ExtractKey, KeyExtractor, KeyFn, KeyedPolicy, KeyedPolicyGuard};
+
+mod rate;
+#[doc(inline)]
+pub use rate::{Rate, RateLimitPolicy, RateLimited};
+
+mod store;
+#[doc(inline)]
+pub use store::{
+    LocalRateLimitStore, RateLimitStore, StoreFailureMode, StoreRateLimitError,
+    StoreRateLimitPolicy,
+};
+
 mod matcher;
 
 /// The full result of a limit policy.
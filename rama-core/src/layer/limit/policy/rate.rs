@@ -0,0 +1,520 @@
+//! [`Policy`]s that limit the rate of requests.
+//!
+//! See [`RateLimitPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::limit::{Limit, policy::{Rate, RateLimitPolicy}};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! # use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = service_fn(async |_, _| {
+//!     Ok::<_, Infallible>(())
+//! });
+//! let service = Limit::new(service, RateLimitPolicy::token_bucket(Rate::per_second(1), 1));
+//!
+//! assert!(service.serve(Context::default(), ()).await.is_ok());
+//! assert!(service.serve(Context::default(), ()).await.is_err());
+//! # }
+//! ```
+
+use super::{Policy, PolicyOutput, PolicyResult};
+use crate::Context;
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The rate of requests allowed by a [`RateLimitPolicy`],
+/// expressed as a limit of requests per period.
+pub struct Rate {
+    limit: u32,
+    period: Duration,
+}
+
+impl Rate {
+    /// Create a new [`Rate`] allowing `limit` requests per `period`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0` or `period` is zero.
+    /// Use a [`ConcurrentPolicy`] with a max of `0` to block requests instead.
+    ///
+    /// [`ConcurrentPolicy`]: super::ConcurrentPolicy
+    pub fn new(limit: u32, period: Duration) -> Self {
+        assert!(limit > 0, "rate limit must be greater than 0");
+        assert!(!period.is_zero(), "rate period must be greater than 0");
+        Self { limit, period }
+    }
+
+    /// Create a new [`Rate`] allowing `limit` requests per second.
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    /// Create a new [`Rate`] allowing `limit` requests per minute.
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    /// Create a new [`Rate`] allowing `limit` requests per hour.
+    pub fn per_hour(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60 * 60))
+    }
+
+    /// The amount of requests allowed per [`Self::period`].
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// The period in which [`Self::limit`] requests are allowed.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// The average time between two requests.
+    pub(super) fn emission_interval(&self) -> Duration {
+        self.period / self.limit
+    }
+}
+
+/// A [`Policy`] that limits the rate of requests.
+///
+/// Three algorithms are available:
+///
+/// - [`RateLimitPolicy::token_bucket`]: a bucket of `burst` tokens is refilled at the given [`Rate`],
+///   each request consuming a single token;
+/// - [`RateLimitPolicy::gcra`]: the Generic Cell Rate Algorithm, which behaves as a token bucket
+///   but only needs to track a single timestamp;
+/// - [`RateLimitPolicy::sliding_window`]: allows at most [`Rate::limit`] requests
+///   in any window of [`Rate::period`], approximated by weighing the count of the previous window.
+///
+/// Requests exceeding the rate are aborted with a [`RateLimited`] error,
+/// which reports after how long the request can be retried. Use it in the error
+/// into response function of the [`LimitLayer`] to respond with a `429 Too Many Requests`.
+///
+/// Alternatively requests can be delayed instead of aborted, for as long as
+/// the delay is not longer than the one configured using [`RateLimitPolicy::with_max_delay`].
+///
+/// Cloning a [`RateLimitPolicy`] shares the rate limit state.
+///
+/// [`LimitLayer`]: crate::layer::LimitLayer
+pub struct RateLimitPolicy {
+    limiter: Arc<Mutex<RateLimiter>>,
+    max_delay: Option<Duration>,
+}
+
+impl fmt::Debug for RateLimitPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitPolicy")
+            .field("limiter", &self.limiter)
+            .field("max_delay", &self.max_delay)
+            .finish()
+    }
+}
+
+impl Clone for RateLimitPolicy {
+    fn clone(&self) -> Self {
+        Self {
+            limiter: self.limiter.clone(),
+            max_delay: self.max_delay,
+        }
+    }
+}
+
+impl RateLimitPolicy {
+    fn new(limiter: RateLimiter) -> Self {
+        Self {
+            limiter: Arc::new(Mutex::new(limiter)),
+            max_delay: None,
+        }
+    }
+
+    /// Create a new token bucket [`RateLimitPolicy`],
+    /// allowing bursts of up to `burst` requests,
+    /// refilled at the given [`Rate`].
+    ///
+    /// A `burst` of `0` is treated as a `burst` of `1`.
+    pub fn token_bucket(rate: Rate, burst: u32) -> Self {
+        Self::new(RateLimiter::TokenBucket(TokenBucket::new(
+            rate,
+            burst,
+            Instant::now(),
+        )))
+    }
+
+    /// Create a new GCRA [`RateLimitPolicy`],
+    /// allowing bursts of up to `burst` requests at the given [`Rate`].
+    ///
+    /// A `burst` of `0` is treated as a `burst` of `1`.
+    pub fn gcra(rate: Rate, burst: u32) -> Self {
+        Self::new(RateLimiter::Gcra(Gcra::new(rate, burst, Instant::now())))
+    }
+
+    /// Create a new sliding window [`RateLimitPolicy`],
+    /// allowing [`Rate::limit`] requests in any window of [`Rate::period`].
+    pub fn sliding_window(rate: Rate) -> Self {
+        Self::new(RateLimiter::SlidingWindow(SlidingWindow::new(
+            rate,
+            Instant::now(),
+        )))
+    }
+
+    /// Delay requests exceeding the rate, instead of aborting them,
+    /// for as long as they can be retried within the given `max_delay`.
+    pub fn set_max_delay(&mut self, max_delay: Duration) -> &mut Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Same as [`Self::set_max_delay`] but consuming self.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+}
+
+impl<State, Request> Policy<State, Request> for RateLimitPolicy
+where
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Guard = ();
+    type Error = RateLimited;
+
+    async fn check(
+        &self,
+        ctx: Context<State>,
+        request: Request,
+    ) -> PolicyResult<State, Request, Self::Guard, Self::Error> {
+        let result = self.limiter.lock().check(Instant::now());
+        let output = match result {
+            Ok(()) => PolicyOutput::Ready(()),
+            Err(retry_after) if self.max_delay.is_some_and(|max| retry_after <= max) => {
+                tokio::time::sleep(retry_after).await;
+                PolicyOutput::Retry
+            }
+            Err(retry_after) => PolicyOutput::Abort(RateLimited { retry_after }),
+        };
+        PolicyResult {
+            ctx,
+            request,
+            output,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Error returned by a [`RateLimitPolicy`] for requests exceeding the rate.
+pub struct RateLimited {
+    retry_after: Duration,
+}
+
+impl RateLimited {
+    /// Create a new [`RateLimited`] error,
+    /// for a request which can be retried after the given duration.
+    pub const fn new(retry_after: Duration) -> Self {
+        Self { retry_after }
+    }
+
+    /// The duration after which the request can be retried,
+    /// e.g. to be used as the value of a `Retry-After` header.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "request aborted due to exhausted rate limit (retry after {:?})",
+            self.retry_after
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+#[derive(Debug)]
+enum RateLimiter {
+    TokenBucket(TokenBucket),
+    Gcra(Gcra),
+    SlidingWindow(SlidingWindow),
+}
+
+impl RateLimiter {
+    /// Try to consume a single request at the given instant,
+    /// returning the duration after which it can be retried if not allowed.
+    fn check(&mut self, now: Instant) -> Result<(), Duration> {
+        match self {
+            Self::TokenBucket(bucket) => bucket.check(now),
+            Self::Gcra(gcra) => gcra.check(now),
+            Self::SlidingWindow(window) => window.check(now),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    /// tokens added per second
+    refill_rate: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: Rate, burst: u32, now: Instant) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_rate: rate.limit as f64 / rate.period.as_secs_f64(),
+            last_refill: now,
+        }
+    }
+
+    fn check(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = elapsed
+            .as_secs_f64()
+            .mul_add(self.refill_rate, self.tokens)
+            .min(self.capacity);
+        self.last_refill = now.max(self.last_refill);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_rate,
+            ))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Gcra {
+    emission_interval: Duration,
+    /// how far the theoretical arrival time can be ahead of now
+    tolerance: Duration,
+    /// theoretical arrival time of the next request
+    tat: Instant,
+}
+
+impl Gcra {
+    fn new(rate: Rate, burst: u32, now: Instant) -> Self {
+        let emission_interval = rate.emission_interval();
+        Self {
+            emission_interval,
+            tolerance: emission_interval * burst.max(1),
+            tat: now,
+        }
+    }
+
+    fn check(&mut self, now: Instant) -> Result<(), Duration> {
+        let tat = self.tat.max(now) + self.emission_interval;
+        match tat.checked_sub(self.tolerance) {
+            Some(allow_at) if allow_at > now => Err(allow_at - now),
+            _ => {
+                self.tat = tat;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SlidingWindow {
+    limit: u32,
+    window: Duration,
+    window_start: Instant,
+    previous_count: u32,
+    current_count: u32,
+}
+
+impl SlidingWindow {
+    fn new(rate: Rate, now: Instant) -> Self {
+        Self {
+            limit: rate.limit,
+            window: rate.period,
+            window_start: now,
+            previous_count: 0,
+            current_count: 0,
+        }
+    }
+
+    fn check(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed >= self.window {
+            let windows_passed = (elapsed.as_nanos() / self.window.as_nanos()) as u32;
+            self.previous_count = if windows_passed == 1 {
+                self.current_count
+            } else {
+                0
+            };
+            self.current_count = 0;
+            self.window_start += self.window * windows_passed;
+        }
+
+        let elapsed = now.saturating_duration_since(self.window_start);
+        sliding_window_check(
+            self.limit.into(),
+            self.previous_count.into(),
+            self.current_count.into(),
+            elapsed,
+            self.window,
+        )?;
+        self.current_count += 1;
+        Ok(())
+    }
+}
+
+/// Check whether a request is allowed within a sliding window,
+/// approximated using the count of the previous and current window,
+/// with `elapsed` the time passed since the start of the current window.
+///
+/// The current count excludes the request being checked.
+pub(super) fn sliding_window_check(
+    limit: u64,
+    previous_count: u64,
+    current_count: u64,
+    elapsed: Duration,
+    window: Duration,
+) -> Result<(), Duration> {
+    let elapsed = elapsed.as_secs_f64();
+    let window = window.as_secs_f64();
+    let weight = 1.0 - elapsed / window;
+    let count = (previous_count as f64).mul_add(weight, current_count as f64);
+
+    if count + 1.0 <= limit as f64 {
+        return Ok(());
+    }
+
+    let retry_after = if current_count < limit {
+        // wait until the weight of the previous window dropped enough
+        let max_weight = (limit - current_count - 1) as f64 / previous_count as f64;
+        window.mul_add(1.0 - max_weight, -elapsed)
+    } else {
+        // wait until the current window became the previous one, and its weight dropped enough
+        let max_weight = (limit - 1) as f64 / current_count as f64;
+        window.mul_add(1.0 - max_weight, window - elapsed)
+    };
+    Err(Duration::from_secs_f64(retry_after.max(0.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(Rate::per_second(2), 3, now);
+
+        for _ in 0..3 {
+            assert!(bucket.check(now).is_ok());
+        }
+        let retry_after = bucket.check(now).unwrap_err();
+        assert_eq!(retry_after.as_millis(), 500);
+
+        assert!(bucket.check(now + 250 * MS).is_err());
+        assert!(bucket.check(now + 500 * MS).is_ok());
+        assert!(bucket.check(now + 500 * MS).is_err());
+
+        // refill is capped at the burst size
+        let later = now + 10 * SECOND;
+        for _ in 0..3 {
+            assert!(bucket.check(later).is_ok());
+        }
+        assert!(bucket.check(later).is_err());
+    }
+
+    #[test]
+    fn test_gcra() {
+        let now = Instant::now();
+        let mut gcra = Gcra::new(Rate::per_second(2), 3, now);
+
+        for _ in 0..3 {
+            assert!(gcra.check(now).is_ok());
+        }
+        assert_eq!(gcra.check(now).unwrap_err(), 500 * MS);
+
+        assert_eq!(gcra.check(now + 400 * MS).unwrap_err(), 100 * MS);
+        assert!(gcra.check(now + 500 * MS).is_ok());
+        assert!(gcra.check(now + 500 * MS).is_err());
+
+        let later = now + 10 * SECOND;
+        for _ in 0..3 {
+            assert!(gcra.check(later).is_ok());
+        }
+        assert!(gcra.check(later).is_err());
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let now = Instant::now();
+        let mut window = SlidingWindow::new(Rate::per_second(4), now);
+
+        for _ in 0..4 {
+            assert!(window.check(now).is_ok());
+        }
+        // previous window becomes the current one at 1s,
+        // after which its weight has to drop to 3/4
+        assert_eq!(window.check(now).unwrap_err(), 1250 * MS);
+
+        // at 1.5s the previous window still counts for 2 requests
+        assert!(window.check(now + 1500 * MS).is_ok());
+        assert!(window.check(now + 1500 * MS).is_ok());
+        assert!(window.check(now + 1500 * MS).is_err());
+
+        // two windows later nothing is counted anymore
+        let later = now + 3 * SECOND;
+        for _ in 0..4 {
+            assert!(window.check(later).is_ok());
+        }
+        assert!(window.check(later).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_policy_abort() {
+        let policy = RateLimitPolicy::gcra(Rate::per_minute(1), 1);
+
+        let result = policy.check(Context::default(), ()).await;
+        assert!(matches!(result.output, PolicyOutput::Ready(())));
+
+        let result = policy.clone().check(Context::default(), ()).await;
+        match result.output {
+            PolicyOutput::Abort(err) => {
+                assert!(err.retry_after() > 59 * SECOND);
+                assert!(err.retry_after() <= 60 * SECOND);
+            }
+            _ => panic!("unexpected output, expected abort"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_policy_delay() {
+        let policy = RateLimitPolicy::token_bucket(Rate::per_second(100), 1).with_max_delay(SECOND);
+
+        let result = policy.check(Context::default(), ()).await;
+        assert!(matches!(result.output, PolicyOutput::Ready(())));
+
+        let result = policy.check(Context::default(), ()).await;
+        assert!(matches!(result.output, PolicyOutput::Retry));
+
+        let result = policy.check(Context::default(), ()).await;
+        assert!(matches!(result.output, PolicyOutput::Ready(())));
+    }
+}
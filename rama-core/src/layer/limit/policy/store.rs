@@ -0,0 +1,753 @@
+//! A [`Policy`] that limits the rate of requests using the state
+//! kept in a [`RateLimitStore`], such that the limits can be shared
+//! by multiple instances of a service.
+//!
+//! See [`StoreRateLimitPolicy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::layer::limit::{Limit, policy::{KeyFn, LocalRateLimitStore, Rate, StoreRateLimitPolicy}};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! # use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let service = service_fn(async |_, _: &'static str| {
+//!     Ok::<_, Infallible>(())
+//! });
+//! let service = Limit::new(
+//!     service,
+//!     StoreRateLimitPolicy::sliding_window(
+//!         LocalRateLimitStore::new(),
+//!         KeyFn::new(|_: &Context<()>, req: &&'static str| Some(*req)),
+//!         Rate::per_minute(1),
+//!     ),
+//! );
+//!
+//! assert!(service.serve(Context::default(), "alice").await.is_ok());
+//! assert!(service.serve(Context::default(), "alice").await.is_err());
+//! assert!(service.serve(Context::default(), "bob").await.is_ok());
+//! # }
+//! ```
+
+use super::{ExtractKey, Policy, PolicyOutput, PolicyResult, Rate, RateLimited};
+use crate::Context;
+use crate::error::BoxError;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// A store of counters and values, used by a [`StoreRateLimitPolicy`].
+///
+/// Updates have to be atomic, such that a store
+/// can be shared by multiple instances of a service,
+/// e.g. by storing the counters in a Redis server.
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// The error returned in case the store could not be accessed.
+    type Error: Into<BoxError> + Send + 'static;
+
+    /// Atomically increment the counter of the given key by one,
+    /// returning the incremented count.
+    ///
+    /// A counter which does not exist yet is created with a count of `0`
+    /// prior to being incremented, and is removed after the given `ttl`.
+    fn increment(
+        &self,
+        key: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Get the count of the counter of the given key,
+    /// which is `0` for counters which do not exist.
+    fn get(&self, key: &str) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+
+    /// Atomically set the value of the given key to `new`, which is removed after
+    /// the given `ttl`, only if its value (as returned by [`Self::get`]) is `current`.
+    ///
+    /// Returns whether or not the value was set.
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: u64,
+        new: u64,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+}
+
+impl<S: RateLimitStore> RateLimitStore for Arc<S> {
+    type Error = S::Error;
+
+    fn increment(
+        &self,
+        key: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+        (**self).increment(key, ttl)
+    }
+
+    fn get(&self, key: &str) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+        (**self).get(key)
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: u64,
+        new: u64,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
+        (**self).compare_and_swap(key, current, new, ttl)
+    }
+}
+
+/// An in-process [`RateLimitStore`].
+///
+/// Cloning a [`LocalRateLimitStore`] shares its counters.
+#[derive(Debug, Clone, Default)]
+pub struct LocalRateLimitStore {
+    counters: Arc<Mutex<LocalCounters>>,
+}
+
+#[derive(Debug, Default)]
+struct LocalCounters {
+    counters: HashMap<String, LocalCounter>,
+    updates_since_purge: usize,
+}
+
+impl LocalCounters {
+    /// Amount of updates after which expired counters are removed.
+    const PURGE_INTERVAL: usize = 1024;
+
+    fn on_update(&mut self, now: Instant) {
+        self.updates_since_purge += 1;
+        if self.updates_since_purge >= Self::PURGE_INTERVAL {
+            self.updates_since_purge = 0;
+            self.counters.retain(|_, counter| counter.expires_at > now);
+        }
+    }
+
+    fn get(&self, key: &str, now: Instant) -> u64 {
+        self.counters
+            .get(key)
+            .filter(|counter| counter.expires_at > now)
+            .map(|counter| counter.count)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+struct LocalCounter {
+    count: u64,
+    expires_at: Instant,
+}
+
+impl LocalRateLimitStore {
+    /// Create a new empty [`LocalRateLimitStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RateLimitStore for LocalRateLimitStore {
+    type Error = std::convert::Infallible;
+
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64, Self::Error> {
+        let now = Instant::now();
+        let mut counters = self.counters.lock();
+        counters.on_update(now);
+
+        let counter = counters
+            .counters
+            .entry(key.to_owned())
+            .or_insert_with(|| LocalCounter {
+                count: 0,
+                expires_at: now + ttl,
+            });
+        if counter.expires_at <= now {
+            counter.count = 0;
+            counter.expires_at = now + ttl;
+        }
+        counter.count += 1;
+        Ok(counter.count)
+    }
+
+    async fn get(&self, key: &str) -> Result<u64, Self::Error> {
+        Ok(self.counters.lock().get(key, Instant::now()))
+    }
+
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        current: u64,
+        new: u64,
+        ttl: Duration,
+    ) -> Result<bool, Self::Error> {
+        let now = Instant::now();
+        let mut counters = self.counters.lock();
+        if counters.get(key, now) != current {
+            return Ok(false);
+        }
+        counters.on_update(now);
+        counters.counters.insert(
+            key.to_owned(),
+            LocalCounter {
+                count: new,
+                expires_at: now + ttl,
+            },
+        );
+        Ok(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StoreAlgorithm {
+    FixedWindow,
+    SlidingWindow,
+    Gcra { burst: u32 },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// How a [`StoreRateLimitPolicy`] handles requests
+/// in case its [`RateLimitStore`] cannot be accessed.
+pub enum StoreFailureMode {
+    #[default]
+    /// Allow the request to proceed,
+    /// such that the limit is applied on a best-effort basis.
+    Open,
+    /// Abort the request with a [`StoreRateLimitError::Store`] error,
+    /// such that no request exceeds the limit.
+    Closed,
+}
+
+#[derive(Debug)]
+/// Error returned by a [`StoreRateLimitPolicy`] for aborted requests.
+pub enum StoreRateLimitError {
+    /// The request exceeds the rate.
+    RateLimited(RateLimited),
+    /// The store could not be accessed, while failing closed.
+    Store(BoxError),
+}
+
+impl fmt::Display for StoreRateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RateLimited(err) => err.fmt(f),
+            Self::Store(err) => write!(
+                f,
+                "request aborted due to inaccessible rate limit store: {err}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StoreRateLimitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::RateLimited(err) => Some(err),
+            Self::Store(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// A [`Policy`] that limits the rate of requests per key,
+/// using the state kept in a [`RateLimitStore`].
+///
+/// Four algorithms are available:
+///
+/// - [`StoreRateLimitPolicy::fixed_window`]: a single counter per window;
+/// - [`StoreRateLimitPolicy::sliding_window`]: the counters of the current
+///   and previous window, weighing the count of the previous window;
+/// - [`StoreRateLimitPolicy::gcra`]: the Generic Cell Rate Algorithm,
+///   storing the theoretical arrival time as a single value per key;
+/// - [`StoreRateLimitPolicy::token_bucket`]: a token bucket,
+///   backed by the same state as the GCRA to which it is equivalent.
+///
+/// The key is extracted from the [`Context`] and request using an [`ExtractKey`]
+/// implementation, just like for the [`KeyedPolicy`], and is formatted
+/// together with the key prefix (and window) as the key in the store.
+/// Requests for which no key is extracted are not limited.
+///
+/// Windows and timestamps are relative to the unix epoch, such that all instances
+/// sharing a store use the same state. The clocks of these instances
+/// are therefore expected to be synchronised.
+///
+/// Requests exceeding the rate are aborted with a [`StoreRateLimitError::RateLimited`] error.
+/// Using the window algorithms these requests still count towards the limit.
+/// How requests are handled in case the store cannot be accessed
+/// is configured using [`StoreRateLimitPolicy::with_failure_mode`].
+///
+/// Cloning a [`StoreRateLimitPolicy`] shares the store.
+///
+/// [`KeyedPolicy`]: super::KeyedPolicy
+pub struct StoreRateLimitPolicy<S, K> {
+    store: S,
+    extractor: K,
+    rate: Rate,
+    algorithm: StoreAlgorithm,
+    key_prefix: Arc<str>,
+    failure_mode: StoreFailureMode,
+}
+
+impl<S: fmt::Debug, K: fmt::Debug> fmt::Debug for StoreRateLimitPolicy<S, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreRateLimitPolicy")
+            .field("store", &self.store)
+            .field("extractor", &self.extractor)
+            .field("rate", &self.rate)
+            .field("algorithm", &self.algorithm)
+            .field("key_prefix", &self.key_prefix)
+            .field("failure_mode", &self.failure_mode)
+            .finish()
+    }
+}
+
+impl<S: Clone, K: Clone> Clone for StoreRateLimitPolicy<S, K> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            extractor: self.extractor.clone(),
+            rate: self.rate,
+            algorithm: self.algorithm,
+            key_prefix: self.key_prefix.clone(),
+            failure_mode: self.failure_mode,
+        }
+    }
+}
+
+impl<S, K> StoreRateLimitPolicy<S, K> {
+    /// Maximum amount of attempts to update the state of a key
+    /// in case it is concurrently updated by other requests.
+    const MAX_UPDATE_ATTEMPTS: usize = 8;
+    /// Default prefix of the keys in the store.
+    pub const DEFAULT_KEY_PREFIX: &str = "rama:ratelimit";
+
+    fn new(store: S, extractor: K, rate: Rate, algorithm: StoreAlgorithm) -> Self {
+        Self {
+            store,
+            extractor,
+            rate,
+            algorithm,
+            key_prefix: Self::DEFAULT_KEY_PREFIX.into(),
+            failure_mode: StoreFailureMode::default(),
+        }
+    }
+
+    /// Create a new [`StoreRateLimitPolicy`] allowing [`Rate::limit`] requests
+    /// per window of [`Rate::period`], using a single counter per window.
+    ///
+    /// Note that this allows bursts of up to twice the limit
+    /// around the boundary of two windows.
+    ///
+    /// The counter is incremented prior to checking it, using a single
+    /// atomic [`RateLimitStore::increment`], such that aborted requests
+    /// count towards the limit as well. Clients retrying while limited
+    /// therefore keep the counter above the limit until the window ends.
+    pub fn fixed_window(store: S, extractor: K, rate: Rate) -> Self {
+        Self::new(store, extractor, rate, StoreAlgorithm::FixedWindow)
+    }
+
+    /// Create a new [`StoreRateLimitPolicy`] allowing [`Rate::limit`] requests
+    /// in any window of [`Rate::period`], approximated by weighing
+    /// the counter of the previous window.
+    ///
+    /// Unlike the in-process [`RateLimitPolicy::sliding_window`],
+    /// the counter is incremented prior to checking it, using a single
+    /// atomic [`RateLimitStore::increment`], such that aborted requests
+    /// count towards the limit as well. Clients retrying while limited
+    /// therefore also delay being allowed again in the next window,
+    /// as their retries weigh in as part of the previous window.
+    ///
+    /// [`RateLimitPolicy::sliding_window`]: super::RateLimitPolicy::sliding_window
+    pub fn sliding_window(store: S, extractor: K, rate: Rate) -> Self {
+        Self::new(store, extractor, rate, StoreAlgorithm::SlidingWindow)
+    }
+
+    /// Create a new GCRA [`StoreRateLimitPolicy`],
+    /// allowing bursts of up to `burst` requests at the given [`Rate`].
+    ///
+    /// A `burst` of `0` is treated as a `burst` of `1`.
+    pub fn gcra(store: S, extractor: K, rate: Rate, burst: u32) -> Self {
+        Self::new(store, extractor, rate, StoreAlgorithm::Gcra { burst })
+    }
+
+    /// Create a new token bucket [`StoreRateLimitPolicy`],
+    /// allowing bursts of up to `burst` requests,
+    /// refilled at the given [`Rate`].
+    ///
+    /// The bucket is tracked as the theoretical arrival time of the GCRA,
+    /// such that it only needs a single value per key,
+    /// and thus behaves exactly as [`Self::gcra`].
+    ///
+    /// A `burst` of `0` is treated as a `burst` of `1`.
+    pub fn token_bucket(store: S, extractor: K, rate: Rate, burst: u32) -> Self {
+        Self::gcra(store, extractor, rate, burst)
+    }
+
+    /// Set how requests are handled in case the store cannot be accessed,
+    /// failing open by default.
+    pub fn set_failure_mode(&mut self, mode: StoreFailureMode) -> &mut Self {
+        self.failure_mode = mode;
+        self
+    }
+
+    /// Same as [`Self::set_failure_mode`] but consuming self.
+    pub fn with_failure_mode(mut self, mode: StoreFailureMode) -> Self {
+        self.failure_mode = mode;
+        self
+    }
+
+    /// Set the prefix of the keys in the store,
+    /// e.g. to separate the state of multiple policies sharing a store.
+    pub fn set_key_prefix(&mut self, prefix: impl Into<Arc<str>>) -> &mut Self {
+        self.key_prefix = prefix.into();
+        self
+    }
+
+    /// Same as [`Self::set_key_prefix`] but consuming self.
+    pub fn with_key_prefix(mut self, prefix: impl Into<Arc<str>>) -> Self {
+        self.key_prefix = prefix.into();
+        self
+    }
+
+    /// The store used by this [`StoreRateLimitPolicy`].
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    fn counter_key(&self, key: &impl fmt::Display, window: u128) -> String {
+        format!("{}:{key}:{window}", self.key_prefix)
+    }
+
+    async fn check_key(&self, key: &impl fmt::Display) -> Result<Result<(), Duration>, BoxError>
+    where
+        S: RateLimitStore,
+    {
+        let period = self.rate.period();
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let window = since_epoch.as_nanos() / period.as_nanos();
+        let elapsed = Duration::from_nanos((since_epoch.as_nanos() % period.as_nanos()) as u64);
+        let limit = u64::from(self.rate.limit());
+
+        match self.algorithm {
+            StoreAlgorithm::FixedWindow => {
+                let count = self
+                    .store
+                    .increment(&self.counter_key(key, window), period)
+                    .await
+                    .map_err(Into::into)?;
+                Ok(if count <= limit {
+                    Ok(())
+                } else {
+                    Err(period - elapsed)
+                })
+            }
+            StoreAlgorithm::SlidingWindow => {
+                let previous_key = self.counter_key(key, window.saturating_sub(1));
+                let current_key = self.counter_key(key, window);
+                let count = self
+                    .store
+                    .increment(&current_key, period * 2)
+                    .await
+                    .map_err(Into::into)?;
+                let previous = self.store.get(&previous_key).await.map_err(Into::into)?;
+                Ok(super::rate::sliding_window_check(
+                    limit,
+                    previous,
+                    count.saturating_sub(1),
+                    elapsed,
+                    period,
+                ))
+            }
+            StoreAlgorithm::Gcra { burst } => self.check_key_gcra(key, burst).await,
+        }
+    }
+
+    async fn check_key_gcra(
+        &self,
+        key: &impl fmt::Display,
+        burst: u32,
+    ) -> Result<Result<(), Duration>, BoxError>
+    where
+        S: RateLimitStore,
+    {
+        let key = format!("{}:{key}", self.key_prefix);
+        let emission_interval = self.rate.emission_interval().as_nanos() as u64;
+        let tolerance = emission_interval.saturating_mul(u64::from(burst.max(1)));
+
+        for _ in 0..Self::MAX_UPDATE_ATTEMPTS {
+            let stored_tat = self.store.get(&key).await.map_err(Into::into)?;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64;
+
+            let tat = stored_tat.max(now).saturating_add(emission_interval);
+            let allow_at = tat.saturating_sub(tolerance);
+            if allow_at > now {
+                return Ok(Err(Duration::from_nanos(allow_at - now)));
+            }
+
+            if self
+                .store
+                .compare_and_swap(&key, stored_tat, tat, Duration::from_nanos(tat - now))
+                .await
+                .map_err(Into::into)?
+            {
+                return Ok(Ok(()));
+            }
+        }
+
+        Err(BoxError::from(
+            "state concurrently updated by other requests",
+        ))
+    }
+}
+
+impl<S, K, State, Request> Policy<State, Request> for StoreRateLimitPolicy<S, K>
+where
+    S: RateLimitStore,
+    K: ExtractKey<State, Request, Key: fmt::Display>,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Guard = ();
+    type Error = StoreRateLimitError;
+
+    async fn check(
+        &self,
+        ctx: Context<State>,
+        request: Request,
+    ) -> PolicyResult<State, Request, Self::Guard, Self::Error> {
+        let output = match self.extractor.extract_key(&ctx, &request) {
+            Some(key) => match self.check_key(&key).await {
+                Ok(Ok(())) => PolicyOutput::Ready(()),
+                Ok(Err(retry_after)) => PolicyOutput::Abort(StoreRateLimitError::RateLimited(
+                    RateLimited::new(retry_after),
+                )),
+                Err(err) => match self.failure_mode {
+                    StoreFailureMode::Open => {
+                        tracing::debug!(error = %err, "StoreRateLimitPolicy: failed to access store: allow request");
+                        PolicyOutput::Ready(())
+                    }
+                    StoreFailureMode::Closed => {
+                        tracing::debug!(error = %err, "StoreRateLimitPolicy: failed to access store: abort request");
+                        PolicyOutput::Abort(StoreRateLimitError::Store(err))
+                    }
+                },
+            },
+            None => PolicyOutput::Ready(()),
+        };
+        PolicyResult {
+            ctx,
+            request,
+            output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::limit::policy::KeyFn;
+
+    type TestKeyFn = KeyFn<fn(&Context<()>, &&'static str) -> Option<&'static str>, &'static str>;
+
+    fn test_key_fn() -> TestKeyFn {
+        KeyFn::new(|_, req| (!req.is_empty()).then_some(*req))
+    }
+
+    async fn is_ready<P>(policy: &P, req: &'static str) -> bool
+    where
+        P: Policy<(), &'static str>,
+    {
+        let result = policy.check(Context::default(), req).await;
+        match result.output {
+            PolicyOutput::Ready(_) => true,
+            PolicyOutput::Abort(_) => false,
+            PolicyOutput::Retry => panic!("unexpected output, expected ready or abort"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_store() {
+        let store = LocalRateLimitStore::new();
+        assert_eq!(store.get("a").await.unwrap(), 0);
+        assert_eq!(
+            store.increment("a", Duration::from_secs(60)).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            store.increment("a", Duration::from_secs(60)).await.unwrap(),
+            2
+        );
+        assert_eq!(store.clone().get("a").await.unwrap(), 2);
+        assert_eq!(store.get("b").await.unwrap(), 0);
+
+        assert_eq!(store.increment("c", Duration::ZERO).await.unwrap(), 1);
+        assert_eq!(store.get("c").await.unwrap(), 0);
+        assert_eq!(store.increment("c", Duration::ZERO).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_fixed_window() {
+        let policy = StoreRateLimitPolicy::fixed_window(
+            LocalRateLimitStore::new(),
+            test_key_fn(),
+            Rate::per_hour(2),
+        );
+
+        assert!(is_ready(&policy, "alice").await);
+        assert!(is_ready(&policy, "alice").await);
+        assert!(!is_ready(&policy, "alice").await);
+        assert!(is_ready(&policy, "bob").await);
+        assert!(is_ready(&policy, "").await);
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_window_retries_count() {
+        // a period long enough for the window not to change during the test
+        let rate = Rate::new(2, Duration::from_secs(365 * 24 * 60 * 60));
+        let store = LocalRateLimitStore::new();
+        let window = || {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+                / rate.period().as_nanos()
+        };
+
+        for policy in [
+            StoreRateLimitPolicy::fixed_window(store.clone(), test_key_fn(), rate)
+                .with_key_prefix("fixed"),
+            StoreRateLimitPolicy::sliding_window(store.clone(), test_key_fn(), rate)
+                .with_key_prefix("sliding"),
+        ] {
+            assert!(is_ready(&policy, "alice").await);
+            assert!(is_ready(&policy, "alice").await);
+            for _ in 0..3 {
+                assert!(!is_ready(&policy, "alice").await);
+            }
+
+            // the aborted retries count towards the limit as well
+            let count = store
+                .get(&policy.counter_key(&"alice", window()))
+                .await
+                .unwrap();
+            assert_eq!(count, 5);
+            assert!(!is_ready(&policy, "alice").await);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_sliding_window_shared_store() {
+        let store = LocalRateLimitStore::new();
+        let policy_a =
+            StoreRateLimitPolicy::sliding_window(store.clone(), test_key_fn(), Rate::per_hour(2));
+        let policy_b =
+            StoreRateLimitPolicy::sliding_window(store, test_key_fn(), Rate::per_hour(2));
+
+        assert!(is_ready(&policy_a, "alice").await);
+        assert!(is_ready(&policy_b, "alice").await);
+        assert!(!is_ready(&policy_a, "alice").await);
+        assert!(!is_ready(&policy_b, "alice").await);
+
+        let policy_c = policy_a.clone().with_key_prefix("other");
+        assert!(is_ready(&policy_c, "alice").await);
+    }
+
+    #[tokio::test]
+    async fn test_local_store_compare_and_swap() {
+        let store = LocalRateLimitStore::new();
+        let ttl = Duration::from_secs(60);
+        assert!(!store.compare_and_swap("a", 1, 2, ttl).await.unwrap());
+        assert!(store.compare_and_swap("a", 0, 2, ttl).await.unwrap());
+        assert_eq!(store.get("a").await.unwrap(), 2);
+        assert!(!store.compare_and_swap("a", 0, 3, ttl).await.unwrap());
+        assert!(store.compare_and_swap("a", 2, 3, ttl).await.unwrap());
+        assert_eq!(store.get("a").await.unwrap(), 3);
+
+        assert!(
+            store
+                .compare_and_swap("b", 0, 1, Duration::ZERO)
+                .await
+                .unwrap()
+        );
+        assert_eq!(store.get("b").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_gcra_shared_store() {
+        let store = LocalRateLimitStore::new();
+        let policy_a =
+            StoreRateLimitPolicy::gcra(store.clone(), test_key_fn(), Rate::per_hour(1), 2);
+        let policy_b =
+            StoreRateLimitPolicy::token_bucket(store, test_key_fn(), Rate::per_hour(1), 2);
+
+        assert!(is_ready(&policy_a, "alice").await);
+        assert!(is_ready(&policy_b, "alice").await);
+        assert!(!is_ready(&policy_a, "alice").await);
+        assert!(!is_ready(&policy_b, "alice").await);
+        assert!(is_ready(&policy_a, "bob").await);
+        assert!(is_ready(&policy_a, "").await);
+
+        let result = policy_a.check(Context::default(), "alice").await;
+        let PolicyOutput::Abort(StoreRateLimitError::RateLimited(err)) = result.output else {
+            panic!("unexpected output, expected rate limited abort");
+        };
+        assert!(err.retry_after() > Duration::from_secs(3500));
+        assert!(err.retry_after() <= Duration::from_secs(3600));
+    }
+
+    #[derive(Debug, Clone)]
+    struct FailingStore;
+
+    impl RateLimitStore for FailingStore {
+        type Error = BoxError;
+
+        async fn increment(&self, _key: &str, _ttl: Duration) -> Result<u64, Self::Error> {
+            Err("store unavailable".into())
+        }
+
+        async fn get(&self, _key: &str) -> Result<u64, Self::Error> {
+            Err("store unavailable".into())
+        }
+
+        async fn compare_and_swap(
+            &self,
+            _key: &str,
+            _current: u64,
+            _new: u64,
+            _ttl: Duration,
+        ) -> Result<bool, Self::Error> {
+            Err("store unavailable".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_policy_failure_mode() {
+        let policy =
+            StoreRateLimitPolicy::fixed_window(FailingStore, test_key_fn(), Rate::per_hour(1));
+        assert!(is_ready(&policy, "alice").await);
+        assert!(is_ready(&policy, "alice").await);
+
+        let policy = policy.with_failure_mode(StoreFailureMode::Closed);
+        let result = policy.check(Context::default(), "alice").await;
+        assert!(matches!(
+            result.output,
+            PolicyOutput::Abort(StoreRateLimitError::Store(_))
+        ));
+        assert!(is_ready(&policy, "").await);
+
+        let policy = StoreRateLimitPolicy::gcra(FailingStore, test_key_fn(), Rate::per_hour(1), 1)
+            .with_failure_mode(StoreFailureMode::Closed);
+        assert!(!is_ready(&policy, "alice").await);
+    }
+}
@@ -866,6 +866,9 @@ pub use timeout::{Timeout, TimeoutLayer};
 pub mod limit;
 pub use limit::{Limit, LimitLayer};
 
+pub mod circuit_breaker;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerLayer};
+
 pub mod add_extension;
 pub use add_extension::{AddExtension, AddExtensionLayer};
 
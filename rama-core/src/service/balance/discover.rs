@@ -0,0 +1,355 @@
+use crate::error::{BoxError, ErrorContext, ErrorExt, OpaqueError};
+use parking_lot::Mutex;
+use std::{collections::HashMap, fmt, hash::Hash, path::PathBuf, sync::Arc};
+
+/// An endpoint discovered by a [`Discover`] implementation,
+/// to which a [`Balance`] service can send requests.
+///
+/// [`Balance`]: super::Balance
+pub struct Endpoint<K, S> {
+    key: K,
+    service: S,
+    weight: u32,
+}
+
+impl<K: fmt::Debug, S: fmt::Debug> fmt::Debug for Endpoint<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Endpoint")
+            .field("key", &self.key)
+            .field("service", &self.service)
+            .field("weight", &self.weight)
+            .finish()
+    }
+}
+
+impl<K: Clone, S: Clone> Clone for Endpoint<K, S> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            service: self.service.clone(),
+            weight: self.weight,
+        }
+    }
+}
+
+impl<K, S> Endpoint<K, S> {
+    /// Create a new [`Endpoint`] identified by the given key,
+    /// serving requests using the given service, with a weight of `1`.
+    pub const fn new(key: K, service: S) -> Self {
+        Self {
+            key,
+            service,
+            weight: 1,
+        }
+    }
+
+    /// Set the weight of this [`Endpoint`], used by weighted strategies.
+    ///
+    /// A weight of `0` is treated as a weight of `1`.
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight.max(1);
+        self
+    }
+
+    /// The key which identifies this [`Endpoint`].
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The service used to serve requests sent to this [`Endpoint`].
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+
+    /// The weight of this [`Endpoint`].
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    pub(super) fn into_parts(self) -> (K, S, u32) {
+        (self.key, self.service, self.weight)
+    }
+}
+
+/// Discovers the set of [`Endpoint`]s a [`Balance`] service spreads its requests over.
+///
+/// The full set of endpoints is returned on each call, such that endpoints
+/// which are no longer returned are removed. Endpoints are identified by their key,
+/// such that the state kept for them by the [`Balance`] service survives rediscovery.
+///
+/// [`Balance`]: super::Balance
+pub trait Discover: Send + Sync + 'static {
+    /// The key which identifies an [`Endpoint`].
+    type Key: Clone + Eq + Hash + fmt::Debug + Send + Sync + 'static;
+    /// The service used to serve requests sent to an [`Endpoint`].
+    type Service: Send + Sync + 'static;
+    /// The error returned in case discovery failed.
+    type Error: Into<BoxError> + Send + 'static;
+
+    /// Discover the current set of [`Endpoint`]s.
+    fn discover(
+        &self,
+    ) -> impl Future<Output = Result<Vec<Endpoint<Self::Key, Self::Service>>, Self::Error>> + Send + '_;
+}
+
+impl<D: Discover> Discover for Arc<D> {
+    type Key = D::Key;
+    type Service = D::Service;
+    type Error = D::Error;
+
+    fn discover(
+        &self,
+    ) -> impl Future<Output = Result<Vec<Endpoint<Self::Key, Self::Service>>, Self::Error>> + Send + '_
+    {
+        (**self).discover()
+    }
+}
+
+/// A [`Discover`] implementation returning a static list of [`Endpoint`]s.
+pub struct StaticDiscovery<K, S> {
+    endpoints: Vec<Endpoint<K, S>>,
+}
+
+impl<K: fmt::Debug, S: fmt::Debug> fmt::Debug for StaticDiscovery<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticDiscovery")
+            .field("endpoints", &self.endpoints)
+            .finish()
+    }
+}
+
+impl<K: Clone, S: Clone> Clone for StaticDiscovery<K, S> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: self.endpoints.clone(),
+        }
+    }
+}
+
+impl<K, S> StaticDiscovery<K, S> {
+    /// Create a new [`StaticDiscovery`] for the given [`Endpoint`]s.
+    pub fn new(endpoints: impl IntoIterator<Item = Endpoint<K, S>>) -> Self {
+        Self {
+            endpoints: endpoints.into_iter().collect(),
+        }
+    }
+}
+
+impl<K, S> FromIterator<Endpoint<K, S>> for StaticDiscovery<K, S> {
+    fn from_iter<T: IntoIterator<Item = Endpoint<K, S>>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl<K, S> Discover for StaticDiscovery<K, S>
+where
+    K: Clone + Eq + Hash + fmt::Debug + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    type Key = K;
+    type Service = S;
+    type Error = std::convert::Infallible;
+
+    async fn discover(&self) -> Result<Vec<Endpoint<K, S>>, Self::Error> {
+        Ok(self.endpoints.clone())
+    }
+}
+
+/// A [`Discover`] implementation reading its [`Endpoint`]s from a file.
+///
+/// Each non-empty line which does not start with `#` defines an endpoint
+/// as its target, optionally followed by whitespace and its weight,
+/// e.g. `10.0.0.1:8080 3`. The target is used as the key of the endpoint,
+/// and passed to the given function to create the service of the endpoint.
+///
+/// The file is read again on each discovery, such that endpoints
+/// can be added or removed without restarting the service.
+/// The services of endpoints which were already discovered are reused,
+/// such that services are only created for new endpoints.
+///
+/// Cloning a [`FileDiscovery`] shares the services of its endpoints.
+pub struct FileDiscovery<F, S> {
+    path: PathBuf,
+    make_service: F,
+    services: Arc<Mutex<HashMap<String, S>>>,
+}
+
+impl<F, S> fmt::Debug for FileDiscovery<F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileDiscovery")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl<F: Clone, S> Clone for FileDiscovery<F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            make_service: self.make_service.clone(),
+            services: self.services.clone(),
+        }
+    }
+}
+
+impl<F, S> FileDiscovery<F, S> {
+    /// Create a new [`FileDiscovery`] reading the file at the given path,
+    /// creating the service of each endpoint using the given function.
+    pub fn new<E>(path: impl Into<PathBuf>, make_service: F) -> Self
+    where
+        F: Fn(&str) -> Result<S, E>,
+    {
+        Self {
+            path: path.into(),
+            make_service,
+            services: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The path of the file read by this [`FileDiscovery`].
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl<F, S, E> Discover for FileDiscovery<F, S>
+where
+    F: Fn(&str) -> Result<S, E> + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+    E: Into<BoxError> + Send + 'static,
+{
+    type Key = String;
+    type Service = S;
+    type Error = OpaqueError;
+
+    async fn discover(&self) -> Result<Vec<Endpoint<String, S>>, Self::Error> {
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .context("FileDiscovery: read endpoints file")?;
+        let endpoints = parse_endpoints(&content)?;
+
+        let mut services = self.services.lock();
+        let mut discovered = HashMap::with_capacity(endpoints.len());
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(target, weight)| {
+                let service = match services.get(target) {
+                    Some(service) => service.clone(),
+                    None => (self.make_service)(target).map_err(|err| {
+                        OpaqueError::from_boxed(err.into())
+                            .context("FileDiscovery: create endpoint service")
+                    })?,
+                };
+                discovered.insert(target.to_owned(), service.clone());
+                Ok(Endpoint::new(target.to_owned(), service).with_weight(weight))
+            })
+            .collect::<Result<_, OpaqueError>>()?;
+        // drop the services of the endpoints which were removed
+        *services = discovered;
+        Ok(endpoints)
+    }
+}
+
+fn parse_endpoints(content: &str) -> Result<Vec<(&str, u32)>, OpaqueError> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let target = parts.next().unwrap_or_default();
+            let weight = match parts.next() {
+                Some(weight) => weight.parse().map_err(|_| {
+                    OpaqueError::from_display(format!(
+                        "FileDiscovery: invalid weight for endpoint {target}: {weight}"
+                    ))
+                })?,
+                None => 1,
+            };
+            if parts.next().is_some() {
+                return Err(OpaqueError::from_display(format!(
+                    "FileDiscovery: invalid endpoint line: {line}"
+                )));
+            }
+            Ok((target, weight))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoints() {
+        let content = "
+            # upstreams
+            10.0.0.1:8080
+            10.0.0.2:8080 3
+
+            example.com:80  2
+        ";
+        assert_eq!(
+            parse_endpoints(content).unwrap(),
+            vec![
+                ("10.0.0.1:8080", 1),
+                ("10.0.0.2:8080", 3),
+                ("example.com:80", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_endpoints_invalid() {
+        assert!(parse_endpoints("10.0.0.1:8080 heavy").is_err());
+        assert!(parse_endpoints("10.0.0.1:8080 1 2").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_discovery() {
+        let path =
+            std::env::temp_dir().join(format!("rama-file-discovery-{}.txt", std::process::id()));
+        tokio::fs::write(&path, "a 2\nb\n").await.unwrap();
+
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let discovery = FileDiscovery::new(&path, {
+            let created = created.clone();
+            move |target: &str| {
+                created.lock().push(target.to_owned());
+                Ok::<_, OpaqueError>(target.to_uppercase())
+            }
+        });
+        let endpoints = discovery.discover().await.unwrap();
+
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[0].key(), "a");
+        assert_eq!(endpoints[0].service(), "A");
+        assert_eq!(endpoints[0].weight(), 2);
+        assert_eq!(endpoints[1].key(), "b");
+        assert_eq!(endpoints[1].weight(), 1);
+
+        // services are only created for new endpoints
+        tokio::fs::write(
+            &path, "b
+c
+",
+        )
+        .await
+        .unwrap();
+        let endpoints = discovery.discover().await.unwrap();
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[1].service(), "C");
+        tokio::fs::write(
+            &path, "a
+b
+c
+",
+        )
+        .await
+        .unwrap();
+        discovery.discover().await.unwrap();
+        let _ = tokio::fs::remove_file(&path).await;
+
+        assert_eq!(created.lock().as_slice(), ["a", "b", "c", "a"]);
+    }
+}
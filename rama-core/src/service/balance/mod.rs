@@ -0,0 +1,782 @@
+//! Service that spreads requests over a dynamic set of endpoints.
+//!
+//! The endpoints are found using a [`Discover`] implementation,
+//! such as the [`StaticDiscovery`] or [`FileDiscovery`]. Endpoints are
+//! rediscovered periodically, at which point they are checked using
+//! a [`ReadinessCheck`], ejecting those that are not ready
+//! until they are found to be ready again.
+//!
+//! Requests are spread over the ready endpoints using a [`BalanceStrategy`].
+//!
+//! See [`Balance`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rama_core::service::balance::{Balance, BalanceStrategy, Endpoint, StaticDiscovery};
+//! use rama_core::service::service_fn;
+//! use rama_core::{Context, Service};
+//! # use std::convert::Infallible;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//!
+//! let upstream =
+//!     |name: &'static str| service_fn(move || async move { Ok::<_, Infallible>(name) });
+//!
+//! let balance = Balance::new(StaticDiscovery::new([
+//!     Endpoint::new("a", upstream("a")),
+//!     Endpoint::new("b", upstream("b")),
+//! ]))
+//! .with_strategy(BalanceStrategy::RoundRobin);
+//!
+//! let ctx: Context<()> = Context::default();
+//! assert_eq!(balance.serve(ctx.clone(), ()).await.unwrap(), "a");
+//! assert_eq!(balance.serve(ctx.clone(), ()).await.unwrap(), "b");
+//! assert_eq!(balance.serve(ctx, ()).await.unwrap(), "a");
+//! # }
+//! ```
+
+use crate::error::BoxError;
+use crate::{Context, Service};
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{fmt, time::Duration};
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+mod discover;
+#[doc(inline)]
+pub use discover::{Discover, Endpoint, FileDiscovery, StaticDiscovery};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The strategy used by a [`Balance`] service to pick
+/// the endpoint to which a request is sent.
+pub enum BalanceStrategy {
+    /// Pick the ready endpoints in turn.
+    #[default]
+    RoundRobin,
+    /// Pick the ready endpoints in turn, proportional to their weight,
+    /// using smooth weighted round-robin.
+    Weighted,
+    /// Pick the ready endpoint with the least outstanding requests.
+    LeastOutstanding,
+    /// Pick the endpoint with the least outstanding requests
+    /// out of two randomly chosen ready endpoints.
+    PowerOfTwoChoices,
+}
+
+/// Checks whether an [`Endpoint`] is ready to serve requests.
+///
+/// Used by a [`Balance`] service to eject endpoints which are not ready,
+/// e.g. by requesting the readiness endpoint of a k8s health service.
+pub trait ReadinessCheck<K, S>: Send + Sync + 'static {
+    /// Returns `true` if the endpoint with the given key and service is ready.
+    fn is_ready(&self, key: &K, service: &S) -> impl Future<Output = bool> + Send;
+}
+
+/// Considers all endpoints as ready.
+impl<K: Sync, S: Sync> ReadinessCheck<K, S> for () {
+    async fn is_ready(&self, _key: &K, _service: &S) -> bool {
+        true
+    }
+}
+
+impl<K, S, R: ReadinessCheck<K, S>> ReadinessCheck<K, S> for Arc<R> {
+    fn is_ready(&self, key: &K, service: &S) -> impl Future<Output = bool> + Send {
+        (**self).is_ready(key, service)
+    }
+}
+
+rama_utils::macros::error::static_str_error! {
+    #[doc = "no ready endpoint available to serve the request"]
+    pub struct NoReadyEndpoint;
+}
+
+struct EndpointState<K, S> {
+    key: K,
+    service: S,
+    weight: u32,
+    outstanding: Arc<AtomicUsize>,
+}
+
+struct Snapshot<K, S> {
+    endpoints: Vec<Arc<EndpointState<K, S>>>,
+    /// current weights of smooth weighted round-robin
+    current_weights: Mutex<Vec<i64>>,
+}
+
+impl<K, S> Snapshot<K, S> {
+    fn new(endpoints: Vec<Arc<EndpointState<K, S>>>) -> Self {
+        let current_weights = Mutex::new(vec![0; endpoints.len()]);
+        Self {
+            endpoints,
+            current_weights,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RefreshState {
+    /// when the endpoints were last discovered, successfully or not
+    last_attempt: Option<Instant>,
+    /// amount of consecutive failed discoveries
+    failures: u32,
+    /// whether the endpoints were discovered successfully at least once
+    discovered: bool,
+}
+
+struct BalanceState<K, S> {
+    snapshot: RwLock<Arc<Snapshot<K, S>>>,
+    refresh: Mutex<RefreshState>,
+    refreshing: AtomicBool,
+    /// held while awaiting the initial discovery, such that it is shared by concurrent requests
+    initial_refresh: tokio::sync::Mutex<()>,
+    cursor: AtomicUsize,
+}
+
+/// A [`Service`] which spreads requests over the
+/// ready [`Endpoint`]s found by a [`Discover`] implementation.
+///
+/// Endpoints are discovered at the first request, which awaits the initial discovery,
+/// shared by all requests arriving while it is in progress.
+/// Afterwards endpoints are rediscovered in the background at the first request after
+/// the refresh interval elapsed, while requests keep being served by the current endpoints.
+/// Use [`Balance::refresh`] to trigger this manually. In case discovery fails the previous
+/// endpoints are kept, and discovery is retried after the retry interval,
+/// doubled for each consecutive failure up to the refresh interval.
+///
+/// The readiness of the discovered endpoints is checked concurrently.
+///
+/// Requests are rejected with a [`NoReadyEndpoint`] error
+/// in case no ready endpoint is available.
+///
+/// Cloning a [`Balance`] service shares its endpoints.
+///
+/// See the [module docs](self) for more information.
+pub struct Balance<D: Discover, R = ()> {
+    discover: Arc<D>,
+    readiness: Arc<R>,
+    strategy: BalanceStrategy,
+    refresh_interval: Duration,
+    retry_interval: Duration,
+    state: Arc<BalanceState<D::Key, D::Service>>,
+}
+
+impl<D, R> fmt::Debug for Balance<D, R>
+where
+    D: Discover + fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Balance")
+            .field("discover", &self.discover)
+            .field("readiness", &self.readiness)
+            .field("strategy", &self.strategy)
+            .field("refresh_interval", &self.refresh_interval)
+            .field("retry_interval", &self.retry_interval)
+            .field("endpoints", &self.endpoints())
+            .finish()
+    }
+}
+
+impl<D: Discover, R> Clone for Balance<D, R> {
+    fn clone(&self) -> Self {
+        Self {
+            discover: self.discover.clone(),
+            readiness: self.readiness.clone(),
+            strategy: self.strategy,
+            refresh_interval: self.refresh_interval,
+            retry_interval: self.retry_interval,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<D: Discover> Balance<D> {
+    /// Default interval after which endpoints are rediscovered.
+    pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+    /// Default interval after which a failed discovery is retried.
+    pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Create a new [`Balance`] service using the given [`Discover`] implementation,
+    /// spreading requests using the [`BalanceStrategy::RoundRobin`] strategy.
+    pub fn new(discover: D) -> Self {
+        Self {
+            discover: Arc::new(discover),
+            readiness: Arc::new(()),
+            strategy: BalanceStrategy::default(),
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            retry_interval: Self::DEFAULT_RETRY_INTERVAL,
+            state: Arc::new(BalanceState {
+                snapshot: RwLock::new(Arc::new(Snapshot::new(Vec::new()))),
+                refresh: Mutex::new(RefreshState::default()),
+                refreshing: AtomicBool::new(false),
+                initial_refresh: tokio::sync::Mutex::new(()),
+                cursor: AtomicUsize::new(0),
+            }),
+        }
+    }
+}
+
+impl<D: Discover, R> Balance<D, R> {
+    /// Set the [`BalanceStrategy`] used to pick the endpoint of a request.
+    pub fn set_strategy(&mut self, strategy: BalanceStrategy) -> &mut Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Same as [`Self::set_strategy`] but consuming self.
+    pub fn with_strategy(mut self, strategy: BalanceStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set the interval after which endpoints are rediscovered.
+    pub fn set_refresh_interval(&mut self, interval: Duration) -> &mut Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Same as [`Self::set_refresh_interval`] but consuming self.
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Set the interval after which a failed discovery is retried,
+    /// doubled for each consecutive failure up to the refresh interval.
+    pub fn set_retry_interval(&mut self, interval: Duration) -> &mut Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Same as [`Self::set_retry_interval`] but consuming self.
+    pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Check discovered endpoints using the given [`ReadinessCheck`],
+    /// ejecting those which are not ready.
+    pub fn with_readiness_check<R2>(self, readiness: R2) -> Balance<D, R2> {
+        Balance {
+            discover: self.discover,
+            readiness: Arc::new(readiness),
+            strategy: self.strategy,
+            refresh_interval: self.refresh_interval,
+            retry_interval: self.retry_interval,
+            state: self.state,
+        }
+    }
+
+    /// The keys of the ready endpoints currently in use.
+    pub fn endpoints(&self) -> Vec<D::Key> {
+        self.state
+            .snapshot
+            .read()
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.key.clone())
+            .collect()
+    }
+
+    /// Discover the endpoints and check their readiness,
+    /// replacing the endpoints currently in use.
+    ///
+    /// The endpoints currently in use are kept in case discovery failed.
+    pub async fn refresh(&self) -> Result<(), BoxError>
+    where
+        R: ReadinessCheck<D::Key, D::Service>,
+    {
+        let result = self
+            .discover_ready_endpoints()
+            .await
+            .map(|endpoints| *self.state.snapshot.write() = Arc::new(Snapshot::new(endpoints)));
+
+        let mut refresh = self.state.refresh.lock();
+        refresh.last_attempt = Some(Instant::now());
+        if result.is_ok() {
+            refresh.failures = 0;
+            refresh.discovered = true;
+        } else {
+            refresh.failures = refresh.failures.saturating_add(1);
+        }
+        result
+    }
+
+    async fn discover_ready_endpoints(
+        &self,
+    ) -> Result<Vec<Arc<EndpointState<D::Key, D::Service>>>, BoxError>
+    where
+        R: ReadinessCheck<D::Key, D::Service>,
+    {
+        let discovered = self.discover.discover().await.map_err(Into::into)?;
+        let previous = self.state.snapshot.read().clone();
+
+        let mut checks = JoinSet::new();
+        for (index, endpoint) in discovered.into_iter().enumerate() {
+            let (key, service, weight) = endpoint.into_parts();
+            // keep tracking the requests still in flight for known endpoints
+            let outstanding = previous
+                .endpoints
+                .iter()
+                .find(|endpoint| endpoint.key == key)
+                .map(|endpoint| endpoint.outstanding.clone())
+                .unwrap_or_default();
+            let endpoint = Arc::new(EndpointState {
+                key,
+                service,
+                weight,
+                outstanding,
+            });
+            let readiness = self.readiness.clone();
+            checks.spawn(async move {
+                let ready = readiness.is_ready(&endpoint.key, &endpoint.service).await;
+                (index, ready, endpoint)
+            });
+        }
+
+        let mut endpoints = Vec::with_capacity(checks.len());
+        while let Some(result) = checks.join_next().await {
+            let (index, ready, endpoint) = result?;
+            if ready {
+                endpoints.push((index, endpoint));
+            } else {
+                tracing::debug!(key = ?endpoint.key, "balance: eject endpoint which is not ready");
+            }
+        }
+        // keep the order of discovery, as some strategies depend on it
+        endpoints.sort_unstable_by_key(|(index, _)| *index);
+        Ok(endpoints
+            .into_iter()
+            .map(|(_, endpoint)| endpoint)
+            .collect())
+    }
+
+    /// The interval after which the endpoints are to be rediscovered,
+    /// given the amount of consecutive failed discoveries.
+    fn refresh_interval(&self, failures: u32) -> Duration {
+        match failures {
+            0 => self.refresh_interval,
+            failures => self
+                .retry_interval
+                .saturating_mul(1 << (failures - 1).min(16))
+                .min(self.refresh_interval),
+        }
+    }
+
+    fn refresh_due(&self) -> RefreshDue<D::Key, D::Service> {
+        {
+            let refresh = self.state.refresh.lock();
+            if refresh.last_attempt.is_some_and(|last_attempt| {
+                last_attempt.elapsed() < self.refresh_interval(refresh.failures)
+            }) {
+                return RefreshDue::No;
+            }
+            // nothing to serve requests with until the first discovery succeeded
+            if !refresh.discovered {
+                return RefreshDue::Initial;
+            }
+        }
+        match self.state.refreshing.compare_exchange(
+            false,
+            true,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => RefreshDue::Periodic(RefreshingGuard(self.state.clone())),
+            // another request is already refreshing the endpoints
+            Err(_) => RefreshDue::No,
+        }
+    }
+
+    fn pick(
+        &self,
+        snapshot: &Snapshot<D::Key, D::Service>,
+    ) -> Option<Arc<EndpointState<D::Key, D::Service>>> {
+        let endpoints = &snapshot.endpoints;
+        let index = match (endpoints.len(), self.strategy) {
+            (0, _) => return None,
+            (1, _) => 0,
+            (len, BalanceStrategy::RoundRobin) => {
+                self.state.cursor.fetch_add(1, Ordering::Relaxed) % len
+            }
+            (_, BalanceStrategy::Weighted) => {
+                let mut current_weights = snapshot.current_weights.lock();
+                let mut total = 0;
+                let mut best = 0;
+                for (index, endpoint) in endpoints.iter().enumerate() {
+                    let weight = i64::from(endpoint.weight);
+                    current_weights[index] += weight;
+                    total += weight;
+                    if current_weights[index] > current_weights[best] {
+                        best = index;
+                    }
+                }
+                current_weights[best] -= total;
+                best
+            }
+            (len, BalanceStrategy::LeastOutstanding) => {
+                // start at a rotating offset such that ties are spread
+                let offset = self.state.cursor.fetch_add(1, Ordering::Relaxed);
+                (0..len)
+                    .map(|i| (offset + i) % len)
+                    .min_by_key(|&index| endpoints[index].outstanding.load(Ordering::Relaxed))
+                    .unwrap_or_default()
+            }
+            (len, BalanceStrategy::PowerOfTwoChoices) => {
+                let a = rand::random_range(0..len);
+                let b = (a + rand::random_range(1..len)) % len;
+                if endpoints[b].outstanding.load(Ordering::Relaxed)
+                    < endpoints[a].outstanding.load(Ordering::Relaxed)
+                {
+                    b
+                } else {
+                    a
+                }
+            }
+        };
+        Some(endpoints[index].clone())
+    }
+}
+
+enum RefreshDue<K, S> {
+    No,
+    Initial,
+    Periodic(RefreshingGuard<K, S>),
+}
+
+/// Marks the endpoints as being refreshed, for as long as it is not dropped.
+struct RefreshingGuard<K, S>(Arc<BalanceState<K, S>>);
+
+impl<K, S> Drop for RefreshingGuard<K, S> {
+    fn drop(&mut self) {
+        self.0.refreshing.store(false, Ordering::Release);
+    }
+}
+
+/// Tracks a request in flight, for as long as it is not dropped.
+struct OutstandingGuard(Arc<AtomicUsize>);
+
+impl OutstandingGuard {
+    fn new(outstanding: Arc<AtomicUsize>) -> Self {
+        outstanding.fetch_add(1, Ordering::AcqRel);
+        Self(outstanding)
+    }
+}
+
+impl Drop for OutstandingGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<D, R, State, Request> Service<State, Request> for Balance<D, R>
+where
+    D: Discover<Service: Service<State, Request, Error: Into<BoxError>>>,
+    R: ReadinessCheck<D::Key, D::Service>,
+    State: Clone + Send + Sync + 'static,
+    Request: Send + 'static,
+{
+    type Response = <D::Service as Service<State, Request>>::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        match self.refresh_due() {
+            RefreshDue::No => (),
+            RefreshDue::Initial => {
+                let _guard = self.state.initial_refresh.lock().await;
+                // concurrent requests share the discovery of the request which got the lock first
+                if matches!(self.refresh_due(), RefreshDue::Initial) {
+                    if let Err(err) = self.refresh().await {
+                        tracing::debug!(error = %err, "balance: failed to discover endpoints");
+                    }
+                }
+            }
+            RefreshDue::Periodic(guard) => {
+                // refresh in the background, serving requests using the current endpoints meanwhile
+                let balance = self.clone();
+                ctx.executor().spawn_task(async move {
+                    let _guard = guard;
+                    if let Err(err) = balance.refresh().await {
+                        tracing::debug!(error = %err, "balance: failed to refresh endpoints");
+                    }
+                });
+            }
+        }
+
+        let snapshot = self.state.snapshot.read().clone();
+        let endpoint = self.pick(&snapshot).ok_or(NoReadyEndpoint)?;
+        drop(snapshot);
+
+        let _guard = OutstandingGuard::new(endpoint.outstanding.clone());
+        endpoint.service.serve(ctx, req).await.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    #[derive(Debug, Clone)]
+    struct Named(&'static str);
+
+    impl Service<(), ()> for Named {
+        type Response = &'static str;
+        type Error = Infallible;
+
+        async fn serve(&self, _ctx: Context<()>, _req: ()) -> Result<Self::Response, Self::Error> {
+            Ok(self.0)
+        }
+    }
+
+    fn endpoint(key: &'static str, weight: u32) -> Endpoint<&'static str, Named> {
+        Endpoint::new(key, Named(key)).with_weight(weight)
+    }
+
+    async fn serve_n<D, R>(balance: &Balance<D, R>, n: usize) -> Vec<&'static str>
+    where
+        D: Discover<Service = Named>,
+        R: ReadinessCheck<D::Key, Named>,
+    {
+        let mut responses = Vec::with_capacity(n);
+        for _ in 0..n {
+            responses.push(balance.serve(Context::default(), ()).await.unwrap());
+        }
+        responses
+    }
+
+    #[tokio::test]
+    async fn test_balance_round_robin() {
+        let balance = Balance::new(StaticDiscovery::new([
+            endpoint("a", 1),
+            endpoint("b", 1),
+            endpoint("c", 1),
+        ]));
+        assert_eq!(serve_n(&balance, 6).await, ["a", "b", "c", "a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_balance_weighted() {
+        let balance = Balance::new(StaticDiscovery::new([
+            endpoint("a", 5),
+            endpoint("b", 1),
+            endpoint("c", 1),
+        ]))
+        .with_strategy(BalanceStrategy::Weighted);
+        assert_eq!(
+            serve_n(&balance, 7).await,
+            ["a", "a", "b", "a", "c", "a", "a"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_balance_least_outstanding() {
+        let balance = Balance::new(StaticDiscovery::new([endpoint("a", 1), endpoint("b", 1)]))
+            .with_strategy(BalanceStrategy::LeastOutstanding);
+        balance.refresh().await.unwrap();
+
+        let snapshot = balance.state.snapshot.read().clone();
+        let _busy = OutstandingGuard::new(snapshot.endpoints[0].outstanding.clone());
+        assert_eq!(serve_n(&balance, 3).await, ["b", "b", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_balance_power_of_two_choices() {
+        let balance = Balance::new(StaticDiscovery::new([endpoint("a", 1), endpoint("b", 1)]))
+            .with_strategy(BalanceStrategy::PowerOfTwoChoices);
+        balance.refresh().await.unwrap();
+
+        let snapshot = balance.state.snapshot.read().clone();
+        let _busy = OutstandingGuard::new(snapshot.endpoints[1].outstanding.clone());
+        assert_eq!(serve_n(&balance, 3).await, ["a", "a", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_balance_keeps_outstanding_on_refresh() {
+        let balance = Balance::new(StaticDiscovery::new([endpoint("a", 1)]));
+        balance.refresh().await.unwrap();
+
+        let outstanding = balance.state.snapshot.read().endpoints[0]
+            .outstanding
+            .clone();
+        let _busy = OutstandingGuard::new(outstanding.clone());
+        balance.refresh().await.unwrap();
+
+        let snapshot = balance.state.snapshot.read().clone();
+        assert!(Arc::ptr_eq(
+            &snapshot.endpoints[0].outstanding,
+            &outstanding
+        ));
+        assert_eq!(outstanding.load(Ordering::Acquire), 1);
+    }
+
+    #[derive(Debug)]
+    struct NotReady(&'static str);
+
+    impl<S: Sync> ReadinessCheck<&'static str, S> for NotReady {
+        async fn is_ready(&self, key: &&'static str, _service: &S) -> bool {
+            *key != self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_balance_readiness_ejection() {
+        let balance = Balance::new(StaticDiscovery::new([endpoint("a", 1), endpoint("b", 1)]))
+            .with_readiness_check(NotReady("a"));
+        assert_eq!(serve_n(&balance, 2).await, ["b", "b"]);
+        assert_eq!(balance.endpoints(), ["b"]);
+    }
+
+    #[derive(Debug)]
+    struct BarrierReady(tokio::sync::Barrier);
+
+    impl<S: Sync> ReadinessCheck<&'static str, S> for BarrierReady {
+        async fn is_ready(&self, _key: &&'static str, _service: &S) -> bool {
+            // only completes in case all endpoints are checked concurrently
+            self.0.wait().await;
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_balance_concurrent_readiness_checks() {
+        let balance = Balance::new(StaticDiscovery::new([
+            endpoint("a", 1),
+            endpoint("b", 1),
+            endpoint("c", 1),
+        ]))
+        .with_readiness_check(BarrierReady(tokio::sync::Barrier::new(3)));
+        tokio::time::timeout(Duration::from_secs(5), balance.refresh())
+            .await
+            .expect("concurrent readiness checks")
+            .unwrap();
+        assert_eq!(balance.endpoints(), ["a", "b", "c"]);
+    }
+
+    #[derive(Debug, Default)]
+    struct BlockingReady(AtomicBool);
+
+    impl<S: Sync> ReadinessCheck<&'static str, S> for BlockingReady {
+        async fn is_ready(&self, _key: &&'static str, _service: &S) -> bool {
+            if self.0.load(Ordering::Acquire) {
+                std::future::pending::<()>().await;
+            }
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_balance_periodic_refresh_in_background() {
+        let readiness = Arc::new(BlockingReady::default());
+        let balance = Balance::new(StaticDiscovery::new([endpoint("a", 1)]))
+            .with_refresh_interval(Duration::ZERO)
+            .with_readiness_check(readiness.clone());
+        assert_eq!(serve_n(&balance, 1).await, ["a"]);
+
+        // requests are served while the endpoints are being refreshed
+        readiness.0.store(true, Ordering::Release);
+        let responses = tokio::time::timeout(Duration::from_secs(5), serve_n(&balance, 3))
+            .await
+            .expect("requests not blocked by refresh");
+        assert_eq!(responses, ["a", "a", "a"]);
+        assert!(balance.state.refreshing.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_balance_refreshing_guard() {
+        let balance = Balance::new(StaticDiscovery::new([endpoint("a", 1)]));
+        *balance.state.refresh.lock() = RefreshState {
+            last_attempt: Some(Instant::now() - Duration::from_secs(60)),
+            failures: 0,
+            discovered: true,
+        };
+
+        let due = balance.refresh_due();
+        assert!(matches!(due, RefreshDue::Periodic(_)));
+        assert!(matches!(balance.refresh_due(), RefreshDue::No));
+
+        // a dropped refresh no longer blocks future refreshes
+        drop(due);
+        assert!(matches!(balance.refresh_due(), RefreshDue::Periodic(_)));
+    }
+
+    #[derive(Debug, Default)]
+    struct FlakyDiscovery {
+        calls: AtomicUsize,
+        failures: usize,
+    }
+
+    impl Discover for FlakyDiscovery {
+        type Key = &'static str;
+        type Service = Named;
+        type Error = BoxError;
+
+        async fn discover(&self) -> Result<Vec<Endpoint<&'static str, Named>>, Self::Error> {
+            let call = self.calls.fetch_add(1, Ordering::AcqRel);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if call < self.failures {
+                Err("discovery failed".into())
+            } else {
+                Ok(vec![endpoint("a", 1)])
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_balance_initial_refresh_shared() {
+        let balance = Balance::new(FlakyDiscovery::default());
+
+        let mut requests = JoinSet::new();
+        for _ in 0..8 {
+            let balance = balance.clone();
+            requests.spawn(async move { balance.serve(Context::default(), ()).await.unwrap() });
+        }
+        while let Some(response) = requests.join_next().await {
+            assert_eq!(response.unwrap(), "a");
+        }
+        assert_eq!(balance.discover.calls.load(Ordering::Acquire), 1);
+    }
+
+    #[tokio::test]
+    async fn test_balance_retry_failed_discovery() {
+        let balance = Balance::new(FlakyDiscovery {
+            calls: AtomicUsize::new(0),
+            failures: 2,
+        })
+        .with_retry_interval(Duration::from_millis(100));
+
+        let err = balance.serve(Context::default(), ()).await.unwrap_err();
+        assert!(err.downcast_ref::<NoReadyEndpoint>().is_some());
+        // failed discoveries are not retried before the retry interval elapsed
+        assert!(balance.serve(Context::default(), ()).await.is_err());
+        assert_eq!(balance.discover.calls.load(Ordering::Acquire), 1);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(balance.serve(Context::default(), ()).await.is_err());
+        assert_eq!(balance.discover.calls.load(Ordering::Acquire), 2);
+
+        // the retry interval doubles for consecutive failures
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(balance.serve(Context::default(), ()).await.is_err());
+        assert_eq!(balance.discover.calls.load(Ordering::Acquire), 2);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(serve_n(&balance, 1).await, ["a"]);
+        assert_eq!(balance.discover.calls.load(Ordering::Acquire), 3);
+    }
+
+    #[tokio::test]
+    async fn test_balance_no_ready_endpoint() {
+        let balance = Balance::new(StaticDiscovery::<&'static str, Named>::new([]));
+        let err = balance.serve(Context::default(), ()).await.unwrap_err();
+        assert!(err.downcast_ref::<NoReadyEndpoint>().is_some());
+    }
+}
@@ -8,3 +8,7 @@ pub use svc::{BoxService, Service};
 
 pub mod handler;
 pub use handler::service_fn;
+
+pub mod balance;
+#[doc(inline)]
+pub use balance::Balance;
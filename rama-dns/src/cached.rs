@@ -0,0 +1,628 @@
+use crate::{DnsName, DnsRecordType, DnsRecords, DnsResolver, MxRecord, SrvRecord, SvcbRecord};
+use parking_lot::Mutex;
+use rama_core::error::BoxError;
+use rama_net::address::Domain;
+use rama_utils::macros::error::static_str_error;
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::OnceCell;
+
+const DEFAULT_MIN_TTL: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_ENTRIES: usize = 4096;
+
+static_str_error! {
+    #[doc = "no dns records found"]
+    pub struct DnsRecordsNotFoundError;
+}
+
+/// A [`DnsResolver`] which caches the records resolved by the inner [`DnsResolver`].
+///
+/// Records are cached for the time-to-live (TTL) reported by
+/// [`DnsResolver::lookup_with_ttl`], clamped between the configured minimum
+/// and maximum TTL. Resolvers that do not report a TTL (e.g. [`InMemoryDns`])
+/// have their records cached for the default TTL instead.
+///
+/// Names without records of the requested type (e.g. `NXDOMAIN`) are cached as well,
+/// using the negative TTL reported by the inner resolver or the default negative TTL.
+/// Lookups for such names fail with a [`DnsRecordsNotFoundError`].
+///
+/// Optionally expired records can be served for a while longer in case
+/// the inner resolver fails to refresh them, see [`CachedDnsResolver::with_serve_stale`].
+///
+/// Concurrent lookups of the same uncached records are coalesced
+/// into a single lookup using the inner resolver.
+///
+/// Cloning a [`CachedDnsResolver`] shares the cache and its [`DnsCacheMetrics`].
+///
+/// [`InMemoryDns`]: crate::InMemoryDns
+#[derive(Debug, Clone)]
+pub struct CachedDnsResolver<R> {
+    inner: R,
+    cache: Arc<Mutex<HashMap<CacheKey, CacheEntry>>>,
+    in_flight: Arc<Mutex<HashMap<CacheKey, Arc<InFlightLookup>>>>,
+    metrics: Arc<AtomicMetrics>,
+    min_ttl: Duration,
+    max_ttl: Duration,
+    default_ttl: Duration,
+    negative_ttl: Duration,
+    serve_stale: Option<Duration>,
+    max_entries: usize,
+}
+
+type CacheKey = (DnsName, DnsRecordType);
+
+/// The result of a lookup, shared with the concurrent lookups of the same key.
+///
+/// Errors are shared as their message, as they cannot be cloned.
+type InFlightLookup = OnceCell<Result<(DnsRecords, Duration), String>>;
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    records: DnsRecords,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct AtomicMetrics {
+    hits: AtomicU64,
+    negative_hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// A snapshot of the metrics of a [`CachedDnsResolver`].
+///
+/// Each lookup is counted exactly once, in one of its fields.
+pub struct DnsCacheMetrics {
+    /// Lookups answered by fresh cached records.
+    pub hits: u64,
+    /// Lookups answered by a fresh cached absence of records.
+    pub negative_hits: u64,
+    /// Lookups answered by expired cached records,
+    /// because the inner resolver failed to refresh them.
+    pub stale_hits: u64,
+    /// Lookups which could not be answered from the cache.
+    pub misses: u64,
+}
+
+impl<R> CachedDnsResolver<R> {
+    /// Create a new [`CachedDnsResolver`] wrapping the given [`DnsResolver`].
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            cache: Default::default(),
+            in_flight: Default::default(),
+            metrics: Default::default(),
+            min_ttl: DEFAULT_MIN_TTL,
+            max_ttl: DEFAULT_MAX_TTL,
+            default_ttl: DEFAULT_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            serve_stale: None,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+
+    /// Set the minimum TTL records are cached for (default: 1 second).
+    pub fn set_min_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.min_ttl = ttl;
+        self
+    }
+
+    /// Replace the minimum TTL records are cached for (default: 1 second).
+    pub fn with_min_ttl(mut self, ttl: Duration) -> Self {
+        self.min_ttl = ttl;
+        self
+    }
+
+    /// Set the maximum TTL records are cached for (default: 1 day).
+    pub fn set_max_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.max_ttl = ttl;
+        self
+    }
+
+    /// Replace the maximum TTL records are cached for (default: 1 day).
+    pub fn with_max_ttl(mut self, ttl: Duration) -> Self {
+        self.max_ttl = ttl;
+        self
+    }
+
+    /// Set the TTL used for records of which the inner resolver
+    /// did not report a TTL (default: 60 seconds).
+    pub fn set_default_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Replace the TTL used for records of which the inner resolver
+    /// did not report a TTL (default: 60 seconds).
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Set the TTL used to cache the absence of records in case
+    /// the inner resolver did not report a negative TTL (default: 30 seconds).
+    pub fn set_negative_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    /// Replace the TTL used to cache the absence of records in case
+    /// the inner resolver did not report a negative TTL (default: 30 seconds).
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    /// Set the maximum amount of cached entries (default: 4096).
+    ///
+    /// New records are not cached while the cache is full of unexpired entries.
+    pub fn set_max_entries(&mut self, max_entries: usize) -> &mut Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Replace the maximum amount of cached entries (default: 4096).
+    ///
+    /// New records are not cached while the cache is full of unexpired entries.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Set the duration for which expired records can still be served
+    /// in case the inner resolver fails to refresh them (disabled by default).
+    pub fn set_serve_stale(&mut self, window: Duration) -> &mut Self {
+        self.serve_stale = Some(window);
+        self
+    }
+
+    /// Replace the duration for which expired records can still be served
+    /// in case the inner resolver fails to refresh them (disabled by default).
+    pub fn with_serve_stale(mut self, window: Duration) -> Self {
+        self.serve_stale = Some(window);
+        self
+    }
+
+    /// Replace the duration for which expired records can still be served
+    /// in case the inner resolver fails to refresh them, `None` disables it (default).
+    pub fn maybe_with_serve_stale(mut self, window: Option<Duration>) -> Self {
+        self.serve_stale = window;
+        self
+    }
+
+    /// Returns a snapshot of the metrics of this cache.
+    pub fn metrics(&self) -> DnsCacheMetrics {
+        DnsCacheMetrics {
+            hits: self.metrics.hits.load(Ordering::Relaxed),
+            negative_hits: self.metrics.negative_hits.load(Ordering::Relaxed),
+            stale_hits: self.metrics.stale_hits.load(Ordering::Relaxed),
+            misses: self.metrics.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Remove all cached records.
+    pub fn clear(&self) {
+        self.cache.lock().clear();
+    }
+
+    /// Reference to the inner [`DnsResolver`].
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    fn ttl_for(&self, records: &DnsRecords, ttl: Option<Duration>) -> Duration {
+        let ttl = ttl.unwrap_or(if records.is_empty() {
+            self.negative_ttl
+        } else {
+            self.default_ttl
+        });
+        ttl.min(self.max_ttl).max(self.min_ttl)
+    }
+
+    /// Returns the cached records of the given key in case they did not expire yet.
+    fn get_fresh(&self, key: &CacheKey) -> Option<(DnsRecords, Duration)> {
+        let now = Instant::now();
+        let entry = self
+            .cache
+            .lock()
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+            .cloned()?;
+        self.count_hit(&entry.records);
+        Some((entry.records, entry.expires_at - now))
+    }
+
+    fn count_hit(&self, records: &DnsRecords) {
+        let counter = if records.is_empty() {
+            &self.metrics.negative_hits
+        } else {
+            &self.metrics.hits
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn insert(&self, key: CacheKey, records: DnsRecords, ttl: Duration) {
+        let mut cache = self.cache.lock();
+        if cache.len() >= self.max_entries && !cache.contains_key(&key) {
+            let now = Instant::now();
+            let stale = self.serve_stale.unwrap_or_default();
+            cache.retain(|_, entry| entry.expires_at + stale > now);
+            if cache.len() >= self.max_entries {
+                tracing::trace!(name = %key.0, "dns cache is full: do not cache records");
+                return;
+            }
+        }
+        cache.insert(
+            key,
+            CacheEntry {
+                records,
+                expires_at: Instant::now() + ttl,
+            },
+        );
+    }
+}
+
+impl<R: DnsResolver<Error: Into<BoxError>>> CachedDnsResolver<R> {
+    async fn cached_lookup(
+        &self,
+        name: DnsName,
+        record_type: DnsRecordType,
+    ) -> Result<(DnsRecords, Duration), BoxError> {
+        let key = (name, record_type);
+        if let Some(fresh) = self.get_fresh(&key) {
+            return Ok(fresh);
+        }
+
+        // coalesce concurrent misses of the same key into a single lookup
+        let in_flight = self
+            .in_flight
+            .lock()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut own_result = None;
+        let shared = in_flight
+            .get_or_init(|| async {
+                let result = self.lookup(key.clone()).await;
+                let shared = match &result {
+                    Ok(records) => Ok(records.clone()),
+                    Err(err) => Err(err.to_string()),
+                };
+                own_result = Some(result);
+                shared
+            })
+            .await;
+
+        match own_result {
+            Some(result) => {
+                self.in_flight.lock().remove(&key);
+                result
+            }
+            None => match shared {
+                Ok((records, ttl)) => {
+                    // stale records are served with a zero ttl
+                    if ttl.is_zero() {
+                        self.metrics.stale_hits.fetch_add(1, Ordering::Relaxed);
+                    } else {
+                        self.count_hit(records);
+                    }
+                    Ok((records.clone(), *ttl))
+                }
+                Err(err) => {
+                    self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+                    Err(err.as_str().into())
+                }
+            },
+        }
+    }
+
+    async fn lookup(&self, key: CacheKey) -> Result<(DnsRecords, Duration), BoxError> {
+        // the records might have been cached by a lookup which just finished
+        if let Some(fresh) = self.get_fresh(&key) {
+            return Ok(fresh);
+        }
+
+        let now = Instant::now();
+        let stale = self.serve_stale.and_then(|window| {
+            self.cache
+                .lock()
+                .get(&key)
+                .filter(|entry| entry.expires_at + window > now)
+                .map(|entry| entry.records.clone())
+        });
+
+        let record_type = key.1;
+        match self.inner.lookup_with_ttl(key.0.clone(), record_type).await {
+            Ok((records, ttl)) => {
+                self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+                let ttl = self.ttl_for(&records, ttl);
+                self.insert(key, records.clone(), ttl);
+                Ok((records, ttl))
+            }
+            Err(err) => match stale {
+                Some(records) => {
+                    let err = err.into();
+                    tracing::debug!(name = %key.0, ?record_type, %err, "serve stale dns records");
+                    self.metrics.stale_hits.fetch_add(1, Ordering::Relaxed);
+                    Ok((records, Duration::ZERO))
+                }
+                None => {
+                    self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+                    Err(err.into())
+                }
+            },
+        }
+    }
+}
+
+macro_rules! cached_dns_resolver_impl {
+    ($($method:ident($name_ty:ty): $variant:ident => $output:ty),+ $(,)?) => {
+        $(
+            async fn $method(&self, name: $name_ty) -> Result<$output, Self::Error> {
+                match self.cached_lookup(name.into(), DnsRecordType::$variant).await? {
+                    (DnsRecords::$variant(records), _) if !records.is_empty() => Ok(records),
+                    (DnsRecords::$variant(_), _) => Err(DnsRecordsNotFoundError.into()),
+                    (records, _) => Err(format!(
+                        "unexpected dns records of type {:?}: expected {:?}",
+                        records.record_type(),
+                        DnsRecordType::$variant,
+                    )
+                    .into()),
+                }
+            }
+        )+
+    };
+}
+
+impl<R: DnsResolver<Error: Into<BoxError>>> DnsResolver for CachedDnsResolver<R> {
+    type Error = BoxError;
+
+    cached_dns_resolver_impl! {
+        ipv4_lookup(Domain): Ipv4 => Vec<Ipv4Addr>,
+        ipv6_lookup(Domain): Ipv6 => Vec<Ipv6Addr>,
+        txt_lookup(DnsName): Txt => Vec<Vec<u8>>,
+        srv_lookup(DnsName): Srv => Vec<SrvRecord>,
+        mx_lookup(DnsName): Mx => Vec<MxRecord>,
+        cname_lookup(DnsName): Cname => Vec<DnsName>,
+        https_lookup(DnsName): Https => Vec<SvcbRecord>,
+        svcb_lookup(DnsName): Svcb => Vec<SvcbRecord>,
+    }
+
+    async fn lookup_with_ttl(
+        &self,
+        name: DnsName,
+        record_type: DnsRecordType,
+    ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
+        let (records, ttl) = self.cached_lookup(name, record_type).await?;
+        Ok((records, Some(ttl)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+    use std::sync::atomic::AtomicBool;
+
+    #[derive(Debug, Clone, Default)]
+    struct FlakyDns {
+        dns: InMemoryDns,
+        ttl: Option<Duration>,
+        fail: Arc<AtomicBool>,
+        lookups: Arc<AtomicU64>,
+    }
+
+    impl FlakyDns {
+        fn lookups(&self) -> u64 {
+            self.lookups.load(Ordering::SeqCst)
+        }
+    }
+
+    macro_rules! flaky_dns_lookups {
+        ($($method:ident($name_ty:ty): $output:ty),+ $(,)?) => {
+            $(
+                async fn $method(&self, name: $name_ty) -> Result<$output, Self::Error> {
+                    Ok(self.dns.$method(name).await?)
+                }
+            )+
+        };
+    }
+
+    impl DnsResolver for FlakyDns {
+        type Error = BoxError;
+
+        flaky_dns_lookups! {
+            ipv4_lookup(Domain): Vec<Ipv4Addr>,
+            ipv6_lookup(Domain): Vec<Ipv6Addr>,
+            txt_lookup(DnsName): Vec<Vec<u8>>,
+            srv_lookup(DnsName): Vec<SrvRecord>,
+            mx_lookup(DnsName): Vec<MxRecord>,
+            cname_lookup(DnsName): Vec<DnsName>,
+            https_lookup(DnsName): Vec<SvcbRecord>,
+            svcb_lookup(DnsName): Vec<SvcbRecord>,
+        }
+
+        async fn lookup_with_ttl(
+            &self,
+            name: DnsName,
+            record_type: DnsRecordType,
+        ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            // give concurrent lookups the chance to run
+            tokio::task::yield_now().await;
+            if self.fail.load(Ordering::SeqCst) {
+                return Err("upstream failure".into());
+            }
+            match self.dns.lookup_with_ttl(name, record_type).await {
+                Ok((records, _)) => Ok((records, self.ttl)),
+                Err(_) => Ok((DnsRecords::empty(record_type), self.ttl)),
+            }
+        }
+    }
+
+    fn flaky_dns(ttl: Option<Duration>) -> FlakyDns {
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        );
+        FlakyDns {
+            dns,
+            ttl,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_dns_hit_miss() {
+        let inner = flaky_dns(None);
+        let dns = CachedDnsResolver::new(inner.clone());
+
+        for _ in 0..3 {
+            let ips = dns
+                .ipv4_lookup(Domain::from_static("example.com"))
+                .await
+                .unwrap();
+            assert_eq!(ips, vec![Ipv4Addr::new(127, 0, 0, 1)]);
+        }
+        assert_eq!(inner.lookups(), 1);
+        assert_eq!(
+            dns.metrics(),
+            DnsCacheMetrics {
+                hits: 2,
+                misses: 1,
+                ..Default::default()
+            }
+        );
+
+        // other record types are cached separately
+        assert!(
+            dns.ipv6_lookup(Domain::from_static("example.com"))
+                .await
+                .is_err()
+        );
+        assert_eq!(inner.lookups(), 2);
+
+        dns.clear();
+        dns.ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(inner.lookups(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cached_dns_coalesce_misses() {
+        let inner = flaky_dns(None);
+        let dns = CachedDnsResolver::new(inner.clone());
+
+        let (a, b) = tokio::join!(
+            dns.ipv4_lookup(Domain::from_static("example.com")),
+            dns.ipv4_lookup(Domain::from_static("example.com")),
+        );
+        assert_eq!(a.unwrap(), vec![Ipv4Addr::new(127, 0, 0, 1)]);
+        assert_eq!(b.unwrap(), vec![Ipv4Addr::new(127, 0, 0, 1)]);
+        assert_eq!(inner.lookups(), 1);
+        assert_eq!(
+            dns.metrics(),
+            DnsCacheMetrics {
+                hits: 1,
+                misses: 1,
+                ..Default::default()
+            }
+        );
+
+        // failed lookups are shared as well, but not cached
+        inner.fail.store(true, Ordering::SeqCst);
+        let (a, b) = tokio::join!(
+            dns.ipv6_lookup(Domain::from_static("example.com")),
+            dns.ipv6_lookup(Domain::from_static("example.com")),
+        );
+        assert!(a.is_err());
+        assert!(b.is_err());
+        assert_eq!(inner.lookups(), 2);
+        assert!(dns.in_flight.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cached_dns_negative() {
+        let inner = flaky_dns(None);
+        let dns = CachedDnsResolver::new(inner.clone());
+
+        for _ in 0..2 {
+            let err = dns
+                .ipv4_lookup(Domain::from_static("unknown.example.com"))
+                .await
+                .unwrap_err();
+            assert!(err.downcast_ref::<DnsRecordsNotFoundError>().is_some());
+        }
+        assert_eq!(inner.lookups(), 1);
+        assert_eq!(dns.metrics().negative_hits, 1);
+        assert_eq!(dns.metrics().misses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cached_dns_ttl_clamp() {
+        let inner = flaky_dns(Some(Duration::from_secs(1_000_000)));
+        let dns = CachedDnsResolver::new(inner.clone()).with_max_ttl(Duration::from_secs(10));
+        let (_, ttl) = dns
+            .lookup_with_ttl(DnsName::from_static("example.com"), DnsRecordType::Ipv4)
+            .await
+            .unwrap();
+        assert!(ttl.unwrap() <= Duration::from_secs(10));
+
+        // expired records are looked up again
+        let inner = flaky_dns(Some(Duration::ZERO));
+        let dns = CachedDnsResolver::new(inner.clone()).with_min_ttl(Duration::ZERO);
+        for _ in 0..2 {
+            dns.ipv4_lookup(Domain::from_static("example.com"))
+                .await
+                .unwrap();
+        }
+        assert_eq!(inner.lookups(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_dns_serve_stale() {
+        let inner = flaky_dns(Some(Duration::ZERO));
+        let dns = CachedDnsResolver::new(inner.clone()).with_min_ttl(Duration::ZERO);
+
+        dns.ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        inner.fail.store(true, Ordering::SeqCst);
+
+        // serve stale is disabled by default
+        assert!(
+            dns.ipv4_lookup(Domain::from_static("example.com"))
+                .await
+                .is_err()
+        );
+
+        let dns = dns.with_serve_stale(Duration::from_secs(60));
+        let ips = dns
+            .ipv4_lookup(Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(ips, vec![Ipv4Addr::new(127, 0, 0, 1)]);
+        assert_eq!(dns.metrics().stale_hits, 1);
+
+        // unknown domains are not served from a stale cache
+        assert!(
+            dns.ipv4_lookup(Domain::from_static("other.example.com"))
+                .await
+                .is_err()
+        );
+    }
+}
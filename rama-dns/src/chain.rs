@@ -1,34 +1,68 @@
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
 
 use rama_core::error::BoxError;
 use rama_net::address::Domain;
 
-use crate::DnsResolver;
+use crate::{DnsName, DnsRecordType, DnsRecords, DnsResolver, MxRecord, SrvRecord, SvcbRecord};
 
 macro_rules! dns_resolver_chain_impl {
     () => {
-        async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
-            let mut errors = Vec::new();
-            for resolver in self {
-                match resolver.ipv4_lookup(domain.clone()).await {
-                    Ok(ipv4s) => return Ok(ipv4s),
-                    Err(err) => errors.push(err.into()),
-                }
-            }
-            Err(errors)
+        dns_resolver_chain_impl! {
+            ipv4_lookup(domain: Domain): Vec<Ipv4Addr>,
+            ipv6_lookup(domain: Domain): Vec<Ipv6Addr>,
+            txt_lookup(name: DnsName): Vec<Vec<u8>>,
+            srv_lookup(name: DnsName): Vec<SrvRecord>,
+            mx_lookup(name: DnsName): Vec<MxRecord>,
+            cname_lookup(name: DnsName): Vec<DnsName>,
+            https_lookup(name: DnsName): Vec<SvcbRecord>,
+            svcb_lookup(name: DnsName): Vec<SvcbRecord>,
         }
 
-        async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+        /// Returns the first non-empty records of the chain. Empty records are only
+        /// returned in case all resolvers reported to have no records, using the
+        /// smallest negative TTL reported.
+        async fn lookup_with_ttl(
+            &self,
+            name: DnsName,
+            record_type: DnsRecordType,
+        ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
             let mut errors = Vec::new();
+            let mut negative_ttl = None;
             for resolver in self {
-                match resolver.ipv6_lookup(domain.clone()).await {
-                    Ok(ipv6s) => return Ok(ipv6s),
+                match resolver.lookup_with_ttl(name.clone(), record_type).await {
+                    Ok((records, ttl)) if !records.is_empty() => return Ok((records, ttl)),
+                    Ok((_, ttl)) => {
+                        negative_ttl = match (negative_ttl, ttl) {
+                            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                            (a, b) => a.or(b),
+                        };
+                    }
                     Err(err) => errors.push(err.into()),
                 }
             }
+            if errors.is_empty() && !self.is_empty() {
+                return Ok((DnsRecords::empty(record_type), negative_ttl));
+            }
             Err(errors)
         }
     };
+    ($($method:ident($name:ident: $name_ty:ty): $output:ty),+ $(,)?) => {
+        $(
+            async fn $method(&self, $name: $name_ty) -> Result<$output, Self::Error> {
+                let mut errors = Vec::new();
+                for resolver in self {
+                    match resolver.$method($name.clone()).await {
+                        Ok(records) => return Ok(records),
+                        Err(err) => errors.push(err.into()),
+                    }
+                }
+                Err(errors)
+            }
+        )+
+    };
 }
 
 impl<R> DnsResolver for Vec<R>
@@ -1,4 +1,4 @@
-use crate::DnsResolver;
+use crate::{DnsName, DnsResolver, MxRecord, SrvRecord, SvcbRecord};
 use rama_net::address::Domain;
 use rama_utils::macros::error::static_str_error;
 use std::net::{Ipv4Addr, Ipv6Addr};
@@ -32,4 +32,28 @@ impl DnsResolver for DenyAllDns {
     async fn ipv6_lookup(&self, _domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
         Err(DnsDeniedError)
     }
+
+    async fn txt_lookup(&self, _name: DnsName) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Err(DnsDeniedError)
+    }
+
+    async fn srv_lookup(&self, _name: DnsName) -> Result<Vec<SrvRecord>, Self::Error> {
+        Err(DnsDeniedError)
+    }
+
+    async fn mx_lookup(&self, _name: DnsName) -> Result<Vec<MxRecord>, Self::Error> {
+        Err(DnsDeniedError)
+    }
+
+    async fn cname_lookup(&self, _name: DnsName) -> Result<Vec<DnsName>, Self::Error> {
+        Err(DnsDeniedError)
+    }
+
+    async fn https_lookup(&self, _name: DnsName) -> Result<Vec<SvcbRecord>, Self::Error> {
+        Err(DnsDeniedError)
+    }
+
+    async fn svcb_lookup(&self, _name: DnsName) -> Result<Vec<SvcbRecord>, Self::Error> {
+        Err(DnsDeniedError)
+    }
 }
@@ -0,0 +1,203 @@
+use crate::DnsResolver;
+use parking_lot::Mutex;
+use rama_core::error::{BoxError, ErrorExt, OpaqueError};
+use rama_core::service::balance::{Discover, Endpoint};
+use rama_net::address::Domain;
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+/// A [`Discover`] implementation resolving its [`Endpoint`]s
+/// from the 'A' and 'AAAA' records of a [`Domain`].
+///
+/// Each resolved address, combined with the configured port,
+/// is used as the key of an endpoint, and passed to the given
+/// function to create the service of the endpoint.
+///
+/// The services of addresses which were already resolved are reused,
+/// such that services are only created for new addresses.
+///
+/// Discovery only fails in case both the 'A' and 'AAAA' lookups failed.
+///
+/// Cloning a [`DnsDiscovery`] shares the services of its endpoints.
+pub struct DnsDiscovery<R, F, S> {
+    resolver: R,
+    domain: Domain,
+    port: u16,
+    make_service: F,
+    services: Arc<Mutex<HashMap<SocketAddr, S>>>,
+}
+
+impl<R: fmt::Debug, F, S> fmt::Debug for DnsDiscovery<R, F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsDiscovery")
+            .field("resolver", &self.resolver)
+            .field("domain", &self.domain)
+            .field("port", &self.port)
+            .finish()
+    }
+}
+
+impl<R: Clone, F: Clone, S> Clone for DnsDiscovery<R, F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            resolver: self.resolver.clone(),
+            domain: self.domain.clone(),
+            port: self.port,
+            make_service: self.make_service.clone(),
+            services: self.services.clone(),
+        }
+    }
+}
+
+impl<R, F, S> DnsDiscovery<R, F, S> {
+    /// Create a new [`DnsDiscovery`] resolving the given [`Domain`] using the given resolver,
+    /// creating the service of each endpoint using the given function.
+    pub fn new<E>(resolver: R, domain: Domain, port: u16, make_service: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Result<S, E>,
+    {
+        Self {
+            resolver,
+            domain,
+            port,
+            make_service,
+            services: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The [`Domain`] resolved by this [`DnsDiscovery`].
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+
+    /// The port used for the resolved addresses.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl<R, F, S, E> Discover for DnsDiscovery<R, F, S>
+where
+    R: DnsResolver<Error: Into<BoxError> + Send>,
+    F: Fn(SocketAddr) -> Result<S, E> + Send + Sync + 'static,
+    S: Clone + Send + Sync + 'static,
+    E: Into<BoxError> + Send + 'static,
+{
+    type Key = SocketAddr;
+    type Service = S;
+    type Error = OpaqueError;
+
+    async fn discover(&self) -> Result<Vec<Endpoint<SocketAddr, S>>, Self::Error> {
+        let (ipv4, ipv6) = tokio::join!(
+            self.resolver.ipv4_lookup(self.domain.clone()),
+            self.resolver.ipv6_lookup(self.domain.clone()),
+        );
+
+        let ips: Vec<IpAddr> = match (ipv4, ipv6) {
+            (Err(ipv4_err), Err(ipv6_err)) => {
+                let ipv4_err: BoxError = ipv4_err.into();
+                let ipv6_err: BoxError = ipv6_err.into();
+                return Err(OpaqueError::from_display(format!(
+                    "DnsDiscovery: resolve {}: ipv4: {ipv4_err}; ipv6: {ipv6_err}",
+                    self.domain
+                )));
+            }
+            (ipv4, ipv6) => ipv4
+                .unwrap_or_default()
+                .into_iter()
+                .map(IpAddr::V4)
+                .chain(ipv6.unwrap_or_default().into_iter().map(IpAddr::V6))
+                .collect(),
+        };
+
+        let mut services = self.services.lock();
+        let mut discovered = HashMap::with_capacity(ips.len());
+        let endpoints = ips
+            .into_iter()
+            .map(|ip| {
+                let addr = SocketAddr::new(ip, self.port);
+                let service = match services.get(&addr) {
+                    Some(service) => service.clone(),
+                    None => (self.make_service)(addr).map_err(|err| {
+                        OpaqueError::from_boxed(err.into())
+                            .context("DnsDiscovery: create endpoint service")
+                    })?,
+                };
+                discovered.insert(addr, service.clone());
+                Ok(Endpoint::new(addr, service))
+            })
+            .collect::<Result<_, OpaqueError>>()?;
+        // drop the services of the addresses which are no longer resolved
+        *services = discovered;
+        Ok(endpoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn make_service(addr: SocketAddr) -> Result<String, OpaqueError> {
+        Ok(addr.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("upstream.internal"),
+            [
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ],
+        );
+
+        let discovery = DnsDiscovery::new(
+            dns,
+            Domain::from_static("upstream.internal"),
+            8080,
+            make_service,
+        );
+        let endpoints = discovery.discover().await.unwrap();
+        let keys: Vec<_> = endpoints.iter().map(|e| e.key().to_string()).collect();
+        assert_eq!(keys, ["10.0.0.1:8080", "10.0.0.2:8080", "[::1]:8080"]);
+        assert_eq!(endpoints[0].service(), "10.0.0.1:8080");
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery_reuses_services() {
+        let domain = Domain::from_static("upstream.internal");
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(domain.clone(), [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]);
+
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let discovery = DnsDiscovery::new(dns, domain, 8080, {
+            let created = created.clone();
+            move |addr: SocketAddr| {
+                created.lock().push(addr);
+                make_service(addr)
+            }
+        });
+        assert_eq!(discovery.discover().await.unwrap().len(), 1);
+        assert_eq!(discovery.discover().await.unwrap().len(), 1);
+        assert_eq!(created.lock().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery_not_found() {
+        let discovery = DnsDiscovery::new(
+            InMemoryDns::new(),
+            Domain::from_static("upstream.internal"),
+            8080,
+            make_service,
+        );
+        assert!(discovery.discover().await.is_err());
+    }
+}
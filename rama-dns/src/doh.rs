@@ -0,0 +1,228 @@
+use crate::{
+    DnsResolver,
+    wire::{DnsExchange, dns_exchange_resolver_impl},
+};
+use base64::Engine as _;
+use rama_core::{
+    Context, Service,
+    error::{BoxError, ErrorContext, OpaqueError},
+};
+use rama_http_types::{
+    Body, Method, Request, Response, Uri,
+    dep::http_body_util::{BodyExt, Limited},
+    header::{ACCEPT, CONTENT_TYPE},
+};
+use rama_utils::macros::define_inner_service_accessors;
+
+const DNS_MESSAGE_MIME: &str = "application/dns-message";
+
+/// Maximum size of a DNS message.
+const MAX_DNS_MESSAGE_SIZE: usize = u16::MAX as usize;
+
+const BASE64_URL: base64::engine::GeneralPurpose = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The HTTP method used by a [`DohResolver`] to send its DNS queries.
+pub enum DohMethod {
+    #[default]
+    /// Send the query as the body of a `POST` request.
+    Post,
+    /// Send the query as the base64url encoded `dns` query parameter
+    /// of a `GET` request, which makes the responses cacheable by HTTP caches.
+    Get,
+}
+
+#[derive(Debug, Clone)]
+/// A [`DnsResolver`] using DNS-over-HTTPS (DoH), as defined in [RFC 8484].
+///
+/// DNS queries are sent using the wrapped HTTP client [`Service`],
+/// which means that any rama client stack can be used, e.g. to
+/// resolve over a proxy or using a specific TLS configuration.
+///
+/// [RFC 8484]: https://datatracker.ietf.org/doc/html/rfc8484
+///
+/// # Example
+///
+/// ```ignore
+/// use rama::dns::DohResolver;
+/// use rama::http::client::EasyHttpWebClient;
+///
+/// let dns = DohResolver::new(
+///     EasyHttpWebClient::default(),
+///     "https://cloudflare-dns.com/dns-query".parse().unwrap(),
+/// );
+/// ```
+pub struct DohResolver<S> {
+    inner: S,
+    uri: Uri,
+    method: DohMethod,
+}
+
+impl<S> DohResolver<S> {
+    /// Create a new [`DohResolver`], sending its queries
+    /// to the given (DoH) [`Uri`] using the given HTTP client.
+    pub fn new(client: S, uri: Uri) -> Self {
+        Self {
+            inner: client,
+            uri,
+            method: DohMethod::default(),
+        }
+    }
+
+    /// Set the [`DohMethod`] used to send queries (default: [`DohMethod::Post`]).
+    pub fn set_method(&mut self, method: DohMethod) -> &mut Self {
+        self.method = method;
+        self
+    }
+
+    /// Replace the [`DohMethod`] used to send queries (default: [`DohMethod::Post`]).
+    pub fn with_method(mut self, method: DohMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    define_inner_service_accessors!();
+
+    fn build_request(&self, query: Vec<u8>) -> Result<Request, OpaqueError> {
+        let builder = Request::builder().header(ACCEPT, DNS_MESSAGE_MIME);
+        match self.method {
+            DohMethod::Post => builder
+                .method(Method::POST)
+                .uri(self.uri.clone())
+                .header(CONTENT_TYPE, DNS_MESSAGE_MIME)
+                .body(Body::from(query)),
+            DohMethod::Get => {
+                let dns = BASE64_URL.encode(query);
+                let path_and_query = match self.uri.query() {
+                    Some(query) => format!("{}?{query}&dns={dns}", self.uri.path()),
+                    None => format!("{}?dns={dns}", self.uri.path()),
+                };
+                let mut parts = self.uri.clone().into_parts();
+                parts.path_and_query = Some(
+                    path_and_query
+                        .parse()
+                        .context("create DoH GET request path")?,
+                );
+                let uri = Uri::from_parts(parts).context("create DoH GET request uri")?;
+                builder.method(Method::GET).uri(uri).body(Body::empty())
+            }
+        }
+        .context("create DoH request")
+    }
+}
+
+impl<S> DnsExchange for DohResolver<S>
+where
+    S: Service<(), Request, Response = Response, Error: Into<BoxError>>,
+{
+    async fn exchange(&self, query: Vec<u8>) -> Result<Vec<u8>, OpaqueError> {
+        let request = self.build_request(query)?;
+        let response = self
+            .inner
+            .serve(Context::default(), request)
+            .await
+            .map_err(|err| OpaqueError::from_boxed(err.into()))
+            .context("send DoH request")?;
+
+        if !response.status().is_success() {
+            return Err(OpaqueError::from_display(format!(
+                "unexpected DoH response status: {}",
+                response.status()
+            )));
+        }
+        let content_type = response.headers().get(CONTENT_TYPE);
+        if content_type.is_some_and(|value| value.as_bytes() != DNS_MESSAGE_MIME.as_bytes()) {
+            return Err(OpaqueError::from_display(format!(
+                "unexpected DoH response content type: {content_type:?}"
+            )));
+        }
+
+        let body = Limited::new(response.into_body(), MAX_DNS_MESSAGE_SIZE)
+            .collect()
+            .await
+            .map_err(OpaqueError::from_boxed)
+            .context("read DoH response body")?;
+        Ok(body.to_bytes().to_vec())
+    }
+}
+
+impl<S> DnsResolver for DohResolver<S>
+where
+    S: Service<(), Request, Response = Response, Error: Into<BoxError>>,
+{
+    type Error = OpaqueError;
+
+    dns_exchange_resolver_impl!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DnsName, DnsRecordType, InMemoryDns, server::DnsServer};
+    use rama_core::service::service_fn;
+    use rama_net::address::Domain;
+    use std::{convert::Infallible, net::Ipv4Addr};
+
+    fn doh_server() -> impl Service<(), Request, Response = Response, Error = Infallible> {
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        );
+        let server = DnsServer::new(dns);
+
+        service_fn(move |req: Request| {
+            let server = server.clone();
+            async move {
+                let query = match *req.method() {
+                    Method::GET => {
+                        let query = req.uri().query().unwrap();
+                        let dns = query
+                            .split('&')
+                            .find_map(|param| param.strip_prefix("dns="))
+                            .unwrap();
+                        BASE64_URL.decode(dns).unwrap()
+                    }
+                    _ => {
+                        assert_eq!(req.headers()[CONTENT_TYPE], DNS_MESSAGE_MIME);
+                        req.into_body().collect().await.unwrap().to_bytes().to_vec()
+                    }
+                };
+                let response = server.respond(&query).await.unwrap();
+                Ok(Response::builder()
+                    .header(CONTENT_TYPE, DNS_MESSAGE_MIME)
+                    .body(Body::from(response))
+                    .unwrap())
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_doh_resolver() {
+        for method in [DohMethod::Post, DohMethod::Get] {
+            let dns = DohResolver::new(
+                doh_server(),
+                "https://dns.example/dns-query?foo=bar".parse().unwrap(),
+            )
+            .with_method(method);
+
+            assert_eq!(
+                dns.ipv4_lookup(Domain::from_static("example.com"))
+                    .await
+                    .unwrap(),
+                vec![Ipv4Addr::new(127, 0, 0, 1)]
+            );
+
+            let (records, _) = dns
+                .lookup_with_ttl(DnsName::from_static("example.org"), DnsRecordType::Ipv4)
+                .await
+                .unwrap();
+            assert!(records.is_empty());
+            assert!(
+                dns.ipv6_lookup(Domain::from_static("example.com"))
+                    .await
+                    .is_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use crate::{
+    DnsResolver,
+    wire::{DnsExchange, dns_exchange_resolver_impl},
+};
+use parking_lot::Mutex;
+use rama_core::{
+    Context,
+    error::{ErrorContext, OpaqueError},
+};
+use rama_net::{
+    client::{ConnectorService, EstablishedClientConnection},
+    stream::Stream,
+};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_MAX_IDLE_CONNECTIONS: usize = 4;
+
+type IdleConnection = Box<dyn Stream + Unpin>;
+
+/// A [`DnsResolver`] using DNS-over-TLS (DoT), as defined in [RFC 7858].
+///
+/// DNS queries are sent over the connection established by the wrapped
+/// [`ConnectorService`] for the configured request, e.g. a `TlsConnector`
+/// layered on top of a `TcpConnector` for a `TcpRequest` to the resolver on port 853.
+/// Any rama connector stack can be used this way, including proxy connectors.
+///
+/// Connections are kept open after a query has been answered, and reused
+/// for later queries, as recommended by [RFC 7858]. Idle connections closed
+/// by the server are replaced transparently by a new connection.
+/// Each query, including the time to establish a connection, is bound
+/// by a [timeout](Self::set_timeout).
+///
+/// Given the transport is defined by the connector, this resolver
+/// can be used for plain DNS over TCP as well.
+///
+/// [RFC 7858]: https://datatracker.ietf.org/doc/html/rfc7858
+pub struct DotResolver<C, R> {
+    connector: C,
+    request: R,
+    timeout: Duration,
+    max_idle_connections: usize,
+    idle_connections: Arc<Mutex<Vec<IdleConnection>>>,
+}
+
+impl<C: fmt::Debug, R: fmt::Debug> fmt::Debug for DotResolver<C, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DotResolver")
+            .field("connector", &self.connector)
+            .field("request", &self.request)
+            .field("timeout", &self.timeout)
+            .field("max_idle_connections", &self.max_idle_connections)
+            .field("idle_connections", &self.idle_connections.lock().len())
+            .finish()
+    }
+}
+
+impl<C: Clone, R: Clone> Clone for DotResolver<C, R> {
+    fn clone(&self) -> Self {
+        Self {
+            connector: self.connector.clone(),
+            request: self.request.clone(),
+            timeout: self.timeout,
+            max_idle_connections: self.max_idle_connections,
+            idle_connections: self.idle_connections.clone(),
+        }
+    }
+}
+
+impl<C, R> DotResolver<C, R> {
+    /// Create a new [`DotResolver`], sending its queries over
+    /// the connection established by the connector for the given request.
+    pub fn new(connector: C, request: R) -> Self {
+        Self {
+            connector,
+            request,
+            timeout: DEFAULT_TIMEOUT,
+            max_idle_connections: DEFAULT_MAX_IDLE_CONNECTIONS,
+            idle_connections: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Gets a reference to the underlying connector.
+    pub fn get_ref(&self) -> &C {
+        &self.connector
+    }
+
+    /// Gets a reference to the request used to establish connections.
+    pub fn request(&self) -> &R {
+        &self.request
+    }
+
+    /// Set the timeout of a single query,
+    /// including the time to establish a connection (default: 5 seconds).
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Replace the timeout of a single query,
+    /// including the time to establish a connection (default: 5 seconds).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the maximum number of idle connections kept open for reuse (default: 4).
+    ///
+    /// Use `0` to establish a new connection for each query.
+    pub fn set_max_idle_connections(&mut self, max: usize) -> &mut Self {
+        self.max_idle_connections = max;
+        self
+    }
+
+    /// Replace the maximum number of idle connections kept open for reuse (default: 4).
+    ///
+    /// Use `0` to establish a new connection for each query.
+    pub fn with_max_idle_connections(mut self, max: usize) -> Self {
+        self.max_idle_connections = max;
+        self
+    }
+
+    fn release_connection(&self, conn: IdleConnection) {
+        let mut idle_connections = self.idle_connections.lock();
+        if idle_connections.len() < self.max_idle_connections {
+            idle_connections.push(conn);
+        }
+    }
+}
+
+impl<C, R> DotResolver<C, R>
+where
+    C: ConnectorService<(), R, Connection: Stream + Unpin>,
+    R: Clone + Send + Sync + 'static,
+{
+    async fn exchange_with_pool(&self, query: &[u8]) -> Result<Vec<u8>, OpaqueError> {
+        loop {
+            // bind the popped connection first, so the lock is released before awaiting
+            let conn = self.idle_connections.lock().pop();
+            let Some(mut conn) = conn else {
+                break;
+            };
+            match exchange_over_stream(&mut conn, query).await {
+                Ok(response) => {
+                    self.release_connection(conn);
+                    return Ok(response);
+                }
+                Err(err) => {
+                    tracing::trace!(%err, "DoT: idle connection failed, try next connection");
+                }
+            }
+        }
+
+        let EstablishedClientConnection { conn, .. } = self
+            .connector
+            .connect(Context::default(), self.request.clone())
+            .await
+            .map_err(|err| OpaqueError::from_boxed(err.into()))
+            .context("establish DoT connection")?;
+        let mut conn: IdleConnection = Box::new(conn);
+        let response = exchange_over_stream(&mut conn, query).await?;
+        self.release_connection(conn);
+        Ok(response)
+    }
+}
+
+/// Exchange a single query over the stream,
+/// with messages prefixed by their length as a two byte integer.
+async fn exchange_over_stream(
+    stream: &mut IdleConnection,
+    query: &[u8],
+) -> Result<Vec<u8>, OpaqueError> {
+    let len = u16::try_from(query.len()).context("dns query too large")?;
+    stream.write_u16(len).await.context("write DoT query")?;
+    stream.write_all(query).await.context("write DoT query")?;
+    stream.flush().await.context("flush DoT query")?;
+
+    let len = stream.read_u16().await.context("read DoT response")?;
+    let mut response = vec![0u8; len as usize];
+    stream
+        .read_exact(&mut response)
+        .await
+        .context("read DoT response")?;
+    Ok(response)
+}
+
+impl<C, R> DnsExchange for DotResolver<C, R>
+where
+    C: ConnectorService<(), R, Connection: Stream + Unpin>,
+    R: Clone + Send + Sync + 'static,
+{
+    async fn exchange(&self, query: Vec<u8>) -> Result<Vec<u8>, OpaqueError> {
+        // a connection is dropped, rather than reused, when its exchange times out
+        tokio::time::timeout(self.timeout, self.exchange_with_pool(&query))
+            .await
+            .map_err(|_| OpaqueError::from_display("DoT query timed out"))?
+    }
+}
+
+impl<C, R> DnsResolver for DotResolver<C, R>
+where
+    C: ConnectorService<(), R, Connection: Stream + Unpin>,
+    R: Clone + Send + Sync + 'static,
+{
+    type Error = OpaqueError;
+
+    dns_exchange_resolver_impl!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DnsName, InMemoryDns, MxRecord, server::DnsServer};
+    use rama_core::{Layer, Service, service::service_fn};
+    use rama_net::{
+        address::{Authority, Domain},
+        tls::server::SelfSignedData,
+        transport::{TransportContext, TransportProtocol, TryRefIntoTransportContext},
+    };
+    use rama_tls_rustls::{
+        client::{TlsConnector, TlsConnectorDataBuilder},
+        server::{TlsAcceptorDataBuilder, TlsAcceptorLayer},
+    };
+    use std::{
+        convert::Infallible,
+        net::SocketAddr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[derive(Debug, Clone)]
+    struct TestRequest(Authority);
+
+    impl<State> TryRefIntoTransportContext<State> for TestRequest {
+        type Error = Infallible;
+
+        fn try_ref_into_transport_ctx(
+            &self,
+            _ctx: &Context<State>,
+        ) -> Result<TransportContext, Self::Error> {
+            Ok(TransportContext {
+                protocol: TransportProtocol::Tcp,
+                app_protocol: None,
+                http_version: None,
+                authority: self.0.clone(),
+            })
+        }
+    }
+
+    async fn spawn_dot_server(server: DnsServer<InMemoryDns>) -> SocketAddr {
+        let acceptor_data = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData::default())
+            .unwrap()
+            .build();
+        let server = TlsAcceptorLayer::new(acceptor_data).layer(server);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let server = server.clone();
+                tokio::spawn(async move { server.serve(Context::default(), stream).await });
+            }
+        });
+        addr
+    }
+
+    fn dot_resolver(
+        addr: SocketAddr,
+        connections: Arc<AtomicUsize>,
+    ) -> DotResolver<impl ConnectorService<(), TestRequest, Connection: Stream + Unpin>, TestRequest>
+    {
+        let connector = service_fn(move |ctx: Context<()>, req: TestRequest| {
+            let connections = connections.clone();
+            async move {
+                let conn = TcpStream::connect(addr).await?;
+                connections.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, std::io::Error>(EstablishedClientConnection { ctx, req, conn })
+            }
+        });
+        let connector = TlsConnector::secure(connector).with_connector_data(
+            TlsConnectorDataBuilder::new()
+                .with_no_cert_verifier()
+                .build(),
+        );
+        DotResolver::new(
+            connector,
+            TestRequest(Authority::new(
+                Domain::from_static("dns.example").into(),
+                853,
+            )),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_dot_resolver() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_mx(
+            DnsName::from_static("example.com"),
+            vec![MxRecord {
+                preference: 10,
+                exchange: Domain::from_static("mail.example.com"),
+            }],
+        )
+        .insert_cname(
+            DnsName::from_static("www.example.com"),
+            DnsName::from_static("cdn.example.com"),
+        )
+        .insert_cname(
+            DnsName::from_static("cdn.example.com"),
+            DnsName::from_static("edge.example.net"),
+        );
+        let addr = spawn_dot_server(DnsServer::new(dns)).await;
+
+        let connections = Arc::new(AtomicUsize::new(0));
+        let dns = dot_resolver(addr, connections.clone());
+
+        let mx = dns
+            .mx_lookup(DnsName::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(mx[0].exchange, Domain::from_static("mail.example.com"));
+
+        let chain = dns
+            .cname_lookup(DnsName::from_static("www.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            chain,
+            vec![
+                DnsName::from_static("cdn.example.com"),
+                DnsName::from_static("edge.example.net"),
+            ]
+        );
+
+        assert!(
+            dns.ipv4_lookup(Domain::from_static("example.com"))
+                .await
+                .is_err()
+        );
+
+        // all queries are sent over the same connection
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dot_resolver_reconnect_closed_connection() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_txt(DnsName::from_static("example.com"), ["hello"]);
+        let server = DnsServer::new(dns).with_tcp_idle_timeout(Duration::from_millis(20));
+        let addr = spawn_dot_server(server).await;
+
+        let connections = Arc::new(AtomicUsize::new(0));
+        let dns = dot_resolver(addr, connections.clone());
+
+        for _ in 0..2 {
+            let txt = dns
+                .txt_lookup(DnsName::from_static("example.com"))
+                .await
+                .unwrap();
+            assert_eq!(txt, vec![b"hello".to_vec()]);
+
+            // let the server close the idle connection
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dot_resolver_timeout() {
+        // a server which accepts connections, but never completes a handshake
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut streams = Vec::new();
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                streams.push(stream);
+            }
+        });
+
+        let dns = dot_resolver(addr, Arc::new(AtomicUsize::new(0)))
+            .with_timeout(Duration::from_millis(50));
+        assert!(
+            dns.txt_lookup(DnsName::from_static("example.com"))
+                .await
+                .is_err()
+        );
+    }
+}
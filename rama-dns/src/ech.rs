@@ -0,0 +1,274 @@
+use crate::{DnsName, DnsResolver, SvcbRecord};
+use rama_core::{
+    Context, Layer, Service,
+    error::{BoxError, ErrorExt, OpaqueError},
+};
+use rama_net::{
+    address::{Domain, Host},
+    tls::{
+        EchConfigList,
+        client::{ClientConfig, append_client_config_to_ctx, extract_client_config_from_ctx},
+    },
+    transport::TryRefIntoTransportContext,
+};
+use std::fmt;
+
+/// Maximum amount of AliasMode records followed when resolving
+/// the 'HTTPS' records of a domain, as a protection against (accidental) loops.
+const MAX_ALIAS_CHAIN_LEN: usize = 8;
+
+/// Resolve the [`EchConfigList`] of the given [`Domain`], using its 'HTTPS' records.
+///
+/// AliasMode records are followed, after which the list of the most preferred
+/// ServiceMode record that defines one is returned. `None` is returned in case
+/// no such record exists. Lists which are not well-formed are ignored.
+pub async fn lookup_ech_config_list<R: DnsResolver>(
+    resolver: &R,
+    domain: Domain,
+) -> Result<Option<EchConfigList>, R::Error> {
+    let mut name = DnsName::from(domain);
+    for _ in 0..MAX_ALIAS_CHAIN_LEN {
+        let records = resolver.https_lookup(name.clone()).await?;
+        if let Some(alias) = records.iter().find(|record| record.is_alias()) {
+            tracing::trace!(%name, target = %alias.target, "ech config lookup: follow alias");
+            name = alias.target.clone();
+            continue;
+        }
+        return Ok(ech_config_list_from_records(records));
+    }
+    tracing::debug!(%name, "ech config lookup: alias chain too long");
+    Ok(None)
+}
+
+fn ech_config_list_from_records(mut records: Vec<SvcbRecord>) -> Option<EchConfigList> {
+    records.sort_by_key(|record| record.priority);
+    records.into_iter().find_map(|record| {
+        let data = record.ech_config_list?;
+        EchConfigList::try_from_bytes(data)
+            .inspect_err(|err| {
+                tracing::debug!(target = %record.target, error = %err, "ech config lookup: ignore invalid ech config list");
+            })
+            .ok()
+    })
+}
+
+/// A [`Layer`] which wraps the given service with an [`EchConfigLookupService`].
+///
+/// See [`EchConfigLookupService`] for more information.
+pub struct EchConfigLookupLayer<R> {
+    resolver: R,
+}
+
+impl<R> EchConfigLookupLayer<R> {
+    /// Create a new [`EchConfigLookupLayer`] using the given [`DnsResolver`].
+    pub const fn new(resolver: R) -> Self {
+        Self { resolver }
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for EchConfigLookupLayer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EchConfigLookupLayer")
+            .field("resolver", &self.resolver)
+            .finish()
+    }
+}
+
+impl<R: Clone> Clone for EchConfigLookupLayer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            resolver: self.resolver.clone(),
+        }
+    }
+}
+
+impl<S, R: Clone> Layer<S> for EchConfigLookupLayer<R> {
+    type Service = EchConfigLookupService<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        EchConfigLookupService {
+            inner,
+            resolver: self.resolver.clone(),
+        }
+    }
+}
+
+/// A [`Service`] which resolves the [`EchConfigList`] of the server
+/// a request is made to, such that its client hello can be encrypted.
+///
+/// For secure requests to a domain, the [`EchConfigList`] is resolved
+/// using [`lookup_ech_config_list`] and added to the [`Context`]
+/// as a [`ClientConfig`], to be used by the tls connector of the inner service,
+/// e.g. the `EasyHttpWebClient`. Requests for which a [`ClientConfig`]
+/// with an [`EchConfigList`] is already defined are passed as-is.
+///
+/// Encrypted Client Hello is used opportunistically, meaning that
+/// failed lookups are logged and the request is still served by the inner service.
+pub struct EchConfigLookupService<S, R> {
+    inner: S,
+    resolver: R,
+}
+
+impl<S, R> EchConfigLookupService<S, R> {
+    /// Create a new [`EchConfigLookupService`] using the given [`DnsResolver`].
+    pub const fn new(inner: S, resolver: R) -> Self {
+        Self { inner, resolver }
+    }
+}
+
+impl<S: fmt::Debug, R: fmt::Debug> fmt::Debug for EchConfigLookupService<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EchConfigLookupService")
+            .field("inner", &self.inner)
+            .field("resolver", &self.resolver)
+            .finish()
+    }
+}
+
+impl<S: Clone, R: Clone> Clone for EchConfigLookupService<S, R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            resolver: self.resolver.clone(),
+        }
+    }
+}
+
+impl<S, R, State, Request> Service<State, Request> for EchConfigLookupService<S, R>
+where
+    S: Service<State, Request, Error: Into<BoxError>>,
+    R: DnsResolver<Error: Into<BoxError>>,
+    State: Clone + Send + Sync + 'static,
+    Request: TryRefIntoTransportContext<State, Error: Into<BoxError> + Send + Sync + 'static>
+        + Send
+        + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let transport_ctx = ctx
+            .get_or_try_insert_with_ctx(|ctx| req.try_ref_into_transport_ctx(ctx))
+            .map_err(|err| {
+                OpaqueError::from_boxed(err.into())
+                    .context("EchConfigLookupService: compute transport context")
+            })?;
+
+        let is_secure = transport_ctx
+            .app_protocol
+            .as_ref()
+            .is_some_and(|p| p.is_secure());
+        let domain = match transport_ctx.authority.host() {
+            Host::Name(domain) if is_secure => Some(domain.clone()),
+            _ => None,
+        };
+
+        let has_ech_config_list = extract_client_config_from_ctx(&ctx)
+            .is_some_and(|chain| chain.iter().any(|cfg| cfg.ech_config_list.is_some()));
+
+        if let Some(domain) = domain.filter(|_| !has_ech_config_list) {
+            match lookup_ech_config_list(&self.resolver, domain.clone()).await {
+                Ok(Some(ech_config_list)) => {
+                    tracing::trace!(%domain, "EchConfigLookupService: use resolved ech config list");
+                    append_client_config_to_ctx(
+                        &mut ctx,
+                        ClientConfig {
+                            ech_config_list: Some(ech_config_list),
+                            ..Default::default()
+                        },
+                    );
+                }
+                Ok(None) => {
+                    tracing::trace!(%domain, "EchConfigLookupService: no ech config list found");
+                }
+                Err(err) => {
+                    let err: BoxError = err.into();
+                    tracing::debug!(%domain, error = %err, "EchConfigLookupService: ech config lookup failed");
+                }
+            }
+        }
+
+        self.inner.serve(ctx, req).await.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDns;
+    use rama_net::tls::EchConfig;
+
+    fn ech_config_list(config_id: u8) -> EchConfigList {
+        EchConfigList::try_from_configs(&[EchConfig::new(
+            config_id,
+            vec![config_id; 32],
+            Domain::from_static("public.example.com"),
+        )])
+        .unwrap()
+    }
+
+    fn https_record(priority: u16, target: &'static str, ech: Option<Vec<u8>>) -> SvcbRecord {
+        let mut record = SvcbRecord::new(priority, DnsName::from_static(target));
+        record.ech_config_list = ech;
+        record
+    }
+
+    #[tokio::test]
+    async fn test_lookup_ech_config_list_preferred_record() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_https(
+            DnsName::from_static("example.com"),
+            vec![
+                https_record(2, "b.example.com", Some(ech_config_list(2).into_bytes())),
+                https_record(1, "a.example.com", Some(vec![0xff])),
+                https_record(3, "c.example.com", None),
+                https_record(1, "a.example.com", Some(ech_config_list(1).into_bytes())),
+            ],
+        );
+
+        let list = lookup_ech_config_list(&dns, Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(list, Some(ech_config_list(1)));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_ech_config_list_follow_alias() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_https(
+            DnsName::from_static("example.com"),
+            vec![https_record(0, "cdn.example.net", None)],
+        );
+        dns.insert_https(
+            DnsName::from_static("cdn.example.net"),
+            vec![https_record(
+                1,
+                "cdn.example.net",
+                Some(ech_config_list(3).into_bytes()),
+            )],
+        );
+
+        let list = lookup_ech_config_list(&dns, Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert_eq!(list, Some(ech_config_list(3)));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_ech_config_list_none() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_https(
+            DnsName::from_static("example.com"),
+            vec![https_record(1, "example.com", None)],
+        );
+
+        let list = lookup_ech_config_list(&dns, Domain::from_static("example.com"))
+            .await
+            .unwrap();
+        assert!(list.is_none());
+    }
+}
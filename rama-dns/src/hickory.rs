@@ -1,16 +1,28 @@
 //! dns using the [`hickory_resolver`] crate
 
-use crate::DnsResolver;
+use crate::{
+    DnsName, DnsRecordType, DnsRecords, DnsResolver, MAX_CNAME_CHAIN_LEN, MxRecord, SrvRecord,
+    SvcbRecord,
+    wire::{
+        dns_name_from_name, domain_from_name, fqdn_from_dns_name, fqdn_from_domain,
+        records_from_rdata, svcb_record_from_svcb,
+    },
+};
 use hickory_resolver::{
-    Name, TokioResolver,
+    ResolveError, TokioResolver,
     name_server::TokioConnectionProvider,
-    proto::rr::rdata::{A, AAAA},
+    proto::ProtoErrorKind,
+    proto::rr::{
+        RData, RecordType,
+        rdata::{A, AAAA, CNAME, HTTPS},
+    },
 };
-use rama_core::error::{ErrorContext, OpaqueError};
+use rama_core::error::{ErrorContext, ErrorExt, OpaqueError};
 use rama_net::address::Domain;
 use std::{
     net::{Ipv4Addr, Ipv6Addr},
     sync::{Arc, OnceLock},
+    time::{Duration, Instant},
 };
 
 pub use hickory_resolver::config;
@@ -131,10 +143,143 @@ impl DnsResolver for HickoryDns {
             .map(|AAAA(ip)| ip)
             .collect())
     }
+
+    async fn txt_lookup(&self, name: DnsName) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let name = fqdn_from_dns_name(&name)?;
+        Ok(self
+            .0
+            .txt_lookup(name)
+            .await
+            .context("lookup TXT record(s)")?
+            .into_iter()
+            .map(|txt| txt.iter().flat_map(|data| data.iter().copied()).collect())
+            .collect())
+    }
+
+    async fn srv_lookup(&self, name: DnsName) -> Result<Vec<SrvRecord>, Self::Error> {
+        let name = fqdn_from_dns_name(&name)?;
+        self.0
+            .srv_lookup(name)
+            .await
+            .context("lookup SRV record(s)")?
+            .into_iter()
+            .map(|srv| {
+                Ok(SrvRecord {
+                    priority: srv.priority(),
+                    weight: srv.weight(),
+                    port: srv.port(),
+                    target: domain_from_name(srv.target())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn mx_lookup(&self, name: DnsName) -> Result<Vec<MxRecord>, Self::Error> {
+        let name = fqdn_from_dns_name(&name)?;
+        self.0
+            .mx_lookup(name)
+            .await
+            .context("lookup MX record(s)")?
+            .into_iter()
+            .map(|mx| {
+                Ok(MxRecord {
+                    preference: mx.preference(),
+                    exchange: domain_from_name(mx.exchange())?,
+                })
+            })
+            .collect()
+    }
+
+    async fn cname_lookup(&self, name: DnsName) -> Result<Vec<DnsName>, Self::Error> {
+        let mut name = fqdn_from_dns_name(&name)?;
+        let mut chain = Vec::new();
+
+        while chain.len() < MAX_CNAME_CHAIN_LEN {
+            let result = self.0.lookup(name.clone(), RecordType::CNAME).await;
+            let lookup = match result {
+                Ok(lookup) => lookup,
+                Err(err) if !chain.is_empty() && err.is_no_records_found() => break,
+                Err(err) => return Err(OpaqueError::from_std(err).context("lookup CNAME record")),
+            };
+            let Some(target) = lookup.iter().find_map(|rdata| match rdata {
+                RData::CNAME(CNAME(target)) => Some(target.clone()),
+                _ => None,
+            }) else {
+                break;
+            };
+            chain.push(dns_name_from_name(&target)?);
+            name = target;
+        }
+
+        if chain.is_empty() {
+            return Err(OpaqueError::from_display("no CNAME record found"));
+        }
+        Ok(chain)
+    }
+
+    async fn https_lookup(&self, owner: DnsName) -> Result<Vec<SvcbRecord>, Self::Error> {
+        let name = fqdn_from_dns_name(&owner)?;
+        self.0
+            .lookup(name, RecordType::HTTPS)
+            .await
+            .context("lookup HTTPS record(s)")?
+            .iter()
+            .filter_map(|rdata| match rdata {
+                RData::HTTPS(HTTPS(svcb)) => Some(svcb_record_from_svcb(&owner, svcb)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    async fn svcb_lookup(&self, owner: DnsName) -> Result<Vec<SvcbRecord>, Self::Error> {
+        let name = fqdn_from_dns_name(&owner)?;
+        self.0
+            .lookup(name, RecordType::SVCB)
+            .await
+            .context("lookup SVCB record(s)")?
+            .iter()
+            .filter_map(|rdata| match rdata {
+                RData::SVCB(svcb) => Some(svcb_record_from_svcb(&owner, svcb)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    async fn lookup_with_ttl(
+        &self,
+        owner: DnsName,
+        record_type: DnsRecordType,
+    ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
+        if record_type == DnsRecordType::Cname {
+            // a chain is resolved using multiple lookups, hickory caches those
+            let chain = self.cname_lookup(owner).await?;
+            return Ok((DnsRecords::Cname(chain), None));
+        }
+
+        let name = fqdn_from_dns_name(&owner)?;
+        let lookup = match self.0.lookup(name, record_type.into()).await {
+            Ok(lookup) => lookup,
+            Err(err) if err.is_no_records_found() => {
+                return Ok((DnsRecords::empty(record_type), negative_ttl(&err)));
+            }
+            Err(err) => {
+                return Err(OpaqueError::from_std(err).context("lookup DNS record(s)"));
+            }
+        };
+        let ttl = lookup
+            .valid_until()
+            .saturating_duration_since(Instant::now());
+
+        let records = records_from_rdata(&owner, record_type, lookup.iter())?;
+        Ok((records, Some(ttl)))
+    }
 }
 
-fn fqdn_from_domain(domain: Domain) -> Result<Name, OpaqueError> {
-    let mut name = Name::from_utf8(domain).context("try to consume a Domain as a Dns Name")?;
-    name.set_fqdn(true);
-    Ok(name)
+fn negative_ttl(err: &ResolveError) -> Option<Duration> {
+    match err.proto()?.kind() {
+        ProtoErrorKind::NoRecordsFound { negative_ttl, .. } => {
+            negative_ttl.map(|ttl| Duration::from_secs(ttl as u64))
+        }
+        _ => None,
+    }
 }
@@ -1,10 +1,14 @@
-use crate::DnsResolver;
+use crate::{
+    DnsName, DnsRecordType, DnsRecords, DnsResolver, MAX_CNAME_CHAIN_LEN, MxRecord, SrvRecord,
+    SvcbRecord,
+};
 use rama_net::address::Domain;
 use rama_utils::macros::{error::static_str_error, impl_deref};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Duration,
 };
 
 #[derive(Debug, Clone)]
@@ -25,6 +29,7 @@ impl<'de> Deserialize<'de> for DnsOverwrite {
         let map = HashMap::<Domain, Vec<IpAddr>>::deserialize(deserializer)?;
         Ok(DnsOverwrite(InMemoryDns {
             map: (!map.is_empty()).then_some(map),
+            records: None,
         }))
     }
 }
@@ -46,6 +51,18 @@ impl Serialize for DnsOverwrite {
 /// or wrapped in [`DnsOverwrite`] to indicate dns overwrites.
 pub struct InMemoryDns {
     map: Option<HashMap<Domain, Vec<IpAddr>>>,
+    records: Option<HashMap<DnsName, InMemoryRecords>>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Non-address records of a single name, stored in an [`InMemoryDns`].
+struct InMemoryRecords {
+    txt: Vec<Vec<u8>>,
+    srv: Vec<SrvRecord>,
+    mx: Vec<MxRecord>,
+    cname: Option<DnsName>,
+    https: Vec<SvcbRecord>,
+    svcb: Vec<SvcbRecord>,
 }
 
 impl InMemoryDns {
@@ -94,6 +111,79 @@ impl InMemoryDns {
         self.map.get_or_insert_with(HashMap::new).extend(overwrites);
         self
     }
+
+    /// Insert the 'TXT' records for a name.
+    ///
+    /// Existing 'TXT' records of that name will be overwritten.
+    pub fn insert_txt<I: IntoIterator<Item: Into<Vec<u8>>>>(
+        &mut self,
+        name: DnsName,
+        records: I,
+    ) -> &mut Self {
+        self.records_mut(name).txt = records.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Insert the 'SRV' records for a name.
+    ///
+    /// Existing 'SRV' records of that name will be overwritten.
+    pub fn insert_srv(&mut self, name: DnsName, records: Vec<SrvRecord>) -> &mut Self {
+        self.records_mut(name).srv = records;
+        self
+    }
+
+    /// Insert the 'MX' records for a name.
+    ///
+    /// Existing 'MX' records of that name will be overwritten.
+    pub fn insert_mx(&mut self, name: DnsName, records: Vec<MxRecord>) -> &mut Self {
+        self.records_mut(name).mx = records;
+        self
+    }
+
+    /// Insert the 'CNAME' record for a name,
+    /// making it an alias of the given target.
+    ///
+    /// An existing 'CNAME' record of that name will be overwritten.
+    pub fn insert_cname(&mut self, name: DnsName, target: DnsName) -> &mut Self {
+        self.records_mut(name).cname = Some(target);
+        self
+    }
+
+    /// Insert the 'HTTPS' records for a name.
+    ///
+    /// Existing 'HTTPS' records of that name will be overwritten.
+    pub fn insert_https(&mut self, name: DnsName, records: Vec<SvcbRecord>) -> &mut Self {
+        self.records_mut(name).https = records;
+        self
+    }
+
+    /// Insert the 'SVCB' records for a name.
+    ///
+    /// Existing 'SVCB' records of that name will be overwritten.
+    pub fn insert_svcb(&mut self, name: DnsName, records: Vec<SvcbRecord>) -> &mut Self {
+        self.records_mut(name).svcb = records;
+        self
+    }
+
+    fn records_mut(&mut self, name: DnsName) -> &mut InMemoryRecords {
+        self.records
+            .get_or_insert_with(HashMap::new)
+            .entry(name)
+            .or_default()
+    }
+
+    fn lookup_records<T: Clone>(
+        &self,
+        name: &DnsName,
+        select: impl FnOnce(&InMemoryRecords) -> &Vec<T>,
+    ) -> Result<Vec<T>, DomainNotMappedErr> {
+        self.records
+            .as_ref()
+            .and_then(|m| m.get(name))
+            .map(select)
+            .and_then(|records| (!records.is_empty()).then(|| records.clone()))
+            .ok_or(DomainNotMappedErr)
+    }
 }
 
 static_str_error! {
@@ -137,6 +227,76 @@ impl DnsResolver for InMemoryDns {
             })
             .ok_or(DomainNotMappedErr)
     }
+
+    async fn txt_lookup(&self, name: DnsName) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.lookup_records(&name, |records| &records.txt)
+    }
+
+    async fn srv_lookup(&self, name: DnsName) -> Result<Vec<SrvRecord>, Self::Error> {
+        self.lookup_records(&name, |records| &records.srv)
+    }
+
+    async fn mx_lookup(&self, name: DnsName) -> Result<Vec<MxRecord>, Self::Error> {
+        self.lookup_records(&name, |records| &records.mx)
+    }
+
+    async fn cname_lookup(&self, name: DnsName) -> Result<Vec<DnsName>, Self::Error> {
+        let Some(records) = self.records.as_ref() else {
+            return Err(DomainNotMappedErr);
+        };
+
+        let mut chain: Vec<DnsName> = Vec::new();
+        let mut current = &name;
+        while let Some(target) = records.get(current).and_then(|r| r.cname.as_ref()) {
+            if chain.len() >= MAX_CNAME_CHAIN_LEN {
+                break;
+            }
+            chain.push(target.clone());
+            current = target;
+        }
+
+        if chain.is_empty() {
+            return Err(DomainNotMappedErr);
+        }
+        Ok(chain)
+    }
+
+    async fn https_lookup(&self, name: DnsName) -> Result<Vec<SvcbRecord>, Self::Error> {
+        self.lookup_records(&name, |records| &records.https)
+    }
+
+    async fn svcb_lookup(&self, name: DnsName) -> Result<Vec<SvcbRecord>, Self::Error> {
+        self.lookup_records(&name, |records| &records.svcb)
+    }
+
+    /// Unmapped names are reported as having no records,
+    /// such that they are not treated as failed lookups.
+    async fn lookup_with_ttl(
+        &self,
+        name: DnsName,
+        record_type: DnsRecordType,
+    ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
+        let result = match record_type {
+            DnsRecordType::Ipv4 => match Domain::try_from(name) {
+                Ok(domain) => self.ipv4_lookup(domain).await.map(DnsRecords::Ipv4),
+                Err(_) => Err(DomainNotMappedErr),
+            },
+            DnsRecordType::Ipv6 => match Domain::try_from(name) {
+                Ok(domain) => self.ipv6_lookup(domain).await.map(DnsRecords::Ipv6),
+                Err(_) => Err(DomainNotMappedErr),
+            },
+            DnsRecordType::Txt => self.txt_lookup(name).await.map(DnsRecords::Txt),
+            DnsRecordType::Srv => self.srv_lookup(name).await.map(DnsRecords::Srv),
+            DnsRecordType::Mx => self.mx_lookup(name).await.map(DnsRecords::Mx),
+            DnsRecordType::Cname => self.cname_lookup(name).await.map(DnsRecords::Cname),
+            DnsRecordType::Https => self.https_lookup(name).await.map(DnsRecords::Https),
+            DnsRecordType::Svcb => self.svcb_lookup(name).await.map(DnsRecords::Svcb),
+        };
+        Ok((
+            result.unwrap_or_else(|DomainNotMappedErr| DnsRecords::empty(record_type)),
+            None,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -252,6 +412,134 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_in_memory_dns_records() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_txt(DnsName::from_static("example.com"), ["v=spf1 -all"])
+            .insert_srv(
+                DnsName::from_static("_imaps._tcp.example.com"),
+                vec![SrvRecord {
+                    priority: 10,
+                    weight: 5,
+                    port: 993,
+                    target: Domain::from_static("mail.example.com"),
+                }],
+            )
+            .insert_cname(
+                DnsName::from_static("www.example.com"),
+                DnsName::from_static("cdn.example.com"),
+            )
+            .insert_cname(
+                DnsName::from_static("cdn.example.com"),
+                DnsName::from_static("edge.example.net"),
+            )
+            .insert_https(
+                DnsName::from_static("example.com"),
+                vec![SvcbRecord::new(1, DnsName::from_static("example.com"))],
+            );
+
+        assert_eq!(
+            dns.txt_lookup(DnsName::from_static("example.com"))
+                .await
+                .unwrap(),
+            vec![b"v=spf1 -all".to_vec()],
+        );
+        assert_eq!(
+            dns.srv_lookup(DnsName::from_static("_imaps._tcp.example.com"))
+                .await
+                .unwrap()[0]
+                .port,
+            993,
+        );
+        assert_eq!(
+            dns.cname_lookup(DnsName::from_static("www.example.com"))
+                .await
+                .unwrap(),
+            vec![
+                DnsName::from_static("cdn.example.com"),
+                DnsName::from_static("edge.example.net"),
+            ],
+        );
+        assert!(
+            dns.cname_lookup(DnsName::from_static("example.com"))
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            dns.https_lookup(DnsName::from_static("example.com"))
+                .await
+                .unwrap()
+                .len(),
+            1,
+        );
+        assert!(
+            dns.svcb_lookup(DnsName::from_static("example.com"))
+                .await
+                .is_err()
+        );
+        assert!(
+            dns.mx_lookup(DnsName::from_static("example.com"))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_dns_cname_loop() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_cname(DnsName::from_static("a.com"), DnsName::from_static("b.com"))
+            .insert_cname(DnsName::from_static("b.com"), DnsName::from_static("a.com"));
+
+        assert_eq!(
+            dns.cname_lookup(DnsName::from_static("a.com"))
+                .await
+                .unwrap()
+                .len(),
+            MAX_CNAME_CHAIN_LEN,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_dns_lookup_with_ttl() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        )
+        .insert_txt(DnsName::from_static("_dmarc.example.com"), ["v=DMARC1"]);
+
+        assert_eq!(
+            dns.lookup_with_ttl(DnsName::from_static("example.com"), DnsRecordType::Ipv4)
+                .await
+                .unwrap(),
+            (DnsRecords::Ipv4(vec![Ipv4Addr::new(127, 0, 0, 1)]), None),
+        );
+        assert_eq!(
+            dns.lookup_with_ttl(
+                DnsName::from_static("_dmarc.example.com"),
+                DnsRecordType::Txt
+            )
+            .await
+            .unwrap(),
+            (DnsRecords::Txt(vec![b"v=DMARC1".to_vec()]), None),
+        );
+
+        // unmapped names have no records, rather than failing the lookup
+        for (name, record_type) in [
+            ("example.com", DnsRecordType::Ipv6),
+            ("example.com", DnsRecordType::Txt),
+            ("unknown.example.com", DnsRecordType::Ipv4),
+            ("_dmarc.example.com", DnsRecordType::Ipv4),
+        ] {
+            assert_eq!(
+                dns.lookup_with_ttl(DnsName::from_static(name), record_type)
+                    .await
+                    .unwrap(),
+                (DnsRecords::empty(record_type), None),
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_dns_overwrite_deserialize_empty() {
         let dns_overwrite: DnsOverwrite = serde_html_form::from_str("").unwrap();
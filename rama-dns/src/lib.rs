@@ -22,9 +22,18 @@ use rama_net::address::Domain;
 use std::{
     net::{Ipv4Addr, Ipv6Addr},
     sync::Arc,
+    time::Duration,
 };
 
-/// A resolver of domains into IP addresses.
+mod name;
+#[doc(inline)]
+pub use name::DnsName;
+
+mod record;
+#[doc(inline)]
+pub use record::{DnsRecordType, DnsRecords, MxRecord, SrvRecord, SvcbRecord};
+
+/// A resolver of domains into IP addresses and of dns names into other DNS records.
 pub trait DnsResolver: Send + Sync + 'static {
     /// Error returned by the [`DnsResolver`]
     type Error;
@@ -40,39 +49,169 @@ pub trait DnsResolver: Send + Sync + 'static {
         &self,
         domain: Domain,
     ) -> impl Future<Output = Result<Vec<Ipv6Addr>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'TXT' records accessible by this resolver for the given [`DnsName`].
+    ///
+    /// Each item is the data of a single record, with its character-strings concatenated.
+    fn txt_lookup(
+        &self,
+        name: DnsName,
+    ) -> impl Future<Output = Result<Vec<Vec<u8>>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'SRV' records accessible by this resolver for the given [`DnsName`],
+    /// e.g. `_imaps._tcp.example.com`.
+    fn srv_lookup(
+        &self,
+        name: DnsName,
+    ) -> impl Future<Output = Result<Vec<SrvRecord>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'MX' records accessible by this resolver for the given [`DnsName`].
+    fn mx_lookup(
+        &self,
+        name: DnsName,
+    ) -> impl Future<Output = Result<Vec<MxRecord>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'CNAME' chain accessible by this resolver for the given [`DnsName`].
+    ///
+    /// The chain is returned in order of resolution, excluding the given name itself,
+    /// meaning the last [`DnsName`] is the canonical name. An error is returned
+    /// in case the given [`DnsName`] is not an alias.
+    fn cname_lookup(
+        &self,
+        name: DnsName,
+    ) -> impl Future<Output = Result<Vec<DnsName>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'HTTPS' records accessible by this resolver for the given [`DnsName`].
+    ///
+    /// These can be used to discover the ALPN protocols, alternative endpoints
+    /// and ECH configurations of an https origin.
+    fn https_lookup(
+        &self,
+        name: DnsName,
+    ) -> impl Future<Output = Result<Vec<SvcbRecord>, Self::Error>> + Send + '_;
+
+    /// Resolve the 'SVCB' records accessible by this resolver for the given [`DnsName`].
+    fn svcb_lookup(
+        &self,
+        name: DnsName,
+    ) -> impl Future<Output = Result<Vec<SvcbRecord>, Self::Error>> + Send + '_;
+
+    /// Resolve the records of the given [`DnsRecordType`] for the given [`DnsName`],
+    /// together with the time-to-live (TTL) of those records, if known.
+    ///
+    /// Resolvers which can tell a name has no records of the requested type
+    /// (e.g. `NXDOMAIN`) apart from a failed lookup can return empty [`DnsRecords`],
+    /// optionally with a negative TTL.
+    ///
+    /// The default implementation dispatches to the lookup method
+    /// of the requested record type and reports no TTL. Names which
+    /// are not a valid [`Domain`] are reported to have no address records.
+    fn lookup_with_ttl(
+        &self,
+        name: DnsName,
+        record_type: DnsRecordType,
+    ) -> impl Future<Output = Result<(DnsRecords, Option<Duration>), Self::Error>> + Send + '_ {
+        async move {
+            let records = match record_type {
+                DnsRecordType::Ipv4 | DnsRecordType::Ipv6 => {
+                    let Ok(domain) = Domain::try_from(name) else {
+                        return Ok((DnsRecords::empty(record_type), None));
+                    };
+                    if record_type == DnsRecordType::Ipv4 {
+                        DnsRecords::Ipv4(self.ipv4_lookup(domain).await?)
+                    } else {
+                        DnsRecords::Ipv6(self.ipv6_lookup(domain).await?)
+                    }
+                }
+                DnsRecordType::Txt => DnsRecords::Txt(self.txt_lookup(name).await?),
+                DnsRecordType::Srv => DnsRecords::Srv(self.srv_lookup(name).await?),
+                DnsRecordType::Mx => DnsRecords::Mx(self.mx_lookup(name).await?),
+                DnsRecordType::Cname => DnsRecords::Cname(self.cname_lookup(name).await?),
+                DnsRecordType::Https => DnsRecords::Https(self.https_lookup(name).await?),
+                DnsRecordType::Svcb => DnsRecords::Svcb(self.svcb_lookup(name).await?),
+            };
+            Ok((records, None))
+        }
+    }
+}
+
+/// Maximum amount of aliases followed when resolving a 'CNAME' chain,
+/// as a protection against (accidental) loops.
+const MAX_CNAME_CHAIN_LEN: usize = 8;
+
+macro_rules! dns_resolver_deref_impl {
+    ($($method:ident($name:ident: $name_ty:ty): $output:ty),+ $(,)?) => {
+        $(
+            fn $method(
+                &self,
+                $name: $name_ty,
+            ) -> impl Future<Output = Result<$output, Self::Error>> + Send + '_ {
+                (**self).$method($name)
+            }
+        )+
+    };
 }
 
 impl<R: DnsResolver> DnsResolver for Arc<R> {
     type Error = R::Error;
 
-    fn ipv4_lookup(
-        &self,
-        domain: Domain,
-    ) -> impl Future<Output = Result<Vec<Ipv4Addr>, Self::Error>> + Send + '_ {
-        (**self).ipv4_lookup(domain)
+    dns_resolver_deref_impl! {
+        ipv4_lookup(domain: Domain): Vec<Ipv4Addr>,
+        ipv6_lookup(domain: Domain): Vec<Ipv6Addr>,
+        txt_lookup(name: DnsName): Vec<Vec<u8>>,
+        srv_lookup(name: DnsName): Vec<SrvRecord>,
+        mx_lookup(name: DnsName): Vec<MxRecord>,
+        cname_lookup(name: DnsName): Vec<DnsName>,
+        https_lookup(name: DnsName): Vec<SvcbRecord>,
+        svcb_lookup(name: DnsName): Vec<SvcbRecord>,
     }
 
-    fn ipv6_lookup(
+    fn lookup_with_ttl(
         &self,
-        domain: Domain,
-    ) -> impl Future<Output = Result<Vec<Ipv6Addr>, Self::Error>> + Send + '_ {
-        (**self).ipv6_lookup(domain)
+        name: DnsName,
+        record_type: DnsRecordType,
+    ) -> impl Future<Output = Result<(DnsRecords, Option<Duration>), Self::Error>> + Send + '_ {
+        (**self).lookup_with_ttl(name, record_type)
     }
 }
 
+macro_rules! dns_resolver_option_impl {
+    ($($method:ident($name:ident: $name_ty:ty): $output:ty),+ $(,)?) => {
+        $(
+            async fn $method(&self, $name: $name_ty) -> Result<$output, Self::Error> {
+                match self {
+                    Some(d) => d.$method($name).await.map_err(Into::into),
+                    None => Err(DomainNotMappedErr.into()),
+                }
+            }
+        )+
+    };
+}
+
 impl<R: DnsResolver<Error: Into<BoxError>>> DnsResolver for Option<R> {
     type Error = BoxError;
 
-    async fn ipv4_lookup(&self, domain: Domain) -> Result<Vec<Ipv4Addr>, Self::Error> {
-        match self {
-            Some(d) => d.ipv4_lookup(domain).await.map_err(Into::into),
-            None => Err(DomainNotMappedErr.into()),
-        }
+    dns_resolver_option_impl! {
+        ipv4_lookup(domain: Domain): Vec<Ipv4Addr>,
+        ipv6_lookup(domain: Domain): Vec<Ipv6Addr>,
+        txt_lookup(name: DnsName): Vec<Vec<u8>>,
+        srv_lookup(name: DnsName): Vec<SrvRecord>,
+        mx_lookup(name: DnsName): Vec<MxRecord>,
+        cname_lookup(name: DnsName): Vec<DnsName>,
+        https_lookup(name: DnsName): Vec<SvcbRecord>,
+        svcb_lookup(name: DnsName): Vec<SvcbRecord>,
     }
 
-    async fn ipv6_lookup(&self, domain: Domain) -> Result<Vec<Ipv6Addr>, Self::Error> {
+    async fn lookup_with_ttl(
+        &self,
+        name: DnsName,
+        record_type: DnsRecordType,
+    ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
         match self {
-            Some(d) => d.ipv6_lookup(domain).await.map_err(Into::into),
+            Some(d) => d
+                .lookup_with_ttl(name, record_type)
+                .await
+                .map_err(Into::into),
             None => Err(DomainNotMappedErr.into()),
         }
     }
@@ -92,4 +231,28 @@ pub use deny_all::{DenyAllDns, DnsDeniedError};
 
 pub mod chain;
 
+pub mod server;
+
+mod doh;
+#[doc(inline)]
+pub use doh::{DohMethod, DohResolver};
+
+mod dot;
+#[doc(inline)]
+pub use dot::DotResolver;
+
+mod cached;
+#[doc(inline)]
+pub use cached::{CachedDnsResolver, DnsCacheMetrics, DnsRecordsNotFoundError};
+
+mod ech;
+#[doc(inline)]
+pub use ech::{EchConfigLookupLayer, EchConfigLookupService, lookup_ech_config_list};
+
+mod discovery;
+#[doc(inline)]
+pub use discovery::DnsDiscovery;
+
 mod variant;
+
+mod wire;
@@ -0,0 +1,245 @@
+use rama_core::error::{ErrorContext, OpaqueError};
+use rama_net::address::Domain;
+use smol_str::SmolStr;
+use std::fmt;
+
+/// The name of a node in the DNS, e.g. the owner name of a record.
+///
+/// Contrary to a [`Domain`], which is meant to be a hostname,
+/// the labels of a [`DnsName`] can contain underscores,
+/// as is common for the owner names of service records
+/// (e.g. `_imaps._tcp.example.com`) and other non-address records
+/// (e.g. `_dmarc.example.com`).
+///
+/// Any [`Domain`] is a valid [`DnsName`], while the inverse is not true.
+#[derive(Debug, Clone)]
+pub struct DnsName(SmolStr);
+
+impl DnsName {
+    /// The maximum length of a label.
+    const MAX_LABEL_LEN: usize = 63;
+
+    /// The maximum length of a name, excluding the trailing dot.
+    const MAX_NAME_LEN: usize = 253;
+
+    /// Creates a [`DnsName`] at compile time.
+    ///
+    /// # Panics
+    ///
+    /// This function panics at **compile time** when the static string is not a valid name.
+    pub const fn from_static(s: &'static str) -> Self {
+        if !is_valid_name(s.as_bytes()) {
+            panic!("static str is an invalid dns name");
+        }
+        Self(SmolStr::new_static(s))
+    }
+
+    /// Gets the name as reference.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns `true` if this name is a valid [`Domain`] (hostname) as well.
+    pub fn is_domain(&self) -> bool {
+        self.as_str().parse::<Domain>().is_ok()
+    }
+
+    fn normalized(&self) -> &str {
+        self.0.strip_suffix('.').unwrap_or(&self.0)
+    }
+}
+
+impl From<Domain> for DnsName {
+    fn from(domain: Domain) -> Self {
+        // a domain can start with a dot, which is not part of the name
+        Self(SmolStr::new(domain.as_str().trim_start_matches('.')))
+    }
+}
+
+impl TryFrom<DnsName> for Domain {
+    type Error = OpaqueError;
+
+    fn try_from(name: DnsName) -> Result<Self, Self::Error> {
+        name.as_str()
+            .parse()
+            .context("try to convert a dns name into a domain")
+    }
+}
+
+impl TryFrom<&str> for DnsName {
+    type Error = OpaqueError;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        if is_valid_name(name.as_bytes()) {
+            Ok(Self(SmolStr::new(name)))
+        } else {
+            Err(OpaqueError::from_display("invalid dns name"))
+        }
+    }
+}
+
+impl TryFrom<String> for DnsName {
+    type Error = OpaqueError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.as_str().try_into()
+    }
+}
+
+impl std::str::FromStr for DnsName {
+    type Err = OpaqueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl AsRef<str> for DnsName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for DnsName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for DnsName {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized().eq_ignore_ascii_case(other.normalized())
+    }
+}
+
+impl Eq for DnsName {}
+
+impl PartialEq<str> for DnsName {
+    fn eq(&self, other: &str) -> bool {
+        self.normalized()
+            .eq_ignore_ascii_case(other.strip_suffix('.').unwrap_or(other))
+    }
+}
+
+impl PartialEq<&str> for DnsName {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<Domain> for DnsName {
+    fn eq(&self, other: &Domain) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl std::hash::Hash for DnsName {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for b in self.normalized().bytes() {
+            b.to_ascii_lowercase().hash(state);
+        }
+    }
+}
+
+const fn is_valid_label(name: &[u8], start: usize, stop: usize) -> bool {
+    if start >= stop
+        || stop - start > DnsName::MAX_LABEL_LEN
+        || name[start] == b'-'
+        || name[stop - 1] == b'-'
+    {
+        return false;
+    }
+    let mut i = start;
+    while i < stop {
+        let c = name[i];
+        if !c.is_ascii_alphanumeric() && c != b'-' && c != b'_' {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Checks if the name is a valid dns name,
+/// optionally ending with a dot (fully qualified).
+const fn is_valid_name(name: &[u8]) -> bool {
+    let len = if !name.is_empty() && name[name.len() - 1] == b'.' {
+        name.len() - 1
+    } else {
+        name.len()
+    };
+    if len == 0 || len > DnsName::MAX_NAME_LEN {
+        return false;
+    }
+    let mut start = 0;
+    let mut i = 0;
+    while i < len {
+        if name[i] == b'.' {
+            if !is_valid_label(name, start, i) {
+                return false;
+            }
+            start = i + 1;
+        }
+        i += 1;
+    }
+    is_valid_label(name, start, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_name_parse_valid() {
+        for name in [
+            "example.com",
+            "example.com.",
+            "_imaps._tcp.example.com",
+            "_dmarc.example.com",
+            "_8443._https.example.com",
+            "a_b.example.com",
+            "localhost",
+        ] {
+            assert_eq!(DnsName::try_from(name).unwrap(), name, "parse: {name}");
+        }
+    }
+
+    #[test]
+    fn test_dns_name_parse_invalid() {
+        for name in [
+            "",
+            ".",
+            ".example.com",
+            "example..com",
+            "-example.com",
+            "example-.com",
+            "exa mple.com",
+            "example.com..",
+        ] {
+            assert!(DnsName::try_from(name).is_err(), "parse: {name}");
+        }
+    }
+
+    #[test]
+    fn test_dns_name_domain_conversion() {
+        let name = DnsName::from(Domain::from_static("example.com"));
+        assert!(name.is_domain());
+        assert_eq!(Domain::try_from(name).unwrap(), "example.com");
+
+        let name = DnsName::from_static("_imaps._tcp.example.com");
+        assert!(!name.is_domain());
+        assert!(Domain::try_from(name).is_err());
+    }
+
+    #[test]
+    fn test_dns_name_eq() {
+        assert_eq!(
+            DnsName::from_static("_Imaps._TCP.Example.com."),
+            DnsName::from_static("_imaps._tcp.example.com"),
+        );
+        assert_ne!(
+            DnsName::from_static("_imaps._tcp.example.com"),
+            DnsName::from_static("_imap._tcp.example.com"),
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::DnsName;
+use rama_net::{address::Domain, tls::ApplicationProtocol};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A service record ('SRV'), as defined in [RFC 2782].
+///
+/// [RFC 2782]: https://datatracker.ietf.org/doc/html/rfc2782
+pub struct SrvRecord {
+    /// Priority of the target host, lower value means more preferred.
+    pub priority: u16,
+    /// Relative weight for records with the same priority,
+    /// higher value means a higher chance of getting picked.
+    pub weight: u16,
+    /// The port on the target host of this service.
+    pub port: u16,
+    /// The domain name of the target host.
+    pub target: Domain,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A mail exchange record ('MX'), as defined in [RFC 1035].
+///
+/// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.9
+pub struct MxRecord {
+    /// Preference given to this record among others of the same owner,
+    /// lower values are preferred.
+    pub preference: u16,
+    /// The domain name of the host willing to act as a mail exchange.
+    pub exchange: Domain,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A service binding record, either of type 'SVCB' or 'HTTPS',
+/// as defined in [RFC 9460].
+///
+/// Only the service parameters relevant to establishing
+/// a connection are exposed, unknown parameters are ignored.
+///
+/// [RFC 9460]: https://datatracker.ietf.org/doc/html/rfc9460
+pub struct SvcbRecord {
+    /// Priority of this record, lower value means more preferred.
+    ///
+    /// A priority of `0` indicates that this record is in AliasMode,
+    /// see [`SvcbRecord::is_alias`].
+    pub priority: u16,
+    /// The name of either the alias target (AliasMode)
+    /// or the alternative endpoint (ServiceMode).
+    ///
+    /// The special target name `"."` is already resolved
+    /// into the owner name (the queried name).
+    pub target: DnsName,
+    /// The ALPN protocol identifiers supported by this service endpoint.
+    pub alpn: Vec<ApplicationProtocol>,
+    /// If `true` the default ALPN protocol(s) of the scheme are not
+    /// supported by this service endpoint, and only the ones in [`Self::alpn`] are.
+    pub no_default_alpn: bool,
+    /// The alternative port of the service endpoint, if different from the default one.
+    pub port: Option<u16>,
+    /// IPv4 address hints of the service endpoint.
+    pub ipv4_hint: Vec<Ipv4Addr>,
+    /// IPv6 address hints of the service endpoint.
+    pub ipv6_hint: Vec<Ipv6Addr>,
+    /// The encoded `ECHConfigList` which can be used to
+    /// enable Encrypted Client Hello (ECH) for this service endpoint,
+    /// see [`lookup_ech_config_list`].
+    ///
+    /// [`lookup_ech_config_list`]: crate::lookup_ech_config_list
+    pub ech_config_list: Option<Vec<u8>>,
+}
+
+impl SvcbRecord {
+    /// Create a new ServiceMode [`SvcbRecord`] for the given target,
+    /// without any service parameters.
+    pub fn new(priority: u16, target: DnsName) -> Self {
+        Self {
+            priority,
+            target,
+            alpn: Vec::new(),
+            no_default_alpn: false,
+            port: None,
+            ipv4_hint: Vec::new(),
+            ipv6_hint: Vec::new(),
+            ech_config_list: None,
+        }
+    }
+
+    /// Returns `true` if this record is in AliasMode,
+    /// meaning that the owner name is an alias of the [`Self::target`].
+    pub fn is_alias(&self) -> bool {
+        self.priority == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The type of DNS records that can be resolved by a [`DnsResolver`].
+///
+/// [`DnsResolver`]: crate::DnsResolver
+pub enum DnsRecordType {
+    /// 'A' records, resolved into [`Ipv4Addr`]esses.
+    Ipv4,
+    /// 'AAAA' records, resolved into [`Ipv6Addr`]esses.
+    Ipv6,
+    /// 'TXT' records.
+    Txt,
+    /// 'SRV' records, resolved into [`SrvRecord`]s.
+    Srv,
+    /// 'MX' records, resolved into [`MxRecord`]s.
+    Mx,
+    /// 'CNAME' records, resolved into an alias chain.
+    Cname,
+    /// 'HTTPS' records, resolved into [`SvcbRecord`]s.
+    Https,
+    /// 'SVCB' records, resolved into [`SvcbRecord`]s.
+    Svcb,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The records resolved for a single [`DnsRecordType`].
+pub enum DnsRecords {
+    /// Resolved 'A' records.
+    Ipv4(Vec<Ipv4Addr>),
+    /// Resolved 'AAAA' records.
+    Ipv6(Vec<Ipv6Addr>),
+    /// Resolved 'TXT' records.
+    Txt(Vec<Vec<u8>>),
+    /// Resolved 'SRV' records.
+    Srv(Vec<SrvRecord>),
+    /// Resolved 'MX' records.
+    Mx(Vec<MxRecord>),
+    /// Resolved 'CNAME' chain.
+    Cname(Vec<DnsName>),
+    /// Resolved 'HTTPS' records.
+    Https(Vec<SvcbRecord>),
+    /// Resolved 'SVCB' records.
+    Svcb(Vec<SvcbRecord>),
+}
+
+impl DnsRecords {
+    /// Create an empty [`DnsRecords`] for the given [`DnsRecordType`],
+    /// used to indicate that a name has no records of that type.
+    pub fn empty(record_type: DnsRecordType) -> Self {
+        match record_type {
+            DnsRecordType::Ipv4 => Self::Ipv4(Vec::new()),
+            DnsRecordType::Ipv6 => Self::Ipv6(Vec::new()),
+            DnsRecordType::Txt => Self::Txt(Vec::new()),
+            DnsRecordType::Srv => Self::Srv(Vec::new()),
+            DnsRecordType::Mx => Self::Mx(Vec::new()),
+            DnsRecordType::Cname => Self::Cname(Vec::new()),
+            DnsRecordType::Https => Self::Https(Vec::new()),
+            DnsRecordType::Svcb => Self::Svcb(Vec::new()),
+        }
+    }
+
+    /// Returns the [`DnsRecordType`] of these records.
+    pub fn record_type(&self) -> DnsRecordType {
+        match self {
+            Self::Ipv4(_) => DnsRecordType::Ipv4,
+            Self::Ipv6(_) => DnsRecordType::Ipv6,
+            Self::Txt(_) => DnsRecordType::Txt,
+            Self::Srv(_) => DnsRecordType::Srv,
+            Self::Mx(_) => DnsRecordType::Mx,
+            Self::Cname(_) => DnsRecordType::Cname,
+            Self::Https(_) => DnsRecordType::Https,
+            Self::Svcb(_) => DnsRecordType::Svcb,
+        }
+    }
+
+    /// Returns `true` if no records are contained.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Ipv4(records) => records.is_empty(),
+            Self::Ipv6(records) => records.is_empty(),
+            Self::Txt(records) => records.is_empty(),
+            Self::Srv(records) => records.is_empty(),
+            Self::Mx(records) => records.is_empty(),
+            Self::Cname(records) => records.is_empty(),
+            Self::Https(records) | Self::Svcb(records) => records.is_empty(),
+        }
+    }
+}
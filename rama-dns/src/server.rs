@@ -0,0 +1,800 @@
+//! DNS server support, to answer DNS queries using any [`DnsResolver`].
+//!
+//! The [`DnsServer`] can be served over UDP using [`DnsServer::serve_udp`]
+//! and over TCP by serving it as a [`Service`] for incoming (tcp) streams,
+//! e.g. using a `rama::tcp::server::TcpListener`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use rama_dns::{InMemoryDns, server::DnsServer};
+//! use rama_net::address::Domain;
+//! use rama_udp::UdpSocket;
+//! use std::net::Ipv4Addr;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut dns = InMemoryDns::new();
+//! dns.insert_address(Domain::from_static("example.com"), Ipv4Addr::new(127, 0, 0, 1));
+//!
+//! let socket = UdpSocket::bind("127.0.0.1:5353").await.unwrap();
+//! DnsServer::new(dns).serve_udp(socket).await;
+//! # }
+//! ```
+
+use crate::{
+    DnsName, DnsRecordType, DnsRecords, DnsResolver, SvcbRecord,
+    wire::{dns_name_from_name, fqdn_from_dns_name, fqdn_from_domain},
+};
+use hickory_resolver::proto::{
+    op::{Edns, Message, MessageType, OpCode, Query, ResponseCode},
+    rr::{
+        DNSClass, Name, RData, Record, RecordType,
+        rdata::{
+            A, AAAA, CNAME, HTTPS, MX, SRV, TXT,
+            svcb::{Alpn, EchConfigList, IpHint, SVCB, SvcParamKey, SvcParamValue},
+        },
+    },
+};
+use rama_core::{
+    Context, Service,
+    error::{BoxError, ErrorContext, OpaqueError},
+    graceful::ShutdownGuard,
+};
+use rama_net::stream::Stream;
+use rama_udp::UdpSocket;
+use std::{fmt, net::SocketAddr, pin::pin, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+
+/// Maximum size of a DNS message received over UDP.
+const MAX_UDP_MESSAGE_SIZE: usize = 4096;
+
+/// Maximum UDP payload size advertised in EDNS responses,
+/// as recommended by the DNS Flag Day 2020.
+const EDNS_MAX_PAYLOAD: u16 = 1232;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+const DEFAULT_MAX_CONCURRENT_UDP_QUERIES: usize = 1024;
+
+const DEFAULT_TCP_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A DNS server, answering DNS queries using the wrapped [`DnsResolver`].
+///
+/// Queries for the record types supported by [`DnsRecordType`] are answered
+/// using [`DnsResolver::lookup_with_ttl`], other queries are answered with `NOTIMP`.
+/// Queries for which the resolver has no records are answered with
+/// an empty answer section (`NOERROR`), given the resolver cannot tell
+/// whether the name exists at all, while resolver errors result in a `SERVFAIL` response.
+///
+/// See [the module docs](self) for more information.
+pub struct DnsServer<R> {
+    resolver: Arc<R>,
+    default_ttl: Duration,
+    max_concurrent_udp_queries: usize,
+    tcp_idle_timeout: Duration,
+}
+
+impl<R: fmt::Debug> fmt::Debug for DnsServer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsServer")
+            .field("resolver", &self.resolver)
+            .field("default_ttl", &self.default_ttl)
+            .field(
+                "max_concurrent_udp_queries",
+                &self.max_concurrent_udp_queries,
+            )
+            .field("tcp_idle_timeout", &self.tcp_idle_timeout)
+            .finish()
+    }
+}
+
+impl<R> Clone for DnsServer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            resolver: self.resolver.clone(),
+            default_ttl: self.default_ttl,
+            max_concurrent_udp_queries: self.max_concurrent_udp_queries,
+            tcp_idle_timeout: self.tcp_idle_timeout,
+        }
+    }
+}
+
+impl<R> DnsServer<R> {
+    /// Create a new [`DnsServer`] answering queries using the given [`DnsResolver`].
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver: Arc::new(resolver),
+            default_ttl: DEFAULT_TTL,
+            max_concurrent_udp_queries: DEFAULT_MAX_CONCURRENT_UDP_QUERIES,
+            tcp_idle_timeout: DEFAULT_TCP_IDLE_TIMEOUT,
+        }
+    }
+
+    /// Set the TTL of answers for which the resolver
+    /// did not report a TTL (default: 60 seconds).
+    pub fn set_default_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Replace the TTL of answers for which the resolver
+    /// did not report a TTL (default: 60 seconds).
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Set the maximum number of udp queries answered concurrently
+    /// per served socket (default: 1024).
+    ///
+    /// Queries received while this limit is reached are dropped,
+    /// leaving it up to the client to retry.
+    pub fn set_max_concurrent_udp_queries(&mut self, max: usize) -> &mut Self {
+        self.max_concurrent_udp_queries = max;
+        self
+    }
+
+    /// Replace the maximum number of udp queries answered concurrently
+    /// per served socket (default: 1024).
+    ///
+    /// Queries received while this limit is reached are dropped,
+    /// leaving it up to the client to retry.
+    pub fn with_max_concurrent_udp_queries(mut self, max: usize) -> Self {
+        self.max_concurrent_udp_queries = max;
+        self
+    }
+
+    /// Set the duration after which a stream (e.g. TCP) connection
+    /// is closed in case no (complete) query is received (default: 10 seconds).
+    pub fn set_tcp_idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.tcp_idle_timeout = timeout;
+        self
+    }
+
+    /// Replace the duration after which a stream (e.g. TCP) connection
+    /// is closed in case no (complete) query is received (default: 10 seconds).
+    pub fn with_tcp_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.tcp_idle_timeout = timeout;
+        self
+    }
+}
+
+impl<R: DnsResolver<Error: Into<BoxError>>> DnsServer<R> {
+    /// Answer a single DNS query message, in wire format.
+    ///
+    /// `None` is returned for messages that cannot be answered,
+    /// such as responses or messages too short to contain a header.
+    ///
+    /// No size limit is applied to the response, which is fine for stream transports
+    /// such as TCP. Responses served by [`Self::serve_udp`] are truncated when needed.
+    pub async fn respond(&self, request: &[u8]) -> Option<Vec<u8>> {
+        let response = self.handle_message(request).await?;
+        encode_message(&response)
+    }
+
+    /// Serve DNS queries received on the given [`UdpSocket`].
+    ///
+    /// Each query is answered in its own task, bounded by
+    /// [`Self::set_max_concurrent_udp_queries`]. This method only returns
+    /// in case the socket fails to receive any more datagrams.
+    pub async fn serve_udp(&self, socket: UdpSocket) {
+        let socket = Arc::new(socket);
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_udp_queries));
+        let mut buf = vec![0u8; MAX_UDP_MESSAGE_SIZE];
+        loop {
+            let (request, peer) = match recv_udp_query(&socket, &mut buf).await {
+                Ok(query) => query,
+                Err(err) => {
+                    tracing::debug!(%err, "dns server: stop serving udp socket");
+                    return;
+                }
+            };
+            let Some(permit) = try_acquire_udp_permit(&semaphore, peer) else {
+                continue;
+            };
+            let server = self.clone();
+            let socket = socket.clone();
+            tokio::spawn(async move {
+                server.answer_udp(&socket, request, peer).await;
+                drop(permit);
+            });
+        }
+    }
+
+    /// Serve DNS queries received on the given [`UdpSocket`],
+    /// until the given [`ShutdownGuard`] is cancelled.
+    ///
+    /// See [`Self::serve_udp`] for more information.
+    pub async fn serve_udp_graceful(&self, guard: ShutdownGuard, socket: UdpSocket) {
+        let socket = Arc::new(socket);
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_udp_queries));
+        let mut buf = vec![0u8; MAX_UDP_MESSAGE_SIZE];
+        let mut cancelled_fut = pin!(guard.cancelled());
+        loop {
+            tokio::select! {
+                _ = cancelled_fut.as_mut() => {
+                    tracing::trace!("signal received: stop dns udp server");
+                    return;
+                }
+                result = recv_udp_query(&socket, &mut buf) => {
+                    let (request, peer) = match result {
+                        Ok(query) => query,
+                        Err(err) => {
+                            tracing::debug!(%err, "dns server: stop serving udp socket");
+                            return;
+                        }
+                    };
+                    let Some(permit) = try_acquire_udp_permit(&semaphore, peer) else {
+                        continue;
+                    };
+                    let server = self.clone();
+                    let socket = socket.clone();
+                    guard.spawn_task(async move {
+                        server.answer_udp(&socket, request, peer).await;
+                        drop(permit);
+                    });
+                }
+            }
+        }
+    }
+
+    async fn answer_udp(&self, socket: &UdpSocket, request: Vec<u8>, peer: SocketAddr) {
+        let Some(mut response) = self.handle_message(&request).await else {
+            return;
+        };
+        let max_size = Message::from_vec(&request)
+            .map(|request| request.max_payload().min(EDNS_MAX_PAYLOAD))
+            .unwrap_or(512) as usize;
+
+        let Some(mut data) = encode_message(&response) else {
+            return;
+        };
+        if data.len() > max_size {
+            // let the client retry over TCP
+            response.take_answers();
+            response.set_truncated(true);
+            let Some(truncated) = encode_message(&response) else {
+                return;
+            };
+            data = truncated;
+        }
+
+        if let Err(err) = socket.send_to(&data, peer).await {
+            tracing::debug!(%err, %peer, "dns server: failed to send udp response");
+        }
+    }
+
+    async fn handle_message(&self, request: &[u8]) -> Option<Message> {
+        let request = match Message::from_vec(request) {
+            Ok(request) => request,
+            Err(err) => {
+                tracing::debug!(%err, "dns server: received invalid dns message");
+                let id = u16::from_be_bytes([*request.first()?, *request.get(1)?]);
+                let mut response = Message::new();
+                response
+                    .set_id(id)
+                    .set_message_type(MessageType::Response)
+                    .set_response_code(ResponseCode::FormErr);
+                return Some(response);
+            }
+        };
+        if request.message_type() != MessageType::Query {
+            return None;
+        }
+
+        let mut response = Message::new();
+        response
+            .set_id(request.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(request.op_code())
+            .set_recursion_desired(request.recursion_desired())
+            .set_recursion_available(true)
+            .add_queries(request.queries().iter().cloned());
+        if request.extensions().is_some() {
+            let mut edns = Edns::new();
+            edns.set_max_payload(EDNS_MAX_PAYLOAD);
+            response.set_edns(edns);
+        }
+
+        let query = match request.queries() {
+            [query] if request.op_code() == OpCode::Query => query,
+            [_] => {
+                response.set_response_code(ResponseCode::NotImp);
+                return Some(response);
+            }
+            _ => {
+                response.set_response_code(ResponseCode::FormErr);
+                return Some(response);
+            }
+        };
+
+        match self.answer_query(query).await {
+            Ok(answers) => {
+                response.add_answers(answers);
+            }
+            Err(response_code) => {
+                response.set_response_code(response_code);
+            }
+        }
+        Some(response)
+    }
+
+    async fn answer_query(&self, query: &Query) -> Result<Vec<Record>, ResponseCode> {
+        let record_type = match query.query_type() {
+            RecordType::A => DnsRecordType::Ipv4,
+            RecordType::AAAA => DnsRecordType::Ipv6,
+            RecordType::TXT => DnsRecordType::Txt,
+            RecordType::SRV => DnsRecordType::Srv,
+            RecordType::MX => DnsRecordType::Mx,
+            RecordType::CNAME => DnsRecordType::Cname,
+            RecordType::HTTPS => DnsRecordType::Https,
+            RecordType::SVCB => DnsRecordType::Svcb,
+            _ => return Err(ResponseCode::NotImp),
+        };
+        if query.query_class() != DNSClass::IN {
+            return Err(ResponseCode::NotImp);
+        }
+        let name = dns_name_from_name(query.name()).map_err(|_| ResponseCode::FormErr)?;
+
+        let (records, ttl) = match self
+            .resolver
+            .lookup_with_ttl(name.clone(), record_type)
+            .await
+        {
+            Ok(result) => result,
+            Err(err) => {
+                let err = err.into();
+                tracing::debug!(%name, ?record_type, %err, "dns server: failed to resolve query");
+                return Err(ResponseCode::ServFail);
+            }
+        };
+        if records.is_empty() {
+            // NODATA: the name might still have records of other types
+            return Ok(Vec::new());
+        }
+
+        let ttl = ttl
+            .unwrap_or(self.default_ttl)
+            .as_secs()
+            .min(u32::MAX as u64) as u32;
+        answer_records(query.name(), &name, records, ttl).map_err(|err| {
+            tracing::debug!(%name, ?record_type, %err, "dns server: failed to encode records");
+            ResponseCode::ServFail
+        })
+    }
+}
+
+impl<R, State, IO> Service<State, IO> for DnsServer<R>
+where
+    R: DnsResolver<Error: Into<BoxError>>,
+    State: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+{
+    type Response = ();
+    type Error = BoxError;
+
+    /// Serve DNS queries received over a stream (e.g. TCP),
+    /// each message prefixed by its length as a two byte integer.
+    ///
+    /// The stream is closed once no complete query is received
+    /// within the configured [idle timeout](DnsServer::set_tcp_idle_timeout).
+    async fn serve(
+        &self,
+        _ctx: Context<State>,
+        mut stream: IO,
+    ) -> Result<Self::Response, Self::Error> {
+        loop {
+            let Ok(request) =
+                tokio::time::timeout(self.tcp_idle_timeout, read_stream_query(&mut stream)).await
+            else {
+                tracing::trace!("dns server: close idle stream");
+                return Ok(());
+            };
+            let Some(request) = request? else {
+                return Ok(());
+            };
+
+            let Some(response) = self.respond(&request).await else {
+                continue;
+            };
+            let len = u16::try_from(response.len()).context("dns response too large")?;
+            stream.write_u16(len).await.context("write dns response")?;
+            stream
+                .write_all(&response)
+                .await
+                .context("write dns response")?;
+            stream.flush().await.context("flush dns response")?;
+        }
+    }
+}
+
+/// Read a single length-prefixed query from the stream,
+/// returning `None` in case the stream was closed in between queries.
+async fn read_stream_query<IO: Stream + Unpin>(
+    stream: &mut IO,
+) -> Result<Option<Vec<u8>>, BoxError> {
+    let len = match stream.read_u16().await {
+        Ok(len) => len,
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut request = vec![0u8; len as usize];
+    stream
+        .read_exact(&mut request)
+        .await
+        .context("read dns query")?;
+    Ok(Some(request))
+}
+
+fn try_acquire_udp_permit(
+    semaphore: &Arc<Semaphore>,
+    peer: SocketAddr,
+) -> Option<OwnedSemaphorePermit> {
+    match semaphore.clone().try_acquire_owned() {
+        Ok(permit) => Some(permit),
+        Err(_) => {
+            tracing::debug!(%peer, "dns server: too many concurrent udp queries, drop query");
+            None
+        }
+    }
+}
+
+async fn recv_udp_query(
+    socket: &UdpSocket,
+    buf: &mut [u8],
+) -> std::io::Result<(Vec<u8>, SocketAddr)> {
+    loop {
+        match socket.recv_from(buf).await {
+            Ok((n, peer)) => return Ok((buf[..n].to_vec(), peer.into())),
+            // e.g. ICMP port unreachable of a previous response on some platforms
+            Err(err) if rama_net::conn::is_connection_error(&err) => {
+                tracing::trace!(%err, "dns server: ignore udp connection error");
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn encode_message(message: &Message) -> Option<Vec<u8>> {
+    message
+        .to_vec()
+        .inspect_err(|err| tracing::debug!(%err, "dns server: failed to encode dns message"))
+        .ok()
+}
+
+fn answer_records(
+    name: &Name,
+    owner: &DnsName,
+    records: DnsRecords,
+    ttl: u32,
+) -> Result<Vec<Record>, OpaqueError> {
+    let record = |rdata| Record::from_rdata(name.clone(), ttl, rdata);
+    Ok(match records {
+        DnsRecords::Ipv4(ips) => ips.into_iter().map(|ip| record(RData::A(A(ip)))).collect(),
+        DnsRecords::Ipv6(ips) => ips
+            .into_iter()
+            .map(|ip| record(RData::AAAA(AAAA(ip))))
+            .collect(),
+        DnsRecords::Txt(txts) => txts
+            .iter()
+            // character-strings are limited to 255 bytes each
+            .map(|txt| record(RData::TXT(TXT::from_bytes(txt.chunks(255).collect()))))
+            .collect(),
+        DnsRecords::Srv(srvs) => srvs
+            .into_iter()
+            .map(|srv| {
+                let target = fqdn_from_domain(srv.target)?;
+                Ok(record(RData::SRV(SRV::new(
+                    srv.priority,
+                    srv.weight,
+                    srv.port,
+                    target,
+                ))))
+            })
+            .collect::<Result<_, OpaqueError>>()?,
+        DnsRecords::Mx(mxs) => mxs
+            .into_iter()
+            .map(|mx| {
+                let exchange = fqdn_from_domain(mx.exchange)?;
+                Ok(record(RData::MX(MX::new(mx.preference, exchange))))
+            })
+            .collect::<Result<_, OpaqueError>>()?,
+        DnsRecords::Cname(chain) => {
+            let mut alias = name.clone();
+            let mut answers = Vec::with_capacity(chain.len());
+            for target in chain {
+                let target = fqdn_from_dns_name(&target)?;
+                answers.push(Record::from_rdata(
+                    alias,
+                    ttl,
+                    RData::CNAME(CNAME(target.clone())),
+                ));
+                alias = target;
+            }
+            answers
+        }
+        DnsRecords::Https(svcbs) => svcbs
+            .into_iter()
+            .map(|svcb| Ok(record(RData::HTTPS(HTTPS(svcb_from_record(owner, svcb)?)))))
+            .collect::<Result<_, OpaqueError>>()?,
+        DnsRecords::Svcb(svcbs) => svcbs
+            .into_iter()
+            .map(|svcb| Ok(record(RData::SVCB(svcb_from_record(owner, svcb)?))))
+            .collect::<Result<_, OpaqueError>>()?,
+    })
+}
+
+fn svcb_from_record(owner: &DnsName, record: SvcbRecord) -> Result<SVCB, OpaqueError> {
+    let target = if &record.target == owner {
+        Name::root()
+    } else {
+        fqdn_from_dns_name(&record.target)?
+    };
+
+    // service parameters have to be in strictly increasing key order
+    let mut params = Vec::new();
+    if !record.alpn.is_empty() {
+        let alpn = record
+            .alpn
+            .iter()
+            .map(|proto| String::from_utf8_lossy(proto.as_bytes()).into_owned())
+            .collect();
+        params.push((SvcParamKey::Alpn, SvcParamValue::Alpn(Alpn(alpn))));
+    }
+    if record.no_default_alpn {
+        params.push((SvcParamKey::NoDefaultAlpn, SvcParamValue::NoDefaultAlpn));
+    }
+    if let Some(port) = record.port {
+        params.push((SvcParamKey::Port, SvcParamValue::Port(port)));
+    }
+    if !record.ipv4_hint.is_empty() {
+        let hint = record.ipv4_hint.into_iter().map(A).collect();
+        params.push((SvcParamKey::Ipv4Hint, SvcParamValue::Ipv4Hint(IpHint(hint))));
+    }
+    if let Some(ech) = record.ech_config_list {
+        params.push((
+            SvcParamKey::EchConfigList,
+            SvcParamValue::EchConfigList(EchConfigList(ech)),
+        ));
+    }
+    if !record.ipv6_hint.is_empty() {
+        let hint = record.ipv6_hint.into_iter().map(AAAA).collect();
+        params.push((SvcParamKey::Ipv6Hint, SvcParamValue::Ipv6Hint(IpHint(hint))));
+    }
+
+    Ok(SVCB::new(record.priority, target, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HickoryDns, InMemoryDns, MxRecord, SrvRecord, hickory::config};
+    use rama_net::address::Domain;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    async fn spawn_udp_dns_server(dns: InMemoryDns) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move { DnsServer::new(dns).serve_udp(socket).await });
+        addr
+    }
+
+    fn hickory_client(addr: SocketAddr) -> HickoryDns {
+        let mut resolver_config = config::ResolverConfig::new();
+        resolver_config.add_name_server(config::NameServerConfig::new(
+            addr,
+            hickory_resolver::proto::xfer::Protocol::Udp,
+        ));
+        let mut options = config::ResolverOpts::default();
+        options.cache_size = 0;
+        options.attempts = 0;
+        HickoryDns::builder()
+            .with_config(resolver_config)
+            .with_options(options)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_dns_server_udp() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_addresses(
+            Domain::from_static("example.com"),
+            [
+                IpAddr::from(Ipv4Addr::new(127, 0, 0, 1)),
+                IpAddr::from(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)),
+            ],
+        )
+        .insert_txt(DnsName::from_static("example.com"), ["hello".repeat(60)])
+        .insert_mx(
+            DnsName::from_static("example.com"),
+            vec![MxRecord {
+                preference: 10,
+                exchange: Domain::from_static("mail.example.com"),
+            }],
+        )
+        .insert_srv(
+            DnsName::from_static("_imaps._tcp.example.com"),
+            vec![SrvRecord {
+                priority: 10,
+                weight: 5,
+                port: 993,
+                target: Domain::from_static("mail.example.com"),
+            }],
+        )
+        .insert_https(DnsName::from_static("example.com"), {
+            let mut record = SvcbRecord::new(1, DnsName::from_static("example.com"));
+            record.alpn = vec![
+                rama_net::tls::ApplicationProtocol::HTTP_3,
+                rama_net::tls::ApplicationProtocol::HTTP_2,
+            ];
+            record.port = Some(8443);
+            record.ipv4_hint = vec![Ipv4Addr::new(127, 0, 0, 2)];
+            vec![record]
+        });
+
+        let client = hickory_client(spawn_udp_dns_server(dns).await);
+        let domain = Domain::from_static("example.com");
+        let name = DnsName::from_static("example.com");
+
+        assert_eq!(
+            client.ipv4_lookup(domain.clone()).await.unwrap(),
+            vec![Ipv4Addr::new(127, 0, 0, 1)]
+        );
+        assert_eq!(
+            client.ipv6_lookup(domain).await.unwrap(),
+            vec![Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)]
+        );
+        assert_eq!(
+            client.txt_lookup(name.clone()).await.unwrap(),
+            vec!["hello".repeat(60).into_bytes()]
+        );
+        assert_eq!(
+            client.mx_lookup(name.clone()).await.unwrap()[0].exchange,
+            Domain::from_static("mail.example.com")
+        );
+        assert_eq!(
+            client
+                .srv_lookup(DnsName::from_static("_imaps._tcp.example.com"))
+                .await
+                .unwrap()[0]
+                .port,
+            993
+        );
+
+        let https = client.https_lookup(name.clone()).await.unwrap();
+        assert_eq!(https.len(), 1);
+        assert_eq!(https[0].target, name);
+        assert_eq!(https[0].port, Some(8443));
+        assert_eq!(
+            https[0].alpn,
+            vec![
+                rama_net::tls::ApplicationProtocol::HTTP_3,
+                rama_net::tls::ApplicationProtocol::HTTP_2,
+            ]
+        );
+        assert_eq!(https[0].ipv4_hint, vec![Ipv4Addr::new(127, 0, 0, 2)]);
+
+        let (records, _) = client
+            .lookup_with_ttl(
+                DnsName::from_static("unknown.example.com"),
+                DnsRecordType::Ipv4,
+            )
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dns_server_tcp() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        );
+        let server = DnsServer::new(dns);
+
+        let mut request = Message::new();
+        request
+            .set_id(42)
+            .set_recursion_desired(true)
+            .add_query(Query::query(
+                Name::from_ascii("example.com.").unwrap(),
+                RecordType::A,
+            ));
+        let request = request.to_vec().unwrap();
+
+        let (mut client, stream) = tokio::io::duplex(1024);
+        let server_task =
+            tokio::spawn(async move { server.serve(Context::default(), stream).await });
+
+        for _ in 0..2 {
+            client.write_u16(request.len() as u16).await.unwrap();
+            client.write_all(&request).await.unwrap();
+            let len = client.read_u16().await.unwrap();
+            let mut response = vec![0u8; len as usize];
+            client.read_exact(&mut response).await.unwrap();
+
+            let response = Message::from_vec(&response).unwrap();
+            assert_eq!(response.id(), 42);
+            assert_eq!(response.response_code(), ResponseCode::NoError);
+            assert_eq!(
+                response.answers()[0].data(),
+                &RData::A(A(Ipv4Addr::new(127, 0, 0, 1)))
+            );
+        }
+
+        drop(client);
+        server_task.await.unwrap().unwrap();
+    }
+
+    fn encode_query(name: &str, record_type: RecordType) -> Vec<u8> {
+        let mut request = Message::new();
+        request
+            .set_id(42)
+            .set_recursion_desired(true)
+            .add_query(Query::query(Name::from_ascii(name).unwrap(), record_type));
+        request.to_vec().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dns_server_no_data() {
+        let mut dns = InMemoryDns::new();
+        dns.insert_address(
+            Domain::from_static("example.com"),
+            Ipv4Addr::new(127, 0, 0, 1),
+        );
+        let server = DnsServer::new(dns);
+
+        for name in ["example.com.", "unknown.example.com."] {
+            let response = server
+                .respond(&encode_query(name, RecordType::AAAA))
+                .await
+                .unwrap();
+            let response = Message::from_vec(&response).unwrap();
+            assert_eq!(response.response_code(), ResponseCode::NoError, "{name}");
+            assert!(response.answers().is_empty(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dns_server_tcp_idle_timeout() {
+        let server =
+            DnsServer::new(InMemoryDns::new()).with_tcp_idle_timeout(Duration::from_millis(10));
+
+        let (_client, stream) = tokio::io::duplex(1024);
+        server.serve(Context::default(), stream).await.unwrap();
+
+        // an incomplete query is considered idle as well
+        let (mut client, stream) = tokio::io::duplex(1024);
+        client.write_u16(32).await.unwrap();
+        server.serve(Context::default(), stream).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dns_server_udp_max_concurrent_queries() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            DnsServer::new(InMemoryDns::new())
+                .with_max_concurrent_udp_queries(0)
+                .serve_udp(socket)
+                .await
+        });
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client
+            .send_to(&encode_query("example.com.", RecordType::A), addr)
+            .await
+            .unwrap();
+        let mut buf = [0u8; 512];
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), client.recv_from(&mut buf))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,6 +1,9 @@
-use crate::DnsResolver;
+use crate::{DnsName, DnsRecordType, DnsRecords, DnsResolver, MxRecord, SrvRecord, SvcbRecord};
 use rama_net::address::Domain;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
 
 macro_rules! impl_dns_resolver_either_either {
     ($id:ident, $($param:ident),+ $(,)?) => {
@@ -10,26 +13,23 @@ macro_rules! impl_dns_resolver_either_either {
         {
             type Error = ::rama_core::error::BoxError;
 
-            async fn ipv4_lookup(
-                &self,
-                domain: Domain,
-            ) -> Result<Vec<Ipv4Addr>, Self::Error>{
-                match self {
-                    $(
-                        ::rama_core::combinators::$id::$param(d) => d.ipv4_lookup(domain)
-                            .await
-                            .map_err(Into::into),
-                    )+
-                }
-            }
+            impl_dns_resolver_either_either!(@lookup $id, ipv4_lookup(Domain), Vec<Ipv4Addr>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, ipv6_lookup(Domain), Vec<Ipv6Addr>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, txt_lookup(DnsName), Vec<Vec<u8>>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, srv_lookup(DnsName), Vec<SrvRecord>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, mx_lookup(DnsName), Vec<MxRecord>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, cname_lookup(DnsName), Vec<DnsName>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, https_lookup(DnsName), Vec<SvcbRecord>, $($param),+);
+            impl_dns_resolver_either_either!(@lookup $id, svcb_lookup(DnsName), Vec<SvcbRecord>, $($param),+);
 
-            async fn ipv6_lookup(
+            async fn lookup_with_ttl(
                 &self,
-                domain: Domain,
-            ) -> Result<Vec<Ipv6Addr>, Self::Error> {
+                name: DnsName,
+                record_type: DnsRecordType,
+            ) -> Result<(DnsRecords, Option<Duration>), Self::Error> {
                 match self {
                     $(
-                        ::rama_core::combinators::$id::$param(d) => d.ipv6_lookup(domain)
+                        ::rama_core::combinators::$id::$param(d) => d.lookup_with_ttl(name, record_type)
                             .await
                             .map_err(Into::into),
                     )+
@@ -37,18 +37,42 @@ macro_rules! impl_dns_resolver_either_either {
             }
         }
     };
+    (@lookup $id:ident, $method:ident($name_ty:ty), $output:ty, $($param:ident),+) => {
+        async fn $method(
+            &self,
+            name: $name_ty,
+        ) -> Result<$output, Self::Error> {
+            match self {
+                $(
+                    ::rama_core::combinators::$id::$param(d) => d.$method(name)
+                        .await
+                        .map_err(Into::into),
+                )+
+            }
+        }
+    };
 }
 
 rama_core::combinators::impl_either!(impl_dns_resolver_either_either);
 
 #[cfg(test)]
 mod tests {
-    use crate::DnsResolver;
+    use crate::{DnsName, DnsResolver, MxRecord, SrvRecord, SvcbRecord};
     use rama_core::combinators::Either;
     use rama_core::error::BoxError;
     use rama_net::address::Domain;
     use std::net::{Ipv4Addr, Ipv6Addr};
 
+    macro_rules! mock_unsupported_lookups {
+        ($($method:ident: $output:ty),+ $(,)?) => {
+            $(
+                async fn $method(&self, _name: DnsName) -> Result<$output, Self::Error> {
+                    Err(BoxError::from("unsupported"))
+                }
+            )+
+        };
+    }
+
     // Mock DNS resolvers for testing
     struct MockResolver1;
     struct MockResolver2;
@@ -69,6 +93,15 @@ mod tests {
         ) -> impl Future<Output = Result<Vec<Ipv6Addr>, Self::Error>> {
             std::future::ready(Ok(vec![Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)]))
         }
+
+        mock_unsupported_lookups! {
+            txt_lookup: Vec<Vec<u8>>,
+            srv_lookup: Vec<SrvRecord>,
+            mx_lookup: Vec<MxRecord>,
+            cname_lookup: Vec<DnsName>,
+            https_lookup: Vec<SvcbRecord>,
+            svcb_lookup: Vec<SvcbRecord>,
+        }
     }
 
     impl DnsResolver for MockResolver2 {
@@ -87,6 +120,15 @@ mod tests {
         ) -> impl Future<Output = Result<Vec<Ipv6Addr>, Self::Error>> + Send + '_ {
             std::future::ready(Ok(vec![Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 2)]))
         }
+
+        mock_unsupported_lookups! {
+            txt_lookup: Vec<Vec<u8>>,
+            srv_lookup: Vec<SrvRecord>,
+            mx_lookup: Vec<MxRecord>,
+            cname_lookup: Vec<DnsName>,
+            https_lookup: Vec<SvcbRecord>,
+            svcb_lookup: Vec<SvcbRecord>,
+        }
     }
 
     #[tokio::test]
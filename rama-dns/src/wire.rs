@@ -0,0 +1,365 @@
+//! Shared utilities to encode and decode DNS messages in wire format,
+//! using the [`hickory_resolver::proto`] crate.
+
+use crate::{
+    DnsName, DnsRecordType, DnsRecords, DnsRecordsNotFoundError, MAX_CNAME_CHAIN_LEN, MxRecord,
+    SrvRecord, SvcbRecord,
+};
+use hickory_resolver::proto::{
+    op::{Message, MessageType, Query, ResponseCode},
+    rr::{
+        DNSClass, Name, RData, RecordType,
+        rdata::{
+            A, AAAA, CNAME, HTTPS,
+            svcb::{SVCB, SvcParamValue},
+        },
+    },
+};
+use rama_core::error::{ErrorContext, OpaqueError};
+use rama_net::{address::Domain, tls::ApplicationProtocol};
+use std::time::Duration;
+
+impl From<DnsRecordType> for RecordType {
+    fn from(record_type: DnsRecordType) -> Self {
+        match record_type {
+            DnsRecordType::Ipv4 => Self::A,
+            DnsRecordType::Ipv6 => Self::AAAA,
+            DnsRecordType::Txt => Self::TXT,
+            DnsRecordType::Srv => Self::SRV,
+            DnsRecordType::Mx => Self::MX,
+            DnsRecordType::Cname => Self::CNAME,
+            DnsRecordType::Https => Self::HTTPS,
+            DnsRecordType::Svcb => Self::SVCB,
+        }
+    }
+}
+
+/// A transport able to exchange a DNS query for a DNS response, both in wire format.
+pub(crate) trait DnsExchange: Send + Sync + 'static {
+    fn exchange(
+        &self,
+        query: Vec<u8>,
+    ) -> impl Future<Output = Result<Vec<u8>, OpaqueError>> + Send + '_;
+}
+
+/// Resolve the records of the given [`DnsRecordType`] using a [`DnsExchange`],
+/// following the chain in case of 'CNAME' records.
+pub(crate) async fn exchange_lookup<T: DnsExchange>(
+    transport: &T,
+    mut name: DnsName,
+    record_type: DnsRecordType,
+) -> Result<(DnsRecords, Option<Duration>), OpaqueError> {
+    if record_type != DnsRecordType::Cname {
+        let query = encode_query(&name, record_type)?;
+        let response = transport.exchange(query).await?;
+        return decode_response(&name, record_type, &response);
+    }
+
+    let mut chain = Vec::new();
+    let mut ttl: Option<Duration> = None;
+    while chain.len() < MAX_CNAME_CHAIN_LEN {
+        let query = encode_query(&name, DnsRecordType::Cname)?;
+        let response = transport.exchange(query).await?;
+        let (records, record_ttl) = decode_response(&name, DnsRecordType::Cname, &response)?;
+        let DnsRecords::Cname(targets) = records else {
+            unreachable!("cname records decoded")
+        };
+        let Some(target) = targets.into_iter().next() else {
+            if chain.is_empty() {
+                return Ok((DnsRecords::Cname(chain), record_ttl));
+            }
+            break;
+        };
+        ttl = min_ttl(ttl, record_ttl);
+        chain.push(target.clone());
+        name = target;
+    }
+    Ok((DnsRecords::Cname(chain), ttl))
+}
+
+/// Implements the [`DnsResolver`] methods for a [`DnsExchange`].
+///
+/// [`DnsResolver`]: crate::DnsResolver
+macro_rules! dns_exchange_resolver_impl {
+    () => {
+        dns_exchange_resolver_impl! {
+            ipv4_lookup(rama_net::address::Domain): Ipv4 => Vec<std::net::Ipv4Addr>,
+            ipv6_lookup(rama_net::address::Domain): Ipv6 => Vec<std::net::Ipv6Addr>,
+            txt_lookup($crate::DnsName): Txt => Vec<Vec<u8>>,
+            srv_lookup($crate::DnsName): Srv => Vec<$crate::SrvRecord>,
+            mx_lookup($crate::DnsName): Mx => Vec<$crate::MxRecord>,
+            cname_lookup($crate::DnsName): Cname => Vec<$crate::DnsName>,
+            https_lookup($crate::DnsName): Https => Vec<$crate::SvcbRecord>,
+            svcb_lookup($crate::DnsName): Svcb => Vec<$crate::SvcbRecord>,
+        }
+
+        async fn lookup_with_ttl(
+            &self,
+            name: $crate::DnsName,
+            record_type: $crate::DnsRecordType,
+        ) -> Result<($crate::DnsRecords, Option<std::time::Duration>), Self::Error> {
+            $crate::wire::exchange_lookup(self, name, record_type).await
+        }
+    };
+    ($($method:ident($name_ty:ty): $variant:ident => $output:ty),+ $(,)?) => {
+        $(
+            async fn $method(&self, name: $name_ty) -> Result<$output, Self::Error> {
+                let (records, _) = $crate::wire::exchange_lookup(
+                    self,
+                    name.into(),
+                    $crate::DnsRecordType::$variant,
+                )
+                .await?;
+                match records {
+                    $crate::DnsRecords::$variant(records) if !records.is_empty() => Ok(records),
+                    _ => Err($crate::wire::records_not_found()),
+                }
+            }
+        )+
+    };
+}
+
+pub(crate) use dns_exchange_resolver_impl;
+
+pub(crate) fn records_not_found() -> OpaqueError {
+    OpaqueError::from_std(DnsRecordsNotFoundError)
+}
+
+/// Encode a recursive query for the given [`DnsName`] and [`DnsRecordType`].
+///
+/// The message id is set to `0`, as recommended for DNS-over-HTTPS
+/// in [RFC 8484](https://datatracker.ietf.org/doc/html/rfc8484#section-4.1).
+pub(crate) fn encode_query(
+    name: &DnsName,
+    record_type: DnsRecordType,
+) -> Result<Vec<u8>, OpaqueError> {
+    let name = fqdn_from_dns_name(name)?;
+    let mut message = Message::new();
+    message
+        .set_id(0)
+        .set_message_type(MessageType::Query)
+        .set_recursion_desired(true)
+        .add_query(Query::query(name, record_type.into()));
+    message.to_vec().context("encode dns query")
+}
+
+/// Decode a response to a query encoded using [`encode_query`].
+///
+/// Responses whose question does not match the query are rejected.
+/// Responses indicating that no records exist (`NXDOMAIN` or an empty answer)
+/// are decoded as empty [`DnsRecords`], with the negative TTL if one is present.
+pub(crate) fn decode_response(
+    name: &DnsName,
+    record_type: DnsRecordType,
+    response: &[u8],
+) -> Result<(DnsRecords, Option<Duration>), OpaqueError> {
+    let response = Message::from_vec(response).context("decode dns response")?;
+    if response.message_type() != MessageType::Response {
+        return Err(OpaqueError::from_display(
+            "unexpected dns message: expected a response",
+        ));
+    }
+    if response.truncated() {
+        return Err(OpaqueError::from_display("received truncated dns response"));
+    }
+
+    let hickory_record_type = RecordType::from(record_type);
+    let expected_name = fqdn_from_dns_name(name)?;
+    match response.queries() {
+        [query]
+            if query.name() == &expected_name
+                && query.query_type() == hickory_record_type
+                && query.query_class() == DNSClass::IN => {}
+        _ => {
+            return Err(OpaqueError::from_display(
+                "dns response question does not match the query",
+            ));
+        }
+    }
+
+    match response.response_code() {
+        ResponseCode::NoError => (),
+        ResponseCode::NXDomain => {
+            return Ok((DnsRecords::empty(record_type), negative_ttl(&response)));
+        }
+        code => {
+            return Err(OpaqueError::from_display(format!(
+                "dns server responded with error code: {code}"
+            )));
+        }
+    }
+
+    let mut ttl = None;
+    let mut rdata = Vec::new();
+    for answer in response.answers() {
+        // also take the ttl of (followed) aliases into account
+        if answer.record_type() == hickory_record_type || answer.record_type() == RecordType::CNAME
+        {
+            ttl = min_ttl(ttl, Some(Duration::from_secs(answer.ttl() as u64)));
+        }
+        rdata.push(answer.data());
+    }
+
+    let records = records_from_rdata(name, record_type, rdata)?;
+    if records.is_empty() {
+        return Ok((records, negative_ttl(&response)));
+    }
+    Ok((records, ttl))
+}
+
+fn negative_ttl(response: &Message) -> Option<Duration> {
+    response
+        .name_servers()
+        .iter()
+        .find_map(|record| match record.data() {
+            RData::SOA(soa) => Some(Duration::from_secs(record.ttl().min(soa.minimum()) as u64)),
+            _ => None,
+        })
+}
+
+fn min_ttl(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Collect the records of the given [`DnsRecordType`] from resolved record data.
+///
+/// Record data of other types, e.g. aliases followed to get
+/// to the requested records, is ignored.
+pub(crate) fn records_from_rdata<'a>(
+    owner: &DnsName,
+    record_type: DnsRecordType,
+    rdata: impl IntoIterator<Item = &'a RData>,
+) -> Result<DnsRecords, OpaqueError> {
+    let mut records = DnsRecords::empty(record_type);
+    for rdata in rdata {
+        match (&mut records, rdata) {
+            (DnsRecords::Ipv4(ips), RData::A(A(ip))) => ips.push(*ip),
+            (DnsRecords::Ipv6(ips), RData::AAAA(AAAA(ip))) => ips.push(*ip),
+            (DnsRecords::Txt(txts), RData::TXT(txt)) => {
+                txts.push(txt.iter().flat_map(|data| data.iter().copied()).collect())
+            }
+            (DnsRecords::Srv(srvs), RData::SRV(srv)) => srvs.push(SrvRecord {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                port: srv.port(),
+                target: domain_from_name(srv.target())?,
+            }),
+            (DnsRecords::Mx(mxs), RData::MX(mx)) => mxs.push(MxRecord {
+                preference: mx.preference(),
+                exchange: domain_from_name(mx.exchange())?,
+            }),
+            (DnsRecords::Cname(chain), RData::CNAME(CNAME(target))) => {
+                chain.push(dns_name_from_name(target)?)
+            }
+            (DnsRecords::Https(svcbs), RData::HTTPS(HTTPS(svcb)))
+            | (DnsRecords::Svcb(svcbs), RData::SVCB(svcb)) => {
+                svcbs.push(svcb_record_from_svcb(owner, svcb)?)
+            }
+            _ => (),
+        }
+    }
+    Ok(records)
+}
+
+pub(crate) fn svcb_record_from_svcb(
+    owner: &DnsName,
+    svcb: &SVCB,
+) -> Result<SvcbRecord, OpaqueError> {
+    let target = if svcb.target_name().is_root() {
+        owner.clone()
+    } else {
+        dns_name_from_name(svcb.target_name())?
+    };
+
+    let mut record = SvcbRecord::new(svcb.svc_priority(), target);
+    for (_, value) in svcb.svc_params() {
+        match value {
+            SvcParamValue::Alpn(alpn) => {
+                record.alpn = alpn
+                    .0
+                    .iter()
+                    .map(|id| ApplicationProtocol::from(id.as_bytes()))
+                    .collect();
+            }
+            SvcParamValue::NoDefaultAlpn => record.no_default_alpn = true,
+            SvcParamValue::Port(port) => record.port = Some(*port),
+            SvcParamValue::Ipv4Hint(hint) => {
+                record.ipv4_hint = hint.0.iter().map(|A(ip)| *ip).collect();
+            }
+            SvcParamValue::Ipv6Hint(hint) => {
+                record.ipv6_hint = hint.0.iter().map(|AAAA(ip)| *ip).collect();
+            }
+            SvcParamValue::EchConfigList(ech) => record.ech_config_list = Some(ech.0.clone()),
+            _ => (), // other parameters are not relevant to connect
+        }
+    }
+    Ok(record)
+}
+
+pub(crate) fn domain_from_name(name: &Name) -> Result<Domain, OpaqueError> {
+    let name = name.to_ascii();
+    Domain::try_from(name.trim_end_matches('.').to_owned())
+        .context("try to convert a Dns Name into a Domain")
+}
+
+pub(crate) fn fqdn_from_domain(domain: Domain) -> Result<Name, OpaqueError> {
+    let mut name = Name::from_utf8(domain).context("try to consume a Domain as a Dns Name")?;
+    name.set_fqdn(true);
+    Ok(name)
+}
+
+pub(crate) fn dns_name_from_name(name: &Name) -> Result<DnsName, OpaqueError> {
+    let name = name.to_ascii();
+    DnsName::try_from(name.trim_end_matches('.'))
+        .context("try to convert a hickory Dns Name into a DnsName")
+}
+
+pub(crate) fn fqdn_from_dns_name(name: &DnsName) -> Result<Name, OpaqueError> {
+    let mut name =
+        Name::from_utf8(name).context("try to convert a DnsName into a hickory Dns Name")?;
+    name.set_fqdn(true);
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_response(name: &str, record_type: RecordType) -> Vec<u8> {
+        let mut response = Message::new();
+        response
+            .set_message_type(MessageType::Response)
+            .add_query(Query::query(Name::from_ascii(name).unwrap(), record_type));
+        response.to_vec().unwrap()
+    }
+
+    #[test]
+    fn test_decode_response_question() {
+        let name = DnsName::from_static("example.com");
+
+        let (records, _) = decode_response(
+            &name,
+            DnsRecordType::Ipv4,
+            &encode_response("Example.COM.", RecordType::A),
+        )
+        .unwrap();
+        assert!(records.is_empty());
+
+        for (other_name, other_type) in [
+            ("example.org.", RecordType::A),
+            ("example.com.", RecordType::AAAA),
+        ] {
+            assert!(
+                decode_response(
+                    &name,
+                    DnsRecordType::Ipv4,
+                    &encode_response(other_name, other_type)
+                )
+                .is_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! A minimal in-memory cache of `h3` alternative services,
+//! as advertised by origins using the `Alt-Svc` header.
+
+use parking_lot::Mutex;
+use rama_http_types::headers::AltSvc;
+use rama_net::address::{Authority, Host};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Upper bound of the amount of origins remembered,
+/// to protect against unbounded memory growth.
+const MAX_ENTRIES: usize = 1024;
+
+/// Duration during which an alternative service which failed
+/// to connect is not used, even when advertised again.
+const BROKEN_DURATION: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct AltSvcCache {
+    entries: Arc<Mutex<HashMap<Authority, AltSvcEntry>>>,
+}
+
+#[derive(Debug, Clone)]
+struct AltSvcEntry {
+    authority: Authority,
+    expires_at: Instant,
+    broken_until: Option<Instant>,
+}
+
+impl AltSvcCache {
+    /// Returns the (h3) alternative authority for the given origin,
+    /// if one is known, still fresh and not marked as broken.
+    pub(super) fn get_h3(&self, origin: &Authority) -> Option<Authority> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get(origin)?;
+        let now = Instant::now();
+        if entry.expires_at <= now {
+            entries.remove(origin);
+            return None;
+        }
+        if entry
+            .broken_until
+            .is_some_and(|broken_until| broken_until > now)
+        {
+            return None;
+        }
+        Some(entry.authority.clone())
+    }
+
+    /// Update the cache for the given origin using the
+    /// `Alt-Svc` header as received from that origin.
+    pub(super) fn update(&self, origin: &Authority, alt_svc: &AltSvc) {
+        let mut entries = self.entries.lock();
+
+        let Some(service) = alt_svc.iter().find(|service| service.is_h3()) else {
+            // no h3 service (anymore), a clear is implied in that case
+            entries.remove(origin);
+            return;
+        };
+
+        let host = match service.host() {
+            Some(host) => match host.parse::<Host>() {
+                Ok(host) => host,
+                Err(err) => {
+                    tracing::debug!(?err, %origin, "ignore alt-svc with invalid host");
+                    return;
+                }
+            },
+            None => origin.host().clone(),
+        };
+
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(origin) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= MAX_ENTRIES {
+                tracing::trace!(%origin, "alt-svc cache is full: ignore h3 service");
+                return;
+            }
+        }
+
+        let authority = Authority::new(host, service.port());
+        // an advertisement does not repair a service which is known to be broken
+        let broken_until = entries
+            .get(origin)
+            .filter(|entry| entry.authority == authority)
+            .and_then(|entry| entry.broken_until);
+
+        let max_age = service.max_age().min(Duration::from_secs(u32::MAX as u64));
+        entries.insert(
+            origin.clone(),
+            AltSvcEntry {
+                authority,
+                expires_at: Instant::now() + max_age,
+                broken_until,
+            },
+        );
+    }
+
+    /// Mark the alternative service of the given origin as broken,
+    /// e.g. because it turned out to be unreachable, such that it is
+    /// not used for a while and requests go to the origin instead.
+    pub(super) fn mark_broken(&self, origin: &Authority) {
+        if let Some(entry) = self.entries.lock().get_mut(origin) {
+            entry.broken_until = Some(Instant::now() + BROKEN_DURATION);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_http_types::headers::AltService;
+
+    #[test]
+    fn test_alt_svc_cache() {
+        let cache = AltSvcCache::default();
+        let origin: Authority = "example.com:443".parse().unwrap();
+        assert_eq!(cache.get_h3(&origin), None);
+
+        cache.update(
+            &origin,
+            &AltSvc::Services(vec![AltService::new("h2", 443), AltService::h3(8443)]),
+        );
+        assert_eq!(
+            cache.get_h3(&origin),
+            Some("example.com:8443".parse().unwrap())
+        );
+
+        cache.update(
+            &origin,
+            &AltSvc::from(AltService::h3(443).with_host("alt.example.com".to_owned())),
+        );
+        assert_eq!(
+            cache.get_h3(&origin),
+            Some("alt.example.com:443".parse().unwrap())
+        );
+
+        cache.mark_broken(&origin);
+        assert_eq!(cache.get_h3(&origin), None);
+        cache.update(
+            &origin,
+            &AltSvc::from(AltService::h3(443).with_host("alt.example.com".to_owned())),
+        );
+        assert_eq!(cache.get_h3(&origin), None);
+        cache.update(&origin, &AltSvc::from(AltService::h3(8443)));
+        assert_eq!(
+            cache.get_h3(&origin),
+            Some("example.com:8443".parse().unwrap())
+        );
+
+        cache.update(&origin, &AltSvc::Clear);
+        assert_eq!(cache.get_h3(&origin), None);
+
+        cache.update(
+            &origin,
+            &AltSvc::from(AltService::h3(443).with_max_age(Duration::ZERO)),
+        );
+        assert_eq!(cache.get_h3(&origin), None);
+    }
+}
@@ -186,7 +186,7 @@ where
                 let (sender, conn) = builder.handshake(io).await?;
 
                 ctx.spawn(async move {
-                    if let Err(err) = conn.await {
+                    if let Err(err) = conn.with_upgrades().await {
                         tracing::debug!("connection failed: {:?}", err);
                     }
                 });
@@ -0,0 +1,224 @@
+use super::{HttpClientService, svc::SendRequest};
+use rama_core::{
+    Context, Layer, Service,
+    error::{BoxError, ErrorContext},
+    inspect::RequestInspector,
+};
+use rama_http_types::{Request, dep::http_body};
+use rama_net::client::{ConnectorService, EstablishedClientConnection};
+use rama_quic::QuicConnection;
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+use tracing::trace;
+
+/// A [`Service`] which establishes an HTTP/3 Connection,
+/// on top of a [`QuicConnection`] established by the inner connector
+/// (e.g. a [`QuicConnector`]).
+///
+/// [`QuicConnector`]: rama_quic::client::QuicConnector
+pub struct Http3Connector<S, I1 = (), I2 = ()> {
+    inner: S,
+    http_req_inspector_jit: I1,
+    http_req_inspector_svc: I2,
+}
+
+impl<S: fmt::Debug, I1: fmt::Debug, I2: fmt::Debug> fmt::Debug for Http3Connector<S, I1, I2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Http3Connector")
+            .field("inner", &self.inner)
+            .field("http_req_inspector_jit", &self.http_req_inspector_jit)
+            .field("http_req_inspector_svc", &self.http_req_inspector_svc)
+            .finish()
+    }
+}
+
+impl<S> Http3Connector<S> {
+    /// Create a new [`Http3Connector`].
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            http_req_inspector_jit: (),
+            http_req_inspector_svc: (),
+        }
+    }
+}
+
+impl<S, I1, I2> Http3Connector<S, I1, I2> {
+    pub fn with_jit_req_inspector<T>(self, http_req_inspector: T) -> Http3Connector<S, T, I2> {
+        Http3Connector {
+            inner: self.inner,
+            http_req_inspector_jit: http_req_inspector,
+            http_req_inspector_svc: self.http_req_inspector_svc,
+        }
+    }
+
+    pub fn with_svc_req_inspector<T>(self, http_req_inspector: T) -> Http3Connector<S, I1, T> {
+        Http3Connector {
+            inner: self.inner,
+            http_req_inspector_jit: self.http_req_inspector_jit,
+            http_req_inspector_svc: http_req_inspector,
+        }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S, I1, I2> Clone for Http3Connector<S, I1, I2>
+where
+    S: Clone,
+    I1: Clone,
+    I2: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            http_req_inspector_jit: self.http_req_inspector_jit.clone(),
+            http_req_inspector_svc: self.http_req_inspector_svc.clone(),
+        }
+    }
+}
+
+impl<S, I1, I2, State, BodyIn, BodyOut> Service<State, Request<BodyIn>>
+    for Http3Connector<S, I1, I2>
+where
+    I1: RequestInspector<
+            State,
+            Request<BodyIn>,
+            Error: Into<BoxError>,
+            StateOut = State,
+            RequestOut = Request<BodyIn>,
+        >,
+    I2: RequestInspector<
+            State,
+            Request<BodyIn>,
+            Error: Into<BoxError>,
+            RequestOut = Request<BodyOut>,
+        > + Clone,
+    S: ConnectorService<State, Request<BodyIn>, Connection = QuicConnection, Error: Into<BoxError>>,
+    State: Clone + Send + Sync + 'static,
+    BodyIn: http_body::Body<Data: Send + 'static, Error: Into<BoxError>> + Unpin + Send + 'static,
+    BodyOut: http_body::Body<Data: Send + 'static, Error: Into<BoxError>> + Unpin + Send + 'static,
+{
+    type Response =
+        EstablishedClientConnection<HttpClientService<BodyOut, I2>, I1::StateOut, I1::RequestOut>;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request<BodyIn>,
+    ) -> Result<Self::Response, Self::Error> {
+        let EstablishedClientConnection { ctx, req, conn } =
+            self.inner.connect(ctx, req).await.map_err(Into::into)?;
+
+        let (ctx, req) = self
+            .http_req_inspector_jit
+            .inspect_request(ctx, req)
+            .await
+            .map_err(Into::into)?;
+
+        trace!(uri = %req.uri(), "create h3 client executor");
+
+        let (mut driver, sender) = h3::client::new(h3_quinn::Connection::new(conn))
+            .await
+            .context("h3 client handshake")?;
+
+        ctx.spawn(async move {
+            let err = std::future::poll_fn(|cx| driver.poll_close(cx)).await;
+            if !err.is_h3_no_error() {
+                tracing::debug!("connection failed: {:?}", err);
+            }
+        });
+
+        let svc = HttpClientService {
+            sender: SendRequest::Http3(sender),
+            http_req_inspector: self.http_req_inspector_svc.clone(),
+        };
+
+        Ok(EstablishedClientConnection {
+            ctx,
+            req,
+            conn: svc,
+        })
+    }
+}
+
+/// A [`Layer`] that produces an [`Http3Connector`].
+pub struct Http3ConnectorLayer<I1 = (), I2 = ()> {
+    http_req_inspector_jit: I1,
+    http_req_inspector_svc: I2,
+}
+
+impl Http3ConnectorLayer {
+    /// Create a new [`Http3ConnectorLayer`].
+    pub const fn new() -> Self {
+        Self {
+            http_req_inspector_jit: (),
+            http_req_inspector_svc: (),
+        }
+    }
+}
+
+impl<I1, I2> Http3ConnectorLayer<I1, I2> {
+    pub fn with_jit_req_inspector<T>(self, http_req_inspector: T) -> Http3ConnectorLayer<T, I2> {
+        Http3ConnectorLayer {
+            http_req_inspector_jit: http_req_inspector,
+            http_req_inspector_svc: self.http_req_inspector_svc,
+        }
+    }
+
+    pub fn with_svc_req_inspector<T>(self, http_req_inspector: T) -> Http3ConnectorLayer<I1, T> {
+        Http3ConnectorLayer {
+            http_req_inspector_jit: self.http_req_inspector_jit,
+            http_req_inspector_svc: http_req_inspector,
+        }
+    }
+}
+
+impl<I1: fmt::Debug, I2: fmt::Debug> fmt::Debug for Http3ConnectorLayer<I1, I2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Http3ConnectorLayer")
+            .field("http_req_inspector_jit", &self.http_req_inspector_jit)
+            .field("http_req_inspector_svc", &self.http_req_inspector_svc)
+            .finish()
+    }
+}
+
+impl<I1, I2> Clone for Http3ConnectorLayer<I1, I2>
+where
+    I1: Clone,
+    I2: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            http_req_inspector_jit: self.http_req_inspector_jit.clone(),
+            http_req_inspector_svc: self.http_req_inspector_svc.clone(),
+        }
+    }
+}
+
+impl Default for Http3ConnectorLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I1: Clone, I2: Clone, S> Layer<S> for Http3ConnectorLayer<I1, I2> {
+    type Service = Http3Connector<S, I1, I2>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Http3Connector {
+            inner,
+            http_req_inspector_jit: self.http_req_inspector_jit.clone(),
+            http_req_inspector_svc: self.http_req_inspector_svc.clone(),
+        }
+    }
+
+    fn into_layer(self, inner: S) -> Self::Service {
+        Http3Connector {
+            inner,
+            http_req_inspector_jit: self.http_req_inspector_jit,
+            http_req_inspector_svc: self.http_req_inspector_svc,
+        }
+    }
+}
@@ -19,10 +19,17 @@ use rama_net::{
     },
     http::RequestContext,
 };
+use rama_socks5::Socks5ProxyConnector;
 use rama_tcp::client::service::TcpConnector;
 
 #[cfg(feature = "boring")]
-use rama_net::tls::client::{ClientConfig, ProxyClientConfig, extract_client_config_from_ctx};
+use rama_net::tls::client::ProxyClientConfig;
+
+#[cfg(any(feature = "rustls", feature = "boring"))]
+use rama_net::tls::client::{ClientConfig, extract_client_config_from_ctx};
+
+#[cfg(feature = "rustls")]
+use rama_net::tls::{ApplicationProtocol, client::ClientHelloExtension};
 
 #[cfg(feature = "boring")]
 use rama_tls::boring::client::{
@@ -40,6 +47,19 @@ use http_inspector::HttpsAlpnModifier;
 #[cfg(any(feature = "rustls", feature = "boring"))]
 use rama_net::client::EitherConn;
 
+#[cfg(feature = "http3")]
+use rama_core::service::service_fn;
+#[cfg(feature = "http3")]
+use rama_http_types::headers::{AltSvc, HeaderMapExt};
+#[cfg(feature = "http3")]
+use rama_net::address::ProxyAddress;
+#[cfg(feature = "http3")]
+use rama_net::transport::{TransportContext, TransportProtocol};
+#[cfg(feature = "http3")]
+use rama_quic::client::{QuicConnectAuthority, QuicConnector};
+#[cfg(feature = "http3")]
+use std::convert::Infallible;
+
 mod svc;
 #[doc(inline)]
 pub use svc::HttpClientService;
@@ -47,6 +67,17 @@ pub use svc::HttpClientService;
 mod conn;
 #[doc(inline)]
 pub use conn::{HttpConnector, HttpConnectorLayer};
+
+#[cfg(feature = "http3")]
+mod http3;
+#[cfg(feature = "http3")]
+#[doc(inline)]
+pub use http3::{Http3Connector, Http3ConnectorLayer};
+
+#[cfg(feature = "http3")]
+mod alt_svc;
+#[cfg(feature = "http3")]
+use alt_svc::AltSvcCache;
 use tracing::trace;
 
 pub mod http_inspector;
@@ -64,6 +95,8 @@ pub struct EasyHttpWebClient<I1 = (), I2 = (), P = ()> {
     tls_connector_config: Option<TlsConnectorConfig>,
     #[cfg(any(feature = "rustls", feature = "boring"))]
     proxy_tls_connector_config: Option<TlsConnectorConfig>,
+    #[cfg(feature = "http3")]
+    http3: Option<Http3Support>,
     connection_pool: P,
     proxy_http_connect_version: Option<Version>,
     http_req_inspector_jit: I1,
@@ -78,16 +111,64 @@ pub enum TlsConnectorConfig {
     Rustls(Option<RustlsTlsConnectorData>),
 }
 
+#[cfg(feature = "http3")]
+#[derive(Debug, Clone, Default)]
+/// HTTP/3 configuration and state of an [`EasyHttpWebClient`].
+struct Http3Support {
+    /// shared by all requests, such that a single client endpoint is used
+    quic_connector: QuicConnector,
+    alt_svc_cache: AltSvcCache,
+}
+
+#[cfg(feature = "http3")]
+/// The way a request is to be sent over HTTP/3.
+enum Http3Route {
+    /// Explicit h3 request, to be sent directly to the origin.
+    Origin,
+    /// Request to be sent to the cached h3 alternative service of the origin.
+    AltSvc { origin: Authority, alt: Authority },
+}
+
+#[cfg(feature = "http3")]
+impl Http3Support {
+    /// Returns the authority of the https origin targeted by the request,
+    /// in case it is eligible for alternative services.
+    fn alt_svc_origin<State, Body>(ctx: &Context<State>, req: &Request<Body>) -> Option<Authority> {
+        if ctx.get::<ProxyAddress>().is_some() {
+            return None;
+        }
+        let req_ctx = match ctx.get::<RequestContext>() {
+            Some(req_ctx) => req_ctx.clone(),
+            None => RequestContext::try_from((ctx, req)).ok()?,
+        };
+        (req_ctx.protocol == Protocol::HTTPS).then_some(req_ctx.authority)
+    }
+
+    fn route<State, Body>(&self, ctx: &Context<State>, req: &Request<Body>) -> Option<Http3Route> {
+        if req.version() == Version::HTTP_3 {
+            return ctx
+                .get::<ProxyAddress>()
+                .is_none()
+                .then_some(Http3Route::Origin);
+        }
+        let origin = Self::alt_svc_origin(ctx, req)?;
+        let alt = self.alt_svc_cache.get_h3(&origin)?;
+        Some(Http3Route::AltSvc { origin, alt })
+    }
+}
+
 #[cfg(any(feature = "rustls", feature = "boring"))]
 impl<I1: fmt::Debug, I2: fmt::Debug, P: fmt::Debug> fmt::Debug for EasyHttpWebClient<I1, I2, P> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("EasyHttpWebClient")
-            .field("tls_connector_config", &self.tls_connector_config)
+        let mut d = f.debug_struct("EasyHttpWebClient");
+        d.field("tls_connector_config", &self.tls_connector_config)
             .field(
                 "proxy_tls_connector_config",
                 &self.proxy_tls_connector_config,
-            )
-            .field("connection_pool", &self.connection_pool)
+            );
+        #[cfg(feature = "http3")]
+        d.field("http3", &self.http3);
+        d.field("connection_pool", &self.connection_pool)
             .field(
                 "proxy_http_connect_version",
                 &self.proxy_http_connect_version,
@@ -119,6 +200,8 @@ impl<I1: Clone, I2: Clone, P: Clone> Clone for EasyHttpWebClient<I1, I2, P> {
         Self {
             tls_connector_config: self.tls_connector_config.clone(),
             proxy_tls_connector_config: self.proxy_tls_connector_config.clone(),
+            #[cfg(feature = "http3")]
+            http3: self.http3.clone(),
             connection_pool: self.connection_pool.clone(),
             proxy_http_connect_version: self.proxy_http_connect_version,
             http_req_inspector_jit: self.http_req_inspector_jit.clone(),
@@ -150,6 +233,8 @@ impl Default for EasyHttpWebClient {
             proxy_tls_connector_config: Some(TlsConnectorConfig::Boring(None)),
             #[cfg(all(feature = "rustls", not(feature = "boring")))]
             proxy_tls_connector_config: Some(TlsConnectorConfig::Rustls(None)),
+            #[cfg(feature = "http3")]
+            http3: None,
             connection_pool: (),
             proxy_http_connect_version: Some(Version::HTTP_11),
             http_req_inspector_jit: (),
@@ -211,6 +296,61 @@ impl<I1, I2, P> EasyHttpWebClient<I1, I2, P> {
         self
     }
 
+    #[cfg(feature = "http3")]
+    /// Enable HTTP/3 support for this [`EasyHttpWebClient`].
+    ///
+    /// Requests with version [`Version::HTTP_3`] are sent over QUIC,
+    /// and `h3` alternative services advertised by https origins
+    /// using the `Alt-Svc` header are used for subsequent requests
+    /// to these origins. Requests which go via a proxy are never sent over HTTP/3.
+    ///
+    /// Alternative services are authenticated as their origin. In case an
+    /// alternative service cannot be reached it is marked as broken for a while,
+    /// and the request is sent to the origin over TCP instead.
+    pub fn with_http3_support(mut self) -> Self {
+        self.set_http3_support();
+        self
+    }
+
+    #[cfg(feature = "http3")]
+    /// Enable HTTP/3 support for this [`EasyHttpWebClient`].
+    ///
+    /// See [`Self::with_http3_support`] for more information.
+    pub fn set_http3_support(&mut self) -> &mut Self {
+        self.http3.get_or_insert_with(Http3Support::default);
+        self
+    }
+
+    #[cfg(feature = "http3")]
+    /// Enable HTTP/3 support for this [`EasyHttpWebClient`],
+    /// using the given [`RustlsTlsConnectorData`] to secure the QUIC connections.
+    ///
+    /// See [`Self::with_http3_support`] for more information.
+    pub fn with_http3_connector_data(mut self, data: RustlsTlsConnectorData) -> Self {
+        self.set_http3_connector_data(data);
+        self
+    }
+
+    #[cfg(feature = "http3")]
+    /// Enable HTTP/3 support for this [`EasyHttpWebClient`],
+    /// using the given [`RustlsTlsConnectorData`] to secure the QUIC connections.
+    ///
+    /// See [`Self::with_http3_support`] for more information.
+    pub fn set_http3_connector_data(&mut self, data: RustlsTlsConnectorData) -> &mut Self {
+        self.http3
+            .get_or_insert_with(Http3Support::default)
+            .quic_connector
+            .set_connector_data(data);
+        self
+    }
+
+    #[cfg(feature = "http3")]
+    /// Disable HTTP/3 support for this [`EasyHttpWebClient`], which is the default.
+    pub fn without_http3_support(mut self) -> Self {
+        self.http3 = None;
+        self
+    }
+
     /// Set the HTTP version to use for the Http Proxy CONNECT request.
     ///
     /// By default this is set to HTTP/1.1.
@@ -245,6 +385,8 @@ impl<I1, I2, P> EasyHttpWebClient<I1, I2, P> {
         EasyHttpWebClient {
             tls_connector_config: self.tls_connector_config,
             proxy_tls_connector_config: self.proxy_tls_connector_config,
+            #[cfg(feature = "http3")]
+            http3: self.http3,
             proxy_http_connect_version: self.proxy_http_connect_version,
             http_req_inspector_jit: http_req_inspector,
             http_req_inspector_svc: self.http_req_inspector_svc,
@@ -273,6 +415,8 @@ impl<I1, I2, P> EasyHttpWebClient<I1, I2, P> {
         EasyHttpWebClient {
             tls_connector_config: self.tls_connector_config,
             proxy_tls_connector_config: self.proxy_tls_connector_config,
+            #[cfg(feature = "http3")]
+            http3: self.http3,
             proxy_http_connect_version: self.proxy_http_connect_version,
             http_req_inspector_jit: self.http_req_inspector_jit,
             http_req_inspector_svc: http_req_inspector,
@@ -298,6 +442,8 @@ impl<I1, I2, P> EasyHttpWebClient<I1, I2, P> {
         EasyHttpWebClient {
             tls_connector_config: self.tls_connector_config,
             proxy_tls_connector_config: self.proxy_tls_connector_config,
+            #[cfg(feature = "http3")]
+            http3: self.http3,
             proxy_http_connect_version: self.proxy_http_connect_version,
             http_req_inspector_jit: self.http_req_inspector_jit,
             http_req_inspector_svc: self.http_req_inspector_svc,
@@ -310,6 +456,8 @@ impl<I1, I2, P> EasyHttpWebClient<I1, I2, P> {
         EasyHttpWebClient {
             tls_connector_config: self.tls_connector_config,
             proxy_tls_connector_config: self.proxy_tls_connector_config,
+            #[cfg(feature = "http3")]
+            http3: self.http3,
             proxy_http_connect_version: self.proxy_http_connect_version,
             http_req_inspector_jit: self.http_req_inspector_jit,
             http_req_inspector_svc: self.http_req_inspector_svc,
@@ -461,6 +609,78 @@ where
     ) -> Result<Self::Response, Self::Error> {
         let uri = req.uri().clone();
 
+        #[cfg(feature = "http3")]
+        let alt_svc_origin = match &self.http3 {
+            Some(http3) => match http3.route(&ctx, &req) {
+                Some(Http3Route::Origin) => {
+                    let connector = Http3Connector::new(http3.quic_connector.clone())
+                        .with_jit_req_inspector(self.http_req_inspector_jit.clone())
+                        .with_svc_req_inspector(self.http_req_inspector_svc.clone());
+                    let connection = self
+                        .connect(connector, ctx, req)
+                        .await
+                        .with_context(|| format!("http request failure for uri: {uri}"))?;
+                    trace!(uri = %uri, "send http req to h3 connector stack");
+                    return send_request_over_connection(connection, &uri).await;
+                }
+                Some(Http3Route::AltSvc { origin, alt }) => {
+                    trace!(uri = %uri, %alt, "use cached h3 alt-svc for request");
+                    // connect prior to handing over the request,
+                    // such that it can still be sent to the origin in case this fails
+                    let mut alt_ctx = ctx.clone();
+                    alt_ctx.insert(TransportContext {
+                        protocol: TransportProtocol::Udp,
+                        app_protocol: Some(Protocol::HTTPS),
+                        http_version: Some(Version::HTTP_3),
+                        authority: origin.clone(),
+                    });
+                    alt_ctx.insert(QuicConnectAuthority(alt.clone()));
+                    let mut probe = Request::new(());
+                    *probe.uri_mut() = uri.clone();
+                    *probe.version_mut() = Version::HTTP_3;
+
+                    match http3.quic_connector.connect(alt_ctx, probe).await {
+                        Ok(EstablishedClientConnection {
+                            ctx: alt_ctx, conn, ..
+                        }) => {
+                            let connector = Http3Connector::new(service_fn(
+                                move |ctx: Context<State>, req: Request<BodyIn>| {
+                                    let conn = conn.clone();
+                                    async move {
+                                        Ok::<_, Infallible>(EstablishedClientConnection {
+                                            ctx,
+                                            req,
+                                            conn,
+                                        })
+                                    }
+                                },
+                            ))
+                            .with_jit_req_inspector(self.http_req_inspector_jit.clone())
+                            .with_svc_req_inspector(self.http_req_inspector_svc.clone());
+                            let connection = self
+                                .connect(connector, alt_ctx, req)
+                                .await
+                                .with_context(|| format!("http request failure for uri: {uri}"))?;
+                            trace!(uri = %uri, "send http req to h3 connector stack");
+                            return send_request_over_connection(connection, &uri).await;
+                        }
+                        Err(err) => {
+                            tracing::debug!(
+                                uri = %uri,
+                                %alt,
+                                error = %err,
+                                "h3 alt-svc unreachable: mark as broken and fall back to origin",
+                            );
+                            http3.alt_svc_cache.mark_broken(&origin);
+                            Some(origin)
+                        }
+                    }
+                }
+                None => Http3Support::alt_svc_origin(&ctx, &req),
+            },
+            None => None,
+        };
+
         let tcp_connector = TcpConnector::new();
 
         #[cfg(any(feature = "rustls", feature = "boring"))]
@@ -494,7 +714,8 @@ where
                 EitherConn::B(connector)
             };
 
-            // 3 Http proxy tunnel if needed
+            // 3 Socks5 or Http proxy tunnel if needed
+            let connector = Socks5ProxyConnector::optional(connector);
             let mut connector = HttpProxyConnector::optional(connector);
             match self.proxy_http_connect_version {
                 Some(version) => connector.set_version(version),
@@ -531,7 +752,8 @@ where
 
         #[cfg(not(any(feature = "rustls", feature = "boring")))]
         let connector = {
-            let mut proxy_connector = HttpProxyConnector::optional(tcp_connector);
+            let socks5_proxy_connector = Socks5ProxyConnector::optional(tcp_connector);
+            let mut proxy_connector = HttpProxyConnector::optional(socks5_proxy_connector);
             match self.proxy_http_connect_version {
                 Some(version) => proxy_connector.set_version(version),
                 None => proxy_connector.set_auto_version(),
@@ -547,25 +769,46 @@ where
         let connection = self.connect(connector, ctx, req).await?;
         trace!(uri = %uri, "send http req to connector stack");
 
-        let result = match connection {
-            Connection::Direct(EstablishedClientConnection { ctx, req, conn }) => {
-                conn.serve(ctx, req).await
-            }
-            Connection::Pooled(EstablishedClientConnection { ctx, req, conn }) => {
-                conn.serve(ctx, req).await
-            }
-        };
-
-        let resp = result
-            .map_err(OpaqueError::from_boxed)
-            .with_context(|| format!("http request failure for uri: {uri}"))?;
+        let resp = send_request_over_connection(connection, &uri).await?;
 
-        trace!(uri = %uri, "response received from connector stack");
+        #[cfg(feature = "http3")]
+        if let (Some(http3), Some(origin)) = (&self.http3, alt_svc_origin) {
+            if let Some(alt_svc) = resp.headers().typed_get::<AltSvc>() {
+                trace!(uri = %uri, %origin, "update h3 alt-svc cache for origin");
+                http3.alt_svc_cache.update(&origin, &alt_svc);
+            }
+        }
 
         Ok(resp)
     }
 }
 
+async fn send_request_over_connection<State, BodyIn, BodyOut, I2>(
+    connection: Connection<HttpClientService<BodyOut, I2>, State, BodyIn>,
+    uri: &rama_http_types::Uri,
+) -> Result<Response, OpaqueError>
+where
+    HttpClientService<BodyOut, I2>:
+        Service<State, Request<BodyIn>, Response = Response, Error = BoxError>,
+{
+    let result = match connection {
+        Connection::Direct(EstablishedClientConnection { ctx, req, conn }) => {
+            conn.serve(ctx, req).await
+        }
+        Connection::Pooled(EstablishedClientConnection { ctx, req, conn }) => {
+            conn.serve(ctx, req).await
+        }
+    };
+
+    let resp = result
+        .map_err(OpaqueError::from_boxed)
+        .with_context(|| format!("http request failure for uri: {uri}"))?;
+
+    trace!(uri = %uri, "response received from connector stack");
+
+    Ok(resp)
+}
+
 #[cfg(feature = "boring")]
 fn create_connector_data_boring<State>(
     ctx: &Context<State>,
@@ -635,16 +878,33 @@ fn create_proxy_connector_data_boring<State>(
 
 #[cfg(feature = "rustls")]
 fn create_connector_data_rustls<State>(
-    _ctx: &Context<State>,
+    ctx: &Context<State>,
     tls_config: &Option<RustlsTlsConnectorData>,
 ) -> Result<RustlsTlsConnectorData, OpaqueError> {
-    // TODO do we also want to support getting this from ctx?
-    match tls_config {
-        Some(tls_config) => {
+    match (tls_config, extract_client_config_from_ctx(ctx)) {
+        (Some(tls_config), _) => {
             trace!("create tls connector using pre-defined rustls tls client config");
             Ok(tls_config.clone())
         }
-        None => {
+        (None, Some(chain_ref)) => {
+            trace!("create tls connector using rama tls client config(s) from context");
+            let mut cfg = ClientConfig {
+                extensions: Some(vec![
+                    ClientHelloExtension::ApplicationLayerProtocolNegotiation(vec![
+                        ApplicationProtocol::HTTP_2,
+                        ApplicationProtocol::HTTP_11,
+                    ]),
+                ]),
+                ..Default::default()
+            };
+            for other in chain_ref.iter() {
+                cfg.merge(other.clone());
+            }
+            cfg.try_into().context(
+                "EasyHttpWebClient: create tls connector data from tls client config(s) from context",
+            )
+        }
+        (None, None) => {
             trace!("create tls connector using the 'new_http_auto' constructor");
             RustlsTlsConnectorData::new_http_auto()
                 .context("EasyHttpWebClient: create tls connector data for http (auto)")
@@ -670,3 +930,84 @@ fn create_proxy_connector_data_rustls<State>(
         }
     }
 }
+
+#[cfg(all(test, feature = "http3"))]
+mod tests {
+    use super::*;
+    use crate::server::HttpServer;
+    use rama_core::{Layer, rt::Executor};
+    use rama_http_types::{Body, HeaderValue, header};
+    use rama_net::tls::{ApplicationProtocol, server::SelfSignedData};
+    use rama_quic::server::QuicListener;
+    use rama_tcp::server::TcpListener;
+    use rama_tls_rustls::client::TlsConnectorDataBuilder;
+    use rama_tls_rustls::server::{TlsAcceptorDataBuilder, TlsAcceptorLayer};
+
+    #[tokio::test]
+    async fn test_alt_svc_fallback_to_origin() {
+        // an alternative service which fails the handshake, as it does not speak h3
+        let quic_tls_config = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData::default())
+            .unwrap()
+            .with_alpn_protocols(&[ApplicationProtocol::HTTP_2])
+            .into_rustls_config();
+        let quic_listener = QuicListener::bind("127.0.0.1:0", quic_tls_config)
+            .await
+            .unwrap();
+        let alt_port = quic_listener.local_addr().unwrap().port();
+        tokio::spawn(quic_listener.serve(service_fn(async || Ok::<_, Infallible>(()))));
+
+        let tls_data = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData::default())
+            .unwrap()
+            .with_alpn_protocols_http_auto()
+            .build();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let origin_addr = listener.local_addr().unwrap();
+        tokio::spawn(listener.serve(TlsAcceptorLayer::new(tls_data).into_layer(
+            HttpServer::auto(Executor::default()).service(service_fn(move || async move {
+                let mut resp = Response::new(Body::empty());
+                resp.headers_mut().insert(
+                    header::ALT_SVC,
+                    HeaderValue::from_str(&format!("h3=\":{alt_port}\"")).unwrap(),
+                );
+                Ok::<_, Infallible>(resp)
+            })),
+        )));
+
+        let client = EasyHttpWebClient::default()
+            .with_tls_connector_config(TlsConnectorConfig::Rustls(Some(
+                TlsConnectorDataBuilder::new()
+                    .with_no_cert_verifier()
+                    .with_alpn_protocols_http_auto()
+                    .build(),
+            )))
+            .with_http3_connector_data(
+                TlsConnectorDataBuilder::new()
+                    .with_no_cert_verifier()
+                    .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+                    .build(),
+            );
+        let alt_svc_cache = &client.http3.as_ref().unwrap().alt_svc_cache;
+        let origin: Authority = origin_addr.into();
+        let alt = Authority::new(origin.host().clone(), alt_port);
+
+        let send = async || {
+            let req = Request::builder()
+                .uri(format!("https://{origin_addr}"))
+                .body(Body::empty())
+                .unwrap();
+            client.serve(Context::default(), req).await.unwrap()
+        };
+
+        // learn the alternative service from the origin
+        assert_ne!(Version::HTTP_3, send().await.version());
+        assert_eq!(Some(alt), alt_svc_cache.get_h3(&origin));
+
+        // the alternative service fails, so the request falls back to the origin
+        assert_ne!(Version::HTTP_3, send().await.version());
+        assert_eq!(None, alt_svc_cache.get_h3(&origin));
+
+        // the broken alternative service is not used, despite being advertised again
+        assert_ne!(Version::HTTP_3, send().await.version());
+        assert_eq!(None, alt_svc_cache.get_h3(&origin));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::client::proxy::is_socks5_proxy;
 use rama_core::{Context, Layer, Service};
 use rama_http_types::{
     Request,
@@ -79,7 +80,7 @@ where
         {
             // only do this for non-secure requests!!!
 
-            if let Some(pa) = ctx.get::<ProxyAddress>() {
+            if let Some(pa) = ctx.get::<ProxyAddress>().filter(|pa| !is_socks5_proxy(pa)) {
                 if let Some(credential) = pa.credential.clone() {
                     match credential {
                         ProxyCredential::Basic(basic) => {
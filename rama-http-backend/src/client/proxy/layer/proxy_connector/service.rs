@@ -1,4 +1,4 @@
-use crate::client::proxy::layer::HttpProxyError;
+use crate::client::proxy::{is_socks5_proxy, layer::HttpProxyError};
 
 use super::InnerHttpProxyConnector;
 use rama_core::{
@@ -122,7 +122,11 @@ where
         mut ctx: Context<State>,
         req: Request,
     ) -> Result<Self::Response, Self::Error> {
-        let address = ctx.get::<ProxyAddress>().cloned();
+        // socks5 proxies are handled by a dedicated connector
+        let address = ctx
+            .get::<ProxyAddress>()
+            .filter(|address| !is_socks5_proxy(address))
+            .cloned();
 
         let transport_ctx = ctx
             .get_or_try_insert_with_ctx(|ctx| req.try_ref_into_transport_ctx(ctx))
@@ -1,3 +1,14 @@
 //! Client Http Proxy Connector Support.
 
+use rama_net::address::ProxyAddress;
+
 pub mod layer;
+
+/// Returns `true` in case the [`ProxyAddress`] is one of a socks5(h) proxy,
+/// which is to be handled by the socks5 proxy connector instead.
+pub(crate) fn is_socks5_proxy(address: &ProxyAddress) -> bool {
+    address
+        .protocol
+        .as_ref()
+        .is_some_and(|protocol| protocol.is_socks5() || protocol.is_socks5h())
+}
@@ -1,3 +1,4 @@
+use super::proxy::is_socks5_proxy;
 use rama_core::{
     Context, Service,
     context::{self, RequestContextExt},
@@ -10,13 +11,19 @@ use rama_http_types::{
     header::{CONNECTION, HOST, KEEP_ALIVE, PROXY_CONNECTION, TRANSFER_ENCODING, UPGRADE},
     headers::HeaderMapExt,
 };
+
 use rama_net::{address::ProxyAddress, http::RequestContext};
+
+#[cfg(feature = "http3")]
+use crate::h3_body::{H3IncomingBody, send_h3_body};
 use std::fmt;
 use tokio::sync::Mutex;
 
 pub(super) enum SendRequest<Body> {
     Http1(Mutex<rama_http_core::client::conn::http1::SendRequest<Body>>),
     Http2(rama_http_core::client::conn::http2::SendRequest<Body>),
+    #[cfg(feature = "http3")]
+    Http3(h3::client::SendRequest<h3_quinn::OpenStreams, bytes::Bytes>),
 }
 
 impl<Body: fmt::Debug> fmt::Debug for SendRequest<Body> {
@@ -25,6 +32,8 @@ impl<Body: fmt::Debug> fmt::Debug for SendRequest<Body> {
         match self {
             SendRequest::Http1(send_request) => f.field(send_request).finish(),
             SendRequest::Http2(send_request) => f.field(send_request).finish(),
+            #[cfg(feature = "http3")]
+            SendRequest::Http3(_) => f.field(&"h3").finish(),
         }
     }
 }
@@ -90,6 +99,23 @@ where
                     *req.version_mut() = Version::HTTP_2;
                 }
             },
+            #[cfg(feature = "http3")]
+            SendRequest::Http3(_) => match original_http_version {
+                Version::HTTP_3 => {
+                    tracing::trace!(
+                        ?original_http_version,
+                        "request version is already h3 compatible, it will remain unchanged",
+                    );
+                }
+                _ => {
+                    tracing::debug!(
+                        ?original_http_version,
+                        new_http_version = ?Version::HTTP_3,
+                        "modify request version to compatible h3 connection version",
+                    );
+                    *req.version_mut() = Version::HTTP_3;
+                }
+            },
         }
 
         let (mut ctx, req) = self
@@ -108,20 +134,52 @@ where
         // directly instead of here...
         let req = sanitize_client_req_header(&mut ctx, req)?;
 
+        #[cfg(feature = "http3")]
+        let executor = ctx.executor().clone();
         let context::Parts { extensions, .. } = ctx.into_parts();
 
         let mut resp = match &self.sender {
             SendRequest::Http1(sender) => {
                 let mut sender = sender.lock().await;
                 sender.ready().await?;
-                sender.send_request(req).await
+                sender
+                    .send_request(req)
+                    .await?
+                    .map(rama_http_types::Body::new)
             }
             SendRequest::Http2(sender) => {
                 let mut sender = sender.clone();
                 sender.ready().await?;
-                sender.send_request(req).await
+                sender
+                    .send_request(req)
+                    .await?
+                    .map(rama_http_types::Body::new)
             }
-        }?;
+            #[cfg(feature = "http3")]
+            SendRequest::Http3(sender) => {
+                let mut sender = sender.clone();
+                let (parts, body) = req.into_parts();
+                let stream = sender
+                    .send_request(Request::from_parts(parts, ()))
+                    .await
+                    .context("send h3 request head")?;
+                let (mut send, mut recv) = stream.split();
+
+                // the request body is sent in its own task,
+                // such that the response can be received in the meantime
+                executor.spawn_task(async move {
+                    if let Err(err) = send_h3_body(&mut send, body).await {
+                        tracing::debug!(?err, "failed to send h3 request body");
+                    }
+                });
+
+                let resp = recv
+                    .recv_response()
+                    .await
+                    .context("receive h3 response head")?;
+                resp.map(|()| rama_http_types::Body::new(H3IncomingBody::new(recv)))
+            }
+        };
 
         resp.extensions_mut()
             .insert(RequestContextExt::from(extensions));
@@ -141,7 +199,7 @@ where
             );
         }
 
-        Ok(resp.map(rama_http_types::Body::new))
+        Ok(resp)
     }
 }
 
@@ -159,7 +217,7 @@ fn sanitize_client_req_header<S, B>(
         Version::HTTP_09 | Version::HTTP_10 | Version::HTTP_11 => {
             // remove authority and scheme for non-connect requests
             // cfr: <https://datatracker.ietf.org/doc/html/rfc2616#section-5.1.2>
-            if !ctx.contains::<ProxyAddress>() && req.uri().host().is_some() {
+            if ctx.get::<ProxyAddress>().is_none_or(is_socks5_proxy) && req.uri().host().is_some() {
                 tracing::trace!(
                     "remove authority and scheme from non-connect direct http(~1) request"
                 );
@@ -213,7 +271,7 @@ fn sanitize_client_req_header<S, B>(
                 req
             }
         }
-        Version::HTTP_2 => {
+        Version::HTTP_2 | Version::HTTP_3 => {
             // set scheme/host if not defined as otherwise pseudo
             // headers won't be possible to be set in the h2 crate
             let mut req = if req.uri().host().is_none() {
@@ -272,19 +330,16 @@ fn sanitize_client_req_header<S, B>(
                 &HOST,
             ] {
                 if let Some(header) = req.headers_mut().remove(illegal_h2_header) {
-                    tracing::trace!(?header, "removed illegal (~http1) header from h2 request");
+                    tracing::trace!(
+                        http_version = ?req.version(),
+                        ?header,
+                        "removed illegal (~http1) header from h2+ request",
+                    );
                 }
             }
 
             req
         }
-        Version::HTTP_3 => {
-            tracing::warn!(
-                uri = %req.uri(),
-                "h3 request detected, but sanitize_client_req_header does not yet support this",
-            );
-            req
-        }
         _ => {
             tracing::warn!(
                 uri = %req.uri(),
@@ -0,0 +1,208 @@
+//! Shared HTTP/3 body utilities for the client and server.
+
+use bytes::{Buf, Bytes};
+use h3::error::StreamError;
+use rama_core::error::{BoxError, ErrorContext, OpaqueError};
+use rama_http_types::{
+    HeaderMap,
+    dep::{
+        http_body::{self, Frame},
+        http_body_util::BodyExt,
+    },
+};
+use std::{
+    fmt,
+    pin::{Pin, pin},
+    task::{Context, Poll, ready},
+};
+
+/// Internal trait to abstract over the client and server
+/// receive halves of an h3 request stream.
+pub(crate) trait H3RecvStream: Send + Unpin + 'static {
+    fn poll_recv_data(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Bytes>, StreamError>>;
+
+    fn poll_recv_trailers(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, StreamError>>;
+}
+
+impl H3RecvStream for h3::server::RequestStream<h3_quinn::RecvStream, Bytes> {
+    fn poll_recv_data(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Bytes>, StreamError>> {
+        let data = ready!(self.poll_recv_data(cx))?;
+        Poll::Ready(Ok(data.map(|mut buf| buf.copy_to_bytes(buf.remaining()))))
+    }
+
+    fn poll_recv_trailers(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, StreamError>> {
+        self.poll_recv_trailers(cx)
+    }
+}
+
+impl H3RecvStream for h3::client::RequestStream<h3_quinn::RecvStream, Bytes> {
+    fn poll_recv_data(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Bytes>, StreamError>> {
+        let data = ready!(self.poll_recv_data(cx))?;
+        Poll::Ready(Ok(data.map(|mut buf| buf.copy_to_bytes(buf.remaining()))))
+    }
+
+    fn poll_recv_trailers(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, StreamError>> {
+        self.poll_recv_trailers(cx)
+    }
+}
+
+/// An [`http_body::Body`] which streams the data (and trailers)
+/// received on an h3 request stream.
+pub(crate) struct H3IncomingBody<S> {
+    stream: S,
+    data_done: bool,
+    trailers_done: bool,
+}
+
+impl<S> H3IncomingBody<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            stream,
+            data_done: false,
+            trailers_done: false,
+        }
+    }
+}
+
+impl<S> fmt::Debug for H3IncomingBody<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("H3IncomingBody")
+            .field("data_done", &self.data_done)
+            .field("trailers_done", &self.trailers_done)
+            .finish()
+    }
+}
+
+impl<S: H3RecvStream> http_body::Body for H3IncomingBody<S> {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+
+        if !this.data_done {
+            match ready!(this.stream.poll_recv_data(cx)) {
+                Ok(Some(data)) => return Poll::Ready(Some(Ok(Frame::data(data)))),
+                Ok(None) => this.data_done = true,
+                Err(err) => {
+                    this.data_done = true;
+                    this.trailers_done = true;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+            }
+        }
+
+        if !this.trailers_done {
+            let result = ready!(this.stream.poll_recv_trailers(cx));
+            this.trailers_done = true;
+            return match result {
+                Ok(Some(trailers)) => Poll::Ready(Some(Ok(Frame::trailers(trailers)))),
+                Ok(None) => Poll::Ready(None),
+                Err(err) => Poll::Ready(Some(Err(err.into()))),
+            };
+        }
+
+        Poll::Ready(None)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data_done && self.trailers_done
+    }
+}
+
+/// Send the given body over the (send half) of an h3 request stream,
+/// finishing the stream once the body is fully written.
+pub(crate) async fn send_h3_body<B, S>(stream: &mut S, body: B) -> Result<(), OpaqueError>
+where
+    B: http_body::Body<Data: Send, Error: Into<BoxError>>,
+    S: H3SendStream,
+{
+    let mut body = pin!(body);
+    loop {
+        // NOTE: frame is mapped immediately, as the body error is not required to be `Send`
+        let Some(frame) = body
+            .frame()
+            .await
+            .map(|frame| frame.map_err(|err| OpaqueError::from_boxed(err.into())))
+        else {
+            break;
+        };
+        let frame = frame.context("read http body frame")?;
+        match frame.into_data() {
+            Ok(mut data) => {
+                let data = data.copy_to_bytes(data.remaining());
+                if !data.is_empty() {
+                    stream.send_data(data).await.context("send h3 data")?;
+                }
+            }
+            Err(frame) => {
+                if let Ok(trailers) = frame.into_trailers() {
+                    stream
+                        .send_trailers(trailers)
+                        .await
+                        .context("send h3 trailers")?;
+                }
+            }
+        }
+    }
+    stream.finish().await.context("finish h3 stream")
+}
+
+/// Internal trait to abstract over the client and server
+/// send halves of an h3 request stream.
+pub(crate) trait H3SendStream: Send {
+    fn send_data(&mut self, data: Bytes) -> impl Future<Output = Result<(), StreamError>> + Send;
+
+    fn send_trailers(
+        &mut self,
+        trailers: HeaderMap,
+    ) -> impl Future<Output = Result<(), StreamError>> + Send;
+
+    fn finish(&mut self) -> impl Future<Output = Result<(), StreamError>> + Send;
+}
+
+impl H3SendStream for h3::server::RequestStream<h3_quinn::SendStream<Bytes>, Bytes> {
+    fn send_data(&mut self, data: Bytes) -> impl Future<Output = Result<(), StreamError>> + Send {
+        self.send_data(data)
+    }
+
+    fn send_trailers(
+        &mut self,
+        trailers: HeaderMap,
+    ) -> impl Future<Output = Result<(), StreamError>> + Send {
+        self.send_trailers(trailers)
+    }
+
+    fn finish(&mut self) -> impl Future<Output = Result<(), StreamError>> + Send {
+        self.finish()
+    }
+}
+
+impl H3SendStream for h3::client::RequestStream<h3_quinn::SendStream<Bytes>, Bytes> {
+    fn send_data(&mut self, data: Bytes) -> impl Future<Output = Result<(), StreamError>> + Send {
+        self.send_data(data)
+    }
+
+    fn send_trailers(
+        &mut self,
+        trailers: HeaderMap,
+    ) -> impl Future<Output = Result<(), StreamError>> + Send {
+        self.send_trailers(trailers)
+    }
+
+    fn finish(&mut self) -> impl Future<Output = Result<(), StreamError>> + Send {
+        self.finish()
+    }
+}
@@ -18,6 +18,9 @@
 pub mod client;
 pub mod server;
 
+#[cfg(feature = "http3")]
+mod h3_body;
+
 #[cfg(test)]
 mod tests {
     use super::{client::HttpConnector, server::HttpServer};
@@ -97,9 +100,119 @@ mod tests {
         Ok(Response::new(Body::from("a random response body")))
     }
 
+    #[cfg(feature = "http3")]
+    #[tokio::test]
+    async fn test_http3_multiplex() {
+        use crate::client::Http3Connector;
+        use rama_quic::client::QuicConnector;
+
+        let addr = spawn_h3_server(service_fn(server_svc_fn)).await;
+        let connector =
+            Http3Connector::new(QuicConnector::new().with_connector_data(h3_test_connector_data()));
+
+        let conn = connector
+            .serve(
+                Context::default(),
+                create_test_request_for(&format!("https://{addr}"), Version::HTTP_3),
+            )
+            .await
+            .unwrap()
+            .conn;
+
+        // We have an artificial sleep of 100ms, so multiplexing should be < 200ms
+        let start = Instant::now();
+        let (res1, res2) = join(
+            conn.serve(
+                Context::default(),
+                create_test_request_for(&format!("https://{addr}"), Version::HTTP_3),
+            ),
+            conn.serve(
+                Context::default(),
+                create_test_request_for(&format!("https://{addr}"), Version::HTTP_3),
+            ),
+        )
+        .await;
+
+        let duration = start.elapsed();
+        assert_eq!(Version::HTTP_3, res1.unwrap().version());
+        assert_eq!(Version::HTTP_3, res2.unwrap().version());
+
+        assert!(duration < Duration::from_millis(200));
+    }
+
+    #[cfg(feature = "http3")]
+    #[tokio::test]
+    async fn test_http3_easy_web_client_echo() {
+        use crate::client::EasyHttpWebClient;
+        use rama_http_types::dep::http_body_util::BodyExt;
+
+        let addr = spawn_h3_server(service_fn(async |req: Request| {
+            assert_eq!(Version::HTTP_3, req.version());
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        }))
+        .await;
+
+        let client =
+            EasyHttpWebClient::default().with_http3_connector_data(h3_test_connector_data());
+
+        let resp = client
+            .serve(
+                Context::default(),
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("https://{addr}/echo"))
+                    .version(Version::HTTP_3)
+                    .body(Body::from("hello h3"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(Version::HTTP_3, resp.version());
+        let body = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!("hello h3", body);
+    }
+
+    #[cfg(feature = "http3")]
+    async fn spawn_h3_server<S>(service: S) -> std::net::SocketAddr
+    where
+        S: Service<(), Request, Response = Response, Error = Infallible> + Clone,
+    {
+        use rama_net::tls::{ApplicationProtocol, server::SelfSignedData};
+        use rama_quic::server::QuicListener;
+        use rama_tls_rustls::server::TlsAcceptorDataBuilder;
+
+        let tls_config = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData::default())
+            .unwrap()
+            .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+            .into_rustls_config();
+        let listener = QuicListener::bind("127.0.0.1:0", tls_config).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(listener.serve(HttpServer::h3(Executor::default()).service(service)));
+
+        addr
+    }
+
+    #[cfg(feature = "http3")]
+    fn h3_test_connector_data() -> rama_tls_rustls::client::TlsConnectorData {
+        use rama_net::tls::ApplicationProtocol;
+        use rama_tls_rustls::client::TlsConnectorDataBuilder;
+
+        TlsConnectorDataBuilder::new()
+            .with_no_cert_verifier()
+            .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+            .build()
+    }
+
     fn create_test_request(version: Version) -> Request {
+        create_test_request_for("https://www.example.com", version)
+    }
+
+    fn create_test_request_for(uri: &str, version: Version) -> Request {
         Request::builder()
-            .uri("https://www.example.com")
+            .uri(uri)
             .version(version)
             .body(Body::from("a reandom request body"))
             .unwrap()
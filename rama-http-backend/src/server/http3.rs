@@ -0,0 +1,156 @@
+//! HTTP/3 server support, serving HTTP over QUIC connections.
+
+use super::HttpServeResult;
+use crate::h3_body::{H3IncomingBody, send_h3_body};
+use bytes::Bytes;
+use h3::server::RequestResolver;
+use rama_core::error::{BoxError, ErrorContext, OpaqueError};
+use rama_core::rt::Executor;
+use rama_core::{Context, Service};
+use rama_http_types::{Body, IntoResponse, Request, Response};
+use rama_quic::QuicConnection;
+use rama_utils::future::Fuse;
+use std::convert::Infallible;
+use std::pin::pin;
+use tokio::select;
+
+/// A builder used to configure and serve HTTP/3 connections,
+/// established over QUIC (see `rama-quic`).
+///
+/// Usually used via [`HttpServer::h3`].
+///
+/// [`HttpServer::h3`]: super::HttpServer::h3
+#[derive(Debug, Clone)]
+pub struct Http3Builder {
+    exec: Executor,
+    max_field_section_size: Option<u64>,
+    send_grease: Option<bool>,
+}
+
+impl Http3Builder {
+    /// Create a new [`Http3Builder`] with default settings,
+    /// using the given [`Executor`] to spawn the request tasks.
+    pub fn new(exec: Executor) -> Self {
+        Self {
+            exec,
+            max_field_section_size: None,
+            send_grease: None,
+        }
+    }
+
+    /// Set the maximum header size the server is willing to accept.
+    ///
+    /// See [header size constraints] section of the specification for details.
+    ///
+    /// [header size constraints]: https://www.rfc-editor.org/rfc/rfc9114.html#name-header-size-constraints
+    pub fn max_field_section_size(&mut self, value: u64) -> &mut Self {
+        self.max_field_section_size = Some(value);
+        self
+    }
+
+    /// Set whether to send reserved (grease) frames, settings and streams,
+    /// which is done by default, as a way to exercise peer extensibility.
+    pub fn send_grease(&mut self, value: bool) -> &mut Self {
+        self.send_grease = Some(value);
+        self
+    }
+
+    /// Serve a single established [`QuicConnection`] as HTTP/3.
+    ///
+    /// Each request is served using a clone of the given [`Context`],
+    /// in its own task spawned with the builder's [`Executor`].
+    pub async fn serve_connection<State, S, R>(
+        &self,
+        ctx: Context<State>,
+        conn: QuicConnection,
+        service: S,
+    ) -> HttpServeResult
+    where
+        State: Clone + Send + Sync + 'static,
+        S: Service<State, Request, Response = R, Error = Infallible> + Clone,
+        R: IntoResponse + Send + 'static,
+    {
+        let mut builder = h3::server::builder();
+        if let Some(value) = self.max_field_section_size {
+            builder.max_field_section_size(value);
+        }
+        if let Some(value) = self.send_grease {
+            builder.send_grease(value);
+        }
+
+        let mut conn: h3::server::Connection<_, Bytes> =
+            match builder.build(h3_quinn::Connection::new(conn)).await {
+                Ok(conn) => conn,
+                Err(err) => return map_h3_conn_result(Err(err)),
+            };
+
+        let guard = ctx.guard().cloned();
+        let mut cancelled_fut = pin!(Fuse::new(async move {
+            match guard {
+                Some(guard) => guard.cancelled().await,
+                None => std::future::pending().await,
+            }
+        }));
+
+        loop {
+            let resolver = select! {
+                _ = cancelled_fut.as_mut() => {
+                    tracing::trace!("signal received: initiate graceful shutdown");
+                    // allow in-flight requests to finish, but no new ones
+                    return map_h3_conn_result(conn.shutdown(0).await);
+                }
+                result = conn.accept() => match result {
+                    Ok(Some(resolver)) => resolver,
+                    Ok(None) => {
+                        tracing::trace!("connection finished");
+                        return Ok(());
+                    }
+                    Err(err) => return map_h3_conn_result(Err(err)),
+                }
+            };
+
+            let ctx = ctx.clone();
+            let service = service.clone();
+            self.exec.spawn_task(async move {
+                if let Err(err) = serve_request(ctx, resolver, service).await {
+                    tracing::debug!(?err, "failed to serve h3 request");
+                }
+            });
+        }
+    }
+}
+
+async fn serve_request<State, S, R>(
+    ctx: Context<State>,
+    resolver: RequestResolver<h3_quinn::Connection, Bytes>,
+    service: S,
+) -> Result<(), OpaqueError>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request, Response = R, Error = Infallible>,
+    R: IntoResponse + Send + 'static,
+{
+    let (req, stream) = resolver
+        .resolve_request()
+        .await
+        .context("resolve h3 request")?;
+    let (mut send, recv) = stream.split();
+
+    let req = req.map(|()| Body::new(H3IncomingBody::new(recv)));
+    let Ok(resp) = service.serve(ctx, req).await;
+    let (parts, body) = resp.into_response().into_parts();
+
+    send.send_response(Response::from_parts(parts, ()))
+        .await
+        .context("send h3 response head")?;
+    send_h3_body(&mut send, body).await
+}
+
+/// A utility function to map h3 connection errors to our own error type.
+fn map_h3_conn_result(result: Result<(), h3::error::ConnectionError>) -> HttpServeResult {
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) if err.is_h3_no_error() => Ok(()),
+        Err(err) => Err(BoxError::from(err)),
+    }
+}
@@ -9,4 +9,10 @@ pub use service::HttpServer;
 
 mod hyper_conn;
 
+#[cfg(feature = "http3")]
+mod http3;
+#[cfg(feature = "http3")]
+#[doc(inline)]
+pub use http3::Http3Builder;
+
 pub mod layer;
@@ -19,9 +19,16 @@ use std::convert::Infallible;
 use std::fmt;
 use std::sync::Arc;
 
+#[cfg(feature = "http3")]
+use super::Http3Builder;
+#[cfg(feature = "http3")]
+use rama_quic::{QuicConnection, server::QuicListener};
+#[cfg(feature = "http3")]
+use rama_tls_rustls::dep::rustls;
+
 /// A builder for configuring and listening over HTTP using a [`Service`].
 ///
-/// Supported Protocols: HTTP/1, H2, Auto (HTTP/1 + H2)
+/// Supported Protocols: HTTP/1, H2, Auto (HTTP/1 + H2), H3 (requires the `http3` feature)
 ///
 /// [`Service`]: rama_core::Service
 pub struct HttpServer<B> {
@@ -131,6 +138,103 @@ impl HttpServer<AutoConnBuilder> {
     }
 }
 
+#[cfg(feature = "http3")]
+impl HttpServer<Http3Builder> {
+    /// Create a new h3 `Builder` with default settings.
+    ///
+    /// Contrary to the other builders, this one serves
+    /// [`QuicConnection`]s instead of IO Byte streams.
+    pub fn h3(exec: Executor) -> Self {
+        let guard = exec.guard().cloned();
+        Self {
+            builder: Http3Builder::new(exec),
+            guard,
+        }
+    }
+
+    /// H3 configuration.
+    pub fn h3_mut(&mut self) -> &mut Http3Builder {
+        &mut self.builder
+    }
+
+    /// Turn this `HttpServer` into a [`Service`] that can be used to serve
+    /// [`QuicConnection`]s as HTTP/3.
+    pub fn service<S>(self, service: S) -> HttpService<Http3Builder, S> {
+        HttpService::new(self.builder, service)
+    }
+
+    /// Serve a single [`QuicConnection`] as HTTP/3.
+    pub async fn serve<State, S, Response>(
+        &self,
+        ctx: Context<State>,
+        conn: QuicConnection,
+        service: S,
+    ) -> HttpServeResult
+    where
+        State: Clone + Send + Sync + 'static,
+        S: Service<State, Request, Response = Response, Error = Infallible> + Clone,
+        Response: IntoResponse + Send + 'static,
+    {
+        self.builder.serve_connection(ctx, conn, service).await
+    }
+
+    /// Listen for QUIC connections on the given (UDP) address, serving HTTP/3 connections.
+    ///
+    /// The given [`rustls::ServerConfig`] is used to secure the QUIC connections,
+    /// and should have the `h3` ALPN protocol configured.
+    ///
+    /// It's a shortcut in case you don't need to operate on the transport layer directly.
+    pub async fn listen<S, Response, A>(
+        self,
+        addr: A,
+        tls_config: impl Into<Arc<rustls::ServerConfig>>,
+        service: S,
+    ) -> HttpServeResult
+    where
+        S: Service<(), Request, Response = Response, Error = Infallible>,
+        Response: IntoResponse + Send + 'static,
+        A: TryInto<SocketAddress, Error: Into<BoxError>>,
+    {
+        let quic = QuicListener::bind(addr, tls_config).await?;
+        let service = HttpService::new(self.builder, service);
+        match self.guard {
+            Some(guard) => quic.serve_graceful(guard, service).await,
+            None => quic.serve(service).await,
+        };
+        Ok(())
+    }
+
+    /// Listen for QUIC connections on the given (UDP) address, serving HTTP/3 connections.
+    ///
+    /// Same as [`Self::listen`], but including the given state in the [`Service`]'s [`Context`].
+    ///
+    /// [`Service`]: rama_core::Service
+    /// [`Context`]: rama_core::Context
+    pub async fn listen_with_state<State, S, Response, A>(
+        self,
+        state: State,
+        addr: A,
+        tls_config: impl Into<Arc<rustls::ServerConfig>>,
+        service: S,
+    ) -> HttpServeResult
+    where
+        State: Clone + Send + Sync + 'static,
+        S: Service<State, Request, Response = Response, Error = Infallible>,
+        Response: IntoResponse + Send + 'static,
+        A: TryInto<SocketAddress, Error: Into<BoxError>>,
+    {
+        let quic = QuicListener::build_with_state(state)
+            .bind(addr, tls_config)
+            .await?;
+        let service = HttpService::new(self.builder, service);
+        match self.guard {
+            Some(guard) => quic.serve_graceful(guard, service).await,
+            None => quic.serve(service).await,
+        };
+        Ok(())
+    }
+}
+
 impl<B> HttpServer<B>
 where
     B: HttpCoreConnServer,
@@ -263,3 +367,23 @@ where
             .http_core_serve_connection(ctx, stream, service)
     }
 }
+
+#[cfg(feature = "http3")]
+impl<State, S, Response> Service<State, QuicConnection> for HttpService<Http3Builder, S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request, Response = Response, Error = Infallible>,
+    Response: IntoResponse + Send + 'static,
+{
+    type Response = ();
+    type Error = rama_core::error::BoxError;
+
+    fn serve(
+        &self,
+        ctx: Context<State>,
+        conn: QuicConnection,
+    ) -> impl Future<Output = Result<Self::Response, Self::Error>> + Send + '_ {
+        let service = self.service.clone();
+        self.builder.serve_connection(ctx, conn, service)
+    }
+}
@@ -0,0 +1,330 @@
+use crate::headers::{self, Header};
+use crate::{HeaderName, HeaderValue};
+use std::fmt;
+use std::time::Duration;
+
+/// `Alt-Svc` header, defined in [RFC7838](https://datatracker.ietf.org/doc/html/rfc7838#section-3)
+///
+/// The `Alt-Svc` header field is used by servers to advertise
+/// alternative services (e.g. HTTP/3 over QUIC) through which
+/// the same resources can be reached.
+///
+/// # ABNF
+///
+/// ```text
+/// Alt-Svc       = clear / 1#alt-value
+/// clear         = %s"clear"; "clear", case-sensitive
+/// alt-value     = alternative *( OWS ";" OWS parameter )
+/// alternative   = protocol-id "=" alt-authority
+/// protocol-id   = token ; percent-encoded ALPN protocol name
+/// alt-authority = quoted-string ; containing [ uri-host ] ":" port
+/// parameter     = token "=" ( token / quoted-string )
+/// ```
+///
+/// # Example values
+/// * `h3=":443"; ma=86400`
+/// * `h3="alt.example.com:8443", h2=":443"; ma=3600; persist=1`
+/// * `clear`
+///
+/// # Examples
+/// ```
+/// use rama_http_types::headers::{AltService, AltSvc, HeaderMapExt};
+///
+/// let mut headers = rama_http_types::HeaderMap::new();
+/// headers.typed_insert(AltSvc::from(AltService::h3(443)));
+/// assert_eq!(headers["alt-svc"], "h3=\":443\"");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AltSvc {
+    /// Invalidate all alternative services of the origin.
+    Clear,
+    /// The advertised alternative services, in order of preference.
+    Services(Vec<AltService>),
+}
+
+/// A single alternative service as advertised by the [`AltSvc`] header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltService {
+    protocol_id: String,
+    host: Option<String>,
+    port: u16,
+    max_age: Option<Duration>,
+    persist: bool,
+}
+
+/// Default freshness lifetime of an alternative service (24 hours),
+/// as defined in [RFC7838](https://datatracker.ietf.org/doc/html/rfc7838#section-3.1).
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(86400);
+
+impl AltService {
+    /// Create a new [`AltService`] for the given ALPN protocol id, reachable
+    /// on the given port of the same host as the origin.
+    pub fn new(protocol_id: impl Into<String>, port: u16) -> Self {
+        Self {
+            protocol_id: protocol_id.into(),
+            host: None,
+            port,
+            max_age: None,
+            persist: false,
+        }
+    }
+
+    /// Create a new `h3` [`AltService`], reachable on the given (UDP) port
+    /// of the same host as the origin.
+    pub fn h3(port: u16) -> Self {
+        Self::new("h3", port)
+    }
+
+    rama_utils::macros::generate_field_setters!(host, String);
+    rama_utils::macros::generate_field_setters!(max_age, Duration);
+
+    /// Set whether or not this alternative service should
+    /// persist across network configuration changes.
+    pub fn with_persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    /// Set whether or not this alternative service should
+    /// persist across network configuration changes.
+    pub fn set_persist(&mut self, persist: bool) -> &mut Self {
+        self.persist = persist;
+        self
+    }
+
+    /// The ALPN protocol id of this alternative service, e.g. `h3`.
+    pub fn protocol_id(&self) -> &str {
+        &self.protocol_id
+    }
+
+    /// The host of this alternative service,
+    /// `None` in case it is the same host as the origin.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// The port of this alternative service.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The freshness lifetime of this alternative service,
+    /// defaulting to 24 hours if not explicitly advertised.
+    pub fn max_age(&self) -> Duration {
+        self.max_age.unwrap_or(DEFAULT_MAX_AGE)
+    }
+
+    /// Returns true if this alternative service should
+    /// persist across network configuration changes.
+    pub fn persist(&self) -> bool {
+        self.persist
+    }
+
+    /// Returns true if this alternative service is reachable over HTTP/3.
+    pub fn is_h3(&self) -> bool {
+        self.protocol_id == "h3"
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split(';');
+
+        let (protocol_id, alt_authority) = parts.next()?.trim().split_once('=')?;
+        let protocol_id = protocol_id.trim();
+        if protocol_id.is_empty() {
+            return None;
+        }
+        let alt_authority = unquote(alt_authority.trim())?;
+        let (host, port) = alt_authority.rsplit_once(':')?;
+        let port = port.parse().ok()?;
+        let host = (!host.is_empty()).then(|| host.to_owned());
+
+        let mut service = Self {
+            protocol_id: protocol_id.to_owned(),
+            host,
+            port,
+            max_age: None,
+            persist: false,
+        };
+
+        for param in parts {
+            let Some((name, value)) = param.trim().split_once('=') else {
+                continue;
+            };
+            let value = unquote(value.trim()).unwrap_or(value.trim());
+            match name.trim() {
+                "ma" => service.max_age = Some(Duration::from_secs(value.parse().ok()?)),
+                "persist" => service.persist = value == "1",
+                _ => (), // unknown parameters are to be ignored
+            }
+        }
+
+        Some(service)
+    }
+}
+
+fn unquote(s: &str) -> Option<&str> {
+    s.strip_prefix('"')?.strip_suffix('"')
+}
+
+impl fmt::Display for AltService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}=\"{}:{}\"",
+            self.protocol_id,
+            self.host.as_deref().unwrap_or_default(),
+            self.port
+        )?;
+        if let Some(max_age) = self.max_age {
+            write!(f, "; ma={}", max_age.as_secs())?;
+        }
+        if self.persist {
+            write!(f, "; persist=1")?;
+        }
+        Ok(())
+    }
+}
+
+impl AltSvc {
+    /// Returns an iterator over the advertised alternative services,
+    /// which is empty in case of [`AltSvc::Clear`].
+    pub fn iter(&self) -> impl Iterator<Item = &AltService> {
+        match self {
+            AltSvc::Clear => [].iter(),
+            AltSvc::Services(services) => services.iter(),
+        }
+    }
+}
+
+impl From<AltService> for AltSvc {
+    fn from(value: AltService) -> Self {
+        AltSvc::Services(vec![value])
+    }
+}
+
+impl FromIterator<AltService> for AltSvc {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = AltService>,
+    {
+        AltSvc::Services(iter.into_iter().collect())
+    }
+}
+
+impl Header for AltSvc {
+    fn name() -> &'static HeaderName {
+        &crate::header::ALT_SVC
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let mut services = Vec::new();
+        for value in values {
+            let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+            if value.trim() == "clear" {
+                return Ok(AltSvc::Clear);
+            }
+            for alt_value in value.split(',') {
+                if alt_value.trim().is_empty() {
+                    continue;
+                }
+                services.push(AltService::parse(alt_value).ok_or_else(headers::Error::invalid)?);
+            }
+        }
+        if services.is_empty() {
+            return Err(headers::Error::invalid());
+        }
+        Ok(AltSvc::Services(services))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = match self {
+            AltSvc::Clear => HeaderValue::from_static("clear"),
+            AltSvc::Services(services) => {
+                let s = services
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                HeaderValue::from_str(&s).unwrap()
+            }
+        };
+        values.extend(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(values: &[&'static str]) -> Option<AltSvc> {
+        AltSvc::decode(
+            &mut values
+                .iter()
+                .map(|s| HeaderValue::from_static(s))
+                .collect::<Vec<_>>()
+                .iter(),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn test_decode_alt_svc() {
+        assert_eq!(decode(&["clear"]), Some(AltSvc::Clear));
+        assert_eq!(
+            decode(&["h3=\":443\"; ma=86400"]),
+            Some(AltSvc::from(
+                AltService::h3(443).with_max_age(Duration::from_secs(86400))
+            ))
+        );
+        assert_eq!(
+            decode(&["h3=\"alt.example.com:8443\"; persist=1, h2=\":443\"; foo=\"bar\""]),
+            Some(AltSvc::Services(vec![
+                AltService::h3(8443)
+                    .with_host("alt.example.com".to_owned())
+                    .with_persist(true),
+                AltService::new("h2", 443),
+            ]))
+        );
+        assert_eq!(
+            decode(&["h3-29=\":443\"", "h3=\":443\""])
+                .unwrap()
+                .iter()
+                .map(|svc| svc.protocol_id().to_owned())
+                .collect::<Vec<_>>(),
+            vec!["h3-29".to_owned(), "h3".to_owned()],
+        );
+    }
+
+    #[test]
+    fn test_decode_alt_svc_invalid() {
+        assert_eq!(decode(&[""]), None);
+        assert_eq!(decode(&["h3"]), None);
+        assert_eq!(decode(&["h3=:443"]), None);
+        assert_eq!(decode(&["h3=\"example.com\""]), None);
+        assert_eq!(decode(&["h3=\":443\"; ma=forever"]), None);
+    }
+
+    #[test]
+    fn test_encode_alt_svc() {
+        let mut values = Vec::new();
+        AltSvc::Services(vec![
+            AltService::h3(443).with_max_age(Duration::from_secs(3600)),
+            AltService::h3(8443)
+                .with_host("alt.example.com".to_owned())
+                .with_persist(true),
+        ])
+        .encode(&mut values);
+        assert_eq!(
+            values,
+            vec![HeaderValue::from_static(
+                "h3=\":443\"; ma=3600, h3=\"alt.example.com:8443\"; persist=1"
+            )]
+        );
+
+        let mut values = Vec::new();
+        AltSvc::Clear.encode(&mut values);
+        assert_eq!(values, vec![HeaderValue::from_static("clear")]);
+    }
+}
@@ -1,2 +1,5 @@
 mod accept;
 pub use accept::Accept;
+
+mod alt_svc;
+pub use alt_svc::{AltService, AltSvc};
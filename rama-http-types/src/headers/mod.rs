@@ -97,7 +97,7 @@ pub mod specifier;
 pub mod util;
 
 mod common;
-pub use common::Accept;
+pub use common::{Accept, AltService, AltSvc};
 
 mod client_hints;
 pub use client_hints::{
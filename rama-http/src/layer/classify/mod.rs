@@ -1,6 +1,7 @@
 //! Tools for classifying responses as either success or failure.
 
 use crate::{HeaderMap, Request, Response, StatusCode};
+use rama_core::layer::circuit_breaker::FailurePredicate;
 use std::{convert::Infallible, fmt, marker::PhantomData};
 
 pub(crate) mod grpc_errors_as_failures;
@@ -122,6 +123,28 @@ where
     }
 }
 
+/// Use the classifier to decide whether a request failed for a [`CircuitBreaker`],
+/// e.g. to also consider `5xx` responses as failures using [`ServerErrorsAsFailures`].
+///
+/// Responses which can only be classified at the end of their stream are not
+/// considered as failures, as the circuit breaker does not wait for the body.
+///
+/// [`CircuitBreaker`]: rama_core::layer::CircuitBreaker
+impl<C, B, E> FailurePredicate<Response<B>, E> for SharedClassifier<C>
+where
+    C: ClassifyResponse + Clone,
+{
+    fn is_failure(&self, result: &Result<Response<B>, E>) -> bool {
+        match result {
+            Ok(res) => matches!(
+                self.classifier.clone().classify_response(res),
+                ClassifiedResponse::Ready(Err(_))
+            ),
+            Err(_) => true,
+        }
+    }
+}
+
 /// Trait for classifying responses as either success or failure. Designed to support both unary
 /// requests (single request for a single response) as well as streaming responses.
 ///
@@ -366,3 +389,48 @@ impl fmt::Display for ServerErrorsFailureClass {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::error::BoxError;
+    use rama_core::layer::circuit_breaker::{CircuitOpen, CircuitState};
+    use rama_core::layer::{CircuitBreakerLayer, Layer};
+    use rama_core::service::service_fn;
+    use rama_core::{Context, Service};
+
+    #[tokio::test]
+    async fn test_shared_classifier_as_circuit_breaker_failure_predicate() {
+        // a request of `None` results in an error
+        let service = CircuitBreakerLayer::new()
+            .with_failure_threshold(2)
+            .with_failure_predicate(ServerErrorsAsFailures::make_classifier())
+            .into_layer(service_fn(async |_, status: Option<u16>| {
+                let status = status.ok_or_else(|| BoxError::from("connection reset"))?;
+                Ok::<_, BoxError>(
+                    Response::builder()
+                        .status(StatusCode::from_u16(status).unwrap())
+                        .body(())
+                        .unwrap(),
+                )
+            }));
+
+        // successes and client errors reset the consecutive failures
+        for request in [Some(500), Some(404), None, Some(200), Some(503), Some(429)] {
+            let result = service.serve(Context::default(), request).await;
+            assert_eq!(result.is_err(), request.is_none());
+            assert_eq!(service.state(&()), CircuitState::Closed);
+        }
+
+        let res = service.serve(Context::default(), Some(502)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+        assert!(service.serve(Context::default(), None).await.is_err());
+        assert_eq!(service.state(&()), CircuitState::Open);
+
+        let err = service
+            .serve(Context::default(), Some(200))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<CircuitOpen>().is_some());
+    }
+}
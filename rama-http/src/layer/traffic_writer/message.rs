@@ -0,0 +1,119 @@
+use bytes::Bytes;
+use std::fmt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::{Sender, UnboundedSender};
+
+/// A trait for writing messages relayed over an upgraded http connection,
+/// such as WebSocket messages.
+pub trait MessageWriter: Send + Sync + 'static {
+    /// Write the upgraded message.
+    fn write_message(&self, msg: UpgradedMessage) -> impl Future<Output = ()> + Send + '_;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The direction in which an [`UpgradedMessage`] was relayed.
+pub enum MessageDirection {
+    /// The message was sent by the client (to the server).
+    ClientToServer,
+    /// The message was sent by the server (to the client).
+    ServerToClient,
+}
+
+impl fmt::Display for MessageDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClientToServer => f.write_str(">"),
+            Self::ServerToClient => f.write_str("<"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A message relayed over an upgraded http connection.
+pub struct UpgradedMessage {
+    /// The direction in which the message was relayed.
+    pub direction: MessageDirection,
+    /// The protocol of the upgraded connection (e.g. `websocket`).
+    pub protocol: &'static str,
+    /// The protocol specific kind of message (e.g. `text`).
+    pub kind: &'static str,
+    /// The payload of the message.
+    pub payload: Bytes,
+}
+
+/// Write an [`UpgradedMessage`] to the given writer.
+///
+/// The headline (direction, protocol, kind and size) is written in case `write_headers` is true,
+/// while the payload is written (as lossy utf-8) in case `write_body` is true.
+pub(super) async fn write_upgraded_message<W>(
+    w: &mut W,
+    msg: &UpgradedMessage,
+    write_headers: bool,
+    write_body: bool,
+) -> Result<(), std::io::Error>
+where
+    W: AsyncWrite + Unpin + Send + Sync + 'static,
+{
+    if write_headers {
+        w.write_all(
+            format!(
+                "{} {} {} ({} bytes)\r\n",
+                msg.direction,
+                msg.protocol,
+                msg.kind,
+                msg.payload.len(),
+            )
+            .as_bytes(),
+        )
+        .await?;
+    }
+    if write_body && !msg.payload.is_empty() {
+        w.write_all(String::from_utf8_lossy(&msg.payload).as_bytes())
+            .await?;
+        w.write_all(b"\r\n").await?;
+    }
+    Ok(())
+}
+
+impl MessageWriter for Sender<UpgradedMessage> {
+    async fn write_message(&self, msg: UpgradedMessage) {
+        if let Err(err) = self.send(msg).await {
+            tracing::error!(err = %err, "failed to send upgraded message to channel")
+        }
+    }
+}
+
+impl MessageWriter for UnboundedSender<UpgradedMessage> {
+    async fn write_message(&self, msg: UpgradedMessage) {
+        if let Err(err) = self.send(msg) {
+            tracing::error!(err = %err, "failed to send upgraded message to unbounded channel")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_upgraded_message() {
+        let msg = UpgradedMessage {
+            direction: MessageDirection::ServerToClient,
+            protocol: "websocket",
+            kind: "text",
+            payload: Bytes::from_static(b"hello"),
+        };
+
+        let mut buf = Vec::new();
+        write_upgraded_message(&mut buf, &msg, true, true)
+            .await
+            .unwrap();
+        assert_eq!(buf, b"< websocket text (5 bytes)\r\nhello\r\n");
+
+        let mut buf = Vec::new();
+        write_upgraded_message(&mut buf, &msg, true, false)
+            .await
+            .unwrap();
+        assert_eq!(buf, b"< websocket text (5 bytes)\r\n");
+    }
+}
@@ -1,6 +1,10 @@
 //! Middleware to write Http traffic in std format.
 //!
 //! Can be useful for cli / debug purposes.
+//!
+//! Messages relayed over upgraded connections (e.g. WebSocket messages)
+//! can be written as well, using the [`MessageWriter`] implementation
+//! of the [`BidirectionalWriter`].
 
 use crate::{
     Request, Response,
@@ -16,6 +20,11 @@ mod request;
 #[doc(inline)]
 pub use request::{DoNotWriteRequest, RequestWriter, RequestWriterInspector};
 
+mod message;
+use message::write_upgraded_message;
+#[doc(inline)]
+pub use message::{MessageDirection, MessageWriter, UpgradedMessage};
+
 mod response;
 #[doc(inline)]
 pub use response::{
@@ -107,6 +116,22 @@ impl BidirectionalWriter<UnboundedSender<BidirectionalMessage>> {
                             tracing::error!(err = %err, "failed to write http response to writer")
                         }
                     }
+                    BidirectionalMessage::Message(msg) => {
+                        let (write_headers, write_body) = match msg.direction {
+                            MessageDirection::ClientToServer => {
+                                (write_request_headers, write_request_body)
+                            }
+                            MessageDirection::ServerToClient => {
+                                (write_response_headers, write_response_body)
+                            }
+                        };
+                        if let Err(err) =
+                            write_upgraded_message(&mut writer, &msg, write_headers, write_body)
+                                .await
+                        {
+                            tracing::error!(err = %err, "failed to write upgraded message to writer")
+                        }
+                    }
                 }
                 if let Err(err) = writer.write_all(b"\r\n").await {
                     tracing::error!(err = %err, "failed to write separator to writer")
@@ -192,6 +217,22 @@ impl BidirectionalWriter<Sender<BidirectionalMessage>> {
                             tracing::error!(err = %err, "failed to write http response to writer")
                         }
                     }
+                    BidirectionalMessage::Message(msg) => {
+                        let (write_headers, write_body) = match msg.direction {
+                            MessageDirection::ClientToServer => {
+                                (write_request_headers, write_request_body)
+                            }
+                            MessageDirection::ServerToClient => {
+                                (write_response_headers, write_response_body)
+                            }
+                        };
+                        if let Err(err) =
+                            write_upgraded_message(&mut writer, &msg, write_headers, write_body)
+                                .await
+                        {
+                            tracing::error!(err = %err, "failed to write upgraded message to writer")
+                        }
+                    }
                 }
                 if let Err(err) = writer.write_all(b"\r\n").await {
                     tracing::error!(err = %err, "failed to write separator to writer")
@@ -203,6 +244,8 @@ impl BidirectionalWriter<Sender<BidirectionalMessage>> {
     }
 
     /// Create a new [`BidirectionalWriter`] with a custom writer that only writes the last request and response received.
+    ///
+    /// Upgraded messages are not written by this writer.
     pub fn last<W>(
         executor: &Executor,
         mut writer: W,
@@ -235,6 +278,7 @@ impl BidirectionalWriter<Sender<BidirectionalMessage>> {
                 match msg {
                     BidirectionalMessage::Request(req) => last_request = Some(req),
                     BidirectionalMessage::Response(res) => last_response = Some(res),
+                    BidirectionalMessage::Message(_) => (),
                 }
             }
 
@@ -355,6 +399,22 @@ impl ResponseWriter for BidirectionalWriter<Sender<BidirectionalMessage>> {
     }
 }
 
+impl MessageWriter for BidirectionalWriter<UnboundedSender<BidirectionalMessage>> {
+    async fn write_message(&self, msg: UpgradedMessage) {
+        if let Err(err) = self.sender.send(BidirectionalMessage::Message(msg)) {
+            tracing::error!(err = %err, "failed to send upgraded message to writer over unbounded channel")
+        }
+    }
+}
+
+impl MessageWriter for BidirectionalWriter<Sender<BidirectionalMessage>> {
+    async fn write_message(&self, msg: UpgradedMessage) {
+        if let Err(err) = self.sender.send(BidirectionalMessage::Message(msg)).await {
+            tracing::error!(err = %err, "failed to send upgraded message to writer over bounded channel")
+        }
+    }
+}
+
 /// The internal message type for the [`BidirectionalWriter`].
 #[derive(Debug)]
 pub enum BidirectionalMessage {
@@ -362,4 +422,9 @@ pub enum BidirectionalMessage {
     Request(Request),
     /// A response to be written.
     Response(Response),
+    /// A message relayed over an upgraded connection to be written.
+    ///
+    /// The request [`WriterMode`] is used for messages sent by the client,
+    /// while the response [`WriterMode`] is used for messages sent by the server.
+    Message(UpgradedMessage),
 }
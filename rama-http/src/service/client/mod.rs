@@ -3,3 +3,7 @@
 mod ext;
 #[doc(inline)]
 pub use ext::{HttpClientExt, IntoUrl, RequestBuilder};
+
+mod readiness;
+#[doc(inline)]
+pub use readiness::HttpReadinessCheck;
@@ -0,0 +1,193 @@
+use crate::{Body, Method, Request, Response, Scheme};
+use rama_core::{Context, Service, error::BoxError, service::balance::ReadinessCheck};
+use std::{borrow::Cow, fmt, time::Duration};
+
+/// A [`ReadinessCheck`] which requests the readiness endpoint of an upstream,
+/// considering it ready in case it responds with a 2xx status code in time.
+///
+/// The request is sent to `{scheme}://{key}{path}` using the service of the endpoint,
+/// where the scheme defaults to `http` and the path defaults to the readiness endpoint
+/// of the [`k8s_health`] service.
+///
+/// [`k8s_health`]: crate::service::web::k8s_health
+#[derive(Debug, Clone)]
+pub struct HttpReadinessCheck {
+    scheme: Scheme,
+    path: Cow<'static, str>,
+    timeout: Duration,
+}
+
+impl HttpReadinessCheck {
+    /// Default path of the readiness endpoint.
+    pub const DEFAULT_PATH: &'static str = "/k8s/ready";
+
+    /// Default duration after which a readiness request is considered failed.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Create a new [`HttpReadinessCheck`], requesting `http://{key}/k8s/ready`
+    /// with a timeout of 2 seconds.
+    pub const fn new() -> Self {
+        Self {
+            scheme: Scheme::HTTP,
+            path: Cow::Borrowed(Self::DEFAULT_PATH),
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set the scheme used to request the readiness endpoint,
+    /// e.g. [`Scheme::HTTPS`] for upstreams only served over TLS.
+    pub fn set_scheme(&mut self, scheme: Scheme) -> &mut Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Same as [`Self::set_scheme`] but consuming self.
+    pub fn with_scheme(mut self, scheme: Scheme) -> Self {
+        self.set_scheme(scheme);
+        self
+    }
+
+    /// Set the path of the readiness endpoint.
+    pub fn set_path(&mut self, path: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Same as [`Self::set_path`] but consuming self.
+    pub fn with_path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.set_path(path);
+        self
+    }
+
+    /// Set the duration after which a readiness request is considered failed.
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Same as [`Self::set_timeout`] but consuming self.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for HttpReadinessCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, S, B> ReadinessCheck<K, S> for HttpReadinessCheck
+where
+    K: fmt::Display + Sync,
+    S: Service<(), Request, Response = Response<B>, Error: Into<BoxError>>,
+    B: Send + 'static,
+{
+    async fn is_ready(&self, key: &K, service: &S) -> bool {
+        let req = match Request::builder()
+            .method(Method::GET)
+            .uri(format!("{}://{key}{}", self.scheme, self.path))
+            .body(Body::empty())
+        {
+            Ok(req) => req,
+            Err(err) => {
+                tracing::debug!(%key, error = %err, "http readiness check: invalid request");
+                return false;
+            }
+        };
+
+        match tokio::time::timeout(self.timeout, service.serve(Context::default(), req)).await {
+            Ok(Ok(resp)) => resp.status().is_success(),
+            Ok(Err(err)) => {
+                let err: BoxError = err.into();
+                tracing::debug!(%key, error = %err, "http readiness check: request failed");
+                false
+            }
+            Err(_) => {
+                tracing::debug!(%key, "http readiness check: request timed out");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StatusCode;
+    use crate::service::web::k8s_health_builder;
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn test_http_readiness_check() {
+        let ready = k8s_health_builder::<()>().ready(|| true).build();
+        assert!(HttpReadinessCheck::new().is_ready(&"a:80", &ready).await);
+
+        let not_ready = k8s_health_builder::<()>().ready(|| false).build();
+        assert!(
+            !HttpReadinessCheck::new()
+                .is_ready(&"a:80", &not_ready)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_readiness_check_path() {
+        let service = service_fn(async |req: Request| {
+            if req.uri().path() == "/healthz" {
+                Ok::<_, Infallible>(Response::new(Body::empty()))
+            } else {
+                let mut resp = Response::new(Body::empty());
+                *resp.status_mut() = StatusCode::NOT_FOUND;
+                Ok(resp)
+            }
+        });
+        assert!(!HttpReadinessCheck::new().is_ready(&"a:80", &service).await);
+        assert!(
+            HttpReadinessCheck::new()
+                .with_path("/healthz")
+                .is_ready(&"a:80", &service)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_readiness_check_uri() {
+        let service = service_fn(async |req: Request| {
+            let mut resp = Response::new(Body::empty());
+            if req.uri() != "https://a:443/healthz" {
+                *resp.status_mut() = StatusCode::NOT_FOUND;
+            }
+            Ok::<_, Infallible>(resp)
+        });
+        assert!(
+            !HttpReadinessCheck::new()
+                .with_path("/healthz")
+                .is_ready(&"a:443", &service)
+                .await
+        );
+        assert!(
+            HttpReadinessCheck::new()
+                .with_scheme(Scheme::HTTPS)
+                .with_path("/healthz")
+                .is_ready(&"a:443", &service)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_readiness_check_timeout() {
+        let slow = service_fn(async || {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        });
+        assert!(
+            !HttpReadinessCheck::new()
+                .with_timeout(Duration::from_millis(10))
+                .is_ready(&"a:80", &slow)
+                .await
+        );
+    }
+}
@@ -636,7 +636,14 @@ mod tests {
                 ext.insert(NegotiatedTlsParameters {
                     protocol_version: negotiated_protocol_version,
                     application_layer_protocol: None,
+                    cipher_suite: None,
+                    key_exchange_group: None,
+                    signature_scheme: None,
+                    server_name: None,
                     peer_certificate_chain: None,
+                    peer_certificate: None,
+                    session_resumed: false,
+                    ech_accepted: false,
                 });
             }
 
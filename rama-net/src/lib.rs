@@ -21,6 +21,7 @@ pub mod asn;
 pub mod client;
 pub mod conn;
 pub mod forwarded;
+pub mod limit;
 pub mod mode;
 pub mod stream;
 pub mod test_utils;
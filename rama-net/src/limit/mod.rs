@@ -0,0 +1,159 @@
+//! Key extractors for the [`KeyedPolicy`] and [`StoreRateLimitPolicy`],
+//! to limit requests per client, user or target,
+//! and the [`RedisRateLimitStore`] to share rate limits between instances.
+//!
+//! [`KeyedPolicy`]: rama_core::layer::limit::policy::KeyedPolicy
+//! [`StoreRateLimitPolicy`]: rama_core::layer::limit::policy::StoreRateLimitPolicy
+
+use crate::{stream::SocketInfo, user::UserId};
+use rama_core::{
+    Context,
+    layer::limit::policy::{ExtractKey, KeyExtractor},
+};
+use std::net::IpAddr;
+
+mod redis;
+#[doc(inline)]
+pub use redis::{RedisRateLimitStore, RedisRateLimitStoreBuilder};
+
+#[cfg(feature = "http")]
+use crate::{
+    address::Authority,
+    http::RequestContext,
+    transport::{TransportContext, TryRefIntoTransportContext},
+};
+
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// Extracts the [`IpAddr`] of the peer, as found in the [`SocketInfo`] of the [`Context`].
+pub struct PeerIpKey;
+
+impl PeerIpKey {
+    /// Create a new [`PeerIpKey`].
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl KeyExtractor for PeerIpKey {
+    type Key = IpAddr;
+}
+
+impl<State, Request> ExtractKey<State, Request> for PeerIpKey {
+    fn extract_key(&self, ctx: &Context<State>, _req: &Request) -> Option<IpAddr> {
+        ctx.get::<SocketInfo>().map(|info| info.peer_addr().ip())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// Extracts the [`UserId`] of the authenticated user, as found in the [`Context`].
+///
+/// Anonymous users share a single key.
+pub struct UserIdKey;
+
+impl UserIdKey {
+    /// Create a new [`UserIdKey`].
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl KeyExtractor for UserIdKey {
+    type Key = UserId;
+}
+
+impl<State, Request> ExtractKey<State, Request> for UserIdKey {
+    fn extract_key(&self, ctx: &Context<State>, _req: &Request) -> Option<UserId> {
+        ctx.get::<UserId>().cloned()
+    }
+}
+
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// Extracts the target [`Authority`] of the request.
+///
+/// The authority is taken from the [`RequestContext`] or [`TransportContext`]
+/// found in the [`Context`], and otherwise computed from the request.
+pub struct AuthorityKey;
+
+#[cfg(feature = "http")]
+impl AuthorityKey {
+    /// Create a new [`AuthorityKey`].
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "http")]
+impl KeyExtractor for AuthorityKey {
+    type Key = Authority;
+}
+
+#[cfg(feature = "http")]
+impl<State, Request> ExtractKey<State, Request> for AuthorityKey
+where
+    Request: TryRefIntoTransportContext<State>,
+{
+    fn extract_key(&self, ctx: &Context<State>, req: &Request) -> Option<Authority> {
+        if let Some(req_ctx) = ctx.get::<RequestContext>() {
+            return Some(req_ctx.authority.clone());
+        }
+        if let Some(transport_ctx) = ctx.get::<TransportContext>() {
+            return Some(transport_ctx.authority.clone());
+        }
+        req.try_ref_into_transport_ctx(ctx)
+            .inspect_err(|_| {
+                tracing::debug!("AuthorityKey: failed to compute transport context");
+            })
+            .ok()
+            .map(|transport_ctx| transport_ctx.authority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_ip_key() {
+        let mut ctx = Context::<()>::default();
+        assert!(PeerIpKey::new().extract_key(&ctx, &()).is_none());
+
+        ctx.insert(SocketInfo::new(None, ([127, 0, 0, 1], 8080).into()));
+        assert_eq!(
+            PeerIpKey::new().extract_key(&ctx, &()),
+            Some(IpAddr::from([127, 0, 0, 1]))
+        );
+    }
+
+    #[test]
+    fn test_user_id_key() {
+        let mut ctx = Context::<()>::default();
+        assert!(UserIdKey::new().extract_key(&ctx, &()).is_none());
+
+        ctx.insert(UserId::Username("alice".to_owned()));
+        assert_eq!(
+            UserIdKey::new().extract_key(&ctx, &()),
+            Some(UserId::Username("alice".to_owned()))
+        );
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_authority_key() {
+        use crate::address::Domain;
+        use rama_http_types::Request;
+
+        let ctx = Context::<()>::default();
+        let req = Request::builder()
+            .uri("https://example.com/foo")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            AuthorityKey::new().extract_key(&ctx, &req),
+            Some(Authority::from((Domain::from_static("example.com"), 443)))
+        );
+    }
+}
@@ -0,0 +1,790 @@
+use crate::address::Authority;
+use parking_lot::Mutex;
+use rama_core::{
+    error::{ErrorContext, OpaqueError},
+    layer::limit::policy::RateLimitStore,
+};
+use std::{fmt, io, pin::Pin, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream},
+    net::TcpStream,
+};
+
+/// A [`RateLimitStore`] which stores its counters in a Redis server,
+/// or any other server speaking the Redis serialization protocol (RESP),
+/// such that rate limits can be shared by multiple instances of a service.
+///
+/// Counters are created and incremented atomically within a transaction,
+/// using the `SET` (with `NX` and `PX`), `INCR` and `GET` commands.
+/// Values are compared and swapped using an optimistic transaction,
+/// guarded by the `WATCH` command.
+///
+/// Connections are reused for subsequent commands. A connection
+/// on which an error occurred is dropped.
+///
+/// Connecting to the server and executing commands are bounded by
+/// a timeout, such that an unresponsive server results in an error,
+/// handled according to the [`StoreFailureMode`] of the policy,
+/// rather than stalling the requests being checked.
+///
+/// Cloning a [`RedisRateLimitStore`] shares its connections.
+///
+/// [`StoreFailureMode`]: rama_core::layer::limit::policy::StoreFailureMode
+#[derive(Clone)]
+pub struct RedisRateLimitStore {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    authority: Authority,
+    auth: Option<(Option<String>, String)>,
+    max_idle_connections: usize,
+    connect_timeout: Duration,
+    command_timeout: Duration,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl fmt::Debug for RedisRateLimitStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisRateLimitStore")
+            .field("authority", &self.inner.authority)
+            .field(
+                "username",
+                &self.inner.auth.as_ref().and_then(|(user, _)| user.as_ref()),
+            )
+            .field("max_idle_connections", &self.inner.max_idle_connections)
+            .field("connect_timeout", &self.inner.connect_timeout)
+            .field("command_timeout", &self.inner.command_timeout)
+            .finish()
+    }
+}
+
+/// Builder to create a [`RedisRateLimitStore`].
+///
+/// Created using [`RedisRateLimitStore::builder`].
+#[derive(Debug, Clone)]
+pub struct RedisRateLimitStoreBuilder {
+    authority: Authority,
+    username: Option<String>,
+    password: Option<String>,
+    max_idle_connections: usize,
+    connect_timeout: Duration,
+    command_timeout: Duration,
+}
+
+impl RedisRateLimitStoreBuilder {
+    /// Authenticate using the given password,
+    /// for servers which require the `AUTH` command.
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Authenticate as the given user, using the password
+    /// defined using [`Self::with_password`].
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Set the maximum amount of idle connections kept for reuse.
+    pub fn with_max_idle_connections(mut self, max: usize) -> Self {
+        self.max_idle_connections = max;
+        self
+    }
+
+    /// Set the timeout to connect to (and authenticate with) the server.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Set the timeout to execute the commands of a single store operation,
+    /// from writing the commands up to reading their replies.
+    pub fn with_command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    /// Build the [`RedisRateLimitStore`].
+    ///
+    /// No connection is made until the store is first used.
+    pub fn build(self) -> RedisRateLimitStore {
+        RedisRateLimitStore {
+            inner: Arc::new(Inner {
+                authority: self.authority,
+                auth: self.password.map(|password| (self.username, password)),
+                max_idle_connections: self.max_idle_connections,
+                connect_timeout: self.connect_timeout,
+                command_timeout: self.command_timeout,
+                idle: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+}
+
+impl RedisRateLimitStore {
+    /// Default maximum amount of idle connections kept for reuse.
+    pub const DEFAULT_MAX_IDLE_CONNECTIONS: usize = 8;
+    /// Default timeout to connect to (and authenticate with) the server.
+    pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+    /// Default timeout to execute the commands of a single store operation.
+    pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Create a new [`RedisRateLimitStore`] for the server at the given [`Authority`].
+    pub fn new(authority: impl Into<Authority>) -> Self {
+        Self::builder(authority).build()
+    }
+
+    /// Create a new [`RedisRateLimitStoreBuilder`] for the server at the given [`Authority`].
+    pub fn builder(authority: impl Into<Authority>) -> RedisRateLimitStoreBuilder {
+        RedisRateLimitStoreBuilder {
+            authority: authority.into(),
+            username: None,
+            password: None,
+            max_idle_connections: Self::DEFAULT_MAX_IDLE_CONNECTIONS,
+            connect_timeout: Self::DEFAULT_CONNECT_TIMEOUT,
+            command_timeout: Self::DEFAULT_COMMAND_TIMEOUT,
+        }
+    }
+
+    /// The [`Authority`] of the server used by this store.
+    pub fn authority(&self) -> &Authority {
+        &self.inner.authority
+    }
+
+    async fn connection(&self) -> Result<Connection, OpaqueError> {
+        if let Some(conn) = self.inner.idle.lock().pop() {
+            return Ok(conn);
+        }
+
+        tokio::time::timeout(self.inner.connect_timeout, self.connect())
+            .await
+            .context("RedisRateLimitStore: connect to server: timeout")?
+    }
+
+    async fn connect(&self) -> Result<Connection, OpaqueError> {
+        let stream = TcpStream::connect(self.inner.authority.to_string())
+            .await
+            .context("RedisRateLimitStore: connect to server")?;
+        let mut conn = Connection {
+            stream: BufStream::new(stream),
+            command_timeout: self.inner.command_timeout,
+        };
+
+        if let Some((username, password)) = &self.inner.auth {
+            let mut cmd = vec!["AUTH"];
+            if let Some(username) = username {
+                cmd.push(username);
+            }
+            cmd.push(password);
+            match conn.execute(&[&cmd]).await?.pop() {
+                Some(RespValue::Simple(_)) => (),
+                value => return Err(unexpected_reply("AUTH", value)),
+            }
+        }
+
+        Ok(conn)
+    }
+
+    fn release(&self, conn: Connection) {
+        let mut idle = self.inner.idle.lock();
+        if idle.len() < self.inner.max_idle_connections {
+            idle.push(conn);
+        }
+    }
+
+    async fn execute(&self, commands: &[&[&str]]) -> Result<Vec<RespValue>, OpaqueError> {
+        let mut conn = self.connection().await?;
+        let replies = conn.execute(commands).await?;
+        self.release(conn);
+        Ok(replies)
+    }
+}
+
+impl RateLimitStore for RedisRateLimitStore {
+    type Error = OpaqueError;
+
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64, Self::Error> {
+        // sub-millisecond ttls would be rejected by the server
+        let ttl = ttl.as_millis().max(1).to_string();
+        let mut replies = self
+            .execute(&[
+                &["MULTI"],
+                &["SET", key, "0", "PX", &ttl, "NX"],
+                &["INCR", key],
+                &["EXEC"],
+            ])
+            .await?;
+
+        match replies.pop() {
+            Some(RespValue::Array(Some(mut values))) if values.len() == 2 => match values.pop() {
+                Some(RespValue::Integer(count)) => Ok(count.try_into().unwrap_or_default()),
+                value => Err(unexpected_reply("INCR", value)),
+            },
+            value => Err(unexpected_reply("EXEC", value)),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<u64, Self::Error> {
+        let mut replies = self.execute(&[&["GET", key]]).await?;
+        parse_count(replies.pop())
+    }
+
+    async fn compare_and_swap(
+        &self,
+        key: &str,
+        current: u64,
+        new: u64,
+        ttl: Duration,
+    ) -> Result<bool, Self::Error> {
+        let mut conn = self.connection().await?;
+
+        let mut replies = conn.execute(&[&["WATCH", key], &["GET", key]]).await?;
+        let stored = parse_count(replies.pop())?;
+        match replies.pop() {
+            Some(RespValue::Simple(_)) => (),
+            value => return Err(unexpected_reply("WATCH", value)),
+        }
+
+        if stored != current {
+            match conn.execute(&[&["UNWATCH"]]).await?.pop() {
+                Some(RespValue::Simple(_)) => (),
+                value => return Err(unexpected_reply("UNWATCH", value)),
+            }
+            self.release(conn);
+            return Ok(false);
+        }
+
+        // sub-millisecond ttls would be rejected by the server
+        let ttl = ttl.as_millis().max(1).to_string();
+        let new = new.to_string();
+        let mut replies = conn
+            .execute(&[&["MULTI"], &["SET", key, &new, "PX", &ttl], &["EXEC"]])
+            .await?;
+        self.release(conn);
+
+        match replies.pop() {
+            // the transaction is aborted in case the watched key was modified
+            Some(RespValue::Array(None)) => Ok(false),
+            Some(RespValue::Array(Some(mut values))) if values.len() == 1 => match values.pop() {
+                Some(RespValue::Simple(_)) => Ok(true),
+                value => Err(unexpected_reply("SET", value)),
+            },
+            value => Err(unexpected_reply("EXEC", value)),
+        }
+    }
+}
+
+fn parse_count(value: Option<RespValue>) -> Result<u64, OpaqueError> {
+    match value {
+        Some(RespValue::Bulk(None)) => Ok(0),
+        Some(RespValue::Bulk(Some(value))) => std::str::from_utf8(&value)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| OpaqueError::from_display("RedisRateLimitStore: GET: invalid count")),
+        value => Err(unexpected_reply("GET", value)),
+    }
+}
+
+fn unexpected_reply(command: &'static str, value: Option<RespValue>) -> OpaqueError {
+    match value {
+        Some(RespValue::Error(err)) => OpaqueError::from_display(format!(
+            "RedisRateLimitStore: {command}: server error: {err}"
+        )),
+        value => OpaqueError::from_display(format!(
+            "RedisRateLimitStore: {command}: unexpected reply: {value:?}"
+        )),
+    }
+}
+
+struct Connection {
+    stream: BufStream<TcpStream>,
+    command_timeout: Duration,
+}
+
+impl Connection {
+    /// Write the given commands as a single pipeline,
+    /// returning the reply of each command.
+    ///
+    /// The connection is in an unknown state in case of an error
+    /// (e.g. a timeout), and should therefore not be reused.
+    async fn execute(&mut self, commands: &[&[&str]]) -> Result<Vec<RespValue>, OpaqueError> {
+        tokio::time::timeout(self.command_timeout, self.execute_pipeline(commands))
+            .await
+            .context("RedisRateLimitStore: execute command: timeout")?
+    }
+
+    async fn execute_pipeline(
+        &mut self,
+        commands: &[&[&str]],
+    ) -> Result<Vec<RespValue>, OpaqueError> {
+        let mut buf = Vec::new();
+        for command in commands {
+            encode_command(&mut buf, command);
+        }
+        self.stream
+            .write_all(&buf)
+            .await
+            .context("RedisRateLimitStore: write command")?;
+        self.stream
+            .flush()
+            .await
+            .context("RedisRateLimitStore: flush command")?;
+
+        let mut replies = Vec::with_capacity(commands.len());
+        for _ in commands {
+            replies.push(
+                read_value(&mut self.stream)
+                    .await
+                    .context("RedisRateLimitStore: read reply")?,
+            );
+        }
+        Ok(replies)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RespValue {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Option<Vec<RespValue>>),
+}
+
+fn encode_command(buf: &mut Vec<u8>, args: &[&str]) {
+    buf.extend_from_slice(format!("*{}\r\n", args.len()).as_bytes());
+    for arg in args {
+        encode_bulk_string(buf, arg.as_bytes());
+    }
+}
+
+fn encode_bulk_string(buf: &mut Vec<u8>, value: &[u8]) {
+    buf.extend_from_slice(format!("${}\r\n", value.len()).as_bytes());
+    buf.extend_from_slice(value);
+    buf.extend_from_slice(b"\r\n");
+}
+
+/// Maximum length of a bulk string reply, which for the commands
+/// used by the store are counts and timestamps.
+const MAX_BULK_LEN: usize = 4 * 1024;
+/// Maximum amount of values of an array reply, which for the commands
+/// used by the store is the reply of each command within a transaction.
+const MAX_ARRAY_LEN: usize = 64;
+
+type ReadValueFuture<'a> = Pin<Box<dyn Future<Output = io::Result<RespValue>> + Send + 'a>>;
+
+fn read_value<R>(reader: &mut R) -> ReadValueFuture<'_>
+where
+    R: tokio::io::AsyncBufRead + Unpin + Send,
+{
+    Box::pin(async move {
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line).await?;
+        let line = line
+            .strip_suffix(b"\r\n")
+            .ok_or_else(|| invalid_data("missing line terminator"))?;
+        let (kind, data) = line
+            .split_first()
+            .ok_or_else(|| invalid_data("empty line"))?;
+        let data = std::str::from_utf8(data).map_err(|_| invalid_data("non-utf8 line"))?;
+
+        match kind {
+            b'+' => Ok(RespValue::Simple(data.to_owned())),
+            b'-' => Ok(RespValue::Error(data.to_owned())),
+            b':' => data
+                .parse()
+                .map(RespValue::Integer)
+                .map_err(|_| invalid_data("invalid integer")),
+            b'$' => {
+                let len: i64 = data.parse().map_err(|_| invalid_data("invalid length"))?;
+                if len < 0 {
+                    return Ok(RespValue::Bulk(None));
+                }
+                let len = usize::try_from(len)
+                    .ok()
+                    .filter(|len| *len <= MAX_BULK_LEN)
+                    .ok_or_else(|| invalid_data("bulk string too long"))?;
+                let mut value = vec![0; len + 2];
+                reader.read_exact(&mut value).await?;
+                if value.split_off(len) != b"\r\n" {
+                    return Err(invalid_data("missing bulk string terminator"));
+                }
+                Ok(RespValue::Bulk(Some(value)))
+            }
+            b'*' => {
+                let len: i64 = data.parse().map_err(|_| invalid_data("invalid length"))?;
+                if len < 0 {
+                    return Ok(RespValue::Array(None));
+                }
+                let len = usize::try_from(len)
+                    .ok()
+                    .filter(|len| *len <= MAX_ARRAY_LEN)
+                    .ok_or_else(|| invalid_data("array too long"))?;
+                let mut values = Vec::with_capacity(len);
+                for _ in 0..len {
+                    values.push(read_value(reader).await?);
+                }
+                Ok(RespValue::Array(Some(values)))
+            }
+            _ => Err(invalid_data("unknown value type")),
+        }
+    })
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::{net::TcpListener, time::Instant};
+
+    /// A minimal stand-in for a Redis server,
+    /// supporting only the commands used by the [`RedisRateLimitStore`].
+    async fn spawn_server(password: Option<&'static str>) -> Authority {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let data = Arc::new(Mutex::new(Data::default()));
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let data = data.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufStream::new(stream);
+                    let mut authenticated = password.is_none();
+                    let mut queue: Option<Vec<Vec<Vec<u8>>>> = None;
+                    let mut watched: Vec<(Vec<u8>, u64)> = Vec::new();
+                    while let Ok(RespValue::Array(Some(args))) = read_value(&mut stream).await {
+                        let args: Vec<Vec<u8>> = args
+                            .into_iter()
+                            .map(|arg| match arg {
+                                RespValue::Bulk(Some(arg)) => arg,
+                                arg => panic!("unexpected argument: {arg:?}"),
+                            })
+                            .collect();
+
+                        let command = args[0].clone();
+                        let reply = match command.as_slice() {
+                            b"AUTH" => {
+                                authenticated =
+                                    args.last().map(Vec::as_slice) == password.map(str::as_bytes);
+                                if authenticated {
+                                    RespValue::Simple("OK".to_owned())
+                                } else {
+                                    RespValue::Error("WRONGPASS".to_owned())
+                                }
+                            }
+                            _ if !authenticated => RespValue::Error("NOAUTH".to_owned()),
+                            b"MULTI" => {
+                                queue = Some(Vec::new());
+                                RespValue::Simple("OK".to_owned())
+                            }
+                            b"WATCH" => {
+                                let version = data.lock().version(&args[1]);
+                                watched.push((args[1].clone(), version));
+                                RespValue::Simple("OK".to_owned())
+                            }
+                            b"UNWATCH" => {
+                                watched.clear();
+                                RespValue::Simple("OK".to_owned())
+                            }
+                            b"EXEC" => match queue.take() {
+                                Some(_)
+                                    if std::mem::take(&mut watched).iter().any(
+                                        |(key, version)| data.lock().version(key) != *version,
+                                    ) =>
+                                {
+                                    RespValue::Array(None)
+                                }
+                                Some(queued) => RespValue::Array(Some(
+                                    queued.iter().map(|args| apply(&data, args)).collect(),
+                                )),
+                                None => RespValue::Error("ERR EXEC without MULTI".to_owned()),
+                            },
+                            _ => match queue.as_mut() {
+                                Some(queued) => {
+                                    queued.push(args);
+                                    RespValue::Simple("QUEUED".to_owned())
+                                }
+                                None => apply(&data, &args),
+                            },
+                        };
+
+                        let mut buf = Vec::new();
+                        encode_value(&mut buf, &reply);
+                        stream.write_all(&buf).await.unwrap();
+                        stream.flush().await.unwrap();
+                    }
+                });
+            }
+        });
+
+        addr.into()
+    }
+
+    #[derive(Debug, Default)]
+    struct Data {
+        values: HashMap<Vec<u8>, (i64, Option<Instant>)>,
+        /// bumped on every modification of a key, used for `WATCH`
+        versions: HashMap<Vec<u8>, u64>,
+    }
+
+    impl Data {
+        fn version(&self, key: &[u8]) -> u64 {
+            self.versions.get(key).copied().unwrap_or_default()
+        }
+
+        fn touch(&mut self, key: &[u8]) {
+            *self.versions.entry(key.to_vec()).or_default() += 1;
+        }
+    }
+
+    fn apply(data: &Mutex<Data>, args: &[Vec<u8>]) -> RespValue {
+        let now = Instant::now();
+        let mut data = data.lock();
+        data.values
+            .retain(|_, (_, expires_at)| expires_at.is_none_or(|t| t > now));
+
+        match args[0].as_slice() {
+            b"SET" => {
+                let nx = args.iter().any(|arg| arg == b"NX");
+                let px = args.iter().position(|arg| arg == b"PX").map(|i| {
+                    let ms: u64 = std::str::from_utf8(&args[i + 1]).unwrap().parse().unwrap();
+                    now + Duration::from_millis(ms)
+                });
+                if nx && data.values.contains_key(&args[1]) {
+                    return RespValue::Bulk(None);
+                }
+                let value = std::str::from_utf8(&args[2]).unwrap().parse().unwrap();
+                data.values.insert(args[1].clone(), (value, px));
+                data.touch(&args[1]);
+                RespValue::Simple("OK".to_owned())
+            }
+            b"INCR" => {
+                data.touch(&args[1]);
+                let entry = data.values.entry(args[1].clone()).or_insert((0, None));
+                entry.0 += 1;
+                RespValue::Integer(entry.0)
+            }
+            b"GET" => RespValue::Bulk(
+                data.values
+                    .get(&args[1])
+                    .map(|(value, _)| value.to_string().into_bytes()),
+            ),
+            _ => RespValue::Error("ERR unknown command".to_owned()),
+        }
+    }
+
+    fn encode_value(buf: &mut Vec<u8>, value: &RespValue) {
+        match value {
+            RespValue::Simple(s) => buf.extend_from_slice(format!("+{s}\r\n").as_bytes()),
+            RespValue::Error(s) => buf.extend_from_slice(format!("-{s}\r\n").as_bytes()),
+            RespValue::Integer(n) => buf.extend_from_slice(format!(":{n}\r\n").as_bytes()),
+            RespValue::Bulk(None) => buf.extend_from_slice(b"$-1\r\n"),
+            RespValue::Bulk(Some(value)) => encode_bulk_string(buf, value),
+            RespValue::Array(None) => buf.extend_from_slice(b"*-1\r\n"),
+            RespValue::Array(Some(values)) => {
+                buf.extend_from_slice(format!("*{}\r\n", values.len()).as_bytes());
+                for value in values {
+                    encode_value(buf, value);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_value() {
+        let input: &[u8] = b"*3\r\n:1\r\n$5\r\nhello\r\n$-1\r\n";
+        let mut reader = tokio::io::BufReader::new(input);
+        assert_eq!(
+            read_value(&mut reader).await.unwrap(),
+            RespValue::Array(Some(vec![
+                RespValue::Integer(1),
+                RespValue::Bulk(Some(b"hello".to_vec())),
+                RespValue::Bulk(None),
+            ]))
+        );
+
+        let input: &[u8] = b"$1000000000\r\nhello\r\n";
+        let mut reader = tokio::io::BufReader::new(input);
+        let err = read_value(&mut reader).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let input: &[u8] = b"*1000000000\r\n:1\r\n";
+        let mut reader = tokio::io::BufReader::new(input);
+        let err = read_value(&mut reader).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_command_timeout() {
+        use rama_core::{
+            Context,
+            layer::limit::policy::{
+                KeyFn, Policy, PolicyOutput, Rate, StoreFailureMode, StoreRateLimitError,
+                StoreRateLimitPolicy,
+            },
+        };
+
+        // a server which accepts connections, but never replies
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let authority = Authority::from(listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut streams = Vec::new();
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                streams.push(stream);
+            }
+        });
+
+        let store = RedisRateLimitStore::builder(authority)
+            .with_command_timeout(Duration::from_millis(50))
+            .build();
+        let start = Instant::now();
+        assert!(store.get("a").await.is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let policy = StoreRateLimitPolicy::fixed_window(
+            store,
+            KeyFn::new(|_: &Context<()>, req: &&'static str| Some(*req)),
+            Rate::per_hour(1),
+        )
+        .with_failure_mode(StoreFailureMode::Closed);
+        let result = policy.check(Context::default(), "alice").await;
+        assert!(matches!(
+            result.output,
+            PolicyOutput::Abort(StoreRateLimitError::Store(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_increment() {
+        let store = RedisRateLimitStore::new(spawn_server(None).await);
+
+        assert_eq!(store.get("a").await.unwrap(), 0);
+        assert_eq!(
+            store.increment("a", Duration::from_secs(60)).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            store.increment("a", Duration::from_secs(60)).await.unwrap(),
+            2
+        );
+        assert_eq!(store.clone().get("a").await.unwrap(), 2);
+        assert_eq!(store.get("b").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_ttl() {
+        let store = RedisRateLimitStore::new(spawn_server(None).await);
+
+        assert_eq!(
+            store
+                .increment("a", Duration::from_millis(10))
+                .await
+                .unwrap(),
+            1
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(store.get("a").await.unwrap(), 0);
+        assert_eq!(
+            store
+                .increment("a", Duration::from_millis(10))
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_compare_and_swap() {
+        let store = RedisRateLimitStore::new(spawn_server(None).await);
+        let ttl = Duration::from_secs(60);
+
+        assert!(!store.compare_and_swap("a", 1, 2, ttl).await.unwrap());
+        assert!(store.compare_and_swap("a", 0, 2, ttl).await.unwrap());
+        assert_eq!(store.get("a").await.unwrap(), 2);
+        assert!(!store.compare_and_swap("a", 0, 3, ttl).await.unwrap());
+        assert!(store.compare_and_swap("a", 2, 3, ttl).await.unwrap());
+        assert_eq!(store.get("a").await.unwrap(), 3);
+
+        assert!(
+            store
+                .compare_and_swap("b", 0, 1, Duration::from_millis(10))
+                .await
+                .unwrap()
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(store.get("b").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_compare_and_swap_concurrent_update() {
+        let store = RedisRateLimitStore::new(spawn_server(None).await);
+        let ttl = Duration::from_secs(60);
+
+        let mut conn = store.connection().await.unwrap();
+        conn.execute(&[&["WATCH", "a"]]).await.unwrap();
+        assert!(store.compare_and_swap("a", 0, 1, ttl).await.unwrap());
+        let mut replies = conn
+            .execute(&[&["MULTI"], &["SET", "a", "2"], &["EXEC"]])
+            .await
+            .unwrap();
+        assert_eq!(replies.pop(), Some(RespValue::Array(None)));
+        assert_eq!(store.get("a").await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_gcra_policy_shared() {
+        use rama_core::{
+            Context,
+            layer::limit::policy::{KeyFn, Policy, PolicyOutput, Rate, StoreRateLimitPolicy},
+        };
+
+        let authority = spawn_server(None).await;
+        let key_fn = KeyFn::new(|_: &Context<()>, req: &&'static str| Some(*req));
+        let policy_a = StoreRateLimitPolicy::gcra(
+            RedisRateLimitStore::new(authority.clone()),
+            key_fn.clone(),
+            Rate::per_hour(1),
+            2,
+        );
+        let policy_b = StoreRateLimitPolicy::gcra(
+            RedisRateLimitStore::new(authority),
+            key_fn,
+            Rate::per_hour(1),
+            2,
+        );
+
+        for (policy, ready) in [
+            (&policy_a, true),
+            (&policy_b, true),
+            (&policy_a, false),
+            (&policy_b, false),
+        ] {
+            let result = policy.check(Context::default(), "alice").await;
+            assert_eq!(matches!(result.output, PolicyOutput::Ready(_)), ready);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redis_store_auth() {
+        let authority = spawn_server(Some("secret")).await;
+
+        let store = RedisRateLimitStore::new(authority.clone());
+        assert!(store.get("a").await.is_err());
+
+        let store = RedisRateLimitStore::builder(authority)
+            .with_password("secret")
+            .build();
+        assert_eq!(
+            store.increment("a", Duration::from_secs(60)).await.unwrap(),
+            1
+        );
+    }
+}
@@ -1,3 +1,12 @@
+#[cfg(feature = "http")]
+use rama_core::error::ErrorExt;
+use rama_core::error::OpaqueError;
+use rama_core::username::{ComposeError, Composer, UsernameLabelWriter};
+#[cfg(feature = "http")]
+use rama_http_types::HeaderValue;
+use rama_utils::macros::match_ignore_ascii_case_str;
+use std::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
 /// Enum representing the IP modes that can be used by the DNS resolver.
 pub enum DnsResolveIpMode {
@@ -37,3 +46,97 @@ pub enum ConnectIpMode {
     Ipv4,
     Ipv6,
 }
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A vanity [`Extensions`] type for others to easily check if eager DNS resolution is enabled.
+///
+/// [`Extensions`]: rama_core::context::Extensions
+pub struct DnsResolveMode(ResolveMode);
+
+impl fmt::Display for DnsResolveMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ResolveMode::Eager => write!(f, "eager"),
+            ResolveMode::Lazy => write!(f, "lazy"),
+        }
+    }
+}
+
+impl DnsResolveMode {
+    /// Creates a new "eager" resolve mod
+    pub const fn eager() -> Self {
+        Self(ResolveMode::Eager)
+    }
+
+    /// Creates a new "lazy" resolve mode
+    pub const fn lazy() -> Self {
+        Self(ResolveMode::Lazy)
+    }
+
+    /// Returns `true` if the [`DnsResolveMode`] is "eager".
+    pub fn is_eager(&self) -> bool {
+        match self.0 {
+            ResolveMode::Eager => true,
+            ResolveMode::Lazy => false,
+        }
+    }
+
+    /// Returns `true` if the [`DnsResolveMode`] is "lazy".
+    pub fn is_lazy(&self) -> bool {
+        match self.0 {
+            ResolveMode::Eager => false,
+            ResolveMode::Lazy => true,
+        }
+    }
+}
+
+impl std::str::FromStr for DnsResolveMode {
+    type Err = OpaqueError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::try_from(value)
+    }
+}
+
+impl TryFrom<&str> for DnsResolveMode {
+    type Error = OpaqueError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match_ignore_ascii_case_str! {
+            match (value) {
+                "eager" => Ok(DnsResolveMode::eager()),
+                "lazy" => Ok(DnsResolveMode::lazy()),
+                _ => Err(OpaqueError::from_display("Invalid DNS resolve mode: unknown str")),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl TryFrom<&HeaderValue> for DnsResolveMode {
+    type Error = OpaqueError;
+
+    fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
+        match value.to_str() {
+            Ok(value) => Self::try_from(value),
+            Err(err) => Err(err.context("Invalid DNS resolve mode")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum ResolveMode {
+    Eager,
+    #[default]
+    Lazy,
+}
+
+impl<const SEPARATOR: char> UsernameLabelWriter<SEPARATOR> for DnsResolveMode {
+    fn write_labels(&self, composer: &mut Composer<SEPARATOR>) -> Result<(), ComposeError> {
+        composer.write_label("dns")?;
+        match self.0 {
+            ResolveMode::Eager => composer.write_label("eager"),
+            ResolveMode::Lazy => composer.write_label("lazy"),
+        }
+    }
+}
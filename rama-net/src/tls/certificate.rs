@@ -0,0 +1,502 @@
+use super::SignatureScheme;
+use rama_core::error::OpaqueError;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+use x509_parser::asn1_rs::{BmpString, Tag, UniversalString};
+use x509_parser::prelude::{
+    ASN1Time, FromDer, GeneralName, ParsedExtension, X509Certificate, X509Name,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Information parsed from a (DER-encoded) X.509 certificate,
+/// e.g. the leaf certificate of a peer as found in
+/// [`NegotiatedTlsParameters`].
+///
+/// The parsing is implementation agnostic, such that the info
+/// is the same regardless of the tls implementation in use.
+///
+/// [`NegotiatedTlsParameters`]: super::client::NegotiatedTlsParameters
+pub struct CertificateInfo {
+    /// Distinguished name of the subject, formatted as defined in [RFC 4514]
+    /// (e.g. `CN=example.com,O=Example`).
+    ///
+    /// [RFC 4514]: https://datatracker.ietf.org/doc/html/rfc4514
+    pub subject: String,
+    /// Attributes of the subject, in the order they are encoded in the certificate.
+    ///
+    /// Unlike [`Self::subject`] these can be used to reproduce the subject as-is,
+    /// e.g. to issue certificates with this certificate as their issuer.
+    pub subject_attributes: Vec<NameAttribute>,
+    /// Distinguished name of the issuer, formatted as [`Self::subject`].
+    pub issuer: String,
+    /// Subject alternative names (SAN) of the certificate.
+    pub subject_alt_names: Vec<SubjectAltName>,
+    /// Start of the validity period of the certificate.
+    pub not_before: SystemTime,
+    /// End of the validity period of the certificate.
+    pub not_after: SystemTime,
+    /// Signature scheme used by the issuer to sign the certificate,
+    /// if it maps to a known [`SignatureScheme`].
+    pub signature_scheme: Option<SignatureScheme>,
+    /// DER-encoded `SubjectPublicKeyInfo` of the certificate,
+    /// e.g. to check that a private key belongs to this certificate.
+    pub subject_public_key_info: Vec<u8>,
+    /// Subject key identifier (SKI) of the certificate, if defined as an extension.
+    pub subject_key_identifier: Option<Vec<u8>>,
+    /// Key usage of the certificate, if defined as an extension.
+    pub key_usage: Option<Vec<KeyUsage>>,
+    /// URLs of the OCSP responders of the issuer, as found
+    /// in the authority information access (AIA) extension.
+    pub ocsp_responders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Purpose of the key contained in a certificate, as defined in the
+/// [key usage](https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.3) extension.
+pub enum KeyUsage {
+    /// `digitalSignature`
+    DigitalSignature,
+    /// `contentCommitment` (formerly `nonRepudiation`)
+    ContentCommitment,
+    /// `keyEncipherment`
+    KeyEncipherment,
+    /// `dataEncipherment`
+    DataEncipherment,
+    /// `keyAgreement`
+    KeyAgreement,
+    /// `keyCertSign`
+    KeyCertSign,
+    /// `cRLSign`
+    CrlSign,
+    /// `encipherOnly`
+    EncipherOnly,
+    /// `decipherOnly`
+    DecipherOnly,
+}
+
+impl KeyUsage {
+    /// All key usages, in the order of their bit in the extension.
+    const ALL: [Self; 9] = [
+        Self::DigitalSignature,
+        Self::ContentCommitment,
+        Self::KeyEncipherment,
+        Self::DataEncipherment,
+        Self::KeyAgreement,
+        Self::KeyCertSign,
+        Self::CrlSign,
+        Self::EncipherOnly,
+        Self::DecipherOnly,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A single attribute of a distinguished name, e.g. the common name (CN) of a subject.
+pub struct NameAttribute {
+    /// Object identifier (OID) of the attribute type, as its arcs
+    /// (e.g. `[2, 5, 4, 3]` for the common name).
+    pub oid: Vec<u64>,
+    /// String type the value is encoded as.
+    pub string_kind: NameStringKind,
+    /// Decoded value of the attribute.
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// ASN.1 string type used to encode the value of a [`NameAttribute`].
+pub enum NameStringKind {
+    /// `UTF8String`
+    Utf8String,
+    /// `PrintableString`
+    PrintableString,
+    /// `IA5String`
+    Ia5String,
+    /// `TeletexString` (`T61String`)
+    TeletexString,
+    /// `BMPString`
+    BmpString,
+    /// `UniversalString`
+    UniversalString,
+}
+
+impl NameStringKind {
+    fn from_tag(tag: Tag) -> Option<Self> {
+        Some(match tag {
+            Tag::Utf8String => Self::Utf8String,
+            Tag::PrintableString => Self::PrintableString,
+            Tag::Ia5String => Self::Ia5String,
+            Tag::T61String => Self::TeletexString,
+            Tag::BmpString => Self::BmpString,
+            Tag::UniversalString => Self::UniversalString,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A subject alternative name (SAN) of a certificate.
+pub enum SubjectAltName {
+    /// DNS name, which can contain a wildcard label (e.g. `*.example.com`).
+    Dns(String),
+    /// IP address.
+    Ip(IpAddr),
+    /// Email address (`rfc822Name`).
+    Email(String),
+    /// Uniform resource identifier.
+    Uri(String),
+}
+
+impl fmt::Display for SubjectAltName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dns(name) => write!(f, "DNS:{name}"),
+            Self::Ip(ip) => write!(f, "IP:{ip}"),
+            Self::Email(email) => write!(f, "email:{email}"),
+            Self::Uri(uri) => write!(f, "URI:{uri}"),
+        }
+    }
+}
+
+impl CertificateInfo {
+    /// Parse the [`CertificateInfo`] of a DER-encoded X.509 certificate.
+    pub fn try_from_der(cert: &[u8]) -> Result<Self, OpaqueError> {
+        X509Certificate::from_der(cert)
+            .ok()
+            .and_then(|(_, cert)| parse_certificate(&cert))
+            .ok_or_else(|| OpaqueError::from_display("invalid DER-encoded X.509 certificate"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// SHA-256 fingerprint of a DER-encoded X.509 certificate.
+///
+/// Formatted as lowercase hex.
+pub struct CertificateFingerprint([u8; 32]);
+
+impl CertificateFingerprint {
+    /// Compute the [`CertificateFingerprint`] of a DER-encoded certificate.
+    pub fn from_der(cert: &[u8]) -> Self {
+        Self(Sha256::digest(cert).into())
+    }
+
+    /// Return the SHA-256 digest of this [`CertificateFingerprint`].
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for CertificateFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+const OID_ACCESS_METHOD_OCSP: &str = "1.3.6.1.5.5.7.48.1";
+
+fn parse_certificate(cert: &X509Certificate<'_>) -> Option<CertificateInfo> {
+    let tbs = &cert.tbs_certificate;
+
+    let subject_alt_names = match tbs.subject_alternative_name().ok()? {
+        Some(san) => san
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(name) => Some(SubjectAltName::Dns((*name).to_owned())),
+                GeneralName::RFC822Name(email) => Some(SubjectAltName::Email((*email).to_owned())),
+                GeneralName::URI(uri) => Some(SubjectAltName::Uri((*uri).to_owned())),
+                GeneralName::IPAddress(ip) => match ip.len() {
+                    4 => <[u8; 4]>::try_from(*ip)
+                        .ok()
+                        .map(|ip| SubjectAltName::Ip(ip.into())),
+                    16 => <[u8; 16]>::try_from(*ip)
+                        .ok()
+                        .map(|ip| SubjectAltName::Ip(ip.into())),
+                    _ => None,
+                },
+                // other names (e.g. directory names) are not supported
+                _ => None,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut subject_key_identifier = None;
+    let mut ocsp_responders = Vec::new();
+    for extension in tbs.iter_extensions() {
+        match extension.parsed_extension() {
+            ParsedExtension::SubjectKeyIdentifier(ski) => {
+                subject_key_identifier = Some(ski.0.to_vec());
+            }
+            ParsedExtension::AuthorityInfoAccess(aia) => {
+                ocsp_responders.extend(aia.accessdescs.iter().filter_map(|description| {
+                    if description.access_method.to_id_string() != OID_ACCESS_METHOD_OCSP {
+                        return None;
+                    }
+                    // only URI locations are supported
+                    match description.access_location {
+                        GeneralName::URI(uri) => Some(uri.to_owned()),
+                        _ => None,
+                    }
+                }));
+            }
+            _ => (),
+        }
+    }
+
+    let key_usage = tbs.key_usage().ok()?.map(|key_usage| {
+        KeyUsage::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| key_usage.value.flags & (1 << index) != 0)
+            .map(|(_, key_usage)| key_usage)
+            .collect()
+    });
+
+    let subject = parse_name(&tbs.subject)?;
+    Some(CertificateInfo {
+        subject: format_name(&subject),
+        subject_attributes: subject.into_iter().flatten().collect(),
+        issuer: format_name(&parse_name(&tbs.issuer)?),
+        subject_alt_names,
+        not_before: asn1_time_to_system_time(tbs.validity.not_before)?,
+        not_after: asn1_time_to_system_time(tbs.validity.not_after)?,
+        signature_scheme: signature_scheme_from_oid(
+            &cert.signature_algorithm.algorithm.to_id_string(),
+        ),
+        subject_public_key_info: tbs.subject_pki.raw.to_vec(),
+        subject_key_identifier,
+        key_usage,
+        ocsp_responders,
+    })
+}
+
+/// Parse a [`X509Name`] into its relative distinguished names.
+fn parse_name(name: &X509Name<'_>) -> Option<Vec<Vec<NameAttribute>>> {
+    name.iter_rdn()
+        .map(|rdn| {
+            rdn.iter()
+                .map(|attribute| {
+                    let value = attribute.attr_value();
+                    let string_kind = NameStringKind::from_tag(value.tag())?;
+                    let value = match string_kind {
+                        NameStringKind::BmpString => {
+                            BmpString::try_from(value.clone()).ok()?.string()
+                        }
+                        NameStringKind::UniversalString => {
+                            UniversalString::try_from(value.clone()).ok()?.string()
+                        }
+                        _ => std::str::from_utf8(value.data).ok()?.to_owned(),
+                    };
+                    Some(NameAttribute {
+                        oid: attribute.attr_type().iter()?.collect(),
+                        string_kind,
+                        value,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Format a parsed `Name` as defined in RFC 4514,
+/// meaning the relative distinguished names are listed in reverse order.
+fn format_name(rdns: &[Vec<NameAttribute>]) -> String {
+    rdns.iter()
+        .rev()
+        .map(|attributes| {
+            attributes
+                .iter()
+                .map(|attribute| {
+                    let oid = attribute
+                        .oid
+                        .iter()
+                        .map(u64::to_string)
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    format!(
+                        "{}={}",
+                        attribute_type_name(&oid).unwrap_or(&oid),
+                        escape_attribute_value(&attribute.value)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("+")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn attribute_type_name(oid: &str) -> Option<&'static str> {
+    Some(match oid {
+        "2.5.4.3" => "CN",
+        "2.5.4.5" => "serialNumber",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.9" => "STREET",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "0.9.2342.19200300.100.1.1" => "UID",
+        "0.9.2342.19200300.100.1.25" => "DC",
+        "1.2.840.113549.1.9.1" => "emailAddress",
+        _ => return None,
+    })
+}
+
+fn escape_attribute_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (index, c) in value.char_indices() {
+        let needs_escape = matches!(c, '"' | '+' | ',' | ';' | '<' | '>' | '\\')
+            || (index == 0 && matches!(c, ' ' | '#'))
+            || (index + c.len_utf8() == value.len() && c == ' ');
+        if needs_escape {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub(super) fn asn1_time_to_system_time(time: ASN1Time) -> Option<SystemTime> {
+    let secs = time.timestamp();
+    if secs >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+    }
+}
+
+pub(super) fn signature_scheme_from_oid(oid: &str) -> Option<SignatureScheme> {
+    Some(match oid {
+        "1.2.840.113549.1.1.5" => SignatureScheme::RSA_PKCS1_SHA1,
+        "1.2.840.113549.1.1.11" => SignatureScheme::RSA_PKCS1_SHA256,
+        "1.2.840.113549.1.1.12" => SignatureScheme::RSA_PKCS1_SHA384,
+        "1.2.840.113549.1.1.13" => SignatureScheme::RSA_PKCS1_SHA512,
+        "1.2.840.10045.4.1" => SignatureScheme::ECDSA_SHA1_Legacy,
+        "1.2.840.10045.4.3.2" => SignatureScheme::ECDSA_NISTP256_SHA256,
+        "1.2.840.10045.4.3.3" => SignatureScheme::ECDSA_NISTP384_SHA384,
+        "1.2.840.10045.4.3.4" => SignatureScheme::ECDSA_NISTP521_SHA512,
+        "1.3.101.112" => SignatureScheme::ED25519,
+        "1.3.101.113" => SignatureScheme::ED448,
+        // RSASSA-PSS (1.2.840.113549.1.1.10) requires its parameters to be parsed,
+        // and is therefore not mapped
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use x509_parser::asn1_rs::{Any, Class, Header, Length, Oid, Sequence, ToDer};
+
+    /// Create an authority information access extension with the given OCSP responder URL.
+    pub(in crate::tls) fn authority_info_access_extension(
+        ocsp_url: &str,
+    ) -> rcgen::CustomExtension {
+        let access_method = Oid::from(&[1, 3, 6, 1, 5, 5, 7, 48, 1])
+            .unwrap()
+            .to_der_vec()
+            .unwrap();
+        // uniformResourceIdentifier [6] IMPLICIT IA5String
+        let access_location = Any::new(
+            Header::new(
+                Class::ContextSpecific,
+                false,
+                Tag(6),
+                Length::Definite(ocsp_url.len()),
+            ),
+            ocsp_url.as_bytes(),
+        )
+        .to_der_vec()
+        .unwrap();
+        let description = Sequence::new([access_method, access_location].concat().into())
+            .to_der_vec()
+            .unwrap();
+        let value = Sequence::new(description.into()).to_der_vec().unwrap();
+        rcgen::CustomExtension::from_oid_content(&[1, 3, 6, 1, 5, 5, 7, 1, 1], value)
+    }
+
+    #[test]
+    fn test_certificate_info_try_from_der() {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(vec![
+            "example.com".to_owned(),
+            "*.example.com".to_owned(),
+        ])
+        .unwrap();
+        params
+            .subject_alt_names
+            .push(rcgen::SanType::IpAddress([127, 0, 0, 1].into()));
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "example.com");
+        params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Example, Inc.");
+        params.not_before = rcgen::date_time_ymd(2024, 1, 1);
+        params.not_after = rcgen::date_time_ymd(2034, 1, 1);
+        params.is_ca = rcgen::IsCa::ExplicitNoCa;
+        params.key_usages = vec![
+            rcgen::KeyUsagePurpose::DigitalSignature,
+            rcgen::KeyUsagePurpose::DecipherOnly,
+        ];
+        params.key_identifier_method = rcgen::KeyIdMethod::PreSpecified(vec![1, 2, 3, 4]);
+        params
+            .custom_extensions
+            .push(authority_info_access_extension("http://ocsp.example.com"));
+        let cert = params.self_signed(&key_pair).unwrap();
+
+        let info = CertificateInfo::try_from_der(cert.der()).unwrap();
+        assert_eq!(info.subject, r"O=Example\, Inc.,CN=example.com");
+        assert_eq!(info.issuer, info.subject);
+        assert_eq!(
+            info.subject_attributes,
+            vec![
+                NameAttribute {
+                    oid: vec![2, 5, 4, 3],
+                    string_kind: NameStringKind::Utf8String,
+                    value: "example.com".to_owned(),
+                },
+                NameAttribute {
+                    oid: vec![2, 5, 4, 10],
+                    string_kind: NameStringKind::Utf8String,
+                    value: "Example, Inc.".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            info.subject_alt_names,
+            vec![
+                SubjectAltName::Dns("example.com".to_owned()),
+                SubjectAltName::Dns("*.example.com".to_owned()),
+                SubjectAltName::Ip([127, 0, 0, 1].into()),
+            ]
+        );
+        assert_eq!(
+            info.not_before,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1704067200)
+        );
+        assert_eq!(
+            info.not_after,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(2019686400)
+        );
+        assert_eq!(
+            info.signature_scheme,
+            Some(SignatureScheme::ECDSA_NISTP256_SHA256)
+        );
+        assert_eq!(info.subject_public_key_info, key_pair.public_key_der());
+        assert_eq!(info.subject_key_identifier, Some(vec![1, 2, 3, 4]));
+        assert_eq!(
+            info.key_usage,
+            Some(vec![KeyUsage::DigitalSignature, KeyUsage::DecipherOnly])
+        );
+        assert_eq!(info.ocsp_responders, vec!["http://ocsp.example.com"]);
+
+        assert!(CertificateInfo::try_from_der(b"\x30\x03\x02\x01\x00").is_err());
+    }
+}
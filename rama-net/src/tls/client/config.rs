@@ -2,8 +2,10 @@ use std::sync::Arc;
 
 use rama_core::{Context, combinators::Either3};
 
-use super::{ClientHelloExtension, merge_client_hello_lists};
-use crate::tls::{CipherSuite, CompressionAlgorithm, DataEncoding, KeyLogIntent, ProtocolVersion};
+use super::{ClientHelloExtension, CustomServerVerify, merge_client_hello_lists};
+use crate::tls::{
+    CipherSuite, CompressionAlgorithm, DataEncoding, EchConfigList, KeyLogIntent, ProtocolVersion,
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct ClientConfigChain {
@@ -199,7 +201,7 @@ where
 /// the info found in context for a dynamic https client.
 pub struct ProxyClientConfig(pub Arc<ClientConfig>);
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Hash)]
 /// Common API to configure a TLS Client
 pub struct ClientConfig {
     /// optional intent for cipher suites to be used by client
@@ -221,6 +223,13 @@ pub struct ClientConfig {
     pub key_logger: Option<KeyLogIntent>,
     /// if enabled server certificates will be stored in [`NegotiatedTlsParameters`]
     pub store_server_certificate_chain: bool,
+    /// optional [`EchConfigList`] of the server, to encrypt the client hello with
+    ///
+    /// Can be defined statically or resolved from the DNS 'HTTPS' records of the server.
+    /// Whether the server accepted it is reported in [`NegotiatedTlsParameters::ech_accepted`].
+    ///
+    /// [`NegotiatedTlsParameters::ech_accepted`]: super::NegotiatedTlsParameters::ech_accepted
+    pub ech_config_list: Option<EchConfigList>,
 }
 
 impl ClientConfig {
@@ -251,10 +260,14 @@ impl ClientConfig {
         if let Some(key_logger) = other.key_logger {
             self.key_logger = Some(key_logger);
         }
+
+        if let Some(ech_config_list) = other.ech_config_list {
+            self.ech_config_list = Some(ech_config_list);
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 /// The kind of client auth to be used.
 pub enum ClientAuth {
     /// Request the tls implementation to generate self-signed single data
@@ -263,7 +276,7 @@ pub enum ClientAuth {
     Single(ClientAuthData),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 /// Raw private key and certificate data to facilitate client authentication.
 pub struct ClientAuthData {
     /// private key used by client
@@ -272,7 +285,7 @@ pub struct ClientAuthData {
     pub cert_chain: DataEncoding,
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// Mode of server verification by a (tls) client
 pub enum ServerVerifyMode {
     #[default]
@@ -281,6 +294,9 @@ pub enum ServerVerifyMode {
     Auto,
     /// Explicitly disable server verification (if possible)
     Disable,
+    /// Verify the server using custom trust anchors
+    /// and/or public key pinning, see [`CustomServerVerify`].
+    Custom(CustomServerVerify),
 }
 
 impl From<super::ClientHello> for ClientConfig {
@@ -280,7 +280,7 @@ pub struct ECHClientHelloOuter {
     pub payload: Vec<u8>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
 /// HPKE KDF and AEAD pair used to encrypt ClientHello
 pub struct HpkeSymmetricCipherSuite {
     pub kdf_id: KeyDerivationFunction,
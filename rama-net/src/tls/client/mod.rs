@@ -10,12 +10,13 @@
 
 mod hello;
 #[doc(inline)]
-pub use hello::{ClientHello, ClientHelloExtension, ECHClientHello};
+pub use hello::{
+    ClientHello, ClientHelloExtension, ECHClientHello, ECHClientHelloOuter,
+    HpkeSymmetricCipherSuite,
+};
 
-#[cfg(any(test, feature = "boring"))]
 mod parser;
-
-#[cfg(any(test, feature = "boring"))]
+#[doc(inline)]
 pub use parser::parse_client_hello;
 
 mod config;
@@ -26,7 +27,21 @@ pub use config::{
     extract_client_config_from_ctx,
 };
 
-use super::{ApplicationProtocol, DataEncoding, ProtocolVersion};
+mod verify;
+#[doc(inline)]
+pub use verify::{CertificatePinningError, CustomServerVerify, InvalidSpkiPin, SpkiPin};
+
+mod session;
+#[doc(inline)]
+pub use session::{
+    InMemoryTlsSessionStore, TlsSession, TlsSessionKey, TlsSessionStore, default_tls_session_store,
+};
+
+use super::{
+    ApplicationProtocol, CertificateInfo, CipherSuite, DataEncoding, ProtocolVersion,
+    SignatureScheme, SupportedGroup,
+};
+use crate::address::Host;
 
 #[derive(Debug, Clone)]
 /// Indicate (some) of the negotiated tls parameters that
@@ -42,8 +57,30 @@ pub struct NegotiatedTlsParameters {
     ///
     /// e.g. [`ApplicationProtocol::HTTP_2`]
     pub application_layer_protocol: Option<ApplicationProtocol>,
+    /// The negotiated [`CipherSuite`],
+    /// in case the tls implementation can surface this.
+    pub cipher_suite: Option<CipherSuite>,
+    /// The [`SupportedGroup`] used for the key exchange,
+    /// in case the tls implementation can surface this.
+    pub key_exchange_group: Option<SupportedGroup>,
+    /// The [`SignatureScheme`] used by the peer to sign the handshake,
+    /// in case the tls implementation can surface this.
+    ///
+    /// This is not available for resumed sessions, and
+    /// for servers only in case the client authenticated itself.
+    pub signature_scheme: Option<SignatureScheme>,
+    /// The server name (SNI) of the connection,
+    /// as sent by the client or received by the server.
+    pub server_name: Option<Host>,
     /// Certificate chain provided the peer (only stored if config requested this)
     pub peer_certificate_chain: Option<DataEncoding>,
+    /// Info parsed from the leaf certificate provided by the peer, if any.
+    pub peer_certificate: Option<CertificateInfo>,
+    /// Indicates if a previous session was resumed,
+    /// instead of a full handshake being performed.
+    pub session_resumed: bool,
+    /// Indicates if Encrypted Client Hello (ECH) was accepted.
+    pub ech_accepted: bool,
 }
 
 /// Merge extension lists A and B, with
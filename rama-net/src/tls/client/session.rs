@@ -0,0 +1,245 @@
+use crate::address::Host;
+use parking_lot::Mutex;
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Key under which a [`TlsSession`] is stored in a [`TlsSessionStore`].
+///
+/// Sessions are only resumed for the same server and for a client configuration
+/// with the same identity, as a session established with one configuration
+/// (e.g. with server verification disabled) should never be resumed by another.
+pub struct TlsSessionKey {
+    /// The server (name) the session was established with.
+    pub server: Host,
+    /// Identity of the client configuration used to establish the session.
+    pub config_id: u64,
+}
+
+impl TlsSessionKey {
+    /// Create a new [`TlsSessionKey`].
+    pub const fn new(server: Host, config_id: u64) -> Self {
+        Self { server, config_id }
+    }
+}
+
+#[derive(Clone)]
+/// A (tls) client session, as stored in a [`TlsSessionStore`].
+///
+/// The content of a session is specific to the tls implementation
+/// which created it, and is therefore opaque to the store.
+pub struct TlsSession(Arc<dyn Any + Send + Sync>);
+
+impl TlsSession {
+    /// Create a new [`TlsSession`] from implementation specific session data.
+    pub fn new<T: Any + Send + Sync>(session: T) -> Self {
+        Self(Arc::new(session))
+    }
+
+    /// Get a reference to the implementation specific session data,
+    /// if it is of the given type.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl fmt::Debug for TlsSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // session data is sensitive, so it is never printed
+        f.debug_tuple("TlsSession").finish_non_exhaustive()
+    }
+}
+
+/// A store of (tls) client sessions, used by tls connectors
+/// to resume sessions across connections (using tickets or PSK).
+///
+/// See [`InMemoryTlsSessionStore`] for the default implementation.
+pub trait TlsSessionStore: fmt::Debug + Send + Sync + 'static {
+    /// Get the session stored for the given key, if any.
+    fn get(&self, key: &TlsSessionKey) -> Option<TlsSession>;
+
+    /// Store a session for the given key, replacing any previous session.
+    fn insert(&self, key: TlsSessionKey, session: TlsSession);
+
+    /// Remove the session stored for the given key, if any.
+    fn remove(&self, key: &TlsSessionKey) -> Option<TlsSession>;
+}
+
+impl<S: TlsSessionStore> TlsSessionStore for Arc<S> {
+    fn get(&self, key: &TlsSessionKey) -> Option<TlsSession> {
+        (**self).get(key)
+    }
+
+    fn insert(&self, key: TlsSessionKey, session: TlsSession) {
+        (**self).insert(key, session)
+    }
+
+    fn remove(&self, key: &TlsSessionKey) -> Option<TlsSession> {
+        (**self).remove(key)
+    }
+}
+
+/// Default capacity of an [`InMemoryTlsSessionStore`].
+const DEFAULT_CAPACITY: usize = 256;
+
+/// In-memory [`TlsSessionStore`], evicting the least recently used session
+/// once its capacity is reached.
+///
+/// A capacity of `0` disables session resumption.
+pub struct InMemoryTlsSessionStore {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Default)]
+struct LruState {
+    sessions: HashMap<TlsSessionKey, (TlsSession, u64)>,
+    tick: u64,
+}
+
+impl InMemoryTlsSessionStore {
+    /// Create a new [`InMemoryTlsSessionStore`] holding at most `capacity` sessions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    /// Return the number of stored sessions.
+    pub fn len(&self) -> usize {
+        self.state.lock().sessions.len()
+    }
+
+    /// Return `true` if no sessions are stored.
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().sessions.is_empty()
+    }
+
+    /// Remove all stored sessions.
+    pub fn clear(&self) {
+        self.state.lock().sessions.clear();
+    }
+}
+
+impl Default for InMemoryTlsSessionStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl fmt::Debug for InMemoryTlsSessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InMemoryTlsSessionStore")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl TlsSessionStore for InMemoryTlsSessionStore {
+    fn get(&self, key: &TlsSessionKey) -> Option<TlsSession> {
+        let mut state = self.state.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        state.sessions.get_mut(key).map(|(session, last_used)| {
+            *last_used = tick;
+            session.clone()
+        })
+    }
+
+    fn insert(&self, key: TlsSessionKey, session: TlsSession) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        if state.sessions.len() >= self.capacity && !state.sessions.contains_key(&key) {
+            if let Some(lru_key) = state
+                .sessions
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone())
+            {
+                state.sessions.remove(&lru_key);
+            }
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.sessions.insert(key, (session, tick));
+    }
+
+    fn remove(&self, key: &TlsSessionKey) -> Option<TlsSession> {
+        self.state
+            .lock()
+            .sessions
+            .remove(key)
+            .map(|(session, _)| session)
+    }
+}
+
+/// Return the globally shared [`TlsSessionStore`],
+/// which tls connectors can opt into to share sessions process-wide.
+pub fn default_tls_session_store() -> Arc<dyn TlsSessionStore> {
+    static STORE: OnceLock<Arc<dyn TlsSessionStore>> = OnceLock::new();
+    STORE
+        .get_or_init(|| Arc::new(InMemoryTlsSessionStore::default()))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Domain;
+
+    fn key(name: &'static str) -> TlsSessionKey {
+        TlsSessionKey::new(Domain::from_static(name).into(), 1)
+    }
+
+    #[test]
+    fn test_in_memory_tls_session_store_lru() {
+        let store = InMemoryTlsSessionStore::new(2);
+        store.insert(key("a.example"), TlsSession::new(1u8));
+        store.insert(key("b.example"), TlsSession::new(2u8));
+
+        // touch a, so that b is the least recently used
+        assert_eq!(
+            store
+                .get(&key("a.example"))
+                .unwrap()
+                .downcast_ref::<u8>()
+                .copied(),
+            Some(1)
+        );
+        store.insert(key("c.example"), TlsSession::new(3u8));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&key("b.example")).is_none());
+        assert!(store.get(&key("a.example")).is_some());
+        assert!(store.get(&key("c.example")).is_some());
+
+        // same server, other config
+        assert!(
+            store
+                .get(&TlsSessionKey::new(
+                    Domain::from_static("a.example").into(),
+                    2
+                ))
+                .is_none()
+        );
+
+        assert!(store.remove(&key("a.example")).is_some());
+        assert!(store.get(&key("a.example")).is_none());
+    }
+
+    #[test]
+    fn test_in_memory_tls_session_store_disabled() {
+        let store = InMemoryTlsSessionStore::new(0);
+        store.insert(key("a.example"), TlsSession::new(1u8));
+        assert!(store.is_empty());
+        assert!(store.get(&key("a.example")).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::tls::DataEncoding;
+use crate::tls::ocsp::{OcspError, OcspSignedData, verify_stapled_ocsp_response};
+use base64::Engine as _;
+use rama_core::error::OpaqueError;
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr, time::SystemTime};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+const BASE64: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+const SPKI_PIN_PREFIX: &str = "sha256/";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Custom server verification, used by [`super::ServerVerifyMode::Custom`].
+///
+/// Allows to trust extra (or only) specific root CAs,
+/// to pin the public keys of the server certificate chain,
+/// as well as to check whether the server certificate is revoked.
+pub struct CustomServerVerify {
+    /// Root CA certificates to trust, in addition to the default roots
+    /// of the (tls) client implementation, or instead of them in
+    /// case [`Self::replace_default_roots`] is enabled.
+    pub root_certs: Option<DataEncoding>,
+    /// If enabled only [`Self::root_certs`] are trusted,
+    /// and the default roots of the (tls) client implementation are not used.
+    pub replace_default_roots: bool,
+    /// SHA-256 pins of the DER-encoded SubjectPublicKeyInfo (SPKI)
+    /// of certificates in the verified server certificate chain,
+    /// from the leaf certificate up to and including the trust anchor.
+    ///
+    /// If not empty, at least one certificate in the verified chain
+    /// has to match one of these pins, on top of the regular verification.
+    /// Certificates sent by the server which are not part of the
+    /// verified chain are not taken into account.
+    pub spki_pins: Vec<SpkiPin>,
+    /// If enabled the server has to staple a valid OCSP response,
+    /// reporting its (leaf) certificate as not revoked.
+    ///
+    /// The issuer of the leaf certificate has to be part of the verified chain,
+    /// which includes the trust anchor.
+    pub require_ocsp_staple: bool,
+    /// Certificate revocation lists (CRLs) used to check whether
+    /// the server certificate chain contains revoked certificates.
+    ///
+    /// Not supported by all (tls) client implementations.
+    pub crls: Option<DataEncoding>,
+}
+
+impl CustomServerVerify {
+    /// Verify that at least one of the given [`SpkiPin`]s, computed for the
+    /// verified server certificate chain (including the trust anchor),
+    /// matches one of the configured pins.
+    ///
+    /// Always succeeds if no pins are configured.
+    pub fn verify_spki_pins(
+        &self,
+        chain: impl IntoIterator<Item = SpkiPin>,
+    ) -> Result<(), CertificatePinningError> {
+        if self.spki_pins.is_empty() {
+            return Ok(());
+        }
+        if chain.into_iter().any(|pin| self.spki_pins.contains(&pin)) {
+            Ok(())
+        } else {
+            Err(CertificatePinningError)
+        }
+    }
+
+    /// Verify the OCSP response stapled by the server for the verified
+    /// server certificate chain, in case [`Self::require_ocsp_staple`] is enabled.
+    ///
+    /// The chain consists of the (DER-encoded) certificates, leaf first,
+    /// up to but excluding the trust anchor, of which the (DER-encoded)
+    /// SubjectPublicKeyInfo is given separately.
+    ///
+    /// The signature of the response is verified using the given `verify_signature`
+    /// function, see [`crate::tls::ocsp::verify_stapled_ocsp_response`] for more information.
+    ///
+    /// Always succeeds if no OCSP staple is required.
+    pub fn verify_ocsp_staple<C: AsRef<[u8]>>(
+        &self,
+        chain: &[C],
+        trust_anchor_spki: &[u8],
+        ocsp_response: &[u8],
+        now: SystemTime,
+        verify_signature: impl Fn(&OcspSignedData<'_>) -> bool,
+    ) -> Result<(), OcspError> {
+        if !self.require_ocsp_staple {
+            return Ok(());
+        }
+        verify_stapled_ocsp_response(
+            chain,
+            trust_anchor_spki,
+            ocsp_response,
+            now,
+            verify_signature,
+        )
+        .map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// SHA-256 digest of a DER-encoded SubjectPublicKeyInfo (SPKI),
+/// used to pin the public key of a certificate.
+///
+/// Formatted and parsed as `sha256/<base64>`, as used by
+/// [RFC 7469] and tools such as curl's `--pinnedpubkey`.
+///
+/// [RFC 7469]: https://datatracker.ietf.org/doc/html/rfc7469
+pub struct SpkiPin([u8; 32]);
+
+impl SpkiPin {
+    /// Create a [`SpkiPin`] from an existing SHA-256 digest.
+    pub const fn new(digest: [u8; 32]) -> Self {
+        Self(digest)
+    }
+
+    /// Create a [`SpkiPin`] by hashing a DER-encoded SubjectPublicKeyInfo.
+    pub fn from_spki_der(spki: &[u8]) -> Self {
+        Self(Sha256::digest(spki).into())
+    }
+
+    /// Create a [`SpkiPin`] for the public key of a DER-encoded X.509 certificate.
+    pub fn try_from_certificate_der(cert: &[u8]) -> Result<Self, OpaqueError> {
+        let (_, cert) = X509Certificate::from_der(cert).map_err(|_| {
+            OpaqueError::from_display("invalid DER certificate: subject public key info not found")
+        })?;
+        Ok(Self::from_spki_der(cert.public_key().raw))
+    }
+
+    /// Return the SHA-256 digest of this [`SpkiPin`].
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for SpkiPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SPKI_PIN_PREFIX}{}", BASE64.encode(self.0))
+    }
+}
+
+impl FromStr for SpkiPin {
+    type Err = InvalidSpkiPin;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let encoded = s.strip_prefix(SPKI_PIN_PREFIX).unwrap_or(s);
+        let digest = BASE64.decode(encoded).map_err(|_| InvalidSpkiPin)?;
+        digest.try_into().map(Self).map_err(|_| InvalidSpkiPin)
+    }
+}
+
+rama_utils::macros::error::static_str_error! {
+    #[doc = "invalid SPKI pin (expected sha256/<base64>)"]
+    pub struct InvalidSpkiPin;
+}
+
+rama_utils::macros::error::static_str_error! {
+    #[doc = "server certificate chain does not match any of the pinned public keys"]
+    pub struct CertificatePinningError;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spki_pin_display_from_str() {
+        let pin = SpkiPin::from_spki_der(b"foo");
+        let s = pin.to_string();
+        assert!(s.starts_with("sha256/"));
+        assert_eq!(s.parse::<SpkiPin>().unwrap(), pin);
+        assert_eq!(
+            s.trim_start_matches("sha256/").parse::<SpkiPin>().unwrap(),
+            pin
+        );
+        assert!("sha256/Zm9v".parse::<SpkiPin>().is_err());
+        assert!("sha256/!!".parse::<SpkiPin>().is_err());
+    }
+
+    #[test]
+    fn test_spki_pin_try_from_certificate_der() {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["example.com".to_owned()])
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+
+        let pin = SpkiPin::try_from_certificate_der(cert.der()).unwrap();
+        assert_eq!(pin, SpkiPin::from_spki_der(&key_pair.public_key_der()));
+
+        assert!(SpkiPin::try_from_certificate_der(b"\x30\x03\x02\x01").is_err());
+    }
+
+    #[test]
+    fn test_verify_spki_pins() {
+        let a = SpkiPin::from_spki_der(b"a");
+        let b = SpkiPin::from_spki_der(b"b");
+
+        let verify = CustomServerVerify::default();
+        assert!(verify.verify_spki_pins([a]).is_ok());
+
+        let verify = CustomServerVerify {
+            spki_pins: vec![b],
+            ..Default::default()
+        };
+        assert!(verify.verify_spki_pins([a, b]).is_ok());
+        assert!(verify.verify_spki_pins([a]).is_err());
+        assert!(verify.verify_spki_pins([]).is_err());
+    }
+
+    #[test]
+    fn test_verify_ocsp_staple() {
+        let chain: [&[u8]; 0] = [];
+        let verify = CustomServerVerify::default();
+        assert!(
+            verify
+                .verify_ocsp_staple(&chain, &[], &[], SystemTime::now(), |_| false)
+                .is_ok()
+        );
+
+        let verify = CustomServerVerify {
+            require_ocsp_staple: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            verify.verify_ocsp_staple(&chain, &[], &[], SystemTime::now(), |_| true),
+            Err(OcspError::Missing)
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Encrypted Client Hello (ECH) configurations and keys,
+//! as defined in <https://datatracker.ietf.org/doc/html/draft-ietf-tls-esni/>.
+
+use super::{
+    AuthenticatedEncryptionWithAssociatedData, KeyDerivationFunction, KeyEncapsulationMechanism,
+    client::HpkeSymmetricCipherSuite,
+};
+use crate::address::Domain;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as ENGINE;
+use rama_core::error::{ErrorContext, OpaqueError};
+use std::{fmt, str::FromStr};
+
+/// Version of the `ECHConfig` structure supported by rama.
+const ECH_CONFIG_VERSION: u16 = 0xfe0d;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A single ECH configuration, as published by a server
+/// for clients to encrypt their (inner) ClientHello with.
+pub struct EchConfig {
+    /// Identifier of this config, sent by the client
+    /// to allow the server to find the matching key.
+    pub config_id: u8,
+    /// The [`KeyEncapsulationMechanism`] of the [`Self::public_key`].
+    pub kem_id: KeyEncapsulationMechanism,
+    /// The encoded HPKE public key of the server.
+    pub public_key: Vec<u8>,
+    /// HPKE cipher suites supported by the server for this config.
+    pub cipher_suites: Vec<HpkeSymmetricCipherSuite>,
+    /// The longest name the server expects clients to use,
+    /// used by clients to pad their inner ClientHello. `0` if unknown.
+    pub maximum_name_length: u8,
+    /// The name used as SNI in the outer (unencrypted) ClientHello.
+    pub public_name: Domain,
+    /// Encoded ECH config extensions, without their length prefix.
+    pub extensions: Vec<u8>,
+}
+
+impl EchConfig {
+    /// Create a new [`EchConfig`] for the given X25519 public key,
+    /// supporting the HKDF-SHA256 KDF together with the AES-128-GCM
+    /// and ChaCha20Poly1305 AEADs.
+    pub fn new(config_id: u8, public_key: Vec<u8>, public_name: Domain) -> Self {
+        Self {
+            config_id,
+            kem_id: KeyEncapsulationMechanism::DHKEM_X25519_HKDF_SHA256,
+            public_key,
+            cipher_suites: vec![
+                HpkeSymmetricCipherSuite {
+                    kdf_id: KeyDerivationFunction::HKDF_SHA256,
+                    aead_id: AuthenticatedEncryptionWithAssociatedData::AES_128_GCM,
+                },
+                HpkeSymmetricCipherSuite {
+                    kdf_id: KeyDerivationFunction::HKDF_SHA256,
+                    aead_id: AuthenticatedEncryptionWithAssociatedData::ChaCha20Poly1305,
+                },
+            ],
+            maximum_name_length: 0,
+            public_name,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Decode a single (versioned) `ECHConfig`.
+    pub fn decode(data: &[u8]) -> Result<Self, OpaqueError> {
+        let mut r = Reader(data);
+        let config = match decode_config(&mut r)? {
+            Some(config) => config,
+            None => {
+                return Err(OpaqueError::from_display(
+                    "decode ech config: unsupported version",
+                ));
+            }
+        };
+        r.finish()?;
+        Ok(config)
+    }
+
+    /// Encode this config as a (versioned) `ECHConfig`.
+    pub fn encode(&self) -> Result<Vec<u8>, OpaqueError> {
+        let mut contents = Vec::with_capacity(64 + self.public_key.len());
+        contents.push(self.config_id);
+        contents.extend_from_slice(&u16::from(self.kem_id).to_be_bytes());
+        write_u16_prefixed(&mut contents, &self.public_key).context("ech config: public key")?;
+        let mut suites = Vec::with_capacity(self.cipher_suites.len() * 4);
+        for suite in &self.cipher_suites {
+            suites.extend_from_slice(&u16::from(suite.kdf_id).to_be_bytes());
+            suites.extend_from_slice(&u16::from(suite.aead_id).to_be_bytes());
+        }
+        write_u16_prefixed(&mut contents, &suites).context("ech config: cipher suites")?;
+        contents.push(self.maximum_name_length);
+        let public_name = self.public_name.as_str().as_bytes();
+        contents.push(u8::try_from(public_name.len()).context("ech config: public name too long")?);
+        contents.extend_from_slice(public_name);
+        write_u16_prefixed(&mut contents, &self.extensions).context("ech config: extensions")?;
+
+        let mut output = Vec::with_capacity(4 + contents.len());
+        output.extend_from_slice(&ECH_CONFIG_VERSION.to_be_bytes());
+        write_u16_prefixed(&mut output, &contents).context("ech config: contents")?;
+        Ok(output)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+/// An encoded `ECHConfigList`, as published by a server
+/// in the `ech` parameter of its DNS 'HTTPS' records,
+/// or returned by it as retry configs.
+///
+/// The encoded form is kept as-is, such that configs of versions
+/// not supported by rama can still be passed to tls implementations that do.
+pub struct EchConfigList(Vec<u8>);
+
+impl EchConfigList {
+    /// Create an [`EchConfigList`] from its encoded form,
+    /// failing if the list is not well-formed.
+    pub fn try_from_bytes(data: impl Into<Vec<u8>>) -> Result<Self, OpaqueError> {
+        let data = data.into();
+        decode_config_list(&data).context("decode ech config list")?;
+        Ok(Self(data))
+    }
+
+    /// Create an [`EchConfigList`] containing the given configs.
+    pub fn try_from_configs<'a>(
+        configs: impl IntoIterator<Item = &'a EchConfig>,
+    ) -> Result<Self, OpaqueError> {
+        let mut list = Vec::new();
+        for config in configs {
+            list.extend(config.encode()?);
+        }
+        let mut data = Vec::with_capacity(2 + list.len());
+        write_u16_prefixed(&mut data, &list).context("encode ech config list")?;
+        Ok(Self(data))
+    }
+
+    /// Return the encoded form of this [`EchConfigList`].
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consume this [`EchConfigList`] into its encoded form.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Return the [`EchConfig`]s in this list of a version supported by rama.
+    pub fn configs(&self) -> Vec<EchConfig> {
+        // validated on creation
+        decode_config_list(&self.0).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for EchConfigList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EchConfigList")
+            .field(&ENGINE.encode(&self.0))
+            .finish()
+    }
+}
+
+impl fmt::Display for EchConfigList {
+    /// Formats the list in base64, as used in the presentation format of DNS records.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&ENGINE.encode(&self.0))
+    }
+}
+
+impl FromStr for EchConfigList {
+    type Err = OpaqueError;
+
+    /// Parses the base64 form of the list, as used in the presentation format of DNS records.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = ENGINE
+            .decode(s.trim())
+            .context("decode base64 ech config list")?;
+        Self::try_from_bytes(data)
+    }
+}
+
+impl TryFrom<Vec<u8>> for EchConfigList {
+    type Error = OpaqueError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(value)
+    }
+}
+
+impl TryFrom<&[u8]> for EchConfigList {
+    type Error = OpaqueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from_bytes(value)
+    }
+}
+
+#[derive(Clone)]
+/// An [`EchConfig`] together with its private key,
+/// used by a server to decrypt the ClientHello of clients using that config.
+pub struct EchKey {
+    /// The config published for this key.
+    pub config: EchConfig,
+    /// The raw HPKE private key matching the public key of the [`Self::config`].
+    pub private_key: Vec<u8>,
+    /// If `true` the [`Self::config`] is sent to clients as retry config,
+    /// in case they used a config unknown to the server.
+    pub retry_config: bool,
+}
+
+impl EchKey {
+    /// Create a new [`EchKey`] for the given config and private key,
+    /// with the config used as retry config.
+    pub fn new(config: EchConfig, private_key: Vec<u8>) -> Self {
+        Self {
+            config,
+            private_key,
+            retry_config: true,
+        }
+    }
+}
+
+impl fmt::Debug for EchKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EchKey")
+            .field("config", &self.config)
+            .field("private_key", &"<redacted>")
+            .field("retry_config", &self.retry_config)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The set of [`EchKey`]s a server can decrypt ClientHellos with.
+///
+/// Multiple keys are typically used during key rotation,
+/// such that clients using a previously published config are still accepted.
+pub struct EchKeySet {
+    keys: Vec<EchKey>,
+}
+
+impl EchKeySet {
+    /// Create a new empty [`EchKeySet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an [`EchKey`] to this set.
+    pub fn add_key(&mut self, key: EchKey) -> &mut Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Same as [`Self::add_key`] but consuming self.
+    pub fn with_key(mut self, key: EchKey) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Return the [`EchKey`]s in this set.
+    pub fn keys(&self) -> &[EchKey] {
+        &self.keys
+    }
+
+    /// Returns `true` if this set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Return the [`EchConfigList`] of the retry configs in this set,
+    /// which is the list to be published in the DNS 'HTTPS' records of the server.
+    pub fn ech_config_list(&self) -> Result<EchConfigList, OpaqueError> {
+        EchConfigList::try_from_configs(
+            self.keys
+                .iter()
+                .filter(|key| key.retry_config)
+                .map(|key| &key.config),
+        )
+    }
+}
+
+impl FromIterator<EchKey> for EchKeySet {
+    fn from_iter<T: IntoIterator<Item = EchKey>>(iter: T) -> Self {
+        Self {
+            keys: iter.into_iter().collect(),
+        }
+    }
+}
+
+fn write_u16_prefixed(output: &mut Vec<u8>, data: &[u8]) -> Result<(), OpaqueError> {
+    let len = u16::try_from(data.len()).context("data too long for u16 length prefix")?;
+    output.extend_from_slice(&len.to_be_bytes());
+    output.extend_from_slice(data);
+    Ok(())
+}
+
+/// Decode an `ECHConfigList`, skipping configs of unsupported versions.
+fn decode_config_list(data: &[u8]) -> Result<Vec<EchConfig>, OpaqueError> {
+    let mut r = Reader(data);
+    let mut list = Reader(r.u16_prefixed()?);
+    r.finish()?;
+    if list.0.is_empty() {
+        return Err(OpaqueError::from_display("empty ech config list"));
+    }
+
+    let mut configs = Vec::new();
+    while !list.0.is_empty() {
+        if let Some(config) = decode_config(&mut list)? {
+            configs.push(config);
+        }
+    }
+    Ok(configs)
+}
+
+/// Decode a single `ECHConfig`, returning `None` for unsupported versions.
+fn decode_config(r: &mut Reader<'_>) -> Result<Option<EchConfig>, OpaqueError> {
+    let version = r.u16()?;
+    let mut contents = Reader(r.u16_prefixed()?);
+    if version != ECH_CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    let config_id = contents.u8()?;
+    let kem_id = contents.u16()?.into();
+    let public_key = contents.u16_prefixed()?.to_vec();
+    if public_key.is_empty() {
+        return Err(OpaqueError::from_display("ech config: empty public key"));
+    }
+
+    let mut suites = Reader(contents.u16_prefixed()?);
+    if suites.0.is_empty() || suites.0.len() % 4 != 0 {
+        return Err(OpaqueError::from_display(
+            "ech config: invalid cipher suites",
+        ));
+    }
+    let mut cipher_suites = Vec::with_capacity(suites.0.len() / 4);
+    while !suites.0.is_empty() {
+        cipher_suites.push(HpkeSymmetricCipherSuite {
+            kdf_id: suites.u16()?.into(),
+            aead_id: suites.u16()?.into(),
+        });
+    }
+
+    let maximum_name_length = contents.u8()?;
+    let public_name = contents.u8_prefixed()?;
+    let public_name = Domain::try_from(public_name).context("ech config: invalid public name")?;
+    let extensions = contents.u16_prefixed()?.to_vec();
+    contents.finish()?;
+
+    Ok(Some(EchConfig {
+        config_id,
+        kem_id,
+        public_key,
+        cipher_suites,
+        maximum_name_length,
+        public_name,
+        extensions,
+    }))
+}
+
+/// Minimal reader of the TLS presentation language encoding.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], OpaqueError> {
+        if self.0.len() < n {
+            return Err(OpaqueError::from_display("unexpected end of ech data"));
+        }
+        let (data, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> Result<u8, OpaqueError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, OpaqueError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u8_prefixed(&mut self) -> Result<&'a [u8], OpaqueError> {
+        let n = self.u8()? as usize;
+        self.bytes(n)
+    }
+
+    fn u16_prefixed(&mut self) -> Result<&'a [u8], OpaqueError> {
+        let n = self.u16()? as usize;
+        self.bytes(n)
+    }
+
+    fn finish(&self) -> Result<(), OpaqueError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(OpaqueError::from_display("trailing ech data"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(config_id: u8) -> EchConfig {
+        EchConfig::new(
+            config_id,
+            vec![config_id; 32],
+            Domain::from_static("public.example.com"),
+        )
+    }
+
+    #[test]
+    fn test_ech_config_encode_decode() {
+        let config = test_config(7);
+        let encoded = config.encode().unwrap();
+        assert_eq!(&encoded[..2], &[0xfe, 0x0d]);
+        assert_eq!(EchConfig::decode(&encoded).unwrap(), config);
+    }
+
+    #[test]
+    fn test_ech_config_decode_invalid() {
+        let encoded = test_config(1).encode().unwrap();
+        assert!(EchConfig::decode(&encoded[..encoded.len() - 1]).is_err());
+
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(EchConfig::decode(&trailing).is_err());
+
+        let mut unknown_version = encoded;
+        unknown_version[1] = 0x0a;
+        assert!(EchConfig::decode(&unknown_version).is_err());
+    }
+
+    #[test]
+    fn test_ech_config_list_roundtrip() {
+        let configs = [test_config(1), test_config(2)];
+        let list = EchConfigList::try_from_configs(&configs).unwrap();
+        assert_eq!(list.configs(), configs);
+
+        let parsed: EchConfigList = list.to_string().parse().unwrap();
+        assert_eq!(parsed, list);
+        assert_eq!(
+            EchConfigList::try_from_bytes(list.as_bytes()).unwrap(),
+            list
+        );
+    }
+
+    #[test]
+    fn test_ech_config_list_skips_unsupported_versions() {
+        let config = test_config(3).encode().unwrap();
+        let unsupported = [0xfe, 0x0a, 0x00, 0x02, 0xaa, 0xbb];
+
+        let mut list = Vec::new();
+        list.extend_from_slice(&((config.len() + unsupported.len()) as u16).to_be_bytes());
+        list.extend_from_slice(&unsupported);
+        list.extend_from_slice(&config);
+
+        let list = EchConfigList::try_from_bytes(list).unwrap();
+        assert_eq!(list.configs(), vec![test_config(3)]);
+    }
+
+    #[test]
+    fn test_ech_config_list_invalid() {
+        assert!(EchConfigList::try_from_bytes(vec![]).is_err());
+        assert!(EchConfigList::try_from_bytes(vec![0, 0]).is_err());
+        assert!(EchConfigList::try_from_bytes(vec![0, 4, 0xfe, 0x0d]).is_err());
+        assert!("not base64!".parse::<EchConfigList>().is_err());
+    }
+
+    #[test]
+    fn test_ech_key_set_config_list() {
+        let mut old_key = EchKey::new(test_config(1), vec![1; 32]);
+        old_key.retry_config = false;
+        let keys: EchKeySet = [old_key, EchKey::new(test_config(2), vec![0xaa; 32])]
+            .into_iter()
+            .collect();
+
+        assert_eq!(keys.keys().len(), 2);
+        assert_eq!(
+            keys.ech_config_list().unwrap().configs(),
+            vec![test_config(2)]
+        );
+        assert!(!format!("{:?}", keys).contains("170, 170"));
+    }
+}
@@ -92,447 +92,305 @@ pub fn openssl_cipher_list_str_from_cipher_list(suites: &[super::CipherSuite]) -
     (!s.is_empty()).then_some(s)
 }
 
-fn openssl_cipher_str_from_cipher_suite(suite: super::CipherSuite) -> Option<&'static str> {
-    match suite {
-        super::CipherSuite::TLS_RSA_WITH_NULL_MD5 => Some("NULL-MD5"),
-        super::CipherSuite::TLS_RSA_WITH_NULL_SHA => Some("NULL-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_RC4_128_MD5 => Some("RC4-MD5"),
-        super::CipherSuite::TLS_RSA_WITH_RC4_128_SHA => Some("RC4-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_IDEA_CBC_SHA => Some("IDEA-CBC-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_3DES_EDE_CBC_SHA => Some("DES-CBC3-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_3DES_EDE_CBC_SHA => Some("DH-DSS-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_3DES_EDE_CBC_SHA => Some("DH-RSA-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_3DES_EDE_CBC_SHA => Some("DHE-DSS-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA => Some("DHE-RSA-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_DH_anon_WITH_RC4_128_MD5 => Some("ADH-RC4-MD5"),
-        super::CipherSuite::TLS_DH_anon_WITH_3DES_EDE_CBC_SHA => Some("ADH-DES-CBC3-SHA"),
-        super::CipherSuite::SSL_FORTEZZA_KEA_WITH_NULL_SHA => None, // not implemented (SSL v3.0)
-        super::CipherSuite::SSL_FORTEZZA_KEA_WITH_FORTEZZA_CBC_SHA => None, // not implemented (SSL v3.0)
-        super::CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA => Some("AES128-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_AES_256_CBC_SHA => Some("AES256-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_AES_128_CBC_SHA => Some("DH-DSS-AES128-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_AES_256_CBC_SHA => Some("DH-DSS-AES256-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_AES_128_CBC_SHA => Some("DH-RSA-AES128-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_AES_256_CBC_SHA => Some("DH-RSA-AES256-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_AES_128_CBC_SHA => Some("DHE-DSS-AES128-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_AES_256_CBC_SHA => Some("DHE-DSS-AES256-SHA"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_128_CBC_SHA => Some("DHE-RSA-AES128-SHA"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_256_CBC_SHA => Some("DHE-RSA-AES256-SHA"),
-        super::CipherSuite::TLS_DH_anon_WITH_AES_128_CBC_SHA => Some("ADH-AES128-SHA"),
-        super::CipherSuite::TLS_DH_anon_WITH_AES_256_CBC_SHA => Some("ADH-AES256-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_CAMELLIA_128_CBC_SHA => Some("CAMELLIA128-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_CAMELLIA_256_CBC_SHA => Some("CAMELLIA256-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA => Some("DH-DSS-CAMELLIA128-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA => Some("DH-DSS-CAMELLIA256-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA => Some("DH-RSA-CAMELLIA128-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA => Some("DH-RSA-CAMELLIA256-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA => {
-            Some("DHE-DSS-CAMELLIA128-SHA")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA => {
-            Some("DHE-DSS-CAMELLIA256-SHA")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA => {
-            Some("DHE-RSA-CAMELLIA128-SHA")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA => {
-            Some("DHE-RSA-CAMELLIA256-SHA")
-        }
-        super::CipherSuite::TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA => Some("ADH-CAMELLIA128-SHA"),
-        super::CipherSuite::TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA => Some("ADH-CAMELLIA256-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_SEED_CBC_SHA => Some("SEED-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_SEED_CBC_SHA => Some("DH-DSS-SEED-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_SEED_CBC_SHA => Some("DH-RSA-SEED-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_SEED_CBC_SHA => Some("DHE-DSS-SEED-SHA"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_SEED_CBC_SHA => Some("DHE-RSA-SEED-SHA"),
-        super::CipherSuite::TLS_DH_anon_WITH_SEED_CBC_SHA => Some("ADH-SEED-SHA"),
-        super::CipherSuite::TLS_GOSTR341094_WITH_28147_CNT_IMIT => Some("GOST94-GOST89-GOST89"),
-        super::CipherSuite::TLS_GOSTR341001_WITH_28147_CNT_IMIT => Some("GOST2001-GOST89-GOST89"),
-        super::CipherSuite::TLS_GOSTR341094_WITH_NULL_GOSTR3411 => Some("GOST94-NULL-GOST94"),
-        super::CipherSuite::TLS_GOSTR341001_WITH_NULL_GOSTR3411 => Some("GOST2001-NULL-GOST94"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_RC4_128_SHA => Some("DHE-DSS-RC4-SHA"),
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_NULL_SHA => Some("ECDHE-RSA-NULL-SHA"),
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_RC4_128_SHA => Some("ECDHE-RSA-RC4-SHA"),
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA => Some("ECDHE-RSA-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA => Some("ECDHE-RSA-AES128-SHA"),
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA => Some("ECDHE-RSA-AES256-SHA"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_NULL_SHA => Some("ECDHE-ECDSA-NULL-SHA"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_RC4_128_SHA => Some("ECDHE-ECDSA-RC4-SHA"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA => {
-            Some("ECDHE-ECDSA-DES-CBC3-SHA")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA => Some("ECDHE-ECDSA-AES128-SHA"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA => Some("ECDHE-ECDSA-AES256-SHA"),
-        super::CipherSuite::TLS_ECDH_anon_WITH_NULL_SHA => Some("AECDH-NULL-SHA"),
-        super::CipherSuite::TLS_ECDH_anon_WITH_RC4_128_SHA => Some("AECDH-RC4-SHA"),
-        super::CipherSuite::TLS_ECDH_anon_WITH_3DES_EDE_CBC_SHA => Some("AECDH-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_ECDH_anon_WITH_AES_128_CBC_SHA => Some("AECDH-AES128-SHA"),
-        super::CipherSuite::TLS_ECDH_anon_WITH_AES_256_CBC_SHA => Some("AECDH-AES256-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_NULL_SHA256 => Some("NULL-SHA256"),
-        super::CipherSuite::TLS_RSA_WITH_AES_128_CBC_SHA256 => Some("AES128-SHA256"),
-        super::CipherSuite::TLS_RSA_WITH_AES_256_CBC_SHA256 => Some("AES256-SHA256"),
-        super::CipherSuite::TLS_RSA_WITH_AES_128_GCM_SHA256 => Some("AES128-GCM-SHA256"),
-        super::CipherSuite::TLS_RSA_WITH_AES_256_GCM_SHA384 => Some("AES256-GCM-SHA384"),
-        super::CipherSuite::TLS_DH_RSA_WITH_AES_128_CBC_SHA256 => Some("DH-RSA-AES128-SHA256"),
-        super::CipherSuite::TLS_DH_RSA_WITH_AES_256_CBC_SHA256 => Some("DH-RSA-AES256-SHA256"),
-        super::CipherSuite::TLS_DH_RSA_WITH_AES_128_GCM_SHA256 => Some("DH-RSA-AES128-GCM-SHA256"),
-        super::CipherSuite::TLS_DH_RSA_WITH_AES_256_GCM_SHA384 => Some("DH-RSA-AES256-GCM-SHA384"),
-        super::CipherSuite::TLS_DH_DSS_WITH_AES_128_CBC_SHA256 => Some("DH-DSS-AES128-SHA256"),
-        super::CipherSuite::TLS_DH_DSS_WITH_AES_256_CBC_SHA256 => Some("DH-DSS-AES256-SHA256"),
-        super::CipherSuite::TLS_DH_DSS_WITH_AES_128_GCM_SHA256 => Some("DH-DSS-AES128-GCM-SHA256"),
-        super::CipherSuite::TLS_DH_DSS_WITH_AES_256_GCM_SHA384 => Some("DH-DSS-AES256-GCM-SHA384"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_128_CBC_SHA256 => Some("DHE-RSA-AES128-SHA256"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_256_CBC_SHA256 => Some("DHE-RSA-AES256-SHA256"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_128_GCM_SHA256 => {
-            Some("DHE-RSA-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_256_GCM_SHA384 => {
-            Some("DHE-RSA-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_AES_128_CBC_SHA256 => Some("DHE-DSS-AES128-SHA256"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_AES_256_CBC_SHA256 => Some("DHE-DSS-AES256-SHA256"),
-        super::CipherSuite::TLS_DHE_DSS_WITH_AES_128_GCM_SHA256 => {
-            Some("DHE-DSS-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_AES_256_GCM_SHA384 => {
-            Some("DHE-DSS-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256 => {
-            Some("ECDHE-RSA-AES128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384 => {
-            Some("ECDHE-RSA-AES256-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 => {
-            Some("ECDHE-RSA-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384 => {
-            Some("ECDHE-RSA-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256 => {
-            Some("ECDHE-ECDSA-AES128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384 => {
-            Some("ECDHE-ECDSA-AES256-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256 => {
-            Some("ECDHE-ECDSA-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384 => {
-            Some("ECDHE-ECDSA-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_DH_anon_WITH_AES_128_CBC_SHA256 => Some("ADH-AES128-SHA256"),
-        super::CipherSuite::TLS_DH_anon_WITH_AES_256_CBC_SHA256 => Some("ADH-AES256-SHA256"),
-        super::CipherSuite::TLS_DH_anon_WITH_AES_128_GCM_SHA256 => Some("ADH-AES128-GCM-SHA256"),
-        super::CipherSuite::TLS_DH_anon_WITH_AES_256_GCM_SHA384 => Some("ADH-AES256-GCM-SHA384"),
-        super::CipherSuite::TLS_RSA_WITH_AES_128_CCM => Some("AES128-CCM"),
-        super::CipherSuite::TLS_RSA_WITH_AES_256_CCM => Some("AES256-CCM"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_128_CCM => Some("DHE-RSA-AES128-CCM"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_256_CCM => Some("DHE-RSA-AES256-CCM"),
-        super::CipherSuite::TLS_RSA_WITH_AES_128_CCM_8 => Some("AES128-CCM8"),
-        super::CipherSuite::TLS_RSA_WITH_AES_256_CCM_8 => Some("AES256-CCM8"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_128_CCM_8 => Some("DHE-RSA-AES128-CCM8"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_AES_256_CCM_8 => Some("DHE-RSA-AES256-CCM8"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_CCM => Some("ECDHE-ECDSA-AES128-CCM"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_CCM => Some("ECDHE-ECDSA-AES256-CCM"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8 => Some("ECDHE-ECDSA-AES128-CCM8"),
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8 => Some("ECDHE-ECDSA-AES256-CCM8"),
-        super::CipherSuite::TLS_RSA_WITH_ARIA_128_GCM_SHA256 => Some("ARIA128-GCM-SHA256"),
-        super::CipherSuite::TLS_RSA_WITH_ARIA_256_GCM_SHA384 => Some("ARIA256-GCM-SHA384"),
-        super::CipherSuite::TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256 => {
-            Some("DHE-RSA-ARIA128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384 => {
-            Some("DHE-RSA-ARIA256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_ARIA_128_GCM_SHA256 => {
-            Some("DHE-DSS-ARIA128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_ARIA_256_GCM_SHA384 => {
-            Some("DHE-DSS-ARIA256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256 => {
-            Some("ECDHE-ECDSA-ARIA128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384 => {
-            Some("ECDHE-ECDSA-ARIA256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256 => {
-            Some("ECDHE-ARIA128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384 => {
-            Some("ECDHE-ARIA256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_PSK_WITH_ARIA_128_GCM_SHA256 => Some("PSK-ARIA128-GCM-SHA256"),
-        super::CipherSuite::TLS_PSK_WITH_ARIA_256_GCM_SHA384 => Some("PSK-ARIA256-GCM-SHA384"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256 => {
-            Some("DHE-PSK-ARIA128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384 => {
-            Some("DHE-PSK-ARIA256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256 => {
-            Some("RSA-PSK-ARIA128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384 => {
-            Some("RSA-PSK-ARIA256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("ECDHE-ECDSA-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("ECDHE-ECDSA-CAMELLIA256-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("ECDHE-RSA-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("ECDHE-RSA-CAMELLIA256-SHA384")
-        }
-        super::CipherSuite::TLS_PSK_WITH_NULL_SHA => Some("PSK-NULL-SHA"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_NULL_SHA => Some("DHE-PSK-NULL-SHA"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_NULL_SHA => Some("RSA-PSK-NULL-SHA"),
-        super::CipherSuite::TLS_PSK_WITH_RC4_128_SHA => Some("PSK-RC4-SHA"),
-        super::CipherSuite::TLS_PSK_WITH_3DES_EDE_CBC_SHA => Some("PSK-3DES-EDE-CBC-SHA"),
-        super::CipherSuite::TLS_PSK_WITH_AES_128_CBC_SHA => Some("PSK-AES128-CBC-SHA"),
-        super::CipherSuite::TLS_PSK_WITH_AES_256_CBC_SHA => Some("PSK-AES256-CBC-SHA"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_RC4_128_SHA => Some("DHE-PSK-RC4-SHA"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_3DES_EDE_CBC_SHA => Some("DHE-PSK-3DES-EDE-CBC-SHA"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_128_CBC_SHA => Some("DHE-PSK-AES128-CBC-SHA"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_256_CBC_SHA => Some("DHE-PSK-AES256-CBC-SHA"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_RC4_128_SHA => Some("RSA-PSK-RC4-SHA"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_3DES_EDE_CBC_SHA => Some("RSA-PSK-3DES-EDE-CBC-SHA"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_AES_128_CBC_SHA => Some("RSA-PSK-AES128-CBC-SHA"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_AES_256_CBC_SHA => Some("RSA-PSK-AES256-CBC-SHA"),
-        super::CipherSuite::TLS_PSK_WITH_AES_128_GCM_SHA256 => Some("PSK-AES128-GCM-SHA256"),
-        super::CipherSuite::TLS_PSK_WITH_AES_256_GCM_SHA384 => Some("PSK-AES256-GCM-SHA384"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_128_GCM_SHA256 => {
-            Some("DHE-PSK-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_256_GCM_SHA384 => {
-            Some("DHE-PSK-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_AES_128_GCM_SHA256 => {
-            Some("RSA-PSK-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_AES_256_GCM_SHA384 => {
-            Some("RSA-PSK-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_PSK_WITH_AES_128_CBC_SHA256 => Some("PSK-AES128-CBC-SHA256"),
-        super::CipherSuite::TLS_PSK_WITH_AES_256_CBC_SHA384 => Some("PSK-AES256-CBC-SHA384"),
-        super::CipherSuite::TLS_PSK_WITH_NULL_SHA256 => Some("PSK-NULL-SHA256"),
-        super::CipherSuite::TLS_PSK_WITH_NULL_SHA384 => Some("PSK-NULL-SHA384"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_128_CBC_SHA256 => {
-            Some("DHE-PSK-AES128-CBC-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_256_CBC_SHA384 => {
-            Some("DHE-PSK-AES256-CBC-SHA384")
-        }
-        super::CipherSuite::TLS_DHE_PSK_WITH_NULL_SHA256 => Some("DHE-PSK-NULL-SHA256"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_NULL_SHA384 => Some("DHE-PSK-NULL-SHA384"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_AES_128_CBC_SHA256 => {
-            Some("RSA-PSK-AES128-CBC-SHA256")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_AES_256_CBC_SHA384 => {
-            Some("RSA-PSK-AES256-CBC-SHA384")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_NULL_SHA256 => Some("RSA-PSK-NULL-SHA256"),
-        super::CipherSuite::TLS_RSA_PSK_WITH_NULL_SHA384 => Some("RSA-PSK-NULL-SHA384"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_RC4_128_SHA => Some("ECDHE-PSK-RC4-SHA"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_3DES_EDE_CBC_SHA => {
-            Some("ECDHE-PSK-3DES-EDE-CBC-SHA")
-        }
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA => Some("ECDHE-PSK-AES128-CBC-SHA"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA => Some("ECDHE-PSK-AES256-CBC-SHA"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256 => {
-            Some("ECDHE-PSK-AES128-CBC-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384 => {
-            Some("ECDHE-PSK-AES256-CBC-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_NULL_SHA => Some("ECDHE-PSK-NULL-SHA"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_NULL_SHA256 => Some("ECDHE-PSK-NULL-SHA256"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_NULL_SHA384 => Some("ECDHE-PSK-NULL-SHA384"),
-        super::CipherSuite::TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256 => Some("PSK-CAMELLIA128-SHA256"),
-        super::CipherSuite::TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384 => Some("PSK-CAMELLIA256-SHA384"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("DHE-PSK-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("DHE-PSK-CAMELLIA256-SHA384")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("RSA-PSK-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("RSA-PSK-CAMELLIA256-SHA384")
-        }
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("ECDHE-PSK-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("ECDHE-PSK-CAMELLIA256-SHA384")
-        }
-        super::CipherSuite::TLS_PSK_WITH_AES_128_CCM => Some("PSK-AES128-CCM"),
-        super::CipherSuite::TLS_PSK_WITH_AES_256_CCM => Some("PSK-AES256-CCM"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_128_CCM => Some("DHE-PSK-AES128-CCM"),
-        super::CipherSuite::TLS_DHE_PSK_WITH_AES_256_CCM => Some("DHE-PSK-AES256-CCM"),
-        super::CipherSuite::TLS_PSK_WITH_AES_128_CCM_8 => Some("PSK-AES128-CCM8"),
-        super::CipherSuite::TLS_PSK_WITH_AES_256_CCM_8 => Some("PSK-AES256-CCM8"),
-        super::CipherSuite::TLS_PSK_DHE_WITH_AES_128_CCM_8 => Some("DHE-PSK-AES128-CCM8"),
-        super::CipherSuite::TLS_PSK_DHE_WITH_AES_256_CCM_8 => Some("DHE-PSK-AES256-CCM8"),
-        super::CipherSuite::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256 => {
-            Some("ECDHE-RSA-CHACHA20-POLY1305")
-        }
-        super::CipherSuite::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256 => {
-            Some("ECDHE-ECDSA-CHACHA20-POLY1305")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256 => {
-            Some("DHE-RSA-CHACHA20-POLY1305")
-        }
-        super::CipherSuite::TLS_PSK_WITH_CHACHA20_POLY1305_SHA256 => Some("PSK-CHACHA20-POLY1305"),
-        super::CipherSuite::TLS_ECDHE_PSK_WITH_CHACHA20_POLY1305_SHA256 => {
-            Some("ECDHE-PSK-CHACHA20-POLY1305")
-        }
-        super::CipherSuite::TLS_DHE_PSK_WITH_CHACHA20_POLY1305_SHA256 => {
-            Some("DHE-PSK-CHACHA20-POLY1305")
-        }
-        super::CipherSuite::TLS_RSA_PSK_WITH_CHACHA20_POLY1305_SHA256 => {
-            Some("RSA-PSK-CHACHA20-POLY1305")
-        }
-        super::CipherSuite::TLS13_AES_128_GCM_SHA256 => Some("TLS_AES_128_GCM_SHA256"),
-        super::CipherSuite::TLS13_AES_256_GCM_SHA384 => Some("TLS_AES_256_GCM_SHA384"),
-        super::CipherSuite::TLS13_CHACHA20_POLY1305_SHA256 => Some("TLS_CHACHA20_POLY1305_SHA256"),
-        super::CipherSuite::TLS13_AES_128_CCM_SHA256 => Some("TLS_AES_128_CCM_SHA256"),
-        super::CipherSuite::TLS13_AES_128_CCM_8_SHA256 => Some("TLS_AES_128_CCM_8_SHA256"),
-        super::CipherSuite::TLS_NULL_WITH_NULL_NULL => Some("NULL"),
-        super::CipherSuite::TLS_RSA_EXPORT_WITH_RC4_40_MD5 => Some("EXP-RC4-MD5"),
-        super::CipherSuite::TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5 => Some("EXP-RC2-CBC-MD5"),
-        super::CipherSuite::TLS_RSA_EXPORT_WITH_DES40_CBC_SHA => Some("EXP-DES-CBC-SHA"),
-        super::CipherSuite::TLS_RSA_WITH_DES_CBC_SHA => Some("DES-CBC-SHA"),
-        super::CipherSuite::TLS_DH_DSS_EXPORT_WITH_DES40_CBC_SHA => Some("EXP-DH-DSS-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DH_DSS_WITH_DES_CBC_SHA => Some("DH-DSS-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DH_RSA_EXPORT_WITH_DES40_CBC_SHA => Some("EXP-DH-RSA-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DH_RSA_WITH_DES_CBC_SHA => Some("DH-RSA-DES-CBC-SHA	"),
-        super::CipherSuite::TLS_DHE_DSS_EXPORT_WITH_DES40_CBC_SHA => {
-            Some("EXP-EDH-DSS-DES-CBC-SHA")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_DES_CBC_SHA => Some("EDH-DSS-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA => {
-            Some("EXP-EDH-RSA-DES-CBC-SHA")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_DES_CBC_SHA => Some("EDH-RSA-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DH_anon_EXPORT_WITH_RC4_40_MD5 => Some("EXP-ADH-RC4-MD5"),
-        super::CipherSuite::TLS_DH_anon_EXPORT_WITH_DES40_CBC_SHA => Some("EXP-ADH-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DH_anon_WITH_DES_CBC_SHA => Some("ADH-DES-CBC-SHA"),
-        super::CipherSuite::TLS_KRB5_WITH_DES_CBC_SHA_or_SSL_FORTEZZA_KEA_WITH_RC4_128_SHA => {
-            Some("KRB5-DES-CBC-SHA")
-        }
-        super::CipherSuite::TLS_KRB5_WITH_3DES_EDE_CBC_SHA => Some("KRB5-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_KRB5_WITH_RC4_128_SHA => Some("KRB5-RC4-SHA"),
-        super::CipherSuite::TLS_KRB5_WITH_IDEA_CBC_SHA => Some("KRB5-IDEA-CBC-SHA"),
-        super::CipherSuite::TLS_KRB5_WITH_DES_CBC_MD5 => Some("KRB5-DES-CBC-MD5"),
-        super::CipherSuite::TLS_KRB5_WITH_3DES_EDE_CBC_MD5 => Some("KRB5-DES-CBC3-MD5"),
-        super::CipherSuite::TLS_KRB5_WITH_RC4_128_MD5 => Some("KRB5-RC4-MD5"),
-        super::CipherSuite::TLS_KRB5_WITH_IDEA_CBC_MD5 => Some("KRB5-IDEA-CBC-MD5"),
-        super::CipherSuite::TLS_KRB5_EXPORT_WITH_DES_CBC_40_SHA => Some("EXP-KRB5-DES-CBC-SHA"),
-        super::CipherSuite::TLS_KRB5_EXPORT_WITH_RC2_CBC_40_SHA => Some("EXP-KRB5-RC2-CBC-SHA"),
-        super::CipherSuite::TLS_KRB5_EXPORT_WITH_RC4_40_SHA => Some("EXP-KRB5-RC4-SHA"),
-        super::CipherSuite::TLS_KRB5_EXPORT_WITH_DES_CBC_40_MD5 => Some("EXP-KRB5-DES-CBC-MD5"),
-        super::CipherSuite::TLS_KRB5_EXPORT_WITH_RC2_CBC_40_MD5 => Some("EXP-KRB5-RC2-CBC-MD5"),
-        super::CipherSuite::TLS_KRB5_EXPORT_WITH_RC4_40_MD5 => Some("EXP-KRB5-RC4-MD5"),
-        super::CipherSuite::TLS_RSA_EXPORT1024_WITH_RC4_56_MD5 => Some("EXP1024-RC4-MD5"),
-        super::CipherSuite::TLS_RSA_EXPORT1024_WITH_RC2_CBC_56_MD5 => Some("EXP1024-RC2-CBC-MD5"),
-        super::CipherSuite::TLS_RSA_EXPORT1024_WITH_DES_CBC_SHA => Some("EXP1024-DES-CBC-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_EXPORT1024_WITH_DES_CBC_SHA => {
-            Some("EXP1024-DHE-DSS-DES-CBC-SHA")
-        }
-        super::CipherSuite::TLS_RSA_EXPORT1024_WITH_RC4_56_SHA => Some("EXP1024-RC4-SHA"),
-        super::CipherSuite::TLS_DHE_DSS_EXPORT1024_WITH_RC4_56_SHA => {
-            Some("EXP1024-DHE-DSS-RC4-SHA")
-        }
-        super::CipherSuite::TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256 => Some("CAMELLIA256-SHA256"),
-        super::CipherSuite::TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256 => Some("CAMELLIA128-SHA256"),
-        super::CipherSuite::TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("DH-DSS-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("DH-RSA-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("DHE-DSS-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("DHE-RSA-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256 => {
-            Some("DHE-RSA-CAMELLIA256-SHA256")
-        }
-        super::CipherSuite::TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("ADH-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_EMPTY_RENEGOTIATION_INFO_SCSV => Some("TLS_FALLBACK_SCSV"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_NULL_SHA => Some("ECDH-ECDSA-NULL-SHA"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_RC4_128_SHA => Some("ECDH-ECDSA-RC4-SHA"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_3DES_EDE_CBC_SHA => Some("ECDH-ECDSA-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA => Some("ECDH-ECDSA-AES128-SHA"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA => Some("ECDH-ECDSA-AES256-SHA"),
-        super::CipherSuite::TLS_ECDH_RSA_WITH_NULL_SHA => Some("ECDH-RSA-NULL-SHA"),
-        super::CipherSuite::TLS_ECDH_RSA_WITH_RC4_128_SHA => Some("ECDH-RSA-RC4-SHA"),
-        super::CipherSuite::TLS_ECDH_RSA_WITH_3DES_EDE_CBC_SHA => Some("ECDH-RSA-DES-CBC3-SHA"),
-        super::CipherSuite::TLS_ECDH_RSA_WITH_AES_128_CBC_SHA => Some("ECDH-RSA-AES128-SHA"),
-        super::CipherSuite::TLS_ECDH_RSA_WITH_AES_256_CBC_SHA => Some("ECDH-RSA-AES256-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_WITH_3DES_EDE_CBC_SHA => Some("SRP-3DES-EDE-CBC-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_RSA_WITH_3DES_EDE_CBC_SHA => {
-            Some("SRP-RSA-3DES-EDE-CBC-SHA")
-        }
-        super::CipherSuite::TLS_SRP_SHA_DSS_WITH_3DES_EDE_CBC_SHA => {
-            Some("SRP-DSS-3DES-EDE-CBC-SHA")
-        }
-        super::CipherSuite::TLS_SRP_SHA_WITH_AES_128_CBC_SHA => Some("SRP-AES-128-CBC-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_RSA_WITH_AES_128_CBC_SHA => Some("SRP-RSA-AES-128-CBC-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_DSS_WITH_AES_128_CBC_SHA => Some("SRP-DSS-AES-128-CBC-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_WITH_AES_256_CBC_SHA => Some("SRP-AES-256-CBC-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_RSA_WITH_AES_256_CBC_SHA => Some("SRP-RSA-AES-256-CBC-SHA"),
-        super::CipherSuite::TLS_SRP_SHA_DSS_WITH_AES_256_CBC_SHA => Some("SRP-DSS-AES-256-CBC-SHA"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256 => {
-            Some("ECDH-ECDSA-AES128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384 => {
-            Some("ECDH-ECDSA-AES256-SHA384")
-        }
-        super::CipherSuite::TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256 => Some("ECDH-RSA-AES128-SHA256"),
-        super::CipherSuite::TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384 => Some("ECDH-RSA-AES256-SHA384"),
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256 => {
-            Some("ECDH-ECDSA-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384 => {
-            Some("ECDH-ECDSA-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256 => {
-            Some("ECDH-RSA-AES128-GCM-SHA256")
-        }
-        super::CipherSuite::TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384 => {
-            Some("ECDH-RSA-AES256-GCM-SHA384")
-        }
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("ECDH-ECDSA-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("ECDH-ECDSA-CAMELLIA256-SHA384")
-        }
-        super::CipherSuite::TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256 => {
-            Some("ECDH-RSA-CAMELLIA128-SHA256")
-        }
-        super::CipherSuite::TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384 => {
-            Some("ECDH-RSA-CAMELLIA256-SHA384")
+macro_rules! openssl_cipher_mapping {
+    ($($suite:ident => $name:literal),+ $(,)?) => {
+        fn openssl_cipher_str_from_cipher_suite(suite: super::CipherSuite) -> Option<&'static str> {
+            match suite {
+                $(super::CipherSuite::$suite => Some($name),)+
+                other => {
+                    trace!("openssl_cipher_str_from_cipher_suite: ignore cipher suite: {other}");
+                    None
+                }
+            }
         }
-        other => {
-            trace!(
-                "openssl_cipher_str_From_cipher_suite: ignore cipher s
-        uite: {other}"
-            );
-            None
+
+        /// Return the [`CipherSuite`] for the given openssl cipher name,
+        /// e.g. as returned by `SslCipherRef::name` for the current cipher of a connection.
+        ///
+        /// This is the inverse of the mapping used by [`openssl_cipher_list_str_from_cipher_list`].
+        ///
+        /// [`CipherSuite`]: super::CipherSuite
+        pub fn cipher_suite_from_openssl_cipher_str(name: &str) -> Option<super::CipherSuite> {
+            match name {
+                $($name => Some(super::CipherSuite::$suite),)+
+                _ => None,
+            }
         }
-    }
+    };
+}
+
+openssl_cipher_mapping! {
+    TLS_RSA_WITH_NULL_MD5 => "NULL-MD5",
+    TLS_RSA_WITH_NULL_SHA => "NULL-SHA",
+    TLS_RSA_WITH_RC4_128_MD5 => "RC4-MD5",
+    TLS_RSA_WITH_RC4_128_SHA => "RC4-SHA",
+    TLS_RSA_WITH_IDEA_CBC_SHA => "IDEA-CBC-SHA",
+    TLS_RSA_WITH_3DES_EDE_CBC_SHA => "DES-CBC3-SHA",
+    TLS_DH_DSS_WITH_3DES_EDE_CBC_SHA => "DH-DSS-DES-CBC3-SHA",
+    TLS_DH_RSA_WITH_3DES_EDE_CBC_SHA => "DH-RSA-DES-CBC3-SHA",
+    TLS_DHE_DSS_WITH_3DES_EDE_CBC_SHA => "DHE-DSS-DES-CBC3-SHA",
+    TLS_DHE_RSA_WITH_3DES_EDE_CBC_SHA => "DHE-RSA-DES-CBC3-SHA",
+    TLS_DH_anon_WITH_RC4_128_MD5 => "ADH-RC4-MD5",
+    TLS_DH_anon_WITH_3DES_EDE_CBC_SHA => "ADH-DES-CBC3-SHA",
+    TLS_RSA_WITH_AES_128_CBC_SHA => "AES128-SHA",
+    TLS_RSA_WITH_AES_256_CBC_SHA => "AES256-SHA",
+    TLS_DH_DSS_WITH_AES_128_CBC_SHA => "DH-DSS-AES128-SHA",
+    TLS_DH_DSS_WITH_AES_256_CBC_SHA => "DH-DSS-AES256-SHA",
+    TLS_DH_RSA_WITH_AES_128_CBC_SHA => "DH-RSA-AES128-SHA",
+    TLS_DH_RSA_WITH_AES_256_CBC_SHA => "DH-RSA-AES256-SHA",
+    TLS_DHE_DSS_WITH_AES_128_CBC_SHA => "DHE-DSS-AES128-SHA",
+    TLS_DHE_DSS_WITH_AES_256_CBC_SHA => "DHE-DSS-AES256-SHA",
+    TLS_DHE_RSA_WITH_AES_128_CBC_SHA => "DHE-RSA-AES128-SHA",
+    TLS_DHE_RSA_WITH_AES_256_CBC_SHA => "DHE-RSA-AES256-SHA",
+    TLS_DH_anon_WITH_AES_128_CBC_SHA => "ADH-AES128-SHA",
+    TLS_DH_anon_WITH_AES_256_CBC_SHA => "ADH-AES256-SHA",
+    TLS_RSA_WITH_CAMELLIA_128_CBC_SHA => "CAMELLIA128-SHA",
+    TLS_RSA_WITH_CAMELLIA_256_CBC_SHA => "CAMELLIA256-SHA",
+    TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA => "DH-DSS-CAMELLIA128-SHA",
+    TLS_DH_DSS_WITH_CAMELLIA_256_CBC_SHA => "DH-DSS-CAMELLIA256-SHA",
+    TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA => "DH-RSA-CAMELLIA128-SHA",
+    TLS_DH_RSA_WITH_CAMELLIA_256_CBC_SHA => "DH-RSA-CAMELLIA256-SHA",
+    TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA => "DHE-DSS-CAMELLIA128-SHA",
+    TLS_DHE_DSS_WITH_CAMELLIA_256_CBC_SHA => "DHE-DSS-CAMELLIA256-SHA",
+    TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA => "DHE-RSA-CAMELLIA128-SHA",
+    TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA => "DHE-RSA-CAMELLIA256-SHA",
+    TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA => "ADH-CAMELLIA128-SHA",
+    TLS_DH_anon_WITH_CAMELLIA_256_CBC_SHA => "ADH-CAMELLIA256-SHA",
+    TLS_RSA_WITH_SEED_CBC_SHA => "SEED-SHA",
+    TLS_DH_DSS_WITH_SEED_CBC_SHA => "DH-DSS-SEED-SHA",
+    TLS_DH_RSA_WITH_SEED_CBC_SHA => "DH-RSA-SEED-SHA",
+    TLS_DHE_DSS_WITH_SEED_CBC_SHA => "DHE-DSS-SEED-SHA",
+    TLS_DHE_RSA_WITH_SEED_CBC_SHA => "DHE-RSA-SEED-SHA",
+    TLS_DH_anon_WITH_SEED_CBC_SHA => "ADH-SEED-SHA",
+    TLS_GOSTR341094_WITH_28147_CNT_IMIT => "GOST94-GOST89-GOST89",
+    TLS_GOSTR341001_WITH_28147_CNT_IMIT => "GOST2001-GOST89-GOST89",
+    TLS_GOSTR341094_WITH_NULL_GOSTR3411 => "GOST94-NULL-GOST94",
+    TLS_GOSTR341001_WITH_NULL_GOSTR3411 => "GOST2001-NULL-GOST94",
+    TLS_DHE_DSS_WITH_RC4_128_SHA => "DHE-DSS-RC4-SHA",
+    TLS_ECDHE_RSA_WITH_NULL_SHA => "ECDHE-RSA-NULL-SHA",
+    TLS_ECDHE_RSA_WITH_RC4_128_SHA => "ECDHE-RSA-RC4-SHA",
+    TLS_ECDHE_RSA_WITH_3DES_EDE_CBC_SHA => "ECDHE-RSA-DES-CBC3-SHA",
+    TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA => "ECDHE-RSA-AES128-SHA",
+    TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA => "ECDHE-RSA-AES256-SHA",
+    TLS_ECDHE_ECDSA_WITH_NULL_SHA => "ECDHE-ECDSA-NULL-SHA",
+    TLS_ECDHE_ECDSA_WITH_RC4_128_SHA => "ECDHE-ECDSA-RC4-SHA",
+    TLS_ECDHE_ECDSA_WITH_3DES_EDE_CBC_SHA => "ECDHE-ECDSA-DES-CBC3-SHA",
+    TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA => "ECDHE-ECDSA-AES128-SHA",
+    TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA => "ECDHE-ECDSA-AES256-SHA",
+    TLS_ECDH_anon_WITH_NULL_SHA => "AECDH-NULL-SHA",
+    TLS_ECDH_anon_WITH_RC4_128_SHA => "AECDH-RC4-SHA",
+    TLS_ECDH_anon_WITH_3DES_EDE_CBC_SHA => "AECDH-DES-CBC3-SHA",
+    TLS_ECDH_anon_WITH_AES_128_CBC_SHA => "AECDH-AES128-SHA",
+    TLS_ECDH_anon_WITH_AES_256_CBC_SHA => "AECDH-AES256-SHA",
+    TLS_RSA_WITH_NULL_SHA256 => "NULL-SHA256",
+    TLS_RSA_WITH_AES_128_CBC_SHA256 => "AES128-SHA256",
+    TLS_RSA_WITH_AES_256_CBC_SHA256 => "AES256-SHA256",
+    TLS_RSA_WITH_AES_128_GCM_SHA256 => "AES128-GCM-SHA256",
+    TLS_RSA_WITH_AES_256_GCM_SHA384 => "AES256-GCM-SHA384",
+    TLS_DH_RSA_WITH_AES_128_CBC_SHA256 => "DH-RSA-AES128-SHA256",
+    TLS_DH_RSA_WITH_AES_256_CBC_SHA256 => "DH-RSA-AES256-SHA256",
+    TLS_DH_RSA_WITH_AES_128_GCM_SHA256 => "DH-RSA-AES128-GCM-SHA256",
+    TLS_DH_RSA_WITH_AES_256_GCM_SHA384 => "DH-RSA-AES256-GCM-SHA384",
+    TLS_DH_DSS_WITH_AES_128_CBC_SHA256 => "DH-DSS-AES128-SHA256",
+    TLS_DH_DSS_WITH_AES_256_CBC_SHA256 => "DH-DSS-AES256-SHA256",
+    TLS_DH_DSS_WITH_AES_128_GCM_SHA256 => "DH-DSS-AES128-GCM-SHA256",
+    TLS_DH_DSS_WITH_AES_256_GCM_SHA384 => "DH-DSS-AES256-GCM-SHA384",
+    TLS_DHE_RSA_WITH_AES_128_CBC_SHA256 => "DHE-RSA-AES128-SHA256",
+    TLS_DHE_RSA_WITH_AES_256_CBC_SHA256 => "DHE-RSA-AES256-SHA256",
+    TLS_DHE_RSA_WITH_AES_128_GCM_SHA256 => "DHE-RSA-AES128-GCM-SHA256",
+    TLS_DHE_RSA_WITH_AES_256_GCM_SHA384 => "DHE-RSA-AES256-GCM-SHA384",
+    TLS_DHE_DSS_WITH_AES_128_CBC_SHA256 => "DHE-DSS-AES128-SHA256",
+    TLS_DHE_DSS_WITH_AES_256_CBC_SHA256 => "DHE-DSS-AES256-SHA256",
+    TLS_DHE_DSS_WITH_AES_128_GCM_SHA256 => "DHE-DSS-AES128-GCM-SHA256",
+    TLS_DHE_DSS_WITH_AES_256_GCM_SHA384 => "DHE-DSS-AES256-GCM-SHA384",
+    TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256 => "ECDHE-RSA-AES128-SHA256",
+    TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384 => "ECDHE-RSA-AES256-SHA384",
+    TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256 => "ECDHE-RSA-AES128-GCM-SHA256",
+    TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384 => "ECDHE-RSA-AES256-GCM-SHA384",
+    TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256 => "ECDHE-ECDSA-AES128-SHA256",
+    TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384 => "ECDHE-ECDSA-AES256-SHA384",
+    TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256 => "ECDHE-ECDSA-AES128-GCM-SHA256",
+    TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384 => "ECDHE-ECDSA-AES256-GCM-SHA384",
+    TLS_DH_anon_WITH_AES_128_CBC_SHA256 => "ADH-AES128-SHA256",
+    TLS_DH_anon_WITH_AES_256_CBC_SHA256 => "ADH-AES256-SHA256",
+    TLS_DH_anon_WITH_AES_128_GCM_SHA256 => "ADH-AES128-GCM-SHA256",
+    TLS_DH_anon_WITH_AES_256_GCM_SHA384 => "ADH-AES256-GCM-SHA384",
+    TLS_RSA_WITH_AES_128_CCM => "AES128-CCM",
+    TLS_RSA_WITH_AES_256_CCM => "AES256-CCM",
+    TLS_DHE_RSA_WITH_AES_128_CCM => "DHE-RSA-AES128-CCM",
+    TLS_DHE_RSA_WITH_AES_256_CCM => "DHE-RSA-AES256-CCM",
+    TLS_RSA_WITH_AES_128_CCM_8 => "AES128-CCM8",
+    TLS_RSA_WITH_AES_256_CCM_8 => "AES256-CCM8",
+    TLS_DHE_RSA_WITH_AES_128_CCM_8 => "DHE-RSA-AES128-CCM8",
+    TLS_DHE_RSA_WITH_AES_256_CCM_8 => "DHE-RSA-AES256-CCM8",
+    TLS_ECDHE_ECDSA_WITH_AES_128_CCM => "ECDHE-ECDSA-AES128-CCM",
+    TLS_ECDHE_ECDSA_WITH_AES_256_CCM => "ECDHE-ECDSA-AES256-CCM",
+    TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8 => "ECDHE-ECDSA-AES128-CCM8",
+    TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8 => "ECDHE-ECDSA-AES256-CCM8",
+    TLS_RSA_WITH_ARIA_128_GCM_SHA256 => "ARIA128-GCM-SHA256",
+    TLS_RSA_WITH_ARIA_256_GCM_SHA384 => "ARIA256-GCM-SHA384",
+    TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256 => "DHE-RSA-ARIA128-GCM-SHA256",
+    TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384 => "DHE-RSA-ARIA256-GCM-SHA384",
+    TLS_DHE_DSS_WITH_ARIA_128_GCM_SHA256 => "DHE-DSS-ARIA128-GCM-SHA256",
+    TLS_DHE_DSS_WITH_ARIA_256_GCM_SHA384 => "DHE-DSS-ARIA256-GCM-SHA384",
+    TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256 => "ECDHE-ECDSA-ARIA128-GCM-SHA256",
+    TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384 => "ECDHE-ECDSA-ARIA256-GCM-SHA384",
+    TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256 => "ECDHE-ARIA128-GCM-SHA256",
+    TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384 => "ECDHE-ARIA256-GCM-SHA384",
+    TLS_PSK_WITH_ARIA_128_GCM_SHA256 => "PSK-ARIA128-GCM-SHA256",
+    TLS_PSK_WITH_ARIA_256_GCM_SHA384 => "PSK-ARIA256-GCM-SHA384",
+    TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256 => "DHE-PSK-ARIA128-GCM-SHA256",
+    TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384 => "DHE-PSK-ARIA256-GCM-SHA384",
+    TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256 => "RSA-PSK-ARIA128-GCM-SHA256",
+    TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384 => "RSA-PSK-ARIA256-GCM-SHA384",
+    TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256 => "ECDHE-ECDSA-CAMELLIA128-SHA256",
+    TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384 => "ECDHE-ECDSA-CAMELLIA256-SHA384",
+    TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256 => "ECDHE-RSA-CAMELLIA128-SHA256",
+    TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384 => "ECDHE-RSA-CAMELLIA256-SHA384",
+    TLS_PSK_WITH_NULL_SHA => "PSK-NULL-SHA",
+    TLS_DHE_PSK_WITH_NULL_SHA => "DHE-PSK-NULL-SHA",
+    TLS_RSA_PSK_WITH_NULL_SHA => "RSA-PSK-NULL-SHA",
+    TLS_PSK_WITH_RC4_128_SHA => "PSK-RC4-SHA",
+    TLS_PSK_WITH_3DES_EDE_CBC_SHA => "PSK-3DES-EDE-CBC-SHA",
+    TLS_PSK_WITH_AES_128_CBC_SHA => "PSK-AES128-CBC-SHA",
+    TLS_PSK_WITH_AES_256_CBC_SHA => "PSK-AES256-CBC-SHA",
+    TLS_DHE_PSK_WITH_RC4_128_SHA => "DHE-PSK-RC4-SHA",
+    TLS_DHE_PSK_WITH_3DES_EDE_CBC_SHA => "DHE-PSK-3DES-EDE-CBC-SHA",
+    TLS_DHE_PSK_WITH_AES_128_CBC_SHA => "DHE-PSK-AES128-CBC-SHA",
+    TLS_DHE_PSK_WITH_AES_256_CBC_SHA => "DHE-PSK-AES256-CBC-SHA",
+    TLS_RSA_PSK_WITH_RC4_128_SHA => "RSA-PSK-RC4-SHA",
+    TLS_RSA_PSK_WITH_3DES_EDE_CBC_SHA => "RSA-PSK-3DES-EDE-CBC-SHA",
+    TLS_RSA_PSK_WITH_AES_128_CBC_SHA => "RSA-PSK-AES128-CBC-SHA",
+    TLS_RSA_PSK_WITH_AES_256_CBC_SHA => "RSA-PSK-AES256-CBC-SHA",
+    TLS_PSK_WITH_AES_128_GCM_SHA256 => "PSK-AES128-GCM-SHA256",
+    TLS_PSK_WITH_AES_256_GCM_SHA384 => "PSK-AES256-GCM-SHA384",
+    TLS_DHE_PSK_WITH_AES_128_GCM_SHA256 => "DHE-PSK-AES128-GCM-SHA256",
+    TLS_DHE_PSK_WITH_AES_256_GCM_SHA384 => "DHE-PSK-AES256-GCM-SHA384",
+    TLS_RSA_PSK_WITH_AES_128_GCM_SHA256 => "RSA-PSK-AES128-GCM-SHA256",
+    TLS_RSA_PSK_WITH_AES_256_GCM_SHA384 => "RSA-PSK-AES256-GCM-SHA384",
+    TLS_PSK_WITH_AES_128_CBC_SHA256 => "PSK-AES128-CBC-SHA256",
+    TLS_PSK_WITH_AES_256_CBC_SHA384 => "PSK-AES256-CBC-SHA384",
+    TLS_PSK_WITH_NULL_SHA256 => "PSK-NULL-SHA256",
+    TLS_PSK_WITH_NULL_SHA384 => "PSK-NULL-SHA384",
+    TLS_DHE_PSK_WITH_AES_128_CBC_SHA256 => "DHE-PSK-AES128-CBC-SHA256",
+    TLS_DHE_PSK_WITH_AES_256_CBC_SHA384 => "DHE-PSK-AES256-CBC-SHA384",
+    TLS_DHE_PSK_WITH_NULL_SHA256 => "DHE-PSK-NULL-SHA256",
+    TLS_DHE_PSK_WITH_NULL_SHA384 => "DHE-PSK-NULL-SHA384",
+    TLS_RSA_PSK_WITH_AES_128_CBC_SHA256 => "RSA-PSK-AES128-CBC-SHA256",
+    TLS_RSA_PSK_WITH_AES_256_CBC_SHA384 => "RSA-PSK-AES256-CBC-SHA384",
+    TLS_RSA_PSK_WITH_NULL_SHA256 => "RSA-PSK-NULL-SHA256",
+    TLS_RSA_PSK_WITH_NULL_SHA384 => "RSA-PSK-NULL-SHA384",
+    TLS_ECDHE_PSK_WITH_RC4_128_SHA => "ECDHE-PSK-RC4-SHA",
+    TLS_ECDHE_PSK_WITH_3DES_EDE_CBC_SHA => "ECDHE-PSK-3DES-EDE-CBC-SHA",
+    TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA => "ECDHE-PSK-AES128-CBC-SHA",
+    TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA => "ECDHE-PSK-AES256-CBC-SHA",
+    TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256 => "ECDHE-PSK-AES128-CBC-SHA256",
+    TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384 => "ECDHE-PSK-AES256-CBC-SHA384",
+    TLS_ECDHE_PSK_WITH_NULL_SHA => "ECDHE-PSK-NULL-SHA",
+    TLS_ECDHE_PSK_WITH_NULL_SHA256 => "ECDHE-PSK-NULL-SHA256",
+    TLS_ECDHE_PSK_WITH_NULL_SHA384 => "ECDHE-PSK-NULL-SHA384",
+    TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256 => "PSK-CAMELLIA128-SHA256",
+    TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384 => "PSK-CAMELLIA256-SHA384",
+    TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256 => "DHE-PSK-CAMELLIA128-SHA256",
+    TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384 => "DHE-PSK-CAMELLIA256-SHA384",
+    TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256 => "RSA-PSK-CAMELLIA128-SHA256",
+    TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384 => "RSA-PSK-CAMELLIA256-SHA384",
+    TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256 => "ECDHE-PSK-CAMELLIA128-SHA256",
+    TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384 => "ECDHE-PSK-CAMELLIA256-SHA384",
+    TLS_PSK_WITH_AES_128_CCM => "PSK-AES128-CCM",
+    TLS_PSK_WITH_AES_256_CCM => "PSK-AES256-CCM",
+    TLS_DHE_PSK_WITH_AES_128_CCM => "DHE-PSK-AES128-CCM",
+    TLS_DHE_PSK_WITH_AES_256_CCM => "DHE-PSK-AES256-CCM",
+    TLS_PSK_WITH_AES_128_CCM_8 => "PSK-AES128-CCM8",
+    TLS_PSK_WITH_AES_256_CCM_8 => "PSK-AES256-CCM8",
+    TLS_PSK_DHE_WITH_AES_128_CCM_8 => "DHE-PSK-AES128-CCM8",
+    TLS_PSK_DHE_WITH_AES_256_CCM_8 => "DHE-PSK-AES256-CCM8",
+    TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256 => "ECDHE-RSA-CHACHA20-POLY1305",
+    TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256 => "ECDHE-ECDSA-CHACHA20-POLY1305",
+    TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256 => "DHE-RSA-CHACHA20-POLY1305",
+    TLS_PSK_WITH_CHACHA20_POLY1305_SHA256 => "PSK-CHACHA20-POLY1305",
+    TLS_ECDHE_PSK_WITH_CHACHA20_POLY1305_SHA256 => "ECDHE-PSK-CHACHA20-POLY1305",
+    TLS_DHE_PSK_WITH_CHACHA20_POLY1305_SHA256 => "DHE-PSK-CHACHA20-POLY1305",
+    TLS_RSA_PSK_WITH_CHACHA20_POLY1305_SHA256 => "RSA-PSK-CHACHA20-POLY1305",
+    TLS13_AES_128_GCM_SHA256 => "TLS_AES_128_GCM_SHA256",
+    TLS13_AES_256_GCM_SHA384 => "TLS_AES_256_GCM_SHA384",
+    TLS13_CHACHA20_POLY1305_SHA256 => "TLS_CHACHA20_POLY1305_SHA256",
+    TLS13_AES_128_CCM_SHA256 => "TLS_AES_128_CCM_SHA256",
+    TLS13_AES_128_CCM_8_SHA256 => "TLS_AES_128_CCM_8_SHA256",
+    TLS_NULL_WITH_NULL_NULL => "NULL",
+    TLS_RSA_EXPORT_WITH_RC4_40_MD5 => "EXP-RC4-MD5",
+    TLS_RSA_EXPORT_WITH_RC2_CBC_40_MD5 => "EXP-RC2-CBC-MD5",
+    TLS_RSA_EXPORT_WITH_DES40_CBC_SHA => "EXP-DES-CBC-SHA",
+    TLS_RSA_WITH_DES_CBC_SHA => "DES-CBC-SHA",
+    TLS_DH_DSS_EXPORT_WITH_DES40_CBC_SHA => "EXP-DH-DSS-DES-CBC-SHA",
+    TLS_DH_DSS_WITH_DES_CBC_SHA => "DH-DSS-DES-CBC-SHA",
+    TLS_DH_RSA_EXPORT_WITH_DES40_CBC_SHA => "EXP-DH-RSA-DES-CBC-SHA",
+    TLS_DH_RSA_WITH_DES_CBC_SHA => "DH-RSA-DES-CBC-SHA	",
+    TLS_DHE_DSS_EXPORT_WITH_DES40_CBC_SHA => "EXP-EDH-DSS-DES-CBC-SHA",
+    TLS_DHE_DSS_WITH_DES_CBC_SHA => "EDH-DSS-DES-CBC-SHA",
+    TLS_DHE_RSA_EXPORT_WITH_DES40_CBC_SHA => "EXP-EDH-RSA-DES-CBC-SHA",
+    TLS_DHE_RSA_WITH_DES_CBC_SHA => "EDH-RSA-DES-CBC-SHA",
+    TLS_DH_anon_EXPORT_WITH_RC4_40_MD5 => "EXP-ADH-RC4-MD5",
+    TLS_DH_anon_EXPORT_WITH_DES40_CBC_SHA => "EXP-ADH-DES-CBC-SHA",
+    TLS_DH_anon_WITH_DES_CBC_SHA => "ADH-DES-CBC-SHA",
+    TLS_KRB5_WITH_DES_CBC_SHA_or_SSL_FORTEZZA_KEA_WITH_RC4_128_SHA => "KRB5-DES-CBC-SHA",
+    TLS_KRB5_WITH_3DES_EDE_CBC_SHA => "KRB5-DES-CBC3-SHA",
+    TLS_KRB5_WITH_RC4_128_SHA => "KRB5-RC4-SHA",
+    TLS_KRB5_WITH_IDEA_CBC_SHA => "KRB5-IDEA-CBC-SHA",
+    TLS_KRB5_WITH_DES_CBC_MD5 => "KRB5-DES-CBC-MD5",
+    TLS_KRB5_WITH_3DES_EDE_CBC_MD5 => "KRB5-DES-CBC3-MD5",
+    TLS_KRB5_WITH_RC4_128_MD5 => "KRB5-RC4-MD5",
+    TLS_KRB5_WITH_IDEA_CBC_MD5 => "KRB5-IDEA-CBC-MD5",
+    TLS_KRB5_EXPORT_WITH_DES_CBC_40_SHA => "EXP-KRB5-DES-CBC-SHA",
+    TLS_KRB5_EXPORT_WITH_RC2_CBC_40_SHA => "EXP-KRB5-RC2-CBC-SHA",
+    TLS_KRB5_EXPORT_WITH_RC4_40_SHA => "EXP-KRB5-RC4-SHA",
+    TLS_KRB5_EXPORT_WITH_DES_CBC_40_MD5 => "EXP-KRB5-DES-CBC-MD5",
+    TLS_KRB5_EXPORT_WITH_RC2_CBC_40_MD5 => "EXP-KRB5-RC2-CBC-MD5",
+    TLS_KRB5_EXPORT_WITH_RC4_40_MD5 => "EXP-KRB5-RC4-MD5",
+    TLS_RSA_EXPORT1024_WITH_RC4_56_MD5 => "EXP1024-RC4-MD5",
+    TLS_RSA_EXPORT1024_WITH_RC2_CBC_56_MD5 => "EXP1024-RC2-CBC-MD5",
+    TLS_RSA_EXPORT1024_WITH_DES_CBC_SHA => "EXP1024-DES-CBC-SHA",
+    TLS_DHE_DSS_EXPORT1024_WITH_DES_CBC_SHA => "EXP1024-DHE-DSS-DES-CBC-SHA",
+    TLS_RSA_EXPORT1024_WITH_RC4_56_SHA => "EXP1024-RC4-SHA",
+    TLS_DHE_DSS_EXPORT1024_WITH_RC4_56_SHA => "EXP1024-DHE-DSS-RC4-SHA",
+    TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256 => "CAMELLIA256-SHA256",
+    TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256 => "CAMELLIA128-SHA256",
+    TLS_DH_DSS_WITH_CAMELLIA_128_CBC_SHA256 => "DH-DSS-CAMELLIA128-SHA256",
+    TLS_DH_RSA_WITH_CAMELLIA_128_CBC_SHA256 => "DH-RSA-CAMELLIA128-SHA256",
+    TLS_DHE_DSS_WITH_CAMELLIA_128_CBC_SHA256 => "DHE-DSS-CAMELLIA128-SHA256",
+    TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256 => "DHE-RSA-CAMELLIA128-SHA256",
+    TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256 => "DHE-RSA-CAMELLIA256-SHA256",
+    TLS_DH_anon_WITH_CAMELLIA_128_CBC_SHA256 => "ADH-CAMELLIA128-SHA256",
+    TLS_EMPTY_RENEGOTIATION_INFO_SCSV => "TLS_FALLBACK_SCSV",
+    TLS_ECDH_ECDSA_WITH_NULL_SHA => "ECDH-ECDSA-NULL-SHA",
+    TLS_ECDH_ECDSA_WITH_RC4_128_SHA => "ECDH-ECDSA-RC4-SHA",
+    TLS_ECDH_ECDSA_WITH_3DES_EDE_CBC_SHA => "ECDH-ECDSA-DES-CBC3-SHA",
+    TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA => "ECDH-ECDSA-AES128-SHA",
+    TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA => "ECDH-ECDSA-AES256-SHA",
+    TLS_ECDH_RSA_WITH_NULL_SHA => "ECDH-RSA-NULL-SHA",
+    TLS_ECDH_RSA_WITH_RC4_128_SHA => "ECDH-RSA-RC4-SHA",
+    TLS_ECDH_RSA_WITH_3DES_EDE_CBC_SHA => "ECDH-RSA-DES-CBC3-SHA",
+    TLS_ECDH_RSA_WITH_AES_128_CBC_SHA => "ECDH-RSA-AES128-SHA",
+    TLS_ECDH_RSA_WITH_AES_256_CBC_SHA => "ECDH-RSA-AES256-SHA",
+    TLS_SRP_SHA_WITH_3DES_EDE_CBC_SHA => "SRP-3DES-EDE-CBC-SHA",
+    TLS_SRP_SHA_RSA_WITH_3DES_EDE_CBC_SHA => "SRP-RSA-3DES-EDE-CBC-SHA",
+    TLS_SRP_SHA_DSS_WITH_3DES_EDE_CBC_SHA => "SRP-DSS-3DES-EDE-CBC-SHA",
+    TLS_SRP_SHA_WITH_AES_128_CBC_SHA => "SRP-AES-128-CBC-SHA",
+    TLS_SRP_SHA_RSA_WITH_AES_128_CBC_SHA => "SRP-RSA-AES-128-CBC-SHA",
+    TLS_SRP_SHA_DSS_WITH_AES_128_CBC_SHA => "SRP-DSS-AES-128-CBC-SHA",
+    TLS_SRP_SHA_WITH_AES_256_CBC_SHA => "SRP-AES-256-CBC-SHA",
+    TLS_SRP_SHA_RSA_WITH_AES_256_CBC_SHA => "SRP-RSA-AES-256-CBC-SHA",
+    TLS_SRP_SHA_DSS_WITH_AES_256_CBC_SHA => "SRP-DSS-AES-256-CBC-SHA",
+    TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256 => "ECDH-ECDSA-AES128-SHA256",
+    TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384 => "ECDH-ECDSA-AES256-SHA384",
+    TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256 => "ECDH-RSA-AES128-SHA256",
+    TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384 => "ECDH-RSA-AES256-SHA384",
+    TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256 => "ECDH-ECDSA-AES128-GCM-SHA256",
+    TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384 => "ECDH-ECDSA-AES256-GCM-SHA384",
+    TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256 => "ECDH-RSA-AES128-GCM-SHA256",
+    TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384 => "ECDH-RSA-AES256-GCM-SHA384",
+    TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256 => "ECDH-ECDSA-CAMELLIA128-SHA256",
+    TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384 => "ECDH-ECDSA-CAMELLIA256-SHA384",
+    TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256 => "ECDH-RSA-CAMELLIA128-SHA256",
+    TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384 => "ECDH-RSA-CAMELLIA256-SHA384",
 }
@@ -11,7 +11,7 @@ mod rustls;
 mod boring;
 #[cfg(feature = "boring")]
 #[doc(inline)]
-pub use boring::openssl_cipher_list_str_from_cipher_list;
+pub use boring::{cipher_suite_from_openssl_cipher_str, openssl_cipher_list_str_from_cipher_list};
 
 enum_builder! {
     /// The `ProtocolVersion` TLS protocol enum.  Values in this enum are taken
@@ -734,6 +734,18 @@ enum_builder! {
     }
 }
 
+enum_builder! {
+    /// Key encapsulation mechanism (KEM) used in hybrid public key encryption
+    @U16
+    pub enum KeyEncapsulationMechanism {
+        DHKEM_P256_HKDF_SHA256 => 0x0010,
+        DHKEM_P384_HKDF_SHA384 => 0x0011,
+        DHKEM_P521_HKDF_SHA512 => 0x0012,
+        DHKEM_X25519_HKDF_SHA256 => 0x0020,
+        DHKEM_X448_HKDF_SHA512 => 0x0021,
+    }
+}
+
 enum_builder! {
     /// Authenticated encryption with associated data (AEAD) used in hybrid public key encryption
     @U16
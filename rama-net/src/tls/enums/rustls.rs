@@ -22,6 +22,20 @@ macro_rules! enum_from_rustls {
 
 enum_from_rustls!(u16 => ProtocolVersion, CipherSuite, SignatureScheme);
 
+impl From<rustls::NamedGroup> for super::SupportedGroup {
+    fn from(value: rustls::NamedGroup) -> Self {
+        let n: u16 = value.into();
+        n.into()
+    }
+}
+
+impl From<super::SupportedGroup> for rustls::NamedGroup {
+    fn from(value: super::SupportedGroup) -> Self {
+        let n: u16 = value.into();
+        n.into()
+    }
+}
+
 impl TryFrom<super::ProtocolVersion> for &rustls::SupportedProtocolVersion {
     type Error = super::ProtocolVersion;
 
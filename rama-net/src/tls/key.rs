@@ -0,0 +1,208 @@
+//! Parsing of private keys, as far as required to check
+//! whether or not a private key belongs to a certificate.
+//!
+//! No cryptography is involved: the public key is only
+//! taken from the private key in case it is embedded in it,
+//! which is the case for RSA and EC keys, as well as for
+//! PKCS#8 v2 keys of any other type.
+
+use super::ocsp::{read_der, spki_parts};
+use x509_parser::asn1_rs::{Class, FromDer, Oid, Tag};
+
+const OID_RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const OID_EC_PUBLIC_KEY: &str = "1.2.840.10045.2.1";
+
+const LABEL_PKCS8_PRIVATE_KEY: &str = "PRIVATE KEY";
+const LABEL_RSA_PRIVATE_KEY: &str = "RSA PRIVATE KEY";
+const LABEL_EC_PRIVATE_KEY: &str = "EC PRIVATE KEY";
+
+#[derive(Debug, PartialEq, Eq)]
+/// Public key as embedded in a private key.
+enum PublicKey<'a> {
+    Rsa {
+        modulus: &'a [u8],
+        public_exponent: &'a [u8],
+    },
+    /// Content of the `subjectPublicKey` bit string, e.g. an EC point
+    Raw(&'a [u8]),
+}
+
+/// Check whether or not the given DER-encoded private key, of the given PEM label,
+/// belongs to the given DER-encoded SubjectPublicKeyInfo (e.g. of a leaf cert).
+///
+/// Returns `None` in case this cannot be determined, which is the case
+/// for keys of unknown types and for keys which do not embed their public key.
+pub(crate) fn private_key_matches_spki(
+    label: &str,
+    private_key: &[u8],
+    subject_public_key_info: &[u8],
+) -> Option<bool> {
+    let (algorithm, public_key) = spki_parts(subject_public_key_info)?;
+    let (_, algorithm) = Oid::from_der(algorithm).ok()?;
+    let algorithm = algorithm.to_id_string();
+
+    let private_key_public_key = match label {
+        LABEL_PKCS8_PRIVATE_KEY => pkcs8_public_key(private_key, &algorithm)?,
+        LABEL_RSA_PRIVATE_KEY if algorithm == OID_RSA_ENCRYPTION => rsa_public_key(private_key)?,
+        LABEL_EC_PRIVATE_KEY if algorithm == OID_EC_PUBLIC_KEY => ec_public_key(private_key)?,
+        LABEL_RSA_PRIVATE_KEY | LABEL_EC_PRIVATE_KEY => return Some(false),
+        _ => return None,
+    };
+
+    let public_key = if algorithm == OID_RSA_ENCRYPTION {
+        let (_, public_key, _) = read_der(public_key, Class::Universal, Tag::Sequence)?;
+        let (_, modulus, rest) = read_der(public_key, Class::Universal, Tag::Integer)?;
+        let (_, public_exponent, _) = read_der(rest, Class::Universal, Tag::Integer)?;
+        PublicKey::Rsa {
+            modulus,
+            public_exponent,
+        }
+    } else {
+        PublicKey::Raw(public_key)
+    };
+    Some(private_key_public_key == public_key)
+}
+
+/// Public key of a PKCS#8 private key.
+///
+/// ```text
+/// OneAsymmetricKey ::= SEQUENCE {
+///     version                   Version,
+///     privateKeyAlgorithm       PrivateKeyAlgorithmIdentifier,
+///     privateKey                PrivateKey,
+///     attributes            [0] Attributes OPTIONAL,
+///     ...,
+///     [[2: publicKey        [1] PublicKey OPTIONAL ]],
+///     ... }
+/// ```
+///
+/// Returns a public key which never matches
+/// in case the algorithm differs from the given (expected) one.
+fn pkcs8_public_key<'a>(private_key: &'a [u8], algorithm: &str) -> Option<PublicKey<'a>> {
+    let (_, private_key, _) = read_der(private_key, Class::Universal, Tag::Sequence)?;
+    let (_, _, rest) = read_der(private_key, Class::Universal, Tag::Integer)?;
+    let (_, private_key_algorithm, rest) = read_der(rest, Class::Universal, Tag::Sequence)?;
+    let (_, private_key_algorithm) = Oid::from_der(private_key_algorithm).ok()?;
+    if private_key_algorithm.to_id_string() != algorithm {
+        return Some(PublicKey::Raw(&[]));
+    }
+    let (_, private_key, mut rest) = read_der(rest, Class::Universal, Tag::OctetString)?;
+
+    match algorithm {
+        OID_RSA_ENCRYPTION => rsa_public_key(private_key),
+        OID_EC_PUBLIC_KEY => ec_public_key(private_key),
+        _ => {
+            if let Some((_, _, next)) = read_der(rest, Class::ContextSpecific, Tag(0)) {
+                rest = next;
+            }
+            let (_, public_key, _) = read_der(rest, Class::ContextSpecific, Tag(1))?;
+            // first byte is the number of unused bits in the last byte
+            let (_, public_key) = public_key.split_first()?;
+            Some(PublicKey::Raw(public_key))
+        }
+    }
+}
+
+/// Public key of an RSA private key.
+///
+/// ```text
+/// RSAPrivateKey ::= SEQUENCE {
+///     version           Version,
+///     modulus           INTEGER,  -- n
+///     publicExponent    INTEGER,  -- e
+///     ... }
+/// ```
+fn rsa_public_key(private_key: &[u8]) -> Option<PublicKey<'_>> {
+    let (_, private_key, _) = read_der(private_key, Class::Universal, Tag::Sequence)?;
+    let (_, _, rest) = read_der(private_key, Class::Universal, Tag::Integer)?;
+    let (_, modulus, rest) = read_der(rest, Class::Universal, Tag::Integer)?;
+    let (_, public_exponent, _) = read_der(rest, Class::Universal, Tag::Integer)?;
+    Some(PublicKey::Rsa {
+        modulus,
+        public_exponent,
+    })
+}
+
+/// Public key of an EC private key.
+///
+/// ```text
+/// ECPrivateKey ::= SEQUENCE {
+///     version        INTEGER { ecPrivkeyVer1(1) },
+///     privateKey     OCTET STRING,
+///     parameters [0] ECParameters OPTIONAL,
+///     publicKey  [1] BIT STRING OPTIONAL }
+/// ```
+fn ec_public_key(private_key: &[u8]) -> Option<PublicKey<'_>> {
+    let (_, private_key, _) = read_der(private_key, Class::Universal, Tag::Sequence)?;
+    let (_, _, rest) = read_der(private_key, Class::Universal, Tag::Integer)?;
+    let (_, _, mut rest) = read_der(rest, Class::Universal, Tag::OctetString)?;
+    if let Some((_, _, next)) = read_der(rest, Class::ContextSpecific, Tag(0)) {
+        rest = next;
+    }
+    let (_, public_key, _) = read_der(rest, Class::ContextSpecific, Tag(1))?;
+    let (_, public_key, _) = read_der(public_key, Class::Universal, Tag::BitString)?;
+    // first byte is the number of unused bits in the last byte
+    let (_, public_key) = public_key.split_first()?;
+    Some(PublicKey::Raw(public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_key_matches_spki() {
+        for alg in [
+            &rcgen::PKCS_ECDSA_P256_SHA256,
+            &rcgen::PKCS_ECDSA_P384_SHA384,
+            &rcgen::PKCS_ED25519,
+        ] {
+            let key_pair = rcgen::KeyPair::generate_for(alg).unwrap();
+            let other_key_pair = rcgen::KeyPair::generate_for(alg).unwrap();
+            let private_key = key_pair.serialize_der();
+            assert_eq!(
+                private_key_matches_spki("PRIVATE KEY", &private_key, &key_pair.public_key_der()),
+                Some(true),
+                "{alg:?}"
+            );
+            assert_eq!(
+                private_key_matches_spki(
+                    "PRIVATE KEY",
+                    &private_key,
+                    &other_key_pair.public_key_der()
+                ),
+                Some(false),
+                "{alg:?}"
+            );
+        }
+
+        let key_pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_ECDSA_P256_SHA256).unwrap();
+        let rsa_key_pair =
+            rcgen::KeyPair::generate_rsa_for(&rcgen::PKCS_RSA_SHA256, rcgen::RsaKeySize::_2048)
+                .unwrap();
+        assert_eq!(
+            private_key_matches_spki(
+                "PRIVATE KEY",
+                &rsa_key_pair.serialize_der(),
+                &rsa_key_pair.public_key_der()
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            private_key_matches_spki(
+                "PRIVATE KEY",
+                &key_pair.serialize_der(),
+                &rsa_key_pair.public_key_der()
+            ),
+            Some(false)
+        );
+        assert_eq!(
+            private_key_matches_spki(
+                "UNKNOWN PRIVATE KEY",
+                &key_pair.serialize_der(),
+                &key_pair.public_key_der()
+            ),
+            None
+        );
+    }
+}
@@ -4,15 +4,30 @@
 use rama_utils::str::NonEmptyString;
 
 mod enums;
-#[cfg(feature = "boring")]
-pub use enums::openssl_cipher_list_str_from_cipher_list;
 pub use enums::{
-    ApplicationProtocol, CertificateCompressionAlgorithm, CipherSuite, CompressionAlgorithm,
-    ECPointFormat, ExtensionId, ProtocolVersion, SignatureScheme, SupportedGroup,
+    ApplicationProtocol, AuthenticatedEncryptionWithAssociatedData,
+    CertificateCompressionAlgorithm, CipherSuite, CompressionAlgorithm, ECPointFormat, ExtensionId,
+    KeyDerivationFunction, KeyEncapsulationMechanism, ProtocolVersion, SignatureScheme,
+    SupportedGroup,
+};
+#[cfg(feature = "boring")]
+pub use enums::{cipher_suite_from_openssl_cipher_str, openssl_cipher_list_str_from_cipher_list};
+
+mod certificate;
+pub use certificate::{
+    CertificateFingerprint, CertificateInfo, KeyUsage, NameAttribute, NameStringKind,
+    SubjectAltName,
 };
 
+mod key;
+mod pem;
+
+mod ech;
+pub use ech::{EchConfig, EchConfigList, EchKey, EchKeySet};
+
 pub mod client;
 pub mod keylog;
+pub mod ocsp;
 pub mod server;
 
 #[derive(Debug, Clone)]
@@ -50,7 +65,7 @@ impl SecureTransport {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Hash)]
 /// Intent for a (tls) keylogger to be used.
 ///
 /// Applicable to both a client- and server- config.
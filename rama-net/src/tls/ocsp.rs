@@ -0,0 +1,1013 @@
+//! Implementation agnostic support for the Online Certificate Status Protocol (OCSP),
+//! as defined in [RFC 6960].
+//!
+//! Used to request the OCSP responses stapled by servers
+//! (see `OcspStapler` in [`crate::tls::server`], available with the `http` feature),
+//! as well as to verify the OCSP responses stapled by servers on the client side.
+//!
+//! Rama does not implement any cryptography itself, which is why the signatures
+//! of OCSP responses are verified by the tls implementation in use,
+//! see [`OcspSignedData`].
+//!
+//! [RFC 6960]: https://datatracker.ietf.org/doc/html/rfc6960
+
+use super::SignatureScheme;
+use super::certificate::{asn1_time_to_system_time, signature_scheme_from_oid};
+use rama_core::error::OpaqueError;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+use x509_parser::asn1_rs::{
+    Any, Class, Integer, Null, OctetString, Oid, Sequence, SerializeResult, Tag, ToDer,
+};
+use x509_parser::oid_registry::{
+    OID_HASH_SHA1, OID_NIST_HASH_SHA256, OID_NIST_HASH_SHA384, OID_NIST_HASH_SHA512,
+};
+use x509_parser::prelude::{ASN1Time, FromDer, X509Certificate};
+
+const OID_OCSP_BASIC: &str = "1.3.6.1.5.5.7.48.1.1";
+
+const TAG_CERT_STATUS_GOOD: Tag = Tag(0);
+const TAG_CERT_STATUS_REVOKED: Tag = Tag(1);
+const TAG_CERT_STATUS_UNKNOWN: Tag = Tag(2);
+
+/// Clock skew tolerated when checking the validity period of OCSP responses.
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Identifier of a certificate, as used in OCSP requests and responses.
+pub struct OcspCertId {
+    issuer_name: Vec<u8>,
+    issuer_subject_public_key_info: Vec<u8>,
+    issuer_public_key: Vec<u8>,
+    serial_number: Vec<u8>,
+}
+
+impl OcspCertId {
+    /// Try to create the [`OcspCertId`] of the given (DER-encoded) certificate,
+    /// issued by the given (DER-encoded) issuer certificate.
+    pub fn try_new(cert: &[u8], issuer: &[u8]) -> Result<Self, OpaqueError> {
+        let (_, cert) = X509Certificate::from_der(cert)
+            .map_err(|_| OpaqueError::from_display("ocsp cert id: invalid DER certificate"))?;
+        let (_, issuer) = X509Certificate::from_der(issuer).map_err(|_| {
+            OpaqueError::from_display("ocsp cert id: invalid DER issuer certificate")
+        })?;
+        if cert.issuer().as_raw() != issuer.subject().as_raw() {
+            return Err(OpaqueError::from_display(
+                "ocsp cert id: certificate is not issued by the given issuer",
+            ));
+        }
+        Self::from_issuer_spki(&cert, issuer.public_key().raw).ok_or_else(|| {
+            OpaqueError::from_display("ocsp cert id: invalid issuer subject public key info")
+        })
+    }
+
+    /// Create the [`OcspCertId`] of the given certificate, issued by the issuer
+    /// with the given (DER-encoded) SubjectPublicKeyInfo, e.g. a trust anchor
+    /// which is not available as a certificate.
+    fn from_issuer_spki(cert: &X509Certificate<'_>, issuer_spki: &[u8]) -> Option<Self> {
+        let (_, issuer_public_key) = spki_parts(issuer_spki)?;
+        Some(Self {
+            issuer_name: cert.issuer().as_raw().to_vec(),
+            issuer_subject_public_key_info: issuer_spki.to_vec(),
+            issuer_public_key: issuer_public_key.to_vec(),
+            serial_number: cert.tbs_certificate.raw_serial().to_vec(),
+        })
+    }
+
+    /// Encode a (DER) OCSP request for the status of this certificate,
+    /// e.g. to be sent to an OCSP responder using HTTP `POST`
+    /// with content type `application/ocsp-request`.
+    ///
+    /// The certificate is identified using SHA-1 hashes, as required by [RFC 5019].
+    ///
+    /// [RFC 5019]: https://datatracker.ietf.org/doc/html/rfc5019
+    pub fn to_ocsp_request_der(&self) -> Result<Vec<u8>, OpaqueError> {
+        self.encode_ocsp_request().map_err(OpaqueError::from_std)
+    }
+
+    fn encode_ocsp_request(&self) -> SerializeResult<Vec<u8>> {
+        let request = Sequence::new(self.try_to_der()?.into()).to_der_vec()?;
+        let request_list = Sequence::new(request.into()).to_der_vec()?;
+        let tbs_request = Sequence::new(request_list.into()).to_der_vec()?;
+        Sequence::new(tbs_request.into()).to_der_vec()
+    }
+
+    /// Encode this identifier as a (DER) `CertID`, using SHA-1 hashes.
+    fn try_to_der(&self) -> SerializeResult<Vec<u8>> {
+        let hash_algorithm = [OID_HASH_SHA1.to_der_vec()?, Null::new().to_der_vec()?].concat();
+        let cert_id = [
+            Sequence::new(hash_algorithm.into()).to_der_vec()?,
+            OctetString::new(&Sha1::digest(&self.issuer_name)).to_der_vec()?,
+            OctetString::new(&Sha1::digest(&self.issuer_public_key)).to_der_vec()?,
+            Integer::new(&self.serial_number).to_der_vec()?,
+        ]
+        .concat();
+        Sequence::new(cert_id.into()).to_der_vec()
+    }
+
+    fn matches(&self, id: &ResponseCertId) -> bool {
+        let digest = |data: &[u8]| -> Option<Vec<u8>> {
+            Some(match &id.hash_algorithm {
+                oid if *oid == OID_HASH_SHA1 => Sha1::digest(data).to_vec(),
+                oid if *oid == OID_NIST_HASH_SHA256 => Sha256::digest(data).to_vec(),
+                oid if *oid == OID_NIST_HASH_SHA384 => Sha384::digest(data).to_vec(),
+                oid if *oid == OID_NIST_HASH_SHA512 => Sha512::digest(data).to_vec(),
+                _ => return None,
+            })
+        };
+        self.serial_number == id.serial_number
+            && digest(&self.issuer_name).is_some_and(|hash| hash == id.issuer_name_hash)
+            && digest(&self.issuer_public_key).is_some_and(|hash| hash == id.issuer_key_hash)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Certificate identifier as found in an OCSP response.
+struct ResponseCertId {
+    hash_algorithm: Oid<'static>,
+    issuer_name_hash: Vec<u8>,
+    issuer_key_hash: Vec<u8>,
+    serial_number: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Status of a certificate, as reported by an OCSP responder.
+pub enum OcspCertStatus {
+    /// The certificate is not revoked.
+    Good,
+    /// The certificate is revoked.
+    Revoked {
+        /// Time at which the certificate was revoked.
+        revocation_time: SystemTime,
+    },
+    /// The responder does not know about the certificate.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Status of a single certificate, as found in an [`OcspResponse`].
+pub struct OcspSingleResponse {
+    cert_id: ResponseCertId,
+    /// Status of the certificate.
+    pub cert_status: OcspCertStatus,
+    /// Time at which the status was known to be correct.
+    pub this_update: SystemTime,
+    /// Time at (or before) which newer information about the status will be available,
+    /// if defined by the responder.
+    pub next_update: Option<SystemTime>,
+}
+
+impl OcspSingleResponse {
+    /// Returns `true` if this response is valid at the given time,
+    /// tolerating a clock skew of a few minutes.
+    pub fn is_valid_at(&self, now: SystemTime) -> bool {
+        self.this_update <= now + MAX_CLOCK_SKEW
+            && self
+                .next_update
+                .is_none_or(|next_update| now <= next_update + MAX_CLOCK_SKEW)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A (basic) OCSP response, as returned by an OCSP responder.
+pub struct OcspResponse {
+    produced_at: SystemTime,
+    responses: Vec<OcspSingleResponse>,
+    tbs_response_data: Vec<u8>,
+    signature_algorithm: Vec<u8>,
+    signature: Vec<u8>,
+    certs: Vec<Vec<u8>>,
+}
+
+impl OcspResponse {
+    /// Try to parse a DER-encoded OCSP response.
+    ///
+    /// Fails for responses without a successful response status.
+    pub fn try_from_der(der: &[u8]) -> Result<Self, OcspError> {
+        let (_, response, _) =
+            read_der(der, Class::Universal, Tag::Sequence).ok_or(OcspError::Malformed)?;
+        let (_, status, rest) =
+            read_der(response, Class::Universal, Tag::Enumerated).ok_or(OcspError::Malformed)?;
+        match status {
+            [0] => (),
+            [status] => return Err(OcspError::Unsuccessful(*status)),
+            _ => return Err(OcspError::Malformed),
+        }
+        parse_response_bytes(rest).ok_or(OcspError::Malformed)
+    }
+
+    /// Time at which the response was signed.
+    pub fn produced_at(&self) -> SystemTime {
+        self.produced_at
+    }
+
+    /// Status of all certificates found in the response.
+    pub fn responses(&self) -> &[OcspSingleResponse] {
+        &self.responses
+    }
+
+    /// Find the status of the certificate with the given [`OcspCertId`].
+    pub fn find(&self, cert_id: &OcspCertId) -> Option<&OcspSingleResponse> {
+        self.responses
+            .iter()
+            .find(|response| cert_id.matches(&response.cert_id))
+    }
+
+    /// Verify that this response reports the certificate with the
+    /// given [`OcspCertId`] as [`OcspCertStatus::Good`] at the given time.
+    ///
+    /// The response has to be signed by the issuer of the certificate,
+    /// or by a responder certificate issued by it for OCSP signing.
+    /// The signatures are verified using the given `verify_signature` function,
+    /// which has to return `true` only if the signature is valid.
+    pub fn verify(
+        &self,
+        cert_id: &OcspCertId,
+        now: SystemTime,
+        verify_signature: impl Fn(&OcspSignedData<'_>) -> bool,
+    ) -> Result<&OcspSingleResponse, OcspError> {
+        let response = self.find(cert_id).ok_or(OcspError::NoMatchingResponse)?;
+        if !self.verify_signer(cert_id, now, &verify_signature) {
+            return Err(OcspError::InvalidSignature);
+        }
+        if !response.is_valid_at(now) {
+            return Err(OcspError::Expired);
+        }
+        match response.cert_status {
+            OcspCertStatus::Good => Ok(response),
+            OcspCertStatus::Revoked { .. } => Err(OcspError::Revoked),
+            OcspCertStatus::Unknown => Err(OcspError::UnknownCert),
+        }
+    }
+
+    fn verify_signer(
+        &self,
+        cert_id: &OcspCertId,
+        now: SystemTime,
+        verify_signature: &impl Fn(&OcspSignedData<'_>) -> bool,
+    ) -> bool {
+        let verify_response_signature = |subject_public_key_info| {
+            OcspSignedData::new(
+                subject_public_key_info,
+                &self.signature_algorithm,
+                &self.tbs_response_data,
+                &self.signature,
+            )
+            .is_some_and(|data| verify_signature(&data))
+        };
+
+        // signed by the issuer itself
+        if verify_response_signature(&cert_id.issuer_subject_public_key_info) {
+            return true;
+        }
+
+        // signed by a responder delegated by the issuer
+        self.certs.iter().any(|der| {
+            let Ok((_, cert)) = X509Certificate::from_der(der) else {
+                return false;
+            };
+            let Some(signature_algorithm) = certificate_signature_algorithm(der) else {
+                return false;
+            };
+            let validity = cert.validity();
+            cert.issuer().as_raw() == cert_id.issuer_name
+                && is_valid_at(validity.not_before, validity.not_after, now)
+                && cert
+                    .extended_key_usage()
+                    .ok()
+                    .flatten()
+                    .is_some_and(|usage| usage.value.ocsp_signing)
+                && OcspSignedData::new(
+                    &cert_id.issuer_subject_public_key_info,
+                    signature_algorithm,
+                    cert.tbs_certificate.as_ref(),
+                    &cert.signature_value.data,
+                )
+                .is_some_and(|data| verify_signature(&data))
+                && verify_response_signature(cert.tbs_certificate.subject_pki.raw)
+        })
+    }
+}
+
+/// Verify the (DER-encoded) OCSP response stapled by a server,
+/// for the verified certificate path of that server.
+///
+/// The path consists of the (DER-encoded) certificates, leaf first, up to but
+/// excluding the trust anchor, of which only the (DER-encoded) SubjectPublicKeyInfo
+/// is given. It has to be the path as verified by the tls implementation: certificates
+/// sent by the server which are not part of that path are not to be trusted.
+///
+/// The issuer of the leaf is the next certificate in the path, or the trust anchor
+/// for a leaf issued by it directly, and is only accepted if its public key
+/// verifies the signature of the leaf certificate.
+/// See [`OcspResponse::verify`] for more information.
+pub fn verify_stapled_ocsp_response<C: AsRef<[u8]>>(
+    path: &[C],
+    trust_anchor_spki: &[u8],
+    response: &[u8],
+    now: SystemTime,
+    verify_signature: impl Fn(&OcspSignedData<'_>) -> bool,
+) -> Result<OcspSingleResponse, OcspError> {
+    if response.is_empty() {
+        return Err(OcspError::Missing);
+    }
+    let (leaf, intermediates) = path.split_first().ok_or(OcspError::IssuerNotFound)?;
+    let (_, leaf_cert) =
+        X509Certificate::from_der(leaf.as_ref()).map_err(|_| OcspError::IssuerNotFound)?;
+    let issuer_spki = match intermediates.first() {
+        Some(issuer) => {
+            let (_, issuer) = X509Certificate::from_der(issuer.as_ref())
+                .map_err(|_| OcspError::IssuerNotFound)?;
+            if issuer.subject().as_raw() != leaf_cert.issuer().as_raw() {
+                return Err(OcspError::IssuerNotFound);
+            }
+            issuer.tbs_certificate.subject_pki.raw
+        }
+        None => trust_anchor_spki,
+    };
+    let signed_by_issuer = certificate_signature_algorithm(leaf.as_ref())
+        .and_then(|signature_algorithm| {
+            OcspSignedData::new(
+                issuer_spki,
+                signature_algorithm,
+                leaf_cert.tbs_certificate.as_ref(),
+                &leaf_cert.signature_value.data,
+            )
+        })
+        .is_some_and(|data| verify_signature(&data));
+    if !signed_by_issuer {
+        return Err(OcspError::IssuerNotFound);
+    }
+    let cert_id =
+        OcspCertId::from_issuer_spki(&leaf_cert, issuer_spki).ok_or(OcspError::IssuerNotFound)?;
+    OcspResponse::try_from_der(response)?
+        .verify(&cert_id, now, verify_signature)
+        .cloned()
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Data signed as part of an OCSP response,
+/// of which the signature has to be verified by the tls implementation.
+pub struct OcspSignedData<'a> {
+    /// DER-encoded SubjectPublicKeyInfo of the signer.
+    pub subject_public_key_info: &'a [u8],
+    /// DER-encoded AlgorithmIdentifier of the public key of the signer,
+    /// without its outer `SEQUENCE` tag and length.
+    pub public_key_algorithm: &'a [u8],
+    /// Public key of the signer, the content of the `subjectPublicKey` bit string.
+    pub public_key: &'a [u8],
+    /// DER-encoded AlgorithmIdentifier of the signature,
+    /// without its outer `SEQUENCE` tag and length.
+    pub signature_algorithm: &'a [u8],
+    /// Signature scheme of the signature, if it maps to a known [`SignatureScheme`].
+    pub signature_scheme: Option<SignatureScheme>,
+    /// The signed message.
+    pub message: &'a [u8],
+    /// The signature.
+    pub signature: &'a [u8],
+}
+
+impl<'a> OcspSignedData<'a> {
+    fn new(
+        subject_public_key_info: &'a [u8],
+        signature_algorithm: &'a [u8],
+        message: &'a [u8],
+        signature: &'a [u8],
+    ) -> Option<Self> {
+        let (public_key_algorithm, public_key) = spki_parts(subject_public_key_info)?;
+        let signature_scheme = Oid::from_der(signature_algorithm)
+            .ok()
+            .and_then(|(_, oid)| signature_scheme_from_oid(&oid.to_id_string()));
+        Some(Self {
+            subject_public_key_info,
+            public_key_algorithm,
+            public_key,
+            signature_algorithm,
+            signature_scheme,
+            message,
+            signature,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Error returned when an OCSP response could not be verified.
+pub enum OcspError {
+    /// No OCSP response was stapled by the server.
+    Missing,
+    /// The OCSP response could not be parsed.
+    Malformed,
+    /// The OCSP responder returned an unsuccessful response status.
+    Unsuccessful(u8),
+    /// The issuer of the certificate could not be found in the verified certificate path,
+    /// or its public key does not verify the signature of the certificate.
+    IssuerNotFound,
+    /// The OCSP response contains no status for the certificate.
+    NoMatchingResponse,
+    /// The signature of the OCSP response could not be verified.
+    InvalidSignature,
+    /// The OCSP response is not (or no longer) valid.
+    Expired,
+    /// The certificate is revoked.
+    Revoked,
+    /// The certificate is unknown to the OCSP responder.
+    UnknownCert,
+}
+
+impl fmt::Display for OcspError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "OCSP Error: no OCSP response stapled"),
+            Self::Malformed => write!(f, "OCSP Error: malformed OCSP response"),
+            Self::Unsuccessful(status) => {
+                write!(f, "OCSP Error: unsuccessful OCSP response status: {status}")
+            }
+            Self::IssuerNotFound => {
+                write!(
+                    f,
+                    "OCSP Error: issuer of certificate not found in verified path"
+                )
+            }
+            Self::NoMatchingResponse => {
+                write!(f, "OCSP Error: no OCSP response found for certificate")
+            }
+            Self::InvalidSignature => write!(f, "OCSP Error: invalid OCSP response signature"),
+            Self::Expired => write!(f, "OCSP Error: OCSP response is not valid at this time"),
+            Self::Revoked => write!(f, "OCSP Error: certificate is revoked"),
+            Self::UnknownCert => {
+                write!(
+                    f,
+                    "OCSP Error: certificate is unknown to the OCSP responder"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcspError {}
+
+fn is_valid_at(not_before: ASN1Time, not_after: ASN1Time, now: SystemTime) -> bool {
+    match (
+        asn1_time_to_system_time(not_before),
+        asn1_time_to_system_time(not_after),
+    ) {
+        (Some(not_before), Some(not_after)) => {
+            not_before <= now + MAX_CLOCK_SKEW && now <= not_after + MAX_CLOCK_SKEW
+        }
+        _ => false,
+    }
+}
+
+/// Read a single DER element with the given class and tag,
+/// returning the complete element, its content and the remaining input.
+pub(super) fn read_der(input: &[u8], class: Class, tag: Tag) -> Option<(&[u8], &[u8], &[u8])> {
+    let (rest, any) = Any::from_der(input).ok()?;
+    if any.class() != class || any.tag() != tag {
+        return None;
+    }
+    Some((&input[..input.len() - rest.len()], any.data, rest))
+}
+
+/// Read a single DER time element, returning it along with the remaining input.
+fn read_time(input: &[u8]) -> Option<(SystemTime, &[u8])> {
+    let (rest, time) = ASN1Time::from_der(input).ok()?;
+    Some((asn1_time_to_system_time(time)?, rest))
+}
+
+/// Content of the signature AlgorithmIdentifier of a DER-encoded certificate,
+/// which is not exposed by the certificate parser.
+fn certificate_signature_algorithm(cert: &[u8]) -> Option<&[u8]> {
+    let (_, cert, _) = read_der(cert, Class::Universal, Tag::Sequence)?;
+    let (_, _, rest) = read_der(cert, Class::Universal, Tag::Sequence)?;
+    let (_, signature_algorithm, _) = read_der(rest, Class::Universal, Tag::Sequence)?;
+    Some(signature_algorithm)
+}
+
+/// Split a DER-encoded SubjectPublicKeyInfo into the content
+/// of its AlgorithmIdentifier and its public key.
+pub(super) fn spki_parts(spki: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (_, spki, _) = read_der(spki, Class::Universal, Tag::Sequence)?;
+    let (_, algorithm, rest) = read_der(spki, Class::Universal, Tag::Sequence)?;
+    let (_, public_key, _) = read_der(rest, Class::Universal, Tag::BitString)?;
+    // first byte is the number of unused bits in the last byte
+    let (_, public_key) = public_key.split_first()?;
+    Some((algorithm, public_key))
+}
+
+/// Parse the `responseBytes` of an OCSP response.
+///
+/// ```text
+/// ResponseBytes ::= SEQUENCE {
+///     responseType   OBJECT IDENTIFIER,
+///     response       OCTET STRING }
+/// BasicOCSPResponse ::= SEQUENCE {
+///     tbsResponseData      ResponseData,
+///     signatureAlgorithm   AlgorithmIdentifier,
+///     signature            BIT STRING,
+///     certs            [0] EXPLICIT SEQUENCE OF Certificate OPTIONAL }
+/// ResponseData ::= SEQUENCE {
+///     version              [0] EXPLICIT Version DEFAULT v1,
+///     responderID              ResponderID,
+///     producedAt               GeneralizedTime,
+///     responses                SEQUENCE OF SingleResponse,
+///     responseExtensions   [1] EXPLICIT Extensions OPTIONAL }
+/// ```
+fn parse_response_bytes(response_bytes: &[u8]) -> Option<OcspResponse> {
+    let (_, response_bytes, _) = read_der(response_bytes, Class::ContextSpecific, Tag(0))?;
+    let (_, response_bytes, _) = read_der(response_bytes, Class::Universal, Tag::Sequence)?;
+    let (rest, response_type) = Oid::from_der(response_bytes).ok()?;
+    if response_type.to_id_string() != OID_OCSP_BASIC {
+        return None;
+    }
+    let (_, basic, _) = read_der(rest, Class::Universal, Tag::OctetString)?;
+
+    let (_, basic, _) = read_der(basic, Class::Universal, Tag::Sequence)?;
+    let (tbs_response_data, mut data, rest) = read_der(basic, Class::Universal, Tag::Sequence)?;
+    let (_, signature_algorithm, rest) = read_der(rest, Class::Universal, Tag::Sequence)?;
+    let (_, signature, rest) = read_der(rest, Class::Universal, Tag::BitString)?;
+    let (_, signature) = signature.split_first()?;
+    let mut certs = Vec::new();
+    if let Some((_, explicit_certs, _)) = read_der(rest, Class::ContextSpecific, Tag(0)) {
+        let (_, mut rest, _) = read_der(explicit_certs, Class::Universal, Tag::Sequence)?;
+        while !rest.is_empty() {
+            let (cert, _, next) = read_der(rest, Class::Universal, Tag::Sequence)?;
+            certs.push(cert.to_vec());
+            rest = next;
+        }
+    }
+
+    if let Some((_, _, rest)) = read_der(data, Class::ContextSpecific, Tag(0)) {
+        data = rest;
+    }
+    // skip responderID
+    let (data, _) = Any::from_der(data).ok()?;
+    let (produced_at, data) = read_time(data)?;
+    let (_, mut responses, _) = read_der(data, Class::Universal, Tag::Sequence)?;
+    let mut single_responses = Vec::new();
+    while !responses.is_empty() {
+        let (_, response, rest) = read_der(responses, Class::Universal, Tag::Sequence)?;
+        single_responses.push(parse_single_response(response)?);
+        responses = rest;
+    }
+
+    Some(OcspResponse {
+        produced_at,
+        responses: single_responses,
+        tbs_response_data: tbs_response_data.to_vec(),
+        signature_algorithm: signature_algorithm.to_vec(),
+        signature: signature.to_vec(),
+        certs,
+    })
+}
+
+/// Parse the content of a `SingleResponse`.
+///
+/// ```text
+/// SingleResponse ::= SEQUENCE {
+///     certID                       CertID,
+///     certStatus                   CertStatus,
+///     thisUpdate                   GeneralizedTime,
+///     nextUpdate         [0]       EXPLICIT GeneralizedTime OPTIONAL,
+///     singleExtensions   [1]       EXPLICIT Extensions OPTIONAL }
+/// CertID ::= SEQUENCE {
+///     hashAlgorithm       AlgorithmIdentifier,
+///     issuerNameHash      OCTET STRING,
+///     issuerKeyHash       OCTET STRING,
+///     serialNumber        CertificateSerialNumber }
+/// CertStatus ::= CHOICE {
+///     good        [0]     IMPLICIT NULL,
+///     revoked     [1]     IMPLICIT RevokedInfo,
+///     unknown     [2]     IMPLICIT UnknownInfo }
+/// ```
+fn parse_single_response(response: &[u8]) -> Option<OcspSingleResponse> {
+    let (_, cert_id, rest) = read_der(response, Class::Universal, Tag::Sequence)?;
+    let (_, hash_algorithm, cert_id) = read_der(cert_id, Class::Universal, Tag::Sequence)?;
+    let (_, hash_algorithm) = Oid::from_der(hash_algorithm).ok()?;
+    let (_, issuer_name_hash, cert_id) = read_der(cert_id, Class::Universal, Tag::OctetString)?;
+    let (_, issuer_key_hash, cert_id) = read_der(cert_id, Class::Universal, Tag::OctetString)?;
+    let (_, serial_number, _) = read_der(cert_id, Class::Universal, Tag::Integer)?;
+
+    let (rest, status) = Any::from_der(rest).ok()?;
+    if status.class() != Class::ContextSpecific {
+        return None;
+    }
+    let cert_status = match status.tag() {
+        TAG_CERT_STATUS_GOOD => OcspCertStatus::Good,
+        TAG_CERT_STATUS_REVOKED => OcspCertStatus::Revoked {
+            revocation_time: read_time(status.data)?.0,
+        },
+        TAG_CERT_STATUS_UNKNOWN => OcspCertStatus::Unknown,
+        _ => return None,
+    };
+
+    let (this_update, rest) = read_time(rest)?;
+    let next_update = match read_der(rest, Class::ContextSpecific, Tag(0)) {
+        Some((_, next_update, _)) => Some(read_time(next_update)?.0),
+        None => None,
+    };
+
+    Some(OcspSingleResponse {
+        cert_id: ResponseCertId {
+            hash_algorithm: hash_algorithm.to_owned(),
+            issuer_name_hash: issuer_name_hash.to_vec(),
+            issuer_key_hash: issuer_key_hash.to_vec(),
+            serial_number: serial_number.to_vec(),
+        },
+        cert_status,
+        this_update,
+        next_update,
+    })
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use x509_parser::asn1_rs::{BitString, Enumerated, Header, Length};
+
+    /// Remote key pair "signing" using [`fake_signature`],
+    /// as rama-net implements no cryptography to verify real signatures.
+    struct FakeKeyPair(Vec<u8>);
+
+    impl rcgen::RemoteKeyPair for FakeKeyPair {
+        fn public_key(&self) -> &[u8] {
+            &self.0
+        }
+
+        fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, rcgen::Error> {
+            Ok(fake_signature(&self.0, msg))
+        }
+
+        fn algorithm(&self) -> &'static rcgen::SignatureAlgorithm {
+            &rcgen::PKCS_ECDSA_P256_SHA256
+        }
+    }
+
+    fn fake_signature(public_key: &[u8], message: &[u8]) -> Vec<u8> {
+        Sha256::digest([public_key, message].concat()).to_vec()
+    }
+
+    pub(in crate::tls) fn fake_key_pair() -> rcgen::KeyPair {
+        let public_key = rcgen::KeyPair::generate()
+            .unwrap()
+            .public_key_raw()
+            .to_vec();
+        rcgen::KeyPair::from_remote(Box::new(FakeKeyPair(public_key))).unwrap()
+    }
+
+    pub(in crate::tls) fn verify_fake_signature(data: &OcspSignedData<'_>) -> bool {
+        data.signature == fake_signature(data.public_key, data.message)
+    }
+
+    /// Create a CA and a leaf certificate issued by it, both using fake key pairs.
+    pub(in crate::tls) fn ca_and_leaf(
+        ocsp_url: &str,
+    ) -> (rcgen::Certificate, rcgen::KeyPair, rcgen::Certificate) {
+        let ca_key = fake_key_pair();
+        let mut ca_params = rcgen::CertificateParams::new(Vec::new()).unwrap();
+        ca_params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "Example CA");
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let mut leaf_params =
+            rcgen::CertificateParams::new(vec!["example.com".to_owned()]).unwrap();
+        leaf_params
+            .custom_extensions
+            .push(super::super::certificate::tests::authority_info_access_extension(ocsp_url));
+        let leaf = leaf_params
+            .signed_by(&fake_key_pair(), &ca, &ca_key)
+            .unwrap();
+
+        (ca, ca_key, leaf)
+    }
+
+    fn der(class: Class, constructed: bool, tag: Tag, content: &[u8]) -> Vec<u8> {
+        let header = Header::new(class, constructed, tag, Length::Definite(content.len()));
+        Any::new(header, content).to_der_vec().unwrap()
+    }
+
+    fn sequence(content: &[u8]) -> Vec<u8> {
+        der(Class::Universal, true, Tag::Sequence, content)
+    }
+
+    fn explicit(tag: u32, content: &[u8]) -> Vec<u8> {
+        der(Class::ContextSpecific, true, Tag(tag), content)
+    }
+
+    fn generalized_time(time: &str) -> Vec<u8> {
+        der(
+            Class::Universal,
+            false,
+            Tag::GeneralizedTime,
+            time.as_bytes(),
+        )
+    }
+
+    /// Create a (DER) OCSP response for the given certificate,
+    /// signed by the given key pair and valid from 2024 until `next_update`.
+    pub(in crate::tls) fn ocsp_response_der(
+        cert_id: &OcspCertId,
+        cert_status: &[u8],
+        next_update: &str,
+        signer: &rcgen::KeyPair,
+        certs: &[&[u8]],
+    ) -> Vec<u8> {
+        let single = sequence(
+            &[
+                cert_id.try_to_der().unwrap(),
+                cert_status.to_vec(),
+                generalized_time("20240101000000Z"),
+                explicit(0, &generalized_time(next_update)),
+            ]
+            .concat(),
+        );
+        let tbs_response_data = sequence(
+            &[
+                // responderID: byKey
+                explicit(2, &OctetString::new(&[1, 2, 3, 4]).to_der_vec().unwrap()),
+                generalized_time("20240101000000Z"),
+                sequence(&single),
+            ]
+            .concat(),
+        );
+        let signature = fake_signature(signer.public_key_raw(), &tbs_response_data);
+        let signature_algorithm = Oid::from(&[1, 2, 840, 10045, 4, 3, 2]).unwrap();
+        let mut basic = [
+            tbs_response_data,
+            sequence(&signature_algorithm.to_der_vec().unwrap()),
+            BitString::new(0, &signature).to_der_vec().unwrap(),
+        ]
+        .concat();
+        if !certs.is_empty() {
+            basic.extend(explicit(0, &sequence(&certs.concat())));
+        }
+        let response_type = Oid::from(&[1, 3, 6, 1, 5, 5, 7, 48, 1, 1]).unwrap();
+        let response_bytes = sequence(
+            &[
+                response_type.to_der_vec().unwrap(),
+                OctetString::new(&sequence(&basic)).to_der_vec().unwrap(),
+            ]
+            .concat(),
+        );
+        sequence(
+            &[
+                Enumerated::new(0).to_der_vec().unwrap(),
+                explicit(0, &response_bytes),
+            ]
+            .concat(),
+        )
+    }
+
+    pub(in crate::tls) fn good() -> Vec<u8> {
+        der(Class::ContextSpecific, false, TAG_CERT_STATUS_GOOD, &[])
+    }
+
+    fn now() -> SystemTime {
+        read_time(&generalized_time("20260101000000Z")).unwrap().0
+    }
+
+    #[test]
+    fn test_ocsp_cert_id() {
+        let (ca, _, leaf) = ca_and_leaf("http://ocsp.example.com");
+        assert!(OcspCertId::try_new(leaf.der(), ca.der()).is_ok());
+        assert!(OcspCertId::try_new(ca.der(), leaf.der()).is_err());
+        assert!(OcspCertId::try_new(&[1, 2, 3], ca.der()).is_err());
+
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+        let request = cert_id.to_ocsp_request_der().unwrap();
+        let (_, request, _) = read_der(&request, Class::Universal, Tag::Sequence).unwrap();
+        let (_, tbs_request, _) = read_der(request, Class::Universal, Tag::Sequence).unwrap();
+        let (_, request_list, _) = read_der(tbs_request, Class::Universal, Tag::Sequence).unwrap();
+        let (_, request, _) = read_der(request_list, Class::Universal, Tag::Sequence).unwrap();
+        assert_eq!(request, cert_id.try_to_der().unwrap());
+    }
+
+    #[test]
+    fn test_ocsp_response_verify_signed_by_issuer() {
+        let (ca, ca_key, leaf) = ca_and_leaf("http://ocsp.example.com");
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+        let der = ocsp_response_der(&cert_id, &good(), "20340101000000Z", &ca_key, &[]);
+
+        let response = OcspResponse::try_from_der(&der).unwrap();
+        assert_eq!(response.responses().len(), 1);
+        let single = response
+            .verify(&cert_id, now(), verify_fake_signature)
+            .unwrap();
+        assert_eq!(single.cert_status, OcspCertStatus::Good);
+        assert!(single.next_update.is_some());
+
+        // signed by another key
+        let der = ocsp_response_der(&cert_id, &good(), "20340101000000Z", &fake_key_pair(), &[]);
+        assert_eq!(
+            OcspResponse::try_from_der(&der)
+                .unwrap()
+                .verify(&cert_id, now(), verify_fake_signature)
+                .unwrap_err(),
+            OcspError::InvalidSignature
+        );
+
+        // for another certificate
+        let (other_ca, _, other_leaf) = ca_and_leaf("http://ocsp.example.com");
+        let other_cert_id = OcspCertId::try_new(other_leaf.der(), other_ca.der()).unwrap();
+        assert_eq!(
+            OcspResponse::try_from_der(&der)
+                .unwrap()
+                .verify(&other_cert_id, now(), verify_fake_signature)
+                .unwrap_err(),
+            OcspError::NoMatchingResponse
+        );
+    }
+
+    #[test]
+    fn test_ocsp_response_verify_delegated_responder() {
+        let (ca, ca_key, leaf) = ca_and_leaf("http://ocsp.example.com");
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+
+        for (extended_key_usages, expected) in [
+            (
+                vec![rcgen::ExtendedKeyUsagePurpose::OcspSigning],
+                Ok(OcspCertStatus::Good),
+            ),
+            (
+                vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth],
+                Err(OcspError::InvalidSignature),
+            ),
+        ] {
+            let responder_key = fake_key_pair();
+            let mut params = rcgen::CertificateParams::new(Vec::new()).unwrap();
+            params
+                .distinguished_name
+                .push(rcgen::DnType::CommonName, "Example OCSP Responder");
+            params.extended_key_usages = extended_key_usages;
+            let responder = params.signed_by(&responder_key, &ca, &ca_key).unwrap();
+
+            let der = ocsp_response_der(
+                &cert_id,
+                &good(),
+                "20340101000000Z",
+                &responder_key,
+                &[responder.der()],
+            );
+            let result = OcspResponse::try_from_der(&der)
+                .unwrap()
+                .verify(&cert_id, now(), verify_fake_signature)
+                .map(|single| single.cert_status);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_ocsp_response_verify_status_and_validity() {
+        let (ca, ca_key, leaf) = ca_and_leaf("http://ocsp.example.com");
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+
+        for (cert_status, next_update, expected) in [
+            (
+                explicit(
+                    TAG_CERT_STATUS_REVOKED.0,
+                    &generalized_time("20250101000000Z"),
+                ),
+                "20340101000000Z",
+                OcspError::Revoked,
+            ),
+            (
+                der(Class::ContextSpecific, false, TAG_CERT_STATUS_UNKNOWN, &[]),
+                "20340101000000Z",
+                OcspError::UnknownCert,
+            ),
+            (good(), "20250101000000Z", OcspError::Expired),
+        ] {
+            let der = ocsp_response_der(&cert_id, &cert_status, next_update, &ca_key, &[]);
+            assert_eq!(
+                OcspResponse::try_from_der(&der)
+                    .unwrap()
+                    .verify(&cert_id, now(), verify_fake_signature)
+                    .unwrap_err(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_ocsp_response_try_from_der_errors() {
+        assert_eq!(
+            OcspResponse::try_from_der(&[1, 2, 3]).unwrap_err(),
+            OcspError::Malformed
+        );
+        // tryLater
+        let der = sequence(&Enumerated::new(3).to_der_vec().unwrap());
+        assert_eq!(
+            OcspResponse::try_from_der(&der).unwrap_err(),
+            OcspError::Unsuccessful(3)
+        );
+    }
+
+    fn spki(cert: &[u8]) -> Vec<u8> {
+        let (_, cert) = X509Certificate::from_der(cert).unwrap();
+        cert.public_key().raw.to_vec()
+    }
+
+    #[test]
+    fn test_verify_stapled_ocsp_response() {
+        let (ca, ca_key, leaf) = ca_and_leaf("http://ocsp.example.com");
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+        let der = ocsp_response_der(&cert_id, &good(), "20340101000000Z", &ca_key, &[]);
+        let ca_spki = spki(ca.der());
+
+        // leaf issued directly by the trust anchor
+        let path = [leaf.der().to_vec()];
+        let single =
+            verify_stapled_ocsp_response(&path, &ca_spki, &der, now(), verify_fake_signature)
+                .unwrap();
+        assert_eq!(single.cert_status, OcspCertStatus::Good);
+
+        // leaf issued by an intermediate
+        let path = [leaf.der().to_vec(), ca.der().to_vec()];
+        let single = verify_stapled_ocsp_response(
+            &path,
+            &spki(leaf.der()),
+            &der,
+            now(),
+            verify_fake_signature,
+        )
+        .unwrap();
+        assert_eq!(single.cert_status, OcspCertStatus::Good);
+
+        assert_eq!(
+            verify_stapled_ocsp_response(&path, &ca_spki, &[], now(), verify_fake_signature)
+                .unwrap_err(),
+            OcspError::Missing
+        );
+        assert_eq!(
+            verify_stapled_ocsp_response(
+                &path[..1],
+                &spki(leaf.der()),
+                &der,
+                now(),
+                verify_fake_signature
+            )
+            .unwrap_err(),
+            OcspError::IssuerNotFound
+        );
+    }
+
+    #[test]
+    fn test_verify_stapled_ocsp_response_rejects_fake_issuer() {
+        let (ca, ca_key, leaf) = ca_and_leaf("http://ocsp.example.com");
+        let revoked = explicit(1, &generalized_time("20240101000000Z"));
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+        let der = ocsp_response_der(&cert_id, &revoked, "20340101000000Z", &ca_key, &[]);
+        let path = [leaf.der().to_vec(), ca.der().to_vec()];
+        assert_eq!(
+            verify_stapled_ocsp_response(&path, &[], &der, now(), verify_fake_signature)
+                .unwrap_err(),
+            OcspError::Revoked
+        );
+
+        // same subject as the real issuer, but a key of the attacker
+        let fake_ca_key = fake_key_pair();
+        let mut fake_ca_params = rcgen::CertificateParams::new(Vec::new()).unwrap();
+        fake_ca_params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "Example CA");
+        fake_ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let fake_ca = fake_ca_params.self_signed(&fake_ca_key).unwrap();
+        let fake_cert_id = OcspCertId::try_new(leaf.der(), fake_ca.der()).unwrap();
+        let fake_der =
+            ocsp_response_der(&fake_cert_id, &good(), "20340101000000Z", &fake_ca_key, &[]);
+
+        let fake_path = [leaf.der().to_vec(), fake_ca.der().to_vec()];
+        assert_eq!(
+            verify_stapled_ocsp_response(
+                &fake_path,
+                &spki(ca.der()),
+                &fake_der,
+                now(),
+                verify_fake_signature
+            )
+            .unwrap_err(),
+            OcspError::IssuerNotFound
+        );
+        assert_eq!(
+            verify_stapled_ocsp_response(
+                &path[..1],
+                &spki(fake_ca.der()),
+                &fake_der,
+                now(),
+                verify_fake_signature
+            )
+            .unwrap_err(),
+            OcspError::IssuerNotFound
+        );
+        assert_eq!(
+            verify_stapled_ocsp_response(&path, &[], &fake_der, now(), verify_fake_signature)
+                .unwrap_err(),
+            OcspError::NoMatchingResponse
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Minimal PEM encoder and decoder,
+//! used to store and load certificates and keys.
+
+use base64::Engine as _;
+
+const BASE64: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+pub(crate) const LABEL_CERTIFICATE: &str = "CERTIFICATE";
+pub(crate) const LABEL_PRIVATE_KEY: &str = "PRIVATE KEY";
+
+/// A single PEM block, as found in PEM-encoded text.
+pub(crate) struct PemBlock<'a> {
+    pub(crate) label: &'a str,
+    /// The complete block, including the begin and end lines.
+    pub(crate) text: &'a str,
+}
+
+impl PemBlock<'_> {
+    /// Decode the (base64) content of this block.
+    pub(crate) fn decode(&self) -> Option<Vec<u8>> {
+        let content: String = self
+            .text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        BASE64.decode(content).ok()
+    }
+}
+
+/// Iterate over all (well-formed) PEM blocks found in the given text.
+pub(crate) fn pem_blocks(mut pem: &str) -> impl Iterator<Item = PemBlock<'_>> {
+    std::iter::from_fn(move || {
+        let start = pem.find("-----BEGIN ")?;
+        let rest = &pem[start + "-----BEGIN ".len()..];
+        let label = &rest[..rest.find("-----")?];
+        let end_line = format!("-----END {label}-----");
+        let end = start + pem[start..].find(&end_line)? + end_line.len();
+        let text = &pem[start..end];
+        pem = &pem[end..];
+        Some(PemBlock { label, text })
+    })
+}
+
+/// Encode the given DER data as a PEM block with the given label.
+pub(crate) fn pem_encode(label: &str, der: &[u8]) -> String {
+    let encoded = BASE64.encode(der);
+    let mut pem = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        // base64 output is always ascii
+        pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {label}-----\n"));
+    pem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pem_encode_and_blocks() {
+        let der: Vec<u8> = (0..=255).collect();
+        let pem = format!(
+            "{}garbage\n{}",
+            pem_encode(LABEL_CERTIFICATE, &der),
+            pem_encode(LABEL_PRIVATE_KEY, b"key")
+        );
+
+        let blocks: Vec<_> = pem_blocks(&pem).collect();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].label, LABEL_CERTIFICATE);
+        assert_eq!(blocks[0].decode().unwrap(), der);
+        assert!(blocks[0].text.lines().all(|line| line.len() <= 64));
+        assert_eq!(blocks[1].label, LABEL_PRIVATE_KEY);
+        assert_eq!(blocks[1].decode().unwrap(), b"key");
+
+        assert_eq!(pem_blocks("-----BEGIN FOO-----\nAAAA\n").count(), 0);
+    }
+}
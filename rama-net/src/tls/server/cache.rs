@@ -0,0 +1,560 @@
+use super::ServerAuthData;
+use crate::address::Host;
+use crate::tls::pem::{LABEL_CERTIFICATE, LABEL_PRIVATE_KEY, pem_blocks, pem_encode};
+use crate::tls::{CertificateFingerprint, CertificateInfo, DataEncoding};
+use parking_lot::Mutex;
+use rama_core::error::{ErrorContext, ErrorExt, OpaqueError};
+use rama_utils::str::NonEmptyString;
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+use tokio::{fs, sync::OnceCell};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Key under which issued server certificates are stored in a [`ServerCertCache`].
+pub struct ServerCertCacheKey {
+    /// The host (usually the SNI of the client) the certificate was issued for.
+    pub host: Host,
+    /// Fingerprint of the CA certificate which issued the certificate,
+    /// such that certificates are never served after the issuer changed.
+    ///
+    /// `None` for dynamic issuers, for which the issuer is not known upfront.
+    pub issuer_fingerprint: Option<CertificateFingerprint>,
+}
+
+impl ServerCertCacheKey {
+    /// Create a new [`ServerCertCacheKey`].
+    pub const fn new(host: Host, issuer_fingerprint: Option<CertificateFingerprint>) -> Self {
+        Self {
+            host,
+            issuer_fingerprint,
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Cache of server certificates, as used by [`super::CacheKind::Custom`].
+///
+/// Internally contains the dyn [`ServerCertCache`].
+///
+/// Concurrent [`Self::try_get_with`] calls for the same key
+/// are coalesced, such that a certificate is only issued once.
+pub struct SharedServerCertCache {
+    cache: Arc<dyn DynServerCertCache + Send + Sync>,
+    in_flight: Arc<Mutex<HashMap<ServerCertCacheKey, Arc<InFlightIssue>>>>,
+}
+
+/// The result of an issue, shared with the concurrent issues of the same key.
+///
+/// Errors are shared as their message, as they cannot be cloned.
+type InFlightIssue = OnceCell<Result<ServerAuthData, String>>;
+
+impl SharedServerCertCache {
+    pub fn new<C: ServerCertCache>(cache: C) -> Self {
+        Self {
+            cache: Arc::new(cache),
+            in_flight: Default::default(),
+        }
+    }
+
+    /// Get the certificate stored for the given key, if any.
+    pub async fn get(&self, key: ServerCertCacheKey) -> Option<ServerAuthData> {
+        self.cache.get(key).await
+    }
+
+    /// Store a certificate for the given key, replacing any previous certificate.
+    pub async fn insert(&self, key: ServerCertCacheKey, data: ServerAuthData) {
+        self.cache.insert(key, data).await
+    }
+
+    /// Remove the certificate stored for the given key, if any.
+    pub async fn remove(&self, key: ServerCertCacheKey) {
+        self.cache.remove(key).await
+    }
+
+    /// Get the certificate stored for the given key,
+    /// or issue it using the given future and store it.
+    ///
+    /// Concurrent calls for the same key wait for the certificate
+    /// issued by the first call, instead of each issuing their own.
+    pub async fn try_get_with(
+        &self,
+        key: ServerCertCacheKey,
+        issue: impl Future<Output = Result<ServerAuthData, OpaqueError>>,
+    ) -> Result<ServerAuthData, OpaqueError> {
+        if let Some(data) = self.get(key.clone()).await {
+            return Ok(data);
+        }
+
+        let in_flight = self
+            .in_flight
+            .lock()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut own_result = None;
+        let shared = in_flight
+            .get_or_init(|| async {
+                let result = match issue.await {
+                    Ok(data) => {
+                        self.insert(key.clone(), data.clone()).await;
+                        Ok(data)
+                    }
+                    Err(err) => Err(err),
+                };
+                let shared = match &result {
+                    Ok(data) => Ok(data.clone()),
+                    Err(err) => Err(err.to_string()),
+                };
+                own_result = Some(result);
+                shared
+            })
+            .await;
+
+        match own_result {
+            Some(result) => {
+                self.in_flight.lock().remove(&key);
+                result
+            }
+            None => shared.clone().map_err(OpaqueError::from_display),
+        }
+    }
+}
+
+impl fmt::Debug for SharedServerCertCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedServerCertCache").finish()
+    }
+}
+
+impl<C: ServerCertCache> From<C> for SharedServerCertCache {
+    fn from(cache: C) -> Self {
+        Self::new(cache)
+    }
+}
+
+/// A cache of server certificates issued on the fly by a cert issuer,
+/// used by tls acceptors when configured with [`super::CacheKind::Custom`].
+///
+/// Unlike [`super::CacheKind::MemCache`] such a cache can be persistent
+/// (e.g. [`FsServerCertCache`]) and shared between instances.
+///
+/// Implementations should not return certificates that are expired (or about to).
+pub trait ServerCertCache: Send + Sync + 'static {
+    /// Get the certificate stored for the given key, if any.
+    fn get(
+        &self,
+        key: ServerCertCacheKey,
+    ) -> impl Future<Output = Option<ServerAuthData>> + Send + '_;
+
+    /// Store a certificate for the given key, replacing any previous certificate.
+    fn insert(
+        &self,
+        key: ServerCertCacheKey,
+        data: ServerAuthData,
+    ) -> impl Future<Output = ()> + Send + '_;
+
+    /// Remove the certificate stored for the given key, if any.
+    fn remove(&self, key: ServerCertCacheKey) -> impl Future<Output = ()> + Send + '_;
+}
+
+impl<C: ServerCertCache> ServerCertCache for Arc<C> {
+    fn get(
+        &self,
+        key: ServerCertCacheKey,
+    ) -> impl Future<Output = Option<ServerAuthData>> + Send + '_ {
+        (**self).get(key)
+    }
+
+    fn insert(
+        &self,
+        key: ServerCertCacheKey,
+        data: ServerAuthData,
+    ) -> impl Future<Output = ()> + Send + '_ {
+        (**self).insert(key, data)
+    }
+
+    fn remove(&self, key: ServerCertCacheKey) -> impl Future<Output = ()> + Send + '_ {
+        (**self).remove(key)
+    }
+}
+
+/// Internal trait to support dynamic dispatch of trait with async fn.
+/// See trait [`rama_core::service::svc::DynService`] for more info about this pattern.
+trait DynServerCertCache {
+    fn get(
+        &self,
+        key: ServerCertCacheKey,
+    ) -> Pin<Box<dyn Future<Output = Option<ServerAuthData>> + Send + '_>>;
+
+    fn insert(
+        &self,
+        key: ServerCertCacheKey,
+        data: ServerAuthData,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+
+    fn remove(&self, key: ServerCertCacheKey) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+}
+
+impl<T> DynServerCertCache for T
+where
+    T: ServerCertCache,
+{
+    fn get(
+        &self,
+        key: ServerCertCacheKey,
+    ) -> Pin<Box<dyn Future<Output = Option<ServerAuthData>> + Send + '_>> {
+        Box::pin(ServerCertCache::get(self, key))
+    }
+
+    fn insert(
+        &self,
+        key: ServerCertCacheKey,
+        data: ServerAuthData,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(ServerCertCache::insert(self, key, data))
+    }
+
+    fn remove(&self, key: ServerCertCacheKey) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(ServerCertCache::remove(self, key))
+    }
+}
+
+/// Default minimum remaining validity of certificates served by a [`FsServerCertCache`].
+const DEFAULT_MIN_REMAINING_VALIDITY: Duration = Duration::from_secs(60 * 60 * 24);
+
+#[derive(Debug, Clone)]
+/// A [`ServerCertCache`] storing certificates on the filesystem.
+///
+/// Each certificate is stored as a single PEM file, containing
+/// the certificate chain followed by the private key.
+/// Certificates which expire within the configured minimum remaining validity
+/// (one day by default) are treated as missing and removed from disk.
+///
+/// The directory can be shared between (horizontally scaled) instances,
+/// as files are replaced atomically.
+pub struct FsServerCertCache {
+    dir: PathBuf,
+    min_remaining_validity: Duration,
+}
+
+impl FsServerCertCache {
+    /// Create a new [`FsServerCertCache`] storing certificates in the given directory,
+    /// creating it if it does not exist yet.
+    pub fn try_new(dir: impl Into<PathBuf>) -> Result<Self, OpaqueError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).context("create server cert cache directory")?;
+        Ok(Self {
+            dir,
+            min_remaining_validity: DEFAULT_MIN_REMAINING_VALIDITY,
+        })
+    }
+
+    /// Set the minimum remaining validity of a certificate for it to be served.
+    pub fn set_min_remaining_validity(&mut self, validity: Duration) -> &mut Self {
+        self.min_remaining_validity = validity;
+        self
+    }
+
+    /// Same as [`Self::set_min_remaining_validity`], but consuming self.
+    pub fn with_min_remaining_validity(mut self, validity: Duration) -> Self {
+        self.min_remaining_validity = validity;
+        self
+    }
+
+    /// Return the directory in which certificates are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &ServerCertCacheKey) -> PathBuf {
+        let host: String = key
+            .host
+            .to_string()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let issuer = key
+            .issuer_fingerprint
+            .map(|fingerprint| fingerprint.to_string())
+            .unwrap_or_else(|| "dynamic".to_owned());
+        self.dir.join(format!("{host}.{issuer}.pem"))
+    }
+
+    async fn try_get(&self, path: &Path) -> Result<Option<ServerAuthData>, OpaqueError> {
+        let pem = match fs::read_to_string(path).await {
+            Ok(pem) => pem,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.context("read cached server cert")),
+        };
+
+        let mut cert_chain = String::new();
+        let mut private_key = None;
+        let mut leaf = None;
+        for block in pem_blocks(&pem) {
+            if block.label == LABEL_CERTIFICATE {
+                if leaf.is_none() {
+                    leaf = Some(block.decode().context("decode cached leaf cert")?);
+                }
+                cert_chain.push_str(block.text);
+                cert_chain.push('\n');
+            } else if block.label.ends_with(LABEL_PRIVATE_KEY) {
+                private_key = Some(block.text.to_owned());
+            }
+        }
+        let leaf = leaf.context("no certificate found in cached server cert")?;
+        let private_key = private_key.context("no private key found in cached server cert")?;
+
+        let info = CertificateInfo::try_from_der(&leaf)?;
+        if info.not_after <= SystemTime::now() + self.min_remaining_validity {
+            tracing::debug!(path = %path.display(), "cached server cert expired: remove it");
+            remove_file(path).await;
+            return Ok(None);
+        }
+
+        Ok(Some(ServerAuthData {
+            private_key: DataEncoding::Pem(
+                NonEmptyString::try_from(private_key).context("cached private key")?,
+            ),
+            cert_chain: DataEncoding::Pem(
+                NonEmptyString::try_from(cert_chain).context("cached cert chain")?,
+            ),
+            ocsp: None,
+        }))
+    }
+}
+
+impl ServerCertCache for FsServerCertCache {
+    async fn get(&self, key: ServerCertCacheKey) -> Option<ServerAuthData> {
+        let path = self.path(&key);
+        self.try_get(&path).await.unwrap_or_else(|err| {
+            tracing::warn!(path = %path.display(), error = %err, "failed to load cached server cert");
+            None
+        })
+    }
+
+    async fn insert(&self, key: ServerCertCacheKey, data: ServerAuthData) {
+        let path = self.path(&key);
+        if let Err(err) = write_server_auth_data(&path, &data).await {
+            tracing::warn!(path = %path.display(), error = %err, "failed to store server cert in cache");
+        }
+    }
+
+    async fn remove(&self, key: ServerCertCacheKey) {
+        remove_file(&self.path(&key)).await;
+    }
+}
+
+/// Write the given [`ServerAuthData`] as a single PEM file, replacing it atomically.
+async fn write_server_auth_data(path: &Path, data: &ServerAuthData) -> Result<(), OpaqueError> {
+    let mut pem = match &data.cert_chain {
+        DataEncoding::Pem(pem) => pem_blocks(pem.as_str())
+            .filter(|block| block.label == LABEL_CERTIFICATE)
+            .map(|block| format!("{}\n", block.text))
+            .collect(),
+        DataEncoding::Der(der) => pem_encode(LABEL_CERTIFICATE, der),
+        DataEncoding::DerStack(ders) => ders
+            .iter()
+            .map(|der| pem_encode(LABEL_CERTIFICATE, der))
+            .collect(),
+    };
+    match &data.private_key {
+        DataEncoding::Pem(key) => {
+            let block = pem_blocks(key.as_str())
+                .find(|block| block.label.ends_with(LABEL_PRIVATE_KEY))
+                .context("no private key found in server auth data")?;
+            pem.push_str(block.text);
+            pem.push('\n');
+        }
+        DataEncoding::Der(der) => pem.push_str(&pem_encode(LABEL_PRIVATE_KEY, der)),
+        DataEncoding::DerStack(ders) => pem.push_str(&pem_encode(
+            LABEL_PRIVATE_KEY,
+            ders.first()
+                .context("no private key found in server auth data")?,
+        )),
+    }
+
+    // write to a temporary file first, such that
+    // concurrent readers never observe a partially written file
+    static TMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+    let tmp_path = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    write_private_file(&tmp_path, pem.as_bytes())
+        .await
+        .context("write cached server cert")?;
+    if let Err(err) = fs::rename(&tmp_path, path).await {
+        remove_file(&tmp_path).await;
+        return Err(err.context("rename cached server cert"));
+    }
+    Ok(())
+}
+
+async fn remove_file(path: &Path) {
+    if let Err(err) = fs::remove_file(path).await {
+        if err.kind() != io::ErrorKind::NotFound {
+            tracing::warn!(path = %path.display(), error = %err, "failed to remove cached server cert");
+        }
+    }
+}
+
+/// Write a file only readable by the current user, as it contains a private key.
+async fn write_private_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    use tokio::io::AsyncWriteExt as _;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path).await?;
+    file.write_all(content).await?;
+    // make sure the content is written before the file is renamed
+    file.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Domain;
+    use std::fs;
+    use std::sync::atomic::AtomicUsize;
+
+    fn server_auth_data(not_after: (i32, u8, u8)) -> ServerAuthData {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["example.com".to_owned()]).unwrap();
+        params.not_after = rcgen::date_time_ymd(not_after.0, not_after.1, not_after.2);
+        let cert = params.self_signed(&key_pair).unwrap();
+        ServerAuthData {
+            private_key: DataEncoding::Der(key_pair.serialize_der()),
+            cert_chain: DataEncoding::Pem(cert.pem().try_into().unwrap()),
+            ocsp: None,
+        }
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rama-fs-server-cert-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_fs_server_cert_cache() {
+        let dir = test_dir("roundtrip");
+        let cache = SharedServerCertCache::new(FsServerCertCache::try_new(&dir).unwrap());
+        let key = ServerCertCacheKey::new(
+            Domain::from_static("example.com").into(),
+            Some(CertificateFingerprint::from_der(b"ca")),
+        );
+        let other_issuer_key = ServerCertCacheKey::new(
+            Domain::from_static("example.com").into(),
+            Some(CertificateFingerprint::from_der(b"other ca")),
+        );
+
+        assert!(cache.get(key.clone()).await.is_none());
+
+        let data = server_auth_data((4000, 1, 1));
+        cache.insert(key.clone(), data.clone()).await;
+        let cached = cache.get(key.clone()).await.unwrap();
+        assert_eq!(cached.cert_chain, data.cert_chain);
+        let DataEncoding::Pem(private_key) = cached.private_key else {
+            panic!("unexpected private key encoding");
+        };
+        let DataEncoding::Der(expected_key) = data.private_key else {
+            unreachable!()
+        };
+        assert_eq!(
+            pem_blocks(private_key.as_str())
+                .next()
+                .unwrap()
+                .decode()
+                .unwrap(),
+            expected_key
+        );
+
+        assert!(cache.get(other_issuer_key).await.is_none());
+
+        // a new cache instance (e.g. after a restart) serves the same certificate
+        let cache = SharedServerCertCache::new(FsServerCertCache::try_new(&dir).unwrap());
+        assert!(cache.get(key.clone()).await.is_some());
+
+        cache.remove(key.clone()).await;
+        assert!(cache.get(key.clone()).await.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fs_server_cert_cache_expired() {
+        let dir = test_dir("expired");
+        let cache = SharedServerCertCache::new(FsServerCertCache::try_new(&dir).unwrap());
+        let key = ServerCertCacheKey::new(Domain::from_static("example.com").into(), None);
+
+        cache
+            .insert(key.clone(), server_auth_data((2000, 1, 1)))
+            .await;
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        assert!(cache.get(key.clone()).await.is_none());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shared_server_cert_cache_try_get_with() {
+        let dir = test_dir("shared");
+        let cache = SharedServerCertCache::new(FsServerCertCache::try_new(&dir).unwrap());
+        let key = ServerCertCacheKey::new(Domain::from_static("example.com").into(), None);
+        let issued = AtomicUsize::new(0);
+        let issue = || async {
+            issued.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok(server_auth_data((4000, 1, 1)))
+        };
+
+        // concurrent misses only issue a single cert
+        let (a, b) = tokio::join!(
+            cache.try_get_with(key.clone(), issue()),
+            cache.try_get_with(key.clone(), issue()),
+        );
+        assert_eq!(a.unwrap().cert_chain, b.unwrap().cert_chain);
+        assert_eq!(issued.load(Ordering::SeqCst), 1);
+        assert!(cache.in_flight.lock().is_empty());
+
+        // the issued cert is stored in the cache
+        cache.try_get_with(key.clone(), issue()).await.unwrap();
+        assert_eq!(issued.load(Ordering::SeqCst), 1);
+
+        // failures are not cached
+        cache.remove(key.clone()).await;
+        assert!(
+            cache
+                .try_get_with(key.clone(), async {
+                    Err(OpaqueError::from_display("issue failed"))
+                })
+                .await
+                .is_err()
+        );
+        assert!(cache.get(key).await.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,14 @@
+use super::{ServerCertCache, SharedServerCertCache, SniMap, UpstreamFetcher};
 use crate::{
     address::Host,
-    tls::{ApplicationProtocol, DataEncoding, KeyLogIntent, ProtocolVersion, client::ClientHello},
+    tls::{
+        ApplicationProtocol, DataEncoding, EchKeySet, KeyLogIntent, ProtocolVersion,
+        client::ClientHello,
+    },
 };
 use rama_core::error::OpaqueError;
 use serde::{Deserialize, Serialize};
-use std::{num::NonZeroU64, pin::Pin, sync::Arc};
+use std::{num::NonZeroU64, pin::Pin, sync::Arc, time::Duration};
 
 #[derive(Debug, Clone)]
 /// Common API to configure a TLS Server
@@ -32,6 +36,14 @@ pub struct ServerConfig {
 
     /// store client certificate chain
     pub store_client_certificate_chain: bool,
+
+    /// optional keys to decrypt Encrypted Client Hello (ECH) with
+    ///
+    /// The [`EchConfigList`] of these keys is to be published by the server,
+    /// e.g. in its DNS 'HTTPS' records, see [`EchKeySet::ech_config_list`].
+    ///
+    /// [`EchConfigList`]: crate::tls::EchConfigList
+    pub ech_keys: Option<EchKeySet>,
 }
 
 impl ServerConfig {
@@ -45,6 +57,7 @@ impl ServerConfig {
             client_verify_mode: ClientVerifyMode::default(),
             key_logger: KeyLogIntent::default(),
             store_client_certificate_chain: false,
+            ech_keys: None,
         }
     }
 }
@@ -58,6 +71,9 @@ pub enum ServerAuth {
     Single(ServerAuthData),
     /// Issuer which provides certs on the fly
     CertIssuer(ServerCertIssuerData),
+    /// Data selected based on the server name (SNI) sent by the client,
+    /// parsed once by the tls implementation when creating the acceptor
+    Sni(SniMap<ServerAuthData>),
 }
 
 impl Default for ServerAuth {
@@ -77,8 +93,19 @@ pub struct ServerCertIssuerData {
 #[derive(Debug, Clone)]
 /// Cache kind that will be used to cache results of certificate issuers
 pub enum CacheKind {
-    MemCache { max_size: NonZeroU64 },
+    MemCache {
+        max_size: NonZeroU64,
+    },
     Disabled,
+    /// Use a custom [`ServerCertCache`], e.g. a persistent [`super::FsServerCertCache`].
+    Custom(SharedServerCertCache),
+}
+
+impl CacheKind {
+    /// Create a [`CacheKind::Custom`] using the given [`ServerCertCache`].
+    pub fn custom<C: ServerCertCache>(cache: C) -> Self {
+        Self::Custom(SharedServerCertCache::new(cache))
+    }
 }
 
 impl Default for CacheKind {
@@ -99,6 +126,44 @@ pub enum ServerCertIssuerKind {
     Single(ServerAuthData),
     /// A dynamic data provider which can decide depending on client hello msg
     Dynamic(DynamicIssuer),
+    /// Issue leaf certs on the fly, signed by the given certificate authority (CA),
+    /// as is required to intercept (MITM) tls traffic for clients which trust the CA.
+    ///
+    /// The issued certs use the server name (SNI) as common name and mirror
+    /// the subject alternative names of the upstream leaf cert, fetched using
+    /// the `upstream` fetcher. The server name is always added as subject alternative name,
+    /// and is the only one in case no `upstream` fetcher is configured.
+    CertificateAuthority {
+        /// certificate of the CA used to sign the issued certs,
+        /// optionally followed by the chain of the CA itself
+        ca_cert: DataEncoding,
+        /// private key of the CA
+        ca_key: DataEncoding,
+        /// how long the issued certs are valid, starting from the moment of issuing
+        validity: Duration,
+        /// type of the key pair generated for each issued cert
+        key_type: ServerCertKeyType,
+        /// fetcher of the upstream leaf cert to mirror the subject alternative names of
+        upstream: Option<UpstreamFetcher>,
+    },
+    /// Issue leaf certs on the fly, signed by the given certificate authority (CA),
+    /// mirroring the certificate of the upstream server.
+    ///
+    /// The subject, subject alternative names and validity window are copied
+    /// from the upstream leaf cert, such that clients see a cert which mostly
+    /// differs from the real one in its key and issuer. The key usage is not copied,
+    /// but derived from the `key_type` of the generated key, as is fitting for a leaf cert.
+    MirrorUpstream {
+        /// certificate of the CA used to sign the issued certs,
+        /// optionally followed by the chain of the CA itself
+        ca_cert: DataEncoding,
+        /// private key of the CA
+        ca_key: DataEncoding,
+        /// type of the key pair generated for each issued cert
+        key_type: ServerCertKeyType,
+        /// fetcher of the upstream leaf cert to mirror
+        upstream: UpstreamFetcher,
+    },
 }
 
 impl Default for ServerCertIssuerKind {
@@ -116,6 +181,20 @@ where
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Type of the key pair generated for certs issued on the fly.
+pub enum ServerCertKeyType {
+    #[default]
+    /// ECDSA key using the P-256 curve
+    EcdsaP256,
+    /// ECDSA key using the P-384 curve
+    EcdsaP384,
+    /// RSA key with a 2048-bit modulus
+    Rsa2048,
+    /// RSA key with a 4096-bit modulus
+    Rsa4096,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 /// Data that can be used to configure the self-signed single data
 pub struct SelfSignedData {
@@ -0,0 +1,193 @@
+//! [`Matcher`]s implementations to match on the [`ClientHello`]
+//! of a (possibly not yet terminated) TLS connection.
+//!
+//! The [`ClientHello`] is looked up in the [`Context`], as inserted by the
+//! [`PeekClientHelloService`], or as part of the [`SecureTransport`]
+//! inserted by tls acceptors configured to store the client hello.
+//!
+//! [`Matcher`]: rama_core::matcher::Matcher
+//! [`PeekClientHelloService`]: super::PeekClientHelloService
+
+use crate::address::{Domain, Host};
+use crate::fingerprint::Ja4;
+use crate::tls::client::ClientHello;
+use crate::tls::{ApplicationProtocol, SecureTransport};
+use rama_core::{Context, context::Extensions};
+
+fn client_hello_from_ctx<State>(ctx: &Context<State>) -> Option<&ClientHello> {
+    ctx.get::<ClientHello>().or_else(|| {
+        ctx.get::<SecureTransport>()
+            .and_then(|st| st.client_hello())
+    })
+}
+
+#[derive(Debug, Clone)]
+/// Matcher based on the server name (SNI) of the [`ClientHello`].
+pub struct SniMatcher {
+    domain: Domain,
+    sub: bool,
+}
+
+impl SniMatcher {
+    /// create a new SNI matcher to match on an exact server name match.
+    ///
+    /// If the server name is an Ip or missing it will not match.
+    pub fn exact(domain: Domain) -> Self {
+        Self { domain, sub: false }
+    }
+
+    /// create a new SNI matcher to match on a subdomain of the server name.
+    ///
+    /// Note that a domain is also a subdomain of itself, so this will also
+    /// include all matches that [`Self::exact`] would capture.
+    pub fn sub(domain: Domain) -> Self {
+        Self { domain, sub: true }
+    }
+}
+
+impl<State, Request> rama_core::matcher::Matcher<State, Request> for SniMatcher {
+    fn matches(&self, _ext: Option<&mut Extensions>, ctx: &Context<State>, _req: &Request) -> bool {
+        match client_hello_from_ctx(ctx).and_then(|hello| hello.ext_server_name()) {
+            Some(Host::Name(domain)) => {
+                if self.sub {
+                    tracing::trace!("SniMatcher: ({}).is_parent_of({})", self.domain, domain);
+                    self.domain.is_parent_of(domain)
+                } else {
+                    tracing::trace!("SniMatcher: ({}) == ({})", self.domain, domain);
+                    self.domain == *domain
+                }
+            }
+            Some(Host::Address(_)) | None => {
+                tracing::trace!("SniMatcher: no server name domain found");
+                false
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Matcher based on the application protocols (ALPN) offered in the [`ClientHello`].
+pub struct AlpnMatcher {
+    protocol: ApplicationProtocol,
+}
+
+impl AlpnMatcher {
+    /// create a new ALPN matcher to match if the given
+    /// [`ApplicationProtocol`] is offered by the client.
+    pub const fn new(protocol: ApplicationProtocol) -> Self {
+        Self { protocol }
+    }
+}
+
+impl<State, Request> rama_core::matcher::Matcher<State, Request> for AlpnMatcher {
+    fn matches(&self, _ext: Option<&mut Extensions>, ctx: &Context<State>, _req: &Request) -> bool {
+        client_hello_from_ctx(ctx)
+            .and_then(|hello| hello.ext_alpn())
+            .map(|protocols| protocols.contains(&self.protocol))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Matcher based on the [`Ja4`] fingerprint of the [`ClientHello`].
+pub struct Ja4Matcher {
+    fingerprint: String,
+}
+
+impl Ja4Matcher {
+    /// create a new [`Ja4`] matcher to match on the given fingerprint,
+    /// either in its hashed form (e.g. `t13d1516h2_8daaf6152771_e5627efa2ab1`)
+    /// or in its raw (human) form.
+    pub fn new(fingerprint: impl Into<String>) -> Self {
+        Self {
+            fingerprint: fingerprint.into(),
+        }
+    }
+}
+
+impl<State, Request> rama_core::matcher::Matcher<State, Request> for Ja4Matcher {
+    fn matches(&self, _ext: Option<&mut Extensions>, ctx: &Context<State>, _req: &Request) -> bool {
+        let Some(client_hello) = client_hello_from_ctx(ctx) else {
+            return false;
+        };
+        match Ja4::compute_from_client_hello(client_hello, None) {
+            Ok(ja4) => {
+                let (hash, human) = (ja4.to_string(), ja4.to_human_string());
+                tracing::trace!("Ja4Matcher: ({}) in [{hash}, {human}]", self.fingerprint);
+                self.fingerprint == hash || self.fingerprint == human
+            }
+            Err(err) => {
+                tracing::debug!(error = %err, "Ja4Matcher: failed to compute ja4 fingerprint");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::client::parse_client_hello;
+    use crate::tls::server::peek::tests::client_hello_body;
+    use rama_core::matcher::Matcher;
+
+    fn ctx_with_client_hello() -> Context<()> {
+        let mut ctx = Context::default();
+        ctx.insert(parse_client_hello(&client_hello_body("www.example.com", "h2")).unwrap());
+        ctx
+    }
+
+    #[test]
+    fn test_sni_matcher() {
+        let ctx = ctx_with_client_hello();
+        for (matcher, expected) in [
+            (
+                SniMatcher::exact(Domain::from_static("www.example.com")),
+                true,
+            ),
+            (SniMatcher::exact(Domain::from_static("example.com")), false),
+            (SniMatcher::sub(Domain::from_static("example.com")), true),
+            (
+                SniMatcher::sub(Domain::from_static("www.example.com")),
+                true,
+            ),
+            (SniMatcher::sub(Domain::from_static("example.org")), false),
+        ] {
+            assert_eq!(matcher.matches(None, &ctx, &()), expected, "{matcher:?}");
+            assert!(!matcher.matches(None, &Context::default(), &()));
+        }
+    }
+
+    #[test]
+    fn test_sni_matcher_secure_transport() {
+        let mut ctx = Context::default();
+        ctx.insert(SecureTransport::with_client_hello(
+            parse_client_hello(&client_hello_body("example.com", "h2")).unwrap(),
+        ));
+        assert!(SniMatcher::exact(Domain::from_static("example.com")).matches(None, &ctx, &()));
+    }
+
+    #[test]
+    fn test_alpn_matcher() {
+        let ctx = ctx_with_client_hello();
+        assert!(AlpnMatcher::new(ApplicationProtocol::HTTP_2).matches(None, &ctx, &()));
+        assert!(!AlpnMatcher::new(ApplicationProtocol::HTTP_11).matches(None, &ctx, &()));
+        assert!(!AlpnMatcher::new(ApplicationProtocol::HTTP_2).matches(
+            None,
+            &Context::<()>::default(),
+            &()
+        ));
+    }
+
+    #[test]
+    fn test_ja4_matcher() {
+        let ctx = ctx_with_client_hello();
+        let ja4 =
+            Ja4::compute_from_client_hello(client_hello_from_ctx(&ctx).unwrap(), None).unwrap();
+        assert!(ja4.to_string().starts_with("t12d0202h2_"));
+
+        assert!(Ja4Matcher::new(ja4.to_string()).matches(None, &ctx, &()));
+        assert!(Ja4Matcher::new(ja4.to_human_string()).matches(None, &ctx, &()));
+        assert!(!Ja4Matcher::new("t13d1516h2_8daaf6152771_e5627efa2ab1").matches(None, &ctx, &()));
+    }
+}
@@ -4,5 +4,35 @@ mod config;
 #[doc(inline)]
 pub use config::{
     CacheKind, ClientVerifyMode, DynamicCertIssuer, DynamicIssuer, SelfSignedData, ServerAuth,
-    ServerAuthData, ServerCertIssuerData, ServerCertIssuerKind, ServerConfig,
+    ServerAuthData, ServerCertIssuerData, ServerCertIssuerKind, ServerCertKeyType, ServerConfig,
 };
+
+mod cache;
+#[doc(inline)]
+pub use cache::{FsServerCertCache, ServerCertCache, ServerCertCacheKey, SharedServerCertCache};
+
+mod upstream;
+#[doc(inline)]
+pub use upstream::{ConnectorUpstreamCertFetcher, UpstreamCertFetcher, UpstreamFetcher};
+
+mod watch;
+#[doc(inline)]
+pub use watch::FileWatchingCertIssuer;
+
+mod sni;
+#[doc(inline)]
+pub use sni::{ServerNamePattern, SniMap};
+
+mod peek;
+#[doc(inline)]
+pub use peek::{PeekClientHelloLayer, PeekClientHelloService};
+
+mod matcher;
+#[doc(inline)]
+pub use matcher::{AlpnMatcher, Ja4Matcher, SniMatcher};
+
+#[cfg(feature = "http")]
+mod ocsp;
+#[cfg(feature = "http")]
+#[doc(inline)]
+pub use ocsp::OcspStapler;
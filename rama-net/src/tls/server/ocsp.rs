@@ -0,0 +1,345 @@
+use super::{CacheKind, DynamicCertIssuer, ServerAuthData, ServerCertIssuerData};
+use crate::address::Host;
+use crate::tls::client::ClientHello;
+use crate::tls::ocsp::{OcspCertId, OcspCertStatus, OcspResponse};
+use crate::tls::pem::{LABEL_CERTIFICATE, pem_blocks};
+use crate::tls::{CertificateInfo, DataEncoding};
+use arc_swap::ArcSwap;
+use rama_core::error::{BoxError, ErrorContext, OpaqueError};
+use rama_core::{Context, Service};
+use rama_http_types::dep::http_body;
+use rama_http_types::dep::http_body_util::{BodyExt, Limited};
+use rama_http_types::{Body, Method, Request, Response, Uri, header::CONTENT_TYPE};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// Interval at which OCSP responses are refreshed,
+/// if the responder does not define when newer information is available.
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Minimum interval between two refreshes of an OCSP response.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// Interval after which a failed refresh of an OCSP response is retried.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Maximum size of an OCSP response read from an OCSP responder.
+const MAX_OCSP_RESPONSE_SIZE: usize = 64 * 1024;
+
+#[derive(Clone)]
+/// A [`DynamicCertIssuer`] serving the given [`ServerAuthData`], stapled with
+/// the OCSP response fetched from the OCSP responder of its leaf certificate.
+///
+/// The OCSP response is (re)fetched by calling [`Self::refresh`],
+/// or automatically by running the future returned by [`Self::run`],
+/// using any http client [`Service`]. The previously fetched response
+/// keeps being stapled in case a refresh fails.
+///
+/// Responses are only checked to be about the served certificate and to be
+/// valid at the time they are fetched: the signature of the response is not verified,
+/// as this is up to the clients which receive the stapled response.
+///
+/// Converting this issuer into [`ServerCertIssuerData`] disables caching
+/// using [`CacheKind::Disabled`], as refreshed OCSP responses would otherwise
+/// not be stapled for hosts for which a cert was already cached.
+pub struct OcspStapler {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    cert_id: OcspCertId,
+    responder: Uri,
+    data: ArcSwap<ServerAuthData>,
+}
+
+impl OcspStapler {
+    /// Try to create a new [`OcspStapler`] for the given [`ServerAuthData`],
+    /// using the OCSP responder found in the authority information access
+    /// extension of its leaf certificate.
+    ///
+    /// The certificate chain has to contain the issuer of the leaf certificate.
+    pub fn try_new(data: ServerAuthData) -> Result<Self, OpaqueError> {
+        let chain = cert_chain_der(&data.cert_chain)?;
+        let responder = CertificateInfo::try_from_der(&chain[0])
+            .context("parse leaf cert")?
+            .ocsp_responders
+            .into_iter()
+            .next()
+            .context("no ocsp responder found in leaf cert")?
+            .parse()
+            .context("parse ocsp responder uri")?;
+        Self::try_new_inner(data, &chain, responder)
+    }
+
+    /// Same as [`Self::try_new`], but using the given OCSP responder
+    /// instead of the one found in the leaf certificate.
+    pub fn try_new_with_responder(
+        data: ServerAuthData,
+        responder: Uri,
+    ) -> Result<Self, OpaqueError> {
+        let chain = cert_chain_der(&data.cert_chain)?;
+        Self::try_new_inner(data, &chain, responder)
+    }
+
+    fn try_new_inner(
+        data: ServerAuthData,
+        chain: &[Vec<u8>],
+        responder: Uri,
+    ) -> Result<Self, OpaqueError> {
+        let leaf_issuer = CertificateInfo::try_from_der(&chain[0])
+            .context("parse leaf cert")?
+            .issuer;
+        let issuer = chain[1..]
+            .iter()
+            .find(|cert| {
+                CertificateInfo::try_from_der(cert).is_ok_and(|info| info.subject == leaf_issuer)
+            })
+            .context("issuer of leaf cert not found in cert chain")?;
+        let cert_id = OcspCertId::try_new(&chain[0], issuer).context("create ocsp cert id")?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                cert_id,
+                responder,
+                data: ArcSwap::from_pointee(data),
+            }),
+        })
+    }
+
+    /// Return the certificate chain, private key and OCSP response currently served.
+    pub fn server_auth_data(&self) -> Arc<ServerAuthData> {
+        self.inner.data.load_full()
+    }
+
+    /// Fetch a new OCSP response from the OCSP responder using the given http client,
+    /// returning after how long the response should be refreshed.
+    ///
+    /// In case of an error the previously fetched response keeps being stapled.
+    pub async fn refresh<S, B>(&self, client: &S) -> Result<Duration, OpaqueError>
+    where
+        S: Service<(), Request, Response = Response<B>, Error: Into<BoxError>>,
+        B: http_body::Body<Data: Send, Error: Into<BoxError>> + Send,
+    {
+        let request_der = self
+            .inner
+            .cert_id
+            .to_ocsp_request_der()
+            .context("encode ocsp request")?;
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(self.inner.responder.clone())
+            .header(CONTENT_TYPE, "application/ocsp-request")
+            .body(Body::from(request_der))
+            .context("build ocsp request")?;
+        let response = client
+            .serve(Context::default(), request)
+            .await
+            .map_err(|err| OpaqueError::from_boxed(err.into()))
+            .context("send ocsp request")?;
+        if !response.status().is_success() {
+            return Err(OpaqueError::from_display(format!(
+                "ocsp responder returned status {}",
+                response.status()
+            )));
+        }
+        let der = Limited::new(response.into_body(), MAX_OCSP_RESPONSE_SIZE)
+            .collect()
+            .await
+            .map_err(OpaqueError::from_boxed)
+            .context("read ocsp response")?
+            .to_bytes();
+
+        let now = SystemTime::now();
+        let ocsp = OcspResponse::try_from_der(&der).context("parse ocsp response")?;
+        let single = ocsp
+            .find(&self.inner.cert_id)
+            .context("ocsp response has no status for served cert")?;
+        if !single.is_valid_at(now) {
+            return Err(OpaqueError::from_display(
+                "ocsp response is not valid at this time",
+            ));
+        }
+        if single.cert_status != OcspCertStatus::Good {
+            tracing::warn!(
+                cert_status = ?single.cert_status,
+                "ocsp responder reports served cert as not good"
+            );
+        }
+        let refresh_in = single
+            .next_update
+            .and_then(|next_update| next_update.duration_since(now).ok())
+            .map(|valid_for| (valid_for / 2).max(MIN_REFRESH_INTERVAL))
+            .unwrap_or(DEFAULT_REFRESH_INTERVAL);
+
+        let mut data = self.server_auth_data().as_ref().clone();
+        data.ocsp = Some(der.to_vec());
+        self.inner.data.store(Arc::new(data));
+        tracing::debug!(
+            responder = %self.inner.responder,
+            ?refresh_in,
+            "refreshed stapled ocsp response"
+        );
+        Ok(refresh_in)
+    }
+
+    /// Keep the stapled OCSP response fresh, using the given http client,
+    /// refreshing it halfway its validity period and retrying failed refreshes every minute.
+    ///
+    /// The returned future runs until dropped, e.g. until a graceful shutdown is triggered.
+    pub async fn run<S, B>(self, client: S)
+    where
+        S: Service<(), Request, Response = Response<B>, Error: Into<BoxError>>,
+        B: http_body::Body<Data: Send, Error: Into<BoxError>> + Send,
+    {
+        loop {
+            let refresh_in = match self.refresh(&client).await {
+                Ok(refresh_in) => refresh_in,
+                Err(err) => {
+                    tracing::error!(
+                        error = %err,
+                        responder = %self.inner.responder,
+                        "failed to refresh ocsp response: keep stapling previous response"
+                    );
+                    RETRY_INTERVAL
+                }
+            };
+            tokio::time::sleep(refresh_in).await;
+        }
+    }
+}
+
+impl fmt::Debug for OcspStapler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OcspStapler")
+            .field("cert_id", &self.inner.cert_id)
+            .field("responder", &self.inner.responder)
+            .finish()
+    }
+}
+
+impl DynamicCertIssuer for OcspStapler {
+    fn issue_cert(
+        &self,
+        _client_hello: ClientHello,
+        _server_name: Option<Host>,
+    ) -> impl Future<Output = Result<ServerAuthData, OpaqueError>> + Send + Sync + '_ {
+        std::future::ready(Ok(self.server_auth_data().as_ref().clone()))
+    }
+}
+
+impl From<OcspStapler> for ServerCertIssuerData {
+    fn from(stapler: OcspStapler) -> Self {
+        Self {
+            kind: stapler.into(),
+            cache_kind: CacheKind::Disabled,
+        }
+    }
+}
+
+/// Decode the given certificate chain into its DER-encoded certificates,
+/// of which there are at least two: the leaf and (normally) its issuer.
+fn cert_chain_der(chain: &DataEncoding) -> Result<Vec<Vec<u8>>, OpaqueError> {
+    let chain = match chain {
+        DataEncoding::Der(der) => vec![der.clone()],
+        DataEncoding::DerStack(ders) => ders.clone(),
+        DataEncoding::Pem(pem) => pem_blocks(pem.as_str())
+            .filter(|block| block.label == LABEL_CERTIFICATE)
+            .map(|block| block.decode().context("decode cert"))
+            .collect::<Result<_, _>>()?,
+    };
+    if chain.len() < 2 {
+        return Err(OpaqueError::from_display(
+            "cert chain has to contain the issuer of the leaf cert for ocsp stapling",
+        ));
+    }
+    Ok(chain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::ocsp::tests::{ca_and_leaf, good, ocsp_response_der};
+    use rama_core::service::service_fn;
+    use rama_utils::str::NonEmptyString;
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn test_ocsp_stapler_refresh() {
+        let (ca, ca_key, leaf) = ca_and_leaf("http://ocsp.example.com/responder");
+        let cert_id = OcspCertId::try_new(leaf.der(), ca.der()).unwrap();
+        let response = ocsp_response_der(&cert_id, &good(), "20340101000000Z", &ca_key, &[]);
+
+        let stapler = OcspStapler::try_new(ServerAuthData {
+            private_key: DataEncoding::Pem(
+                NonEmptyString::try_from(rcgen::KeyPair::generate().unwrap().serialize_pem())
+                    .unwrap(),
+            ),
+            cert_chain: DataEncoding::Pem(
+                NonEmptyString::try_from(format!("{}{}", leaf.pem(), ca.pem())).unwrap(),
+            ),
+            ocsp: None,
+        })
+        .unwrap();
+        assert!(stapler.server_auth_data().ocsp.is_none());
+
+        let expected_request = cert_id.to_ocsp_request_der().unwrap();
+        let stapled_response = response.clone();
+        let responder = service_fn(move |req: Request| {
+            let expected_request = expected_request.clone();
+            let response = stapled_response.clone();
+            async move {
+                assert_eq!(req.method(), Method::POST);
+                assert_eq!(req.uri(), "http://ocsp.example.com/responder");
+                assert_eq!(
+                    req.headers().get(CONTENT_TYPE).unwrap(),
+                    "application/ocsp-request"
+                );
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                assert_eq!(body.as_ref(), expected_request.as_slice());
+                Ok::<_, Infallible>(Response::new(Body::from(response)))
+            }
+        });
+        let refresh_in = stapler.refresh(&responder).await.unwrap();
+        assert!(refresh_in > Duration::from_secs(24 * 60 * 60));
+        assert_eq!(
+            stapler.server_auth_data().ocsp.as_deref(),
+            Some(response.as_slice())
+        );
+
+        // failed refreshes keep the previous response stapled
+        let failing = service_fn(async |_: Request| {
+            Ok::<_, Infallible>(Response::new(Body::from("not an ocsp response")))
+        });
+        assert!(stapler.refresh(&failing).await.is_err());
+        assert_eq!(
+            stapler.server_auth_data().ocsp.as_deref(),
+            Some(response.as_slice())
+        );
+
+        // oversized responses are not read completely
+        let oversized = service_fn(async |_: Request| {
+            Ok::<_, Infallible>(Response::new(Body::from(vec![
+                0;
+                MAX_OCSP_RESPONSE_SIZE + 1
+            ])))
+        });
+        assert!(stapler.refresh(&oversized).await.is_err());
+        assert_eq!(
+            stapler.server_auth_data().ocsp.as_deref(),
+            Some(response.as_slice())
+        );
+
+        let data = ServerCertIssuerData::from(stapler);
+        assert!(matches!(data.cache_kind, CacheKind::Disabled));
+    }
+
+    #[test]
+    fn test_ocsp_stapler_requires_issuer() {
+        let (_, _, leaf) = ca_and_leaf("http://ocsp.example.com");
+        let data = ServerAuthData {
+            private_key: DataEncoding::Der(vec![1, 2, 3]),
+            cert_chain: DataEncoding::Der(leaf.der().to_vec()),
+            ocsp: None,
+        };
+        assert!(OcspStapler::try_new(data).is_err());
+    }
+}
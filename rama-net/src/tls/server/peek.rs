@@ -0,0 +1,345 @@
+use crate::stream::{ChainReader, HeapReader, Stream};
+use crate::tls::client::{ClientHello, parse_client_hello};
+use rama_core::{
+    Context, Layer, Service,
+    error::{BoxError, ErrorContext, OpaqueError},
+};
+use std::fmt;
+use tokio::io::AsyncReadExt;
+
+/// Content type of a TLS record containing handshake messages.
+const CONTENT_TYPE_HANDSHAKE: u8 = 0x16;
+/// Handshake message type of a TLS ClientHello.
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 0x01;
+/// Length of a TLS record header: content type (1), version (2) and length (2).
+const RECORD_HEADER_LEN: usize = 5;
+/// Length of a TLS handshake message header: message type (1) and length (3).
+const HANDSHAKE_HEADER_LEN: usize = 4;
+/// Maximum length of a (plaintext) TLS record fragment.
+const MAX_RECORD_LEN: usize = 1 << 14;
+/// Maximum length of a ClientHello handshake message accepted by the [`PeekClientHelloService`].
+const MAX_CLIENT_HELLO_LEN: usize = 64 * 1024;
+
+/// Layer to peek the TLS [`ClientHello`] of incoming streams,
+/// without terminating the TLS connection.
+///
+/// See [`PeekClientHelloService`] for more information.
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct PeekClientHelloLayer;
+
+impl PeekClientHelloLayer {
+    /// Create a new [`PeekClientHelloLayer`].
+    pub const fn new() -> Self {
+        PeekClientHelloLayer
+    }
+}
+
+impl<S> Layer<S> for PeekClientHelloLayer {
+    type Service = PeekClientHelloService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PeekClientHelloService { inner }
+    }
+}
+
+/// Service to peek the TLS [`ClientHello`] of incoming streams,
+/// without terminating the TLS connection.
+///
+/// The [`ClientHello`] is read from the stream and inserted into the [`Context`],
+/// after which the stream is passed, with all bytes read replayed, to the inner service.
+/// This allows to route TLS traffic based on the [`ClientHello`],
+/// e.g. by using a [`MatcherRouter`] with the [`SniMatcher`], [`AlpnMatcher`]
+/// and [`Ja4Matcher`], to forward the (still encrypted) stream to a backend.
+///
+/// Streams which do not start with a TLS handshake record are passed as-is
+/// to the inner service, without a [`ClientHello`] inserted into the [`Context`].
+///
+/// [`MatcherRouter`]: rama_core::matcher::MatcherRouter
+/// [`SniMatcher`]: super::SniMatcher
+/// [`AlpnMatcher`]: super::AlpnMatcher
+/// [`Ja4Matcher`]: super::Ja4Matcher
+pub struct PeekClientHelloService<S> {
+    inner: S,
+}
+
+impl<S> PeekClientHelloService<S> {
+    /// Create a new [`PeekClientHelloService`] with the given inner service.
+    pub const fn new(inner: S) -> Self {
+        PeekClientHelloService { inner }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for PeekClientHelloService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PeekClientHelloService")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for PeekClientHelloService<S> {
+    fn clone(&self) -> Self {
+        PeekClientHelloService {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<State, S, IO> Service<State, IO> for PeekClientHelloService<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<
+            State,
+            tokio::io::Join<
+                ChainReader<HeapReader, tokio::io::ReadHalf<IO>>,
+                tokio::io::WriteHalf<IO>,
+            >,
+            Error: Into<BoxError>,
+        >,
+    IO: Stream + Unpin,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<State>,
+        mut stream: IO,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut buffer = Vec::with_capacity(1024);
+        match peek_client_hello(&mut stream, &mut buffer).await? {
+            Some(client_hello) => {
+                tracing::trace!(
+                    server_name = ?client_hello.ext_server_name(),
+                    "peeked tls client hello"
+                );
+                ctx.insert(client_hello);
+            }
+            None => {
+                tracing::trace!("no tls handshake record: pass stream without client hello");
+            }
+        }
+
+        // replay all data read so far
+        let (r, w) = tokio::io::split(stream);
+        let mem: HeapReader = buffer.into();
+        let r = ChainReader::new(mem, r);
+        let stream = tokio::io::join(r, w);
+
+        self.inner.serve(ctx, stream).await.map_err(Into::into)
+    }
+}
+
+/// Read the TLS records containing the [`ClientHello`] from the given stream.
+///
+/// All bytes read are stored in the given buffer, such that these can be replayed.
+/// Returns `None` if the stream does not start with a TLS handshake record.
+async fn peek_client_hello<IO>(
+    stream: &mut IO,
+    buffer: &mut Vec<u8>,
+) -> Result<Option<ClientHello>, OpaqueError>
+where
+    IO: Stream + Unpin,
+{
+    read_at_least(stream, buffer, 1).await?;
+    if buffer[0] != CONTENT_TYPE_HANDSHAKE {
+        return Ok(None);
+    }
+
+    // a ClientHello can be fragmented over multiple records
+    let mut handshake = Vec::new();
+    let mut offset = 0;
+    loop {
+        read_at_least(stream, buffer, offset + RECORD_HEADER_LEN).await?;
+        let header = &buffer[offset..offset + RECORD_HEADER_LEN];
+        if header[0] != CONTENT_TYPE_HANDSHAKE {
+            return Err(OpaqueError::from_display(
+                "unexpected tls record content type: expected handshake",
+            ));
+        }
+        let record_len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        if record_len == 0 || record_len > MAX_RECORD_LEN {
+            return Err(OpaqueError::from_display(format!(
+                "invalid tls handshake record length: {record_len}"
+            )));
+        }
+
+        offset += RECORD_HEADER_LEN;
+        read_at_least(stream, buffer, offset + record_len).await?;
+        handshake.extend_from_slice(&buffer[offset..offset + record_len]);
+        offset += record_len;
+
+        if handshake.len() < HANDSHAKE_HEADER_LEN {
+            continue;
+        }
+        if handshake[0] != HANDSHAKE_TYPE_CLIENT_HELLO {
+            return Err(OpaqueError::from_display(
+                "unexpected tls handshake message type: expected client hello",
+            ));
+        }
+        let message_len =
+            u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+        if message_len > MAX_CLIENT_HELLO_LEN {
+            return Err(OpaqueError::from_display(format!(
+                "tls client hello too large: {message_len} bytes"
+            )));
+        }
+        if handshake.len() >= HANDSHAKE_HEADER_LEN + message_len {
+            let client_hello = parse_client_hello(
+                &handshake[HANDSHAKE_HEADER_LEN..HANDSHAKE_HEADER_LEN + message_len],
+            )?;
+            return Ok(Some(client_hello));
+        }
+    }
+}
+
+/// Read from the given stream until the buffer contains at least `len` bytes.
+async fn read_at_least<IO>(
+    stream: &mut IO,
+    buffer: &mut Vec<u8>,
+    len: usize,
+) -> Result<(), OpaqueError>
+where
+    IO: Stream + Unpin,
+{
+    while buffer.len() < len {
+        buffer.reserve(len - buffer.len());
+        let n = stream
+            .read_buf(buffer)
+            .await
+            .context("read tls client hello")?;
+        if n == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+                .context("tls client hello incomplete");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::address::{Domain, Host};
+    use crate::tls::ApplicationProtocol;
+    use rama_core::service::service_fn;
+    use std::convert::Infallible;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Create the raw ClientHello handshake message body for the given server name and ALPN.
+    pub(in crate::tls::server) fn client_hello_body(server_name: &str, alpn: &str) -> Vec<u8> {
+        fn with_u16_len(data: &[u8]) -> Vec<u8> {
+            let mut v = (data.len() as u16).to_be_bytes().to_vec();
+            v.extend_from_slice(data);
+            v
+        }
+
+        let mut sni = vec![0x00];
+        sni.extend(with_u16_len(server_name.as_bytes()));
+        let mut alpn_list = vec![alpn.len() as u8];
+        alpn_list.extend_from_slice(alpn.as_bytes());
+
+        let mut extensions = vec![0x00, 0x00];
+        extensions.extend(with_u16_len(&with_u16_len(&sni)));
+        extensions.extend([0x00, 0x10]);
+        extensions.extend(with_u16_len(&with_u16_len(&alpn_list)));
+
+        let mut body = vec![0x03, 0x03];
+        body.extend([0x42; 32]); // random
+        body.push(0x00); // session id
+        body.extend(with_u16_len(&[0x13, 0x01, 0x13, 0x02]));
+        body.extend([0x01, 0x00]); // compression methods
+        body.extend(with_u16_len(&extensions));
+        body
+    }
+
+    /// Wrap the given ClientHello body as a handshake message in TLS records,
+    /// fragmented in records of at most `fragment_len` bytes.
+    fn client_hello_records(body: &[u8], fragment_len: usize) -> Vec<u8> {
+        let mut message = vec![HANDSHAKE_TYPE_CLIENT_HELLO];
+        message.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        message.extend_from_slice(body);
+
+        let mut records = Vec::new();
+        for fragment in message.chunks(fragment_len) {
+            records.extend([CONTENT_TYPE_HANDSHAKE, 0x03, 0x01]);
+            records.extend_from_slice(&(fragment.len() as u16).to_be_bytes());
+            records.extend_from_slice(fragment);
+        }
+        records
+    }
+
+    async fn read_to_end<IO: Stream + Unpin>(
+        ctx: Context<()>,
+        mut stream: IO,
+    ) -> Result<(Option<ClientHello>, Vec<u8>), Infallible> {
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await.unwrap();
+        Ok((ctx.get::<ClientHello>().cloned(), data))
+    }
+
+    async fn serve_peek(input: Vec<u8>) -> Result<(Option<ClientHello>, Vec<u8>), BoxError> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            client.write_all(&input).await.unwrap();
+            client.shutdown().await.unwrap();
+            // keep the stream open until the server is done
+            let _ = client.read(&mut [0]).await;
+        });
+
+        let svc = PeekClientHelloService::new(service_fn(read_to_end));
+        svc.serve(Context::default(), server).await
+    }
+
+    #[tokio::test]
+    async fn test_peek_client_hello_replays_stream() {
+        let body = client_hello_body("example.com", "h2");
+        for fragment_len in [1, 3, 16, 1024] {
+            let mut input = client_hello_records(&body, fragment_len);
+            input.extend_from_slice(b"more tls data");
+
+            let (client_hello, data) = serve_peek(input.clone()).await.unwrap();
+            let client_hello = client_hello.expect("client hello");
+            assert_eq!(
+                client_hello.ext_server_name(),
+                Some(&Host::Name(Domain::from_static("example.com")))
+            );
+            assert_eq!(
+                client_hello.ext_alpn(),
+                Some(&[ApplicationProtocol::HTTP_2][..])
+            );
+            assert_eq!(data, input, "fragment_len = {fragment_len}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_peek_client_hello_non_tls_stream() {
+        let input = b"GET / HTTP/1.1\r\n\r\n".to_vec();
+        let (client_hello, data) = serve_peek(input.clone()).await.unwrap();
+        assert!(client_hello.is_none());
+        assert_eq!(data, input);
+    }
+
+    #[tokio::test]
+    async fn test_peek_client_hello_errors() {
+        let body = client_hello_body("example.com", "h2");
+        let records = client_hello_records(&body, 1024);
+
+        // incomplete
+        assert!(
+            serve_peek(records[..records.len() - 1].to_vec())
+                .await
+                .is_err()
+        );
+        // not a client hello
+        let mut server_hello = records.clone();
+        server_hello[RECORD_HEADER_LEN] = 0x02;
+        assert!(serve_peek(server_hello).await.is_err());
+        // empty record
+        assert!(
+            serve_peek(vec![CONTENT_TYPE_HANDSHAKE, 0x03, 0x01, 0x00, 0x00])
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use super::{DynamicCertIssuer, ServerAuthData};
+use crate::address::{Domain, Host};
+use crate::tls::client::ClientHello;
+use rama_core::error::{ErrorContext, OpaqueError};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Pattern matched against the server name (SNI) sent by a client.
+pub enum ServerNamePattern {
+    /// Matches the given domain exactly.
+    Exact(Domain),
+    /// Matches any direct subdomain of the given (parent) domain,
+    /// e.g. `*.example.com` matches `www.example.com`,
+    /// but neither `example.com` nor `a.www.example.com`.
+    Wildcard(Domain),
+}
+
+impl fmt::Display for ServerNamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(domain) => domain.fmt(f),
+            Self::Wildcard(parent) => write!(f, "*.{parent}"),
+        }
+    }
+}
+
+impl From<Domain> for ServerNamePattern {
+    fn from(domain: Domain) -> Self {
+        Self::Exact(domain)
+    }
+}
+
+impl FromStr for ServerNamePattern {
+    type Err = OpaqueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("*.") {
+            Some(parent) => Ok(Self::Wildcard(
+                parent
+                    .parse()
+                    .context("parse wildcard server name pattern")?,
+            )),
+            None => Ok(Self::Exact(
+                s.parse().context("parse exact server name pattern")?,
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A map of values selected based on the server name (SNI) sent by a client,
+/// typically used to serve a different certificate per hosted domain.
+///
+/// Exact [`ServerNamePattern`]s take precedence over wildcard patterns.
+/// Clients which sent no (or an unknown) server name get the default value, if any.
+///
+/// [`SniMap<ServerAuthData>`] implements [`DynamicCertIssuer`],
+/// such that it can be used by any tls acceptor supporting dynamic issuers.
+/// Prefer [`super::ServerAuth::Sni`] where supported, as dynamic issuers
+/// return data which is parsed again by the tls acceptor for each handshake.
+pub struct SniMap<T> {
+    exact: HashMap<Domain, T>,
+    wildcard: HashMap<Domain, T>,
+    default: Option<T>,
+    reject_unknown_server_name: bool,
+}
+
+impl<T> Default for SniMap<T> {
+    fn default() -> Self {
+        Self {
+            exact: HashMap::new(),
+            wildcard: HashMap::new(),
+            default: None,
+            reject_unknown_server_name: false,
+        }
+    }
+}
+
+impl<T> SniMap<T> {
+    /// Create a new empty [`SniMap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert the value for the given [`ServerNamePattern`],
+    /// replacing any previous value for the same pattern.
+    pub fn insert(&mut self, pattern: impl Into<ServerNamePattern>, value: T) -> &mut Self {
+        match pattern.into() {
+            ServerNamePattern::Exact(domain) => self.exact.insert(domain, value),
+            ServerNamePattern::Wildcard(parent) => self.wildcard.insert(parent, value),
+        };
+        self
+    }
+
+    /// Same as [`Self::insert`], but consuming self.
+    pub fn with(mut self, pattern: impl Into<ServerNamePattern>, value: T) -> Self {
+        self.insert(pattern, value);
+        self
+    }
+
+    /// Set the default value, used for clients which sent no or an unknown server name.
+    pub fn set_default(&mut self, value: T) -> &mut Self {
+        self.default = Some(value);
+        self
+    }
+
+    /// Same as [`Self::set_default`], but consuming self.
+    pub fn with_default(mut self, value: T) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    /// Reject clients which sent a server name not matched by any pattern,
+    /// instead of using the default value.
+    ///
+    /// Clients which sent no server name still get the default value.
+    pub fn set_reject_unknown_server_name(&mut self, reject: bool) -> &mut Self {
+        self.reject_unknown_server_name = reject;
+        self
+    }
+
+    /// Same as [`Self::set_reject_unknown_server_name`], but consuming self.
+    pub fn with_reject_unknown_server_name(mut self, reject: bool) -> Self {
+        self.reject_unknown_server_name = reject;
+        self
+    }
+
+    /// Returns `true` if this map contains no values, including no default value.
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty() && self.wildcard.is_empty() && self.default.is_none()
+    }
+
+    /// Get the value for the given server name.
+    ///
+    /// Returns `None` if no value can be found,
+    /// or if the server name is unknown and such names are rejected.
+    pub fn get(&self, server_name: Option<&Host>) -> Option<&T> {
+        let domain = match server_name {
+            None => return self.default.as_ref(),
+            Some(Host::Name(domain)) => domain,
+            Some(Host::Address(_)) => return self.get_unknown(),
+        };
+
+        if let Some(value) = self.exact.get(domain) {
+            return Some(value);
+        }
+        let value = domain
+            .as_str()
+            .split_once('.')
+            .and_then(|(_, parent)| parent.parse::<Domain>().ok())
+            .and_then(|parent| self.wildcard.get(&parent));
+        value.or_else(|| self.get_unknown())
+    }
+
+    fn get_unknown(&self) -> Option<&T> {
+        if self.reject_unknown_server_name {
+            None
+        } else {
+            self.default.as_ref()
+        }
+    }
+
+    /// Try to map all values (including the default one) using the given function.
+    pub fn try_map<U, E>(self, mut f: impl FnMut(T) -> Result<U, E>) -> Result<SniMap<U>, E> {
+        Ok(SniMap {
+            exact: self
+                .exact
+                .into_iter()
+                .map(|(domain, value)| Ok((domain, f(value)?)))
+                .collect::<Result<_, E>>()?,
+            wildcard: self
+                .wildcard
+                .into_iter()
+                .map(|(parent, value)| Ok((parent, f(value)?)))
+                .collect::<Result<_, E>>()?,
+            default: self.default.map(f).transpose()?,
+            reject_unknown_server_name: self.reject_unknown_server_name,
+        })
+    }
+}
+
+impl DynamicCertIssuer for SniMap<ServerAuthData> {
+    async fn issue_cert(
+        &self,
+        client_hello: ClientHello,
+        server_name: Option<Host>,
+    ) -> Result<ServerAuthData, OpaqueError> {
+        let server_name = client_hello.ext_server_name().cloned().or(server_name);
+        self.get(server_name.as_ref())
+            .cloned()
+            .with_context(|| match &server_name {
+                Some(server_name) => format!("no server cert found for server name: {server_name}"),
+                None => "no default server cert found for client without server name".to_owned(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_name_pattern_parse() {
+        assert_eq!(
+            "example.com".parse::<ServerNamePattern>().unwrap(),
+            ServerNamePattern::Exact(Domain::from_static("example.com"))
+        );
+        assert_eq!(
+            "*.example.com".parse::<ServerNamePattern>().unwrap(),
+            ServerNamePattern::Wildcard(Domain::from_static("example.com"))
+        );
+        assert_eq!(
+            "*.example.com"
+                .parse::<ServerNamePattern>()
+                .unwrap()
+                .to_string(),
+            "*.example.com"
+        );
+        assert!("*".parse::<ServerNamePattern>().is_err());
+    }
+
+    #[test]
+    fn test_sni_map_get() {
+        let map = SniMap::new()
+            .with(Domain::from_static("example.com"), "exact")
+            .with(
+                "*.example.com".parse::<ServerNamePattern>().unwrap(),
+                "wildcard",
+            )
+            .with(Domain::from_static("www.example.com"), "www")
+            .with_default("default");
+
+        for (server_name, expected) in [
+            (Some("example.com"), Some("exact")),
+            (Some("EXAMPLE.com"), Some("exact")),
+            (Some("www.example.com"), Some("www")),
+            (Some("api.example.com"), Some("wildcard")),
+            (Some("a.api.example.com"), Some("default")),
+            (Some("example.org"), Some("default")),
+            (Some("127.0.0.1"), Some("default")),
+            (None, Some("default")),
+        ] {
+            let server_name: Option<Host> = server_name.map(|s| s.parse().unwrap());
+            assert_eq!(
+                map.get(server_name.as_ref()).copied(),
+                expected,
+                "{server_name:?}"
+            );
+        }
+
+        let map = map.with_reject_unknown_server_name(true);
+        assert_eq!(
+            map.get(Some(&"example.org".parse().unwrap())).copied(),
+            None
+        );
+        assert_eq!(
+            map.get(Some(&"api.example.com".parse().unwrap())).copied(),
+            Some("wildcard")
+        );
+        assert_eq!(map.get(None).copied(), Some("default"));
+    }
+}
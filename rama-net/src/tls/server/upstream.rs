@@ -0,0 +1,186 @@
+use crate::address::{Authority, Host};
+use crate::client::ConnectorService;
+use crate::tls::DataEncoding;
+use crate::tls::client::{ClientHello, NegotiatedTlsParameters};
+use crate::tls::pem::{LABEL_CERTIFICATE, pem_blocks};
+use rama_core::Context;
+use rama_core::error::{ErrorContext, OpaqueError};
+use std::{fmt, marker::PhantomData, pin::Pin, sync::Arc};
+
+#[derive(Clone)]
+/// Fetcher of upstream certificates, as used by
+/// [`super::ServerCertIssuerKind::MirrorUpstream`].
+///
+/// Internally contains the dyn [`UpstreamCertFetcher`].
+pub struct UpstreamFetcher {
+    fetcher: Arc<dyn DynUpstreamCertFetcher + Send + Sync>,
+}
+
+impl UpstreamFetcher {
+    pub fn new<T: UpstreamCertFetcher>(fetcher: T) -> Self {
+        Self {
+            fetcher: Arc::new(fetcher),
+        }
+    }
+
+    pub async fn fetch_upstream_cert(
+        &self,
+        client_hello: ClientHello,
+        server_name: Option<Host>,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        self.fetcher
+            .fetch_upstream_cert(client_hello, server_name)
+            .await
+    }
+}
+
+impl fmt::Debug for UpstreamFetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpstreamFetcher").finish()
+    }
+}
+
+impl<T: UpstreamCertFetcher> From<T> for UpstreamFetcher {
+    fn from(fetcher: T) -> Self {
+        Self::new(fetcher)
+    }
+}
+
+/// Trait that needs to be implemented to fetch the (DER-encoded) leaf certificate
+/// of the upstream server, which the client of the given client hello wants to reach.
+pub trait UpstreamCertFetcher: Send + Sync + 'static {
+    fn fetch_upstream_cert(
+        &self,
+        client_hello: ClientHello,
+        server_name: Option<Host>,
+    ) -> impl Future<Output = Result<Vec<u8>, OpaqueError>> + Send + '_;
+}
+
+/// Internal trait to support dynamic dispatch of trait with async fn.
+/// See trait [`rama_core::service::svc::DynService`] for more info about this pattern.
+trait DynUpstreamCertFetcher {
+    fn fetch_upstream_cert(
+        &self,
+        client_hello: ClientHello,
+        server_name: Option<Host>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, OpaqueError>> + Send + '_>>;
+}
+
+impl<T> DynUpstreamCertFetcher for T
+where
+    T: UpstreamCertFetcher,
+{
+    fn fetch_upstream_cert(
+        &self,
+        client_hello: ClientHello,
+        server_name: Option<Host>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>, OpaqueError>> + Send + '_>> {
+        Box::pin(self.fetch_upstream_cert(client_hello, server_name))
+    }
+}
+
+/// Default port used by a [`ConnectorUpstreamCertFetcher`] to connect upstream.
+const DEFAULT_UPSTREAM_PORT: u16 = 443;
+
+/// [`UpstreamCertFetcher`] which connects to the upstream server using a tls connector,
+/// returning the leaf certificate as found in the [`NegotiatedTlsParameters`]
+/// of the established connection.
+///
+/// The upstream server is the server name (SNI) of the client hello,
+/// falling back to the server name of the accepting side if the client sent no SNI.
+///
+/// The connector is expected to be configured to store the server certificate chain,
+/// e.g. using [`crate::tls::client::ClientConfig::store_server_certificate_chain`].
+pub struct ConnectorUpstreamCertFetcher<C, Request> {
+    connector: C,
+    port: u16,
+    _phantom: PhantomData<fn(Request) -> ()>,
+}
+
+impl<C, Request> ConnectorUpstreamCertFetcher<C, Request> {
+    /// Create a new [`ConnectorUpstreamCertFetcher`] using the given connector,
+    /// connecting to port `443`.
+    pub const fn new(connector: C) -> Self {
+        Self {
+            connector,
+            port: DEFAULT_UPSTREAM_PORT,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set the port used to connect upstream.
+    pub fn set_port(&mut self, port: u16) -> &mut Self {
+        self.port = port;
+        self
+    }
+
+    /// Same as [`Self::set_port`] but consuming self.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+}
+
+impl<C: fmt::Debug, Request> fmt::Debug for ConnectorUpstreamCertFetcher<C, Request> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectorUpstreamCertFetcher")
+            .field("connector", &self.connector)
+            .field("port", &self.port)
+            .finish()
+    }
+}
+
+impl<C: Clone, Request> Clone for ConnectorUpstreamCertFetcher<C, Request> {
+    fn clone(&self) -> Self {
+        Self {
+            connector: self.connector.clone(),
+            port: self.port,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, Request> UpstreamCertFetcher for ConnectorUpstreamCertFetcher<C, Request>
+where
+    C: ConnectorService<(), Request>,
+    Request: From<Authority> + Send + 'static,
+{
+    async fn fetch_upstream_cert(
+        &self,
+        client_hello: ClientHello,
+        server_name: Option<Host>,
+    ) -> Result<Vec<u8>, OpaqueError> {
+        let host = client_hello
+            .ext_server_name()
+            .cloned()
+            .or(server_name)
+            .context("no server name to connect upstream to")?;
+        let authority = Authority::new(host, self.port);
+        tracing::trace!(%authority, "fetch upstream cert");
+
+        let established = self
+            .connector
+            .connect(Context::default(), Request::from(authority))
+            .await
+            .map_err(|err| OpaqueError::from_boxed(err.into()))
+            .context("connect upstream")?;
+
+        let chain = established
+            .ctx
+            .get::<NegotiatedTlsParameters>()
+            .context("no negotiated tls parameters for upstream connection")?
+            .peer_certificate_chain
+            .as_ref()
+            .context(
+                "upstream certificate chain not stored: enable store_server_certificate_chain",
+            )?;
+        match chain {
+            DataEncoding::Der(der) => Some(der.clone()),
+            DataEncoding::DerStack(ders) => ders.first().cloned(),
+            DataEncoding::Pem(pem) => pem_blocks(pem.as_str())
+                .find(|block| block.label == LABEL_CERTIFICATE)
+                .and_then(|block| block.decode()),
+        }
+        .context("no leaf certificate found in upstream certificate chain")
+    }
+}
@@ -0,0 +1,438 @@
+use super::{CacheKind, DynamicCertIssuer, ServerAuthData, ServerCertIssuerData};
+use crate::address::Host;
+use crate::tls::client::ClientHello;
+use crate::tls::key::private_key_matches_spki;
+use crate::tls::pem::{LABEL_CERTIFICATE, LABEL_PRIVATE_KEY, pem_blocks};
+use crate::tls::{CertificateInfo, DataEncoding};
+use arc_swap::ArcSwap;
+use parking_lot::Mutex;
+use rama_core::error::{ErrorContext, OpaqueError};
+use rama_utils::str::NonEmptyString;
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+type ServerAuthDataValidator = dyn Fn(&ServerAuthData) -> Result<(), OpaqueError> + Send + Sync;
+
+#[derive(Clone)]
+/// A [`DynamicCertIssuer`] serving the PEM-encoded certificate chain and private key
+/// found on disk, which can be reloaded without restarting the server.
+///
+/// The files are (re)loaded by calling [`Self::reload`] or [`Self::reload_if_modified`],
+/// or automatically by running the future returned by [`Self::watch`].
+///
+/// A new certificate chain and private key only replace the pair currently served
+/// if they are valid and the private key matches the leaf cert, meaning that the previous
+/// pair keeps being served in case the files are invalid, e.g. because they are only partially written.
+/// Private keys which do not embed their public key (e.g. PKCS#8 v1 Ed25519 keys)
+/// can only be matched by the tls implementation, and are therefore only accepted
+/// by an issuer created using [`Self::try_new_with_validator`],
+/// with the validator provided by the tls implementation.
+///
+/// Certs served by this issuer are not to be cached by the tls acceptor,
+/// as reloaded certs would otherwise not be served to hosts for which a cert
+/// was already cached. Converting this issuer into [`ServerCertIssuerData`]
+/// therefore disables caching, using [`CacheKind::Disabled`].
+pub struct FileWatchingCertIssuer {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    cert_chain_path: PathBuf,
+    private_key_path: PathBuf,
+    validator: Option<Box<ServerAuthDataValidator>>,
+    data: ArcSwap<ServerAuthData>,
+    modified: Mutex<FilesModified>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FilesModified {
+    cert_chain: Option<SystemTime>,
+    private_key: Option<SystemTime>,
+}
+
+impl FilesModified {
+    fn new(cert_chain_path: &Path, private_key_path: &Path) -> Self {
+        Self {
+            cert_chain: fs::metadata(cert_chain_path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            private_key: fs::metadata(private_key_path)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        }
+    }
+
+    async fn load(cert_chain_path: &Path, private_key_path: &Path) -> Self {
+        Self {
+            cert_chain: tokio::fs::metadata(cert_chain_path)
+                .await
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+            private_key: tokio::fs::metadata(private_key_path)
+                .await
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        }
+    }
+}
+
+impl FileWatchingCertIssuer {
+    /// Try to create a new [`FileWatchingCertIssuer`], loading the PEM-encoded
+    /// certificate chain and private key found at the given paths,
+    /// verifying that the private key matches the leaf cert.
+    pub fn try_new(
+        cert_chain_path: impl Into<PathBuf>,
+        private_key_path: impl Into<PathBuf>,
+    ) -> Result<Self, OpaqueError> {
+        Self::try_new_inner(cert_chain_path.into(), private_key_path.into(), None)
+    }
+
+    /// Same as [`Self::try_new`], but using the given validator
+    /// to also validate the certificate chain and private key each time these are (re)loaded.
+    pub fn try_new_with_validator(
+        cert_chain_path: impl Into<PathBuf>,
+        private_key_path: impl Into<PathBuf>,
+        validator: impl Fn(&ServerAuthData) -> Result<(), OpaqueError> + Send + Sync + 'static,
+    ) -> Result<Self, OpaqueError> {
+        Self::try_new_inner(
+            cert_chain_path.into(),
+            private_key_path.into(),
+            Some(Box::new(validator)),
+        )
+    }
+
+    fn try_new_inner(
+        cert_chain_path: PathBuf,
+        private_key_path: PathBuf,
+        validator: Option<Box<ServerAuthDataValidator>>,
+    ) -> Result<Self, OpaqueError> {
+        let modified = FilesModified::new(&cert_chain_path, &private_key_path);
+        let cert_chain = fs::read_to_string(&cert_chain_path).context("read cert chain file")?;
+        let private_key = fs::read_to_string(&private_key_path).context("read private key file")?;
+        let data = parse_server_auth_data(cert_chain, private_key, validator.as_deref())?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                cert_chain_path,
+                private_key_path,
+                validator,
+                data: ArcSwap::from_pointee(data),
+                modified: Mutex::new(modified),
+            }),
+        })
+    }
+
+    /// Return the certificate chain and private key currently served.
+    pub fn server_auth_data(&self) -> Arc<ServerAuthData> {
+        self.inner.data.load_full()
+    }
+
+    /// Reload the certificate chain and private key from disk.
+    ///
+    /// In case of an error the previously loaded pair keeps being served.
+    pub async fn reload(&self) -> Result<(), OpaqueError> {
+        let modified =
+            FilesModified::load(&self.inner.cert_chain_path, &self.inner.private_key_path).await;
+        *self.inner.modified.lock() = modified;
+        let cert_chain = tokio::fs::read_to_string(&self.inner.cert_chain_path)
+            .await
+            .context("read cert chain file")?;
+        let private_key = tokio::fs::read_to_string(&self.inner.private_key_path)
+            .await
+            .context("read private key file")?;
+        let data =
+            parse_server_auth_data(cert_chain, private_key, self.inner.validator.as_deref())?;
+        self.inner.data.store(Arc::new(data));
+        tracing::debug!(
+            cert_chain_path = %self.inner.cert_chain_path.display(),
+            "reloaded server cert and key"
+        );
+        Ok(())
+    }
+
+    /// Reload the certificate chain and private key from disk,
+    /// in case either of the files was modified since they were last (re)loaded.
+    ///
+    /// Returns `true` if the files were reloaded.
+    /// Files which failed to load are only retried once they are modified again.
+    pub async fn reload_if_modified(&self) -> Result<bool, OpaqueError> {
+        let modified =
+            FilesModified::load(&self.inner.cert_chain_path, &self.inner.private_key_path).await;
+        if *self.inner.modified.lock() == modified {
+            return Ok(false);
+        }
+        self.reload().await?;
+        Ok(true)
+    }
+
+    /// Watch the certificate chain and private key files,
+    /// reloading them when they are modified, checked every `interval`.
+    ///
+    /// The returned future runs until dropped, e.g. until a graceful shutdown is triggered.
+    pub async fn watch(self, interval: Duration) {
+        self.watch_inner(interval, false).await
+    }
+
+    #[cfg(unix)]
+    /// Same as [`Self::watch`], but also reloading the files
+    /// each time the process receives a `SIGHUP` signal.
+    ///
+    /// Note that this installs a process-wide `SIGHUP` handler,
+    /// meaning that the process is no longer terminated when receiving this signal,
+    /// even after the returned future is dropped.
+    pub async fn watch_with_hangup(self, interval: Duration) {
+        self.watch_inner(interval, true).await
+    }
+
+    async fn watch_inner(self, interval: Duration, reload_on_hangup: bool) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        #[cfg(unix)]
+        let mut hangup = reload_on_hangup
+            .then(|| {
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                    .inspect_err(|err| {
+                        tracing::warn!(error = %err, "failed to listen for SIGHUP: only watching files");
+                    })
+                    .ok()
+            })
+            .flatten();
+        #[cfg(not(unix))]
+        let _ = reload_on_hangup;
+
+        loop {
+            #[cfg(unix)]
+            tokio::select! {
+                _ = ticker.tick() => self.reload_if_modified_logged().await,
+                _ = recv_hangup(&mut hangup) => {
+                    tracing::debug!("SIGHUP received: reload server cert and key");
+                    if let Err(err) = self.reload().await {
+                        tracing::error!(error = %err, "failed to reload server cert and key: keep serving previous pair");
+                    }
+                }
+            }
+
+            #[cfg(not(unix))]
+            {
+                ticker.tick().await;
+                self.reload_if_modified_logged().await;
+            }
+        }
+    }
+
+    async fn reload_if_modified_logged(&self) {
+        if let Err(err) = self.reload_if_modified().await {
+            tracing::error!(error = %err, "failed to reload modified server cert and key: keep serving previous pair");
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn recv_hangup(hangup: &mut Option<tokio::signal::unix::Signal>) {
+    let received = match hangup {
+        Some(signal) => signal.recv().await.is_some(),
+        None => false,
+    };
+    if !received {
+        std::future::pending::<()>().await;
+    }
+}
+
+impl fmt::Debug for FileWatchingCertIssuer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileWatchingCertIssuer")
+            .field("cert_chain_path", &self.inner.cert_chain_path)
+            .field("private_key_path", &self.inner.private_key_path)
+            .field("modified", &*self.inner.modified.lock())
+            .finish()
+    }
+}
+
+impl DynamicCertIssuer for FileWatchingCertIssuer {
+    fn issue_cert(
+        &self,
+        _client_hello: ClientHello,
+        _server_name: Option<Host>,
+    ) -> impl Future<Output = Result<ServerAuthData, OpaqueError>> + Send + Sync + '_ {
+        std::future::ready(Ok(self.server_auth_data().as_ref().clone()))
+    }
+}
+
+impl From<FileWatchingCertIssuer> for ServerCertIssuerData {
+    fn from(issuer: FileWatchingCertIssuer) -> Self {
+        Self {
+            kind: issuer.into(),
+            cache_kind: CacheKind::Disabled,
+        }
+    }
+}
+
+/// Parse the given PEM-encoded certificate chain and private key,
+/// verifying that the private key matches the leaf cert.
+fn parse_server_auth_data(
+    cert_chain: String,
+    private_key: String,
+    validator: Option<&ServerAuthDataValidator>,
+) -> Result<ServerAuthData, OpaqueError> {
+    let leaf = pem_blocks(&cert_chain)
+        .find(|block| block.label == LABEL_CERTIFICATE)
+        .context("no certificate found in cert chain file")?
+        .decode()
+        .context("decode leaf cert")?;
+    let leaf = CertificateInfo::try_from_der(&leaf).context("parse leaf cert")?;
+    let private_key_block = pem_blocks(&private_key)
+        .find(|block| block.label.ends_with(LABEL_PRIVATE_KEY))
+        .context("no private key found in private key file")?;
+    let private_key_der = private_key_block.decode().context("decode private key")?;
+    match private_key_matches_spki(
+        private_key_block.label,
+        &private_key_der,
+        &leaf.subject_public_key_info,
+    ) {
+        Some(true) => (),
+        Some(false) => {
+            return Err(OpaqueError::from_display(
+                "private key does not match leaf cert",
+            ));
+        }
+        // left to the validator of the tls implementation
+        None if validator.is_some() => (),
+        None => {
+            return Err(OpaqueError::from_display(
+                "unable to verify that the private key matches the leaf cert: use a validator",
+            ));
+        }
+    }
+
+    let data = ServerAuthData {
+        private_key: DataEncoding::Pem(
+            NonEmptyString::try_from(private_key).context("private key")?,
+        ),
+        cert_chain: DataEncoding::Pem(NonEmptyString::try_from(cert_chain).context("cert chain")?),
+        ocsp: None,
+    };
+    if let Some(validator) = validator {
+        validator(&data).context("validate server cert and key")?;
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_cert_and_key(dir: &Path, common_name: &str) {
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec![common_name.to_owned()])
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        fs::write(dir.join("cert.pem"), cert.pem()).unwrap();
+        fs::write(dir.join("key.pem"), key_pair.serialize_pem()).unwrap();
+    }
+
+    /// Move the modification time of the given file forward,
+    /// as writes within the timestamp granularity of the file system
+    /// would otherwise not be noticed.
+    fn bump_modified(path: &Path) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        let modified = file.metadata().unwrap().modified().unwrap();
+        file.set_modified(modified + Duration::from_secs(2))
+            .unwrap();
+    }
+
+    fn served_subject_alt_names(issuer: &FileWatchingCertIssuer) -> String {
+        let DataEncoding::Pem(pem) = &issuer.server_auth_data().cert_chain else {
+            panic!("expected PEM cert chain");
+        };
+        let leaf = pem_blocks(pem.as_str()).next().unwrap().decode().unwrap();
+        CertificateInfo::try_from_der(&leaf)
+            .unwrap()
+            .subject_alt_names[0]
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_file_watching_cert_issuer_reload() {
+        let dir = std::env::temp_dir().join(format!(
+            "rama-file-watching-cert-issuer-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        write_cert_and_key(&dir, "a.example.com");
+
+        let issuer = FileWatchingCertIssuer::try_new_with_validator(
+            dir.join("cert.pem"),
+            dir.join("key.pem"),
+            |data| match &data.cert_chain {
+                DataEncoding::Pem(pem) if pem.as_str().len() > 64 => Ok(()),
+                _ => Err(OpaqueError::from_display("invalid cert chain")),
+            },
+        )
+        .unwrap();
+        assert_eq!(served_subject_alt_names(&issuer), "DNS:a.example.com");
+        assert!(!issuer.reload_if_modified().await.unwrap());
+
+        write_cert_and_key(&dir, "b.example.com");
+        issuer.reload().await.unwrap();
+        assert_eq!(served_subject_alt_names(&issuer), "DNS:b.example.com");
+
+        // modified files are picked up by reload_if_modified
+        write_cert_and_key(&dir, "c.example.com");
+        bump_modified(&dir.join("cert.pem"));
+        assert!(issuer.reload_if_modified().await.unwrap());
+        assert_eq!(served_subject_alt_names(&issuer), "DNS:c.example.com");
+        assert!(!issuer.reload_if_modified().await.unwrap());
+
+        // invalid files keep the previous pair being served
+        fs::write(dir.join("cert.pem"), "-----BEGIN CERTIFICATE-----\n").unwrap();
+        assert!(issuer.reload().await.is_err());
+        assert_eq!(served_subject_alt_names(&issuer), "DNS:c.example.com");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_watching_cert_issuer_into_issuer_data_disables_cache() {
+        let dir = std::env::temp_dir().join(format!(
+            "rama-file-watching-cert-issuer-cache-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        write_cert_and_key(&dir, "a.example.com");
+        let issuer =
+            FileWatchingCertIssuer::try_new(dir.join("cert.pem"), dir.join("key.pem")).unwrap();
+
+        let data = ServerCertIssuerData::from(issuer);
+        assert!(matches!(data.cache_kind, CacheKind::Disabled));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_watching_cert_issuer_rejects_mismatching_key() {
+        let dir = std::env::temp_dir().join(format!(
+            "rama-file-watching-cert-issuer-mismatch-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        write_cert_and_key(&dir, "a.example.com");
+        let issuer =
+            FileWatchingCertIssuer::try_new(dir.join("cert.pem"), dir.join("key.pem")).unwrap();
+
+        // a new key which does not (yet) match the cert is not served
+        let other_key = rcgen::KeyPair::generate().unwrap();
+        fs::write(dir.join("key.pem"), other_key.serialize_pem()).unwrap();
+        assert!(issuer.reload().await.is_err());
+        assert_eq!(served_subject_alt_names(&issuer), "DNS:a.example.com");
+        assert!(
+            FileWatchingCertIssuer::try_new(dir.join("cert.pem"), dir.join("key.pem")).is_err()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,478 @@
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::{ClientConfig, Endpoint};
+use rama_core::{
+    Context, Service,
+    error::{BoxError, ErrorContext, ErrorExt, OpaqueError},
+};
+use rama_dns::{DnsOverwrite, DnsResolver, HickoryDns};
+use rama_net::{
+    address::{Authority, Domain, Host},
+    client::EstablishedClientConnection,
+    mode::{ConnectIpMode, DnsResolveIpMode},
+    stream::{ClientSocketInfo, SocketInfo},
+    transport::TryRefIntoTransportContext,
+};
+use rama_tls_rustls::client::TlsConnectorData;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+use crate::{QuicConnection, QuicTransportConfig, tls::negotiated_tls_parameters};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The [`Authority`] a [`QuicConnector`] connects to instead of
+/// the authority of the [`TransportContext`], e.g. an alternative
+/// service advertised using `Alt-Svc`.
+///
+/// The authority of the [`TransportContext`] remains used as the server name,
+/// such that the server is still authenticated as the original authority.
+///
+/// [`TransportContext`]: rama_net::transport::TransportContext
+pub struct QuicConnectAuthority(pub Authority);
+
+#[derive(Debug, Clone)]
+/// A connector which can be used to establish a QUIC connection to a server.
+///
+/// The authority to connect to is taken from the [`TransportContext`],
+/// which is computed from the request unless already present in the [`Context`].
+/// Insert a [`QuicConnectAuthority`] in the [`Context`] to connect
+/// to an alternative service (e.g. advertised using `Alt-Svc`) instead.
+///
+/// All connections established by a [`QuicConnector`] (and its clones)
+/// share a single client endpoint per IP version.
+///
+/// [`TransportContext`]: rama_net::transport::TransportContext
+pub struct QuicConnector<Dns = HickoryDns> {
+    dns: Dns,
+    connector_data: Option<TlsConnectorData>,
+    transport_config: Option<Arc<QuicTransportConfig>>,
+    endpoints: Arc<ClientEndpoints>,
+}
+
+#[derive(Debug, Default)]
+/// The client endpoints shared by a [`QuicConnector`],
+/// bound lazily on first use.
+struct ClientEndpoints {
+    ipv4: Mutex<Option<Endpoint>>,
+    ipv6: Mutex<Option<Endpoint>>,
+}
+
+impl ClientEndpoints {
+    fn get(&self, addr: SocketAddr) -> Result<Endpoint, OpaqueError> {
+        let (slot, bind_addr): (_, SocketAddr) = match addr {
+            SocketAddr::V4(_) => (&self.ipv4, (Ipv4Addr::UNSPECIFIED, 0).into()),
+            SocketAddr::V6(_) => (&self.ipv6, (Ipv6Addr::UNSPECIFIED, 0).into()),
+        };
+        let mut slot = slot.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(endpoint) = slot.as_ref() {
+            return Ok(endpoint.clone());
+        }
+        let endpoint = Endpoint::client(bind_addr).context("bind quic client endpoint")?;
+        *slot = Some(endpoint.clone());
+        Ok(endpoint)
+    }
+}
+
+impl QuicConnector {
+    /// Create a new [`QuicConnector`], which is used to establish a connection to a server.
+    ///
+    /// You can use middleware around the [`QuicConnector`]
+    /// or add connection pools, retry logic and more.
+    pub fn new() -> Self {
+        Self {
+            dns: HickoryDns::default(),
+            connector_data: None,
+            transport_config: None,
+            endpoints: Arc::default(),
+        }
+    }
+}
+
+impl Default for QuicConnector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Dns> QuicConnector<Dns> {
+    /// Consume `self` to attach the given `dns` (a [`DnsResolver`]) as a new [`QuicConnector`].
+    pub fn with_dns<OtherDns>(self, dns: OtherDns) -> QuicConnector<OtherDns>
+    where
+        OtherDns: DnsResolver<Error: Into<BoxError>> + Clone,
+    {
+        QuicConnector {
+            dns,
+            connector_data: self.connector_data,
+            transport_config: self.transport_config,
+            endpoints: self.endpoints,
+        }
+    }
+
+    /// Set the [`TlsConnectorData`] used to secure the QUIC connection.
+    ///
+    /// By default [`TlsConnectorData::new_http_3`] is used.
+    pub fn with_connector_data(mut self, connector_data: TlsConnectorData) -> Self {
+        self.connector_data = Some(connector_data);
+        self
+    }
+
+    /// Set the [`TlsConnectorData`] used to secure the QUIC connection.
+    ///
+    /// By default [`TlsConnectorData::new_http_3`] is used.
+    pub fn set_connector_data(&mut self, connector_data: TlsConnectorData) -> &mut Self {
+        self.connector_data = Some(connector_data);
+        self
+    }
+
+    /// Set the [`QuicTransportConfig`] used for the established connections.
+    pub fn with_transport_config(mut self, config: QuicTransportConfig) -> Self {
+        self.transport_config = Some(Arc::new(config));
+        self
+    }
+
+    /// Set the [`QuicTransportConfig`] used for the established connections.
+    pub fn set_transport_config(&mut self, config: QuicTransportConfig) -> &mut Self {
+        self.transport_config = Some(Arc::new(config));
+        self
+    }
+}
+
+impl<State, Request, Dns> Service<State, Request> for QuicConnector<Dns>
+where
+    State: Clone + Send + Sync + 'static,
+    Request: TryRefIntoTransportContext<State> + Send + 'static,
+    Request::Error: Into<BoxError> + Send + Sync + 'static,
+    Dns: DnsResolver<Error: Into<BoxError>> + Clone,
+{
+    type Response = EstablishedClientConnection<QuicConnection, State, Request>;
+    type Error = BoxError;
+
+    async fn serve(
+        &self,
+        mut ctx: Context<State>,
+        req: Request,
+    ) -> Result<Self::Response, Self::Error> {
+        let transport_ctx = ctx
+            .get_or_try_insert_with_ctx(|ctx| req.try_ref_into_transport_ctx(ctx))
+            .map_err(|err| {
+                OpaqueError::from_boxed(err.into())
+                    .context("quic connector: compute transport context to get authority")
+            })?;
+        let authority = transport_ctx.authority.clone();
+        let connect_authority = ctx
+            .get::<QuicConnectAuthority>()
+            .map(|QuicConnectAuthority(authority)| authority.clone())
+            .unwrap_or_else(|| authority.clone());
+
+        let connector_data = match &self.connector_data {
+            Some(data) => data.clone(),
+            None => TlsConnectorData::new_http_3()?,
+        };
+        let server_name = connector_data
+            .server_name
+            .clone()
+            .unwrap_or_else(|| authority.host().clone());
+
+        let crypto = QuicClientConfig::try_from(connector_data.client_config)
+            .context("quic connector: create quic client config from rustls client config")?;
+        let mut client_config = ClientConfig::new(Arc::new(crypto));
+        if let Some(transport_config) = self.transport_config.clone() {
+            client_config.transport_config(transport_config);
+        }
+
+        let addresses = resolve_authority(&ctx, connect_authority, self.dns.clone()).await?;
+
+        let mut last_err = None;
+        for addr in addresses {
+            let result = match self.endpoints.get(addr) {
+                Ok(endpoint) => {
+                    quic_connect(&endpoint, client_config.clone(), addr, &server_name).await
+                }
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok((conn, local_addr)) => {
+                    ctx.insert(ClientSocketInfo(SocketInfo::new(local_addr, addr)));
+                    ctx.insert(negotiated_tls_parameters(&conn));
+                    return Ok(EstablishedClientConnection { ctx, req, conn });
+                }
+                Err(err) => {
+                    tracing::trace!(%addr, ?err, "quic connector: failed to connect to address");
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err
+            .unwrap_or_else(|| OpaqueError::from_display("no address resolved to connect to"))
+            .context("quic connector: connect to server")
+            .into_boxed())
+    }
+}
+
+async fn quic_connect(
+    endpoint: &Endpoint,
+    client_config: ClientConfig,
+    addr: SocketAddr,
+    server_name: &Host,
+) -> Result<(QuicConnection, Option<SocketAddr>), OpaqueError> {
+    let connecting = endpoint
+        .connect_with(client_config, addr, &server_name.to_string())
+        .context("create quic connection")?;
+    let conn = connecting.await.context("establish quic connection")?;
+    let local_addr = endpoint
+        .local_addr()
+        .inspect_err(|err| {
+            tracing::debug!(
+                ?err,
+                "failed to receive local addr of established quic connection"
+            )
+        })
+        .ok();
+    Ok((conn, local_addr))
+}
+
+async fn resolve_authority<State, Dns>(
+    ctx: &Context<State>,
+    authority: Authority,
+    dns: Dns,
+) -> Result<Vec<SocketAddr>, OpaqueError>
+where
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    let ip_mode: ConnectIpMode = ctx.get().copied().unwrap_or_default();
+    let dns_mode: DnsResolveIpMode = ctx.get().copied().unwrap_or_default();
+
+    let (host, port) = authority.into_parts();
+    let domain = match host {
+        Host::Name(domain) => domain,
+        Host::Address(ip) => {
+            return match (ip, ip_mode) {
+                (IpAddr::V4(_), ConnectIpMode::Ipv6) => {
+                    Err(OpaqueError::from_display("IPv4 address is not allowed"))
+                }
+                (IpAddr::V6(_), ConnectIpMode::Ipv4) => {
+                    Err(OpaqueError::from_display("IPv6 address is not allowed"))
+                }
+                _ => Ok(vec![(ip, port).into()]),
+            };
+        }
+    };
+
+    if let Some(dns_overwrite) = ctx.get::<DnsOverwrite>() {
+        if let Ok(ips) =
+            resolve_domain(dns_overwrite.deref(), domain.clone(), dns_mode, ip_mode).await
+        {
+            if !ips.is_empty() {
+                return Ok(ips.into_iter().map(|ip| (ip, port).into()).collect());
+            }
+        }
+    }
+
+    let ips = resolve_domain(&dns, domain, dns_mode, ip_mode).await?;
+    Ok(ips.into_iter().map(|ip| (ip, port).into()).collect())
+}
+
+async fn resolve_domain<Dns>(
+    dns: &Dns,
+    domain: Domain,
+    dns_mode: DnsResolveIpMode,
+    ip_mode: ConnectIpMode,
+) -> Result<Vec<IpAddr>, OpaqueError>
+where
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    let ipv4_allowed = dns_mode.ipv4_supported() && ip_mode != ConnectIpMode::Ipv6;
+    let ipv6_allowed = dns_mode.ipv6_supported() && ip_mode != ConnectIpMode::Ipv4;
+
+    let mut ipv4 = Vec::new();
+    if ipv4_allowed {
+        match dns.ipv4_lookup(domain.clone()).await {
+            Ok(ips) => ipv4.extend(ips.into_iter().map(IpAddr::V4)),
+            Err(err) => {
+                let err = err.into();
+                tracing::trace!(?err, %domain, "quic connector: failed to resolve A records");
+            }
+        }
+    }
+
+    let mut ipv6 = Vec::new();
+    if ipv6_allowed {
+        match dns.ipv6_lookup(domain.clone()).await {
+            Ok(ips) => ipv6.extend(ips.into_iter().map(IpAddr::V6)),
+            Err(err) => {
+                let err = err.into();
+                tracing::trace!(?err, %domain, "quic connector: failed to resolve AAAA records");
+            }
+        }
+    }
+
+    let ips: Vec<_> = if dns_mode == DnsResolveIpMode::DualPreferIpV4 {
+        ipv4.into_iter().chain(ipv6).collect()
+    } else {
+        ipv6.into_iter().chain(ipv4).collect()
+    };
+
+    if ips.is_empty() {
+        return Err(OpaqueError::from_display(format!(
+            "failed to resolve domain: {domain}"
+        )));
+    }
+    Ok(ips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuicStream, server::QuicListener};
+    use rama_core::service::service_fn;
+    use rama_net::{
+        tls::{ApplicationProtocol, client::NegotiatedTlsParameters, server::SelfSignedData},
+        transport::{TransportContext, TransportProtocol},
+    };
+    use rama_tls_rustls::{client::TlsConnectorDataBuilder, server::TlsAcceptorDataBuilder};
+    use std::convert::Infallible;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    struct TestRequest(Authority);
+
+    impl<State> TryRefIntoTransportContext<State> for TestRequest {
+        type Error = Infallible;
+
+        fn try_ref_into_transport_ctx(
+            &self,
+            _ctx: &Context<State>,
+        ) -> Result<TransportContext, Self::Error> {
+            Ok(TransportContext {
+                protocol: TransportProtocol::Udp,
+                app_protocol: None,
+                http_version: None,
+                authority: self.0.clone(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quic_connector_echo() {
+        let tls_config = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData::default())
+            .unwrap()
+            .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+            .into_rustls_config();
+        let listener = QuicListener::bind("127.0.0.1:0", tls_config).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(listener.serve(service_fn(
+            async |ctx: Context<()>, conn: QuicConnection| {
+                let params = ctx.get::<NegotiatedTlsParameters>().unwrap();
+                assert_eq!(
+                    Some(ApplicationProtocol::HTTP_3),
+                    params.application_layer_protocol
+                );
+                assert!(ctx.get::<SocketInfo>().is_some());
+
+                let mut stream = QuicStream::accept(&conn).await?;
+                let mut buf = Vec::new();
+                stream.read_to_end(&mut buf).await?;
+                stream.write_all(&buf).await?;
+                stream.shutdown().await?;
+                conn.closed().await;
+                Ok::<_, BoxError>(())
+            },
+        )));
+
+        let connector = QuicConnector::new().with_connector_data(
+            TlsConnectorDataBuilder::new()
+                .with_no_cert_verifier()
+                .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+                .build(),
+        );
+        let EstablishedClientConnection { ctx, conn, .. } = connector
+            .serve(Context::default(), TestRequest(addr.into()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Some(ApplicationProtocol::HTTP_3),
+            ctx.get::<NegotiatedTlsParameters>()
+                .unwrap()
+                .application_layer_protocol
+        );
+        assert_eq!(&addr, ctx.get::<ClientSocketInfo>().unwrap().peer_addr());
+
+        let mut stream = QuicStream::open(&conn).await.unwrap();
+        stream.write_all(b"hello quic").await.unwrap();
+        stream.shutdown().await.unwrap();
+
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(b"hello quic", &buf[..]);
+
+        conn.close(0u32.into(), b"done");
+    }
+
+    #[tokio::test]
+    async fn test_quic_connector_connect_authority() {
+        let tls_config = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData::default())
+            .unwrap()
+            .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+            .into_rustls_config();
+        let listener = QuicListener::bind("127.0.0.1:0", tls_config).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(
+            listener.serve(service_fn(move |ctx: Context<()>, conn: QuicConnection| {
+                let tx = tx.clone();
+                async move {
+                    let params = ctx.get::<NegotiatedTlsParameters>().unwrap();
+                    let _ = tx.send(params.server_name.clone());
+                    conn.closed().await;
+                    Ok::<_, BoxError>(())
+                }
+            })),
+        );
+
+        let connector = QuicConnector::new().with_connector_data(
+            TlsConnectorDataBuilder::new()
+                .with_no_cert_verifier()
+                .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+                .build(),
+        );
+
+        let mut local_ports = Vec::new();
+        for _ in 0..2 {
+            let mut ctx = Context::default();
+            ctx.insert(QuicConnectAuthority(addr.into()));
+            let EstablishedClientConnection { ctx, conn, .. } = connector
+                .serve(
+                    ctx,
+                    TestRequest(Authority::new(
+                        Host::Name(Domain::from_static("origin.example")),
+                        443,
+                    )),
+                )
+                .await
+                .unwrap();
+
+            // dialed the connect authority, but authenticated as the origin
+            assert_eq!(&addr, ctx.get::<ClientSocketInfo>().unwrap().peer_addr());
+            assert_eq!(
+                Some(Host::Name(Domain::from_static("origin.example"))),
+                rx.recv().await.unwrap()
+            );
+            local_ports.push(
+                ctx.get::<ClientSocketInfo>()
+                    .unwrap()
+                    .local_addr()
+                    .unwrap()
+                    .port(),
+            );
+            conn.close(0u32.into(), b"done");
+        }
+
+        // connections share the same client endpoint
+        assert_eq!(local_ports[0], local_ports[1]);
+    }
+}
@@ -0,0 +1,5 @@
+//! QUIC client module for Rama.
+
+mod connector;
+#[doc(inline)]
+pub use connector::{QuicConnectAuthority, QuicConnector};
@@ -0,0 +1,58 @@
+//! QUIC support for Rama.
+//!
+//! QUIC is a secure, multiplexed transport protocol running on top of UDP,
+//! and is the transport used by HTTP/3. This crate builds on top of [`quinn`]
+//! and makes it available as rama services, both for servers ([`server::QuicListener`])
+//! and clients ([`client::QuicConnector`]).
+//!
+//! QUIC always runs with TLS 1.3, which is why the configuration of both
+//! the listener and connector is done using the (rustls) types from `rama-tls-rustls`.
+//!
+//! # Rama
+//!
+//! Crate used by the end-user `rama` crate and `rama` crate authors alike.
+//!
+//! Learn more about `rama`:
+//!
+//! - Github: <https://github.com/plabayo/rama>
+//! - Book: <https://ramaproxy.org/book/>
+//!
+//! [`quinn`]: https://docs.rs/quinn
+
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/plabayo/rama/main/docs/img/old_logo.png"
+)]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/plabayo/rama/main/docs/img/old_logo.png")]
+#![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
+#![cfg_attr(test, allow(clippy::float_cmp))]
+#![cfg_attr(not(test), warn(clippy::print_stdout, clippy::dbg_macro))]
+
+pub mod client;
+pub mod server;
+
+mod stream;
+#[doc(inline)]
+pub use stream::QuicStream;
+
+mod tls;
+
+#[doc(inline)]
+pub use quinn::{
+    Connection as QuicConnection, RecvStream as QuicRecvStream, SendStream as QuicSendStream,
+    TransportConfig as QuicTransportConfig,
+};
+
+pub mod dep {
+    //! Dependencies for rama quic modules.
+    //!
+    //! Exported for your convenience.
+
+    pub mod quinn {
+        //! Re-export of the [`quinn`] crate.
+        //!
+        //! [`quinn`]: https://docs.rs/quinn
+
+        #[doc(inline)]
+        pub use quinn::*;
+    }
+}
@@ -0,0 +1,305 @@
+use quinn::crypto::rustls::QuicServerConfig;
+use quinn::{Endpoint, Incoming, ServerConfig};
+use rama_core::Context;
+use rama_core::Service;
+use rama_core::error::{BoxError, ErrorContext};
+use rama_core::graceful::ShutdownGuard;
+use rama_core::rt::Executor;
+use rama_net::address::SocketAddress;
+use rama_net::stream::SocketInfo;
+use rama_net::tls::SecureTransport;
+use rama_tls_rustls::dep::rustls;
+use std::fmt;
+use std::pin::pin;
+use std::sync::Arc;
+use std::{io, net::SocketAddr};
+
+use crate::{QuicConnection, QuicTransportConfig, tls::negotiated_tls_parameters};
+
+/// Builder for `QuicListener`.
+pub struct QuicListenerBuilder<S> {
+    transport_config: Option<Arc<QuicTransportConfig>>,
+    state: S,
+}
+
+impl<S> fmt::Debug for QuicListenerBuilder<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicListenerBuilder")
+            .field("transport_config", &self.transport_config)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl QuicListenerBuilder<()> {
+    /// Create a new `QuicListenerBuilder` without a state.
+    pub fn new() -> Self {
+        Self {
+            transport_config: None,
+            state: (),
+        }
+    }
+}
+
+impl Default for QuicListenerBuilder<()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Clone> Clone for QuicListenerBuilder<S> {
+    fn clone(&self) -> Self {
+        Self {
+            transport_config: self.transport_config.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S> QuicListenerBuilder<S> {
+    /// Set the [`QuicTransportConfig`] used for all connections accepted by the listener.
+    ///
+    /// If not defined the default transport configuration is used.
+    pub fn with_transport_config(mut self, config: QuicTransportConfig) -> Self {
+        self.transport_config = Some(Arc::new(config));
+        self
+    }
+
+    /// Set the [`QuicTransportConfig`] used for all connections accepted by the listener.
+    ///
+    /// If not defined the default transport configuration is used.
+    pub fn set_transport_config(&mut self, config: QuicTransportConfig) -> &mut Self {
+        self.transport_config = Some(Arc::new(config));
+        self
+    }
+}
+
+impl<S> QuicListenerBuilder<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Create a new `QuicListenerBuilder` with the given state.
+    pub fn with_state(state: S) -> Self {
+        Self {
+            transport_config: None,
+            state,
+        }
+    }
+
+    /// Creates a new QuicListener, which will be bound to the specified (UDP) address.
+    ///
+    /// The given [`rustls::ServerConfig`] is used to secure all accepted connections.
+    /// It has to support TLS 1.3, and for most use cases you'll want to configure
+    /// the ALPN protocols (e.g. `h3`) that you wish to accept.
+    ///
+    /// Binding with a port number of 0 will request that the OS assigns a port
+    /// to this listener. The port allocated can be queried via the `local_addr`
+    /// method.
+    pub async fn bind<A: TryInto<SocketAddress, Error: Into<BoxError>>>(
+        self,
+        addr: A,
+        tls_config: impl Into<Arc<rustls::ServerConfig>>,
+    ) -> Result<QuicListener<S>, BoxError> {
+        let socket_addr = addr.try_into().map_err(Into::<BoxError>::into)?;
+
+        let crypto = QuicServerConfig::try_from(tls_config.into())
+            .context("create quic server config from rustls server config")?;
+        let mut server_config = ServerConfig::with_crypto(Arc::new(crypto));
+        if let Some(transport_config) = self.transport_config {
+            server_config.transport_config(transport_config);
+        }
+
+        let inner = Endpoint::server(server_config, socket_addr.into())?;
+
+        Ok(QuicListener {
+            inner,
+            state: self.state,
+        })
+    }
+}
+
+/// A QUIC server, listening for incoming connections once served
+/// using one of the `serve` methods such as [`QuicListener::serve`].
+///
+/// Connections are only passed to the service once the QUIC (and thus TLS)
+/// handshake is complete, with the [`SocketInfo`], [`SecureTransport`] and
+/// [`NegotiatedTlsParameters`] inserted in the [`Context`].
+///
+/// [`NegotiatedTlsParameters`]: rama_net::tls::client::NegotiatedTlsParameters
+pub struct QuicListener<S> {
+    inner: Endpoint,
+    state: S,
+}
+
+impl<S> fmt::Debug for QuicListener<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicListener")
+            .field("inner", &self.inner)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl QuicListener<()> {
+    /// Create a new `QuicListenerBuilder` without a state,
+    /// which can be used to configure a `QuicListener`.
+    pub fn build() -> QuicListenerBuilder<()> {
+        QuicListenerBuilder::new()
+    }
+
+    /// Create a new `QuicListenerBuilder` with the given state,
+    /// which can be used to configure a `QuicListener`.
+    pub fn build_with_state<S>(state: S) -> QuicListenerBuilder<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        QuicListenerBuilder::with_state(state)
+    }
+
+    /// Creates a new QuicListener, which will be bound to the specified (UDP) address.
+    ///
+    /// See [`QuicListenerBuilder::bind`] for more information.
+    pub async fn bind<A: TryInto<SocketAddress, Error: Into<BoxError>>>(
+        addr: A,
+        tls_config: impl Into<Arc<rustls::ServerConfig>>,
+    ) -> Result<QuicListener<()>, BoxError> {
+        QuicListenerBuilder::default().bind(addr, tls_config).await
+    }
+
+    /// Define the QuicListener's state after it was created,
+    /// useful in case it wasn't built using the builder.
+    pub fn with_state<S>(self, state: S) -> QuicListener<S> {
+        QuicListener {
+            inner: self.inner,
+            state,
+        }
+    }
+}
+
+impl From<Endpoint> for QuicListener<()> {
+    fn from(value: Endpoint) -> Self {
+        Self {
+            inner: value,
+            state: (),
+        }
+    }
+}
+
+impl<S> QuicListener<S> {
+    /// Returns the local address that this listener is bound to.
+    ///
+    /// This can be useful, for example, when binding to port 0 to figure out
+    /// which port was actually bound.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Gets a reference to the listener's state.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Gets an exclusive reference to the listener's state.
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+}
+
+impl<State> QuicListener<State>
+where
+    State: Clone + Send + Sync + 'static,
+{
+    /// Serve connections from this listener with the given service.
+    ///
+    /// This method will block the current listener for each incoming connection,
+    /// the underlying service can choose to spawn a task to handle the accepted connection.
+    pub async fn serve<S>(self, service: S)
+    where
+        S: Service<State, QuicConnection>,
+    {
+        let ctx = Context::new(self.state, Executor::new());
+        let service = Arc::new(service);
+        let local_addr = self.inner.local_addr().ok();
+
+        while let Some(incoming) = self.inner.accept().await {
+            let service = service.clone();
+            let ctx = ctx.clone();
+
+            tokio::spawn(async move {
+                serve_incoming(ctx, local_addr, incoming, service).await;
+            });
+        }
+    }
+
+    /// Serve gracefully connections from this listener with the given service.
+    ///
+    /// This method does the same as [`Self::serve`] but it
+    /// will respect the given [`rama_core::graceful::ShutdownGuard`], and also pass
+    /// it to the service.
+    pub async fn serve_graceful<S>(self, guard: ShutdownGuard, service: S)
+    where
+        S: Service<State, QuicConnection>,
+    {
+        let ctx: Context<State> = Context::new(self.state, Executor::graceful(guard.clone()));
+        let service = Arc::new(service);
+        let local_addr = self.inner.local_addr().ok();
+        let mut cancelled_fut = pin!(guard.cancelled());
+
+        loop {
+            tokio::select! {
+                _ = cancelled_fut.as_mut() => {
+                    tracing::trace!("signal received: initiate graceful shutdown");
+                    break;
+                }
+                incoming = self.inner.accept() => {
+                    let Some(incoming) = incoming else {
+                        tracing::trace!("quic endpoint closed");
+                        break;
+                    };
+
+                    let service = service.clone();
+                    let ctx = ctx.clone();
+
+                    guard.spawn_task(async move {
+                        serve_incoming(ctx, local_addr, incoming, service).await;
+                    });
+                }
+            }
+        }
+    }
+}
+
+async fn serve_incoming<State, S>(
+    mut ctx: Context<State>,
+    local_addr: Option<SocketAddr>,
+    incoming: Incoming,
+    service: Arc<S>,
+) where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, QuicConnection>,
+{
+    let peer_addr = incoming.remote_address();
+    let conn = match incoming.await {
+        Ok(conn) => conn,
+        Err(err) => {
+            tracing::trace!(
+                error = &err as &dyn std::error::Error,
+                %peer_addr,
+                "QUIC accept error: handshake failed"
+            );
+            return;
+        }
+    };
+
+    ctx.insert(SocketInfo::new(local_addr, peer_addr));
+    ctx.insert(negotiated_tls_parameters(&conn));
+    ctx.insert(SecureTransport::default());
+
+    let _ = service.serve(ctx, conn).await;
+}
@@ -0,0 +1,5 @@
+//! QUIC server module for Rama.
+
+mod listener;
+#[doc(inline)]
+pub use listener::{QuicListener, QuicListenerBuilder};
@@ -0,0 +1,88 @@
+use crate::{QuicConnection, QuicRecvStream, QuicSendStream};
+use pin_project_lite::pin_project;
+use rama_core::error::{ErrorContext, OpaqueError};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+pin_project! {
+    /// A bidirectional QUIC stream, combining a [`QuicSendStream`]
+    /// and [`QuicRecvStream`] into a single byte stream.
+    ///
+    /// This allows a QUIC stream to be used anywhere
+    /// a rama [`Stream`] is expected (e.g. a raw tunnel).
+    ///
+    /// [`Stream`]: rama_net::stream::Stream
+    #[derive(Debug)]
+    pub struct QuicStream {
+        #[pin]
+        send: QuicSendStream,
+        #[pin]
+        recv: QuicRecvStream,
+    }
+}
+
+impl QuicStream {
+    /// Create a new [`QuicStream`] from the two halves of a bidirectional QUIC stream.
+    pub fn new(send: QuicSendStream, recv: QuicRecvStream) -> Self {
+        Self { send, recv }
+    }
+
+    /// Open a new outgoing bidirectional stream on the given [`QuicConnection`].
+    pub async fn open(conn: &QuicConnection) -> Result<Self, OpaqueError> {
+        let (send, recv) = conn.open_bi().await.context("open quic bi stream")?;
+        Ok(Self::new(send, recv))
+    }
+
+    /// Accept the next incoming bidirectional stream on the given [`QuicConnection`].
+    pub async fn accept(conn: &QuicConnection) -> Result<Self, OpaqueError> {
+        let (send, recv) = conn.accept_bi().await.context("accept quic bi stream")?;
+        Ok(Self::new(send, recv))
+    }
+
+    /// Get a reference to the send half of this stream.
+    pub fn send_stream(&self) -> &QuicSendStream {
+        &self.send
+    }
+
+    /// Get a reference to the receive half of this stream.
+    pub fn recv_stream(&self) -> &QuicRecvStream {
+        &self.recv
+    }
+
+    /// Consume this [`QuicStream`] into its send and receive halves.
+    pub fn into_parts(self) -> (QuicSendStream, QuicRecvStream) {
+        (self.send, self.recv)
+    }
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        AsyncRead::poll_read(self.project().recv, cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(self.project().send, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(self.project().send, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(self.project().send, cx)
+    }
+}
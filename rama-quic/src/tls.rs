@@ -0,0 +1,48 @@
+use quinn::crypto::rustls::HandshakeData;
+use rama_net::address::Host;
+use rama_net::tls::{
+    ApplicationProtocol, CertificateInfo, ProtocolVersion, client::NegotiatedTlsParameters,
+};
+use rama_tls_rustls::dep::pki_types::CertificateDer;
+
+use crate::QuicConnection;
+
+/// Compute the [`NegotiatedTlsParameters`] of an established [`QuicConnection`].
+///
+/// QUIC only supports TLS 1.3, and quinn does not surface
+/// the negotiated cipher suite and key exchange group,
+/// so only the info found in the handshake data and peer identity is available.
+pub(crate) fn negotiated_tls_parameters(conn: &QuicConnection) -> NegotiatedTlsParameters {
+    let handshake_data = conn
+        .handshake_data()
+        .and_then(|data| data.downcast::<HandshakeData>().ok());
+
+    let peer_certificate = conn
+        .peer_identity()
+        .and_then(|identity| identity.downcast::<Vec<CertificateDer<'static>>>().ok())
+        .and_then(|chain| {
+            chain
+                .first()
+                .and_then(|cert| CertificateInfo::try_from_der(cert).ok())
+        });
+
+    NegotiatedTlsParameters {
+        protocol_version: ProtocolVersion::TLSv1_3,
+        application_layer_protocol: handshake_data
+            .as_ref()
+            .and_then(|data| data.protocol.as_deref())
+            .map(ApplicationProtocol::from),
+        cipher_suite: None,
+        key_exchange_group: None,
+        signature_scheme: None,
+        server_name: handshake_data
+            .as_ref()
+            .and_then(|data| data.server_name.as_deref())
+            .and_then(|name| Host::try_from(name).ok()),
+        peer_certificate_chain: None,
+        peer_certificate,
+        // not surfaced by quinn
+        session_resumed: false,
+        ech_accepted: false,
+    }
+}
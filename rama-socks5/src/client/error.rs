@@ -0,0 +1,108 @@
+use crate::proto::{ProtocolError, ReplyKind, SocksMethod};
+use std::fmt;
+
+#[derive(Debug)]
+/// Error returned by the [`Socks5Client`] in case
+/// the socks5 handshake with the server failed.
+///
+/// [`Socks5Client`]: super::Socks5Client
+pub struct HandshakeError {
+    kind: HandshakeErrorKind,
+    context: Option<&'static str>,
+}
+
+#[derive(Debug)]
+enum HandshakeErrorKind {
+    Protocol(ProtocolError),
+    MethodMismatch(SocksMethod),
+    Unauthorized,
+    Reply(ReplyKind),
+}
+
+impl HandshakeError {
+    pub(crate) fn protocol(error: ProtocolError) -> Self {
+        Self {
+            kind: HandshakeErrorKind::Protocol(error),
+            context: None,
+        }
+    }
+
+    pub(crate) fn method_mismatch(method: SocksMethod) -> Self {
+        Self {
+            kind: HandshakeErrorKind::MethodMismatch(method),
+            context: None,
+        }
+    }
+
+    pub(crate) fn unauthorized() -> Self {
+        Self {
+            kind: HandshakeErrorKind::Unauthorized,
+            context: None,
+        }
+    }
+
+    pub(crate) fn reply(kind: ReplyKind) -> Self {
+        Self {
+            kind: HandshakeErrorKind::Reply(kind),
+            context: None,
+        }
+    }
+
+    pub(crate) fn with_context(mut self, context: &'static str) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Returns the (error) [`ReplyKind`] in case the server
+    /// rejected the client request with it.
+    pub fn reply_kind(&self) -> Option<ReplyKind> {
+        match self.kind {
+            HandshakeErrorKind::Reply(kind) => Some(kind),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` in case the server did not accept
+    /// any of the authentication methods offered by the client,
+    /// or rejected the credentials provided by the client.
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self.kind,
+            HandshakeErrorKind::Unauthorized | HandshakeErrorKind::MethodMismatch(_)
+        )
+    }
+}
+
+impl From<ProtocolError> for HandshakeError {
+    fn from(value: ProtocolError) -> Self {
+        Self::protocol(value)
+    }
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("socks5 client: ")?;
+        if let Some(context) = self.context {
+            write!(f, "{context}: ")?;
+        }
+        match &self.kind {
+            HandshakeErrorKind::Protocol(err) => write!(f, "protocol error: {err}"),
+            HandshakeErrorKind::MethodMismatch(method) => {
+                write!(f, "unexpected or unacceptable method: {method}")
+            }
+            HandshakeErrorKind::Unauthorized => f.write_str("invalid credentials"),
+            HandshakeErrorKind::Reply(kind) => write!(f, "server replied with error: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            HandshakeErrorKind::Protocol(err) => Some(err),
+            HandshakeErrorKind::MethodMismatch(_)
+            | HandshakeErrorKind::Unauthorized
+            | HandshakeErrorKind::Reply(_) => None,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use super::HandshakeError;
+use crate::proto::{
+    Command, ReplyKind, SocksMethod,
+    client::{Header, Request, UsernamePasswordRequest},
+    server,
+};
+use rama_net::{address::Authority, stream::Stream, user::Basic};
+
+#[derive(Debug, Clone, Default)]
+/// Socks5 client implementation, used to establish a connection
+/// to a destination over an (already established) connection with a socks5 server.
+///
+/// Username-Password authentication (RFC 1929) is offered to the
+/// server as an authentication method only in case credentials are defined.
+pub struct Socks5Client {
+    auth: Option<Basic>,
+}
+
+impl Socks5Client {
+    /// Create a new [`Socks5Client`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define the credentials used for username-password authentication.
+    pub fn with_auth(mut self, basic: Basic) -> Self {
+        self.auth = Some(basic);
+        self
+    }
+
+    /// Define the credentials used for username-password authentication.
+    pub fn set_auth(&mut self, basic: Basic) -> &mut Self {
+        self.auth = Some(basic);
+        self
+    }
+
+    /// Define the credentials used for username-password authentication, if any.
+    pub fn maybe_with_auth(mut self, basic: Option<Basic>) -> Self {
+        self.auth = basic;
+        self
+    }
+
+    /// Perform the socks5 handshake for a [`Command::Connect`] request
+    /// to the given destination over the given stream.
+    ///
+    /// On success the bind address as replied by the server is returned,
+    /// and the stream can be used to communicate with the destination.
+    pub async fn handshake_connect<S: Stream + Unpin>(
+        &self,
+        stream: &mut S,
+        destination: &Authority,
+    ) -> Result<Authority, HandshakeError> {
+        let methods = if self.auth.is_some() {
+            vec![
+                SocksMethod::UsernamePassword,
+                SocksMethod::NoAuthenticationRequired,
+            ]
+        } else {
+            vec![SocksMethod::NoAuthenticationRequired]
+        };
+        Header::new(methods)
+            .write_to(stream)
+            .await
+            .map_err(|err| HandshakeError::protocol(err).with_context("write client header"))?;
+
+        let server_header = server::Header::read_from(stream)
+            .await
+            .map_err(|err| HandshakeError::protocol(err).with_context("read server header"))?;
+        tracing::trace!(
+            method = %server_header.method,
+            "socks5 client: server header received",
+        );
+
+        match (server_header.method, &self.auth) {
+            (SocksMethod::NoAuthenticationRequired, _) => (),
+            (SocksMethod::UsernamePassword, Some(basic)) => {
+                UsernamePasswordRequest::new(basic.clone())
+                    .write_to(stream)
+                    .await
+                    .map_err(|err| {
+                        HandshakeError::protocol(err)
+                            .with_context("write username-password request")
+                    })?;
+                let response = server::UsernamePasswordResponse::read_from(stream)
+                    .await
+                    .map_err(|err| {
+                        HandshakeError::protocol(err)
+                            .with_context("read username-password response")
+                    })?;
+                if !response.success {
+                    return Err(HandshakeError::unauthorized());
+                }
+                tracing::trace!("socks5 client: username-password authentication succeeded");
+            }
+            (method, _) => return Err(HandshakeError::method_mismatch(method)),
+        }
+
+        Request::new(Command::Connect, destination.clone())
+            .write_to(stream)
+            .await
+            .map_err(|err| HandshakeError::protocol(err).with_context("write client request"))?;
+
+        let reply = server::Reply::read_from(stream)
+            .await
+            .map_err(|err| HandshakeError::protocol(err).with_context("read server reply"))?;
+        if reply.reply != ReplyKind::Succeeded {
+            return Err(HandshakeError::reply(reply.reply));
+        }
+
+        tracing::trace!(
+            %destination,
+            bind_address = %reply.bind_address,
+            "socks5 client: connect: handshake complete",
+        );
+        Ok(reply.bind_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_test::io::Builder;
+
+    #[tokio::test]
+    async fn test_handshake_connect_no_auth() {
+        let mut stream = Builder::new()
+            .write(b"\x05\x01\x00")
+            .read(b"\x05\x00")
+            .write(b"\x05\x01\x00\x03\x0bexample.com\x00\x50")
+            .read(b"\x05\x00\x00\x01\x7f\x00\x00\x01\x1f\x90")
+            .build();
+
+        let bind_address = Socks5Client::new()
+            .handshake_connect(&mut stream, &"example.com:80".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(bind_address, "127.0.0.1:8080".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_connect_username_password() {
+        let mut stream = Builder::new()
+            .write(b"\x05\x02\x02\x00")
+            .read(b"\x05\x02")
+            .write(b"\x01\x04john\x06secret")
+            .read(b"\x01\x00")
+            .write(b"\x05\x01\x00\x01\x7f\x00\x00\x01\x00\x50")
+            .read(b"\x05\x00\x00\x01\x00\x00\x00\x00\x00\x00")
+            .build();
+
+        let bind_address = Socks5Client::new()
+            .with_auth(Basic::new("john", "secret"))
+            .handshake_connect(&mut stream, &"127.0.0.1:80".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(bind_address, "0.0.0.0:0".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_connect_invalid_credentials() {
+        let mut stream = Builder::new()
+            .write(b"\x05\x02\x02\x00")
+            .read(b"\x05\x02")
+            .write(b"\x01\x04john\x06secret")
+            .read(b"\x01\x01")
+            .build();
+
+        let err = Socks5Client::new()
+            .with_auth(Basic::new("john", "secret"))
+            .handshake_connect(&mut stream, &"127.0.0.1:80".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert!(err.is_unauthorized());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_connect_no_acceptable_methods() {
+        let mut stream = Builder::new()
+            .write(b"\x05\x01\x00")
+            .read(b"\x05\xff")
+            .build();
+
+        let err = Socks5Client::new()
+            .handshake_connect(&mut stream, &"127.0.0.1:80".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert!(err.is_unauthorized());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_connect_error_reply() {
+        let mut stream = Builder::new()
+            .write(b"\x05\x01\x00")
+            .read(b"\x05\x00")
+            .write(b"\x05\x01\x00\x01\x7f\x00\x00\x01\x00\x50")
+            .read(b"\x05\x05\x00\x01\x00\x00\x00\x00\x00\x00")
+            .build();
+
+        let err = Socks5Client::new()
+            .handshake_connect(&mut stream, &"127.0.0.1:80".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(err.reply_kind(), Some(ReplyKind::ConnectionRefused));
+    }
+}
@@ -0,0 +1,17 @@
+//! Socks5 client support.
+//!
+//! The [`Socks5Client`] performs the socks5 handshake over an established
+//! connection, while the [`Socks5ProxyConnector`] can be used as part of a
+//! connector stack to tunnel connections over a socks5 proxy.
+
+mod error;
+#[doc(inline)]
+pub use error::HandshakeError;
+
+mod handshake;
+#[doc(inline)]
+pub use handshake::Socks5Client;
+
+mod proxy;
+#[doc(inline)]
+pub use proxy::{Socks5ProxyConnector, Socks5ProxyConnectorLayer};
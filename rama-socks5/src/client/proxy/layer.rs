@@ -0,0 +1,82 @@
+use super::Socks5ProxyConnector;
+use rama_core::{Layer, error::BoxError};
+use rama_dns::{DnsResolver, HickoryDns};
+use std::fmt;
+
+/// A [`Layer`] which wraps the given service with a [`Socks5ProxyConnector`].
+///
+/// See [`Socks5ProxyConnector`] for more information.
+pub struct Socks5ProxyConnectorLayer<Dns = HickoryDns> {
+    required: bool,
+    dns: Dns,
+}
+
+impl<Dns: fmt::Debug> fmt::Debug for Socks5ProxyConnectorLayer<Dns> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Socks5ProxyConnectorLayer")
+            .field("required", &self.required)
+            .field("dns", &self.dns)
+            .finish()
+    }
+}
+
+impl<Dns: Clone> Clone for Socks5ProxyConnectorLayer<Dns> {
+    fn clone(&self) -> Self {
+        Self {
+            required: self.required,
+            dns: self.dns.clone(),
+        }
+    }
+}
+
+impl Socks5ProxyConnectorLayer {
+    /// Create a new [`Socks5ProxyConnectorLayer`] which creates a [`Socks5ProxyConnector`]
+    /// which will only connect via a socks5 proxy in case the [`ProxyAddress`] is available
+    /// in the [`Context`].
+    ///
+    /// [`Context`]: rama_core::Context
+    /// [`ProxyAddress`]: rama_net::address::ProxyAddress
+    pub fn optional() -> Self {
+        Self {
+            required: false,
+            dns: HickoryDns::default(),
+        }
+    }
+
+    /// Create a new [`Socks5ProxyConnectorLayer`] which creates a [`Socks5ProxyConnector`]
+    /// which will always connect via a socks5 proxy, but fail in case the [`ProxyAddress`] is
+    /// not available in the [`Context`].
+    ///
+    /// [`Context`]: rama_core::Context
+    /// [`ProxyAddress`]: rama_net::address::ProxyAddress
+    pub fn required() -> Self {
+        Self {
+            required: true,
+            dns: HickoryDns::default(),
+        }
+    }
+}
+
+impl<Dns> Socks5ProxyConnectorLayer<Dns> {
+    /// Attach a custom [`DnsResolver`] to the created [`Socks5ProxyConnector`].
+    pub fn with_dns<OtherDns>(self, dns: OtherDns) -> Socks5ProxyConnectorLayer<OtherDns>
+    where
+        OtherDns: DnsResolver<Error: Into<BoxError>>,
+    {
+        Socks5ProxyConnectorLayer {
+            required: self.required,
+            dns,
+        }
+    }
+}
+
+impl<S, Dns> Layer<S> for Socks5ProxyConnectorLayer<Dns>
+where
+    Dns: DnsResolver<Error: Into<BoxError>> + Clone,
+{
+    type Service = Socks5ProxyConnector<S, Dns>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Socks5ProxyConnector::new(inner, self.required).with_dns(self.dns.clone())
+    }
+}
@@ -0,0 +1,7 @@
+mod layer;
+#[doc(inline)]
+pub use layer::Socks5ProxyConnectorLayer;
+
+mod service;
+#[doc(inline)]
+pub use service::Socks5ProxyConnector;
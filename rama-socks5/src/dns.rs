@@ -0,0 +1,39 @@
+use rama_core::error::{BoxError, OpaqueError};
+use rama_dns::DnsResolver;
+use rama_net::address::{Authority, Host};
+use std::net::{IpAddr, SocketAddr};
+
+/// Resolve the given [`Authority`] to a [`SocketAddr`],
+/// preferring an IPv4 address over an IPv6 address for domain names.
+pub(crate) async fn resolve_authority<Dns>(
+    dns: &Dns,
+    authority: Authority,
+) -> Result<SocketAddr, OpaqueError>
+where
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    let (host, port) = authority.into_parts();
+    let ip = match host {
+        Host::Address(ip) => ip,
+        Host::Name(domain) => {
+            let ipv4 = dns
+                .ipv4_lookup(domain.clone())
+                .await
+                .ok()
+                .and_then(|ips| ips.into_iter().next());
+            match ipv4 {
+                Some(ip) => IpAddr::V4(ip),
+                None => {
+                    let ips = dns
+                        .ipv6_lookup(domain)
+                        .await
+                        .map_err(|err| OpaqueError::from_boxed(err.into()))?;
+                    IpAddr::V6(*ips.first().ok_or_else(|| {
+                        OpaqueError::from_display("no ip address found for domain")
+                    })?)
+                }
+            }
+        }
+    };
+    Ok(SocketAddr::new(ip, port))
+}
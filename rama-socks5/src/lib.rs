@@ -1,5 +1,8 @@
 //! SOCKS5 support for Rama.
 //!
+//! - Socks5 (RFC 1928): <https://datatracker.ietf.org/doc/html/rfc1928>
+//! - Username/Password Authentication for SOCKS V5 (RFC 1929): <https://datatracker.ietf.org/doc/html/rfc1929>
+//!
 //! # Rama
 //!
 //! Crate used by the end-user `rama` crate and `rama` crate authors alike.
@@ -17,5 +20,14 @@
 #![cfg_attr(test, allow(clippy::float_cmp))]
 #![cfg_attr(not(test), warn(clippy::print_stdout, clippy::dbg_macro))]
 
-/// TODO
-pub const PLACEHOLDER: bool = true;
+pub mod proto;
+
+mod dns;
+
+pub mod client;
+#[doc(inline)]
+pub use client::{Socks5Client, Socks5ProxyConnector, Socks5ProxyConnectorLayer};
+
+pub mod server;
+#[doc(inline)]
+pub use server::Socks5Acceptor;
@@ -0,0 +1,352 @@
+//! Client-side SOCKS5 protocol messages,
+//! sent by the client and received by the server.
+
+use super::{
+    Command, ProtocolError, ProtocolVersion, SocksMethod, UsernamePasswordSubnegotiationVersion,
+    common::{authority_length, read_authority, write_authority_to_buf},
+};
+use bytes::BufMut;
+use rama_net::{address::Authority, user::Basic};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The initial message sent by the client,
+/// to negotiate the authentication method.
+///
+/// ```plain
+/// +----+----------+----------+
+/// |VER | NMETHODS | METHODS  |
+/// +----+----------+----------+
+/// | 1  |    1     | 1 to 255 |
+/// +----+----------+----------+
+/// ```
+pub struct Header {
+    pub version: ProtocolVersion,
+    pub methods: Vec<SocksMethod>,
+}
+
+impl Header {
+    /// Create a new SOCKS5 [`Header`] for the given methods.
+    pub fn new(methods: Vec<SocksMethod>) -> Self {
+        Self {
+            version: ProtocolVersion::Socks5,
+            methods,
+        }
+    }
+
+    /// Read the [`Header`] from the given reader.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let version: ProtocolVersion = r.read_u8().await?.into();
+        if version != ProtocolVersion::Socks5 {
+            return Err(ProtocolError::unexpected_byte(0, version.into()));
+        }
+
+        let n = r.read_u8().await?;
+        if n == 0 {
+            return Err(ProtocolError::unexpected_byte(1, n));
+        }
+
+        let mut raw = vec![0u8; n as usize];
+        r.read_exact(&mut raw).await?;
+        let methods = raw.into_iter().map(Into::into).collect();
+
+        Ok(Self { version, methods })
+    }
+
+    /// Write the [`Header`] to the given writer.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), ProtocolError> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        self.write_to_buf(&mut buf)?;
+        w.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Write the [`Header`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) -> Result<(), ProtocolError> {
+        let n: u8 = match self.methods.len() {
+            0 => {
+                return Err(ProtocolError::ValueTooLarge {
+                    field: "methods",
+                    len: 0,
+                });
+            }
+            n => n.try_into().map_err(|_| ProtocolError::ValueTooLarge {
+                field: "methods",
+                len: n,
+            })?,
+        };
+        buf.put_u8(self.version.into());
+        buf.put_u8(n);
+        for method in &self.methods {
+            buf.put_u8((*method).into());
+        }
+        Ok(())
+    }
+
+    /// Length of the [`Header`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        2 + self.methods.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The request sent by the client once the
+/// authentication method has been negotiated (and completed).
+///
+/// ```plain
+/// +----+-----+-------+------+----------+----------+
+/// |VER | CMD |  RSV  | ATYP | DST.ADDR | DST.PORT |
+/// +----+-----+-------+------+----------+----------+
+/// | 1  |  1  | X'00' |  1   | Variable |    2     |
+/// +----+-----+-------+------+----------+----------+
+/// ```
+pub struct Request {
+    pub version: ProtocolVersion,
+    pub command: Command,
+    pub destination: Authority,
+}
+
+impl Request {
+    /// Create a new SOCKS5 [`Request`] for the given command and destination.
+    pub fn new(command: Command, destination: Authority) -> Self {
+        Self {
+            version: ProtocolVersion::Socks5,
+            command,
+            destination,
+        }
+    }
+
+    /// Read the [`Request`] from the given reader.
+    ///
+    /// Unknown commands are not rejected by this function,
+    /// as the server is expected to reply to these explicitly.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let version: ProtocolVersion = r.read_u8().await?.into();
+        if version != ProtocolVersion::Socks5 {
+            return Err(ProtocolError::unexpected_byte(0, version.into()));
+        }
+
+        let command: Command = r.read_u8().await?.into();
+
+        let rsv = r.read_u8().await?;
+        if rsv != 0 {
+            return Err(ProtocolError::unexpected_byte(2, rsv));
+        }
+
+        let destination = read_authority(r, 3).await?;
+
+        Ok(Self {
+            version,
+            command,
+            destination,
+        })
+    }
+
+    /// Write the [`Request`] to the given writer.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), ProtocolError> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        self.write_to_buf(&mut buf)?;
+        w.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Write the [`Request`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) -> Result<(), ProtocolError> {
+        buf.put_u8(self.version.into());
+        buf.put_u8(self.command.into());
+        buf.put_u8(0);
+        write_authority_to_buf(&self.destination, buf)
+    }
+
+    /// Length of the [`Request`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        3 + authority_length(&self.destination)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The username-password request sent by the client,
+/// in case [`SocksMethod::UsernamePassword`] was negotiated.
+///
+/// ```plain
+/// +----+------+----------+------+----------+
+/// |VER | ULEN |  UNAME   | PLEN |  PASSWD  |
+/// +----+------+----------+------+----------+
+/// | 1  |  1   | 1 to 255 |  1   | 1 to 255 |
+/// +----+------+----------+------+----------+
+/// ```
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1929>
+pub struct UsernamePasswordRequest {
+    pub version: UsernamePasswordSubnegotiationVersion,
+    pub basic: Basic,
+}
+
+impl UsernamePasswordRequest {
+    /// Create a new [`UsernamePasswordRequest`] for the given credentials.
+    pub fn new(basic: Basic) -> Self {
+        Self {
+            version: UsernamePasswordSubnegotiationVersion::One,
+            basic,
+        }
+    }
+
+    /// Read the [`UsernamePasswordRequest`] from the given reader.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let version: UsernamePasswordSubnegotiationVersion = r.read_u8().await?.into();
+        if version != UsernamePasswordSubnegotiationVersion::One {
+            return Err(ProtocolError::unexpected_byte(0, version.into()));
+        }
+
+        let username_len = r.read_u8().await?;
+        if username_len == 0 {
+            return Err(ProtocolError::unexpected_byte(1, username_len));
+        }
+        let mut username = vec![0u8; username_len as usize];
+        r.read_exact(&mut username).await?;
+        let username =
+            String::from_utf8(username).map_err(|_| ProtocolError::InvalidCredentials)?;
+
+        let password_len = r.read_u8().await?;
+        let mut password = vec![0u8; password_len as usize];
+        r.read_exact(&mut password).await?;
+        let password =
+            String::from_utf8(password).map_err(|_| ProtocolError::InvalidCredentials)?;
+
+        Ok(Self {
+            version,
+            basic: Basic::new(username, password),
+        })
+    }
+
+    /// Write the [`UsernamePasswordRequest`] to the given writer.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), ProtocolError> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        self.write_to_buf(&mut buf)?;
+        w.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Write the [`UsernamePasswordRequest`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) -> Result<(), ProtocolError> {
+        let username = self.basic.username().as_bytes();
+        let username_len: u8 =
+            username
+                .len()
+                .try_into()
+                .map_err(|_| ProtocolError::ValueTooLarge {
+                    field: "username",
+                    len: username.len(),
+                })?;
+        let password = self.basic.password().as_bytes();
+        let password_len: u8 =
+            password
+                .len()
+                .try_into()
+                .map_err(|_| ProtocolError::ValueTooLarge {
+                    field: "password",
+                    len: password.len(),
+                })?;
+
+        buf.put_u8(self.version.into());
+        buf.put_u8(username_len);
+        buf.put_slice(username);
+        buf.put_u8(password_len);
+        buf.put_slice(password);
+        Ok(())
+    }
+
+    /// Length of the [`UsernamePasswordRequest`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        3 + self.basic.username().len() + self.basic.password().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_header_roundtrip() {
+        let header = Header::new(vec![
+            SocksMethod::NoAuthenticationRequired,
+            SocksMethod::UsernamePassword,
+        ]);
+        let mut buf = Vec::new();
+        header.write_to(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x05, 0x02, 0x00, 0x02]);
+        assert_eq!(buf.len(), header.serialized_len());
+
+        let parsed = Header::read_from(&mut &buf[..]).await.unwrap();
+        assert_eq!(parsed, header);
+    }
+
+    #[tokio::test]
+    async fn test_header_invalid_version() {
+        let err = Header::read_from(&mut &[0x04, 0x01, 0x00][..])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ProtocolError::UnexpectedByte { pos: 0, byte: 0x04 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_roundtrip() {
+        for (destination, expected) in [
+            (
+                "127.0.0.1:80",
+                vec![0x05, 0x01, 0x00, 0x01, 127, 0, 0, 1, 0, 80],
+            ),
+            (
+                "[::1]:443",
+                vec![
+                    0x05, 0x01, 0x00, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0xbb,
+                ],
+            ),
+            (
+                "example.com:8080",
+                vec![
+                    0x05, 0x01, 0x00, 0x03, 11, b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.',
+                    b'c', b'o', b'm', 0x1f, 0x90,
+                ],
+            ),
+        ] {
+            let request = Request::new(Command::Connect, destination.parse().unwrap());
+            let mut buf = Vec::new();
+            request.write_to(&mut buf).await.unwrap();
+            assert_eq!(buf, expected, "destination: {destination}");
+            assert_eq!(buf.len(), request.serialized_len());
+
+            let parsed = Request::read_from(&mut &buf[..]).await.unwrap();
+            assert_eq!(parsed, request);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_unknown_address_type() {
+        let err = Request::read_from(&mut &[0x05, 0x01, 0x00, 0x02, 0x00][..])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ProtocolError::UnexpectedByte { pos: 3, byte: 0x02 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_username_password_request_roundtrip() {
+        let request = UsernamePasswordRequest::new(Basic::new("john", "secret"));
+        let mut buf = Vec::new();
+        request.write_to(&mut buf).await.unwrap();
+        assert_eq!(buf, b"\x01\x04john\x06secret");
+        assert_eq!(buf.len(), request.serialized_len());
+
+        let parsed = UsernamePasswordRequest::read_from(&mut &buf[..])
+            .await
+            .unwrap();
+        assert_eq!(parsed.basic.username(), "john");
+        assert_eq!(parsed.basic.password(), "secret");
+    }
+}
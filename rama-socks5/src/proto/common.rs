@@ -0,0 +1,81 @@
+use super::{AddressType, ProtocolError};
+use bytes::BufMut;
+use rama_net::address::{Authority, Domain, Host};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Read an [`Authority`] encoded as `ATYP | ADDR | PORT`,
+/// where `pos` is the position of the `ATYP` byte within the message.
+pub(super) async fn read_authority<R: AsyncRead + Unpin>(
+    r: &mut R,
+    pos: usize,
+) -> Result<Authority, ProtocolError> {
+    let address_type: AddressType = r.read_u8().await?.into();
+    let host: Host = match address_type {
+        AddressType::IpV4 => {
+            let mut octets = [0u8; 4];
+            r.read_exact(&mut octets).await?;
+            Ipv4Addr::from(octets).into()
+        }
+        AddressType::DomainName => {
+            let len = r.read_u8().await? as usize;
+            let mut raw = vec![0u8; len];
+            r.read_exact(&mut raw).await?;
+            Domain::try_from(raw)
+                .map_err(|_| ProtocolError::InvalidDomain)?
+                .into()
+        }
+        AddressType::IpV6 => {
+            let mut octets = [0u8; 16];
+            r.read_exact(&mut octets).await?;
+            Ipv6Addr::from(octets).into()
+        }
+        AddressType::Unknown(byte) => {
+            return Err(ProtocolError::unexpected_byte(pos, byte));
+        }
+    };
+    let port = r.read_u16().await?;
+    Ok(Authority::new(host, port))
+}
+
+/// Write an [`Authority`] encoded as `ATYP | ADDR | PORT`.
+pub(super) fn write_authority_to_buf<B: BufMut>(
+    authority: &Authority,
+    buf: &mut B,
+) -> Result<(), ProtocolError> {
+    match authority.host() {
+        Host::Address(IpAddr::V4(addr)) => {
+            buf.put_u8(AddressType::IpV4.into());
+            buf.put_slice(&addr.octets());
+        }
+        Host::Address(IpAddr::V6(addr)) => {
+            buf.put_u8(AddressType::IpV6.into());
+            buf.put_slice(&addr.octets());
+        }
+        Host::Name(domain) => {
+            let raw = domain.as_str().as_bytes();
+            let len: u8 = raw
+                .len()
+                .try_into()
+                .map_err(|_| ProtocolError::ValueTooLarge {
+                    field: "domain",
+                    len: raw.len(),
+                })?;
+            buf.put_u8(AddressType::DomainName.into());
+            buf.put_u8(len);
+            buf.put_slice(raw);
+        }
+    }
+    buf.put_u16(authority.port());
+    Ok(())
+}
+
+/// Length in bytes of an [`Authority`] encoded as `ATYP | ADDR | PORT`.
+pub(super) fn authority_length(authority: &Authority) -> usize {
+    let addr_len = match authority.host() {
+        Host::Address(IpAddr::V4(_)) => 4,
+        Host::Address(IpAddr::V6(_)) => 16,
+        Host::Name(domain) => 1 + domain.as_str().len(),
+    };
+    1 + addr_len + 2
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+#[derive(Debug)]
+/// Error that can occur while reading or writing
+/// a SOCKS5 protocol message.
+pub enum ProtocolError {
+    /// I/O error while reading or writing the message.
+    IO(std::io::Error),
+    /// Unexpected byte found at the given position of the message.
+    UnexpectedByte {
+        /// Position of the byte within the message.
+        pos: usize,
+        /// The byte which was found.
+        byte: u8,
+    },
+    /// The message contained an invalid domain name.
+    InvalidDomain,
+    /// The message contained invalid (non UTF-8) credentials.
+    InvalidCredentials,
+    /// The message cannot be written as a (field) value is too large.
+    ValueTooLarge {
+        /// Name of the field which is too large.
+        field: &'static str,
+        /// Length of the value.
+        len: usize,
+    },
+}
+
+impl ProtocolError {
+    pub(crate) fn unexpected_byte(pos: usize, byte: u8) -> Self {
+        Self::UnexpectedByte { pos, byte }
+    }
+}
+
+impl From<std::io::Error> for ProtocolError {
+    fn from(value: std::io::Error) -> Self {
+        Self::IO(value)
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "I/O error: {err}"),
+            Self::UnexpectedByte { pos, byte } => {
+                write!(f, "unexpected byte {byte:#04x} at position {pos}")
+            }
+            Self::InvalidDomain => write!(f, "invalid domain name"),
+            Self::InvalidCredentials => write!(f, "invalid (non UTF-8) credentials"),
+            Self::ValueTooLarge { field, len } => {
+                write!(f, "value of field '{field}' is too large: {len} bytes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! SOCKS5 protocol implementation details.
+//!
+//! - Socks5 (RFC 1928): <https://datatracker.ietf.org/doc/html/rfc1928>
+//! - Username/Password Authentication for SOCKS V5 (RFC 1929): <https://datatracker.ietf.org/doc/html/rfc1929>
+
+use rama_utils::macros::enums::enum_builder;
+
+mod common;
+
+mod error;
+#[doc(inline)]
+pub use error::ProtocolError;
+
+pub mod client;
+pub mod server;
+
+mod udp;
+#[doc(inline)]
+pub use udp::UdpHeader;
+
+enum_builder! {
+    /// The SOCKS protocol version.
+    ///
+    /// Only SOCKS5 is supported by this crate.
+    @U8
+    pub enum ProtocolVersion {
+        Socks5 => 0x05,
+    }
+}
+
+enum_builder! {
+    /// Authentication methods that can be negotiated
+    /// between a SOCKS5 client and server.
+    ///
+    /// Values as assigned by IANA:
+    /// <https://www.iana.org/assignments/socks-methods/socks-methods.xhtml>
+    @U8
+    pub enum SocksMethod {
+        NoAuthenticationRequired => 0x00,
+        GSSAPI => 0x01,
+        UsernamePassword => 0x02,
+        ChallengeHandshakeAuthenticationProtocol => 0x03,
+        ChallengeResponseAuthenticationMethod => 0x05,
+        SecureSocksLayer => 0x06,
+        NDSAuthentication => 0x07,
+        MultiAuthenticationFramework => 0x08,
+        JSONParameterBlock => 0x09,
+        NoAcceptableMethods => 0xFF,
+    }
+}
+
+enum_builder! {
+    /// The command requested by the client in a SOCKS5 request.
+    @U8
+    pub enum Command {
+        Connect => 0x01,
+        Bind => 0x02,
+        UdpAssociate => 0x03,
+    }
+}
+
+enum_builder! {
+    /// The type of address used in SOCKS5 requests, replies
+    /// and UDP datagram headers.
+    @U8
+    pub enum AddressType {
+        IpV4 => 0x01,
+        DomainName => 0x03,
+        IpV6 => 0x04,
+    }
+}
+
+enum_builder! {
+    /// The reply field of a SOCKS5 reply, sent by the server.
+    @U8
+    pub enum ReplyKind {
+        Succeeded => 0x00,
+        GeneralServerFailure => 0x01,
+        ConnectionNotAllowed => 0x02,
+        NetworkUnreachable => 0x03,
+        HostUnreachable => 0x04,
+        ConnectionRefused => 0x05,
+        TtlExpired => 0x06,
+        CommandNotSupported => 0x07,
+        AddressTypeNotSupported => 0x08,
+    }
+}
+
+enum_builder! {
+    /// The version of the username-password sub-negotiation (RFC 1929).
+    @U8
+    pub enum UsernamePasswordSubnegotiationVersion {
+        One => 0x01,
+    }
+}
@@ -0,0 +1,279 @@
+//! Server-side SOCKS5 protocol messages,
+//! sent by the server and received by the client.
+
+use super::{
+    ProtocolError, ProtocolVersion, ReplyKind, SocksMethod, UsernamePasswordSubnegotiationVersion,
+    common::{authority_length, read_authority, write_authority_to_buf},
+};
+use bytes::BufMut;
+use rama_net::address::Authority;
+use std::net::Ipv4Addr;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The server's response to the client [`Header`],
+/// containing the selected authentication method.
+///
+/// ```plain
+/// +----+--------+
+/// |VER | METHOD |
+/// +----+--------+
+/// | 1  |   1    |
+/// +----+--------+
+/// ```
+///
+/// [`Header`]: super::client::Header
+pub struct Header {
+    pub version: ProtocolVersion,
+    pub method: SocksMethod,
+}
+
+impl Header {
+    /// Create a new SOCKS5 [`Header`] for the selected method.
+    pub fn new(method: SocksMethod) -> Self {
+        Self {
+            version: ProtocolVersion::Socks5,
+            method,
+        }
+    }
+
+    /// Read the [`Header`] from the given reader.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let version: ProtocolVersion = r.read_u8().await?.into();
+        if version != ProtocolVersion::Socks5 {
+            return Err(ProtocolError::unexpected_byte(0, version.into()));
+        }
+        let method = r.read_u8().await?.into();
+        Ok(Self { version, method })
+    }
+
+    /// Write the [`Header`] to the given writer.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), ProtocolError> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        self.write_to_buf(&mut buf);
+        w.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Write the [`Header`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u8(self.version.into());
+        buf.put_u8(self.method.into());
+    }
+
+    /// Length of the [`Header`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        2
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The server's reply to a client [`Request`].
+///
+/// ```plain
+/// +----+-----+-------+------+----------+----------+
+/// |VER | REP |  RSV  | ATYP | BND.ADDR | BND.PORT |
+/// +----+-----+-------+------+----------+----------+
+/// | 1  |  1  | X'00' |  1   | Variable |    2     |
+/// +----+-----+-------+------+----------+----------+
+/// ```
+///
+/// [`Request`]: super::client::Request
+pub struct Reply {
+    pub version: ProtocolVersion,
+    pub reply: ReplyKind,
+    pub bind_address: Authority,
+}
+
+impl Reply {
+    /// Create a new [`Reply`] for the given bind address.
+    pub fn new(bind_address: Authority) -> Self {
+        Self {
+            version: ProtocolVersion::Socks5,
+            reply: ReplyKind::Succeeded,
+            bind_address,
+        }
+    }
+
+    /// Create a new [`Reply`] to communicate the given (error) [`ReplyKind`],
+    /// using an unspecified bind address.
+    pub fn error_reply(kind: ReplyKind) -> Self {
+        Self {
+            version: ProtocolVersion::Socks5,
+            reply: kind,
+            bind_address: Authority::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+        }
+    }
+
+    /// Read the [`Reply`] from the given reader.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let version: ProtocolVersion = r.read_u8().await?.into();
+        if version != ProtocolVersion::Socks5 {
+            return Err(ProtocolError::unexpected_byte(0, version.into()));
+        }
+
+        let reply = r.read_u8().await?.into();
+
+        let rsv = r.read_u8().await?;
+        if rsv != 0 {
+            return Err(ProtocolError::unexpected_byte(2, rsv));
+        }
+
+        let bind_address = read_authority(r, 3).await?;
+
+        Ok(Self {
+            version,
+            reply,
+            bind_address,
+        })
+    }
+
+    /// Write the [`Reply`] to the given writer.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), ProtocolError> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        self.write_to_buf(&mut buf)?;
+        w.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Write the [`Reply`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) -> Result<(), ProtocolError> {
+        buf.put_u8(self.version.into());
+        buf.put_u8(self.reply.into());
+        buf.put_u8(0);
+        write_authority_to_buf(&self.bind_address, buf)
+    }
+
+    /// Length of the [`Reply`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        3 + authority_length(&self.bind_address)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The server's response to a client [`UsernamePasswordRequest`].
+///
+/// ```plain
+/// +----+--------+
+/// |VER | STATUS |
+/// +----+--------+
+/// | 1  |   1    |
+/// +----+--------+
+/// ```
+///
+/// A `STATUS` field of `X'00'` indicates success,
+/// any other value indicates failure.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1929>
+///
+/// [`UsernamePasswordRequest`]: super::client::UsernamePasswordRequest
+pub struct UsernamePasswordResponse {
+    pub version: UsernamePasswordSubnegotiationVersion,
+    pub success: bool,
+}
+
+impl UsernamePasswordResponse {
+    /// Create a new successful [`UsernamePasswordResponse`].
+    pub fn new_success() -> Self {
+        Self {
+            version: UsernamePasswordSubnegotiationVersion::One,
+            success: true,
+        }
+    }
+
+    /// Create a new failed [`UsernamePasswordResponse`].
+    pub fn new_invalid_credentials() -> Self {
+        Self {
+            version: UsernamePasswordSubnegotiationVersion::One,
+            success: false,
+        }
+    }
+
+    /// Read the [`UsernamePasswordResponse`] from the given reader.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let version: UsernamePasswordSubnegotiationVersion = r.read_u8().await?.into();
+        if version != UsernamePasswordSubnegotiationVersion::One {
+            return Err(ProtocolError::unexpected_byte(0, version.into()));
+        }
+        let status = r.read_u8().await?;
+        Ok(Self {
+            version,
+            success: status == 0,
+        })
+    }
+
+    /// Write the [`UsernamePasswordResponse`] to the given writer.
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, w: &mut W) -> Result<(), ProtocolError> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        self.write_to_buf(&mut buf);
+        w.write_all(&buf).await?;
+        Ok(())
+    }
+
+    /// Write the [`UsernamePasswordResponse`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u8(self.version.into());
+        buf.put_u8(if self.success { 0 } else { 1 });
+    }
+
+    /// Length of the [`UsernamePasswordResponse`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_header_roundtrip() {
+        let header = Header::new(SocksMethod::UsernamePassword);
+        let mut buf = Vec::new();
+        header.write_to(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x05, 0x02]);
+
+        let parsed = Header::read_from(&mut &buf[..]).await.unwrap();
+        assert_eq!(parsed, header);
+    }
+
+    #[tokio::test]
+    async fn test_reply_roundtrip() {
+        let reply = Reply::new("127.0.0.1:1080".parse().unwrap());
+        let mut buf = Vec::new();
+        reply.write_to(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0x04, 0x38]);
+        assert_eq!(buf.len(), reply.serialized_len());
+
+        let parsed = Reply::read_from(&mut &buf[..]).await.unwrap();
+        assert_eq!(parsed, reply);
+    }
+
+    #[tokio::test]
+    async fn test_error_reply() {
+        let reply = Reply::error_reply(ReplyKind::CommandNotSupported);
+        let mut buf = Vec::new();
+        reply.write_to(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x05, 0x07, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_username_password_response_roundtrip() {
+        for (response, expected) in [
+            (UsernamePasswordResponse::new_success(), [0x01, 0x00]),
+            (
+                UsernamePasswordResponse::new_invalid_credentials(),
+                [0x01, 0x01],
+            ),
+        ] {
+            let mut buf = Vec::new();
+            response.write_to(&mut buf).await.unwrap();
+            assert_eq!(buf, expected);
+
+            let parsed = UsernamePasswordResponse::read_from(&mut &buf[..])
+                .await
+                .unwrap();
+            assert_eq!(parsed, response);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::{
+    ProtocolError,
+    common::{authority_length, read_authority, write_authority_to_buf},
+};
+use bytes::BufMut;
+use rama_net::address::Authority;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The header prefixed to each UDP datagram
+/// relayed as part of a UDP association.
+///
+/// ```plain
+/// +----+------+------+----------+----------+----------+
+/// |RSV | FRAG | ATYP | DST.ADDR | DST.PORT |   DATA   |
+/// +----+------+------+----------+----------+----------+
+/// | 2  |  1   |  1   | Variable |    2     | Variable |
+/// +----+------+------+----------+----------+----------+
+/// ```
+pub struct UdpHeader {
+    pub fragment_number: u8,
+    pub destination: Authority,
+}
+
+impl UdpHeader {
+    /// Create a new (unfragmented) [`UdpHeader`] for the given destination.
+    pub fn new(destination: Authority) -> Self {
+        Self {
+            fragment_number: 0,
+            destination,
+        }
+    }
+
+    /// Read the [`UdpHeader`] from the given reader.
+    ///
+    /// Reading from a `&mut &[u8]` datagram will leave
+    /// the slice pointing to the data of the datagram.
+    pub async fn read_from<R: AsyncRead + Unpin>(r: &mut R) -> Result<Self, ProtocolError> {
+        let rsv = r.read_u16().await?;
+        if rsv != 0 {
+            return Err(ProtocolError::unexpected_byte(0, (rsv >> 8) as u8));
+        }
+        let fragment_number = r.read_u8().await?;
+        let destination = read_authority(r, 3).await?;
+        Ok(Self {
+            fragment_number,
+            destination,
+        })
+    }
+
+    /// Write the [`UdpHeader`] to the given buffer.
+    pub fn write_to_buf<B: BufMut>(&self, buf: &mut B) -> Result<(), ProtocolError> {
+        buf.put_u16(0);
+        buf.put_u8(self.fragment_number);
+        write_authority_to_buf(&self.destination, buf)
+    }
+
+    /// Length of the [`UdpHeader`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        3 + authority_length(&self.destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_udp_header_roundtrip() {
+        let header = UdpHeader::new("127.0.0.1:53".parse().unwrap());
+        let mut buf = Vec::new();
+        header.write_to_buf(&mut buf).unwrap();
+        assert_eq!(buf.len(), header.serialized_len());
+        buf.extend_from_slice(b"hello");
+        assert_eq!(
+            buf,
+            [
+                0, 0, 0, 0x01, 127, 0, 0, 1, 0, 53, b'h', b'e', b'l', b'l', b'o'
+            ]
+        );
+
+        let mut datagram = &buf[..];
+        let parsed = UdpHeader::read_from(&mut datagram).await.unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(datagram, b"hello");
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Error, error::reply_kind_for_error};
+use crate::proto::{ReplyKind, server::Reply};
+use rama_core::Context;
+use rama_net::{
+    address::Authority,
+    stream::{SocketInfo, Stream},
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::net::TcpListener;
+
+/// Types which can be used as socks5 [`Command::Bind`] drivers on the server side.
+///
+/// Typically used as a component part of a [`Socks5Acceptor`].
+///
+/// The `()` implementation replies with [`ReplyKind::CommandNotSupported`]
+/// and aborts the connection.
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+/// [`Command::Bind`]: crate::proto::Command::Bind
+pub trait Socks5Binder<S, IO>: Send + Sync + 'static {
+    /// Accept the [`Command::Bind`] request for the given destination.
+    ///
+    /// The implementation is responsible for replying to the client,
+    /// which in case of success happens twice: once to communicate the bound address
+    /// and once more when the incoming connection has been established.
+    ///
+    /// [`Command::Bind`]: crate::proto::Command::Bind
+    fn accept_bind(
+        &self,
+        ctx: Context<S>,
+        stream: IO,
+        destination: Authority,
+    ) -> impl Future<Output = Result<(), Error>> + Send + '_;
+}
+
+impl<S, IO> Socks5Binder<S, IO> for ()
+where
+    S: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+{
+    async fn accept_bind(
+        &self,
+        _ctx: Context<S>,
+        mut stream: IO,
+        destination: Authority,
+    ) -> Result<(), Error> {
+        tracing::debug!(
+            %destination,
+            "socks5 server: abort: command not supported: bind",
+        );
+        Reply::error_reply(ReplyKind::CommandNotSupported)
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+        Err(Error::aborted("command not supported: bind"))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Default [`Socks5Binder`] which can be used by the [`Socks5Acceptor`].
+///
+/// It binds a TCP listener on the bind interface, replies with its address,
+/// accepts a single incoming connection (within the accept timeout) and
+/// afterwards copies the data bidirectionally between the client and the incoming connection.
+///
+/// By default the listener is bound on the same local IP address
+/// as the one the client connected to (if known via [`SocketInfo`]),
+/// falling back to the unspecified IPv4 address.
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+pub struct Binder {
+    bind_interface: Option<IpAddr>,
+    accept_timeout: Duration,
+}
+
+impl Binder {
+    /// Create a new [`Binder`].
+    pub const fn new() -> Self {
+        Self {
+            bind_interface: None,
+            accept_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Define the IP address of the interface to bind the listener on.
+    pub fn with_bind_interface(mut self, ip: impl Into<IpAddr>) -> Self {
+        self.bind_interface = Some(ip.into());
+        self
+    }
+
+    /// Define the IP address of the interface to bind the listener on.
+    pub fn set_bind_interface(&mut self, ip: impl Into<IpAddr>) -> &mut Self {
+        self.bind_interface = Some(ip.into());
+        self
+    }
+
+    /// Define the maximum duration to wait for an incoming connection.
+    pub fn with_accept_timeout(mut self, timeout: Duration) -> Self {
+        self.accept_timeout = timeout;
+        self
+    }
+
+    /// Define the maximum duration to wait for an incoming connection.
+    pub fn set_accept_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.accept_timeout = timeout;
+        self
+    }
+}
+
+impl Default for Binder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, IO> Socks5Binder<S, IO> for Binder
+where
+    S: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+{
+    async fn accept_bind(
+        &self,
+        ctx: Context<S>,
+        mut stream: IO,
+        destination: Authority,
+    ) -> Result<(), Error> {
+        let bind_ip = self
+            .bind_interface
+            .or_else(|| {
+                ctx.get::<SocketInfo>()
+                    .and_then(|info| info.local_addr())
+                    .map(|addr| addr.ip())
+            })
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        let listener = match TcpListener::bind(SocketAddr::new(bind_ip, 0)).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                let reply_kind = reply_kind_for_error(&err);
+                tracing::debug!(
+                    %destination,
+                    %reply_kind,
+                    error = %err,
+                    "socks5 server: bind: failed to bind listener",
+                );
+                Reply::error_reply(reply_kind)
+                    .write_to(&mut stream)
+                    .await
+                    .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+                return Err(Error::io(err).with_context("bind listener"));
+            }
+        };
+
+        let local_addr = listener
+            .local_addr()
+            .map_err(|err| Error::io(err).with_context("listener local address"))?;
+        tracing::trace!(
+            %destination,
+            %local_addr,
+            "socks5 server: bind: listener bound, wait for incoming connection",
+        );
+        Reply::new(local_addr.into())
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write first server reply"))?;
+
+        let (mut incoming, peer_addr) =
+            match tokio::time::timeout(self.accept_timeout, listener.accept()).await {
+                Ok(Ok(accepted)) => accepted,
+                Ok(Err(err)) => {
+                    let reply_kind = reply_kind_for_error(&err);
+                    Reply::error_reply(reply_kind)
+                        .write_to(&mut stream)
+                        .await
+                        .map_err(|err| {
+                            Error::protocol(err).with_context("write second server reply")
+                        })?;
+                    return Err(Error::io(err).with_context("accept incoming connection"));
+                }
+                Err(_) => {
+                    Reply::error_reply(ReplyKind::TtlExpired)
+                        .write_to(&mut stream)
+                        .await
+                        .map_err(|err| {
+                            Error::protocol(err).with_context("write second server reply")
+                        })?;
+                    return Err(Error::aborted("bind: accept timeout"));
+                }
+            };
+
+        tracing::trace!(
+            %destination,
+            %peer_addr,
+            "socks5 server: bind: incoming connection accepted",
+        );
+        Reply::new(peer_addr.into())
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write second server reply"))?;
+
+        match tokio::io::copy_bidirectional(&mut stream, &mut incoming).await {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                if rama_net::conn::is_connection_error(&err) {
+                    Ok(())
+                } else {
+                    Err(Error::io(err).with_context("forward bytes"))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use super::{Error, error::reply_kind_for_error};
+use crate::proto::{ReplyKind, server::Reply};
+use rama_core::{Context, Service, error::BoxError};
+use rama_net::{
+    address::Authority,
+    client::{ConnectorService, EstablishedClientConnection},
+    stream::{ClientSocketInfo, Stream},
+};
+use rama_tcp::client::{
+    Request as TcpRequest,
+    service::{ForwardAuthority, TcpConnector},
+};
+use std::{fmt, net::Ipv4Addr};
+
+/// Types which can be used as socks5 [`Command::Connect`] drivers on the server side.
+///
+/// Typically used as a component part of a [`Socks5Acceptor`].
+///
+/// The `()` implementation replies with [`ReplyKind::CommandNotSupported`]
+/// and aborts the connection.
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+/// [`Command::Connect`]: crate::proto::Command::Connect
+pub trait Socks5Connector<S, IO>: Send + Sync + 'static {
+    /// Accept the [`Command::Connect`] request for the given destination.
+    ///
+    /// The implementation is responsible for replying to the client.
+    ///
+    /// [`Command::Connect`]: crate::proto::Command::Connect
+    fn accept_connect(
+        &self,
+        ctx: Context<S>,
+        stream: IO,
+        destination: Authority,
+    ) -> impl Future<Output = Result<(), Error>> + Send + '_;
+}
+
+impl<S, IO> Socks5Connector<S, IO> for ()
+where
+    S: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+{
+    async fn accept_connect(
+        &self,
+        _ctx: Context<S>,
+        mut stream: IO,
+        destination: Authority,
+    ) -> Result<(), Error> {
+        tracing::debug!(
+            %destination,
+            "socks5 server: abort: command not supported: connect",
+        );
+        Reply::error_reply(ReplyKind::CommandNotSupported)
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+        Err(Error::aborted("command not supported: connect"))
+    }
+}
+
+/// A [`Socks5Connector`] which connects to any destination requested by the client.
+///
+/// It establishes a connection to the requested destination
+/// using the inner connector (a [`TcpConnector`] by default),
+/// replies with the local address of that connection and
+/// afterwards copies the data bidirectionally between the client and destination.
+///
+/// Use [`LazyConnector`] instead in case you wish to hand over
+/// the client stream to a custom [`Service`] (e.g. for MITM purposes).
+///
+/// As it does not restrict the destinations a client can connect to,
+/// make sure that only trusted clients can reach the [`Socks5Acceptor`]
+/// it is attached to. See the [security section] for more information.
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+/// [security section]: super::Socks5Acceptor#security
+pub struct Connector<C = TcpConnector> {
+    connector: C,
+}
+
+impl Connector {
+    /// Create a new [`Connector`] using the default [`TcpConnector`].
+    pub fn new() -> Self {
+        Self {
+            connector: TcpConnector::new(),
+        }
+    }
+}
+
+impl<C> Connector<C> {
+    /// Use a custom connector service to establish the connection
+    /// to the requested destination.
+    pub fn with_connector<T>(self, connector: T) -> Connector<T> {
+        Connector { connector }
+    }
+}
+
+impl Default for Connector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Connector<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connector")
+            .field("connector", &self.connector)
+            .finish()
+    }
+}
+
+impl<C: Clone> Clone for Connector<C> {
+    fn clone(&self) -> Self {
+        Self {
+            connector: self.connector.clone(),
+        }
+    }
+}
+
+impl<S, IO, C> Socks5Connector<S, IO> for Connector<C>
+where
+    S: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+    C: ConnectorService<S, TcpRequest, Connection: Stream + Unpin, Error: Into<BoxError>>,
+{
+    async fn accept_connect(
+        &self,
+        ctx: Context<S>,
+        mut stream: IO,
+        destination: Authority,
+    ) -> Result<(), Error> {
+        tracing::trace!(%destination, "socks5 server: connect: establish connection");
+
+        let req = TcpRequest::new(destination.clone());
+        let result = self.connector.connect(ctx, req).await.map_err(Into::into);
+        let EstablishedClientConnection { ctx, mut conn, .. } = match result {
+            Ok(established) => established,
+            Err(err) => {
+                let err: BoxError = err;
+                let reply_kind = reply_kind_for_error(err.as_ref());
+                tracing::debug!(
+                    %destination,
+                    %reply_kind,
+                    error = %err,
+                    "socks5 server: connect: failed to establish connection",
+                );
+                Reply::error_reply(reply_kind)
+                    .write_to(&mut stream)
+                    .await
+                    .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+                return Err(Error::service(err).with_context("connect to destination"));
+            }
+        };
+
+        let bind_address = ctx
+            .get::<ClientSocketInfo>()
+            .and_then(|info| info.local_addr().copied())
+            .map(Authority::from)
+            .unwrap_or_else(|| Authority::new(Ipv4Addr::UNSPECIFIED.into(), 0));
+        Reply::new(bind_address)
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+
+        tracing::trace!(%destination, "socks5 server: connect: forward bytes");
+        match tokio::io::copy_bidirectional(&mut stream, &mut conn).await {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                if rama_net::conn::is_connection_error(&err) {
+                    Ok(())
+                } else {
+                    Err(Error::io(err).with_context("forward bytes"))
+                }
+            }
+        }
+    }
+}
+
+/// A [`Socks5Connector`] which replies with success immediately,
+/// and hands over the client stream to the inner [`Service`].
+///
+/// The requested destination is inserted as a [`ForwardAuthority`]
+/// in the [`Context`], such that it can for example be served by
+/// a [`Forwarder::ctx`], optionally wrapped with your own middleware.
+///
+/// The reply's bind address is unspecified (`0.0.0.0:0`),
+/// as no connection is established prior to replying.
+///
+/// [`Forwarder::ctx`]: rama_tcp::client::service::Forwarder::ctx
+pub struct LazyConnector<S> {
+    service: S,
+}
+
+impl<S> LazyConnector<S> {
+    /// Create a new [`LazyConnector`] for the given [`Service`].
+    pub const fn new(service: S) -> Self {
+        Self { service }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for LazyConnector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyConnector")
+            .field("service", &self.service)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for LazyConnector<S> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+        }
+    }
+}
+
+impl<State, IO, S> Socks5Connector<State, IO> for LazyConnector<S>
+where
+    State: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+    S: Service<State, IO, Response = (), Error: Into<BoxError>>,
+{
+    async fn accept_connect(
+        &self,
+        mut ctx: Context<State>,
+        mut stream: IO,
+        destination: Authority,
+    ) -> Result<(), Error> {
+        Reply::new(Authority::new(Ipv4Addr::UNSPECIFIED.into(), 0))
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+
+        tracing::trace!(%destination, "socks5 server: lazy connect: serve inner service");
+        ctx.insert(ForwardAuthority::new(destination));
+        self.service
+            .serve(ctx, stream)
+            .await
+            .map_err(|err| Error::service(err).with_context("serve lazy connect"))
+    }
+}
@@ -0,0 +1,120 @@
+use crate::proto::{ProtocolError, ReplyKind};
+use rama_core::error::BoxError;
+use std::fmt;
+
+#[derive(Debug)]
+/// Error returned by the socks5 server implementation,
+/// such as the [`Socks5Acceptor`].
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+pub struct Error {
+    kind: ErrorKind,
+    context: Option<&'static str>,
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    IO(std::io::Error),
+    Protocol(ProtocolError),
+    Aborted(&'static str),
+    Service(BoxError),
+}
+
+impl Error {
+    pub(crate) fn io(error: std::io::Error) -> Self {
+        Self {
+            kind: ErrorKind::IO(error),
+            context: None,
+        }
+    }
+
+    pub(crate) fn protocol(error: ProtocolError) -> Self {
+        Self {
+            kind: ErrorKind::Protocol(error),
+            context: None,
+        }
+    }
+
+    pub(crate) fn aborted(reason: &'static str) -> Self {
+        Self {
+            kind: ErrorKind::Aborted(reason),
+            context: None,
+        }
+    }
+
+    pub(crate) fn service(error: impl Into<BoxError>) -> Self {
+        Self {
+            kind: ErrorKind::Service(error.into()),
+            context: None,
+        }
+    }
+
+    pub(crate) fn with_context(mut self, context: &'static str) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Returns `true` in case the error was caused by
+    /// the server aborting the socks5 handshake (e.g. unauthorized client).
+    pub fn is_aborted(&self) -> bool {
+        matches!(self.kind, ErrorKind::Aborted(_))
+    }
+}
+
+impl From<ProtocolError> for Error {
+    fn from(value: ProtocolError) -> Self {
+        Self::protocol(value)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::io(value)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("socks5 server: ")?;
+        if let Some(context) = self.context {
+            write!(f, "{context}: ")?;
+        }
+        match &self.kind {
+            ErrorKind::IO(err) => write!(f, "I/O error: {err}"),
+            ErrorKind::Protocol(err) => write!(f, "protocol error: {err}"),
+            ErrorKind::Aborted(reason) => write!(f, "aborted: {reason}"),
+            ErrorKind::Service(err) => write!(f, "service error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::IO(err) => Some(err),
+            ErrorKind::Protocol(err) => Some(err),
+            ErrorKind::Aborted(_) => None,
+            ErrorKind::Service(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Map an error (chain) to the most appropriate [`ReplyKind`],
+/// defaulting to [`ReplyKind::GeneralServerFailure`].
+pub(super) fn reply_kind_for_error(error: &(dyn std::error::Error + 'static)) -> ReplyKind {
+    let mut next = Some(error);
+    while let Some(err) = next {
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            return match err.kind() {
+                std::io::ErrorKind::ConnectionRefused => ReplyKind::ConnectionRefused,
+                std::io::ErrorKind::HostUnreachable => ReplyKind::HostUnreachable,
+                std::io::ErrorKind::NetworkUnreachable => ReplyKind::NetworkUnreachable,
+                std::io::ErrorKind::TimedOut => ReplyKind::TtlExpired,
+                std::io::ErrorKind::PermissionDenied => ReplyKind::ConnectionNotAllowed,
+                _ => ReplyKind::GeneralServerFailure,
+            };
+        }
+        next = err.source();
+    }
+    ReplyKind::GeneralServerFailure
+}
@@ -0,0 +1,486 @@
+//! Socks5 Server Implementation for Rama.
+//!
+//! See [`Socks5Acceptor`] for more information.
+//! Its [`Default`] implementation denies all commands,
+//! a [`Connector`] has to be attached explicitly to serve
+//! the [`Command::Connect`] command.
+//!
+//! [`Command::Connect`]: crate::proto::Command::Connect
+
+use crate::proto::{
+    Command, ProtocolError, ReplyKind, SocksMethod,
+    client::{Header, Request, UsernamePasswordRequest},
+    server::{Header as ServerHeader, Reply, UsernamePasswordResponse},
+};
+use rama_core::{Context, Service};
+use rama_net::{
+    stream::Stream,
+    user::{Basic, auth::Authority},
+};
+use std::fmt;
+
+mod error;
+#[doc(inline)]
+pub use error::Error;
+
+mod connect;
+#[doc(inline)]
+pub use connect::{Connector, LazyConnector, Socks5Connector};
+
+mod bind;
+#[doc(inline)]
+pub use bind::{Binder, Socks5Binder};
+
+mod udp;
+#[doc(inline)]
+pub use udp::{Socks5UdpAssociator, UdpRelay};
+
+/// Socks5 server implementation of [RFC 1928]
+///
+/// An instance constructed with [`Socks5Acceptor::new`] denies all commands,
+/// replying with [`ReplyKind::CommandNotSupported`]. Use
+/// [`Socks5Acceptor::with_connector`], [`Socks5Acceptor::with_binder`] and
+/// [`Socks5Acceptor::with_udp_associator`] to support the [`Command::Connect`],
+/// [`Command::Bind`] and [`Command::UdpAssociate`] commands.
+///
+/// # Security
+///
+/// The [`Connector`], [`Binder`] and [`UdpRelay`] drivers relay traffic
+/// to any destination requested by the client. Attaching them to an acceptor
+/// which can be reached by untrusted clients turns it into an open proxy.
+/// Restrict access to such an acceptor, e.g. by requiring authentication
+/// using [`Socks5Acceptor::with_authorizer`], by only accepting
+/// connections from trusted networks, or by using a custom driver
+/// (e.g. a [`LazyConnector`]) which only allows the destinations you intend to serve.
+///
+/// Username-password authentication ([RFC 1929]) is enabled
+/// by attaching an [`Authority`] for [`Basic`] credentials,
+/// using [`Socks5Acceptor::with_authorizer`]. The [`Extensions`]
+/// returned by that authority (e.g. the [`UserId`]) are added to the [`Context`].
+///
+/// The [`Socks5Acceptor`] is a [`Service`] which serves any [`Stream`],
+/// and thus can be used directly with a `TcpListener` or as an inner
+/// service of other stream middleware (e.g. a `HaProxyService`).
+///
+/// [RFC 1928]: https://datatracker.ietf.org/doc/html/rfc1928
+/// [RFC 1929]: https://datatracker.ietf.org/doc/html/rfc1929
+/// [`Extensions`]: rama_core::context::Extensions
+/// [`UserId`]: rama_net::user::UserId
+pub struct Socks5Acceptor<C = (), B = (), U = (), A = Basic> {
+    connector: C,
+    binder: B,
+    udp_associator: U,
+    auth: Option<A>,
+    auth_opt: bool,
+}
+
+impl Socks5Acceptor {
+    /// Create a new [`Socks5Acceptor`] which denies all commands,
+    /// without any authentication.
+    pub fn new() -> Self {
+        Self {
+            connector: (),
+            binder: (),
+            udp_associator: (),
+            auth: None,
+            auth_opt: false,
+        }
+    }
+}
+
+impl Default for Socks5Acceptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, B, U, A> Socks5Acceptor<C, B, U, A> {
+    /// Attach an [`Authority`] to this [`Socks5Acceptor`],
+    /// used to authorize clients using [`Basic`] credentials
+    /// via the username-password authentication method.
+    pub fn with_authorizer<T>(self, authorizer: T) -> Socks5Acceptor<C, B, U, T>
+    where
+        T: Authority<Basic, ()>,
+    {
+        Socks5Acceptor {
+            connector: self.connector,
+            binder: self.binder,
+            udp_associator: self.udp_associator,
+            auth: Some(authorizer),
+            auth_opt: self.auth_opt,
+        }
+    }
+
+    /// Define whether or not clients which do not support
+    /// username-password authentication are still allowed
+    /// (unauthenticated) in case an authorizer is attached.
+    pub fn with_auth_optional(mut self, optional: bool) -> Self {
+        self.auth_opt = optional;
+        self
+    }
+
+    /// Define whether or not clients which do not support
+    /// username-password authentication are still allowed
+    /// (unauthenticated) in case an authorizer is attached.
+    pub fn set_auth_optional(&mut self, optional: bool) -> &mut Self {
+        self.auth_opt = optional;
+        self
+    }
+
+    /// Overwrite the [`Socks5Connector`] used to serve
+    /// the [`Command::Connect`] command.
+    ///
+    /// See the [security section](Socks5Acceptor#security)
+    /// prior to attaching a [`Connector`].
+    pub fn with_connector<T>(self, connector: T) -> Socks5Acceptor<T, B, U, A> {
+        Socks5Acceptor {
+            connector,
+            binder: self.binder,
+            udp_associator: self.udp_associator,
+            auth: self.auth,
+            auth_opt: self.auth_opt,
+        }
+    }
+
+    /// Overwrite the [`Socks5Binder`] used to serve
+    /// the [`Command::Bind`] command.
+    pub fn with_binder<T>(self, binder: T) -> Socks5Acceptor<C, T, U, A> {
+        Socks5Acceptor {
+            connector: self.connector,
+            binder,
+            udp_associator: self.udp_associator,
+            auth: self.auth,
+            auth_opt: self.auth_opt,
+        }
+    }
+
+    /// Overwrite the [`Socks5UdpAssociator`] used to serve
+    /// the [`Command::UdpAssociate`] command.
+    pub fn with_udp_associator<T>(self, udp_associator: T) -> Socks5Acceptor<C, B, T, A> {
+        Socks5Acceptor {
+            connector: self.connector,
+            binder: self.binder,
+            udp_associator,
+            auth: self.auth,
+            auth_opt: self.auth_opt,
+        }
+    }
+}
+
+impl<C: fmt::Debug, B: fmt::Debug, U: fmt::Debug, A: fmt::Debug> fmt::Debug
+    for Socks5Acceptor<C, B, U, A>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Socks5Acceptor")
+            .field("connector", &self.connector)
+            .field("binder", &self.binder)
+            .field("udp_associator", &self.udp_associator)
+            .field("auth", &self.auth)
+            .field("auth_opt", &self.auth_opt)
+            .finish()
+    }
+}
+
+impl<C: Clone, B: Clone, U: Clone, A: Clone> Clone for Socks5Acceptor<C, B, U, A> {
+    fn clone(&self) -> Self {
+        Self {
+            connector: self.connector.clone(),
+            binder: self.binder.clone(),
+            udp_associator: self.udp_associator.clone(),
+            auth: self.auth.clone(),
+            auth_opt: self.auth_opt,
+        }
+    }
+}
+
+impl<State, IO, C, B, U, A> Service<State, IO> for Socks5Acceptor<C, B, U, A>
+where
+    State: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+    C: Socks5Connector<State, IO>,
+    B: Socks5Binder<State, IO>,
+    U: Socks5UdpAssociator<State, IO>,
+    A: Authority<Basic, ()>,
+{
+    type Response = ();
+    type Error = Error;
+
+    async fn serve(&self, mut ctx: Context<State>, mut stream: IO) -> Result<(), Self::Error> {
+        let client_header = Header::read_from(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("read client header"))?;
+
+        let method = self.select_method(&client_header.methods);
+        tracing::trace!(
+            ?client_header.methods,
+            %method,
+            "socks5 server: method selected",
+        );
+
+        ServerHeader::new(method)
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server header"))?;
+
+        match method {
+            SocksMethod::NoAuthenticationRequired => (),
+            SocksMethod::UsernamePassword => {
+                let request = UsernamePasswordRequest::read_from(&mut stream)
+                    .await
+                    .map_err(|err| {
+                        Error::protocol(err).with_context("read username-password request")
+                    })?;
+
+                let authorized = match self.auth.as_ref() {
+                    Some(auth) => auth.authorized(request.basic).await,
+                    None => None,
+                };
+
+                match authorized {
+                    Some(ext) => {
+                        ctx.extend(ext);
+                        UsernamePasswordResponse::new_success()
+                            .write_to(&mut stream)
+                            .await
+                            .map_err(|err| {
+                                Error::protocol(err)
+                                    .with_context("write username-password response")
+                            })?;
+                    }
+                    None => {
+                        UsernamePasswordResponse::new_invalid_credentials()
+                            .write_to(&mut stream)
+                            .await
+                            .map_err(|err| {
+                                Error::protocol(err)
+                                    .with_context("write username-password response")
+                            })?;
+                        return Err(Error::aborted("unauthorized client"));
+                    }
+                }
+            }
+            _ => {
+                return Err(Error::aborted("no acceptable authentication method"));
+            }
+        }
+
+        let request = match Request::read_from(&mut stream).await {
+            Ok(request) => request,
+            Err(err) => {
+                if let ProtocolError::UnexpectedByte { pos: 3, .. } | ProtocolError::InvalidDomain =
+                    err
+                {
+                    Reply::error_reply(ReplyKind::AddressTypeNotSupported)
+                        .write_to(&mut stream)
+                        .await
+                        .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+                }
+                return Err(Error::protocol(err).with_context("read client request"));
+            }
+        };
+
+        tracing::trace!(
+            command = %request.command,
+            destination = %request.destination,
+            "socks5 server: client request received",
+        );
+
+        match request.command {
+            Command::Connect => {
+                self.connector
+                    .accept_connect(ctx, stream, request.destination)
+                    .await
+            }
+            Command::Bind => {
+                self.binder
+                    .accept_bind(ctx, stream, request.destination)
+                    .await
+            }
+            Command::UdpAssociate => {
+                self.udp_associator
+                    .accept_udp_associate(ctx, stream, request.destination)
+                    .await
+            }
+            Command::Unknown(_) => {
+                Reply::error_reply(ReplyKind::CommandNotSupported)
+                    .write_to(&mut stream)
+                    .await
+                    .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+                Err(Error::aborted("unknown command"))
+            }
+        }
+    }
+}
+
+impl<C, B, U, A> Socks5Acceptor<C, B, U, A> {
+    fn select_method(&self, methods: &[SocksMethod]) -> SocksMethod {
+        if self.auth.is_some() && methods.contains(&SocksMethod::UsernamePassword) {
+            return SocksMethod::UsernamePassword;
+        }
+        if (self.auth.is_none() || self.auth_opt)
+            && methods.contains(&SocksMethod::NoAuthenticationRequired)
+        {
+            return SocksMethod::NoAuthenticationRequired;
+        }
+        SocksMethod::NoAcceptableMethods
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::service::service_fn;
+    use rama_net::{address::Authority as NetAuthority, user::UserId};
+    use rama_tcp::client::service::ForwardAuthority;
+    use std::convert::Infallible;
+    use tokio_test::io::Builder;
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_no_auth_lazy_connect() {
+        let stream = Builder::new()
+            .read(b"\x05\x01\x00")
+            .write(b"\x05\x00")
+            .read(b"\x05\x01\x00\x03\x0bexample.com\x00\x50")
+            .write(b"\x05\x00\x00\x01\x00\x00\x00\x00\x00\x00")
+            .read(b"ping")
+            .write(b"pong")
+            .build();
+
+        let acceptor = Socks5Acceptor::new().with_connector(LazyConnector::new(service_fn(
+            async |ctx: Context<()>, mut stream: tokio_test::io::Mock| {
+                use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+                let authority: &NetAuthority = ctx.get::<ForwardAuthority>().unwrap().as_ref();
+                assert_eq!(authority.to_string(), "example.com:80");
+
+                let mut buf = [0u8; 4];
+                stream.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf, b"ping");
+                stream.write_all(b"pong").await.unwrap();
+
+                Ok::<_, Infallible>(())
+            },
+        )));
+
+        acceptor.serve(Context::default(), stream).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_username_password_auth() {
+        let stream = Builder::new()
+            .read(b"\x05\x02\x00\x02")
+            .write(b"\x05\x02")
+            .read(b"\x01\x04john\x06secret")
+            .write(b"\x01\x00")
+            .read(b"\x05\x01\x00\x01\x7f\x00\x00\x01\x1f\x90")
+            .write(b"\x05\x00\x00\x01\x00\x00\x00\x00\x00\x00")
+            .build();
+
+        let acceptor = Socks5Acceptor::new()
+            .with_authorizer(Basic::new("john", "secret"))
+            .with_connector(LazyConnector::new(service_fn(
+                async |ctx: Context<()>, _stream: tokio_test::io::Mock| {
+                    let user: &UserId = ctx.get().unwrap();
+                    assert_eq!(user, "john");
+
+                    let authority: &NetAuthority = ctx.get::<ForwardAuthority>().unwrap().as_ref();
+                    assert_eq!(authority.to_string(), "127.0.0.1:8080");
+
+                    Ok::<_, Infallible>(())
+                },
+            )));
+
+        acceptor.serve(Context::default(), stream).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_invalid_credentials() {
+        let stream = Builder::new()
+            .read(b"\x05\x01\x02")
+            .write(b"\x05\x02")
+            .read(b"\x01\x04john\x05wrong")
+            .write(b"\x01\x01")
+            .build();
+
+        let err = Socks5Acceptor::new()
+            .with_authorizer(Basic::new("john", "secret"))
+            .serve(Context::default(), stream)
+            .await
+            .unwrap_err();
+        assert!(err.is_aborted());
+    }
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_auth_required() {
+        let stream = Builder::new()
+            .read(b"\x05\x01\x00")
+            .write(b"\x05\xff")
+            .build();
+
+        let err = Socks5Acceptor::new()
+            .with_authorizer(Basic::new("john", "secret"))
+            .serve(Context::default(), stream)
+            .await
+            .unwrap_err();
+        assert!(err.is_aborted());
+    }
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_auth_optional() {
+        let stream = Builder::new()
+            .read(b"\x05\x01\x00")
+            .write(b"\x05\x00")
+            .read(b"\x05\x01\x00\x01\x7f\x00\x00\x01\x1f\x90")
+            .write(b"\x05\x00\x00\x01\x00\x00\x00\x00\x00\x00")
+            .build();
+
+        Socks5Acceptor::new()
+            .with_authorizer(Basic::new("john", "secret"))
+            .with_auth_optional(true)
+            .with_connector(LazyConnector::new(service_fn(
+                async |ctx: Context<()>, _stream: tokio_test::io::Mock| {
+                    assert!(ctx.get::<UserId>().is_none());
+                    Ok::<_, Infallible>(())
+                },
+            )))
+            .serve(Context::default(), stream)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_command_not_supported() {
+        for command in [0x01, 0x02, 0x03, 0x09] {
+            let stream = Builder::new()
+                .read(b"\x05\x01\x00")
+                .write(b"\x05\x00")
+                .read(&[
+                    0x05, command, 0x00, 0x01, 0x7f, 0x00, 0x00, 0x01, 0x1f, 0x90,
+                ])
+                .write(b"\x05\x07\x00\x01\x00\x00\x00\x00\x00\x00")
+                .build();
+
+            let err = Socks5Acceptor::new()
+                .serve(Context::default(), stream)
+                .await
+                .unwrap_err();
+            assert!(err.is_aborted(), "command: {command}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_socks5_acceptor_address_type_not_supported() {
+        let stream = Builder::new()
+            .read(b"\x05\x01\x00")
+            .write(b"\x05\x00")
+            .read(b"\x05\x01\x00\x02")
+            .write(b"\x05\x08\x00\x01\x00\x00\x00\x00\x00\x00")
+            .build();
+
+        let err = Socks5Acceptor::new()
+            .serve(Context::default(), stream)
+            .await
+            .unwrap_err();
+        assert!(!err.is_aborted());
+    }
+}
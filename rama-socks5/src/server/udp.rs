@@ -0,0 +1,467 @@
+use super::{Error, error::reply_kind_for_error};
+use crate::dns::resolve_authority;
+use crate::proto::{ReplyKind, UdpHeader, server::Reply};
+use rama_core::{Context, error::BoxError};
+use rama_dns::{DnsResolver, HickoryDns};
+use rama_net::{
+    address::{Authority, Host},
+    stream::{SocketInfo, Stream},
+};
+use rama_udp::UdpSocket;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::io::AsyncReadExt;
+
+/// Types which can be used as socks5 [`Command::UdpAssociate`] drivers on the server side.
+///
+/// Typically used as a component part of a [`Socks5Acceptor`].
+///
+/// The `()` implementation replies with [`ReplyKind::CommandNotSupported`]
+/// and aborts the connection.
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+/// [`Command::UdpAssociate`]: crate::proto::Command::UdpAssociate
+pub trait Socks5UdpAssociator<S, IO>: Send + Sync + 'static {
+    /// Accept the [`Command::UdpAssociate`] request for the given client address.
+    ///
+    /// The implementation is responsible for replying to the client,
+    /// and is expected to keep the association alive for as long as the
+    /// (TCP) stream on which the request was received remains open.
+    ///
+    /// [`Command::UdpAssociate`]: crate::proto::Command::UdpAssociate
+    fn accept_udp_associate(
+        &self,
+        ctx: Context<S>,
+        stream: IO,
+        client_address: Authority,
+    ) -> impl Future<Output = Result<(), Error>> + Send + '_;
+}
+
+impl<S, IO> Socks5UdpAssociator<S, IO> for ()
+where
+    S: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+{
+    async fn accept_udp_associate(
+        &self,
+        _ctx: Context<S>,
+        mut stream: IO,
+        client_address: Authority,
+    ) -> Result<(), Error> {
+        tracing::debug!(
+            %client_address,
+            "socks5 server: abort: command not supported: udp associate",
+        );
+        Reply::error_reply(ReplyKind::CommandNotSupported)
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+        Err(Error::aborted("command not supported: udp associate"))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Default [`Socks5UdpAssociator`] which can be used by the [`Socks5Acceptor`].
+///
+/// It binds a UDP socket on the bind interface, replies with its address
+/// and afterwards relays datagrams between the client and the destinations
+/// requested by the client in the [`UdpHeader`] of each datagram.
+/// Fragmented datagrams are not supported and dropped.
+///
+/// The association is bound to the IP address of the (TCP) control connection's peer,
+/// which has to be known via the [`SocketInfo`] in the [`Context`], otherwise the
+/// association is refused. Only datagrams from the client's address (using the port
+/// as requested or, if unspecified, as learned from the first datagram coming from
+/// the peer IP) are relayed to their destination. Only datagrams from destinations
+/// the client has sent a datagram to are relayed back to the client, all other
+/// datagrams are dropped (RFC 1928, section 7). The association ends as soon as the
+/// (TCP) control stream is closed.
+///
+/// By default the socket is bound on the same local IP address
+/// as the one the client connected to (if known via [`SocketInfo`]),
+/// falling back to the unspecified IPv4 address. In case the socket is bound
+/// on an unspecified address, the reply contains the local IP address of the
+/// control connection instead, as that is the address the client has to send to.
+///
+/// [`Socks5Acceptor`]: super::Socks5Acceptor
+pub struct UdpRelay<Dns = HickoryDns> {
+    dns: Dns,
+    bind_interface: Option<IpAddr>,
+    buffer_size: usize,
+    idle_timeout: Option<Duration>,
+}
+
+impl UdpRelay {
+    /// Create a new [`UdpRelay`].
+    pub fn new() -> Self {
+        Self {
+            dns: HickoryDns::default(),
+            bind_interface: None,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            idle_timeout: None,
+        }
+    }
+}
+
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+impl Default for UdpRelay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Dns> UdpRelay<Dns> {
+    /// Attach a custom [`DnsResolver`] to resolve the domain
+    /// destinations of relayed datagrams.
+    pub fn with_dns<OtherDns>(self, dns: OtherDns) -> UdpRelay<OtherDns>
+    where
+        OtherDns: DnsResolver<Error: Into<BoxError>>,
+    {
+        UdpRelay {
+            dns,
+            bind_interface: self.bind_interface,
+            buffer_size: self.buffer_size,
+            idle_timeout: self.idle_timeout,
+        }
+    }
+
+    /// Define the IP address of the interface to bind the relay socket on.
+    pub fn with_bind_interface(mut self, ip: impl Into<IpAddr>) -> Self {
+        self.bind_interface = Some(ip.into());
+        self
+    }
+
+    /// Define the IP address of the interface to bind the relay socket on.
+    pub fn set_bind_interface(&mut self, ip: impl Into<IpAddr>) -> &mut Self {
+        self.bind_interface = Some(ip.into());
+        self
+    }
+
+    /// Define the size of the buffer used to receive datagrams,
+    /// which is also the max size of a datagram that can be relayed.
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Define the size of the buffer used to receive datagrams,
+    /// which is also the max size of a datagram that can be relayed.
+    pub fn set_buffer_size(&mut self, size: usize) -> &mut Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Define the max duration the association can be idle
+    /// (no datagrams relayed) before it is ended.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Define the max duration the association can be idle
+    /// (no datagrams relayed) before it is ended.
+    pub fn set_idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+}
+
+impl<S, IO, Dns> Socks5UdpAssociator<S, IO> for UdpRelay<Dns>
+where
+    S: Clone + Send + Sync + 'static,
+    IO: Stream + Unpin,
+    Dns: DnsResolver<Error: Into<BoxError>>,
+{
+    async fn accept_udp_associate(
+        &self,
+        ctx: Context<S>,
+        mut stream: IO,
+        client_address: Authority,
+    ) -> Result<(), Error> {
+        let Some(socket_info) = ctx.get::<SocketInfo>() else {
+            tracing::debug!(
+                %client_address,
+                "socks5 server: udp associate: abort: unknown control connection peer",
+            );
+            Reply::error_reply(ReplyKind::GeneralServerFailure)
+                .write_to(&mut stream)
+                .await
+                .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+            return Err(Error::aborted(
+                "udp associate: unknown control connection peer",
+            ));
+        };
+        let peer_ip = socket_info.peer_addr().ip().to_canonical();
+
+        let bind_ip = self
+            .bind_interface
+            .or_else(|| socket_info.local_addr().map(|addr| addr.ip()))
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        let socket = match UdpSocket::bind(SocketAddr::new(bind_ip, 0)).await {
+            Ok(socket) => socket,
+            Err(err) => {
+                let reply_kind = reply_kind_for_error(err.as_ref());
+                tracing::debug!(
+                    %client_address,
+                    %reply_kind,
+                    error = %err,
+                    "socks5 server: udp associate: failed to bind relay socket",
+                );
+                Reply::error_reply(reply_kind)
+                    .write_to(&mut stream)
+                    .await
+                    .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+                return Err(Error::service(err).with_context("bind udp relay socket"));
+            }
+        };
+
+        let mut local_addr = socket
+            .local_addr()
+            .map_err(|err| Error::io(err).with_context("udp relay socket local address"))?;
+        if local_addr.ip().is_unspecified() {
+            if let Some(control_addr) = socket_info.local_addr() {
+                local_addr.set_ip(control_addr.ip());
+            }
+        }
+        Reply::new(local_addr.into())
+            .write_to(&mut stream)
+            .await
+            .map_err(|err| Error::protocol(err).with_context("write server reply"))?;
+
+        // the client address as requested by the client,
+        // only used if it matches the peer of the control connection,
+        // as the association is always bound to the peer's IP
+        let mut client_addr = match client_address.host() {
+            Host::Address(ip) if ip.to_canonical() == peer_ip && client_address.port() != 0 => {
+                Some(SocketAddr::new(
+                    socket_info.peer_addr().ip(),
+                    client_address.port(),
+                ))
+            }
+            _ => None,
+        };
+
+        tracing::trace!(
+            %client_address,
+            %local_addr,
+            "socks5 server: udp associate: relay socket bound",
+        );
+
+        // destinations the client has sent datagrams to,
+        // the only sources from which datagrams are relayed back to the client
+        let mut destinations = HashSet::new();
+
+        let mut buffer = vec![0u8; self.buffer_size];
+        let mut control_buffer = [0u8; 64];
+
+        loop {
+            let recv = async {
+                match self.idle_timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, socket.recv_from(&mut buffer))
+                        .await
+                        .map_err(|_| {
+                            std::io::Error::new(std::io::ErrorKind::TimedOut, "udp relay idle")
+                        })?,
+                    None => socket.recv_from(&mut buffer).await,
+                }
+            };
+
+            let (n, source) = tokio::select! {
+                result = stream.read(&mut control_buffer) => {
+                    match result {
+                        Ok(0) => {
+                            tracing::trace!(%client_address, "socks5 server: udp associate: control stream closed");
+                            return Ok(());
+                        }
+                        Ok(_) => {
+                            // data on the control stream has no meaning, ignore it
+                            continue;
+                        }
+                        Err(err) => {
+                            if rama_net::conn::is_connection_error(&err) {
+                                return Ok(());
+                            }
+                            return Err(Error::io(err).with_context("read control stream"));
+                        }
+                    }
+                }
+                result = recv => {
+                    match result {
+                        Ok((n, source)) => (n, SocketAddr::from(source)),
+                        Err(err) if err.kind() == std::io::ErrorKind::TimedOut => {
+                            tracing::trace!(%client_address, "socks5 server: udp associate: idle timeout");
+                            return Ok(());
+                        }
+                        Err(err) => {
+                            return Err(Error::io(err).with_context("receive datagram"));
+                        }
+                    }
+                }
+            };
+
+            let source_ip = source.ip().to_canonical();
+            let from_client = match client_addr {
+                Some(addr) => addr.ip().to_canonical() == source_ip && addr.port() == source.port(),
+                None if source_ip == peer_ip => {
+                    client_addr = Some(source);
+                    true
+                }
+                None => false,
+            };
+
+            if from_client {
+                let mut datagram = &buffer[..n];
+                let header = match UdpHeader::read_from(&mut datagram).await {
+                    Ok(header) => header,
+                    Err(err) => {
+                        tracing::debug!(%source, error = %err, "socks5 server: udp associate: drop invalid datagram");
+                        continue;
+                    }
+                };
+                if header.fragment_number != 0 {
+                    tracing::debug!(
+                        %source,
+                        fragment_number = header.fragment_number,
+                        "socks5 server: udp associate: drop fragmented datagram",
+                    );
+                    continue;
+                }
+                let destination = match resolve_authority(&self.dns, header.destination).await {
+                    Ok(addr) => addr,
+                    Err(err) => {
+                        tracing::debug!(%source, error = %err, "socks5 server: udp associate: drop datagram for unresolved destination");
+                        continue;
+                    }
+                };
+                destinations.insert(SocketAddr::new(
+                    destination.ip().to_canonical(),
+                    destination.port(),
+                ));
+                if let Err(err) = socket.send_to(datagram, destination).await {
+                    tracing::debug!(%destination, error = %err, "socks5 server: udp associate: failed to relay datagram to destination");
+                }
+            } else if !destinations.contains(&SocketAddr::new(source_ip, source.port())) {
+                tracing::trace!(%source, "socks5 server: udp associate: drop datagram from source not contacted by client");
+            } else if let Some(client_addr) = client_addr {
+                let header = UdpHeader::new(source.into());
+                let mut packet = Vec::with_capacity(header.serialized_len() + n);
+                if let Err(err) = header.write_to_buf(&mut packet) {
+                    tracing::debug!(%source, error = %err, "socks5 server: udp associate: failed to encode datagram header");
+                    continue;
+                }
+                packet.extend_from_slice(&buffer[..n]);
+                if let Err(err) = socket.send_to(&packet, client_addr).await {
+                    tracing::debug!(%client_addr, error = %err, "socks5 server: udp associate: failed to relay datagram to client");
+                }
+            } else {
+                tracing::trace!(%source, "socks5 server: udp associate: drop datagram received prior to client datagram");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UdpSocket as TokioUdpSocket;
+
+    async fn associate(ctx: Context<()>) -> (tokio::io::DuplexStream, Reply) {
+        associate_with_relay(UdpRelay::new(), ctx).await
+    }
+
+    async fn associate_with_relay(
+        relay: UdpRelay,
+        ctx: Context<()>,
+    ) -> (tokio::io::DuplexStream, Reply) {
+        let (mut client, server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            let _ = relay
+                .accept_udp_associate(ctx, server, Authority::new(Ipv4Addr::UNSPECIFIED.into(), 0))
+                .await;
+        });
+        let reply = Reply::read_from(&mut client).await.unwrap();
+        (client, reply)
+    }
+
+    #[tokio::test]
+    async fn test_udp_relay_unknown_peer() {
+        let (_client, reply) = associate(Context::default()).await;
+        assert_eq!(reply.reply, ReplyKind::GeneralServerFailure);
+    }
+
+    #[tokio::test]
+    async fn test_udp_relay_bound_to_control_peer() {
+        let mut ctx = Context::default();
+        ctx.insert(SocketInfo::new(
+            Some(([127, 0, 0, 1], 1080).into()),
+            ([127, 0, 0, 1], 40000).into(),
+        ));
+        let (_client, reply) = associate(ctx).await;
+        assert_eq!(reply.reply, ReplyKind::Succeeded);
+        let relay_addr = match reply.bind_address.host() {
+            Host::Address(ip) => SocketAddr::new(*ip, reply.bind_address.port()),
+            Host::Name(_) => unreachable!(),
+        };
+
+        let destination = TokioUdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut packet = Vec::new();
+        UdpHeader::new(destination.local_addr().unwrap().into())
+            .write_to_buf(&mut packet)
+            .unwrap();
+
+        // a datagram from another IP cannot claim the association
+        let intruder = TokioUdpSocket::bind("127.0.0.2:0").await.unwrap();
+        let mut intruder_packet = packet.clone();
+        intruder_packet.extend_from_slice(b"evil");
+        intruder
+            .send_to(&intruder_packet, relay_addr)
+            .await
+            .unwrap();
+
+        let client = TokioUdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut client_packet = packet;
+        client_packet.extend_from_slice(b"ping");
+        client.send_to(&client_packet, relay_addr).await.unwrap();
+
+        let mut buf = [0u8; 64];
+        let (n, source) = destination.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+
+        // a datagram from a source the client never sent to is not relayed to the client
+        let stranger = TokioUdpSocket::bind("127.0.0.1:0").await.unwrap();
+        stranger.send_to(b"unsolicited", relay_addr).await.unwrap();
+
+        destination.send_to(b"pong", source).await.unwrap();
+
+        let (n, _) = client.recv_from(&mut buf).await.unwrap();
+        let mut datagram = &buf[..n];
+        let header = UdpHeader::read_from(&mut datagram).await.unwrap();
+        assert_eq!(
+            header.destination,
+            Authority::from(destination.local_addr().unwrap())
+        );
+        assert_eq!(datagram, b"pong");
+    }
+
+    #[tokio::test]
+    async fn test_udp_relay_reply_control_local_ip() {
+        let mut ctx = Context::default();
+        ctx.insert(SocketInfo::new(
+            Some(([127, 0, 0, 1], 1080).into()),
+            ([127, 0, 0, 1], 40000).into(),
+        ));
+        let relay = UdpRelay::new().with_bind_interface(Ipv4Addr::UNSPECIFIED);
+        let (_client, reply) = associate_with_relay(relay, ctx).await;
+        assert_eq!(reply.reply, ReplyKind::Succeeded);
+        assert_eq!(
+            reply.bind_address.host(),
+            &Host::Address(Ipv4Addr::LOCALHOST.into())
+        );
+        assert_ne!(reply.bind_address.port(), 0);
+    }
+}
@@ -119,6 +119,13 @@ impl From<Parts> for Request {
     }
 }
 
+impl From<Authority> for Request {
+    #[inline]
+    fn from(value: Authority) -> Self {
+        Self::new(value)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// The parts that make up a Tcp [`Request`].
 pub struct Parts {
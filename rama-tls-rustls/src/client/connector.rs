@@ -1,6 +1,8 @@
 use super::TlsConnectorData;
+use crate::dep::rustls::{HandshakeKind, client::EchStatus};
 use crate::dep::tokio_rustls::{TlsConnector as RustlsConnector, client::TlsStream};
 use crate::types::TlsTunnel;
+use crate::verify::{certificate_pinning_error, record_signature_scheme};
 use pin_project_lite::pin_project;
 use private::{ConnectorKindAuto, ConnectorKindSecure, ConnectorKindTunnel};
 use rama_core::error::ErrorContext;
@@ -9,8 +11,8 @@ use rama_core::{Context, Layer, Service};
 use rama_net::address::Host;
 use rama_net::client::{ConnectorService, EstablishedClientConnection};
 use rama_net::stream::Stream;
-use rama_net::tls::ApplicationProtocol;
 use rama_net::tls::client::NegotiatedTlsParameters;
+use rama_net::tls::{ApplicationProtocol, CertificateInfo};
 use rama_net::transport::TryRefIntoTransportContext;
 use std::fmt;
 use tokio::io::{AsyncRead, AsyncWrite};
@@ -404,13 +406,19 @@ impl<S, K> TlsConnector<S, K> {
             .or(self.connector_data.clone())
             .unwrap_or(TlsConnectorData::new_http_auto()?);
 
-        let server_name = rustls_pki_types::ServerName::try_from(
-            connector_data.server_name.unwrap_or(server_host),
-        )?;
+        let server_host = connector_data.server_name.unwrap_or(server_host);
+        let server_name = rustls_pki_types::ServerName::try_from(server_host.clone())?;
 
         let connector = RustlsConnector::from(connector_data.client_config);
 
-        let stream = connector.connect(server_name, stream).await?;
+        let (stream, signature_scheme) =
+            record_signature_scheme(connector.connect(server_name, stream)).await;
+        let stream = stream.map_err(|err| match certificate_pinning_error(&err) {
+            Some(err) => OpaqueError::from_std(err)
+                .context("rustls connector: connect")
+                .into_boxed(),
+            None => err.into(),
+        })?;
 
         let (_, conn_data_ref) = stream.get_ref();
 
@@ -428,7 +436,22 @@ impl<S, K> TlsConnector<S, K> {
             application_layer_protocol: conn_data_ref
                 .alpn_protocol()
                 .map(ApplicationProtocol::from),
+            cipher_suite: conn_data_ref
+                .negotiated_cipher_suite()
+                .map(|suite| suite.suite().into()),
+            key_exchange_group: conn_data_ref
+                .negotiated_key_exchange_group()
+                .map(|group| group.name().into()),
+            signature_scheme: signature_scheme.map(Into::into),
+            // no SNI is sent for ip addresses
+            server_name: matches!(server_host, Host::Name(_)).then_some(server_host),
             peer_certificate_chain: server_certificate_chain,
+            peer_certificate: conn_data_ref
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .and_then(|cert| CertificateInfo::try_from_der(cert).ok()),
+            session_resumed: conn_data_ref.handshake_kind() == Some(HandshakeKind::Resumed),
+            ech_accepted: conn_data_ref.ech_status() == EchStatus::Accepted,
         };
 
         Ok((stream, params))
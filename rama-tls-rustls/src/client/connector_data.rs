@@ -1,14 +1,29 @@
-use crate::dep::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use crate::dep::pki_types::{
+    CertificateDer, EchConfigListBytes, PrivateKeyDer, PrivatePkcs8KeyDer, pem::PemObject,
+};
 use crate::dep::rcgen::{self, KeyPair};
 use crate::dep::rustls::RootCertStore;
-use crate::dep::rustls::{ALL_VERSIONS, ClientConfig};
+use crate::dep::rustls::{
+    ALL_VERSIONS, ClientConfig,
+    client::{EchConfig, EchMode, Resumption},
+    crypto::{CryptoProvider, aws_lc_rs, hpke::Hpke},
+    sign::{CertifiedKey, SingleCertAndKey},
+};
 use crate::key_log::KeyLogFile;
-use crate::verify::NoServerCertVerifier;
+use crate::verify::{CustomServerCertVerifier, NoServerCertVerifier, SignatureSchemeRecorder};
 use rama_core::error::{BoxError, ErrorContext, OpaqueError};
 use rama_net::address::Host;
-use rama_net::tls::{ApplicationProtocol, KeyLogIntent};
+use rama_net::tls::{
+    ApplicationProtocol, DataEncoding, EchConfigList, KeyLogIntent,
+    client::{ClientAuth, ClientAuthData, ClientHelloExtension, ServerVerifyMode, TlsSessionStore},
+};
 use rustls::client::danger::ServerCertVerifier;
-use std::sync::{Arc, OnceLock};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, OnceLock},
+};
+
+use super::TlsSessionStoreAdapter;
 
 #[derive(Debug, Clone)]
 /// Internal data used as configuration/input for the [`super::HttpsConnector`].
@@ -66,6 +81,15 @@ impl TlsConnectorData {
             .with_alpn_protocols(&[ApplicationProtocol::HTTP_2])
             .build())
     }
+
+    /// Create a default [`TlsConnectorData`] that is focussed
+    /// on providing h3 connections (over QUIC).
+    pub fn new_http_3() -> Result<TlsConnectorData, OpaqueError> {
+        Ok(TlsConnectorDataBuilder::new()
+            .with_env_key_logger()?
+            .with_alpn_protocols(&[ApplicationProtocol::HTTP_3])
+            .build())
+    }
 }
 
 /// [`ClientConfigBuilder`] can be used to construct [`rustls::ClientConfig`] for most common use cases in Rama.
@@ -75,6 +99,7 @@ pub struct TlsConnectorDataBuilder {
     client_config: rustls::ClientConfig,
     server_name: Option<Host>,
     store_server_certificate_chain: bool,
+    session_store: Option<Arc<dyn TlsSessionStore>>,
 }
 
 impl Default for TlsConnectorDataBuilder {
@@ -94,15 +119,19 @@ impl From<ClientConfig> for TlsConnectorDataBuilder {
 
 impl TlsConnectorDataBuilder {
     /// Create a [`TlsConnectorDataBuilder`] with a starting config of: support for all tls versions, global root
-    /// certificate store, and no client auth
+    /// certificate store and no client auth
     pub fn new() -> Self {
-        let config = ClientConfig::builder_with_protocol_versions(ALL_VERSIONS)
-            .with_root_certificates(client_root_certs())
+        let builder = ClientConfig::builder_with_protocol_versions(ALL_VERSIONS);
+        let verifier = default_cert_verifier(builder.crypto_provider());
+        let config = builder
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
             .with_no_client_auth();
         Self {
             client_config: config,
             server_name: None,
             store_server_certificate_chain: false,
+            session_store: None,
         }
     }
 
@@ -112,8 +141,11 @@ impl TlsConnectorDataBuilder {
         client_cert_chain: Vec<CertificateDer<'static>>,
         client_priv_key: PrivateKeyDer<'static>,
     ) -> Result<Self, BoxError> {
-        let config = ClientConfig::builder_with_protocol_versions(ALL_VERSIONS)
-            .with_root_certificates(client_root_certs())
+        let builder = ClientConfig::builder_with_protocol_versions(ALL_VERSIONS);
+        let verifier = default_cert_verifier(builder.crypto_provider());
+        let config = builder
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
             .with_client_auth_cert(client_cert_chain, client_priv_key)
             .map_err(Into::<BoxError>::into)?;
 
@@ -121,9 +153,102 @@ impl TlsConnectorDataBuilder {
             client_config: config,
             server_name: None,
             store_server_certificate_chain: false,
+            session_store: None,
         })
     }
 
+    /// Create a [`TlsConnectorDataBuilder`] with a starting config of: Encrypted Client Hello (ECH)
+    /// enabled for the given [`EchConfigList`], global root certificate store and no client auth
+    ///
+    /// The process-wide default [`CryptoProvider`] is used, with the HPKE suites of
+    /// `aws-lc-rs`, the only provider of rustls implementing HPKE. Use
+    /// [`Self::new_with_ech_config_list_and_provider`] to use another provider.
+    ///
+    /// ECH requires TLS 1.3, support for older tls versions is therefore disabled.
+    /// Whether the server accepted ECH is reported in the [`NegotiatedTlsParameters`].
+    ///
+    /// [`NegotiatedTlsParameters`]: rama_net::tls::client::NegotiatedTlsParameters
+    pub fn new_with_ech_config_list(ech_config_list: &EchConfigList) -> Result<Self, OpaqueError> {
+        let provider = ClientConfig::builder().crypto_provider().clone();
+        Self::new_with_ech_config_list_and_provider(
+            ech_config_list,
+            provider,
+            aws_lc_rs::hpke::ALL_SUPPORTED_SUITES,
+        )
+    }
+
+    /// Same as [`Self::new_with_ech_config_list`], but using the given [`CryptoProvider`]
+    /// and HPKE suites to offer ECH with.
+    pub fn new_with_ech_config_list_and_provider(
+        ech_config_list: &EchConfigList,
+        provider: Arc<CryptoProvider>,
+        hpke_suites: &[&'static dyn Hpke],
+    ) -> Result<Self, OpaqueError> {
+        let ech_config = EchConfig::new(
+            EchConfigListBytes::from(ech_config_list.as_bytes().to_vec()),
+            hpke_suites,
+        )
+        .context("create rustls ech config")?;
+        let builder = ClientConfig::builder_with_provider(provider)
+            .with_ech(EchMode::from(ech_config))
+            .context("enable ech in rustls client config")?;
+        let verifier = default_cert_verifier(builder.crypto_provider());
+        let config = builder
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_no_client_auth();
+
+        Ok(Self {
+            client_config: config,
+            server_name: None,
+            store_server_certificate_chain: false,
+            session_store: None,
+        })
+    }
+
+    /// Set the certificate chain and private key used to authenticate the client.
+    pub fn set_client_auth(
+        &mut self,
+        client_cert_chain: Vec<CertificateDer<'static>>,
+        client_priv_key: PrivateKeyDer<'static>,
+    ) -> Result<&mut Self, OpaqueError> {
+        let certified_key = CertifiedKey::from_der(
+            client_cert_chain,
+            client_priv_key,
+            self.client_config.crypto_provider(),
+        )
+        .context("rustls/TlsConnectorDataBuilder: load client auth cert and key")?;
+        self.client_config.client_auth_cert_resolver =
+            Arc::new(SingleCertAndKey::from(certified_key));
+        Ok(self)
+    }
+
+    /// Same as [`Self::set_client_auth`] but consuming self
+    pub fn with_client_auth(
+        mut self,
+        client_cert_chain: Vec<CertificateDer<'static>>,
+        client_priv_key: PrivateKeyDer<'static>,
+    ) -> Result<Self, OpaqueError> {
+        self.set_client_auth(client_cert_chain, client_priv_key)?;
+        Ok(self)
+    }
+
+    /// Set the key logger according to the given [`KeyLogIntent`],
+    /// disabling key logging in case no file path is intended.
+    pub fn set_key_logger(&mut self, intent: &KeyLogIntent) -> Result<&mut Self, OpaqueError> {
+        self.client_config.key_log = match intent.file_path() {
+            Some(path) => Arc::new(KeyLogFile::new(path)?),
+            None => Arc::new(rustls::NoKeyLog),
+        };
+        Ok(self)
+    }
+
+    /// Same as [`Self::set_key_logger`] but consuming self
+    pub fn with_key_logger(mut self, intent: &KeyLogIntent) -> Result<Self, OpaqueError> {
+        self.set_key_logger(intent)?;
+        Ok(self)
+    }
+
     /// If [`KeyLogIntent::Environment`] is set to a path, create a key logger that will write to that path
     /// and set it in the current config
     pub fn set_env_key_logger(&mut self) -> Result<&mut Self, OpaqueError> {
@@ -169,10 +294,15 @@ impl TlsConnectorDataBuilder {
     }
 
     /// Set certificate verifier that will be used to verify certs
+    ///
+    /// The verifier is wrapped such that the signature scheme used by the server
+    /// is reported in the [`NegotiatedTlsParameters`].
+    ///
+    /// [`NegotiatedTlsParameters`]: rama_net::tls::client::NegotiatedTlsParameters
     pub fn set_cert_verifier(&mut self, verifier: Arc<dyn ServerCertVerifier>) -> &mut Self {
         self.client_config
             .dangerous()
-            .set_certificate_verifier(verifier);
+            .set_certificate_verifier(Arc::new(SignatureSchemeRecorder::new(verifier)));
         self
     }
 
@@ -194,6 +324,63 @@ impl TlsConnectorDataBuilder {
         self
     }
 
+    /// Set the certificate verifier according to the given [`ServerVerifyMode`].
+    ///
+    /// [`ServerVerifyMode::Auto`] resets the verifier to the default one, using the global
+    /// root certificate store, while [`ServerVerifyMode::Custom`] installs a [`CustomServerCertVerifier`].
+    pub fn set_server_verify_mode(
+        &mut self,
+        mode: ServerVerifyMode,
+    ) -> Result<&mut Self, OpaqueError> {
+        match mode {
+            ServerVerifyMode::Auto => {
+                let verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(
+                    client_root_certs(),
+                    self.client_config.crypto_provider().clone(),
+                )
+                .build()
+                .context("build default webpki verifier")?;
+                Ok(self.set_cert_verifier(verifier))
+            }
+            ServerVerifyMode::Disable => Ok(self.set_no_cert_verifier()),
+            ServerVerifyMode::Custom(verify) => {
+                let verifier = CustomServerCertVerifier::try_new(verify)?;
+                Ok(self.set_cert_verifier(Arc::new(verifier)))
+            }
+        }
+    }
+
+    /// Same as [`Self::set_server_verify_mode`] but consuming self
+    pub fn with_server_verify_mode(mut self, mode: ServerVerifyMode) -> Result<Self, OpaqueError> {
+        self.set_server_verify_mode(mode)?;
+        Ok(self)
+    }
+
+    /// Set the [`TlsSessionStore`] used to resume sessions across connections,
+    /// using a [`TlsSessionStoreAdapter`].
+    ///
+    /// Sessions are stored using a hash of the built config, such that sessions
+    /// of different configs are never mixed up. Note that rustls only resumes sessions
+    /// established using the same certificate verifier (and client auth) instance,
+    /// i.e. sessions are resumed by connections using (clones of) the same built config.
+    /// Without a store, sessions are kept in the (in-memory) cache of the built config.
+    ///
+    /// Sessions are resumed regardless of the (proxy) route used to connect,
+    /// e.g. use a store per egress route in case sessions should not be resumed across routes.
+    /// The [`default_tls_session_store`] can be used to share sessions process-wide.
+    ///
+    /// [`default_tls_session_store`]: rama_net::tls::client::default_tls_session_store
+    pub fn set_session_store(&mut self, store: Arc<dyn TlsSessionStore>) -> &mut Self {
+        self.session_store = Some(store);
+        self
+    }
+
+    /// Same as [`Self::set_session_store`] but consuming self
+    pub fn with_session_store(mut self, store: Arc<dyn TlsSessionStore>) -> Self {
+        self.set_session_store(store);
+        self
+    }
+
     /// Set servername that will be used for SNI
     pub fn set_server_name(&mut self, server_name: Host) -> &mut Self {
         self.server_name = Some(server_name);
@@ -226,14 +413,137 @@ impl TlsConnectorDataBuilder {
 
     /// Build [`TlsConnectorData`] from the current config
     pub fn build(self) -> TlsConnectorData {
+        let mut client_config = self.client_config;
+        if let Some(store) = self.session_store {
+            // the session config id should not depend on the resumption (store) itself
+            client_config.resumption = Resumption::disabled();
+            let config_id = session_config_id(&client_config);
+            client_config.resumption =
+                Resumption::store(Arc::new(TlsSessionStoreAdapter::new(store, config_id)));
+        }
         TlsConnectorData {
-            client_config: Arc::new(self.client_config),
+            client_config: Arc::new(client_config),
             server_name: self.server_name,
             store_server_certificate_chain: self.store_server_certificate_chain,
         }
     }
 }
 
+impl TryFrom<rama_net::tls::client::ClientConfig> for TlsConnectorDataBuilder {
+    type Error = OpaqueError;
+
+    /// Create a [`TlsConnectorDataBuilder`] from a (rama) client config.
+    ///
+    /// Only the server name and ALPN extensions are used, as rustls
+    /// does not allow to define the other extensions or their order,
+    /// nor the cipher suites to offer.
+    fn try_from(value: rama_net::tls::client::ClientConfig) -> Result<Self, Self::Error> {
+        let mut builder = match value.ech_config_list.as_ref() {
+            Some(ech_config_list) => Self::new_with_ech_config_list(ech_config_list)?,
+            None => Self::new(),
+        };
+
+        for extension in value.extensions.iter().flatten() {
+            match extension {
+                ClientHelloExtension::ServerName(Some(host)) => {
+                    builder.set_server_name(host.clone());
+                }
+                ClientHelloExtension::ApplicationLayerProtocolNegotiation(protos) => {
+                    builder.set_alpn_protocols(protos);
+                }
+                _ => (),
+            }
+        }
+
+        builder.set_key_logger(
+            value
+                .key_logger
+                .as_ref()
+                .unwrap_or(&KeyLogIntent::Environment),
+        )?;
+        if let Some(mode) = value.server_verify_mode {
+            builder.set_server_verify_mode(mode)?;
+        }
+        match value.client_auth {
+            None => (),
+            Some(ClientAuth::SelfSigned) => {
+                let (cert_chain, key) = self_signed_client_auth()?;
+                builder.set_client_auth(cert_chain, key)?;
+            }
+            Some(ClientAuth::Single(data)) => {
+                let (cert_chain, key) = client_auth_data_to_cert_chain_and_key(&data)?;
+                builder.set_client_auth(cert_chain, key)?;
+            }
+        }
+        builder.set_store_server_certificate_chain(value.store_server_certificate_chain);
+
+        Ok(builder)
+    }
+}
+
+impl TryFrom<rama_net::tls::client::ClientConfig> for TlsConnectorData {
+    type Error = OpaqueError;
+
+    fn try_from(value: rama_net::tls::client::ClientConfig) -> Result<Self, Self::Error> {
+        TlsConnectorDataBuilder::try_from(value).map(TlsConnectorDataBuilder::build)
+    }
+}
+
+fn client_auth_data_to_cert_chain_and_key(
+    data: &ClientAuthData,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), OpaqueError> {
+    let key = match &data.private_key {
+        DataEncoding::Der(raw_data) => PrivateKeyDer::try_from(raw_data.clone())
+            .map_err(OpaqueError::from_display)
+            .context("rustls/TlsConnectorDataBuilder: parse private key from DER content")?,
+        DataEncoding::DerStack(raw_data_list) => PrivateKeyDer::try_from(
+            raw_data_list
+                .first()
+                .context("rustls/TlsConnectorDataBuilder: get first private key raw data")?
+                .clone(),
+        )
+        .map_err(OpaqueError::from_display)
+        .context("rustls/TlsConnectorDataBuilder: parse private key from DER content")?,
+        DataEncoding::Pem(raw_data) => PrivateKeyDer::from_pem_slice(raw_data.as_bytes())
+            .context("rustls/TlsConnectorDataBuilder: parse private key from PEM content")?,
+    };
+
+    let cert_chain = match &data.cert_chain {
+        DataEncoding::Der(raw_data) => vec![raw_data.clone().into()],
+        DataEncoding::DerStack(raw_data_list) => raw_data_list
+            .iter()
+            .map(|raw_data| raw_data.clone().into())
+            .collect(),
+        DataEncoding::Pem(raw_data) => CertificateDer::pem_slice_iter(raw_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .context("rustls/TlsConnectorDataBuilder: parse cert chain from PEM content")?,
+    };
+
+    Ok((cert_chain, key))
+}
+
+/// Compute the identity of a [`ClientConfig`], used to store its sessions.
+///
+/// A rustls config is not hashable, so its debug representation is hashed instead,
+/// which covers the full config, including its verifier, crypto provider and client auth.
+fn session_config_id(config: &ClientConfig) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{config:?}").hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Create the default certificate verifier, using the global root certificate store,
+/// recording the signature scheme used by the server.
+fn default_cert_verifier(provider: &Arc<CryptoProvider>) -> Arc<dyn ServerCertVerifier> {
+    let verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(
+        client_root_certs(),
+        provider.clone(),
+    )
+    .build()
+    .expect("build webpki verifier for non-empty root certificate store");
+    Arc::new(SignatureSchemeRecorder::new(verifier))
+}
+
 pub fn client_root_certs() -> Arc<RootCertStore> {
     static ROOT_CERTS: OnceLock<Arc<RootCertStore>> = OnceLock::new();
     ROOT_CERTS
@@ -267,3 +577,36 @@ pub fn self_signed_client_auth()
         PrivatePkcs8KeyDer::from(client_key_der.secret_pkcs8_der().to_owned()).into(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_net::address::Domain;
+
+    #[test]
+    fn test_tls_connector_data_try_from_client_config() {
+        let ech_config_list = EchConfigList::try_from_configs([&rama_net::tls::EchConfig::new(
+            1,
+            vec![1; 32],
+            Domain::from_static("public.example.com"),
+        )])
+        .unwrap();
+        let data = TlsConnectorData::try_from(rama_net::tls::client::ClientConfig {
+            extensions: Some(vec![
+                ClientHelloExtension::ApplicationLayerProtocolNegotiation(vec![
+                    ApplicationProtocol::HTTP_2,
+                ]),
+            ]),
+            ech_config_list: Some(ech_config_list),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(data.client_config.alpn_protocols, vec![b"h2".to_vec()]);
+        assert!(format!("{:?}", data.client_config).contains("ech_mode: Some("));
+
+        let data =
+            TlsConnectorData::try_from(rama_net::tls::client::ClientConfig::default()).unwrap();
+        assert!(data.client_config.alpn_protocols.is_empty());
+        assert!(format!("{:?}", data.client_config).contains("ech_mode: None"));
+    }
+}
@@ -4,6 +4,10 @@ mod connector;
 #[doc(inline)]
 pub use connector::{AutoTlsStream, TlsConnector, TlsConnectorLayer};
 
+mod session;
+#[doc(inline)]
+pub use session::TlsSessionStoreAdapter;
+
 mod connector_data;
 #[doc(inline)]
 pub use connector_data::{
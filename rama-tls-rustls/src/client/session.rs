@@ -0,0 +1,266 @@
+use crate::dep::pki_types::ServerName;
+use crate::dep::rustls::{
+    NamedGroup,
+    client::{ClientSessionStore, Tls12ClientSessionValue, Tls13ClientSessionValue},
+};
+use parking_lot::Mutex;
+use rama_net::address::Host;
+use rama_net::tls::client::{TlsSession, TlsSessionKey, TlsSessionStore};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Maximum number of TLS 1.3 tickets kept per server,
+/// same as the default in-memory store of rustls.
+const MAX_TLS13_TICKETS_PER_SERVER: usize = 8;
+
+#[derive(Debug)]
+/// Adapter to use a [`TlsSessionStore`] as a rustls [`ClientSessionStore`].
+///
+/// Sessions are stored under a [`TlsSessionKey`] made of the server name
+/// and the given config identity, such that sessions are only resumed
+/// by a [`rustls::ClientConfig`] with the same identity.
+///
+/// The sessions of a server are never modified in place: each change
+/// (e.g. a new ticket) results in an updated session being inserted
+/// into the store, such that any store implementation sees all changes.
+pub struct TlsSessionStoreAdapter {
+    store: Arc<dyn TlsSessionStore>,
+    config_id: u64,
+    // serializes the (read-modify-insert) updates of the stored sessions
+    update_lock: Mutex<()>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ServerSessions {
+    kx_hint: Option<NamedGroup>,
+    tls12: Option<Tls12ClientSessionValue>,
+    tls13: VecDeque<Tls13Ticket>,
+}
+
+/// A TLS 1.3 ticket, which can only be taken (used) once,
+/// even if shared by multiple copies of the [`ServerSessions`].
+type Tls13Ticket = Arc<Mutex<Option<Tls13ClientSessionValue>>>;
+
+impl TlsSessionStoreAdapter {
+    /// Create a new [`TlsSessionStoreAdapter`] for the given [`TlsSessionStore`],
+    /// storing its sessions for the given config identity.
+    ///
+    /// The [`TlsConnectorDataBuilder`] uses a hash of the client config as identity.
+    ///
+    /// [`TlsConnectorDataBuilder`]: super::TlsConnectorDataBuilder
+    pub fn new(store: Arc<dyn TlsSessionStore>, config_id: u64) -> Self {
+        Self {
+            store,
+            config_id,
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    fn key(&self, server_name: &ServerName<'_>) -> Option<TlsSessionKey> {
+        Host::try_from(server_name)
+            .ok()
+            .map(|server| TlsSessionKey::new(server, self.config_id))
+    }
+
+    fn stored_sessions(&self, key: &TlsSessionKey) -> Option<ServerSessions> {
+        self.store
+            .get(key)?
+            .downcast_ref::<ServerSessions>()
+            .cloned()
+    }
+
+    fn sessions(&self, server_name: &ServerName<'_>) -> Option<ServerSessions> {
+        self.stored_sessions(&self.key(server_name)?)
+    }
+
+    /// Update the sessions of the given server, inserting the updated sessions in the store.
+    ///
+    /// Sessions are only created if `create` is `true`, otherwise `None` is returned
+    /// in case no sessions are stored yet for the server.
+    fn update_sessions<T>(
+        &self,
+        server_name: &ServerName<'_>,
+        create: bool,
+        f: impl FnOnce(&mut ServerSessions) -> T,
+    ) -> Option<T> {
+        let key = self.key(server_name)?;
+        let _guard = self.update_lock.lock();
+        let mut sessions = match self.stored_sessions(&key) {
+            Some(sessions) => sessions,
+            None if create => ServerSessions::default(),
+            None => return None,
+        };
+        let output = f(&mut sessions);
+        self.store.insert(key, TlsSession::new(sessions));
+        Some(output)
+    }
+}
+
+impl ClientSessionStore for TlsSessionStoreAdapter {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.update_sessions(&server_name, true, |sessions| {
+            sessions.kx_hint = Some(group)
+        });
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.sessions(server_name)?.kx_hint
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.update_sessions(&server_name, true, |sessions| sessions.tls12 = Some(value));
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.sessions(server_name)?.tls12
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.update_sessions(server_name, false, |sessions| sessions.tls12.take());
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.update_sessions(&server_name, true, |sessions| {
+            if sessions.tls13.len() >= MAX_TLS13_TICKETS_PER_SERVER {
+                sessions.tls13.pop_front();
+            }
+            sessions.tls13.push_back(Arc::new(Mutex::new(Some(value))));
+        });
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        self.update_sessions(server_name, false, |sessions| {
+            std::iter::from_fn(|| sessions.tls13.pop_back()).find_map(|ticket| ticket.lock().take())
+        })
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::TlsConnectorDataBuilder;
+    use crate::dep::rcgen::{self, KeyPair};
+    use crate::dep::rustls::{ClientConfig, HandshakeKind, ServerConfig};
+    use crate::dep::tokio_rustls::{TlsAcceptor, TlsConnector};
+    use rama_net::tls::client::{CustomServerVerify, InMemoryTlsSessionStore, ServerVerifyMode};
+    use rama_net::tls::{ApplicationProtocol, DataEncoding};
+    use rustls_pki_types::PrivatePkcs8KeyDer;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// A store which keeps track of the inserted sessions,
+    /// as a store persisting its sessions elsewhere would.
+    #[derive(Debug, Default)]
+    struct InsertCountingStore {
+        inner: InMemoryTlsSessionStore,
+        inserts: AtomicUsize,
+    }
+
+    impl TlsSessionStore for InsertCountingStore {
+        fn get(&self, key: &TlsSessionKey) -> Option<TlsSession> {
+            self.inner.get(key)
+        }
+
+        fn insert(&self, key: TlsSessionKey, session: TlsSession) {
+            self.inserts.fetch_add(1, Ordering::SeqCst);
+            self.inner.insert(key, session)
+        }
+
+        fn remove(&self, key: &TlsSessionKey) -> Option<TlsSession> {
+            self.inner.remove(key)
+        }
+    }
+
+    async fn connect(client_config: Arc<ClientConfig>, acceptor: TlsAcceptor) -> HandshakeKind {
+        let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+        tokio::spawn(async move {
+            let mut stream = acceptor.accept(server_stream).await.unwrap();
+            stream.write_all(b"x").await.unwrap();
+            stream.flush().await.unwrap();
+        });
+
+        let mut stream = TlsConnector::from(client_config)
+            .connect(ServerName::try_from("example.com").unwrap(), client_stream)
+            .await
+            .unwrap();
+        // read the (post-handshake) session tickets
+        let mut buf = [0u8; 1];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.get_ref().1.handshake_kind().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tls_session_store_adapter_resumption() {
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["example.com".to_owned()])
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivatePkcs8KeyDer::from(key_pair.serialize_der()).into(),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let store = Arc::new(InsertCountingStore::default());
+        let new_client_config = |alpn: ApplicationProtocol| {
+            TlsConnectorDataBuilder::new()
+                .with_server_verify_mode(ServerVerifyMode::Custom(CustomServerVerify {
+                    root_certs: Some(DataEncoding::Der(cert.der().to_vec())),
+                    replace_default_roots: true,
+                    spki_pins: vec![],
+                    require_ocsp_staple: false,
+                    crls: None,
+                }))
+                .unwrap()
+                .with_alpn_protocols(&[alpn])
+                .with_session_store(store.clone())
+                .build()
+                .client_config
+        };
+
+        let client_config = new_client_config(ApplicationProtocol::HTTP_11);
+        assert_eq!(
+            connect(client_config.clone(), acceptor.clone()).await,
+            HandshakeKind::Full
+        );
+        assert_eq!(store.inner.len(), 1);
+        let inserts = store.inserts.load(Ordering::SeqCst);
+        assert_eq!(
+            connect(client_config, acceptor.clone()).await,
+            HandshakeKind::Resumed
+        );
+        // the store sees the ticket taken, as well as the new tickets received
+        assert!(store.inserts.load(Ordering::SeqCst) > inserts + 1);
+
+        // an identical config stores its sessions under the same key,
+        // rustls only resumes them using the same verifier instance though
+        assert_eq!(
+            connect(
+                new_client_config(ApplicationProtocol::HTTP_11),
+                acceptor.clone()
+            )
+            .await,
+            HandshakeKind::Full
+        );
+        assert_eq!(store.inner.len(), 1);
+
+        // other configs do not share sessions
+        assert_eq!(
+            connect(new_client_config(ApplicationProtocol::HTTP_2), acceptor).await,
+            HandshakeKind::Full
+        );
+        assert_eq!(store.inner.len(), 2);
+    }
+}
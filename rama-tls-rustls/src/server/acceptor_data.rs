@@ -1,12 +1,14 @@
+use super::{ServerCertIssuer, SniCertResolver};
 use crate::dep::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
 use crate::dep::rcgen::{self, KeyPair};
 use crate::dep::rustls;
 use crate::key_log::KeyLogFile;
 use rama_core::error::{ErrorContext, OpaqueError};
 use rama_net::address::{Domain, Host};
-use rama_net::tls::server::SelfSignedData;
-use rama_net::tls::{ApplicationProtocol, KeyLogIntent};
+use rama_net::tls::server::{SelfSignedData, ServerAuthData, ServerCertIssuerData, SniMap};
+use rama_net::tls::{ApplicationProtocol, KeyLogIntent, client::ClientHello};
 use rustls::ALL_VERSIONS;
+use rustls::sign::SingleCertAndKey;
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -104,12 +106,14 @@ where
 /// If this doesn't work for your use case, no problem [`TlsConnectorData`] can be created from a raw [`rustls::ServerConfig`]
 pub struct TlsAcceptorDataBuilder {
     server_config: rustls::ServerConfig,
+    async_issuer: Option<Arc<ServerCertIssuer>>,
 }
 
 impl From<rustls::ServerConfig> for TlsAcceptorDataBuilder {
     fn from(value: rustls::ServerConfig) -> Self {
         Self {
             server_config: value,
+            async_issuer: None,
         }
     }
 }
@@ -128,6 +132,7 @@ impl TlsAcceptorDataBuilder {
 
         Ok(Self {
             server_config: config,
+            async_issuer: None,
         })
     }
 
@@ -142,6 +147,49 @@ impl TlsAcceptorDataBuilder {
 
         Ok(Self {
             server_config: config,
+            async_issuer: None,
+        })
+    }
+
+    /// Create a [`TlsAcceptorDataBuilder`] support all tls versions, using no client auth, and
+    /// server certs issued on the fly by a [`ServerCertIssuer`] created from the given data.
+    ///
+    /// [`ServerCertIssuerKind::Dynamic`] is not supported,
+    /// use a [`DynamicConfigProvider`] instead.
+    ///
+    /// For [`ServerCertIssuerKind::MirrorUpstream`] the upstream cert is fetched
+    /// asynchronously, as are certs cached by a [`CacheKind::Custom`] cache,
+    /// which is why the [`TlsAcceptorData`] built from this builder
+    /// will use a [`DynamicConfigProvider`] in those cases.
+    ///
+    /// [`ServerCertIssuerKind::Dynamic`]: rama_net::tls::server::ServerCertIssuerKind::Dynamic
+    /// [`ServerCertIssuerKind::MirrorUpstream`]: rama_net::tls::server::ServerCertIssuerKind::MirrorUpstream
+    /// [`CacheKind::Custom`]: rama_net::tls::server::CacheKind::Custom
+    pub fn new_with_cert_issuer(data: ServerCertIssuerData) -> Result<Self, OpaqueError> {
+        let issuer = Arc::new(ServerCertIssuer::try_new(data)?);
+        let async_issuer = issuer.is_async().then(|| issuer.clone());
+        let config = rustls::ServerConfig::builder_with_protocol_versions(ALL_VERSIONS)
+            .with_no_client_auth()
+            .with_cert_resolver(issuer);
+
+        Ok(Self {
+            server_config: config,
+            async_issuer,
+        })
+    }
+
+    /// Create a [`TlsAcceptorDataBuilder`] support all tls versions, using no client auth, and
+    /// the certificate chain and private key selected by a [`SniCertResolver`]
+    /// based on the server name (SNI) sent by the client.
+    pub fn new_with_sni_map(map: SniMap<ServerAuthData>) -> Result<Self, OpaqueError> {
+        let resolver = SniCertResolver::try_new(map)?;
+        let config = rustls::ServerConfig::builder_with_protocol_versions(ALL_VERSIONS)
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(resolver));
+
+        Ok(Self {
+            server_config: config,
+            async_issuer: None,
         })
     }
 
@@ -191,18 +239,53 @@ impl TlsAcceptorDataBuilder {
 
     /// Build [`TlsAcceptorData`] from the current config
     pub fn build(self) -> TlsAcceptorData {
-        self.server_config.into()
+        match self.async_issuer {
+            None => self.server_config.into(),
+            Some(issuer) => AsyncCertIssuerConfigProvider {
+                base_config: Arc::new(self.server_config),
+                issuer,
+            }
+            .into(),
+        }
     }
 
     /// Convert current config into a rustls config.
     ///
     /// Useful if you want to use some utilities this builder provides and
-    /// then continue on directly with a native rustls config
+    /// then continue on directly with a native rustls config.
+    ///
+    /// Certs mirroring the upstream server or cached by a custom cache
+    /// can not be resolved by the returned config, as these can only be issued asynchronously.
     pub fn into_rustls_config(self) -> rustls::ServerConfig {
         self.server_config
     }
 }
 
+/// [`DynamicConfigProvider`] serving certs issued asynchronously by a [`ServerCertIssuer`],
+/// e.g. for [`ServerCertIssuerKind::MirrorUpstream`] or a [`CacheKind::Custom`] cache.
+///
+/// [`ServerCertIssuerKind::MirrorUpstream`]: rama_net::tls::server::ServerCertIssuerKind::MirrorUpstream
+/// [`CacheKind::Custom`]: rama_net::tls::server::CacheKind::Custom
+struct AsyncCertIssuerConfigProvider {
+    base_config: Arc<rustls::ServerConfig>,
+    issuer: Arc<ServerCertIssuer>,
+}
+
+impl DynamicConfigProvider for AsyncCertIssuerConfigProvider {
+    fn get_config(
+        &self,
+        client_hello: rustls::server::ClientHello<'_>,
+    ) -> impl Future<Output = Result<Arc<rustls::ServerConfig>, OpaqueError>> + Send {
+        let client_hello = ClientHello::from(client_hello);
+        async move {
+            let certified_key = self.issuer.resolve_async(client_hello).await?;
+            let mut config = self.base_config.as_ref().clone();
+            config.cert_resolver = Arc::new(SingleCertAndKey::from(certified_key.as_ref().clone()));
+            Ok(Arc::new(config))
+        }
+    }
+}
+
 pub fn self_signed_server_auth(
     data: SelfSignedData,
 ) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), OpaqueError> {
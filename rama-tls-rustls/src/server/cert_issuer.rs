@@ -0,0 +1,1044 @@
+use crate::dep::pki_types::{
+    CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, pem::PemObject as _,
+};
+use crate::dep::rcgen::{self, KeyPair};
+use crate::dep::rustls::{
+    self,
+    crypto::aws_lc_rs::sign::any_supported_type,
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+use moka::sync::Cache;
+use rama_core::error::{ErrorContext, OpaqueError};
+use rama_net::address::{Domain, Host};
+use rama_net::tls::server::{
+    CacheKind, SelfSignedData, ServerAuthData, ServerCertCacheKey, ServerCertIssuerData,
+    ServerCertIssuerKind, ServerCertKeyType, SharedServerCertCache, UpstreamFetcher,
+};
+use rama_net::tls::{
+    CertificateFingerprint, CertificateInfo, DataEncoding, NameAttribute, NameStringKind,
+    SubjectAltName, client::ClientHello as RamaClientHello,
+};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+const ONE_DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Validity of issued certs, unless configured otherwise
+const DEFAULT_ISSUED_CERT_VALIDITY: Duration = Duration::from_secs(60 * 60 * 24 * 90);
+
+/// Latest time (`9999-12-31T23:59:59Z`) that can be encoded in a certificate.
+const MAX_CERT_UNIX_TIME: u64 = 253_402_300_799;
+
+/// A [`ResolvesServerCert`] which issues server certs on the fly,
+/// as configured by [`ServerCertIssuerData`].
+///
+/// Certs are issued for the server name (SNI) of the client,
+/// defaulting to `localhost` in case the client did not send one.
+///
+/// Use [`super::TlsAcceptorDataBuilder::new_with_cert_issuer`] to create
+/// a [`super::TlsAcceptorDataBuilder`] using this resolver.
+///
+/// Certs mirroring (the subject alt names of) the cert of the upstream server
+/// or cached using a [`CacheKind::Custom`] cache can only be issued asynchronously,
+/// which is why this resolver is used via a [`super::DynamicConfigProvider`] in that case.
+pub struct ServerCertIssuer {
+    ca_cert: rcgen::Certificate,
+    ca_key: KeyPair,
+    /// chain to be served after the issued cert, starting with the CA cert
+    ca_chain: Vec<CertificateDer<'static>>,
+    validity: Duration,
+    key_type: ServerCertKeyType,
+    upstream: Option<UpstreamMirror>,
+    cert_cache: Option<IssuedCertCache>,
+}
+
+/// Upstream server of which the leaf cert is mirrored by the issued certs.
+#[derive(Debug)]
+enum UpstreamMirror {
+    /// Only mirror the subject alt names ([`ServerCertIssuerKind::CertificateAuthority`]).
+    SubjectAltNames(UpstreamFetcher),
+    /// Mirror the upstream cert ([`ServerCertIssuerKind::MirrorUpstream`]).
+    Certificate(UpstreamFetcher),
+}
+
+impl UpstreamMirror {
+    fn fetcher(&self) -> &UpstreamFetcher {
+        match self {
+            Self::SubjectAltNames(fetcher) | Self::Certificate(fetcher) => fetcher,
+        }
+    }
+}
+
+impl fmt::Debug for ServerCertIssuer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerCertIssuer")
+            .field("ca_chain", &self.ca_chain)
+            .field("validity", &self.validity)
+            .field("key_type", &self.key_type)
+            .field("upstream", &self.upstream)
+            .field("cert_cache", &self.cert_cache)
+            .finish()
+    }
+}
+
+impl ServerCertIssuer {
+    /// Try to create a new [`ServerCertIssuer`] for the given [`ServerCertIssuerData`].
+    ///
+    /// [`ServerCertIssuerKind::Dynamic`] is not supported, as rustls resolves
+    /// certs synchronously. Use a [`super::DynamicConfigProvider`] instead.
+    pub fn try_new(data: ServerCertIssuerData) -> Result<Self, OpaqueError> {
+        let mut upstream = None;
+        let (ca_cert, ca_key, ca_chain, validity, key_type) = match data.kind {
+            ServerCertIssuerKind::SelfSigned(data) => {
+                let (ca_cert, ca_key) = self_signed_ca(data)?;
+                let ca_chain = vec![ca_cert.der().clone()];
+                (
+                    ca_cert,
+                    ca_key,
+                    ca_chain,
+                    DEFAULT_ISSUED_CERT_VALIDITY,
+                    ServerCertKeyType::default(),
+                )
+            }
+            ServerCertIssuerKind::Single(data) => {
+                let (mut cert_chain, key) = server_auth_data_to_cert_chain_and_key(&data)?;
+                let ca_cert = cert_chain
+                    .pop()
+                    .context("rustls/ServerCertIssuer: pop CA Cert (last) from stack")?;
+                let (ca_cert_issuer, ca_key) = ca_from_der(ca_cert.as_ref(), &key)?;
+                (
+                    ca_cert_issuer,
+                    ca_key,
+                    vec![ca_cert],
+                    DEFAULT_ISSUED_CERT_VALIDITY,
+                    ServerCertKeyType::default(),
+                )
+            }
+            ServerCertIssuerKind::CertificateAuthority {
+                ca_cert,
+                ca_key,
+                validity,
+                key_type,
+                upstream: fetcher,
+            } => {
+                let (ca_chain, key) = server_auth_data_to_cert_chain_and_key(&ServerAuthData {
+                    private_key: ca_key,
+                    cert_chain: ca_cert,
+                    ocsp: None,
+                })?;
+                let (ca_cert, ca_key) = ca_from_der(
+                    ca_chain
+                        .first()
+                        .context("rustls/ServerCertIssuer: CA: ca cert missing")?,
+                    &key,
+                )?;
+                upstream = fetcher.map(UpstreamMirror::SubjectAltNames);
+                (ca_cert, ca_key, ca_chain, validity, key_type)
+            }
+            ServerCertIssuerKind::MirrorUpstream {
+                ca_cert,
+                ca_key,
+                key_type,
+                upstream: fetcher,
+            } => {
+                let (ca_chain, key) = server_auth_data_to_cert_chain_and_key(&ServerAuthData {
+                    private_key: ca_key,
+                    cert_chain: ca_cert,
+                    ocsp: None,
+                })?;
+                let (ca_cert, ca_key) = ca_from_der(
+                    ca_chain
+                        .first()
+                        .context("rustls/ServerCertIssuer: mirror: ca cert missing")?,
+                    &key,
+                )?;
+                upstream = Some(UpstreamMirror::Certificate(fetcher));
+                // validity is copied from the upstream cert,
+                // and only used here to limit the lifetime of cached certs
+                (
+                    ca_cert,
+                    ca_key,
+                    ca_chain,
+                    DEFAULT_ISSUED_CERT_VALIDITY,
+                    key_type,
+                )
+            }
+            ServerCertIssuerKind::Dynamic(_) => {
+                return Err(OpaqueError::from_display(
+                    "rustls/ServerCertIssuer: dynamic issuer is not supported: use a DynamicConfigProvider instead",
+                ));
+            }
+        };
+
+        if validity.is_zero() {
+            return Err(OpaqueError::from_display(
+                "rustls/ServerCertIssuer: validity of issued certs cannot be zero",
+            ));
+        }
+
+        let cert_cache = IssuedCertCache::try_new(data.cache_kind, &ca_chain[0], validity)?;
+
+        Ok(Self {
+            ca_cert,
+            ca_key,
+            ca_chain,
+            validity,
+            key_type,
+            upstream,
+            cert_cache,
+        })
+    }
+
+    /// Returns `true` if this issuer mirrors upstream certs or uses a custom cache,
+    /// in which case it can only be used via [`Self::resolve_async`].
+    pub(super) fn is_async(&self) -> bool {
+        self.upstream.is_some() || matches!(self.cert_cache, Some(IssuedCertCache::Custom { .. }))
+    }
+
+    /// Issue a cert for the given host, which is used as common name and
+    /// subject alt name, followed by the given subject alt names mirrored from upstream.
+    fn issue_cert(
+        &self,
+        host: &Host,
+        mirrored_subject_alt_names: Vec<SubjectAltName>,
+    ) -> Result<IssuedCert, OpaqueError> {
+        tracing::trace!(%host, "rustls: issue cert for host");
+
+        let mut params = rcgen::CertificateParams::default();
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, host.to_string());
+        let host_subject_alt_name = match host {
+            Host::Name(domain) => SubjectAltName::Dns(domain.as_str().to_owned()),
+            Host::Address(ip) => SubjectAltName::Ip(*ip),
+        };
+        let mut subject_alt_names = mirrored_subject_alt_names;
+        if !subject_alt_names.contains(&host_subject_alt_name) {
+            subject_alt_names.insert(0, host_subject_alt_name);
+        }
+        params.subject_alt_names = rcgen_subject_alt_names(subject_alt_names)
+            .context("rustls/ServerCertIssuer: subject alt names")?;
+
+        let not_before = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("rustls/ServerCertIssuer: compute not before")?;
+        let not_after = not_before
+            .checked_add(self.validity)
+            .filter(|not_after| not_after.as_secs() <= MAX_CERT_UNIX_TIME)
+            .context("rustls/ServerCertIssuer: compute not after")?;
+        params.not_before = rcgen::date_time_ymd(1970, 1, 1) + not_before;
+        params.not_after = rcgen::date_time_ymd(1970, 1, 1) + not_after;
+
+        self.sign_leaf_cert(params)
+    }
+
+    fn issue_mirrored_cert(&self, upstream_cert: &[u8]) -> Result<IssuedCert, OpaqueError> {
+        let info = CertificateInfo::try_from_der(upstream_cert)
+            .context("rustls/ServerCertIssuer: mirror: parse upstream cert")?;
+        tracing::trace!(subject = %info.subject, "rustls: issue cert mirroring upstream cert");
+
+        let mut params = rcgen::CertificateParams::default();
+        params.distinguished_name = distinguished_name_from_attributes(info.subject_attributes)
+            .context("rustls/ServerCertIssuer: mirror: subject")?;
+        params.subject_alt_names = rcgen_subject_alt_names(info.subject_alt_names)
+            .context("rustls/ServerCertIssuer: mirror: subject alt names")?;
+
+        let not_before = info
+            .not_before
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("rustls/ServerCertIssuer: mirror: not before")?;
+        let not_after = info
+            .not_after
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .context("rustls/ServerCertIssuer: mirror: not after")?;
+        params.not_before = rcgen::date_time_ymd(1970, 1, 1) + not_before;
+        params.not_after = rcgen::date_time_ymd(1970, 1, 1) + not_after;
+
+        self.sign_leaf_cert(params)
+    }
+
+    /// Sign the given leaf cert params using the CA, with a newly generated key pair.
+    ///
+    /// The key usages are derived from the type of the generated key,
+    /// and are never copied from the (mirrored) upstream cert.
+    fn sign_leaf_cert(
+        &self,
+        mut params: rcgen::CertificateParams,
+    ) -> Result<IssuedCert, OpaqueError> {
+        params.is_ca = rcgen::IsCa::NoCa;
+        params.key_usages = vec![rcgen::KeyUsagePurpose::DigitalSignature];
+        if matches!(
+            self.key_type,
+            ServerCertKeyType::Rsa2048 | ServerCertKeyType::Rsa4096
+        ) {
+            params
+                .key_usages
+                .push(rcgen::KeyUsagePurpose::KeyEncipherment);
+        }
+        params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth];
+        params.use_authority_key_identifier_extension = true;
+
+        let key_pair = generate_key_pair(self.key_type)?;
+        let cert = params
+            .signed_by(&key_pair, &self.ca_cert, &self.ca_key)
+            .context("rustls/ServerCertIssuer: sign issued cert")?;
+
+        let mut cert_chain = Vec::with_capacity(self.ca_chain.len() + 1);
+        cert_chain.push(cert.into());
+        cert_chain.extend(self.ca_chain.iter().cloned());
+        let key = PrivatePkcs8KeyDer::from(key_pair.serialize_der()).into();
+
+        Ok(IssuedCert { cert_chain, key })
+    }
+
+    fn resolve_host(&self, host: Host) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        match &self.cert_cache {
+            None => self.issue_cert(&host, Vec::new())?.try_into_certified_key(),
+            Some(IssuedCertCache::Mem(cache)) => cache
+                .try_get_with_by_ref(&host, || {
+                    self.issue_cert(&host, Vec::new())?.try_into_certified_key()
+                })
+                .context("mem cache"),
+            Some(IssuedCertCache::Custom { .. }) => Err(OpaqueError::from_display(
+                "rustls/ServerCertIssuer: custom cert caches can only be used asynchronously",
+            )),
+        }
+    }
+
+    /// Resolve a cert for the client of the given [`RamaClientHello`],
+    /// mirroring (the subject alt names of) the cert of the upstream server
+    /// it wants to reach if configured to do so.
+    ///
+    /// Mirrored certs are only cached in case the client sent a server name (SNI).
+    pub(super) async fn resolve_async(
+        &self,
+        client_hello: RamaClientHello,
+    ) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        let host = client_hello.ext_server_name().cloned();
+
+        let Some(upstream) = self.upstream.as_ref() else {
+            let host = host.unwrap_or(Host::Name(Domain::from_static("localhost")));
+            return match &self.cert_cache {
+                None => self.issue_cert(&host, Vec::new())?.try_into_certified_key(),
+                Some(cert_cache) => {
+                    let issued_cert = async { self.issue_cert(&host, Vec::new()) };
+                    cert_cache.try_get_with(host.clone(), issued_cert).await
+                }
+            };
+        };
+
+        let issued_cert = async {
+            let upstream_cert = upstream
+                .fetcher()
+                .fetch_upstream_cert(client_hello, None)
+                .await
+                .context("rustls/ServerCertIssuer: mirror: fetch upstream cert")?;
+            match upstream {
+                UpstreamMirror::SubjectAltNames(_) => {
+                    let info = CertificateInfo::try_from_der(&upstream_cert)
+                        .context("rustls/ServerCertIssuer: mirror: parse upstream cert")?;
+                    let host = host
+                        .clone()
+                        .unwrap_or(Host::Name(Domain::from_static("localhost")));
+                    self.issue_cert(&host, info.subject_alt_names)
+                }
+                UpstreamMirror::Certificate(_) => self.issue_mirrored_cert(&upstream_cert),
+            }
+        };
+        match (host.clone(), &self.cert_cache) {
+            (Some(host), Some(cert_cache)) => cert_cache.try_get_with(host, issued_cert).await,
+            _ => issued_cert.await?.try_into_certified_key(),
+        }
+    }
+}
+
+impl ResolvesServerCert for ServerCertIssuer {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        if self.is_async() {
+            tracing::error!(
+                "rustls: resolve server cert: mirrored or custom cached certs can only be resolved asynchronously"
+            );
+            return None;
+        }
+
+        let host = match client_hello.server_name() {
+            Some(sni) => sni
+                .parse()
+                .inspect_err(|err| {
+                    tracing::warn!(error = %err, "rustls: invalid server name received in resolver");
+                })
+                .ok()?,
+            None => {
+                tracing::debug!("rustls: no server name received: defaulting to 'localhost'");
+                Host::Name(Domain::from_static("localhost"))
+            }
+        };
+
+        self.resolve_host(host)
+            .inspect_err(|err| {
+                tracing::error!(error = %err, "rustls: resolve server cert: issue failed");
+            })
+            .ok()
+    }
+}
+
+struct IssuedCert {
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl IssuedCert {
+    fn try_into_certified_key(self) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        let signing_key =
+            any_supported_type(&self.key).context("rustls/ServerCertIssuer: create signing key")?;
+        Ok(Arc::new(CertifiedKey::new(self.cert_chain, signing_key)))
+    }
+
+    fn into_server_auth_data(self) -> ServerAuthData {
+        ServerAuthData {
+            private_key: DataEncoding::Der(self.key.secret_der().to_vec()),
+            cert_chain: DataEncoding::DerStack(
+                self.cert_chain.iter().map(|cert| cert.to_vec()).collect(),
+            ),
+            ocsp: None,
+        }
+    }
+}
+
+/// Cache for certs already issued, as configured by [`CacheKind`].
+enum IssuedCertCache {
+    Mem(Cache<Host, Arc<CertifiedKey>>),
+    Custom {
+        cache: SharedServerCertCache,
+        issuer_fingerprint: CertificateFingerprint,
+    },
+}
+
+impl fmt::Debug for IssuedCertCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mem(_) => f.debug_tuple("Mem").finish(),
+            Self::Custom {
+                cache,
+                issuer_fingerprint,
+            } => f
+                .debug_struct("Custom")
+                .field("cache", cache)
+                .field("issuer_fingerprint", issuer_fingerprint)
+                .finish(),
+        }
+    }
+}
+
+impl IssuedCertCache {
+    fn try_new(
+        kind: CacheKind,
+        ca_cert: &CertificateDer<'_>,
+        validity: Duration,
+    ) -> Result<Option<Self>, OpaqueError> {
+        Ok(match kind {
+            CacheKind::Disabled => None,
+            CacheKind::MemCache { max_size } => Some(Self::Mem(
+                Cache::builder()
+                    // expire cached certs (at most) a day before they are no longer valid
+                    .time_to_live(validity.saturating_sub(ONE_DAY.min(validity / 2)))
+                    .max_capacity(max_size.into())
+                    .build(),
+            )),
+            CacheKind::Custom(cache) => Some(Self::Custom {
+                cache,
+                issuer_fingerprint: CertificateFingerprint::from_der(ca_cert.as_ref()),
+            }),
+        })
+    }
+
+    /// Get the cert cached for the given host,
+    /// or issue it using the given future and cache it.
+    async fn try_get_with(
+        &self,
+        host: Host,
+        issued_cert: impl Future<Output = Result<IssuedCert, OpaqueError>>,
+    ) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        match self {
+            Self::Mem(cache) => {
+                if let Some(certified_key) = cache.get(&host) {
+                    return Ok(certified_key);
+                }
+                let certified_key = issued_cert.await?.try_into_certified_key()?;
+                cache.insert(host, certified_key.clone());
+                Ok(certified_key)
+            }
+            Self::Custom {
+                cache,
+                issuer_fingerprint,
+            } => {
+                let key = ServerCertCacheKey::new(host, Some(*issuer_fingerprint));
+                let data = cache
+                    .try_get_with(key, async {
+                        issued_cert.await.map(IssuedCert::into_server_auth_data)
+                    })
+                    .await?;
+                let (cert_chain, key) = server_auth_data_to_cert_chain_and_key(&data)?;
+                IssuedCert { cert_chain, key }.try_into_certified_key()
+            }
+        }
+    }
+}
+
+/// Convert the given (parsed) subject alt names into [`rcgen::SanType`]s.
+fn rcgen_subject_alt_names(
+    subject_alt_names: Vec<SubjectAltName>,
+) -> Result<Vec<rcgen::SanType>, rcgen::Error> {
+    subject_alt_names
+        .into_iter()
+        .map(|san| {
+            Ok(match san {
+                SubjectAltName::Dns(name) => rcgen::SanType::DnsName(name.try_into()?),
+                SubjectAltName::Ip(ip) => rcgen::SanType::IpAddress(ip),
+                SubjectAltName::Email(email) => rcgen::SanType::Rfc822Name(email.try_into()?),
+                SubjectAltName::Uri(uri) => rcgen::SanType::URI(uri.try_into()?),
+            })
+        })
+        .collect()
+}
+
+fn generate_key_pair(key_type: ServerCertKeyType) -> Result<KeyPair, OpaqueError> {
+    match key_type {
+        ServerCertKeyType::EcdsaP256 => KeyPair::generate_for(&rcgen::PKCS_ECDSA_P256_SHA256),
+        ServerCertKeyType::EcdsaP384 => KeyPair::generate_for(&rcgen::PKCS_ECDSA_P384_SHA384),
+        ServerCertKeyType::Rsa2048 => {
+            KeyPair::generate_rsa_for(&rcgen::PKCS_RSA_SHA256, rcgen::RsaKeySize::_2048)
+        }
+        ServerCertKeyType::Rsa4096 => {
+            KeyPair::generate_rsa_for(&rcgen::PKCS_RSA_SHA256, rcgen::RsaKeySize::_4096)
+        }
+    }
+    .context("rustls/ServerCertIssuer: generate key pair")
+}
+
+fn self_signed_ca(data: SelfSignedData) -> Result<(rcgen::Certificate, KeyPair), OpaqueError> {
+    let ca_key = KeyPair::generate_for(&rcgen::PKCS_ECDSA_P256_SHA256)
+        .context("rustls/ServerCertIssuer: self-signed: generate ca key pair")?;
+
+    let common_name = data
+        .common_name
+        .unwrap_or(Host::Name(Domain::from_static("localhost")));
+
+    let mut params = rcgen::CertificateParams::default();
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params.distinguished_name.push(
+        rcgen::DnType::OrganizationName,
+        data.organisation_name
+            .unwrap_or_else(|| "Anonymous".to_owned()),
+    );
+    params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, common_name.to_string());
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    params.key_usages = vec![
+        rcgen::KeyUsagePurpose::KeyCertSign,
+        rcgen::KeyUsagePurpose::DigitalSignature,
+        rcgen::KeyUsagePurpose::CrlSign,
+    ];
+    let ca_cert = params
+        .self_signed(&ca_key)
+        .context("rustls/ServerCertIssuer: self-signed: create ca cert")?;
+
+    Ok((ca_cert, ca_key))
+}
+
+/// Create an [`rcgen::Certificate`] which can be used as issuer in place of
+/// the given DER-encoded CA cert, as rcgen only needs its subject and key identifier.
+fn ca_from_der(
+    ca_cert: &[u8],
+    ca_key: &PrivateKeyDer<'_>,
+) -> Result<(rcgen::Certificate, KeyPair), OpaqueError> {
+    let info = CertificateInfo::try_from_der(ca_cert).context("rustls/ServerCertIssuer: CA")?;
+    let ca_key = KeyPair::try_from(ca_key).context("rustls/ServerCertIssuer: CA: parse ca key")?;
+    if ca_key.public_key_der() != info.subject_public_key_info {
+        return Err(OpaqueError::from_display(
+            "rustls/ServerCertIssuer: CA: ca key does not match ca cert",
+        ));
+    }
+
+    let mut params = rcgen::CertificateParams::default();
+    params.distinguished_name = distinguished_name_from_attributes(info.subject_attributes)
+        .context("rustls/ServerCertIssuer: CA: subject")?;
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    if let Some(key_identifier) = info.subject_key_identifier {
+        params.key_identifier_method = rcgen::KeyIdMethod::PreSpecified(key_identifier);
+    }
+
+    let ca_cert = params
+        .self_signed(&ca_key)
+        .context("rustls/ServerCertIssuer: CA: create issuer")?;
+    Ok((ca_cert, ca_key))
+}
+
+/// Create an [`rcgen::DistinguishedName`] from the given (parsed) subject attributes.
+fn distinguished_name_from_attributes(
+    attributes: Vec<NameAttribute>,
+) -> Result<rcgen::DistinguishedName, OpaqueError> {
+    let mut distinguished_name = rcgen::DistinguishedName::new();
+    for attribute in attributes {
+        let dn_type = rcgen::DnType::from_oid(&attribute.oid);
+        if distinguished_name.get(&dn_type).is_some() {
+            return Err(OpaqueError::from_display(
+                "repeated subject attributes are not supported",
+            ));
+        }
+        let value = match attribute.string_kind {
+            NameStringKind::Utf8String => rcgen::DnValue::Utf8String(attribute.value),
+            NameStringKind::PrintableString => rcgen::DnValue::PrintableString(
+                attribute.value.try_into().context("subject attribute")?,
+            ),
+            NameStringKind::Ia5String => {
+                rcgen::DnValue::Ia5String(attribute.value.try_into().context("subject attribute")?)
+            }
+            NameStringKind::TeletexString => rcgen::DnValue::TeletexString(
+                attribute.value.try_into().context("subject attribute")?,
+            ),
+            NameStringKind::BmpString => {
+                rcgen::DnValue::BmpString(attribute.value.try_into().context("subject attribute")?)
+            }
+            NameStringKind::UniversalString => rcgen::DnValue::UniversalString(
+                attribute.value.try_into().context("subject attribute")?,
+            ),
+        };
+        distinguished_name.push(dn_type, value);
+    }
+    Ok(distinguished_name)
+}
+
+/// Create a [`CertifiedKey`] from the given [`ServerAuthData`],
+/// verifying that the private key matches the leaf cert.
+///
+/// The OCSP response of the data, if any, is stapled by the created key.
+pub(super) fn server_auth_data_to_certified_key(
+    data: &ServerAuthData,
+) -> Result<Arc<CertifiedKey>, OpaqueError> {
+    let (cert_chain, key) = server_auth_data_to_cert_chain_and_key(data)?;
+    let signing_key =
+        any_supported_type(&key).context("rustls/ServerCertIssuer: create signing key")?;
+    let mut certified_key = CertifiedKey::new(cert_chain, signing_key);
+    certified_key.ocsp = data.ocsp.clone();
+    match certified_key.keys_match() {
+        // the key might not be able to expose its public key, in which case we can't verify
+        Ok(()) | Err(rustls::Error::InconsistentKeys(rustls::InconsistentKeys::Unknown)) => {
+            Ok(Arc::new(certified_key))
+        }
+        Err(err) => {
+            Err(err).context("rustls/ServerCertIssuer: private key does not match leaf cert")
+        }
+    }
+}
+
+fn server_auth_data_to_cert_chain_and_key(
+    data: &ServerAuthData,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), OpaqueError> {
+    let key = match &data.private_key {
+        DataEncoding::Der(raw_data) => PrivateKeyDer::try_from(raw_data.clone())
+            .map_err(OpaqueError::from_display)
+            .context("rustls/ServerCertIssuer: parse private key from DER content")?,
+        DataEncoding::DerStack(raw_data_list) => PrivateKeyDer::try_from(
+            raw_data_list
+                .first()
+                .context("rustls/ServerCertIssuer: get first private key raw data")?
+                .clone(),
+        )
+        .map_err(OpaqueError::from_display)
+        .context("rustls/ServerCertIssuer: parse private key from DER content")?,
+        DataEncoding::Pem(raw_data) => PrivateKeyDer::from_pem_slice(raw_data.as_bytes())
+            .context("rustls/ServerCertIssuer: parse private key from PEM content")?,
+    };
+
+    let cert_chain = match &data.cert_chain {
+        DataEncoding::Der(raw_data) => vec![raw_data.clone().into()],
+        DataEncoding::DerStack(raw_data_list) => raw_data_list
+            .iter()
+            .map(|raw_data| raw_data.clone().into())
+            .collect(),
+        DataEncoding::Pem(raw_data) => CertificateDer::pem_slice_iter(raw_data.as_bytes())
+            .collect::<Result<_, _>>()
+            .context("rustls/ServerCertIssuer: parse cert chain from PEM content")?,
+    };
+
+    Ok((cert_chain, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dep::rustls::{
+        ClientConfig, RootCertStore, ServerConfig, server::Acceptor, sign::SingleCertAndKey,
+    };
+    use crate::dep::tokio_rustls::{LazyConfigAcceptor, TlsAcceptor, TlsConnector};
+    use rama_net::tls::KeyUsage;
+    use rama_net::tls::server::{FsServerCertCache, UpstreamCertFetcher};
+    use rama_utils::str::NonEmptyString;
+    use rustls_pki_types::ServerName;
+
+    #[tokio::test]
+    async fn test_server_cert_issuer_certificate_authority() {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec![]).unwrap();
+        ca_params.distinguished_name = rcgen::DistinguishedName::new();
+        ca_params.distinguished_name.push(
+            rcgen::DnType::CountryName,
+            rcgen::DnValue::PrintableString("BE".try_into().unwrap()),
+        );
+        ca_params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Rama");
+        ca_params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "Rama Test CA");
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        ca_params.key_usages = vec![
+            rcgen::KeyUsagePurpose::KeyCertSign,
+            rcgen::KeyUsagePurpose::DigitalSignature,
+        ];
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let issuer = ServerCertIssuer::try_new(ServerCertIssuerData {
+            kind: ServerCertIssuerKind::CertificateAuthority {
+                ca_cert: DataEncoding::Pem(NonEmptyString::try_from(ca.pem()).unwrap()),
+                ca_key: DataEncoding::Pem(
+                    NonEmptyString::try_from(ca_key.serialize_pem()).unwrap(),
+                ),
+                validity: ONE_DAY,
+                key_type: ServerCertKeyType::EcdsaP384,
+                upstream: None,
+            },
+            cache_kind: CacheKind::default(),
+        })
+        .unwrap();
+
+        let host: Host = "example.com".parse().unwrap();
+        let certified_key = issuer.resolve_host(host.clone()).unwrap();
+        assert!(Arc::ptr_eq(
+            &certified_key,
+            &issuer.resolve_host(host).unwrap()
+        ));
+        assert_eq!(certified_key.cert.len(), 2);
+        assert_eq!(&certified_key.cert[1], ca.der());
+
+        let ca_info = CertificateInfo::try_from_der(ca.der()).unwrap();
+        let info = CertificateInfo::try_from_der(&certified_key.cert[0]).unwrap();
+        assert_eq!(info.subject, "CN=example.com");
+        assert_eq!(info.issuer, ca_info.subject);
+        assert_eq!(
+            info.subject_alt_names,
+            vec![SubjectAltName::Dns("example.com".to_owned())]
+        );
+        let validity = info.not_after.duration_since(info.not_before).unwrap();
+        assert_eq!(validity, ONE_DAY);
+
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(issuer));
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.der().clone()).unwrap();
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        tokio::spawn(async move {
+            let _ = acceptor.accept(server_stream).await;
+        });
+        TlsConnector::from(Arc::new(client_config))
+            .connect(
+                ServerName::try_from("www.example.com").unwrap(),
+                client_stream,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_server_cert_issuer_certificate_authority_mirrors_subject_alt_names() {
+        struct FakeUpstream(Vec<u8>);
+
+        impl UpstreamCertFetcher for FakeUpstream {
+            async fn fetch_upstream_cert(
+                &self,
+                _client_hello: RamaClientHello,
+                _server_name: Option<Host>,
+            ) -> Result<Vec<u8>, OpaqueError> {
+                Ok(self.0.clone())
+            }
+        }
+
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let upstream_key = KeyPair::generate().unwrap();
+        let upstream_cert = rcgen::CertificateParams::new(vec![
+            "example.com".to_owned(),
+            "*.example.com".to_owned(),
+        ])
+        .unwrap()
+        .self_signed(&upstream_key)
+        .unwrap();
+
+        let issuer = ServerCertIssuer::try_new(ServerCertIssuerData {
+            kind: ServerCertIssuerKind::CertificateAuthority {
+                ca_cert: DataEncoding::Der(ca.der().to_vec()),
+                ca_key: DataEncoding::Der(ca_key.serialize_der()),
+                validity: ONE_DAY,
+                key_type: ServerCertKeyType::EcdsaP256,
+                upstream: Some(FakeUpstream(upstream_cert.der().to_vec()).into()),
+            },
+            cache_kind: CacheKind::default(),
+        })
+        .unwrap();
+        assert!(issuer.is_async());
+
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.der().clone()).unwrap();
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+        let client = tokio::spawn(async move {
+            TlsConnector::from(Arc::new(client_config))
+                .connect(
+                    ServerName::try_from("www.example.com").unwrap(),
+                    client_stream,
+                )
+                .await
+                .unwrap()
+        });
+
+        let start = LazyConfigAcceptor::new(Acceptor::default(), server_stream)
+            .await
+            .unwrap();
+        let certified_key = issuer
+            .resolve_async(RamaClientHello::from(start.client_hello()))
+            .await
+            .unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(SingleCertAndKey::from(
+                certified_key.as_ref().clone(),
+            )));
+        start.into_stream(Arc::new(server_config)).await.unwrap();
+        client.await.unwrap();
+
+        let info = CertificateInfo::try_from_der(&certified_key.cert[0]).unwrap();
+        assert_eq!(info.subject, "CN=www.example.com");
+        assert_eq!(
+            info.subject_alt_names,
+            vec![
+                SubjectAltName::Dns("www.example.com".to_owned()),
+                SubjectAltName::Dns("example.com".to_owned()),
+                SubjectAltName::Dns("*.example.com".to_owned()),
+            ]
+        );
+        let validity = info.not_after.duration_since(info.not_before).unwrap();
+        assert_eq!(validity, ONE_DAY);
+    }
+
+    #[tokio::test]
+    async fn test_server_cert_issuer_mirror_upstream() {
+        struct FakeUpstream(Vec<u8>);
+
+        impl UpstreamCertFetcher for FakeUpstream {
+            async fn fetch_upstream_cert(
+                &self,
+                client_hello: RamaClientHello,
+                _server_name: Option<Host>,
+            ) -> Result<Vec<u8>, OpaqueError> {
+                assert_eq!(
+                    client_hello.ext_server_name(),
+                    Some(&"www.example.com".parse().unwrap())
+                );
+                Ok(self.0.clone())
+            }
+        }
+
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let upstream_key = KeyPair::generate().unwrap();
+        let mut upstream_params = rcgen::CertificateParams::new(vec![
+            "example.com".to_owned(),
+            "www.example.com".to_owned(),
+        ])
+        .unwrap();
+        upstream_params.distinguished_name = rcgen::DistinguishedName::new();
+        upstream_params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Example Inc");
+        upstream_params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "example.com");
+        upstream_params.key_usages = vec![
+            rcgen::KeyUsagePurpose::DigitalSignature,
+            rcgen::KeyUsagePurpose::KeyEncipherment,
+            rcgen::KeyUsagePurpose::KeyCertSign,
+        ];
+        upstream_params.not_before = rcgen::date_time_ymd(2020, 1, 1);
+        upstream_params.not_after = rcgen::date_time_ymd(2090, 1, 1);
+        let upstream_cert = upstream_params.self_signed(&upstream_key).unwrap();
+
+        let issuer = ServerCertIssuer::try_new(ServerCertIssuerData {
+            kind: ServerCertIssuerKind::MirrorUpstream {
+                ca_cert: DataEncoding::Der(ca.der().to_vec()),
+                ca_key: DataEncoding::Der(ca_key.serialize_der()),
+                key_type: ServerCertKeyType::EcdsaP256,
+                upstream: FakeUpstream(upstream_cert.der().to_vec()).into(),
+            },
+            cache_kind: CacheKind::default(),
+        })
+        .unwrap();
+        assert!(issuer.is_async());
+
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.der().clone()).unwrap();
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+        // keep the client stream alive until the server completed the handshake
+        let client = tokio::spawn(async move {
+            TlsConnector::from(Arc::new(client_config))
+                .connect(
+                    ServerName::try_from("www.example.com").unwrap(),
+                    client_stream,
+                )
+                .await
+                .unwrap()
+        });
+
+        let start = LazyConfigAcceptor::new(Acceptor::default(), server_stream)
+            .await
+            .unwrap();
+        let certified_key = issuer
+            .resolve_async(RamaClientHello::from(start.client_hello()))
+            .await
+            .unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(SingleCertAndKey::from(
+                certified_key.as_ref().clone(),
+            )));
+        start.into_stream(Arc::new(server_config)).await.unwrap();
+        client.await.unwrap();
+
+        let upstream_info = CertificateInfo::try_from_der(upstream_cert.der()).unwrap();
+        let info = CertificateInfo::try_from_der(&certified_key.cert[0]).unwrap();
+        assert_eq!(info.subject, upstream_info.subject);
+        assert_eq!(info.subject_alt_names, upstream_info.subject_alt_names);
+        // key usages are derived from the (EC) key type, not copied from upstream
+        assert_eq!(info.key_usage, Some(vec![KeyUsage::DigitalSignature]));
+        assert_eq!(info.not_before, upstream_info.not_before);
+        assert_eq!(info.not_after, upstream_info.not_after);
+        assert_ne!(
+            info.subject_public_key_info,
+            upstream_info.subject_public_key_info
+        );
+    }
+
+    #[tokio::test]
+    async fn test_server_cert_issuer_custom_cache() {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "rama-rustls-server-cert-issuer-cache-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let issuer = ServerCertIssuer::try_new(ServerCertIssuerData {
+            kind: ServerCertIssuerKind::CertificateAuthority {
+                ca_cert: DataEncoding::Der(ca.der().to_vec()),
+                ca_key: DataEncoding::Der(ca_key.serialize_der()),
+                validity: ONE_DAY * 7,
+                key_type: ServerCertKeyType::EcdsaP256,
+                upstream: None,
+            },
+            cache_kind: CacheKind::custom(FsServerCertCache::try_new(&dir).unwrap()),
+        })
+        .unwrap();
+        assert!(issuer.is_async());
+        assert!(issuer.resolve_host("example.com".parse().unwrap()).is_err());
+
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.der().clone()).unwrap();
+        let client_config = Arc::new(
+            ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        );
+
+        let mut issued_certs = Vec::new();
+        for _ in 0..2 {
+            let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+            let client_config = client_config.clone();
+            let client = tokio::spawn(async move {
+                TlsConnector::from(client_config)
+                    .connect(ServerName::try_from("example.com").unwrap(), client_stream)
+                    .await
+                    .unwrap()
+            });
+
+            let start = LazyConfigAcceptor::new(Acceptor::default(), server_stream)
+                .await
+                .unwrap();
+            let certified_key = issuer
+                .resolve_async(RamaClientHello::from(start.client_hello()))
+                .await
+                .unwrap();
+            let server_config = ServerConfig::builder()
+                .with_no_client_auth()
+                .with_cert_resolver(Arc::new(SingleCertAndKey::from(
+                    certified_key.as_ref().clone(),
+                )));
+            start.into_stream(Arc::new(server_config)).await.unwrap();
+            client.await.unwrap();
+            issued_certs.push(certified_key.cert[0].clone());
+        }
+
+        // the second handshake is served the cert stored by the first one
+        assert_eq!(issued_certs[0], issued_certs[1]);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_server_cert_issuer_rejects_mismatching_ca_key() {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let result = ServerCertIssuer::try_new(ServerCertIssuerData {
+            kind: ServerCertIssuerKind::CertificateAuthority {
+                ca_cert: DataEncoding::Der(ca.der().to_vec()),
+                ca_key: DataEncoding::Der(KeyPair::generate().unwrap().serialize_der()),
+                validity: ONE_DAY,
+                key_type: ServerCertKeyType::default(),
+                upstream: None,
+            },
+            cache_kind: CacheKind::Disabled,
+        });
+        assert!(result.is_err());
+    }
+}
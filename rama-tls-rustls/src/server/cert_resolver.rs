@@ -0,0 +1,253 @@
+use super::cert_issuer::server_auth_data_to_certified_key;
+use crate::dep::rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+use parking_lot::Mutex;
+use rama_core::error::OpaqueError;
+use rama_net::address::Host;
+use rama_net::tls::server::{FileWatchingCertIssuer, OcspStapler, ServerAuthData, SniMap};
+use std::{path::PathBuf, sync::Arc};
+
+#[derive(Debug)]
+/// A [`ResolvesServerCert`] serving the certificate chain and private key
+/// loaded by a [`FileWatchingCertIssuer`], such that these can be
+/// reloaded from disk without restarting the server.
+///
+/// Reloaded pairs are only served if the private key matches the leaf cert,
+/// otherwise the previous pair keeps being served.
+/// Use [`Self::issuer`] to get the [`FileWatchingCertIssuer`]
+/// in order to reload or [watch](FileWatchingCertIssuer::watch) the files.
+pub struct FileWatchingCertResolver {
+    issuer: FileWatchingCertIssuer,
+    certified_key: CachedCertifiedKey,
+}
+
+impl FileWatchingCertResolver {
+    /// Try to create a new [`FileWatchingCertResolver`], loading the PEM-encoded
+    /// certificate chain and private key found at the given paths.
+    pub fn try_new(
+        cert_chain_path: impl Into<PathBuf>,
+        private_key_path: impl Into<PathBuf>,
+    ) -> Result<Self, OpaqueError> {
+        let issuer = FileWatchingCertIssuer::try_new_with_validator(
+            cert_chain_path,
+            private_key_path,
+            |data| server_auth_data_to_certified_key(data).map(|_| ()),
+        )?;
+        let certified_key = CachedCertifiedKey::try_new(issuer.server_auth_data())?;
+        Ok(Self {
+            issuer,
+            certified_key,
+        })
+    }
+
+    /// Return the [`FileWatchingCertIssuer`] used to load the certificate chain and private key.
+    pub fn issuer(&self) -> &FileWatchingCertIssuer {
+        &self.issuer
+    }
+
+    fn current_certified_key(&self) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        self.certified_key.get(self.issuer.server_auth_data())
+    }
+}
+
+impl ResolvesServerCert for FileWatchingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.current_certified_key()
+            .inspect_err(|err| {
+                tracing::error!(error = %err, "rustls: resolve server cert: load reloaded cert failed");
+            })
+            .ok()
+    }
+}
+
+#[derive(Debug)]
+/// A [`ResolvesServerCert`] serving the certificate chain and private key
+/// of an [`OcspStapler`], stapled with the OCSP response it keeps fresh.
+///
+/// Use [`Self::stapler`] to get the [`OcspStapler`]
+/// in order to refresh or [run](OcspStapler::run) it.
+pub struct OcspStaplingCertResolver {
+    stapler: OcspStapler,
+    certified_key: CachedCertifiedKey,
+}
+
+impl OcspStaplingCertResolver {
+    /// Try to create a new [`OcspStaplingCertResolver`] for the given [`OcspStapler`],
+    /// verifying that its private key matches the leaf cert.
+    pub fn try_new(stapler: OcspStapler) -> Result<Self, OpaqueError> {
+        let certified_key = CachedCertifiedKey::try_new(stapler.server_auth_data())?;
+        Ok(Self {
+            stapler,
+            certified_key,
+        })
+    }
+
+    /// Return the [`OcspStapler`] used to fetch the stapled OCSP response.
+    pub fn stapler(&self) -> &OcspStapler {
+        &self.stapler
+    }
+
+    fn current_certified_key(&self) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        self.certified_key.get(self.stapler.server_auth_data())
+    }
+}
+
+impl ResolvesServerCert for OcspStaplingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.current_certified_key()
+            .inspect_err(|err| {
+                tracing::error!(error = %err, "rustls: resolve server cert: load stapled cert failed");
+            })
+            .ok()
+    }
+}
+
+#[derive(Debug)]
+/// [`CertifiedKey`] for the [`ServerAuthData`] last served by an issuer,
+/// only recreated when the issuer serves new data.
+struct CachedCertifiedKey(Mutex<(Arc<ServerAuthData>, Arc<CertifiedKey>)>);
+
+impl CachedCertifiedKey {
+    fn try_new(data: Arc<ServerAuthData>) -> Result<Self, OpaqueError> {
+        let certified_key = server_auth_data_to_certified_key(&data)?;
+        Ok(Self(Mutex::new((data, certified_key))))
+    }
+
+    fn get(&self, data: Arc<ServerAuthData>) -> Result<Arc<CertifiedKey>, OpaqueError> {
+        let mut certified_key = self.0.lock();
+        if !Arc::ptr_eq(&certified_key.0, &data) {
+            *certified_key = (data.clone(), server_auth_data_to_certified_key(&data)?);
+        }
+        Ok(certified_key.1.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A [`ResolvesServerCert`] selecting the certificate chain and private key
+/// based on the server name (SNI) sent by the client, as configured by a [`SniMap`].
+///
+/// Use [`super::TlsAcceptorDataBuilder::new_with_sni_map`] to create
+/// a [`super::TlsAcceptorDataBuilder`] using this resolver.
+pub struct SniCertResolver {
+    certified_keys: SniMap<Arc<CertifiedKey>>,
+}
+
+impl SniCertResolver {
+    /// Try to create a new [`SniCertResolver`], verifying that the private key
+    /// of each entry in the given [`SniMap`] matches its leaf cert.
+    pub fn try_new(map: SniMap<ServerAuthData>) -> Result<Self, OpaqueError> {
+        Ok(Self {
+            certified_keys: map.try_map(|data| server_auth_data_to_certified_key(&data))?,
+        })
+    }
+}
+
+impl ResolvesServerCert for SniCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let server_name = match client_hello.server_name() {
+            Some(sni) => Some(
+                sni.parse::<Host>()
+                    .inspect_err(|err| {
+                        tracing::warn!(error = %err, "rustls: invalid server name received in resolver");
+                    })
+                    .ok()?,
+            ),
+            None => None,
+        };
+        let certified_key = self.certified_keys.get(server_name.as_ref());
+        if certified_key.is_none() {
+            tracing::debug!(
+                ?server_name,
+                "rustls: resolve server cert: no cert found for server name"
+            );
+        }
+        certified_key.cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dep::rcgen::{self, KeyPair};
+    use rama_net::address::Domain;
+    use rama_net::tls::DataEncoding;
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_file_watching_cert_resolver_reload() {
+        let dir = std::env::temp_dir().join(format!(
+            "rama-file-watching-cert-resolver-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+
+        let write_cert = |key_pair: &KeyPair| {
+            let cert = rcgen::CertificateParams::new(vec!["example.com".to_owned()])
+                .unwrap()
+                .self_signed(key_pair)
+                .unwrap();
+            fs::write(&cert_path, cert.pem()).unwrap();
+            cert
+        };
+
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = write_cert(&key_pair);
+        fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+
+        let resolver = FileWatchingCertResolver::try_new(&cert_path, &key_path).unwrap();
+        assert_eq!(
+            &resolver.current_certified_key().unwrap().cert[0],
+            cert.der()
+        );
+
+        // a key not matching the cert is rejected, keeping the previous pair
+        fs::write(&key_path, KeyPair::generate().unwrap().serialize_pem()).unwrap();
+        assert!(resolver.issuer().reload().await.is_err());
+        assert_eq!(
+            &resolver.current_certified_key().unwrap().cert[0],
+            cert.der()
+        );
+
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = write_cert(&key_pair);
+        fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+        resolver.issuer().reload().await.unwrap();
+        assert_eq!(
+            &resolver.current_certified_key().unwrap().cert[0],
+            cert.der()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sni_cert_resolver_rejects_mismatching_key() {
+        let key_pair = KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["example.com".to_owned()])
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        let data = |key_pair: &KeyPair| ServerAuthData {
+            private_key: DataEncoding::Der(key_pair.serialize_der()),
+            cert_chain: DataEncoding::Der(cert.der().to_vec()),
+            ocsp: None,
+        };
+
+        assert!(
+            SniCertResolver::try_new(
+                SniMap::new().with(Domain::from_static("example.com"), data(&key_pair))
+            )
+            .is_ok()
+        );
+        assert!(
+            SniCertResolver::try_new(
+                SniMap::new().with_default(data(&KeyPair::generate().unwrap()))
+            )
+            .is_err()
+        );
+    }
+}
@@ -21,6 +21,14 @@ mod layer;
 #[doc(inline)]
 pub use layer::TlsAcceptorLayer;
 
+mod cert_issuer;
+#[doc(inline)]
+pub use cert_issuer::ServerCertIssuer;
+
+mod cert_resolver;
+#[doc(inline)]
+pub use cert_resolver::{FileWatchingCertResolver, OcspStaplingCertResolver, SniCertResolver};
+
 mod acceptor_data;
 #[doc(inline)]
 pub use acceptor_data::{
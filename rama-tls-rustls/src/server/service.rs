@@ -1,4 +1,4 @@
-use crate::dep::rustls::server::Acceptor;
+use crate::dep::rustls::{HandshakeKind, server::Acceptor};
 use crate::dep::tokio_rustls::{LazyConfigAcceptor, server::TlsStream};
 use crate::types::SecureTransport;
 use rama_core::{
@@ -6,8 +6,9 @@ use rama_core::{
     error::{BoxError, ErrorContext, ErrorExt, OpaqueError},
 };
 use rama_net::{
+    address::Host,
     stream::Stream,
-    tls::{ApplicationProtocol, client::NegotiatedTlsParameters},
+    tls::{ApplicationProtocol, CertificateInfo, client::NegotiatedTlsParameters},
 };
 use rama_utils::macros::define_inner_service_accessors;
 
@@ -94,8 +95,26 @@ where
             application_layer_protocol: conn_data_ref
                 .alpn_protocol()
                 .map(ApplicationProtocol::from),
+            cipher_suite: conn_data_ref
+                .negotiated_cipher_suite()
+                .map(|suite| suite.suite().into()),
+            key_exchange_group: conn_data_ref
+                .negotiated_key_exchange_group()
+                .map(|group| group.name().into()),
+            // rustls does not expose the scheme of the client's (CertificateVerify) signature
+            signature_scheme: None,
+            server_name: conn_data_ref
+                .server_name()
+                .and_then(|name| Host::try_from(name).ok()),
             // Currently not supported as this would mean we need to wrap rustls config
             peer_certificate_chain: None,
+            peer_certificate: conn_data_ref
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .and_then(|cert| CertificateInfo::try_from_der(cert).ok()),
+            session_resumed: conn_data_ref.handshake_kind() == Some(HandshakeKind::Resumed),
+            // ECH is not supported by rustls servers
+            ech_accepted: false,
         });
 
         ctx.insert(secure_transport);
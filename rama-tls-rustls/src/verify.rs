@@ -1,12 +1,32 @@
 //! TLS Verify support for Rustls usage in Rama.
 //!
-//! ... or rather the lack of verification where it is not needed.
+//! ... or rather the lack of verification where it is not needed,
+//! or custom verification using extra trust anchors, public key pinning
+//! and revocation checks.
 
+use crate::client::client_root_certs;
 use crate::dep::rustls::{
-    DigitallySignedStruct, SignatureScheme,
-    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    CertificateError, DigitallySignedStruct, OtherError, RootCertStore, SignatureScheme,
+    client::{
+        WebPkiServerVerifier,
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    },
+    crypto::{CryptoProvider, WebPkiSupportedAlgorithms},
+};
+use rama_core::error::{ErrorContext, OpaqueError};
+use rama_net::tls::{
+    DataEncoding,
+    client::{CertificatePinningError, CustomServerVerify, SpkiPin},
+    ocsp::{OcspError, OcspSignedData},
+};
+use rustls_pki_types::{
+    CertificateDer, CertificateRevocationListDer, ServerName, TrustAnchor, UnixTime, pem::PemObject,
+};
+use std::{
+    cell::Cell,
+    sync::Arc,
+    time::{Duration, SystemTime},
 };
-use rustls_pki_types::{CertificateDer, ServerName, UnixTime};
 
 /// Cert verifier that does not verify the server certificate.
 #[derive(Debug)]
@@ -74,3 +94,765 @@ impl ServerCertVerifier for NoServerCertVerifier {
         ]
     }
 }
+
+/// Cert verifier that verifies the server certificate
+/// using custom trust anchors and/or public key pinning.
+///
+/// Created from a [`CustomServerVerify`], e.g. as configured
+/// by [`rama_net::tls::client::ServerVerifyMode::Custom`].
+///
+/// After the regular (webpki) verification succeeded, pins are matched
+/// against the verified certificate path, from the end-entity certificate
+/// up to and including the trust anchor, and the stapled OCSP response
+/// is verified if required. Certificates sent by the server which are not
+/// part of the verified path are never matched against the pins.
+#[derive(Debug)]
+pub struct CustomServerCertVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    roots: Arc<RootCertStore>,
+    verify: CustomServerVerify,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl CustomServerCertVerifier {
+    /// Try to create a new [`CustomServerCertVerifier`] for the given [`CustomServerVerify`].
+    pub fn try_new(verify: CustomServerVerify) -> Result<Self, OpaqueError> {
+        let mut roots = if verify.replace_default_roots {
+            RootCertStore::empty()
+        } else {
+            client_root_certs().as_ref().clone()
+        };
+        let root_certs: Vec<CertificateDer<'static>> = match verify.root_certs.as_ref() {
+            None => Vec::new(),
+            Some(DataEncoding::Der(raw_data)) => vec![raw_data.clone().into()],
+            Some(DataEncoding::DerStack(raw_data_list)) => raw_data_list
+                .iter()
+                .map(|raw_data| raw_data.clone().into())
+                .collect(),
+            Some(DataEncoding::Pem(raw_data)) => {
+                CertificateDer::pem_slice_iter(raw_data.as_bytes())
+                    .collect::<Result<_, _>>()
+                    .context("rustls/CustomServerCertVerifier: parse root certs from PEM content")?
+            }
+        };
+        for cert in root_certs {
+            roots
+                .add(cert)
+                .context("rustls/CustomServerCertVerifier: add root cert")?;
+        }
+        let crls: Vec<CertificateRevocationListDer<'static>> = match verify.crls.as_ref() {
+            None => Vec::new(),
+            Some(DataEncoding::Der(raw_data)) => vec![raw_data.clone().into()],
+            Some(DataEncoding::DerStack(raw_data_list)) => raw_data_list
+                .iter()
+                .map(|raw_data| raw_data.clone().into())
+                .collect(),
+            Some(DataEncoding::Pem(raw_data)) => {
+                CertificateRevocationListDer::pem_slice_iter(raw_data.as_bytes())
+                    .collect::<Result<_, _>>()
+                    .context("rustls/CustomServerCertVerifier: parse crls from PEM content")?
+            }
+        };
+        let roots = Arc::new(roots);
+        let mut builder = WebPkiServerVerifier::builder(roots.clone());
+        if !crls.is_empty() {
+            // certs not covered by any of the given crls are not considered revoked
+            builder = builder.with_crls(crls).allow_unknown_revocation_status();
+        }
+        let inner = builder
+            .build()
+            .context("rustls/CustomServerCertVerifier: build webpki verifier")?;
+        let algorithms = CryptoProvider::get_default()
+            .map(|provider| provider.signature_verification_algorithms)
+            .unwrap_or_else(|| {
+                rustls::crypto::aws_lc_rs::default_provider().signature_verification_algorithms
+            });
+        Ok(Self {
+            inner,
+            roots,
+            verify,
+            algorithms,
+        })
+    }
+}
+
+impl CustomServerCertVerifier {
+    /// Match the pins against, and verify the stapled OCSP response for,
+    /// the certificate path verified for the given certificates.
+    fn verify_path(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<(), rustls::Error> {
+        let webpki_err = |err: webpki::Error| {
+            rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(Arc::new(err))))
+        };
+        let end_entity = webpki::EndEntityCert::try_from(end_entity).map_err(webpki_err)?;
+        // already verified by the inner verifier, this is only done to know the path
+        let path = end_entity
+            .verify_for_usage(
+                self.algorithms.all,
+                &self.roots.roots,
+                intermediates,
+                now,
+                webpki::KeyUsage::server_auth(),
+                None,
+                None,
+            )
+            .map_err(webpki_err)?;
+        let anchor_spki = trust_anchor_spki_der(path.anchor());
+
+        let pins = std::iter::once(&**path.end_entity())
+            .chain(path.intermediate_certificates())
+            .map(|cert| SpkiPin::from_spki_der(&cert.subject_public_key_info()))
+            .chain(std::iter::once(SpkiPin::from_spki_der(&anchor_spki)));
+        self.verify.verify_spki_pins(pins).map_err(|err| {
+            rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(Arc::new(err))))
+        })?;
+
+        let chain: Vec<CertificateDer<'_>> = std::iter::once(&**path.end_entity())
+            .chain(path.intermediate_certificates())
+            .map(|cert| cert.der())
+            .collect();
+        self.verify
+            .verify_ocsp_staple(
+                &chain,
+                &anchor_spki,
+                ocsp_response,
+                SystemTime::UNIX_EPOCH + Duration::from_secs(now.as_secs()),
+                |data| verify_ocsp_signature(&self.algorithms, data),
+            )
+            .map_err(|err| {
+                rustls::Error::InvalidCertificate(match err {
+                    OcspError::Revoked => CertificateError::Revoked,
+                    err => CertificateError::Other(OtherError(Arc::new(err))),
+                })
+            })
+    }
+}
+
+/// Encode the subject public key info of a trust anchor as DER,
+/// given the anchor only holds the content of the SPKI sequence.
+fn trust_anchor_spki_der(anchor: &TrustAnchor<'_>) -> Vec<u8> {
+    const TAG_SEQUENCE: u8 = 0x30;
+
+    let content = anchor.subject_public_key_info.as_ref();
+    let mut der = vec![TAG_SEQUENCE];
+    match content.len() {
+        len @ 0..=0x7f => der.push(len as u8),
+        len => {
+            let len_bytes = len.to_be_bytes();
+            let skip = len_bytes.iter().take_while(|b| **b == 0).count();
+            der.push(0x80 | (len_bytes.len() - skip) as u8);
+            der.extend_from_slice(&len_bytes[skip..]);
+        }
+    }
+    der.extend_from_slice(content);
+    der
+}
+
+impl ServerCertVerifier for CustomServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        if !self.verify.spki_pins.is_empty() || self.verify.require_ocsp_staple {
+            self.verify_path(end_entity, intermediates, ocsp_response, now)?;
+        }
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+tokio::task_local! {
+    static PEER_SIGNATURE_SCHEME: Cell<Option<SignatureScheme>>;
+}
+
+/// Cert verifier wrapping another [`ServerCertVerifier`], recording
+/// the [`SignatureScheme`] of the handshake signature it verified.
+///
+/// The scheme is only recorded for handshakes driven by [`record_signature_scheme`],
+/// as rustls does not expose the negotiated signature scheme on its connections.
+#[derive(Debug)]
+pub(crate) struct SignatureSchemeRecorder(Arc<dyn ServerCertVerifier>);
+
+impl SignatureSchemeRecorder {
+    pub(crate) fn new(verifier: Arc<dyn ServerCertVerifier>) -> Self {
+        Self(verifier)
+    }
+
+    fn record(
+        result: Result<HandshakeSignatureValid, rustls::Error>,
+        scheme: SignatureScheme,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        if result.is_ok() {
+            let _ = PEER_SIGNATURE_SCHEME.try_with(|recorded| recorded.set(Some(scheme)));
+        }
+        result
+    }
+}
+
+impl ServerCertVerifier for SignatureSchemeRecorder {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.0
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Self::record(
+            self.0.verify_tls12_signature(message, cert, dss),
+            dss.scheme,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Self::record(
+            self.0.verify_tls13_signature(message, cert, dss),
+            dss.scheme,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_verify_schemes()
+    }
+
+    fn requires_raw_public_keys(&self) -> bool {
+        self.0.requires_raw_public_keys()
+    }
+
+    fn root_hint_subjects(&self) -> Option<&[rustls::DistinguishedName]> {
+        self.0.root_hint_subjects()
+    }
+}
+
+/// Drive the given handshake, returning its output together with the
+/// [`SignatureScheme`] recorded by a [`SignatureSchemeRecorder`], if any.
+pub(crate) async fn record_signature_scheme<F: Future>(
+    handshake: F,
+) -> (F::Output, Option<SignatureScheme>) {
+    PEER_SIGNATURE_SCHEME
+        .scope(Cell::new(None), async {
+            let output = handshake.await;
+            (output, PEER_SIGNATURE_SCHEME.with(Cell::get))
+        })
+        .await
+}
+
+/// Verify the signature of (a part of) an OCSP response,
+/// using the matching algorithm of the given supported algorithms.
+fn verify_ocsp_signature(
+    algorithms: &WebPkiSupportedAlgorithms,
+    data: &OcspSignedData<'_>,
+) -> bool {
+    algorithms.all.iter().any(|algorithm| {
+        algorithm.public_key_alg_id().as_ref() == data.public_key_algorithm
+            && algorithm.signature_alg_id().as_ref() == data.signature_algorithm
+            && algorithm
+                .verify_signature(data.public_key, data.message, data.signature)
+                .is_ok()
+    })
+}
+
+/// Returns the [`CertificatePinningError`] if the given (handshake) error
+/// was caused by a [`CustomServerCertVerifier`] rejecting the server certificate chain.
+pub(crate) fn certificate_pinning_error(err: &std::io::Error) -> Option<CertificatePinningError> {
+    match err.get_ref()?.downcast_ref::<rustls::Error>()? {
+        rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(err))) => {
+            err.downcast_ref::<CertificatePinningError>().cloned()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dep::rcgen::{self, KeyPair};
+    use crate::dep::rustls::{ClientConfig, ServerConfig};
+    use crate::dep::tokio_rustls::{TlsAcceptor, TlsConnector};
+    use crate::server::OcspStaplingCertResolver;
+    use rama_core::service::service_fn;
+    use rama_http_types::{Body, Request, Response};
+    use rama_net::tls::ocsp::OcspCertId;
+    use rama_net::tls::server::{OcspStapler, ServerAuthData};
+    use rama_utils::str::NonEmptyString;
+    use rustls_pki_types::PrivatePkcs8KeyDer;
+    use std::convert::Infallible;
+
+    struct TestPki {
+        ca: rcgen::Certificate,
+        ca_key: KeyPair,
+        leaf: rcgen::Certificate,
+        leaf_key: KeyPair,
+        ca_pem: String,
+        ca_pin: SpkiPin,
+        leaf_pin: SpkiPin,
+        server_config: Arc<ServerConfig>,
+    }
+
+    fn test_pki() -> TestPki {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let leaf_key = KeyPair::generate().unwrap();
+        let mut leaf_params =
+            rcgen::CertificateParams::new(vec!["example.com".to_owned()]).unwrap();
+        leaf_params.serial_number = Some(42.into());
+        let leaf = leaf_params.signed_by(&leaf_key, &ca, &ca_key).unwrap();
+
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![leaf.der().clone(), ca.der().clone()],
+                PrivatePkcs8KeyDer::from(leaf_key.serialize_der()).into(),
+            )
+            .unwrap();
+
+        TestPki {
+            ca_pem: ca.pem(),
+            ca_pin: SpkiPin::from_spki_der(&ca_key.public_key_der()),
+            leaf_pin: SpkiPin::from_spki_der(&leaf_key.public_key_der()),
+            server_config: Arc::new(server_config),
+            ca,
+            ca_key,
+            leaf,
+            leaf_key,
+        }
+    }
+
+    fn server_config_with_chain(
+        pki: &TestPki,
+        cert_chain: Vec<CertificateDer<'static>>,
+    ) -> Arc<ServerConfig> {
+        Arc::new(
+            ServerConfig::builder()
+                .with_no_client_auth()
+                .with_single_cert(
+                    cert_chain,
+                    PrivatePkcs8KeyDer::from(pki.leaf_key.serialize_der()).into(),
+                )
+                .unwrap(),
+        )
+    }
+
+    async fn handshake(pki: &TestPki, verify: CustomServerVerify) -> Result<(), std::io::Error> {
+        handshake_with_server_config(pki.server_config.clone(), verify).await
+    }
+
+    async fn handshake_with_server_config(
+        server_config: Arc<ServerConfig>,
+        verify: CustomServerVerify,
+    ) -> Result<(), std::io::Error> {
+        let verifier = CustomServerCertVerifier::try_new(verify).unwrap();
+        let client_config = ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+
+        let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+        let acceptor = TlsAcceptor::from(server_config);
+        tokio::spawn(async move {
+            let _ = acceptor.accept(server_stream).await;
+        });
+
+        TlsConnector::from(Arc::new(client_config))
+            .connect(ServerName::try_from("example.com").unwrap(), client_stream)
+            .await
+            .map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn test_custom_server_cert_verifier() {
+        let pki = test_pki();
+        let root_certs = Some(DataEncoding::Pem(
+            NonEmptyString::try_from(pki.ca_pem.clone()).unwrap(),
+        ));
+
+        // unknown CA
+        assert!(
+            handshake(&pki, CustomServerVerify::default())
+                .await
+                .is_err()
+        );
+
+        // trusted custom CA, with and without default roots
+        for replace_default_roots in [false, true] {
+            handshake(
+                &pki,
+                CustomServerVerify {
+                    root_certs: root_certs.clone(),
+                    replace_default_roots,
+                    spki_pins: vec![],
+                    require_ocsp_staple: false,
+                    crls: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        // pinned leaf or intermediate
+        for pin in [pki.leaf_pin, pki.ca_pin] {
+            handshake(
+                &pki,
+                CustomServerVerify {
+                    root_certs: root_certs.clone(),
+                    replace_default_roots: true,
+                    spki_pins: vec![SpkiPin::from_spki_der(b"other"), pin],
+                    require_ocsp_staple: false,
+                    crls: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        // pinned trust anchor, not sent by the server
+        handshake_with_server_config(
+            server_config_with_chain(&pki, vec![pki.leaf.der().clone()]),
+            CustomServerVerify {
+                root_certs: root_certs.clone(),
+                replace_default_roots: true,
+                spki_pins: vec![pki.ca_pin],
+                require_ocsp_staple: false,
+                crls: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        // pinned certificate sent by the server, but not part of the verified path
+        let other_key = KeyPair::generate().unwrap();
+        let other = rcgen::CertificateParams::new(vec![])
+            .unwrap()
+            .self_signed(&other_key)
+            .unwrap();
+        let err = handshake_with_server_config(
+            server_config_with_chain(&pki, vec![pki.leaf.der().clone(), other.der().clone()]),
+            CustomServerVerify {
+                root_certs: root_certs.clone(),
+                replace_default_roots: true,
+                spki_pins: vec![SpkiPin::from_spki_der(&other_key.public_key_der())],
+                require_ocsp_staple: false,
+                crls: None,
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(certificate_pinning_error(&err).is_some());
+
+        // pin mismatch
+        let err = handshake(
+            &pki,
+            CustomServerVerify {
+                root_certs,
+                replace_default_roots: true,
+                spki_pins: vec![SpkiPin::from_spki_der(b"other")],
+                require_ocsp_staple: false,
+                crls: None,
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(certificate_pinning_error(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_signature_scheme_recorder() {
+        let pki = test_pki();
+        let verifier = CustomServerCertVerifier::try_new(CustomServerVerify {
+            root_certs: Some(DataEncoding::Pem(
+                NonEmptyString::try_from(pki.ca_pem.clone()).unwrap(),
+            )),
+            replace_default_roots: true,
+            spki_pins: vec![],
+            require_ocsp_staple: false,
+            crls: None,
+        })
+        .unwrap();
+        let client_config = Arc::new(
+            ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SignatureSchemeRecorder::new(Arc::new(
+                    verifier,
+                ))))
+                .with_no_client_auth(),
+        );
+
+        let (client_stream, server_stream) = tokio::io::duplex(16 * 1024);
+        let acceptor = TlsAcceptor::from(pki.server_config.clone());
+        tokio::spawn(async move {
+            let _ = acceptor.accept(server_stream).await;
+        });
+
+        let (stream, signature_scheme) = record_signature_scheme(
+            TlsConnector::from(client_config)
+                .connect(ServerName::try_from("example.com").unwrap(), client_stream),
+        )
+        .await;
+        stream.unwrap();
+        assert_eq!(
+            signature_scheme,
+            Some(SignatureScheme::ECDSA_NISTP256_SHA256)
+        );
+    }
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut der = vec![tag];
+        match content.len() {
+            len @ 0..=0x7f => der.push(len as u8),
+            len @ 0x80..=0xff => der.extend([0x81, len as u8]),
+            len => der.extend([0x82, (len >> 8) as u8, len as u8]),
+        }
+        der.extend(content);
+        der
+    }
+
+    fn der_content(der: &[u8]) -> &[u8] {
+        match der[1] {
+            0x81 => &der[3..],
+            0x82 => &der[4..],
+            _ => &der[2..],
+        }
+    }
+
+    /// Create an OCSP response for the leaf of the given pki, signed by its CA.
+    fn ocsp_response(pki: &TestPki, cert_status: &[u8]) -> Vec<u8> {
+        // the CertID is the only content of the (nested) OCSP request
+        let request = OcspCertId::try_new(pki.leaf.der(), pki.ca.der())
+            .unwrap()
+            .to_ocsp_request_der()
+            .unwrap();
+        let cert_id = der_content(der_content(der_content(der_content(&request))));
+
+        let single = der(
+            0x30,
+            &[
+                cert_id,
+                cert_status,
+                &der(0x18, b"20240101000000Z"),
+                &der(0xa0, &der(0x18, b"20340101000000Z")),
+            ]
+            .concat(),
+        );
+        let tbs_response_data = der(
+            0x30,
+            &[
+                der(0xa2, &der(0x04, &[1, 2, 3, 4])),
+                der(0x18, b"20240101000000Z"),
+                der(0x30, &single),
+            ]
+            .concat(),
+        );
+        let ca_key = PrivatePkcs8KeyDer::from(pki.ca_key.serialize_der()).into();
+        let signature = rustls::crypto::aws_lc_rs::sign::any_ecdsa_type(&ca_key)
+            .unwrap()
+            .choose_scheme(&[SignatureScheme::ECDSA_NISTP256_SHA256])
+            .unwrap()
+            .sign(&tbs_response_data)
+            .unwrap();
+        let basic = der(
+            0x30,
+            &[
+                tbs_response_data,
+                // ecdsa-with-SHA256
+                der(
+                    0x30,
+                    &der(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02]),
+                ),
+                der(0x03, &[&[0][..], &signature].concat()),
+            ]
+            .concat(),
+        );
+        let response_bytes = der(
+            0x30,
+            &[
+                // id-pkix-ocsp-basic
+                der(
+                    0x06,
+                    &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01],
+                ),
+                der(0x04, &basic),
+            ]
+            .concat(),
+        );
+        der(
+            0x30,
+            &[der(0x0a, &[0]), der(0xa0, &response_bytes)].concat(),
+        )
+    }
+
+    /// Create a server config stapling the OCSP response fetched by an [`OcspStapler`]
+    /// from a local responder stand-in returning the given response.
+    async fn stapling_server_config(pki: &TestPki, response: Vec<u8>) -> Arc<ServerConfig> {
+        let stapler = OcspStapler::try_new_with_responder(
+            ServerAuthData {
+                private_key: DataEncoding::Der(pki.leaf_key.serialize_der()),
+                cert_chain: DataEncoding::DerStack(vec![
+                    pki.leaf.der().to_vec(),
+                    pki.ca.der().to_vec(),
+                ]),
+                ocsp: None,
+            },
+            "http://ocsp.example.com".parse().unwrap(),
+        )
+        .unwrap();
+        let responder = service_fn(move |_: Request| {
+            let response = response.clone();
+            async move { Ok::<_, Infallible>(Response::new(Body::from(response))) }
+        });
+        stapler.refresh(&responder).await.unwrap();
+
+        let resolver = OcspStaplingCertResolver::try_new(stapler).unwrap();
+        Arc::new(
+            ServerConfig::builder()
+                .with_no_client_auth()
+                .with_cert_resolver(Arc::new(resolver)),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_custom_server_cert_verifier_ocsp_staple() {
+        let pki = test_pki();
+        let verify = CustomServerVerify {
+            root_certs: Some(DataEncoding::Pem(
+                NonEmptyString::try_from(pki.ca_pem.clone()).unwrap(),
+            )),
+            replace_default_roots: true,
+            spki_pins: vec![],
+            require_ocsp_staple: true,
+            crls: None,
+        };
+
+        // valid staple
+        let server_config =
+            stapling_server_config(&pki, ocsp_response(&pki, &der(0x80, &[]))).await;
+        handshake_with_server_config(server_config, verify.clone())
+            .await
+            .unwrap();
+
+        // no staple
+        assert!(handshake(&pki, verify.clone()).await.is_err());
+
+        // leaf issued by the trust anchor, which is not sent by the server
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert_with_ocsp(
+                vec![pki.leaf.der().clone()],
+                PrivatePkcs8KeyDer::from(pki.leaf_key.serialize_der()).into(),
+                ocsp_response(&pki, &der(0x80, &[])),
+            )
+            .unwrap();
+        handshake_with_server_config(Arc::new(server_config), verify.clone())
+            .await
+            .unwrap();
+
+        // revoked
+        let server_config = stapling_server_config(
+            &pki,
+            ocsp_response(&pki, &der(0xa1, &der(0x18, b"20250101000000Z"))),
+        )
+        .await;
+        let err = handshake_with_server_config(server_config, verify)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.get_ref().unwrap().downcast_ref::<rustls::Error>(),
+            Some(rustls::Error::InvalidCertificate(CertificateError::Revoked))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_custom_server_cert_verifier_crls() {
+        let pki = test_pki();
+        let crl = |serial_number: u64| {
+            let crl = rcgen::CertificateRevocationListParams {
+                this_update: rcgen::date_time_ymd(2024, 1, 1),
+                next_update: rcgen::date_time_ymd(2034, 1, 1),
+                crl_number: 1.into(),
+                issuing_distribution_point: None,
+                revoked_certs: vec![rcgen::RevokedCertParams {
+                    serial_number: serial_number.into(),
+                    revocation_time: rcgen::date_time_ymd(2025, 1, 1),
+                    reason_code: None,
+                    invalidity_date: None,
+                }],
+                key_identifier_method: rcgen::KeyIdMethod::Sha256,
+            }
+            .signed_by(&pki.ca, &pki.ca_key)
+            .unwrap();
+            CustomServerVerify {
+                root_certs: Some(DataEncoding::Pem(
+                    NonEmptyString::try_from(pki.ca_pem.clone()).unwrap(),
+                )),
+                replace_default_roots: true,
+                spki_pins: vec![],
+                require_ocsp_staple: false,
+                crls: Some(DataEncoding::Pem(
+                    NonEmptyString::try_from(crl.pem().unwrap()).unwrap(),
+                )),
+            }
+        };
+
+        handshake(&pki, crl(7)).await.unwrap();
+
+        let err = handshake(&pki, crl(42)).await.unwrap_err();
+        assert!(matches!(
+            err.get_ref().unwrap().downcast_ref::<rustls::Error>(),
+            Some(rustls::Error::InvalidCertificate(CertificateError::Revoked))
+        ));
+    }
+}
@@ -1,12 +1,14 @@
 use private::{ConnectorKindAuto, ConnectorKindSecure, ConnectorKindTunnel};
+use rama_boring::ssl::NameType;
+use rama_boring::x509::X509VerifyError;
 use rama_boring_tokio::SslStream;
 use rama_core::error::{BoxError, ErrorExt, OpaqueError};
 use rama_core::{Context, Layer, Service};
 use rama_net::address::Host;
 use rama_net::client::{ConnectorService, EstablishedClientConnection};
 use rama_net::stream::Stream;
-use rama_net::tls::ApplicationProtocol;
-use rama_net::tls::client::NegotiatedTlsParameters;
+use rama_net::tls::client::{CertificatePinningError, NegotiatedTlsParameters};
+use rama_net::tls::{ApplicationProtocol, CertificateInfo, cipher_suite_from_openssl_cipher_str};
 use rama_net::transport::TryRefIntoTransportContext;
 use std::fmt;
 
@@ -386,22 +388,37 @@ pub async fn tls_connect<T>(
 where
     T: Stream + Unpin,
 {
-    let client_config_data = match connector_data {
-        Some(connector_data) => connector_data.try_to_build_config()?,
-        None => TlsConnectorData::new()?.try_to_build_config()?,
+    let default_connector_data;
+    let connector_data = match connector_data {
+        Some(connector_data) => connector_data,
+        None => {
+            default_connector_data = TlsConnectorData::new()?;
+            &default_connector_data
+        }
     };
+    let mut client_config_data = connector_data.try_to_build_config()?;
     let server_host = client_config_data.server_name.unwrap_or(server_host);
+    connector_data.prepare_session_resumption(&mut client_config_data.config, &server_host)?;
     let stream = rama_boring_tokio::connect(
         client_config_data.config,
         server_host.to_string().as_str(),
         stream,
     )
     .await
-    .map_err(|err| match err.as_io_error() {
-        Some(err) => OpaqueError::from_display(err.to_string())
-            .context("boring ssl connector: connect")
-            .into_boxed(),
-        None => OpaqueError::from_display("boring ssl connector: connect").into_boxed(),
+    .map_err(|err| {
+        if err.ssl().map(|ssl| ssl.verify_result())
+            == Some(Err(X509VerifyError::APPLICATION_VERIFICATION))
+        {
+            return OpaqueError::from_std(CertificatePinningError::new())
+                .context("boring ssl connector: connect")
+                .into_boxed();
+        }
+        match err.as_io_error() {
+            Some(err) => OpaqueError::from_display(err.to_string())
+                .context("boring ssl connector: connect")
+                .into_boxed(),
+            None => OpaqueError::from_display("boring ssl connector: connect").into_boxed(),
+        }
     })?;
     Ok(TlsStream::new(stream))
 }
@@ -448,7 +465,27 @@ impl<S, K> TlsConnector<S, K> {
                 NegotiatedTlsParameters {
                     protocol_version,
                     application_layer_protocol,
+                    cipher_suite: stream
+                        .ssl()
+                        .current_cipher()
+                        .and_then(|cipher| cipher_suite_from_openssl_cipher_str(cipher.name())),
+                    key_exchange_group: stream
+                        .ssl()
+                        .curve()
+                        .and_then(|curve| curve.try_into().ok()),
+                    signature_scheme: crate::boring::peer_signature_scheme(stream.ssl()),
+                    server_name: stream
+                        .ssl()
+                        .servername(NameType::HOST_NAME)
+                        .and_then(|name| Host::try_from(name).ok()),
                     peer_certificate_chain: server_certificate_chain,
+                    peer_certificate: stream
+                        .ssl()
+                        .peer_certificate()
+                        .and_then(|cert| cert.to_der().ok())
+                        .and_then(|cert| CertificateInfo::try_from_der(&cert).ok()),
+                    session_resumed: stream.ssl().session_reused(),
+                    ech_accepted: stream.ssl().ech_accepted(),
                 }
             }
             None => {
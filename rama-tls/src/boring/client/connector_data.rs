@@ -2,25 +2,40 @@ use itertools::Itertools;
 use rama_boring::{
     asn1::Asn1Time,
     bn::{BigNum, MsbOption},
+    ex_data::Index,
     hash::MessageDigest,
-    pkey::{PKey, Private},
+    pkey::{Id, PKey, Private},
     rsa::Rsa,
-    ssl::{ConnectConfiguration, SslCurve, SslSignatureAlgorithm, SslVerifyMode, SslVersion},
+    sign::Verifier,
+    ssl::{
+        ConnectConfiguration, Ssl, SslCurve, SslSession, SslSessionCacheMode,
+        SslSignatureAlgorithm, SslVerifyMode, SslVersion,
+    },
     x509::{
-        X509,
+        X509, X509StoreContext, X509VerifyError,
         extension::{BasicConstraints, KeyUsage, SubjectKeyIdentifier},
+        store::X509StoreBuilder,
     },
 };
 use rama_core::error::{ErrorContext, ErrorExt, OpaqueError};
 use rama_net::tls::{
-    ApplicationProtocol, CertificateCompressionAlgorithm, ExtensionId, KeyLogIntent,
+    ApplicationProtocol, CertificateCompressionAlgorithm, EchConfigList, ExtensionId, KeyLogIntent,
+    SignatureScheme, ocsp::OcspSignedData,
 };
 use rama_net::tls::{
     DataEncoding,
-    client::{ClientAuth, ClientHelloExtension},
+    client::{
+        ClientAuth, ClientHelloExtension, CustomServerVerify, SpkiPin, TlsSession, TlsSessionKey,
+        TlsSessionStore,
+    },
 };
 use rama_net::{address::Host, tls::client::ServerVerifyMode};
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, OnceLock},
+    time::SystemTime,
+};
 use tracing::{debug, trace};
 
 #[cfg(feature = "compression")]
@@ -37,6 +52,7 @@ use crate::keylog::new_key_log_file_handle;
 pub struct TlsConnectorData {
     pub(super) connect_config_input: Arc<ConnectConfigurationInput>,
     pub(super) server_name: Option<Host>,
+    pub(super) session_store: Option<Arc<dyn TlsSessionStore>>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -49,7 +65,7 @@ pub(super) struct ConnectConfigurationInput {
     pub(super) min_ssl_version: Option<SslVersion>,
     pub(super) max_ssl_version: Option<SslVersion>,
     pub(super) verify_algorithm_prefs: Option<Vec<SslSignatureAlgorithm>>,
-    pub(super) server_verify_mode: Option<ServerVerifyMode>,
+    pub(super) server_verify_mode: Option<ConnectorConfigServerVerifyMode>,
     pub(super) client_auth: Option<ConnectorConfigClientAuth>,
     pub(super) store_server_certificate_chain: bool,
     pub(super) grease_enabled: bool,
@@ -59,6 +75,8 @@ pub(super) struct ConnectConfigurationInput {
     pub(super) record_size_limit: Option<u16>,
     pub(super) delegated_credential_schemes: Option<Vec<SslSignatureAlgorithm>>,
     pub(super) encrypted_client_hello: Option<bool>,
+    pub(super) ech_config_list: Option<EchConfigList>,
+    pub(super) session_config_id: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -67,6 +85,17 @@ pub(super) struct ConnectorConfigClientAuth {
     pub(super) private_key: PKey<Private>,
 }
 
+#[derive(Debug, Clone)]
+pub(super) enum ConnectorConfigServerVerifyMode {
+    Auto,
+    Disable,
+    Custom {
+        root_certs: Vec<X509>,
+        replace_default_roots: bool,
+        verify: Arc<CustomServerVerify>,
+    },
+}
+
 pub(super) struct ConnectConfigData {
     pub(super) config: ConnectConfiguration,
     pub(super) server_name: Option<Host>,
@@ -195,18 +224,109 @@ impl TlsConnectorData {
             }
         }
 
-        match self
-            .connect_config_input
-            .server_verify_mode
-            .unwrap_or_default()
-        {
-            ServerVerifyMode::Auto => {
+        match self.connect_config_input.server_verify_mode.as_ref() {
+            None | Some(ConnectorConfigServerVerifyMode::Auto) => {
                 trace!("boring connector: server verify mode: auto (default verifier)");
             } // nothing explicit to do
-            ServerVerifyMode::Disable => {
+            Some(ConnectorConfigServerVerifyMode::Disable) => {
                 trace!("boring connector: server verify mode: disable");
                 cfg_builder.set_custom_verify_callback(SslVerifyMode::NONE, |_| Ok(()));
             }
+            Some(ConnectorConfigServerVerifyMode::Custom {
+                root_certs,
+                replace_default_roots,
+                verify,
+            }) => {
+                trace!(
+                    "boring connector: server verify mode: custom: {} root cert(s) (replace default roots: {replace_default_roots}), {} spki pin(s), require ocsp staple: {}",
+                    root_certs.len(),
+                    verify.spki_pins.len(),
+                    verify.require_ocsp_staple,
+                );
+                if *replace_default_roots {
+                    let mut store = X509StoreBuilder::new()
+                        .context("build (boring) ssl connector: create root cert store")?;
+                    for cert in root_certs {
+                        store
+                            .add_cert(cert.clone())
+                            .context("build (boring) ssl connector: add root cert")?;
+                    }
+                    cfg_builder.set_cert_store(store.build());
+                } else {
+                    for cert in root_certs {
+                        cfg_builder
+                            .cert_store_mut()
+                            .add_cert(cert.clone())
+                            .context("build (boring) ssl connector: add root cert")?;
+                    }
+                }
+
+                if verify.require_ocsp_staple {
+                    cfg_builder.enable_ocsp_stapling();
+                }
+
+                if !verify.spki_pins.is_empty() || verify.require_ocsp_staple {
+                    let verify = verify.clone();
+                    cfg_builder.set_verify_callback(
+                        SslVerifyMode::PEER,
+                        move |preverify_ok, x509_ctx| {
+                            // pins and ocsp staple are checked once, for the leaf, after the chain is verified
+                            if !preverify_ok || x509_ctx.error_depth() != 0 {
+                                return preverify_ok;
+                            }
+                            // verified chain, leaf first, up to and including the trust anchor
+                            let chain: Vec<(Vec<u8>, Vec<u8>)> = x509_ctx
+                                .chain()
+                                .into_iter()
+                                .flat_map(|chain| chain.iter())
+                                .filter_map(|cert| {
+                                    let spki = cert
+                                        .public_key()
+                                        .and_then(|key| key.public_key_to_der())
+                                        .ok()?;
+                                    Some((cert.to_der().ok()?, spki))
+                                })
+                                .collect();
+
+                            let pins = chain.iter().map(|(_, spki)| SpkiPin::from_spki_der(spki));
+                            if let Err(err) = verify.verify_spki_pins(pins) {
+                                debug!("boring connector: server verify: {err}");
+                                x509_ctx.set_error(Err(X509VerifyError::APPLICATION_VERIFICATION));
+                                return false;
+                            }
+
+                            if verify.require_ocsp_staple {
+                                let ocsp_response = X509StoreContext::ssl_idx()
+                                    .ok()
+                                    .and_then(|idx| x509_ctx.ex_data(idx))
+                                    .and_then(|ssl| ssl.ocsp_status())
+                                    .unwrap_or_default();
+                                let Some(((_, anchor_spki), path)) = chain.split_last() else {
+                                    x509_ctx
+                                        .set_error(Err(X509VerifyError::APPLICATION_VERIFICATION));
+                                    return false;
+                                };
+                                let path: Vec<&[u8]> =
+                                    path.iter().map(|(cert, _)| cert.as_slice()).collect();
+                                if let Err(err) = verify.verify_ocsp_staple(
+                                    &path,
+                                    anchor_spki,
+                                    ocsp_response,
+                                    SystemTime::now(),
+                                    verify_ocsp_signature,
+                                ) {
+                                    debug!("boring connector: server verify: {err}");
+                                    x509_ctx
+                                        .set_error(Err(X509VerifyError::APPLICATION_VERIFICATION));
+                                    return false;
+                                }
+                            }
+
+                            true
+                        },
+                    );
+                }
+            }
         }
 
         if let Some(auth) = self.connect_config_input.client_auth.as_ref() {
@@ -235,6 +355,18 @@ impl TlsConnectorData {
             }
         }
 
+        if let Some(session_store) = self.session_store.clone() {
+            trace!("boring connector: enable client session cache");
+            cfg_builder.set_session_cache_mode(SslSessionCacheMode::CLIENT);
+            let key_index = session_key_index()?;
+            cfg_builder.set_new_session_callback(move |ssl, session| {
+                if let Some(key) = ssl.ex_data(key_index) {
+                    trace!("boring connector: store new session for {}", key.server);
+                    session_store.insert(key.clone(), TlsSession::new(session));
+                }
+            });
+        }
+
         trace!("boring connector: build SSL connector config");
         let mut cfg = cfg_builder
             .build()
@@ -255,7 +387,11 @@ impl TlsConnectorData {
             cfg.set_delegated_credential_schemes(schemes).unwrap();
         }
 
-        if self
+        if let Some(ech_config_list) = self.connect_config_input.ech_config_list.as_ref() {
+            trace!("boring connector: set ech config list");
+            cfg.set_ech_config_list(ech_config_list.as_bytes())
+                .context("build (boring) ssl connector: set ech config list")?;
+        } else if self
             .connect_config_input
             .encrypted_client_hello
             .unwrap_or_default()
@@ -274,6 +410,61 @@ impl TlsConnectorData {
         })
     }
 
+    /// Prepare the given [`ConnectConfiguration`] to resume a previous session
+    /// with the given server, if any, and to store the new session(s) it receives.
+    ///
+    /// Nothing is done in case no [`TlsSessionStore`] is configured.
+    pub(super) fn prepare_session_resumption(
+        &self,
+        cfg: &mut ConnectConfiguration,
+        server_host: &Host,
+    ) -> Result<(), OpaqueError> {
+        let Some(session_store) = self.session_store.as_ref() else {
+            return Ok(());
+        };
+        let key = TlsSessionKey::new(
+            server_host.clone(),
+            self.connect_config_input.session_config_id,
+        );
+        if let Some(session) = session_store
+            .get(&key)
+            .and_then(|session| session.downcast_ref::<SslSession>().cloned())
+        {
+            trace!("boring connector: resume session for {server_host}");
+            // SAFETY: stored sessions are keyed by the identity of the connect config input,
+            // and all ssl contexts for the same input are created identically.
+            unsafe { cfg.set_session(&session) }
+                .context("build (boring) ssl connector: set session")?;
+            if session.protocol_version() == SslVersion::TLS1_3 {
+                // TLS 1.3 tickets are meant to be used only once,
+                // new tickets are received (and stored) for each connection
+                session_store.remove(&key);
+            }
+        }
+
+        cfg.set_ex_data(session_key_index()?, key);
+        Ok(())
+    }
+
+    /// Set the [`TlsSessionStore`] used to resume sessions across connections.
+    ///
+    /// Sessions are not resumed by default. Once set, sessions are resumed by connections
+    /// using an identical config, regardless of the (proxy) route used to connect,
+    /// e.g. use a store per egress route in case sessions should not be resumed across routes.
+    /// The [`default_tls_session_store`] can be used to share sessions process-wide.
+    ///
+    /// [`default_tls_session_store`]: rama_net::tls::client::default_tls_session_store
+    pub fn set_session_store(&mut self, store: Arc<dyn TlsSessionStore>) -> &mut Self {
+        self.session_store = Some(store);
+        self
+    }
+
+    /// Same as [`Self::set_session_store`] but consuming self.
+    pub fn with_session_store(mut self, store: Arc<dyn TlsSessionStore>) -> Self {
+        self.session_store = Some(store);
+        self
+    }
+
     /// Merge `self` together with the `other`, resulting in
     /// a new [`TlsConnectorData`], where any defined properties of `other`
     /// take priority over conflicting ones in `self`.
@@ -335,7 +526,8 @@ impl TlsConnectorData {
                 server_verify_mode: other
                     .connect_config_input
                     .server_verify_mode
-                    .or_else(|| self.connect_config_input.server_verify_mode),
+                    .clone()
+                    .or_else(|| self.connect_config_input.server_verify_mode.clone()),
                 client_auth: other
                     .connect_config_input
                     .client_auth
@@ -387,11 +579,24 @@ impl TlsConnectorData {
                     .connect_config_input
                     .encrypted_client_hello
                     .or(self.connect_config_input.encrypted_client_hello),
+                ech_config_list: other
+                    .connect_config_input
+                    .ech_config_list
+                    .clone()
+                    .or_else(|| self.connect_config_input.ech_config_list.clone()),
+                session_config_id: session_config_id((
+                    self.connect_config_input.session_config_id,
+                    other.connect_config_input.session_config_id,
+                )),
             }),
             server_name: other
                 .server_name
                 .clone()
                 .or_else(|| self.server_name.clone()),
+            session_store: other
+                .session_store
+                .clone()
+                .or_else(|| self.session_store.clone()),
         }
     }
 }
@@ -404,8 +609,12 @@ impl TlsConnectorData {
     /// you may want to use another constructor instead.
     pub fn new() -> Result<TlsConnectorData, OpaqueError> {
         Ok(TlsConnectorData {
-            connect_config_input: Arc::new(ConnectConfigurationInput::default()),
+            connect_config_input: Arc::new(ConnectConfigurationInput {
+                session_config_id: session_config_id("new"),
+                ..Default::default()
+            }),
             server_name: None,
+            session_store: None,
         })
     }
 
@@ -421,9 +630,11 @@ impl TlsConnectorData {
         Ok(TlsConnectorData {
             connect_config_input: Arc::new(ConnectConfigurationInput {
                 alpn_protos: Some(alpn_protos),
+                session_config_id: session_config_id("new_http_auto"),
                 ..Default::default()
             }),
             server_name: None,
+            session_store: None,
         })
     }
 
@@ -437,9 +648,11 @@ impl TlsConnectorData {
         Ok(TlsConnectorData {
             connect_config_input: Arc::new(ConnectConfigurationInput {
                 alpn_protos: Some(alpn_protos),
+                session_config_id: session_config_id("new_http_1"),
                 ..Default::default()
             }),
             server_name: None,
+            session_store: None,
         })
     }
 
@@ -453,9 +666,11 @@ impl TlsConnectorData {
         Ok(TlsConnectorData {
             connect_config_input: Arc::new(ConnectConfigurationInput {
                 alpn_protos: Some(alpn_protos),
+                session_config_id: session_config_id("new_http_2"),
                 ..Default::default()
             }),
             server_name: None,
+            session_store: None,
         })
     }
 }
@@ -499,12 +714,16 @@ impl TlsConnectorData {
         let mut record_size_limit = None;
         let mut delegated_credential_schemes = None;
         let mut encrypted_client_hello = None;
+        let mut ech_config_list = None;
+        let mut session_hasher = DefaultHasher::new();
 
         for cfg in cfg_it {
+            cfg.hash(&mut session_hasher);
             cipher_suites = cfg.cipher_suites.as_ref().or(cipher_suites);
             keylog_intent = cfg.key_logger.as_ref().or(keylog_intent);
             client_auth = cfg.client_auth.as_ref().or(client_auth);
-            server_verify_mode = cfg.server_verify_mode.or(server_verify_mode);
+            server_verify_mode = cfg.server_verify_mode.as_ref().or(server_verify_mode);
+            ech_config_list = cfg.ech_config_list.as_ref().or(ech_config_list);
             store_server_certificate_chain =
                 store_server_certificate_chain || cfg.store_server_certificate_chain;
 
@@ -719,6 +938,44 @@ impl TlsConnectorData {
             cipher_list
         );
 
+        let server_verify_mode = match server_verify_mode {
+            None => None,
+            Some(ServerVerifyMode::Auto) => Some(ConnectorConfigServerVerifyMode::Auto),
+            Some(ServerVerifyMode::Disable) => Some(ConnectorConfigServerVerifyMode::Disable),
+            Some(ServerVerifyMode::Custom(verify)) => {
+                let root_certs = match verify.root_certs.as_ref() {
+                    None => Vec::new(),
+                    Some(DataEncoding::Der(raw_data)) => {
+                        vec![X509::from_der(&raw_data[..]).context(
+                            "boring/TlsConnectorData: parse x509 root cert from DER content",
+                        )?]
+                    }
+                    Some(DataEncoding::DerStack(raw_data_list)) => raw_data_list
+                        .iter()
+                        .map(|raw_data| {
+                            X509::from_der(&raw_data[..]).context(
+                                "boring/TlsConnectorData: parse x509 root cert from DER content",
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    Some(DataEncoding::Pem(raw_data)) => X509::stack_from_pem(raw_data.as_bytes())
+                        .context(
+                            "boring/TlsConnectorData: parse x509 root certs from PEM content",
+                        )?,
+                };
+                if verify.crls.is_some() {
+                    return Err(OpaqueError::from_display(
+                        "boring/TlsConnectorData: certificate revocation lists (crls) are not supported",
+                    ));
+                }
+                Some(ConnectorConfigServerVerifyMode::Custom {
+                    root_certs,
+                    replace_default_roots: verify.replace_default_roots,
+                    verify: Arc::new(verify.clone()),
+                })
+            }
+        };
+
         let client_auth = match client_auth.cloned() {
             None => None,
             Some(ClientAuth::SelfSigned) => {
@@ -792,8 +1049,11 @@ impl TlsConnectorData {
                 delegated_credential_schemes,
                 record_size_limit,
                 encrypted_client_hello,
+                ech_config_list: ech_config_list.cloned(),
+                session_config_id: session_hasher.finish(),
             }),
             server_name,
+            session_store: None,
         })
     }
 }
@@ -806,6 +1066,25 @@ impl TryFrom<rama_net::tls::client::ClientConfig> for TlsConnectorData {
     }
 }
 
+/// Compute the identity of a connect configuration,
+/// used to only resume sessions established using the same configuration.
+fn session_config_id(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Index of the [`TlsSessionKey`] stored in the [`Ssl`] ex data,
+/// used to store the new sessions received from the server.
+fn session_key_index() -> Result<Index<Ssl, TlsSessionKey>, OpaqueError> {
+    static INDEX: OnceLock<Index<Ssl, TlsSessionKey>> = OnceLock::new();
+    if let Some(index) = INDEX.get() {
+        return Ok(*index);
+    }
+    let index = Ssl::new_ex_index().context("create (boring) ssl session key ex data index")?;
+    Ok(*INDEX.get_or_init(|| index))
+}
+
 fn self_signed_client_auth() -> Result<(Vec<X509>, PKey<Private>), OpaqueError> {
     let rsa = Rsa::generate(4096).context("generate 4096 RSA key")?;
     let privkey = PKey::from_rsa(rsa).context("create private key from 4096 RSA key")?;
@@ -874,3 +1153,36 @@ fn self_signed_client_auth() -> Result<(Vec<X509>, PKey<Private>), OpaqueError>
 
     Ok((vec![cert], privkey))
 }
+
+/// Verify the signature of (a part of) an OCSP response stapled by the server.
+fn verify_ocsp_signature(data: &OcspSignedData<'_>) -> bool {
+    let digest = match data.signature_scheme {
+        Some(SignatureScheme::RSA_PKCS1_SHA1 | SignatureScheme::ECDSA_SHA1_Legacy) => {
+            Some(MessageDigest::sha1())
+        }
+        Some(SignatureScheme::RSA_PKCS1_SHA256 | SignatureScheme::ECDSA_NISTP256_SHA256) => {
+            Some(MessageDigest::sha256())
+        }
+        Some(SignatureScheme::RSA_PKCS1_SHA384 | SignatureScheme::ECDSA_NISTP384_SHA384) => {
+            Some(MessageDigest::sha384())
+        }
+        Some(SignatureScheme::RSA_PKCS1_SHA512 | SignatureScheme::ECDSA_NISTP521_SHA512) => {
+            Some(MessageDigest::sha512())
+        }
+        Some(SignatureScheme::ED25519) => None,
+        _ => return false,
+    };
+    let Ok(key) = PKey::public_key_from_der(data.subject_public_key_info) else {
+        return false;
+    };
+    let verified = match digest {
+        Some(digest) => Verifier::new(digest, &key).and_then(|mut verifier| {
+            verifier.update(data.message)?;
+            verifier.verify(data.signature)
+        }),
+        None if key.id() == Id::ED25519 => Verifier::new_without_digest(&key)
+            .and_then(|mut verifier| verifier.verify_oneshot(data.signature, data.message)),
+        None => return false,
+    };
+    verified.unwrap_or_default()
+}
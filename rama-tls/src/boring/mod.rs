@@ -1,8 +1,19 @@
 //! boring based TLS support for rama.
 
+use foreign_types::ForeignTypeRef;
+use rama_boring::ssl::SslRef;
+use rama_net::tls::SignatureScheme;
+
 pub mod client;
 pub mod server;
 
+/// The [`SignatureScheme`] used by the peer to sign the handshake, if any.
+fn peer_signature_scheme(ssl: &SslRef) -> Option<SignatureScheme> {
+    // SAFETY: the pointer is valid for the lifetime of the borrowed ssl ref
+    let sigalg = unsafe { rama_boring_sys::SSL_get_peer_signature_algorithm(ssl.as_ptr()) };
+    (sigalg != 0).then(|| sigalg.into())
+}
+
 pub mod dep {
     //! Dependencies for rama boring modules.
     //!
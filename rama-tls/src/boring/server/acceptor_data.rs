@@ -1,6 +1,7 @@
 use crate::boring::dep::boring::{
     asn1::Asn1Time,
     bn::{BigNum, MsbOption},
+    ec::{EcGroup, EcKey},
     hash::MessageDigest,
     nid::Nid,
     pkey::{PKey, Private},
@@ -13,7 +14,8 @@ use crate::boring::dep::boring::{
 use moka::sync::Cache;
 use parking_lot::Mutex;
 use rama_boring::{
-    ssl::{ClientHello, NameType, SelectCertError, SslAcceptorBuilder, SslRef},
+    hpke::HpkeKey,
+    ssl::{ClientHello, NameType, SelectCertError, SslAcceptorBuilder, SslEchKeys, SslRef},
     x509::extension::{AuthorityKeyIdentifier, SubjectAlternativeName},
 };
 use rama_boring_tokio::{AsyncSelectCertError, BoxSelectCertFinish};
@@ -21,15 +23,27 @@ use rama_core::error::{ErrorContext, OpaqueError};
 use rama_net::{
     address::{Domain, Host},
     tls::{
-        ApplicationProtocol, DataEncoding, KeyLogIntent, ProtocolVersion,
+        ApplicationProtocol, CertificateFingerprint, CertificateInfo, DataEncoding, EchKeySet,
+        KeyEncapsulationMechanism, KeyLogIntent, ProtocolVersion, SubjectAltName,
         client::ClientHello as RamaClientHello,
         server::{
             CacheKind, ClientVerifyMode, DynamicIssuer, SelfSignedData, ServerAuth, ServerAuthData,
-            ServerCertIssuerKind,
+            ServerCertCacheKey, ServerCertIssuerKind, ServerCertKeyType, SharedServerCertCache,
+            SniMap, UpstreamFetcher,
         },
     },
 };
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+const ONE_DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Validity of issued certs, unless configured otherwise
+const DEFAULT_ISSUED_CERT_VALIDITY: Duration = Duration::from_secs(60 * 60 * 24 * 90);
+/// Key type of issued certs, unless configured otherwise
+const DEFAULT_ISSUED_CERT_KEY_TYPE: ServerCertKeyType = ServerCertKeyType::Rsa4096;
 
 #[derive(Debug, Clone)]
 /// Internal data used as configuration/input for the [`super::TlsAcceptorService`].
@@ -53,6 +67,8 @@ pub(super) struct TlsConfig {
     pub(super) client_cert_chain: Option<Vec<X509>>,
     /// store client certificate chain if true and client provided this
     pub store_client_certificate_chain: bool,
+    /// optionally define the keys used to decrypt Encrypted Client Hello (ECH)
+    pub(super) ech_keys: Option<EchKeySet>,
 }
 
 #[derive(Debug, Clone)]
@@ -63,18 +79,42 @@ pub(super) struct TlsCertSource {
 #[derive(Debug, Clone)]
 enum TlsCertSourceKind {
     InMemory(IssuedCert),
+    /// Certs selected based on the server name (SNI) of the client
+    Sni(Arc<SniMap<IssuedCert>>),
     InMemoryIssuer {
         /// Cache for certs already issued
-        cert_cache: Option<Cache<Host, IssuedCert>>,
+        cert_cache: Option<IssuedCertCache>,
         /// Private Key for issueing
         ca_key: PKey<Private>,
         /// CA Cert to be used for issueing
         ca_cert: X509,
+        /// Chain of the CA itself, appended to the chain of issued certs
+        ca_chain: Vec<X509>,
+        /// Validity of the issued certs
+        validity: Duration,
+        /// Type of key pair generated for the issued certs
+        key_type: ServerCertKeyType,
+        /// Fetcher of the upstream cert to mirror the subject alt names of
+        upstream: Option<UpstreamFetcher>,
     },
     DynamicIssuer {
         issuer: DynamicIssuer,
         /// Cache for certs already issued
-        cert_cache: Option<Cache<Host, IssuedCert>>,
+        cert_cache: Option<IssuedCertCache>,
+    },
+    MirrorUpstream {
+        /// Fetcher of the upstream cert to mirror
+        upstream: UpstreamFetcher,
+        /// Cache for certs already issued
+        cert_cache: Option<IssuedCertCache>,
+        /// Private Key for issueing
+        ca_key: PKey<Private>,
+        /// CA Cert to be used for issueing
+        ca_cert: X509,
+        /// Chain of the CA itself, appended to the chain of issued certs
+        ca_chain: Vec<X509>,
+        /// Type of key pair generated for the issued certs
+        key_type: ServerCertKeyType,
     },
 }
 
@@ -82,6 +122,109 @@ enum TlsCertSourceKind {
 struct IssuedCert {
     cert_chain: Vec<X509>,
     key: PKey<Private>,
+    /// DER-encoded OCSP response stapled for the leaf cert
+    ocsp: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+/// Cache for certs already issued, as configured by [`CacheKind`].
+enum IssuedCertCache {
+    Mem(Cache<Host, IssuedCert>),
+    Custom {
+        cache: SharedServerCertCache,
+        issuer_fingerprint: Option<CertificateFingerprint>,
+    },
+}
+
+impl IssuedCertCache {
+    fn try_new(
+        kind: CacheKind,
+        ca_cert: Option<&X509>,
+        validity: Duration,
+    ) -> Result<Option<Self>, OpaqueError> {
+        Ok(match kind {
+            CacheKind::Disabled => None,
+            CacheKind::MemCache { max_size } => Some(Self::Mem(
+                Cache::builder()
+                    // expire cached certs (at most) a day before they are no longer valid
+                    .time_to_live(validity.saturating_sub(ONE_DAY.min(validity / 2)))
+                    .max_capacity(max_size.into())
+                    .build(),
+            )),
+            CacheKind::Custom(cache) => Some(Self::Custom {
+                cache,
+                issuer_fingerprint: match ca_cert {
+                    Some(ca_cert) => Some(CertificateFingerprint::from_der(
+                        &ca_cert
+                            .to_der()
+                            .context("boring/TlsAcceptorData: CA cert to DER")?,
+                    )),
+                    None => None,
+                },
+            }),
+        })
+    }
+
+    async fn get(&self, host: &Host) -> Option<IssuedCert> {
+        match self {
+            Self::Mem(cache) => cache.get(host),
+            Self::Custom {
+                cache,
+                issuer_fingerprint,
+            } => {
+                let data = cache
+                    .get(ServerCertCacheKey::new(host.clone(), *issuer_fingerprint))
+                    .await?;
+                server_auth_data_to_private_key_and_ca_chain(&data)
+                    .inspect_err(|err| {
+                        tracing::warn!(%host, error = %err, "boring: ignore invalid cached cert");
+                    })
+                    .ok()
+            }
+        }
+    }
+
+    async fn insert(&self, host: Host, issued_cert: &IssuedCert) {
+        match self {
+            Self::Mem(cache) => cache.insert(host, issued_cert.clone()),
+            Self::Custom {
+                cache,
+                issuer_fingerprint,
+            } => match issued_cert_to_server_auth_data(issued_cert) {
+                Ok(data) => {
+                    cache
+                        .insert(ServerCertCacheKey::new(host, *issuer_fingerprint), data)
+                        .await;
+                }
+                Err(err) => {
+                    tracing::warn!(%host, error = %err, "boring: failed to cache issued cert");
+                }
+            },
+        }
+    }
+
+    /// Get the cert cached for the given host, or issue it and cache it.
+    ///
+    /// Concurrent calls for the same host only issue a single cert.
+    async fn try_get_with(
+        &self,
+        host: Host,
+        init: impl FnOnce() -> Result<IssuedCert, OpaqueError>,
+    ) -> Result<IssuedCert, OpaqueError> {
+        match self {
+            Self::Mem(cache) => cache.try_get_with(host, init).context("mem cache"),
+            Self::Custom {
+                cache,
+                issuer_fingerprint,
+            } => {
+                let key = ServerCertCacheKey::new(host, *issuer_fingerprint);
+                let data = cache
+                    .try_get_with(key, async { issued_cert_to_server_auth_data(&init()?) })
+                    .await?;
+                server_auth_data_to_private_key_and_ca_chain(&data)
+            }
+        }
+    }
 }
 
 impl TlsCertSource {
@@ -110,6 +253,14 @@ impl TlsCertSource {
                 builder
                     .check_private_key()
                     .context("build boring ssl acceptor: check private key")?;
+                if let Some(ocsp) = issued_cert.ocsp {
+                    builder
+                        .set_status_callback(move |ssl| {
+                            ssl.set_ocsp_status(&ocsp)?;
+                            Ok(true)
+                        })
+                        .context("build boring ssl acceptor: set ocsp status callback")?;
+                }
 
                 if let Some(maybe_client_hello) = maybe_client_hello {
                     let cb_maybe_client_hello = maybe_client_hello.clone();
@@ -126,13 +277,10 @@ impl TlsCertSource {
                     });
                 }
             }
-            TlsCertSourceKind::InMemoryIssuer {
-                cert_cache,
-                ca_key,
-                ca_cert,
-            } => {
+            TlsCertSourceKind::Sni(map) => {
                 let cb_maybe_client_hello = maybe_client_hello.clone();
-                builder.set_select_certificate_callback(move |client_hello| {
+
+                builder.set_select_certificate_callback(move |mut client_hello| {
                     if let Some(cb_maybe_client_hello) = &cb_maybe_client_hello {
                         let maybe_client_hello = match RamaClientHello::try_from(&client_hello) {
                             Ok(ch) => Some(ch),
@@ -144,42 +292,143 @@ impl TlsCertSource {
                         *cb_maybe_client_hello.lock() = maybe_client_hello;
                     }
 
-                    let mut client_hello = client_hello;
                     let ssl_ref = client_hello.ssl_mut();
-
-                    let host = to_host(ssl_ref, &server_name).map_err(|err| {
-                        tracing::error!(error = %err, "boring: failed getting host");
-                        SelectCertError::ERROR
-                    })?;
-
-                    tracing::trace!(%host, "try to use cached issued cert or generate new one");
-                    let issued_cert = match &cert_cache {
-                        None => issue_cert_for_ca(host.clone(), &ca_cert, &ca_key).context("fresh issue of cert").map_err(|err| {
-                            tracing::error!(error = %err, "boring: select certificate callback: issue failed");
-                            SelectCertError::ERROR
-                        })?,
-                        Some(cert_cache) => cert_cache
-                        .try_get_with(host.clone(), || {
-                            issue_cert_for_ca(host.clone(), &ca_cert, &ca_key)
-                        })
-                        .context("fresh issue of cert + insert").map_err(|err| {
-                            tracing::error!(error = %err, "boring: select certificate callback: issue failed");
+                    let host = match ssl_ref.servername(NameType::HOST_NAME) {
+                        Some(sni) => Some(sni.parse::<Host>().map_err(|err| {
+                            tracing::warn!(error = %err, "boring: invalid servername received in callback");
                             SelectCertError::ERROR
-                        })?,
+                        })?),
+                        None => server_name.clone(),
                     };
+                    let issued_cert = map.get(host.as_ref()).cloned().ok_or_else(|| {
+                        tracing::debug!(?host, "boring: no server cert found for server name");
+                        SelectCertError::ERROR
+                    })?;
 
                     add_issued_cert_to_ssl_ref(
-                        host,
+                        host.unwrap_or(Host::Name(Domain::from_static("localhost"))),
                         issued_cert,
                         ssl_ref,
                     ).map_err(|err| {
                         tracing::error!(error = %err, "boring: select certificate callback: add certs to ssl ref");
                         SelectCertError::ERROR
                     })?;
-
                     Ok(())
                 });
             }
+            TlsCertSourceKind::InMemoryIssuer {
+                cert_cache,
+                ca_key,
+                ca_cert,
+                ca_chain,
+                validity,
+                key_type,
+                upstream,
+            } => {
+                let cb_maybe_client_hello = maybe_client_hello.clone();
+                let ca_chain = Arc::new(ca_chain);
+
+                builder.set_async_select_certificate_callback(move |client_hello| {
+                    let rama_client_hello = if cb_maybe_client_hello.is_some() || upstream.is_some() {
+                        match RamaClientHello::try_from(&*client_hello) {
+                            Ok(ch) => Some(ch),
+                            Err(err) => {
+                                tracing::warn!(err = %err, "failed to extract boringssl client hello");
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    if let Some(cb_maybe_client_hello) = &cb_maybe_client_hello {
+                        *cb_maybe_client_hello.lock() = rama_client_hello.clone();
+                    }
+
+                    let ssl_ref = client_hello.ssl_mut();
+                    let host = to_host(ssl_ref, &server_name).map_err(|err| {
+                        tracing::error!(error = %err, "boring: failed getting host");
+                        AsyncSelectCertError{}
+                    })?;
+
+                    let cert_cache = cert_cache.clone();
+                    let ca_key = ca_key.clone();
+                    let ca_cert = ca_cert.clone();
+                    let ca_chain = ca_chain.clone();
+                    let upstream = upstream.clone();
+                    let server_name = server_name.clone();
+
+                    Ok(Box::pin(async move {
+                        tracing::trace!(%host, "try to use cached issued cert or generate new one");
+                        let issued_cert = match upstream {
+                            None => {
+                                let issue_cert = || {
+                                    issue_cert_for_ca(host.clone(), &ca_cert, &ca_key, &ca_chain, validity, key_type, Vec::new())
+                                };
+                                match &cert_cache {
+                                    None => issue_cert().context("fresh issue of cert").map_err(|err| {
+                                        tracing::error!(error = %err, "boring: select certificate callback: issue failed");
+                                        AsyncSelectCertError{}
+                                    })?,
+                                    Some(cert_cache) => cert_cache
+                                    .try_get_with(host.clone(), issue_cert)
+                                    .await
+                                    .context("fresh issue of cert + insert").map_err(|err| {
+                                        tracing::error!(error = %err, "boring: select certificate callback: issue failed");
+                                        AsyncSelectCertError{}
+                                    })?,
+                                }
+                            }
+                            Some(upstream) => {
+                                let cached_cert = match &cert_cache {
+                                    Some(cert_cache) => cert_cache.get(&host).await,
+                                    None => None,
+                                };
+                                if let Some(cached_cert) = cached_cert {
+                                    cached_cert
+                                } else {
+                                    let rama_client_hello = rama_client_hello.ok_or_else(|| {
+                                        tracing::error!("boring: client hello required to fetch upstream cert");
+                                        AsyncSelectCertError{}
+                                    })?;
+                                    let upstream_cert = upstream.fetch_upstream_cert(rama_client_hello, server_name).await.map_err(|err| {
+                                        tracing::error!(error = %err, "boring: fetch upstream cert failed");
+                                        AsyncSelectCertError{}
+                                    })?;
+                                    let upstream_info = CertificateInfo::try_from_der(&upstream_cert).map_err(|err| {
+                                        tracing::error!(error = %err, "boring: parse upstream cert failed");
+                                        AsyncSelectCertError{}
+                                    })?;
+                                    let issued_cert = issue_cert_for_ca(host.clone(), &ca_cert, &ca_key, &ca_chain, validity, key_type, upstream_info.subject_alt_names).map_err(|err| {
+                                        tracing::error!(error = %err, "boring: issue cert mirroring upstream subject alt names failed");
+                                        AsyncSelectCertError{}
+                                    })?;
+                                    if let Some(cert_cache) = &cert_cache {
+                                        cert_cache.insert(host.clone(), &issued_cert).await;
+                                    }
+                                    issued_cert
+                                }
+                            }
+                        };
+
+                        let apply_cert = Box::new(move |client_hello: ClientHello<'_>| {
+                            let mut client_hello = client_hello;
+                            let ssl_ref = client_hello.ssl_mut();
+
+                            add_issued_cert_to_ssl_ref(
+                                host,
+                                issued_cert,
+                                ssl_ref,
+                            ).map_err(|err| {
+                                tracing::error!(error = %err, "boring: async select certificate callback: add certs to ssl ref");
+                                AsyncSelectCertError{}
+                            })?;
+                            Ok(())
+                        }) as BoxSelectCertFinish;
+
+                        Ok(apply_cert)
+                    }))
+                });
+            }
             TlsCertSourceKind::DynamicIssuer { issuer, cert_cache } => {
                 let cb_maybe_client_hello = maybe_client_hello.clone();
                 let cert_cache = cert_cache.clone();
@@ -207,22 +456,102 @@ impl TlsCertSource {
                     let server_name = server_name.clone();
 
                     Ok(Box::pin(async move {
-                        let issued_cert = if let Some(cached_cert) = cert_cache.as_ref().and_then(|cert_cache| cert_cache.get(&host)) {
+                        let cached_cert = match &cert_cache {
+                            Some(cert_cache) => cert_cache.get(&host).await,
+                            None => None,
+                        };
+                        let issued_cert = if let Some(cached_cert) = cached_cert {
                             cached_cert
                         } else {
                             let auth_data = issuer.issue_cert(rama_client_hello, server_name).await.map_err(|err| {
                                 tracing::error!(error = %err, "boring: dynamic cert issuer failed");
                                 AsyncSelectCertError{}
                             })?;
-                            server_auth_data_to_private_key_and_ca_chain(&auth_data).map_err(|err| {
+                            let issued_cert = server_auth_data_to_private_key_and_ca_chain(&auth_data).map_err(|err| {
                                 tracing::error!(error = %err, "boring: server_auth_data to key and ca chain failed");
                                 AsyncSelectCertError{}
-                            })?
+                            })?;
+                            if let Some(cert_cache) = &cert_cache {
+                                cert_cache.insert(host.clone(), &issued_cert).await;
+                            }
+                            issued_cert
                         };
 
-                        if let Some(cert_cache) = cert_cache {
-                            cert_cache.insert(host.clone(), issued_cert.clone());
-                        }
+                        let apply_cert = Box::new(move |client_hello: ClientHello<'_>| {
+                            let mut client_hello = client_hello;
+                            let ssl_ref = client_hello.ssl_mut();
+
+                            add_issued_cert_to_ssl_ref(
+                                host,
+                                issued_cert,
+                                ssl_ref,
+                            ).map_err(|err| {
+                                tracing::error!(error = %err, "boring: async select certificate callback: add certs to ssl ref");
+                                AsyncSelectCertError{}
+                            })?;
+                            Ok(())
+                        }) as BoxSelectCertFinish;
+
+                        Ok(apply_cert)
+                    }))
+                });
+            }
+            TlsCertSourceKind::MirrorUpstream {
+                upstream,
+                cert_cache,
+                ca_key,
+                ca_cert,
+                ca_chain,
+                key_type,
+            } => {
+                let cb_maybe_client_hello = maybe_client_hello.clone();
+                let ca_chain = Arc::new(ca_chain);
+
+                builder.set_async_select_certificate_callback(move |client_hello| {
+                    let rama_client_hello =
+                        RamaClientHello::try_from(&*client_hello).map_err(|err| {
+                            tracing::error!(error = %err, "boring: failed converting to rama client hello");
+                            AsyncSelectCertError{}
+                        })?;
+
+                    if let Some(cb_maybe_client_hello) = &cb_maybe_client_hello {
+                        *cb_maybe_client_hello.lock() = Some(rama_client_hello.clone());
+                    }
+
+                    let ssl_ref = client_hello.ssl_mut();
+                    let host = to_host(ssl_ref, &server_name).map_err(|err| {
+                        tracing::error!(error = %err, "boring: failed getting host");
+                        AsyncSelectCertError{}
+                    })?;
+
+                    let upstream = upstream.clone();
+                    let cert_cache = cert_cache.clone();
+                    let server_name = server_name.clone();
+                    let ca_key = ca_key.clone();
+                    let ca_cert = ca_cert.clone();
+                    let ca_chain = ca_chain.clone();
+
+                    Ok(Box::pin(async move {
+                        let cached_cert = match &cert_cache {
+                            Some(cert_cache) => cert_cache.get(&host).await,
+                            None => None,
+                        };
+                        let issued_cert = if let Some(cached_cert) = cached_cert {
+                            cached_cert
+                        } else {
+                            let upstream_cert = upstream.fetch_upstream_cert(rama_client_hello, server_name).await.map_err(|err| {
+                                tracing::error!(error = %err, "boring: fetch upstream cert failed");
+                                AsyncSelectCertError{}
+                            })?;
+                            let issued_cert = issue_mirrored_cert_for_ca(&upstream_cert, &ca_cert, &ca_key, &ca_chain, key_type).map_err(|err| {
+                                tracing::error!(error = %err, "boring: issue cert mirroring upstream cert failed");
+                                AsyncSelectCertError{}
+                            })?;
+                            if let Some(cert_cache) = &cert_cache {
+                                cert_cache.insert(host.clone(), &issued_cert).await;
+                            }
+                            issued_cert
+                        };
 
                         let apply_cert = Box::new(move |client_hello: ClientHello<'_>| {
                             let mut client_hello = client_hello;
@@ -290,26 +619,34 @@ impl TryFrom<rama_net::tls::server::ServerConfig> for TlsAcceptorData {
 
                 TlsCertSourceKind::InMemory(issued_cert)
             }
+            ServerAuth::Sni(map) => {
+                // parsed once, such that certs are not parsed again for each handshake
+                let map = map.try_map(|data| {
+                    verify_server_auth_data(&data)?;
+                    server_auth_data_to_private_key_and_ca_chain(&data)
+                })?;
+                TlsCertSourceKind::Sni(Arc::new(map))
+            }
 
             ServerAuth::CertIssuer(data) => {
-                let cert_cache = match data.cache_kind {
-                    CacheKind::Disabled => None,
-                    CacheKind::MemCache { max_size } => Some(
-                        Cache::builder()
-                            .time_to_live(Duration::from_secs(60 * 60 * 24 * 89))
-                            .max_capacity(max_size.into())
-                            .build(),
-                    ),
-                };
+                let cache_kind = data.cache_kind;
 
                 match data.kind {
                     ServerCertIssuerKind::SelfSigned(data) => {
                         let (ca_cert, ca_key) = self_signed_server_ca(data)
                             .context("boring/TlsAcceptorData: CA: self-signed ca")?;
                         TlsCertSourceKind::InMemoryIssuer {
-                            cert_cache,
+                            cert_cache: IssuedCertCache::try_new(
+                                cache_kind,
+                                Some(&ca_cert),
+                                DEFAULT_ISSUED_CERT_VALIDITY,
+                            )?,
                             ca_key,
                             ca_cert,
+                            ca_chain: Vec::new(),
+                            validity: DEFAULT_ISSUED_CERT_VALIDITY,
+                            key_type: DEFAULT_ISSUED_CERT_KEY_TYPE,
+                            upstream: None,
                         }
                     }
                     ServerCertIssuerKind::Single(data) => {
@@ -320,18 +657,88 @@ impl TryFrom<rama_net::tls::server::ServerConfig> for TlsAcceptorData {
                             .context("pop CA Cert (last) from stack")?;
 
                         TlsCertSourceKind::InMemoryIssuer {
-                            cert_cache,
+                            cert_cache: IssuedCertCache::try_new(
+                                cache_kind,
+                                Some(&ca_cert),
+                                DEFAULT_ISSUED_CERT_VALIDITY,
+                            )?,
                             ca_key: issued_cert.key,
                             ca_cert,
+                            ca_chain: Vec::new(),
+                            validity: DEFAULT_ISSUED_CERT_VALIDITY,
+                            key_type: DEFAULT_ISSUED_CERT_KEY_TYPE,
+                            upstream: None,
+                        }
+                    }
+                    ServerCertIssuerKind::Dynamic(issuer) => TlsCertSourceKind::DynamicIssuer {
+                        issuer,
+                        cert_cache: IssuedCertCache::try_new(
+                            cache_kind,
+                            None,
+                            DEFAULT_ISSUED_CERT_VALIDITY,
+                        )?,
+                    },
+                    ServerCertIssuerKind::CertificateAuthority {
+                        ca_cert,
+                        ca_key,
+                        validity,
+                        key_type,
+                        upstream,
+                    } => {
+                        let (ca_cert, ca_key, ca_chain) =
+                            certificate_authority_from_server_auth_data(ca_cert, ca_key)?;
+
+                        TlsCertSourceKind::InMemoryIssuer {
+                            cert_cache: IssuedCertCache::try_new(
+                                cache_kind,
+                                Some(&ca_cert),
+                                validity,
+                            )?,
+                            ca_key,
+                            ca_cert,
+                            ca_chain,
+                            validity,
+                            key_type,
+                            upstream,
                         }
                     }
-                    ServerCertIssuerKind::Dynamic(issuer) => {
-                        TlsCertSourceKind::DynamicIssuer { issuer, cert_cache }
+                    ServerCertIssuerKind::MirrorUpstream {
+                        ca_cert,
+                        ca_key,
+                        key_type,
+                        upstream,
+                    } => {
+                        let (ca_cert, ca_key, ca_chain) =
+                            certificate_authority_from_server_auth_data(ca_cert, ca_key)?;
+
+                        TlsCertSourceKind::MirrorUpstream {
+                            upstream,
+                            // validity is copied from the upstream cert,
+                            // the default is only used to limit the lifetime of cached certs
+                            cert_cache: IssuedCertCache::try_new(
+                                cache_kind,
+                                Some(&ca_cert),
+                                DEFAULT_ISSUED_CERT_VALIDITY,
+                            )?,
+                            ca_key,
+                            ca_cert,
+                            ca_chain,
+                            key_type,
+                        }
                     }
                 }
             }
         };
 
+        let ech_keys = match value.ech_keys {
+            Some(ech_keys) if !ech_keys.is_empty() => {
+                // validate early, such that a misconfiguration is not only noticed on accept
+                ech_key_set_to_ssl_ech_keys(&ech_keys)?;
+                Some(ech_keys)
+            }
+            _ => None,
+        };
+
         // return the created server config, all good if you reach here
         Ok(TlsAcceptorData {
             config: Arc::new(TlsConfig {
@@ -343,11 +750,37 @@ impl TryFrom<rama_net::tls::server::ServerConfig> for TlsAcceptorData {
                 protocol_versions: value.protocol_versions.clone(),
                 client_cert_chain,
                 store_client_certificate_chain: value.store_client_certificate_chain,
+                ech_keys,
             }),
         })
     }
 }
 
+/// Convert the rama [`EchKeySet`] into the boring [`SslEchKeys`].
+pub(super) fn ech_key_set_to_ssl_ech_keys(ech_keys: &EchKeySet) -> Result<SslEchKeys, OpaqueError> {
+    let mut builder =
+        SslEchKeys::builder().context("boring/TlsAcceptorData: create ech keys builder")?;
+    for key in ech_keys.keys() {
+        if key.config.kem_id != KeyEncapsulationMechanism::DHKEM_X25519_HKDF_SHA256 {
+            return Err(OpaqueError::from_display(format!(
+                "boring/TlsAcceptorData: ech key (config id {}): unsupported kem: {}",
+                key.config.config_id, key.config.kem_id
+            )));
+        }
+        // NOTE: despite its name this is the (only supported) X25519 HPKE key
+        let hpke_key = HpkeKey::dhkem_p256_sha256(&key.private_key)
+            .context("boring/TlsAcceptorData: create ech hpke key")?;
+        let ech_config = key
+            .config
+            .encode()
+            .context("boring/TlsAcceptorData: encode ech config")?;
+        builder
+            .add_key(key.retry_config, &ech_config, hpke_key)
+            .context("boring/TlsAcceptorData: add ech key")?;
+    }
+    Ok(builder.build())
+}
+
 fn to_host(ssl_ref: &SslRef, server_name: &Option<Host>) -> Result<Host, OpaqueError> {
     let host = match (ssl_ref.servername(NameType::HOST_NAME), &server_name) {
         (Some(sni), _) => {
@@ -409,13 +842,109 @@ fn server_auth_data_to_private_key_and_ca_chain(
     Ok(IssuedCert {
         cert_chain,
         key: private_key,
+        ocsp: data.ocsp.clone(),
     })
 }
 
+/// Verify that the given [`ServerAuthData`] can be used by a boring tls acceptor,
+/// meaning that its cert chain and private key can be parsed,
+/// and that the private key matches the leaf cert.
+///
+/// Can be used as the validator of a [`FileWatchingCertIssuer`],
+/// such that reloaded cert and key pairs are only served if valid.
+///
+/// [`FileWatchingCertIssuer`]: rama_net::tls::server::FileWatchingCertIssuer
+pub fn verify_server_auth_data(data: &ServerAuthData) -> Result<(), OpaqueError> {
+    let issued_cert = server_auth_data_to_private_key_and_ca_chain(data)?;
+    let leaf = issued_cert
+        .cert_chain
+        .first()
+        .context("boring/TlsAcceptorData: leaf cert missing")?;
+    if !leaf
+        .public_key()
+        .context("boring/TlsAcceptorData: get leaf cert public key")?
+        .public_eq(issued_cert.key.as_ref())
+    {
+        return Err(OpaqueError::from_display(
+            "boring/TlsAcceptorData: private key does not match leaf cert",
+        ));
+    }
+    Ok(())
+}
+
+/// Parse the CA cert (followed by its optional chain) and its private key,
+/// verifying that the key belongs to the CA cert.
+fn certificate_authority_from_server_auth_data(
+    ca_cert: DataEncoding,
+    ca_key: DataEncoding,
+) -> Result<(X509, PKey<Private>, Vec<X509>), OpaqueError> {
+    let issued_cert = server_auth_data_to_private_key_and_ca_chain(&ServerAuthData {
+        private_key: ca_key,
+        cert_chain: ca_cert,
+        ocsp: None,
+    })
+    .context("boring/TlsAcceptorData: CA: parse ca cert and key")?;
+    let mut cert_chain = issued_cert.cert_chain.into_iter();
+    let ca_cert = cert_chain
+        .next()
+        .context("boring/TlsAcceptorData: CA: ca cert missing")?;
+    if !ca_cert
+        .public_key()
+        .context("boring/TlsAcceptorData: CA: get ca cert public key")?
+        .public_eq(issued_cert.key.as_ref())
+    {
+        return Err(OpaqueError::from_display(
+            "boring/TlsAcceptorData: CA: ca key does not match ca cert",
+        ));
+    }
+    Ok((ca_cert, issued_cert.key, cert_chain.collect()))
+}
+
+fn issued_cert_to_server_auth_data(
+    issued_cert: &IssuedCert,
+) -> Result<ServerAuthData, OpaqueError> {
+    let mut cert_chain = String::new();
+    for cert in &issued_cert.cert_chain {
+        let pem = cert
+            .to_pem()
+            .context("boring/TlsAcceptorData: x509 cert to PEM")?;
+        cert_chain.push_str(
+            std::str::from_utf8(&pem).context("boring/TlsAcceptorData: x509 cert PEM to str")?,
+        );
+    }
+    let private_key = String::from_utf8(
+        issued_cert
+            .key
+            .private_key_to_pem_pkcs8()
+            .context("boring/TlsAcceptorData: private key to PEM")?,
+    )
+    .context("boring/TlsAcceptorData: private key PEM to str")?;
+
+    Ok(ServerAuthData {
+        private_key: DataEncoding::Pem(
+            private_key
+                .try_into()
+                .context("boring/TlsAcceptorData: private key PEM")?,
+        ),
+        cert_chain: DataEncoding::Pem(
+            cert_chain
+                .try_into()
+                .context("boring/TlsAcceptorData: cert chain PEM")?,
+        ),
+        ocsp: issued_cert.ocsp.clone(),
+    })
+}
+
+/// Issue a cert for the given host signed by the given CA, using the host as
+/// common name and subject alt name, followed by the given subject alt names mirrored from upstream.
 fn issue_cert_for_ca(
     host: Host,
     ca_cert: &X509,
     ca_key: &PKey<Private>,
+    ca_chain: &[X509],
+    validity: Duration,
+    key_type: ServerCertKeyType,
+    mirrored_subject_alt_names: Vec<SubjectAltName>,
 ) -> Result<IssuedCert, OpaqueError> {
     tracing::trace!(
         %host,
@@ -437,12 +966,131 @@ fn issue_cert_for_ca(
         },
         ca_cert,
         ca_key,
+        validity,
+        key_type,
+        &mirrored_subject_alt_names,
     )
     .with_context(|| format!("issue certs in memory for: {host:?}"))?;
 
+    let mut cert_chain = vec![cert, ca_cert.clone()];
+    cert_chain.extend_from_slice(ca_chain);
     Ok(IssuedCert {
-        cert_chain: vec![cert, ca_cert.clone()],
+        cert_chain,
         key,
+        ocsp: None,
+    })
+}
+
+/// Issue a cert signed by the given CA, copying the subject, subject alt names
+/// and validity window of the given (DER-encoded) upstream cert.
+///
+/// The key usage is derived from the generated key instead.
+fn issue_mirrored_cert_for_ca(
+    upstream_cert: &[u8],
+    ca_cert: &X509,
+    ca_key: &PKey<Private>,
+    ca_chain: &[X509],
+    key_type: ServerCertKeyType,
+) -> Result<IssuedCert, OpaqueError> {
+    let upstream_x509 =
+        X509::from_der(upstream_cert).context("mirror: parse x509 upstream cert")?;
+    let upstream_info =
+        CertificateInfo::try_from_der(upstream_cert).context("mirror: parse upstream cert info")?;
+    tracing::trace!(
+        subject = %upstream_info.subject,
+        "generate cert mirroring upstream cert using in-memory ca cert"
+    );
+
+    let privkey = generate_private_key(key_type)?;
+
+    let mut cert_builder = X509::builder().context("create x509 (cert) builder")?;
+    cert_builder
+        .set_version(2)
+        .context("x509 cert builder: set version = 2")?;
+    let serial_number = {
+        let mut serial = BigNum::new().context("x509 cert builder: create big num (serial")?;
+        serial
+            .rand(159, MsbOption::MAYBE_ZERO, false)
+            .context("x509 cert builder: randomise serial number (big num)")?;
+        serial
+            .to_asn1_integer()
+            .context("x509 cert builder: convert serial to ASN1 integer")?
+    };
+    cert_builder
+        .set_serial_number(&serial_number)
+        .context("x509 cert builder: set serial number")?;
+    cert_builder
+        .set_issuer_name(ca_cert.subject_name())
+        .context("x509 cert builder: set issuer name")?;
+    cert_builder
+        .set_subject_name(upstream_x509.subject_name())
+        .context("x509 cert builder: set subject name of upstream cert")?;
+    cert_builder
+        .set_pubkey(&privkey)
+        .context("x509 cert builder: set pub key")?;
+    cert_builder
+        .set_not_before(upstream_x509.not_before())
+        .context("x509 cert builder: set not before of upstream cert")?;
+    cert_builder
+        .set_not_after(upstream_x509.not_after())
+        .context("x509 cert builder: set not after of upstream cert")?;
+
+    cert_builder
+        .append_extension(
+            BasicConstraints::new()
+                .build()
+                .context("x509 cert builder: build basic constraints")?,
+        )
+        .context("x509 cert builder: add basic constraints as x509 extension")?;
+
+    // key usage is derived from the generated key, never copied from upstream
+    cert_builder
+        .append_extension(
+            leaf_key_usage(key_type)
+                .build()
+                .context("x509 cert builder: create key usage")?,
+        )
+        .context("x509 cert builder: add key usage x509 extension")?;
+
+    if !upstream_info.subject_alt_names.is_empty() {
+        let mut subject_alt_name = SubjectAlternativeName::new();
+        for san in &upstream_info.subject_alt_names {
+            add_subject_alt_name(&mut subject_alt_name, san);
+        }
+        let subject_alt_name = subject_alt_name
+            .build(&cert_builder.x509v3_context(Some(ca_cert), None))
+            .context("x509 cert builder: build subject alt name")?;
+        cert_builder
+            .append_extension(subject_alt_name)
+            .context("x509 cert builder: add subject alt name")?;
+    }
+
+    let subject_key_identifier = SubjectKeyIdentifier::new()
+        .build(&cert_builder.x509v3_context(Some(ca_cert), None))
+        .context("x509 cert builder: build subject key id")?;
+    cert_builder
+        .append_extension(subject_key_identifier)
+        .context("x509 cert builder: add subject key id x509 extension")?;
+
+    let auth_key_identifier = AuthorityKeyIdentifier::new()
+        .keyid(false)
+        .issuer(false)
+        .build(&cert_builder.x509v3_context(Some(ca_cert), None))
+        .context("x509 cert builder: build auth key id")?;
+    cert_builder
+        .append_extension(auth_key_identifier)
+        .context("x509 cert builder: set auth key id extension")?;
+
+    cert_builder
+        .sign(ca_key, MessageDigest::sha256())
+        .context("x509 cert builder: sign cert")?;
+
+    let mut cert_chain = vec![cert_builder.build(), ca_cert.clone()];
+    cert_chain.extend_from_slice(ca_chain);
+    Ok(IssuedCert {
+        cert_chain,
+        key: privkey,
+        ocsp: None,
     })
 }
 
@@ -471,6 +1119,11 @@ fn add_issued_cert_to_ssl_ref(
     builder
         .set_private_key(issued_cert.key.as_ref())
         .context("boring add issue cert to ssl ref: set private key")?;
+    if let Some(ocsp) = issued_cert.ocsp.as_deref() {
+        builder
+            .set_ocsp_status(ocsp)
+            .context("boring add issue cert to ssl ref: set ocsp status")?;
+    }
     // builder
     //     .check()
     //     .context("build boring ssl acceptor: issued in-mem: check private key")?;
@@ -480,11 +1133,19 @@ fn add_issued_cert_to_ssl_ref(
 
 fn self_signed_server_auth(data: SelfSignedData) -> Result<IssuedCert, OpaqueError> {
     let (ca_cert, ca_privkey) = self_signed_server_auth_gen_ca(&data).context("self-signed CA")?;
-    let (cert, privkey) = self_signed_server_auth_gen_cert(&data, &ca_cert, &ca_privkey)
-        .context("self-signed cert using self-signed CA")?;
+    let (cert, privkey) = self_signed_server_auth_gen_cert(
+        &data,
+        &ca_cert,
+        &ca_privkey,
+        DEFAULT_ISSUED_CERT_VALIDITY,
+        DEFAULT_ISSUED_CERT_KEY_TYPE,
+        &[],
+    )
+    .context("self-signed cert using self-signed CA")?;
     Ok(IssuedCert {
         cert_chain: vec![cert, ca_cert],
         key: privkey,
+        ocsp: None,
     })
 }
 
@@ -497,9 +1158,11 @@ fn self_signed_server_auth_gen_cert(
     data: &SelfSignedData,
     ca_cert: &X509,
     ca_privkey: &PKey<Private>,
+    validity: Duration,
+    key_type: ServerCertKeyType,
+    mirrored_subject_alt_names: &[SubjectAltName],
 ) -> Result<(X509, PKey<Private>), OpaqueError> {
-    let rsa = Rsa::generate(4096).context("generate 4096 RSA key")?;
-    let privkey = PKey::from_rsa(rsa).context("create private key from 4096 RSA key")?;
+    let privkey = generate_private_key(key_type)?;
 
     let common_name = data
         .common_name
@@ -556,11 +1219,15 @@ fn self_signed_server_auth_gen_cert(
     cert_builder
         .set_not_before(&not_before)
         .context("x509 cert builder: set not before to today")?;
-    let not_after = Asn1Time::days_from_now(90)
-        .context("x509 cert builder: create ASN1Time for 90 days in future")?;
+    let not_after = SystemTime::now()
+        .checked_add(validity)
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .context("x509 cert builder: compute not after")?;
+    let not_after = Asn1Time::from_unix(not_after.as_secs() as _)
+        .context("x509 cert builder: create ASN1Time for not after")?;
     cert_builder
         .set_not_after(&not_after)
-        .context("x509 cert builder: set not after to 90 days in future")?;
+        .context("x509 cert builder: set not after")?;
 
     cert_builder
         .append_extension(
@@ -571,24 +1238,23 @@ fn self_signed_server_auth_gen_cert(
         .context("x509 cert builder: add basic constraints as x509 extension")?;
     cert_builder
         .append_extension(
-            KeyUsage::new()
-                .critical()
-                .non_repudiation()
-                .digital_signature()
-                .key_encipherment()
+            leaf_key_usage(key_type)
                 .build()
                 .context("x509 cert builder: create key usage")?,
         )
         .context("x509 cert builder: add key usage x509 extension")?;
 
     let mut subject_alt_name = SubjectAlternativeName::new();
-    match common_name {
-        Host::Name(domain) => {
-            subject_alt_name.dns(domain.as_str());
-        }
-        Host::Address(addr) => {
-            subject_alt_name.ip(addr.to_string().as_str());
-        }
+    let common_name_subject_alt_name = match common_name {
+        Host::Name(domain) => SubjectAltName::Dns(domain.as_str().to_owned()),
+        Host::Address(addr) => SubjectAltName::Ip(addr),
+    };
+    add_subject_alt_name(&mut subject_alt_name, &common_name_subject_alt_name);
+    for san in mirrored_subject_alt_names
+        .iter()
+        .filter(|san| **san != common_name_subject_alt_name)
+    {
+        add_subject_alt_name(&mut subject_alt_name, san);
     }
     let subject_alt_name = subject_alt_name
         .build(&cert_builder.x509v3_context(Some(ca_cert), None))
@@ -623,6 +1289,48 @@ fn self_signed_server_auth_gen_cert(
     Ok((cert, privkey))
 }
 
+/// Key usage of an issued (leaf) cert, depending on the type of its key.
+fn leaf_key_usage(key_type: ServerCertKeyType) -> KeyUsage {
+    let mut key_usage = KeyUsage::new();
+    key_usage.critical().non_repudiation().digital_signature();
+    if matches!(
+        key_type,
+        ServerCertKeyType::Rsa2048 | ServerCertKeyType::Rsa4096
+    ) {
+        key_usage.key_encipherment();
+    }
+    key_usage
+}
+
+fn add_subject_alt_name(builder: &mut SubjectAlternativeName, san: &SubjectAltName) {
+    match san {
+        SubjectAltName::Dns(name) => builder.dns(name),
+        SubjectAltName::Ip(ip) => builder.ip(ip.to_string().as_str()),
+        SubjectAltName::Email(email) => builder.email(email),
+        SubjectAltName::Uri(uri) => builder.uri(uri),
+    };
+}
+
+fn generate_private_key(key_type: ServerCertKeyType) -> Result<PKey<Private>, OpaqueError> {
+    match key_type {
+        ServerCertKeyType::EcdsaP256 => generate_ec_private_key(Nid::X9_62_PRIME256V1),
+        ServerCertKeyType::EcdsaP384 => generate_ec_private_key(Nid::SECP384R1),
+        ServerCertKeyType::Rsa2048 => generate_rsa_private_key(2048),
+        ServerCertKeyType::Rsa4096 => generate_rsa_private_key(4096),
+    }
+}
+
+fn generate_ec_private_key(curve: Nid) -> Result<PKey<Private>, OpaqueError> {
+    let group = EcGroup::from_curve_name(curve).context("create EC group")?;
+    let ec_key = EcKey::generate(&group).context("generate EC key")?;
+    PKey::from_ec_key(ec_key).context("create private key from EC key")
+}
+
+fn generate_rsa_private_key(bits: u32) -> Result<PKey<Private>, OpaqueError> {
+    let rsa = Rsa::generate(bits).context("generate RSA key")?;
+    PKey::from_rsa(rsa).context("create private key from RSA key")
+}
+
 fn self_signed_server_auth_gen_ca(
     data: &SelfSignedData,
 ) -> Result<(X509, PKey<Private>), OpaqueError> {
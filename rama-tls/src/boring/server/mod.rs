@@ -12,7 +12,7 @@
 
 mod acceptor_data;
 #[doc(inline)]
-pub use acceptor_data::TlsAcceptorData;
+pub use acceptor_data::{TlsAcceptorData, verify_server_auth_data};
 
 mod service;
 #[doc(inline)]
@@ -1,7 +1,7 @@
-use super::TlsAcceptorData;
+use super::{TlsAcceptorData, acceptor_data::ech_key_set_to_ssl_ech_keys};
 use crate::{
     boring::dep::{
-        boring::ssl::{AlpnError, SslAcceptor, SslMethod, SslRef},
+        boring::ssl::{AlpnError, NameType, SslAcceptor, SslMethod, SslRef},
         boring_tokio::SslStream,
     },
     keylog::new_key_log_file_handle,
@@ -13,9 +13,13 @@ use rama_core::{
     error::{BoxError, ErrorContext, ErrorExt, OpaqueError},
 };
 use rama_net::{
+    address::Host,
     http::RequestContext,
     stream::Stream,
-    tls::{ApplicationProtocol, DataEncoding, client::NegotiatedTlsParameters},
+    tls::{
+        ApplicationProtocol, CertificateInfo, DataEncoding, cipher_suite_from_openssl_cipher_str,
+        client::NegotiatedTlsParameters,
+    },
     transport::TransportContext,
 };
 use rama_utils::macros::define_inner_service_accessors;
@@ -172,6 +176,16 @@ where
             );
         }
 
+        if let Some(ech_keys) = tls_config.ech_keys.as_ref() {
+            trace!(
+                "tls boring server service: set {} ech key(s)",
+                ech_keys.keys().len()
+            );
+            acceptor_builder
+                .set_ech_keys(&ech_key_set_to_ssl_ech_keys(ech_keys)?)
+                .context("build boring ssl acceptor: set ech keys")?;
+        }
+
         if let Some(keylog_filename) = tls_config.keylog_intent.file_path() {
             let handle = new_key_log_file_handle(keylog_filename)?;
             acceptor_builder.set_keylog_callback(move |_, line| {
@@ -232,7 +246,27 @@ where
                 ctx.insert(NegotiatedTlsParameters {
                     protocol_version,
                     application_layer_protocol,
+                    cipher_suite: stream
+                        .ssl()
+                        .current_cipher()
+                        .and_then(|cipher| cipher_suite_from_openssl_cipher_str(cipher.name())),
+                    key_exchange_group: stream
+                        .ssl()
+                        .curve()
+                        .and_then(|curve| curve.try_into().ok()),
+                    signature_scheme: crate::boring::peer_signature_scheme(stream.ssl()),
+                    server_name: stream
+                        .ssl()
+                        .servername(NameType::HOST_NAME)
+                        .and_then(|name| Host::try_from(name).ok()),
                     peer_certificate_chain: client_certificate_chain,
+                    peer_certificate: stream
+                        .ssl()
+                        .peer_certificate()
+                        .and_then(|cert| cert.to_der().ok())
+                        .and_then(|cert| CertificateInfo::try_from_der(&cert).ok()),
+                    session_resumed: stream.ssl().session_reused(),
+                    ech_accepted: stream.ssl().ech_accepted(),
                 });
             }
             None => {
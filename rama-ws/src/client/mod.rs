@@ -0,0 +1,406 @@
+//! WebSocket client support.
+//!
+//! See [`WebSocketConnector`] for more information.
+
+use crate::deflate::PerMessageDeflateConfig;
+use crate::handshake::{
+    WEBSOCKET_UPGRADE, WEBSOCKET_VERSION, derive_accept_key, generate_key, is_websocket_upgrade,
+};
+use crate::protocol::Role;
+use crate::{WebSocket, WebSocketConfig};
+use rama_core::{
+    Context, Layer, Service,
+    error::{BoxError, ErrorContext, OpaqueError},
+};
+use rama_http::{HeaderValue, Method, Request, Response, StatusCode, Version, header};
+use rama_http_core::upgrade::{self, Upgraded};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+/// A [`Service`] which establishes a [`WebSocket`] connection
+/// by sending an upgrade request using the inner http client [`Service`],
+/// e.g. the `EasyHttpWebClient`.
+///
+/// The request is turned into a WebSocket upgrade request by this connector,
+/// and the `ws` and `wss` schemes can be used for its uri.
+///
+/// WebSockets are only supported over HTTP/1.1, and thus the request version
+/// is forced to HTTP/1.1. When connecting over TLS, make sure the http client
+/// is configured to only offer `http/1.1` as ALPN, as the negotiated ALPN
+/// otherwise overwrites the request version.
+pub struct WebSocketConnector<S> {
+    inner: S,
+    config: WebSocketConfig,
+    protocols: Option<HeaderValue>,
+    per_message_deflate: Option<PerMessageDeflateConfig>,
+}
+
+impl<S> WebSocketConnector<S> {
+    /// Create a new [`WebSocketConnector`] using the given http client [`Service`].
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            config: WebSocketConfig::new(),
+            protocols: None,
+            per_message_deflate: None,
+        }
+    }
+
+    /// Define the [`WebSocketConfig`] of the established [`WebSocket`].
+    pub fn with_config(mut self, config: WebSocketConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Define the [`WebSocketConfig`] of the established [`WebSocket`].
+    pub fn set_config(&mut self, config: WebSocketConfig) -> &mut Self {
+        self.config = config;
+        self
+    }
+
+    /// Define the sub protocols requested, in order of preference.
+    pub fn with_protocols<I>(mut self, protocols: I) -> Result<Self, OpaqueError>
+    where
+        I: IntoIterator<Item: AsRef<str>>,
+    {
+        self.set_protocols(protocols)?;
+        Ok(self)
+    }
+
+    /// Define the sub protocols requested, in order of preference.
+    pub fn set_protocols<I>(&mut self, protocols: I) -> Result<&mut Self, OpaqueError>
+    where
+        I: IntoIterator<Item: AsRef<str>>,
+    {
+        let protocols = protocols
+            .into_iter()
+            .map(|protocol| protocol.as_ref().to_owned())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.protocols = if protocols.is_empty() {
+            None
+        } else {
+            Some(HeaderValue::from_str(&protocols).context("invalid websocket protocols")?)
+        };
+        Ok(self)
+    }
+
+    /// Offer the per-message-deflate extension to the server.
+    pub fn with_per_message_deflate(mut self, config: PerMessageDeflateConfig) -> Self {
+        self.per_message_deflate = Some(config);
+        self
+    }
+
+    /// Offer the per-message-deflate extension to the server.
+    pub fn set_per_message_deflate(&mut self, config: PerMessageDeflateConfig) -> &mut Self {
+        self.per_message_deflate = Some(config);
+        self
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<S: fmt::Debug> fmt::Debug for WebSocketConnector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketConnector")
+            .field("inner", &self.inner)
+            .field("config", &self.config)
+            .field("protocols", &self.protocols)
+            .field("per_message_deflate", &self.per_message_deflate)
+            .finish()
+    }
+}
+
+impl<S: Clone> Clone for WebSocketConnector<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            config: self.config.clone(),
+            protocols: self.protocols.clone(),
+            per_message_deflate: self.per_message_deflate.clone(),
+        }
+    }
+}
+
+/// A client [`WebSocket`] established by the [`WebSocketConnector`],
+/// together with the `101 Switching Protocols` [`Response`] of the server.
+pub struct ClientWebSocket {
+    socket: WebSocket<Upgraded>,
+    response: Response,
+}
+
+impl fmt::Debug for ClientWebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientWebSocket")
+            .field("socket", &self.socket)
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+impl ClientWebSocket {
+    /// The established [`WebSocket`].
+    pub fn socket(&mut self) -> &mut WebSocket<Upgraded> {
+        &mut self.socket
+    }
+
+    /// The `101 Switching Protocols` [`Response`] sent by the server.
+    pub fn response(&self) -> &Response {
+        &self.response
+    }
+
+    /// The sub protocol selected by the server, if any.
+    pub fn protocol(&self) -> Option<&HeaderValue> {
+        self.response.headers().get(header::SEC_WEBSOCKET_PROTOCOL)
+    }
+
+    /// Consume into the established [`WebSocket`].
+    pub fn into_socket(self) -> WebSocket<Upgraded> {
+        self.socket
+    }
+
+    /// Consume into the established [`WebSocket`] and server [`Response`].
+    pub fn into_parts(self) -> (WebSocket<Upgraded>, Response) {
+        (self.socket, self.response)
+    }
+}
+
+impl<State, S> Service<State, Request> for WebSocketConnector<S>
+where
+    State: Clone + Send + Sync + 'static,
+    S: Service<State, Request, Response = Response, Error: Into<BoxError>>,
+{
+    type Response = ClientWebSocket;
+    type Error = BoxError;
+
+    fn serve(
+        &self,
+        ctx: Context<State>,
+        req: Request,
+    ) -> impl Future<Output = Result<Self::Response, Self::Error>> + Send + '_ {
+        self.handshake(ctx, req, self.protocols.clone())
+    }
+}
+
+impl<S> WebSocketConnector<S> {
+    /// Perform the opening handshake, requesting the given sub protocols
+    /// instead of the ones configured for this connector.
+    pub(crate) async fn handshake<State>(
+        &self,
+        ctx: Context<State>,
+        mut req: Request,
+        protocols: Option<HeaderValue>,
+    ) -> Result<ClientWebSocket, BoxError>
+    where
+        State: Clone + Send + Sync + 'static,
+        S: Service<State, Request, Response = Response, Error: Into<BoxError>>,
+    {
+        let key = generate_key();
+
+        *req.method_mut() = Method::GET;
+        *req.version_mut() = Version::HTTP_11;
+        let headers = req.headers_mut();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(header::UPGRADE, WEBSOCKET_UPGRADE);
+        headers.insert(header::SEC_WEBSOCKET_VERSION, WEBSOCKET_VERSION);
+        headers.insert(header::SEC_WEBSOCKET_KEY, key.clone());
+        if let Some(protocols) = protocols.clone() {
+            headers.insert(header::SEC_WEBSOCKET_PROTOCOL, protocols);
+        }
+        if let Some(config) = self.per_message_deflate.as_ref() {
+            headers.insert(header::SEC_WEBSOCKET_EXTENSIONS, config.to_header_value());
+        }
+
+        let mut response = self.inner.serve(ctx, req).await.map_err(Into::into)?;
+
+        if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(OpaqueError::from_display(format!(
+                "websocket connector: unexpected response status: {}",
+                response.status()
+            ))
+            .into());
+        }
+        if !is_websocket_upgrade(response.headers()) {
+            return Err(OpaqueError::from_display(
+                "websocket connector: response is missing the upgrade headers",
+            )
+            .into());
+        }
+        if response.headers().get(header::SEC_WEBSOCKET_ACCEPT)
+            != Some(&derive_accept_key(key.as_bytes()))
+        {
+            return Err(OpaqueError::from_display(
+                "websocket connector: missing or invalid Sec-WebSocket-Accept header",
+            )
+            .into());
+        }
+
+        if let Some(protocol) = response.headers().get(header::SEC_WEBSOCKET_PROTOCOL) {
+            let requested = protocols
+                .as_ref()
+                .and_then(|protocols| protocols.to_str().ok())
+                .is_some_and(|protocols| {
+                    protocols
+                        .split(',')
+                        .any(|requested| requested.trim().as_bytes() == protocol.as_bytes())
+                });
+            if !requested {
+                return Err(OpaqueError::from_display(
+                    "websocket connector: server selected a protocol which was not requested",
+                )
+                .into());
+            }
+        }
+
+        let extensions = response.headers().get_all(header::SEC_WEBSOCKET_EXTENSIONS);
+        let per_message_deflate = match self.per_message_deflate.as_ref() {
+            Some(config) => config
+                .parse_response(extensions)
+                .map_err(|err| OpaqueError::from_display(format!("websocket connector: {err}")))?,
+            None if extensions.iter().next().is_some() => {
+                return Err(OpaqueError::from_display(
+                    "websocket connector: server accepted an extension which was not offered",
+                )
+                .into());
+            }
+            None => None,
+        };
+
+        let upgraded = upgrade::on(&mut response)
+            .await
+            .context("websocket connector: upgrade connection")?;
+
+        let mut socket = WebSocket::from_raw_socket(upgraded, Role::Client, self.config.clone());
+        if let Some(config) = per_message_deflate {
+            socket = socket.with_per_message_deflate(config);
+        }
+
+        Ok(ClientWebSocket { socket, response })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A [`Layer`] that produces a [`WebSocketConnector`].
+pub struct WebSocketConnectorLayer {
+    config: WebSocketConfig,
+    per_message_deflate: Option<PerMessageDeflateConfig>,
+}
+
+impl WebSocketConnectorLayer {
+    /// Create a new [`WebSocketConnectorLayer`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define the [`WebSocketConfig`] of the established [`WebSocket`]s.
+    pub fn with_config(mut self, config: WebSocketConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Offer the per-message-deflate extension to the server.
+    pub fn with_per_message_deflate(mut self, config: PerMessageDeflateConfig) -> Self {
+        self.per_message_deflate = Some(config);
+        self
+    }
+}
+
+impl<S> Layer<S> for WebSocketConnectorLayer {
+    type Service = WebSocketConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WebSocketConnector {
+            inner,
+            config: self.config.clone(),
+            protocols: None,
+            per_message_deflate: self.per_message_deflate.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WebSocketAcceptor;
+    use crate::protocol::{CloseCode, CloseFrame, Message};
+    use rama_core::service::service_fn;
+    use rama_http::service::web::WebService;
+    use rama_http_backend::client::EasyHttpWebClient;
+    use rama_http_backend::server::HttpServer;
+    use rama_tcp::server::TcpListener;
+    use std::convert::Infallible;
+
+    async fn spawn_echo_server() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = WebService::default().get("/echo", async |ws: WebSocketAcceptor| {
+            ws.with_protocols(["echo"])
+                .with_per_message_deflate(PerMessageDeflateConfig::default())
+                .on_upgrade(async |mut socket| {
+                    while let Ok(Some(message)) = socket.recv_message().await {
+                        if matches!(message, Message::Text(_) | Message::Binary(_))
+                            && socket.send_message(message).await.is_err()
+                        {
+                            return;
+                        }
+                    }
+                })
+        });
+        let server = HttpServer::http1().service(service);
+        tokio::spawn(listener.serve(server));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_websocket_echo_roundtrip() {
+        let addr = spawn_echo_server().await;
+
+        for deflate in [None, Some(PerMessageDeflateConfig::default())] {
+            let mut connector = WebSocketConnector::new(EasyHttpWebClient::default())
+                .with_protocols(["chat", "echo"])
+                .unwrap();
+            if let Some(config) = deflate.clone() {
+                connector.set_per_message_deflate(config);
+            }
+
+            let req = Request::builder()
+                .uri(format!("ws://{addr}/echo"))
+                .body(rama_http::Body::empty())
+                .unwrap();
+            let ws = connector.serve(Context::default(), req).await.unwrap();
+            assert_eq!(ws.protocol().unwrap(), "echo");
+
+            let mut socket = ws.into_socket();
+            assert_eq!(socket.per_message_deflate(), deflate.as_ref());
+
+            for message in [Message::text("hello"), Message::binary(vec![1u8; 10_000])] {
+                socket.send_message(message.clone()).await.unwrap();
+                assert_eq!(socket.recv_message().await.unwrap(), Some(message));
+            }
+
+            socket
+                .close(Some(CloseFrame::new(CloseCode::NormalClosure, "")))
+                .await
+                .unwrap();
+            assert!(matches!(
+                socket.recv_message().await.unwrap(),
+                Some(Message::Close(Some(CloseFrame {
+                    code: CloseCode::NormalClosure,
+                    ..
+                })))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_connector_rejects_non_upgrade_response() {
+        let connector = WebSocketConnector::new(service_fn(async |_req: Request| {
+            Ok::<_, Infallible>(Response::new(rama_http::Body::empty()))
+        }));
+        let req = Request::builder()
+            .uri("ws://example.com")
+            .body(rama_http::Body::empty())
+            .unwrap();
+        assert!(connector.serve(Context::default(), req).await.is_err());
+    }
+}
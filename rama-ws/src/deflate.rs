@@ -0,0 +1,438 @@
+use crate::protocol::{ProtocolError, Role};
+use bytes::Bytes;
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use rama_http::HeaderValue;
+use std::fmt;
+
+/// The name of the per-message-deflate extension,
+/// as used in the `Sec-WebSocket-Extensions` header.
+pub(crate) const PER_MESSAGE_DEFLATE: &str = "permessage-deflate";
+
+/// Trailer appended by a sync flush,
+/// stripped from compressed messages as defined by RFC 7692.
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xFF, 0xFF];
+
+/// Max (and only supported) LZ77 sliding window size (in bits) used to compress messages.
+const MAX_WINDOW_BITS: u8 = 15;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Configuration of the per-message-deflate WebSocket extension.
+///
+/// Only the maximum LZ77 sliding window size (15 bits) is supported
+/// for compression, while any window size is accepted for decompression.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc7692>
+pub struct PerMessageDeflateConfig {
+    /// Do not reuse the compression context of the server
+    /// across messages sent by the server.
+    pub server_no_context_takeover: bool,
+    /// Do not reuse the compression context of the client
+    /// across messages sent by the client.
+    pub client_no_context_takeover: bool,
+}
+
+impl PerMessageDeflateConfig {
+    /// Create a new default [`PerMessageDeflateConfig`],
+    /// reusing the compression context of both sides across messages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request (client) or enforce (server) that the server does not
+    /// reuse its compression context across messages.
+    pub fn with_server_no_context_takeover(mut self, value: bool) -> Self {
+        self.server_no_context_takeover = value;
+        self
+    }
+
+    /// Request (client) or enforce (server) that the client does not
+    /// reuse its compression context across messages.
+    pub fn with_client_no_context_takeover(mut self, value: bool) -> Self {
+        self.client_no_context_takeover = value;
+        self
+    }
+
+    /// Encode this config as a `Sec-WebSocket-Extensions` header value.
+    pub(crate) fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("valid header value")
+    }
+
+    /// Negotiate the extension offers (as sent by a client)
+    /// found in the given `Sec-WebSocket-Extensions` header values.
+    ///
+    /// The first offer which can be accepted is returned,
+    /// combined with the local (server) preferences.
+    pub(crate) fn negotiate_offers<'a>(
+        &self,
+        values: impl IntoIterator<Item = &'a HeaderValue>,
+    ) -> Option<Self> {
+        values
+            .into_iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(|offer| {
+                let params = parse_extension(offer, PER_MESSAGE_DEFLATE)?;
+                let mut accepted = self.clone();
+                let mut seen = Vec::with_capacity(4);
+                for (key, value) in params {
+                    if seen.contains(&key) {
+                        return None;
+                    }
+                    seen.push(key);
+                    match (key, value) {
+                        ("server_no_context_takeover", None) => {
+                            accepted.server_no_context_takeover = true
+                        }
+                        ("client_no_context_takeover", None) => {
+                            accepted.client_no_context_takeover = true
+                        }
+                        // we do not support compressing with a smaller window size
+                        ("server_max_window_bits", Some(bits)) => {
+                            if parse_window_bits(bits)? != MAX_WINDOW_BITS {
+                                return None;
+                            }
+                        }
+                        // any client window size can be decompressed,
+                        // so there is no need to send a hint back to the client
+                        ("client_max_window_bits", bits) => {
+                            if let Some(bits) = bits {
+                                parse_window_bits(bits)?;
+                            }
+                        }
+                        _ => return None,
+                    }
+                }
+                Some(accepted)
+            })
+    }
+
+    /// Parse the extension response (as sent by a server)
+    /// in reply to the offer made using this config.
+    ///
+    /// Returns `Ok(None)` in case the server did not accept the extension.
+    pub(crate) fn parse_response<'a>(
+        &self,
+        values: impl IntoIterator<Item = &'a HeaderValue>,
+    ) -> Result<Option<Self>, String> {
+        let mut negotiated = None;
+        for value in values {
+            let value = value
+                .to_str()
+                .map_err(|_| "non-ascii extension header".to_owned())?;
+            for extension in value.split(',') {
+                let Some(params) = parse_extension(extension, PER_MESSAGE_DEFLATE) else {
+                    return Err(format!(
+                        "unexpected extension in response: {}",
+                        extension.trim()
+                    ));
+                };
+                if negotiated.is_some() {
+                    return Err("duplicate permessage-deflate extension".to_owned());
+                }
+
+                let mut accepted = self.clone();
+                let mut seen = Vec::with_capacity(4);
+                for (key, value) in params {
+                    if seen.contains(&key) {
+                        return Err(format!("duplicate permessage-deflate parameter: {key}"));
+                    }
+                    seen.push(key);
+                    match (key, value) {
+                        ("server_no_context_takeover", None) => {
+                            accepted.server_no_context_takeover = true
+                        }
+                        ("client_no_context_takeover", None) => {
+                            accepted.client_no_context_takeover = true
+                        }
+                        ("server_max_window_bits", Some(bits)) => {
+                            parse_window_bits(bits)
+                                .ok_or_else(|| format!("invalid server_max_window_bits: {bits}"))?;
+                        }
+                        ("client_max_window_bits", Some(bits)) => {
+                            if parse_window_bits(bits) != Some(MAX_WINDOW_BITS) {
+                                return Err(format!("unsupported client_max_window_bits: {bits}"));
+                            }
+                        }
+                        (key, _) => {
+                            return Err(format!("invalid permessage-deflate parameter: {key}"));
+                        }
+                    }
+                }
+                negotiated = Some(accepted);
+            }
+        }
+        Ok(negotiated)
+    }
+}
+
+impl fmt::Display for PerMessageDeflateConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(PER_MESSAGE_DEFLATE)?;
+        if self.server_no_context_takeover {
+            f.write_str("; server_no_context_takeover")?;
+        }
+        if self.client_no_context_takeover {
+            f.write_str("; client_no_context_takeover")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a single extension (e.g. `permessage-deflate; client_max_window_bits`),
+/// returning its parameters in case it has the given name.
+fn parse_extension<'a>(
+    extension: &'a str,
+    name: &str,
+) -> Option<impl Iterator<Item = (&'a str, Option<&'a str>)>> {
+    let mut parts = extension.split(';').map(str::trim);
+    if !parts.next()?.eq_ignore_ascii_case(name) {
+        return None;
+    }
+    Some(
+        parts
+            .filter(|part| !part.is_empty())
+            .map(|part| match part.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim().trim_matches('"'))),
+                None => (part, None),
+            }),
+    )
+}
+
+fn parse_window_bits(value: &str) -> Option<u8> {
+    value
+        .parse::<u8>()
+        .ok()
+        .filter(|bits| (8..=MAX_WINDOW_BITS).contains(bits))
+}
+
+/// The compression contexts of a WebSocket for which
+/// the per-message-deflate extension was negotiated.
+pub(crate) struct DeflateContext {
+    role: Role,
+    config: PerMessageDeflateConfig,
+    compress: Compress,
+    decompress: Decompress,
+}
+
+impl fmt::Debug for DeflateContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeflateContext")
+            .field("role", &self.role)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl DeflateContext {
+    pub(crate) fn new(role: Role, config: PerMessageDeflateConfig) -> Self {
+        Self {
+            role,
+            config,
+            compress: Compress::new(Compression::default(), false),
+            decompress: Decompress::new(false),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &PerMessageDeflateConfig {
+        &self.config
+    }
+
+    fn local_no_context_takeover(&self) -> bool {
+        match self.role {
+            Role::Server => self.config.server_no_context_takeover,
+            Role::Client => self.config.client_no_context_takeover,
+        }
+    }
+
+    fn remote_no_context_takeover(&self) -> bool {
+        match self.role {
+            Role::Server => self.config.client_no_context_takeover,
+            Role::Client => self.config.server_no_context_takeover,
+        }
+    }
+
+    /// Compress the payload of a (complete) message to be sent.
+    pub(crate) fn compress(&mut self, data: &[u8]) -> Result<Bytes, ProtocolError> {
+        let mut output = Vec::with_capacity(data.len() / 2 + 64);
+        let start = self.compress.total_in();
+        loop {
+            let consumed = (self.compress.total_in() - start) as usize;
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity().max(64));
+            }
+            let before_out = self.compress.total_out();
+            self.compress
+                .compress_vec(&data[consumed..], &mut output, FlushCompress::Sync)
+                .map_err(|err| ProtocolError::Compression(err.to_string()))?;
+            let consumed = (self.compress.total_in() - start) as usize;
+            if consumed == data.len() && output.len() < output.capacity() {
+                break;
+            }
+            if consumed == 0 && self.compress.total_out() == before_out && !data.is_empty() {
+                return Err(ProtocolError::Compression(
+                    "compressor made no progress".to_owned(),
+                ));
+            }
+        }
+
+        if output.ends_with(&DEFLATE_TRAILER) {
+            output.truncate(output.len() - DEFLATE_TRAILER.len());
+        }
+        if self.local_no_context_takeover() {
+            self.compress.reset();
+        }
+        Ok(output.into())
+    }
+
+    /// Decompress the payload of a (complete) message received.
+    pub(crate) fn decompress(
+        &mut self,
+        data: &[u8],
+        max_size: Option<usize>,
+    ) -> Result<Bytes, ProtocolError> {
+        let mut input = Vec::with_capacity(data.len() + DEFLATE_TRAILER.len());
+        input.extend_from_slice(data);
+        input.extend_from_slice(&DEFLATE_TRAILER);
+
+        let mut output = Vec::with_capacity(data.len() * 2 + 64);
+        let start = self.decompress.total_in();
+        loop {
+            let consumed = (self.decompress.total_in() - start) as usize;
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity().max(64));
+            }
+            let before_out = self.decompress.total_out();
+            let status = self
+                .decompress
+                .decompress_vec(&input[consumed..], &mut output, FlushDecompress::Sync)
+                .map_err(|err| ProtocolError::Compression(err.to_string()))?;
+            if let Some(max) = max_size {
+                if output.len() > max {
+                    return Err(ProtocolError::MessageTooLarge {
+                        size: output.len(),
+                        max,
+                    });
+                }
+            }
+            let new_consumed = (self.decompress.total_in() - start) as usize;
+            if (new_consumed == input.len() && output.len() < output.capacity())
+                || status == Status::StreamEnd
+            {
+                break;
+            }
+            if new_consumed == consumed && self.decompress.total_out() == before_out {
+                return Err(ProtocolError::Compression(
+                    "decompressor made no progress".to_owned(),
+                ));
+            }
+        }
+
+        if self.remote_no_context_takeover() {
+            self.decompress.reset(false);
+        }
+        Ok(output.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_offers() {
+        let config = PerMessageDeflateConfig::default();
+        let offers = [
+            HeaderValue::from_static("x-webkit-deflate-frame"),
+            HeaderValue::from_static(
+                "permessage-deflate; server_max_window_bits=10, permessage-deflate; client_max_window_bits; server_no_context_takeover",
+            ),
+        ];
+        assert_eq!(
+            config.negotiate_offers(&offers),
+            Some(PerMessageDeflateConfig {
+                server_no_context_takeover: true,
+                client_no_context_takeover: false,
+            })
+        );
+
+        let offers = [HeaderValue::from_static("permessage-deflate; foo")];
+        assert_eq!(config.negotiate_offers(&offers), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let config = PerMessageDeflateConfig::default();
+        assert_eq!(config.parse_response(&[]), Ok(None));
+        assert_eq!(
+            config.parse_response(&[HeaderValue::from_static(
+                "permessage-deflate; client_no_context_takeover; server_max_window_bits=12"
+            )]),
+            Ok(Some(PerMessageDeflateConfig {
+                server_no_context_takeover: false,
+                client_no_context_takeover: true,
+            }))
+        );
+        assert!(
+            config
+                .parse_response(&[HeaderValue::from_static(
+                    "permessage-deflate; client_max_window_bits=10"
+                )])
+                .is_err()
+        );
+        assert!(
+            config
+                .parse_response(&[HeaderValue::from_static("x-unknown")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_config_display() {
+        assert_eq!(
+            PerMessageDeflateConfig::new()
+                .with_client_no_context_takeover(true)
+                .to_string(),
+            "permessage-deflate; client_no_context_takeover"
+        );
+    }
+
+    #[test]
+    fn test_compress_decompress_roundtrip() {
+        for no_context_takeover in [false, true] {
+            let config = PerMessageDeflateConfig {
+                server_no_context_takeover: no_context_takeover,
+                client_no_context_takeover: no_context_takeover,
+            };
+            let mut client = DeflateContext::new(Role::Client, config.clone());
+            let mut server = DeflateContext::new(Role::Server, config);
+
+            for msg in [&b"Hello"[..], b"Hello", &[0u8; 100_000], b""] {
+                let compressed = client.compress(msg).unwrap();
+                assert!(!compressed.ends_with(&DEFLATE_TRAILER));
+                let decompressed = server.decompress(&compressed, None).unwrap();
+                assert_eq!(&decompressed[..], msg);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompress_rfc_example() {
+        // example from RFC 7692 section 7.2.3.1
+        let mut ctx = DeflateContext::new(Role::Client, PerMessageDeflateConfig::default());
+        let decompressed = ctx
+            .decompress(&[0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00], None)
+            .unwrap();
+        assert_eq!(&decompressed[..], b"Hello");
+    }
+
+    #[test]
+    fn test_decompress_max_size() {
+        let mut client = DeflateContext::new(Role::Client, PerMessageDeflateConfig::default());
+        let mut server = DeflateContext::new(Role::Server, PerMessageDeflateConfig::default());
+        let compressed = client.compress(&[0u8; 10_000]).unwrap();
+        assert!(matches!(
+            server.decompress(&compressed, Some(1_000)),
+            Err(ProtocolError::MessageTooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+//! WebSocket opening handshake utilities.
+//!
+//! Reference: <https://datatracker.ietf.org/doc/html/rfc6455#section-4>
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as ENGINE;
+use rama_http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Version, header};
+use sha1::{Digest, Sha1};
+
+/// The GUID appended to the `Sec-WebSocket-Key` to derive the `Sec-WebSocket-Accept` value.
+pub const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The (only) WebSocket version supported, as sent in the `Sec-WebSocket-Version` header.
+pub const WEBSOCKET_VERSION: HeaderValue = HeaderValue::from_static("13");
+
+/// The protocol name used in the `Upgrade` header.
+pub const WEBSOCKET_UPGRADE: HeaderValue = HeaderValue::from_static("websocket");
+
+/// Generate a random `Sec-WebSocket-Key` header value,
+/// as sent by a client.
+pub fn generate_key() -> HeaderValue {
+    let nonce: [u8; 16] = rand::random();
+    HeaderValue::from_str(&ENGINE.encode(nonce)).expect("base64 is a valid header value")
+}
+
+/// Derive the `Sec-WebSocket-Accept` header value,
+/// as sent by a server, from the `Sec-WebSocket-Key` sent by the client.
+pub fn derive_accept_key(key: &[u8]) -> HeaderValue {
+    let mut sha1 = Sha1::new();
+    sha1.update(key);
+    sha1.update(WEBSOCKET_GUID.as_bytes());
+    HeaderValue::from_str(&ENGINE.encode(sha1.finalize())).expect("base64 is a valid header value")
+}
+
+/// Returns `true` in case the `Sec-WebSocket-Key` is valid,
+/// meaning it is a base64-encoded 16-byte value.
+pub fn is_valid_key(key: &HeaderValue) -> bool {
+    key.len() == 24
+        && ENGINE
+            .decode(key.as_bytes())
+            .is_ok_and(|nonce| nonce.len() == 16)
+}
+
+/// Returns `true` in case the request is a WebSocket upgrade request,
+/// meaning it is an HTTP/1.1 `GET` request with the
+/// `Connection: upgrade` and `Upgrade: websocket` headers.
+///
+/// Useful to route WebSocket upgrade requests to a dedicated service,
+/// e.g. a [`WebSocketRelay`] as part of a MITM proxy.
+///
+/// [`WebSocketRelay`]: crate::relay::WebSocketRelay
+pub fn is_websocket_upgrade_request<B>(req: &Request<B>) -> bool {
+    req.method() == Method::GET
+        && req.version() == Version::HTTP_11
+        && is_websocket_upgrade(req.headers())
+}
+
+/// Returns `true` in case any of the comma-separated values
+/// of the given header equals the given token (case-insensitive).
+pub(crate) fn header_contains_token(headers: &HeaderMap, name: &HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(token))
+}
+
+/// Returns `true` in case the headers contain the
+/// `Connection: upgrade` and `Upgrade: websocket` headers.
+pub(crate) fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    header_contains_token(headers, &header::CONNECTION, "upgrade")
+        && header_contains_token(headers, &header::UPGRADE, "websocket")
+}
+
+/// Iterate over the comma-separated (trimmed) values of the given header.
+pub(crate) fn header_values<'a>(
+    headers: &'a HeaderMap,
+    name: &HeaderName,
+) -> impl Iterator<Item = &'a str> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_accept_key() {
+        // example from RFC 6455 section 1.3
+        assert_eq!(
+            derive_accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_generate_key() {
+        let key = generate_key();
+        assert!(is_valid_key(&key));
+        assert_ne!(key, generate_key());
+        assert!(!is_valid_key(&HeaderValue::from_static("foo")));
+    }
+
+    #[test]
+    fn test_is_websocket_upgrade() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONNECTION,
+            HeaderValue::from_static("keep-alive, Upgrade"),
+        );
+        headers.insert(header::UPGRADE, HeaderValue::from_static("WebSocket"));
+        assert!(is_websocket_upgrade(&headers));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("h2c"));
+        assert!(!is_websocket_upgrade(&headers));
+    }
+
+    #[test]
+    fn test_is_websocket_upgrade_request() {
+        let req = Request::builder()
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .body(())
+            .unwrap();
+        assert!(is_websocket_upgrade_request(&req));
+
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .body(())
+            .unwrap();
+        assert!(!is_websocket_upgrade_request(&req));
+    }
+}
@@ -0,0 +1,59 @@
+//! WebSocket support for Rama.
+//!
+//! - The WebSocket Protocol (RFC 6455): <https://datatracker.ietf.org/doc/html/rfc6455>
+//! - Compression Extensions for WebSocket (RFC 7692): <https://datatracker.ietf.org/doc/html/rfc7692>
+//!
+//! The [`WebSocketAcceptor`] can be used as an endpoint extractor
+//! to accept WebSocket connections on the server side,
+//! while the [`WebSocketConnector`] can be used to establish WebSocket connections
+//! as a client using any http client (e.g. the `EasyHttpWebClient`).
+//!
+//! Both result in a [`WebSocket`], which can be used to send and receive
+//! [`Message`]s, or, for lower level use cases, raw [`Frame`]s.
+//!
+//! The [`WebSocketRelay`] combines both in order to relay (and inspect)
+//! the messages of a WebSocket connection, e.g. as part of a MITM proxy.
+//!
+//! [`Message`]: protocol::Message
+//! [`Frame`]: protocol::Frame
+//!
+//! # Rama
+//!
+//! Crate used by the end-user `rama` crate and `rama` crate authors alike.
+//!
+//! Learn more about `rama`:
+//!
+//! - Github: <https://github.com/plabayo/rama>
+//! - Book: <https://ramaproxy.org/book/>
+
+#![doc(
+    html_favicon_url = "https://raw.githubusercontent.com/plabayo/rama/main/docs/img/old_logo.png"
+)]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/plabayo/rama/main/docs/img/old_logo.png")]
+#![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
+#![cfg_attr(test, allow(clippy::float_cmp))]
+#![cfg_attr(not(test), warn(clippy::print_stdout, clippy::dbg_macro))]
+
+pub mod protocol;
+
+pub mod handshake;
+
+mod deflate;
+#[doc(inline)]
+pub use deflate::PerMessageDeflateConfig;
+
+mod socket;
+#[doc(inline)]
+pub use socket::{WebSocket, WebSocketConfig};
+
+pub mod server;
+#[doc(inline)]
+pub use server::WebSocketAcceptor;
+
+pub mod client;
+#[doc(inline)]
+pub use client::WebSocketConnector;
+
+pub mod relay;
+#[doc(inline)]
+pub use relay::WebSocketRelay;
@@ -0,0 +1,79 @@
+use super::OpCode;
+use std::fmt;
+
+#[derive(Debug)]
+/// Error that can be returned while reading or writing WebSocket frames and messages.
+pub enum ProtocolError {
+    /// I/O error while reading or writing
+    IO(std::io::Error),
+    /// A frame with an unknown opcode was received.
+    InvalidOpCode(OpCode),
+    /// A frame was received with reserved bits set,
+    /// which were not negotiated by an extension.
+    ReservedBitsSet,
+    /// A frame was received with an unexpected masking,
+    /// e.g. an unmasked frame received by a server.
+    UnexpectedMasking,
+    /// A control frame was received which was fragmented
+    /// or had a payload larger than 125 bytes.
+    InvalidControlFrame,
+    /// A continuation frame was received without a message being started.
+    UnexpectedContinuation,
+    /// A new data frame was received while a fragmented message was not yet finished.
+    ExpectedContinuation,
+    /// A frame was received with an invalid payload length,
+    /// e.g. a 64-bit length with the most significant bit set.
+    InvalidPayloadLength,
+    /// A frame was received which exceeds the configured max frame size.
+    FrameTooLarge { size: usize, max: usize },
+    /// A message was received which exceeds the configured max message size.
+    MessageTooLarge { size: usize, max: usize },
+    /// A text message (or close reason) was received which is not valid UTF-8.
+    InvalidUtf8,
+    /// A close frame was received with an invalid payload.
+    InvalidCloseFrame,
+    /// The (per-message-deflate) compression or decompression of a message failed.
+    Compression(String),
+    /// A message was attempted to be sent after the connection was closed.
+    AlreadyClosed,
+}
+
+impl From<std::io::Error> for ProtocolError {
+    fn from(value: std::io::Error) -> Self {
+        Self::IO(value)
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "I/O error: {err}"),
+            Self::InvalidOpCode(opcode) => write!(f, "invalid opcode: {opcode}"),
+            Self::ReservedBitsSet => f.write_str("reserved bits set"),
+            Self::UnexpectedMasking => f.write_str("unexpected frame masking"),
+            Self::InvalidControlFrame => f.write_str("fragmented or too large control frame"),
+            Self::UnexpectedContinuation => f.write_str("unexpected continuation frame"),
+            Self::ExpectedContinuation => f.write_str("expected continuation frame"),
+            Self::InvalidPayloadLength => f.write_str("invalid frame payload length"),
+            Self::FrameTooLarge { size, max } => {
+                write!(f, "frame too large: {size} bytes (max {max} bytes)")
+            }
+            Self::MessageTooLarge { size, max } => {
+                write!(f, "message too large: {size} bytes (max {max} bytes)")
+            }
+            Self::InvalidUtf8 => f.write_str("invalid utf-8"),
+            Self::InvalidCloseFrame => f.write_str("invalid close frame"),
+            Self::Compression(reason) => write!(f, "compression error: {reason}"),
+            Self::AlreadyClosed => f.write_str("connection already closed"),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IO(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use super::{OpCode, ProtocolError};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Maximum amount of bytes reserved up front for an incomplete frame,
+/// such that the (peer-defined) payload length cannot trigger huge allocations.
+const MAX_RESERVE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The header of a WebSocket [`Frame`].
+///
+/// ```plain
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-------+-+-------------+-------------------------------+
+/// |F|R|R|R| opcode|M| Payload len |    Extended payload length    |
+/// |I|S|S|S|  (4)  |A|     (7)     |             (16/64)           |
+/// |N|V|V|V|       |S|             |   (if payload len==126/127)   |
+/// | |1|2|3|       |K|             |                               |
+/// +-+-+-+-+-------+-+-------------+ - - - - - - - - - - - - - - - +
+/// |     Extended payload length continued, if payload len == 127  |
+/// + - - - - - - - - - - - - - - - +-------------------------------+
+/// |                               |Masking-key, if MASK set to 1  |
+/// +-------------------------------+-------------------------------+
+/// | Masking-key (continued)       |          Payload Data         |
+/// +-------------------------------- - - - - - - - - - - - - - - - +
+/// ```
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc6455#section-5.2>
+pub struct FrameHeader {
+    pub fin: bool,
+    pub rsv1: bool,
+    pub rsv2: bool,
+    pub rsv3: bool,
+    pub opcode: OpCode,
+    pub mask: Option<[u8; 4]>,
+}
+
+impl FrameHeader {
+    /// Create a new [`FrameHeader`] for a final (unmasked) frame with the given [`OpCode`].
+    pub fn new(opcode: OpCode) -> Self {
+        Self {
+            fin: true,
+            rsv1: false,
+            rsv2: false,
+            rsv3: false,
+            opcode,
+            mask: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A single WebSocket frame.
+///
+/// The payload is always stored unmasked, masking is applied
+/// (as defined by the [`FrameHeader`]) only when the frame is written.
+pub struct Frame {
+    pub header: FrameHeader,
+    pub payload: Bytes,
+}
+
+impl Frame {
+    /// Create a new final (unmasked) [`Frame`] with the given [`OpCode`] and payload.
+    pub fn new(opcode: OpCode, payload: impl Into<Bytes>) -> Self {
+        Self {
+            header: FrameHeader::new(opcode),
+            payload: payload.into(),
+        }
+    }
+
+    /// Try to parse a [`Frame`] from the given buffer.
+    ///
+    /// Returns `None` in case the buffer does not yet contain a complete frame,
+    /// in which case the buffer is left untouched.
+    pub fn parse(
+        buf: &mut BytesMut,
+        max_payload_len: Option<usize>,
+    ) -> Result<Option<Self>, ProtocolError> {
+        if buf.len() < 2 {
+            return Ok(None);
+        }
+
+        let first = buf[0];
+        let second = buf[1];
+
+        let (payload_len, mut header_len): (u64, usize) = match second & 0x7F {
+            126 => {
+                if buf.len() < 4 {
+                    return Ok(None);
+                }
+                (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4)
+            }
+            127 => {
+                if buf.len() < 10 {
+                    return Ok(None);
+                }
+                let mut len = [0u8; 8];
+                len.copy_from_slice(&buf[2..10]);
+                let len = u64::from_be_bytes(len);
+                // the most significant bit must be 0 (RFC 6455, section 5.2)
+                if len & (1 << 63) != 0 {
+                    return Err(ProtocolError::InvalidPayloadLength);
+                }
+                (len, 10)
+            }
+            len => (len as u64, 2),
+        };
+
+        let payload_len = usize::try_from(payload_len).unwrap_or(usize::MAX);
+        if let Some(max) = max_payload_len {
+            if payload_len > max {
+                return Err(ProtocolError::FrameTooLarge {
+                    size: payload_len,
+                    max,
+                });
+            }
+        }
+
+        let masked = second & 0x80 != 0;
+        if masked {
+            header_len += 4;
+        }
+
+        let frame_len = header_len.saturating_add(payload_len);
+        if buf.len() < frame_len {
+            buf.reserve((frame_len - buf.len()).min(MAX_RESERVE_SIZE));
+            return Ok(None);
+        }
+
+        let mask = masked.then(|| {
+            let offset = header_len - 4;
+            [
+                buf[offset],
+                buf[offset + 1],
+                buf[offset + 2],
+                buf[offset + 3],
+            ]
+        });
+
+        buf.advance(header_len);
+        let mut payload = buf.split_to(payload_len);
+        if let Some(mask) = mask {
+            apply_mask(&mut payload, mask);
+        }
+
+        Ok(Some(Self {
+            header: FrameHeader {
+                fin: first & 0x80 != 0,
+                rsv1: first & 0x40 != 0,
+                rsv2: first & 0x20 != 0,
+                rsv3: first & 0x10 != 0,
+                opcode: (first & 0x0F).into(),
+                mask,
+            },
+            payload: payload.freeze(),
+        }))
+    }
+
+    /// Write the [`Frame`] to the given buffer,
+    /// masking the payload in case a mask is defined in the [`FrameHeader`].
+    ///
+    /// The payload is masked in place, within the given buffer.
+    pub fn write_to_buf(&self, buf: &mut BytesMut) {
+        let header = &self.header;
+        let mut first = u8::from(header.opcode) & 0x0F;
+        if header.fin {
+            first |= 0x80;
+        }
+        if header.rsv1 {
+            first |= 0x40;
+        }
+        if header.rsv2 {
+            first |= 0x20;
+        }
+        if header.rsv3 {
+            first |= 0x10;
+        }
+        buf.put_u8(first);
+
+        let mask_bit = if header.mask.is_some() { 0x80 } else { 0 };
+        let len = self.payload.len();
+        if len < 126 {
+            buf.put_u8(mask_bit | len as u8);
+        } else if let Ok(len) = u16::try_from(len) {
+            buf.put_u8(mask_bit | 126);
+            buf.put_u16(len);
+        } else {
+            buf.put_u8(mask_bit | 127);
+            buf.put_u64(len as u64);
+        }
+
+        match header.mask {
+            Some(mask) => {
+                buf.put_slice(&mask);
+                let offset = buf.len();
+                buf.put_slice(&self.payload);
+                apply_mask(&mut buf[offset..], mask);
+            }
+            None => buf.put_slice(&self.payload),
+        }
+    }
+
+    /// Length of the [`Frame`] in bytes, once serialized.
+    pub fn serialized_len(&self) -> usize {
+        let len = self.payload.len();
+        let len_size = if len < 126 {
+            0
+        } else if len <= u16::MAX as usize {
+            2
+        } else {
+            8
+        };
+        let mask_size = if self.header.mask.is_some() { 4 } else { 0 };
+        2 + len_size + mask_size + len
+    }
+}
+
+/// Apply the given masking key to the data,
+/// which both masks and unmasks the data.
+pub fn apply_mask(data: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte ^= mask[i & 3];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_masked_text_frame() {
+        // example from RFC 6455 section 5.7
+        let mut buf = BytesMut::from(
+            &[
+                0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+            ][..],
+        );
+        let frame = Frame::parse(&mut buf, None).unwrap().unwrap();
+        assert!(buf.is_empty());
+        assert!(frame.header.fin);
+        assert_eq!(frame.header.opcode, OpCode::Text);
+        assert_eq!(frame.header.mask, Some([0x37, 0xfa, 0x21, 0x3d]));
+        assert_eq!(frame.payload, "Hello");
+    }
+
+    #[test]
+    fn test_parse_incomplete_frame() {
+        let mut buf = BytesMut::from(&[0x81, 0x05, b'H', b'e'][..]);
+        assert!(Frame::parse(&mut buf, None).unwrap().is_none());
+        assert_eq!(buf.len(), 4);
+        buf.extend_from_slice(b"llo");
+        let frame = Frame::parse(&mut buf, None).unwrap().unwrap();
+        assert_eq!(frame.payload, "Hello");
+    }
+
+    #[test]
+    fn test_parse_frame_too_large() {
+        let mut buf = BytesMut::from(&[0x82, 0x7E, 0x01, 0x00][..]);
+        assert!(matches!(
+            Frame::parse(&mut buf, Some(255)),
+            Err(ProtocolError::FrameTooLarge {
+                size: 256,
+                max: 255
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_frame_invalid_payload_length() {
+        let mut buf = BytesMut::from(&[0x82, 0x7F, 0x80, 0, 0, 0, 0, 0, 0, 0][..]);
+        assert!(matches!(
+            Frame::parse(&mut buf, None),
+            Err(ProtocolError::InvalidPayloadLength)
+        ));
+    }
+
+    #[test]
+    fn test_parse_frame_huge_payload_length() {
+        let mut buf =
+            BytesMut::from(&[0x82, 0x7F, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF][..]);
+        assert!(Frame::parse(&mut buf, None).unwrap().is_none());
+        assert!(buf.capacity() <= 10 + MAX_RESERVE_SIZE);
+    }
+
+    #[test]
+    fn test_frame_roundtrip() {
+        for (len, mask) in [
+            (0, None),
+            (125, Some([1, 2, 3, 4])),
+            (126, None),
+            (65535, Some([5, 6, 7, 8])),
+            (65536, None),
+        ] {
+            let mut frame = Frame::new(OpCode::Binary, vec![0xAB; len]);
+            frame.header.fin = false;
+            frame.header.rsv1 = true;
+            frame.header.mask = mask;
+
+            let mut buf = BytesMut::new();
+            frame.write_to_buf(&mut buf);
+            assert_eq!(buf.len(), frame.serialized_len());
+
+            let parsed = Frame::parse(&mut buf, None).unwrap().unwrap();
+            assert!(buf.is_empty());
+            assert_eq!(parsed, frame);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use super::{CloseCode, OpCode, ProtocolError};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A (complete) WebSocket message,
+/// reassembled from one or multiple [`Frame`]s.
+///
+/// [`Frame`]: super::Frame
+pub enum Message {
+    /// A text message, guaranteed to be valid utf-8.
+    Text(String),
+    /// A binary message.
+    Binary(Bytes),
+    /// A ping control message, with optional application data.
+    Ping(Bytes),
+    /// A pong control message, with optional application data.
+    Pong(Bytes),
+    /// A close control message, with optional close frame.
+    Close(Option<CloseFrame>),
+}
+
+impl Message {
+    /// Create a new text [`Message`].
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Create a new binary [`Message`].
+    pub fn binary(data: impl Into<Bytes>) -> Self {
+        Self::Binary(data.into())
+    }
+
+    /// Returns the [`OpCode`] used to send this [`Message`].
+    pub fn opcode(&self) -> OpCode {
+        match self {
+            Self::Text(_) => OpCode::Text,
+            Self::Binary(_) => OpCode::Binary,
+            Self::Ping(_) => OpCode::Ping,
+            Self::Pong(_) => OpCode::Pong,
+            Self::Close(_) => OpCode::Close,
+        }
+    }
+
+    /// Returns `true` in case this is a control [`Message`].
+    pub fn is_control(&self) -> bool {
+        self.opcode().is_control()
+    }
+
+    /// Returns the length of the (unencoded) payload of this [`Message`].
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Text(text) => text.len(),
+            Self::Binary(data) | Self::Ping(data) | Self::Pong(data) => data.len(),
+            Self::Close(frame) => frame
+                .as_ref()
+                .map(|f| 2 + f.reason.len())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns `true` in case the payload of this [`Message`] is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Consume the [`Message`] into its encoded payload.
+    pub fn into_payload(self) -> Bytes {
+        match self {
+            Self::Text(text) => text.into(),
+            Self::Binary(data) | Self::Ping(data) | Self::Pong(data) => data,
+            Self::Close(None) => Bytes::new(),
+            Self::Close(Some(frame)) => frame.to_payload(),
+        }
+    }
+
+    /// Create a [`Message`] from a (complete and unmasked) payload
+    /// received for the given (non-continuation) [`OpCode`].
+    pub fn from_payload(opcode: OpCode, payload: Bytes) -> Result<Self, ProtocolError> {
+        match opcode {
+            OpCode::Text => String::from_utf8(payload.into())
+                .map(Self::Text)
+                .map_err(|_| ProtocolError::InvalidUtf8),
+            OpCode::Binary => Ok(Self::Binary(payload)),
+            OpCode::Ping => Ok(Self::Ping(payload)),
+            OpCode::Pong => Ok(Self::Pong(payload)),
+            OpCode::Close => CloseFrame::parse(&payload).map(Self::Close),
+            opcode => Err(ProtocolError::InvalidOpCode(opcode)),
+        }
+    }
+}
+
+impl From<String> for Message {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Message {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<Bytes> for Message {
+    fn from(value: Bytes) -> Self {
+        Self::Binary(value)
+    }
+}
+
+impl From<Vec<u8>> for Message {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Binary(value.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The (optional) body of a close control frame.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc6455#section-5.5.1>
+pub struct CloseFrame {
+    pub code: CloseCode,
+    pub reason: String,
+}
+
+impl CloseFrame {
+    /// Create a new [`CloseFrame`] for the given [`CloseCode`] and reason.
+    pub fn new(code: CloseCode, reason: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+
+    /// Parse the (optional) [`CloseFrame`] from the payload of a close frame.
+    pub fn parse(payload: &[u8]) -> Result<Option<Self>, ProtocolError> {
+        match payload {
+            [] => Ok(None),
+            [_] => Err(ProtocolError::InvalidCloseFrame),
+            [hi, lo, reason @ ..] => {
+                let code = CloseCode::from(u16::from_be_bytes([*hi, *lo]));
+                if !code.is_allowed() {
+                    return Err(ProtocolError::InvalidCloseFrame);
+                }
+                let reason = std::str::from_utf8(reason)
+                    .map_err(|_| ProtocolError::InvalidUtf8)?
+                    .to_owned();
+                Ok(Some(Self { code, reason }))
+            }
+        }
+    }
+
+    /// Encode the [`CloseFrame`] as the payload of a close frame.
+    pub fn to_payload(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(2 + self.reason.len());
+        buf.put_u16(self.code.into());
+        buf.put_slice(self.reason.as_bytes());
+        buf.freeze()
+    }
+}
+
+impl fmt::Display for CloseFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.code, self.reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_frame_roundtrip() {
+        let frame = CloseFrame::new(CloseCode::GoingAway, "bye");
+        let payload = frame.to_payload();
+        assert_eq!(&payload[..], &[0x03, 0xE9, b'b', b'y', b'e']);
+        assert_eq!(CloseFrame::parse(&payload).unwrap(), Some(frame));
+        assert_eq!(CloseFrame::parse(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_close_frame_invalid() {
+        assert!(CloseFrame::parse(&[0x03]).is_err());
+        // 1005 (no status received) may not be sent over the wire
+        assert!(CloseFrame::parse(&[0x03, 0xED]).is_err());
+        assert!(CloseFrame::parse(&[0x03, 0xE8, 0xFF]).is_err());
+    }
+
+    #[test]
+    fn test_message_from_payload() {
+        assert_eq!(
+            Message::from_payload(OpCode::Text, Bytes::from_static(b"hi")).unwrap(),
+            Message::text("hi")
+        );
+        assert!(matches!(
+            Message::from_payload(OpCode::Text, Bytes::from_static(&[0xFF])),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+        assert!(Message::from_payload(OpCode::Continue, Bytes::new()).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! WebSocket protocol implementation details.
+//!
+//! - The WebSocket Protocol (RFC 6455): <https://datatracker.ietf.org/doc/html/rfc6455>
+
+use rama_utils::macros::enums::enum_builder;
+
+mod error;
+#[doc(inline)]
+pub use error::ProtocolError;
+
+mod frame;
+#[doc(inline)]
+pub use frame::{Frame, FrameHeader, apply_mask};
+
+mod message;
+#[doc(inline)]
+pub use message::{CloseFrame, Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The role of a WebSocket endpoint.
+///
+/// Frames sent by a client are masked,
+/// while frames sent by a server are not.
+pub enum Role {
+    /// The server side of a WebSocket connection.
+    Server,
+    /// The client side of a WebSocket connection.
+    Client,
+}
+
+enum_builder! {
+    /// The opcode of a WebSocket [`Frame`],
+    /// defining the interpretation of its payload.
+    ///
+    /// Reference: <https://datatracker.ietf.org/doc/html/rfc6455#section-5.2>
+    @U8
+    pub enum OpCode {
+        Continue => 0x0,
+        Text => 0x1,
+        Binary => 0x2,
+        Close => 0x8,
+        Ping => 0x9,
+        Pong => 0xA,
+    }
+}
+
+impl OpCode {
+    /// Returns `true` in case this is the opcode of a control frame.
+    pub fn is_control(&self) -> bool {
+        u8::from(*self) & 0x8 != 0
+    }
+}
+
+enum_builder! {
+    /// Status code used to indicate the reason for closing a WebSocket connection.
+    ///
+    /// Values as assigned by IANA:
+    /// <https://www.iana.org/assignments/websocket/websocket.xhtml#close-code-number>
+    @U16
+    pub enum CloseCode {
+        NormalClosure => 1000,
+        GoingAway => 1001,
+        ProtocolError => 1002,
+        UnsupportedData => 1003,
+        NoStatusReceived => 1005,
+        AbnormalClosure => 1006,
+        InvalidPayloadData => 1007,
+        PolicyViolation => 1008,
+        MessageTooBig => 1009,
+        MandatoryExtension => 1010,
+        InternalError => 1011,
+        ServiceRestart => 1012,
+        TryAgainLater => 1013,
+        BadGateway => 1014,
+        TlsHandshake => 1015,
+    }
+}
+
+impl CloseCode {
+    /// Returns `true` in case this [`CloseCode`] is allowed
+    /// to be sent as part of a close frame.
+    ///
+    /// Reference: <https://datatracker.ietf.org/doc/html/rfc6455#section-7.4>
+    pub fn is_allowed(&self) -> bool {
+        match u16::from(*self) {
+            1000..=1003 | 1007..=1014 => true,
+            code => (3000..=4999).contains(&code),
+        }
+    }
+}
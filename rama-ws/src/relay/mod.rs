@@ -0,0 +1,545 @@
+//! WebSocket relay support, e.g. for MITM proxies.
+//!
+//! See [`WebSocketRelay`] for more information.
+
+use crate::client::WebSocketConnector;
+use crate::deflate::PerMessageDeflateConfig;
+use crate::protocol::{CloseCode, CloseFrame, Message};
+use crate::server::WebSocketAcceptor;
+use crate::{WebSocket, WebSocketConfig};
+use rama_core::{
+    Context, Service,
+    error::{BoxError, OpaqueError},
+};
+use rama_http::service::web::extract::FromRequestContextRefPair;
+use rama_http::{Body, HeaderValue, IntoResponse, Request, Response, StatusCode, header};
+use rama_http_core::upgrade::Upgraded;
+use std::{convert::Infallible, fmt, sync::Arc, time::Duration};
+
+mod writer;
+#[doc(inline)]
+pub use writer::{RelayMessageWriterLayer, RelayMessageWriterService};
+
+/// Default time to wait for the close handshake to complete,
+/// after relaying a close message.
+const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The direction in which a [`RelayMessage`] is relayed.
+pub enum RelayDirection {
+    /// The message is relayed from the client to the server.
+    ClientToServer,
+    /// The message is relayed from the server to the client.
+    ServerToClient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A (data) [`Message`] relayed by the [`WebSocketRelay`].
+pub struct RelayMessage {
+    /// The direction in which the message is relayed.
+    pub direction: RelayDirection,
+    /// The relayed message.
+    pub message: Message,
+}
+
+impl RelayMessage {
+    /// Create a new [`RelayMessage`].
+    pub fn new(direction: RelayDirection, message: impl Into<Message>) -> Self {
+        Self {
+            direction,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+/// Default message handler of the [`WebSocketRelay`],
+/// which forwards all messages as-is.
+pub struct ForwardMessage;
+
+impl ForwardMessage {
+    /// Create a new [`ForwardMessage`] handler.
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl<State> Service<State, RelayMessage> for ForwardMessage
+where
+    State: Clone + Send + Sync + 'static,
+{
+    type Response = Option<RelayMessage>;
+    type Error = Infallible;
+
+    async fn serve(
+        &self,
+        _ctx: Context<State>,
+        msg: RelayMessage,
+    ) -> Result<Self::Response, Self::Error> {
+        Ok(Some(msg))
+    }
+}
+
+/// A [`Service`] which relays a WebSocket connection between a client and a server,
+/// decoding the messages in both directions.
+///
+/// It accepts the WebSocket upgrade request of the client, establishes
+/// a WebSocket connection with the server using the upgrade request
+/// (through the inner http client [`Service`]), after which it relays
+/// the messages between both ends.
+///
+/// Each data (text or binary) message is passed through the message handler [`Service`],
+/// which returns the messages to be relayed (in either direction),
+/// allowing it to forward, modify, drop or inject messages.
+/// Use the [`RelayMessageWriterLayer`] to log the relayed messages.
+///
+/// Ping and pong messages are handled by each end of the relay and are not relayed,
+/// while a close message is relayed to the other end, ending the relay
+/// once the close handshake completed or the close timeout expired.
+///
+/// Sub protocols requested by the client are forwarded to the server,
+/// while extensions (e.g. per-message-deflate) are negotiated by each end separately.
+///
+/// Use [`is_websocket_upgrade_request`] to route WebSocket upgrade requests to this service,
+/// prior to any middleware which removes hop-by-hop headers.
+///
+/// [`is_websocket_upgrade_request`]: crate::handshake::is_websocket_upgrade_request
+pub struct WebSocketRelay<C, H = ForwardMessage> {
+    connector: WebSocketConnector<C>,
+    handler: Arc<H>,
+    config: WebSocketConfig,
+    per_message_deflate: Option<PerMessageDeflateConfig>,
+    close_timeout: Duration,
+}
+
+impl<C> WebSocketRelay<C> {
+    /// Create a new [`WebSocketRelay`], using the given http client [`Service`]
+    /// to establish the WebSocket connection with the server.
+    pub fn new(client: C) -> Self {
+        Self {
+            connector: WebSocketConnector::new(client),
+            handler: Arc::new(ForwardMessage),
+            config: WebSocketConfig::default(),
+            per_message_deflate: None,
+            close_timeout: DEFAULT_CLOSE_TIMEOUT,
+        }
+    }
+}
+
+impl<C, H> WebSocketRelay<C, H> {
+    /// Define the message handler [`Service`] used to handle each relayed data message.
+    pub fn with_handler<T>(self, handler: T) -> WebSocketRelay<C, T> {
+        WebSocketRelay {
+            connector: self.connector,
+            handler: Arc::new(handler),
+            config: self.config,
+            per_message_deflate: self.per_message_deflate,
+            close_timeout: self.close_timeout,
+        }
+    }
+
+    /// Define the [`WebSocketConfig`] used for both ends of the relay.
+    pub fn with_config(mut self, config: WebSocketConfig) -> Self {
+        self.connector.set_config(config.clone());
+        self.config = config;
+        self
+    }
+
+    /// Enable the per-message-deflate extension for both ends of the relay,
+    /// in case supported by the client and server respectively.
+    pub fn with_per_message_deflate(mut self, config: PerMessageDeflateConfig) -> Self {
+        self.connector.set_per_message_deflate(config.clone());
+        self.per_message_deflate = Some(config);
+        self
+    }
+
+    /// Define the max time to wait for the close handshake to complete,
+    /// after a close message was relayed to the other end.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn with_close_timeout(mut self, timeout: Duration) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+
+    /// Define the max time to wait for the close handshake to complete,
+    /// after a close message was relayed to the other end.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn set_close_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.close_timeout = timeout;
+        self
+    }
+}
+
+impl<C: fmt::Debug, H: fmt::Debug> fmt::Debug for WebSocketRelay<C, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketRelay")
+            .field("connector", &self.connector)
+            .field("handler", &self.handler)
+            .field("config", &self.config)
+            .field("per_message_deflate", &self.per_message_deflate)
+            .field("close_timeout", &self.close_timeout)
+            .finish()
+    }
+}
+
+impl<C: Clone, H> Clone for WebSocketRelay<C, H> {
+    fn clone(&self) -> Self {
+        Self {
+            connector: self.connector.clone(),
+            handler: self.handler.clone(),
+            config: self.config.clone(),
+            per_message_deflate: self.per_message_deflate.clone(),
+            close_timeout: self.close_timeout,
+        }
+    }
+}
+
+impl<State, C, H, I> Service<State, Request> for WebSocketRelay<C, H>
+where
+    State: Clone + Send + Sync + 'static,
+    C: Service<State, Request, Response = Response, Error: Into<BoxError>>,
+    H: Service<State, RelayMessage, Response = I, Error: Into<BoxError>>,
+    I: IntoIterator<Item = RelayMessage, IntoIter: Send> + Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+
+    async fn serve(&self, ctx: Context<State>, req: Request) -> Result<Response, Infallible> {
+        let (parts, _) = req.into_parts();
+        let acceptor = match WebSocketAcceptor::from_request_context_ref_pair(&ctx, &parts).await {
+            Ok(acceptor) => acceptor,
+            Err(rejection) => return Ok(rejection.into_response()),
+        };
+
+        let requested_protocols = acceptor
+            .requested_protocols()
+            .collect::<Vec<_>>()
+            .join(", ");
+        let requested_protocols = if requested_protocols.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&requested_protocols).ok()
+        };
+
+        let mut upstream_req = Request::new(Body::empty());
+        *upstream_req.method_mut() = parts.method;
+        *upstream_req.uri_mut() = parts.uri;
+        *upstream_req.version_mut() = parts.version;
+        *upstream_req.headers_mut() = parts.headers;
+        for name in [
+            header::SEC_WEBSOCKET_KEY,
+            header::SEC_WEBSOCKET_EXTENSIONS,
+            header::SEC_WEBSOCKET_PROTOCOL,
+        ] {
+            upstream_req.headers_mut().remove(name);
+        }
+
+        let server_socket = match self
+            .connector
+            .handshake(ctx.clone(), upstream_req, requested_protocols)
+            .await
+        {
+            Ok(server_socket) => server_socket,
+            Err(err) => {
+                tracing::debug!(error = %err, "websocket relay: failed to connect to server");
+                return Ok(StatusCode::BAD_GATEWAY.into_response());
+            }
+        };
+
+        let mut acceptor = acceptor.with_config(self.config.clone());
+        if let Some(protocol) = server_socket.protocol().and_then(|p| p.to_str().ok()) {
+            acceptor = acceptor.with_protocols([protocol]);
+        }
+        if let Some(config) = self.per_message_deflate.clone() {
+            acceptor = acceptor.with_per_message_deflate(config);
+        }
+
+        let handler = self.handler.clone();
+        let close_timeout = self.close_timeout;
+        let server_socket = server_socket.into_socket();
+        Ok(acceptor.on_upgrade(async move |client_socket| {
+            relay(ctx, handler, close_timeout, client_socket, server_socket).await;
+        }))
+    }
+}
+
+async fn relay<State, H, I>(
+    ctx: Context<State>,
+    handler: Arc<H>,
+    close_timeout: Duration,
+    mut client: WebSocket<Upgraded>,
+    mut server: WebSocket<Upgraded>,
+) where
+    State: Clone + Send + Sync + 'static,
+    H: Service<State, RelayMessage, Response = I, Error: Into<BoxError>>,
+    I: IntoIterator<Item = RelayMessage, IntoIter: Send> + Send + 'static,
+{
+    loop {
+        let (direction, result) = tokio::select! {
+            result = client.recv_message() => (RelayDirection::ClientToServer, result),
+            result = server.recv_message() => (RelayDirection::ServerToClient, result),
+        };
+        let target = match direction {
+            RelayDirection::ClientToServer => &mut server,
+            RelayDirection::ServerToClient => &mut client,
+        };
+
+        let message = match result {
+            Ok(Some(message)) => message,
+            Ok(None) => {
+                tracing::trace!(?direction, "websocket relay: connection closed");
+                let _ = target
+                    .close(Some(CloseFrame::new(CloseCode::GoingAway, "")))
+                    .await;
+                return;
+            }
+            Err(err) => {
+                tracing::debug!(?direction, error = %err, "websocket relay: failed to receive message");
+                let _ = target
+                    .close(Some(CloseFrame::new(CloseCode::GoingAway, "")))
+                    .await;
+                return;
+            }
+        };
+
+        match message {
+            Message::Ping(_) | Message::Pong(_) => (),
+            Message::Close(frame) => {
+                tracing::trace!(?direction, ?frame, "websocket relay: relay close");
+                if target.close(frame).await.is_ok() {
+                    // wait for the close handshake to be completed,
+                    // without waiting forever on a peer which never replies
+                    let handshake =
+                        async { while let Ok(Some(_)) = target.recv_message().await {} };
+                    if tokio::time::timeout(close_timeout, handshake)
+                        .await
+                        .is_err()
+                    {
+                        tracing::debug!(?direction, "websocket relay: close handshake timed out");
+                    }
+                }
+                return;
+            }
+            message @ (Message::Text(_) | Message::Binary(_)) => {
+                let messages = match handler
+                    .serve(ctx.clone(), RelayMessage { direction, message })
+                    .await
+                {
+                    Ok(messages) => messages,
+                    Err(err) => {
+                        let err = OpaqueError::from_boxed(err.into());
+                        tracing::debug!(?direction, error = %err, "websocket relay: message handler failed");
+                        let close = CloseFrame::new(CloseCode::InternalError, "");
+                        let _ = client.close(Some(close.clone())).await;
+                        let _ = server.close(Some(close)).await;
+                        return;
+                    }
+                };
+                for RelayMessage { direction, message } in messages {
+                    let target = match direction {
+                        RelayDirection::ClientToServer => &mut server,
+                        RelayDirection::ServerToClient => &mut client,
+                    };
+                    if let Err(err) = target.send_message(message).await {
+                        tracing::debug!(?direction, error = %err, "websocket relay: failed to send message");
+                        let close = CloseFrame::new(CloseCode::GoingAway, "");
+                        let _ = client.close(Some(close.clone())).await;
+                        let _ = server.close(Some(close)).await;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{Message, OpCode};
+    use rama_core::service::service_fn;
+    use rama_http::Uri;
+    use rama_http::service::web::WebService;
+    use rama_http_backend::client::EasyHttpWebClient;
+    use rama_http_backend::server::HttpServer;
+    use rama_tcp::server::TcpListener;
+    use std::net::SocketAddr;
+    use tokio::io::AsyncReadExt;
+
+    async fn spawn_server<S>(service: S) -> SocketAddr
+    where
+        S: Service<(), Request, Response = Response, Error = Infallible>,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(listener.serve(HttpServer::http1().service(service)));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_websocket_relay() {
+        let echo_addr = spawn_server(WebService::default().get(
+            "/echo",
+            async |ws: WebSocketAcceptor| {
+                ws.on_upgrade(async |mut socket| {
+                    while let Ok(Some(message)) = socket.recv_message().await {
+                        if matches!(message, Message::Text(_) | Message::Binary(_))
+                            && socket.send_message(message).await.is_err()
+                        {
+                            return;
+                        }
+                    }
+                })
+            },
+        ))
+        .await;
+
+        // route all upstream requests to the echo server
+        let client = service_fn(move |ctx, mut req: Request| {
+            *req.uri_mut() = Uri::try_from(format!("ws://{echo_addr}/echo")).unwrap();
+            async move { EasyHttpWebClient::default().serve(ctx, req).await }
+        });
+        let handler = service_fn(async |msg: RelayMessage| {
+            Ok::<_, Infallible>(match msg {
+                RelayMessage {
+                    direction: RelayDirection::ClientToServer,
+                    message: Message::Text(text),
+                } => vec![
+                    RelayMessage::new(RelayDirection::ServerToClient, "injected"),
+                    RelayMessage::new(RelayDirection::ClientToServer, text.to_uppercase()),
+                ],
+                RelayMessage {
+                    message: Message::Binary(_),
+                    ..
+                } => vec![],
+                msg => vec![msg],
+            })
+        });
+        let relay = WebSocketRelay::new(client)
+            .with_handler(handler)
+            .with_per_message_deflate(PerMessageDeflateConfig::default());
+        let relay_addr = spawn_server(relay).await;
+
+        let req = Request::builder()
+            .uri(format!("ws://{relay_addr}/echo"))
+            .body(Body::empty())
+            .unwrap();
+        let mut socket = WebSocketConnector::new(EasyHttpWebClient::default())
+            .serve(Context::default(), req)
+            .await
+            .unwrap()
+            .into_socket();
+
+        socket.send_message(Message::text("hello")).await.unwrap();
+        assert_eq!(
+            socket.recv_message().await.unwrap(),
+            Some(Message::text("injected"))
+        );
+        assert_eq!(
+            socket.recv_message().await.unwrap(),
+            Some(Message::text("HELLO"))
+        );
+
+        socket
+            .send_message(Message::binary(vec![1, 2, 3]))
+            .await
+            .unwrap();
+        socket.send_message(Message::text("bye")).await.unwrap();
+        assert_eq!(
+            socket.recv_message().await.unwrap(),
+            Some(Message::text("injected"))
+        );
+        assert_eq!(
+            socket.recv_message().await.unwrap(),
+            Some(Message::text("BYE"))
+        );
+
+        socket
+            .close(Some(CloseFrame::new(CloseCode::NormalClosure, "done")))
+            .await
+            .unwrap();
+        assert!(matches!(
+            socket.recv_message().await.unwrap(),
+            Some(Message::Close(Some(CloseFrame {
+                code: CloseCode::NormalClosure,
+                ..
+            })))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_websocket_relay_close_timeout() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = Arc::new(std::sync::Mutex::new(Some(tx)));
+        let silent_addr = spawn_server(WebService::default().get(
+            "/silent",
+            move |ws: WebSocketAcceptor| {
+                let tx = tx.clone();
+                async move {
+                    ws.on_upgrade(async move |mut socket| {
+                        // receive the close frame without ever replying to it
+                        while let Ok(Some(frame)) = socket.recv_frame().await {
+                            if frame.header.opcode == OpCode::Close {
+                                break;
+                            }
+                        }
+                        // the relay is expected to give up and drop the connection
+                        let mut buf = [0u8; 16];
+                        let eof = matches!(socket.get_mut().read(&mut buf).await, Ok(0) | Err(_));
+                        if let Some(tx) = tx.lock().unwrap().take() {
+                            let _ = tx.send(eof);
+                        }
+                    })
+                }
+            },
+        ))
+        .await;
+
+        let client = service_fn(move |ctx, mut req: Request| {
+            *req.uri_mut() = Uri::try_from(format!("ws://{silent_addr}/silent")).unwrap();
+            async move { EasyHttpWebClient::default().serve(ctx, req).await }
+        });
+        let relay = WebSocketRelay::new(client).with_close_timeout(Duration::from_millis(100));
+        let relay_addr = spawn_server(relay).await;
+
+        let req = Request::builder()
+            .uri(format!("ws://{relay_addr}/silent"))
+            .body(Body::empty())
+            .unwrap();
+        let mut socket = WebSocketConnector::new(EasyHttpWebClient::default())
+            .serve(Context::default(), req)
+            .await
+            .unwrap()
+            .into_socket();
+
+        socket
+            .close(Some(CloseFrame::new(CloseCode::NormalClosure, "")))
+            .await
+            .unwrap();
+        assert!(matches!(
+            socket.recv_message().await.unwrap(),
+            Some(Message::Close(_))
+        ));
+
+        let eof = tokio::time::timeout(Duration::from_secs(5), rx)
+            .await
+            .expect("relay to end within the close timeout")
+            .unwrap();
+        assert!(eof);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_relay_rejects_non_upgrade_request() {
+        let relay = WebSocketRelay::new(service_fn(async |_req: Request| {
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+        let resp = relay
+            .serve(Context::default(), Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
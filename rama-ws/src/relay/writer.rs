@@ -0,0 +1,159 @@
+use super::{RelayDirection, RelayMessage};
+use crate::protocol::Message;
+use rama_core::{Context, Layer, Service};
+use rama_http::layer::traffic_writer::{MessageDirection, MessageWriter, UpgradedMessage};
+use rama_utils::macros::define_inner_service_accessors;
+use std::fmt;
+
+/// Layer that applies [`RelayMessageWriterService`],
+/// which writes each relayed message using a [`MessageWriter`].
+///
+/// The [`BidirectionalWriter`] can for example be used to write
+/// the WebSocket messages alongside the http requests and responses.
+///
+/// [`BidirectionalWriter`]: rama_http::layer::traffic_writer::BidirectionalWriter
+pub struct RelayMessageWriterLayer<W> {
+    writer: W,
+}
+
+impl<W> RelayMessageWriterLayer<W> {
+    /// Create a new [`RelayMessageWriterLayer`] with the given [`MessageWriter`].
+    pub const fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W> fmt::Debug for RelayMessageWriterLayer<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayMessageWriterLayer")
+            .field("writer", &format_args!("{}", std::any::type_name::<W>()))
+            .finish()
+    }
+}
+
+impl<W: Clone> Clone for RelayMessageWriterLayer<W> {
+    fn clone(&self) -> Self {
+        Self {
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+impl<S, W: Clone> Layer<S> for RelayMessageWriterLayer<W> {
+    type Service = RelayMessageWriterService<W, S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RelayMessageWriterService {
+            inner,
+            writer: self.writer.clone(),
+        }
+    }
+
+    fn into_layer(self, inner: S) -> Self::Service {
+        RelayMessageWriterService {
+            inner,
+            writer: self.writer,
+        }
+    }
+}
+
+/// Middleware which writes each [`RelayMessage`] using a [`MessageWriter`],
+/// prior to passing it to the inner message handler.
+pub struct RelayMessageWriterService<W, S> {
+    inner: S,
+    writer: W,
+}
+
+impl<W, S> RelayMessageWriterService<W, S> {
+    /// Create a new [`RelayMessageWriterService`].
+    pub const fn new(writer: W, inner: S) -> Self {
+        Self { inner, writer }
+    }
+
+    define_inner_service_accessors!();
+}
+
+impl<W, S: fmt::Debug> fmt::Debug for RelayMessageWriterService<W, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayMessageWriterService")
+            .field("inner", &self.inner)
+            .field("writer", &format_args!("{}", std::any::type_name::<W>()))
+            .finish()
+    }
+}
+
+impl<W: Clone, S: Clone> Clone for RelayMessageWriterService<W, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+impl<State, W, S> Service<State, RelayMessage> for RelayMessageWriterService<W, S>
+where
+    State: Clone + Send + Sync + 'static,
+    W: MessageWriter,
+    S: Service<State, RelayMessage>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    async fn serve(
+        &self,
+        ctx: Context<State>,
+        msg: RelayMessage,
+    ) -> Result<Self::Response, Self::Error> {
+        let (kind, payload) = match &msg.message {
+            Message::Text(text) => ("text", text.clone().into()),
+            Message::Binary(data) => ("binary", data.clone()),
+            Message::Ping(data) => ("ping", data.clone()),
+            Message::Pong(data) => ("pong", data.clone()),
+            Message::Close(frame) => (
+                "close",
+                frame
+                    .as_ref()
+                    .map(|frame| frame.to_string().into())
+                    .unwrap_or_default(),
+            ),
+        };
+        self.writer
+            .write_message(UpgradedMessage {
+                direction: match msg.direction {
+                    RelayDirection::ClientToServer => MessageDirection::ClientToServer,
+                    RelayDirection::ServerToClient => MessageDirection::ServerToClient,
+                },
+                protocol: "websocket",
+                kind,
+                payload,
+            })
+            .await;
+        self.inner.serve(ctx, msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relay::ForwardMessage;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn test_relay_message_writer() {
+        let (tx, mut rx) = unbounded_channel();
+        let service = RelayMessageWriterLayer::new(tx).into_layer(ForwardMessage::new());
+
+        let msg = RelayMessage::new(RelayDirection::ServerToClient, "hello");
+        let output = service
+            .serve(Context::default(), msg.clone())
+            .await
+            .unwrap();
+        assert_eq!(output, Some(msg));
+
+        let written = rx.recv().await.unwrap();
+        assert_eq!(written.direction, MessageDirection::ServerToClient);
+        assert_eq!(written.kind, "text");
+        assert_eq!(written.payload, "hello");
+    }
+}
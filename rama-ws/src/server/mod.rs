@@ -0,0 +1,334 @@
+//! WebSocket server support.
+//!
+//! See [`WebSocketAcceptor`] for more information.
+
+use crate::deflate::PerMessageDeflateConfig;
+use crate::handshake::{
+    WEBSOCKET_UPGRADE, WEBSOCKET_VERSION, derive_accept_key, header_values, is_valid_key,
+    is_websocket_upgrade,
+};
+use crate::protocol::Role;
+use crate::{WebSocket, WebSocketConfig};
+use rama_core::{Context, rt::Executor};
+use rama_http::dep::http::request::Parts;
+use rama_http::service::web::extract::FromRequestContextRefPair;
+use rama_http::{Body, HeaderValue, IntoResponse, Method, Response, StatusCode, Version, header};
+use rama_http_core::upgrade::{OnUpgrade, Upgraded};
+use std::fmt;
+
+/// Extractor used to accept a WebSocket upgrade request (over HTTP/1.1).
+///
+/// The opening handshake of the request is validated as part of the extraction,
+/// and the connection can be upgraded by returning the response created
+/// by [`WebSocketAcceptor::on_upgrade`] from the endpoint.
+///
+/// The per-message-deflate extension is only negotiated in case it is enabled
+/// using [`WebSocketAcceptor::with_per_message_deflate`].
+///
+/// # Example
+///
+/// ```
+/// use rama_http::service::web::WebService;
+/// use rama_ws::{WebSocketAcceptor, protocol::Message};
+///
+/// let service = WebService::<()>::default().get("/ws", async |ws: WebSocketAcceptor| {
+///     ws.with_protocols(["echo"]).on_upgrade(async |mut socket| {
+///         while let Ok(Some(message)) = socket.recv_message().await {
+///             if matches!(message, Message::Text(_) | Message::Binary(_)) {
+///                 if socket.send_message(message).await.is_err() {
+///                     return;
+///                 }
+///             }
+///         }
+///     })
+/// });
+/// # let _ = service;
+/// ```
+pub struct WebSocketAcceptor {
+    key: HeaderValue,
+    on_upgrade: OnUpgrade,
+    executor: Executor,
+    requested_protocols: Vec<String>,
+    offered_extensions: Vec<HeaderValue>,
+    protocol: Option<HeaderValue>,
+    per_message_deflate: Option<PerMessageDeflateConfig>,
+    config: WebSocketConfig,
+}
+
+impl fmt::Debug for WebSocketAcceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketAcceptor")
+            .field("key", &self.key)
+            .field("requested_protocols", &self.requested_protocols)
+            .field("offered_extensions", &self.offered_extensions)
+            .field("protocol", &self.protocol)
+            .field("per_message_deflate", &self.per_message_deflate)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S> FromRequestContextRefPair<S> for WebSocketAcceptor
+where
+    S: Clone + Send + Sync + 'static,
+{
+    type Rejection = WebSocketRejection;
+
+    async fn from_request_context_ref_pair(
+        ctx: &Context<S>,
+        parts: &Parts,
+    ) -> Result<Self, Self::Rejection> {
+        if parts.method != Method::GET {
+            return Err(WebSocketRejection::MethodNotGet);
+        }
+        if parts.version != Version::HTTP_11 {
+            return Err(WebSocketRejection::InvalidHttpVersion);
+        }
+        if !is_websocket_upgrade(&parts.headers) {
+            return Err(WebSocketRejection::MissingUpgrade);
+        }
+        if parts.headers.get(header::SEC_WEBSOCKET_VERSION) != Some(&WEBSOCKET_VERSION) {
+            return Err(WebSocketRejection::UnsupportedVersion);
+        }
+        let key = match parts.headers.get(header::SEC_WEBSOCKET_KEY) {
+            Some(key) if is_valid_key(key) => key.clone(),
+            _ => return Err(WebSocketRejection::InvalidKey),
+        };
+        let on_upgrade = parts
+            .extensions
+            .get::<OnUpgrade>()
+            .cloned()
+            .ok_or(WebSocketRejection::ConnectionNotUpgradable)?;
+
+        Ok(Self {
+            key,
+            on_upgrade,
+            executor: ctx.executor().clone(),
+            requested_protocols: header_values(&parts.headers, &header::SEC_WEBSOCKET_PROTOCOL)
+                .map(ToOwned::to_owned)
+                .collect(),
+            offered_extensions: parts
+                .headers
+                .get_all(header::SEC_WEBSOCKET_EXTENSIONS)
+                .iter()
+                .cloned()
+                .collect(),
+            protocol: None,
+            per_message_deflate: None,
+            config: WebSocketConfig::default(),
+        })
+    }
+}
+
+impl WebSocketAcceptor {
+    /// The sub protocols requested by the client, in order of preference.
+    pub fn requested_protocols(&self) -> impl Iterator<Item = &str> {
+        self.requested_protocols.iter().map(String::as_str)
+    }
+
+    /// The sub protocol selected by the server, if any.
+    pub fn protocol(&self) -> Option<&HeaderValue> {
+        self.protocol.as_ref()
+    }
+
+    /// Define the sub protocols supported by the server.
+    ///
+    /// The first protocol requested by the client which is supported
+    /// is selected, if any.
+    pub fn with_protocols<I>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item: AsRef<str>>,
+    {
+        let supported: Vec<_> = protocols.into_iter().collect();
+        self.protocol = self
+            .requested_protocols
+            .iter()
+            .find(|requested| supported.iter().any(|p| p.as_ref() == requested.as_str()))
+            .and_then(|protocol| HeaderValue::from_str(protocol).ok());
+        self
+    }
+
+    /// Enable the per-message-deflate extension,
+    /// in case it is offered by the client.
+    pub fn with_per_message_deflate(mut self, config: PerMessageDeflateConfig) -> Self {
+        self.per_message_deflate = Some(config);
+        self
+    }
+
+    /// Define the [`WebSocketConfig`] of the accepted [`WebSocket`].
+    pub fn with_config(mut self, config: WebSocketConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Accept the WebSocket upgrade.
+    ///
+    /// The returned `101 Switching Protocols` [`Response`] is to be returned
+    /// by the endpoint, after which the given callback is spawned
+    /// with the established [`WebSocket`].
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response
+    where
+        F: FnOnce(WebSocket<Upgraded>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let per_message_deflate = self
+            .per_message_deflate
+            .as_ref()
+            .and_then(|config| config.negotiate_offers(&self.offered_extensions));
+
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+        let headers = response.headers_mut();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(header::UPGRADE, WEBSOCKET_UPGRADE);
+        headers.insert(
+            header::SEC_WEBSOCKET_ACCEPT,
+            derive_accept_key(self.key.as_bytes()),
+        );
+        if let Some(protocol) = self.protocol {
+            headers.insert(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+        if let Some(config) = per_message_deflate.as_ref() {
+            headers.insert(header::SEC_WEBSOCKET_EXTENSIONS, config.to_header_value());
+        }
+
+        let on_upgrade = self.on_upgrade;
+        let config = self.config;
+        self.executor.spawn_task(async move {
+            let upgraded = match on_upgrade.await {
+                Ok(upgraded) => upgraded,
+                Err(err) => {
+                    tracing::debug!(error = %err, "websocket acceptor: upgrade failed");
+                    return;
+                }
+            };
+            let mut socket = WebSocket::from_raw_socket(upgraded, Role::Server, config);
+            if let Some(config) = per_message_deflate {
+                socket = socket.with_per_message_deflate(config);
+            }
+            callback(socket).await;
+        });
+
+        response
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+/// Rejection used when the [`WebSocketAcceptor`] fails to validate the upgrade request.
+pub enum WebSocketRejection {
+    /// The request method is not `GET`.
+    MethodNotGet,
+    /// The request is not an HTTP/1.1 request.
+    InvalidHttpVersion,
+    /// The `Connection: upgrade` and/or `Upgrade: websocket` headers are missing.
+    MissingUpgrade,
+    /// The `Sec-WebSocket-Version` header is missing or not `13`.
+    UnsupportedVersion,
+    /// The `Sec-WebSocket-Key` header is missing or invalid.
+    InvalidKey,
+    /// The connection cannot be upgraded by the http server.
+    ConnectionNotUpgradable,
+}
+
+impl WebSocketRejection {
+    /// Get the response body text used for this rejection.
+    pub fn body_text(&self) -> &'static str {
+        match self {
+            Self::MethodNotGet => "WebSocket request method must be `GET`",
+            Self::InvalidHttpVersion => "WebSocket request must be an HTTP/1.1 request",
+            Self::MissingUpgrade => "WebSocket request is missing the upgrade headers",
+            Self::UnsupportedVersion => "WebSocket version is missing or not supported",
+            Self::InvalidKey => "WebSocket request has a missing or invalid `Sec-WebSocket-Key`",
+            Self::ConnectionNotUpgradable => "WebSocket request couldn't be upgraded",
+        }
+    }
+
+    /// Get the status code used for this rejection.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::MethodNotGet => StatusCode::METHOD_NOT_ALLOWED,
+            Self::UnsupportedVersion => StatusCode::UPGRADE_REQUIRED,
+            Self::ConnectionNotUpgradable => StatusCode::UPGRADE_REQUIRED,
+            Self::InvalidHttpVersion | Self::MissingUpgrade | Self::InvalidKey => {
+                StatusCode::BAD_REQUEST
+            }
+        }
+    }
+}
+
+impl IntoResponse for WebSocketRejection {
+    fn into_response(self) -> Response {
+        tracing::debug!(
+            status = %self.status(),
+            body = self.body_text(),
+            "rejecting request due to WebSocketRejection",
+        );
+        let mut response = (self.status(), self.body_text()).into_response();
+        if self == Self::UnsupportedVersion {
+            response
+                .headers_mut()
+                .insert(header::SEC_WEBSOCKET_VERSION, WEBSOCKET_VERSION);
+        }
+        response
+    }
+}
+
+impl fmt::Display for WebSocketRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.body_text())
+    }
+}
+
+impl std::error::Error for WebSocketRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rama_core::Service;
+    use rama_http::Request;
+    use rama_http::service::web::WebService;
+
+    fn upgrade_request(version: &str, key: &str) -> Request {
+        Request::builder()
+            .uri("/ws")
+            .header(header::CONNECTION, "Upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_VERSION, version)
+            .header(header::SEC_WEBSOCKET_KEY, key)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejections() {
+        let service = WebService::default().get("/ws", async |ws: WebSocketAcceptor| {
+            ws.on_upgrade(async |_| {})
+        });
+
+        const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+        for (req, status) in [
+            (
+                Request::builder().uri("/ws").body(Body::empty()).unwrap(),
+                StatusCode::BAD_REQUEST,
+            ),
+            (upgrade_request("8", KEY), StatusCode::UPGRADE_REQUIRED),
+            (upgrade_request("13", "invalid"), StatusCode::BAD_REQUEST),
+            // no OnUpgrade available as the request is not served by an http server
+            (upgrade_request("13", KEY), StatusCode::UPGRADE_REQUIRED),
+        ] {
+            let resp = service.serve(Context::default(), req).await.unwrap();
+            assert_eq!(resp.status(), status);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_version_rejection() {
+        let resp = WebSocketRejection::UnsupportedVersion.into_response();
+        assert_eq!(resp.status(), StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(
+            resp.headers().get(header::SEC_WEBSOCKET_VERSION),
+            Some(&WEBSOCKET_VERSION)
+        );
+    }
+}
@@ -0,0 +1,598 @@
+use crate::deflate::{DeflateContext, PerMessageDeflateConfig};
+use crate::protocol::{CloseFrame, Frame, FrameHeader, Message, OpCode, ProtocolError, Role};
+use bytes::{Buf, Bytes, BytesMut};
+use rama_utils::macros::generate_field_setters;
+use std::fmt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Max payload size of a control frame.
+const MAX_CONTROL_FRAME_PAYLOAD: usize = 125;
+
+const READ_BUFFER_CAPACITY: usize = 8 * 1024;
+
+#[derive(Debug, Clone)]
+/// Configuration of a [`WebSocket`].
+pub struct WebSocketConfig {
+    max_message_size: Option<usize>,
+    max_frame_size: Option<usize>,
+    max_write_frame_size: Option<usize>,
+    accept_unmasked_frames: bool,
+}
+
+impl WebSocketConfig {
+    /// Create a new default [`WebSocketConfig`].
+    pub const fn new() -> Self {
+        Self {
+            max_message_size: Some(64 << 20),
+            max_frame_size: Some(16 << 20),
+            max_write_frame_size: None,
+            accept_unmasked_frames: false,
+        }
+    }
+
+    generate_field_setters!(max_message_size, usize);
+    generate_field_setters!(max_frame_size, usize);
+    generate_field_setters!(max_write_frame_size, usize);
+
+    /// Accept unmasked frames sent by a client,
+    /// which is a violation of the protocol but tolerated by some servers.
+    pub fn with_accept_unmasked_frames(mut self, accept: bool) -> Self {
+        self.accept_unmasked_frames = accept;
+        self
+    }
+
+    /// Accept unmasked frames sent by a client,
+    /// which is a violation of the protocol but tolerated by some servers.
+    pub fn set_accept_unmasked_frames(&mut self, accept: bool) -> &mut Self {
+        self.accept_unmasked_frames = accept;
+        self
+    }
+
+    /// The max size of a (reassembled and decompressed) message received,
+    /// `None` in case there is no limit.
+    pub fn max_message_size(&self) -> Option<usize> {
+        self.max_message_size
+    }
+
+    /// The max payload size of a single frame received,
+    /// `None` in case there is no limit.
+    pub fn max_frame_size(&self) -> Option<usize> {
+        self.max_frame_size
+    }
+
+    /// The max payload size of a single data frame sent,
+    /// messages larger than this are fragmented.
+    /// `None` in case messages are never fragmented.
+    pub fn max_write_frame_size(&self) -> Option<usize> {
+        self.max_write_frame_size
+    }
+
+    /// Returns `true` in case unmasked client frames are accepted.
+    pub fn accept_unmasked_frames(&self) -> bool {
+        self.accept_unmasked_frames
+    }
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A WebSocket connection, established over a stream `S`
+/// on which the opening handshake was already completed.
+///
+/// Received pings and close frames are replied to automatically
+/// when receiving [`Message`]s, with the replies being flushed as part
+/// of the next receive or send call.
+///
+/// All methods are cancel safe.
+pub struct WebSocket<S> {
+    stream: S,
+    role: Role,
+    config: WebSocketConfig,
+    deflate: Option<DeflateContext>,
+    read_buf: BytesMut,
+    write_buf: BytesMut,
+    incomplete: Option<IncompleteMessage>,
+    close_sent: bool,
+    close_received: bool,
+}
+
+struct IncompleteMessage {
+    opcode: OpCode,
+    compressed: bool,
+    data: BytesMut,
+}
+
+impl<S: fmt::Debug> fmt::Debug for WebSocket<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocket")
+            .field("stream", &self.stream)
+            .field("role", &self.role)
+            .field("config", &self.config)
+            .field("deflate", &self.deflate)
+            .field("close_sent", &self.close_sent)
+            .field("close_received", &self.close_received)
+            .finish()
+    }
+}
+
+impl<S> WebSocket<S> {
+    /// Create a [`WebSocket`] from a raw stream
+    /// for which the opening handshake was already completed.
+    pub fn from_raw_socket(stream: S, role: Role, config: WebSocketConfig) -> Self {
+        Self {
+            stream,
+            role,
+            config,
+            deflate: None,
+            read_buf: BytesMut::with_capacity(READ_BUFFER_CAPACITY),
+            write_buf: BytesMut::new(),
+            incomplete: None,
+            close_sent: false,
+            close_received: false,
+        }
+    }
+
+    /// Enable the per-message-deflate extension,
+    /// as negotiated during the opening handshake.
+    pub fn with_per_message_deflate(mut self, config: PerMessageDeflateConfig) -> Self {
+        self.deflate = Some(DeflateContext::new(self.role, config));
+        self
+    }
+
+    /// The [`Role`] of this side of the [`WebSocket`].
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// The [`WebSocketConfig`] of this [`WebSocket`].
+    pub fn config(&self) -> &WebSocketConfig {
+        &self.config
+    }
+
+    /// The negotiated per-message-deflate config, if any.
+    pub fn per_message_deflate(&self) -> Option<&PerMessageDeflateConfig> {
+        self.deflate.as_ref().map(DeflateContext::config)
+    }
+
+    /// Returns `true` in case a close frame was sent.
+    pub fn is_close_sent(&self) -> bool {
+        self.close_sent
+    }
+
+    /// Returns `true` in case a close frame was received.
+    pub fn is_close_received(&self) -> bool {
+        self.close_received
+    }
+
+    /// Get a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the underlying stream.
+    ///
+    /// Reading or writing directly from or to the stream
+    /// will most likely corrupt the WebSocket connection.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consume the [`WebSocket`] into the underlying stream.
+    ///
+    /// Any buffered data which was not yet read or written is lost.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> WebSocket<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Receive the next (validated and unmasked) [`Frame`].
+    ///
+    /// No automatic replies are sent for control frames
+    /// received this way, nor is the payload decompressed.
+    ///
+    /// Returns `None` in case the stream was closed
+    /// or a close frame was already received.
+    pub async fn recv_frame(&mut self) -> Result<Option<Frame>, ProtocolError> {
+        if self.close_received {
+            return Ok(None);
+        }
+
+        // flush pending (automatic) replies first
+        self.flush().await?;
+
+        let frame = loop {
+            if let Some(frame) = Frame::parse(&mut self.read_buf, self.config.max_frame_size)? {
+                break frame;
+            }
+            if self.stream.read_buf(&mut self.read_buf).await? == 0 {
+                if self.read_buf.is_empty() && self.incomplete.is_none() {
+                    return Ok(None);
+                }
+                return Err(ProtocolError::IO(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+        };
+
+        self.validate_frame(&frame.header, frame.payload.len())?;
+        if frame.header.opcode == OpCode::Close {
+            self.close_received = true;
+        }
+        Ok(Some(frame))
+    }
+
+    fn validate_frame(&self, header: &FrameHeader, len: usize) -> Result<(), ProtocolError> {
+        match (self.role, header.mask.is_some()) {
+            (Role::Server, false) if !self.config.accept_unmasked_frames => {
+                return Err(ProtocolError::UnexpectedMasking);
+            }
+            (Role::Client, true) => return Err(ProtocolError::UnexpectedMasking),
+            _ => (),
+        }
+
+        if header.rsv2 || header.rsv3 {
+            return Err(ProtocolError::ReservedBitsSet);
+        }
+
+        match header.opcode {
+            OpCode::Text | OpCode::Binary => {
+                if header.rsv1 && self.deflate.is_none() {
+                    return Err(ProtocolError::ReservedBitsSet);
+                }
+            }
+            OpCode::Continue => {
+                if header.rsv1 {
+                    return Err(ProtocolError::ReservedBitsSet);
+                }
+            }
+            OpCode::Close | OpCode::Ping | OpCode::Pong => {
+                if header.rsv1 {
+                    return Err(ProtocolError::ReservedBitsSet);
+                }
+                if !header.fin || len > MAX_CONTROL_FRAME_PAYLOAD {
+                    return Err(ProtocolError::InvalidControlFrame);
+                }
+            }
+            opcode @ OpCode::Unknown(_) => return Err(ProtocolError::InvalidOpCode(opcode)),
+        }
+
+        Ok(())
+    }
+
+    /// Send a single [`Frame`] as-is (masked in case of a client),
+    /// without any fragmentation or compression applied.
+    pub async fn send_frame(&mut self, frame: Frame) -> Result<(), ProtocolError> {
+        self.queue_frame(frame)?;
+        self.flush().await
+    }
+
+    fn queue_frame(&mut self, mut frame: Frame) -> Result<(), ProtocolError> {
+        if self.close_sent {
+            return Err(ProtocolError::AlreadyClosed);
+        }
+        if frame.header.opcode == OpCode::Close {
+            self.close_sent = true;
+        }
+        frame.header.mask = match self.role {
+            Role::Client => Some(rand::random()),
+            Role::Server => None,
+        };
+        self.write_buf.reserve(frame.serialized_len());
+        frame.write_to_buf(&mut self.write_buf);
+        Ok(())
+    }
+
+    /// Flush all pending frames to the underlying stream.
+    pub async fn flush(&mut self) -> Result<(), ProtocolError> {
+        while !self.write_buf.is_empty() {
+            let n = self.stream.write(&self.write_buf).await?;
+            if n == 0 {
+                return Err(ProtocolError::IO(std::io::ErrorKind::WriteZero.into()));
+            }
+            self.write_buf.advance(n);
+        }
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Receive the next [`Message`].
+    ///
+    /// Pings are replied to with a pong and a close frame is echoed
+    /// (unless already sent), after which no more messages are received.
+    ///
+    /// Returns `None` in case the stream was closed
+    /// or the close handshake was completed.
+    pub async fn recv_message(&mut self) -> Result<Option<Message>, ProtocolError> {
+        loop {
+            let Some(frame) = self.recv_frame().await? else {
+                return Ok(None);
+            };
+
+            match frame.header.opcode {
+                OpCode::Ping => {
+                    if !self.close_sent {
+                        self.queue_frame(Frame::new(OpCode::Pong, frame.payload.clone()))?;
+                    }
+                    return Ok(Some(Message::Ping(frame.payload)));
+                }
+                OpCode::Pong => return Ok(Some(Message::Pong(frame.payload))),
+                OpCode::Close => {
+                    let close = CloseFrame::parse(&frame.payload)?;
+                    if !self.close_sent {
+                        let payload = close
+                            .as_ref()
+                            .map(|close| CloseFrame::new(close.code, "").to_payload())
+                            .unwrap_or_default();
+                        self.queue_frame(Frame::new(OpCode::Close, payload))?;
+                        self.flush().await?;
+                    }
+                    return Ok(Some(Message::Close(close)));
+                }
+                OpCode::Text | OpCode::Binary => {
+                    if self.incomplete.is_some() {
+                        return Err(ProtocolError::ExpectedContinuation);
+                    }
+                    let compressed = frame.header.rsv1;
+                    if frame.header.fin {
+                        return self
+                            .finish_message(frame.header.opcode, compressed, frame.payload)
+                            .map(Some);
+                    }
+                    self.check_message_size(frame.payload.len())?;
+                    self.incomplete = Some(IncompleteMessage {
+                        opcode: frame.header.opcode,
+                        compressed,
+                        data: BytesMut::from(frame.payload),
+                    });
+                }
+                OpCode::Continue => {
+                    let Some(incomplete) = self.incomplete.as_mut() else {
+                        return Err(ProtocolError::UnexpectedContinuation);
+                    };
+                    incomplete.data.extend_from_slice(&frame.payload);
+                    let size = incomplete.data.len();
+                    self.check_message_size(size)?;
+                    if frame.header.fin {
+                        let IncompleteMessage {
+                            opcode,
+                            compressed,
+                            data,
+                        } = self.incomplete.take().expect("incomplete message");
+                        return self
+                            .finish_message(opcode, compressed, data.freeze())
+                            .map(Some);
+                    }
+                }
+                opcode @ OpCode::Unknown(_) => return Err(ProtocolError::InvalidOpCode(opcode)),
+            }
+        }
+    }
+
+    fn check_message_size(&self, size: usize) -> Result<(), ProtocolError> {
+        match self.config.max_message_size {
+            Some(max) if size > max => Err(ProtocolError::MessageTooLarge { size, max }),
+            _ => Ok(()),
+        }
+    }
+
+    fn finish_message(
+        &mut self,
+        opcode: OpCode,
+        compressed: bool,
+        payload: Bytes,
+    ) -> Result<Message, ProtocolError> {
+        let payload = if compressed {
+            let deflate = self
+                .deflate
+                .as_mut()
+                .ok_or(ProtocolError::ReservedBitsSet)?;
+            deflate.decompress(&payload, self.config.max_message_size)?
+        } else {
+            self.check_message_size(payload.len())?;
+            payload
+        };
+        Message::from_payload(opcode, payload)
+    }
+
+    /// Send a [`Message`], compressing and fragmenting it
+    /// as negotiated and configured.
+    pub async fn send_message(&mut self, message: Message) -> Result<(), ProtocolError> {
+        if self.close_sent {
+            return Err(ProtocolError::AlreadyClosed);
+        }
+
+        let opcode = message.opcode();
+        if opcode.is_control() {
+            if message.len() > MAX_CONTROL_FRAME_PAYLOAD {
+                return Err(ProtocolError::InvalidControlFrame);
+            }
+            self.queue_frame(Frame::new(opcode, message.into_payload()))?;
+            return self.flush().await;
+        }
+
+        let mut payload = message.into_payload();
+        let compressed = match self.deflate.as_mut() {
+            Some(deflate) => {
+                payload = deflate.compress(&payload)?;
+                true
+            }
+            None => false,
+        };
+
+        let chunk_size = self
+            .config
+            .max_write_frame_size
+            .filter(|size| *size > 0)
+            .unwrap_or(usize::MAX);
+        let mut first = true;
+        loop {
+            let chunk = payload.split_to(payload.len().min(chunk_size));
+            let mut frame = Frame::new(if first { opcode } else { OpCode::Continue }, chunk);
+            frame.header.rsv1 = first && compressed;
+            frame.header.fin = payload.is_empty();
+            self.queue_frame(frame)?;
+            if payload.is_empty() {
+                break;
+            }
+            first = false;
+        }
+        self.flush().await
+    }
+
+    /// Initiate the close handshake by sending a close frame,
+    /// in case none was sent already.
+    ///
+    /// Continue to [receive messages] until `None` is returned
+    /// in order to complete the close handshake.
+    ///
+    /// [receive messages]: Self::recv_message
+    pub async fn close(&mut self, frame: Option<CloseFrame>) -> Result<(), ProtocolError> {
+        if !self.close_sent {
+            self.queue_frame(Frame::new(
+                OpCode::Close,
+                frame.map(|frame| frame.to_payload()).unwrap_or_default(),
+            ))?;
+        }
+        self.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::CloseCode;
+    use tokio::io::{DuplexStream, duplex};
+
+    fn socket_pair(
+        config: WebSocketConfig,
+        deflate: Option<PerMessageDeflateConfig>,
+    ) -> (WebSocket<DuplexStream>, WebSocket<DuplexStream>) {
+        let (client, server) = duplex(1024);
+        let mut client = WebSocket::from_raw_socket(client, Role::Client, config.clone());
+        let mut server = WebSocket::from_raw_socket(server, Role::Server, config);
+        if let Some(deflate) = deflate {
+            client = client.with_per_message_deflate(deflate.clone());
+            server = server.with_per_message_deflate(deflate);
+        }
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_message_roundtrip() {
+        for deflate in [None, Some(PerMessageDeflateConfig::default())] {
+            let (mut client, mut server) = socket_pair(
+                WebSocketConfig::default().with_max_write_frame_size(16),
+                deflate,
+            );
+
+            let messages = [
+                Message::text("Hello, world!"),
+                Message::text("a".repeat(100)),
+                Message::binary(vec![0xAB; 1000]),
+                Message::Ping(Bytes::from_static(b"ping")),
+            ];
+            let expected = messages.clone();
+            let client_task = tokio::spawn(async move {
+                for message in messages {
+                    client.send_message(message).await.unwrap();
+                }
+                let pong = client.recv_message().await.unwrap().unwrap();
+                assert_eq!(pong, Message::Pong(Bytes::from_static(b"ping")));
+                client.close(None).await.unwrap();
+                assert_eq!(
+                    client.recv_message().await.unwrap(),
+                    Some(Message::Close(None))
+                );
+                assert!(client.recv_message().await.unwrap().is_none());
+            });
+
+            for message in expected {
+                assert_eq!(server.recv_message().await.unwrap().unwrap(), message);
+            }
+            assert_eq!(
+                server.recv_message().await.unwrap(),
+                Some(Message::Close(None))
+            );
+            assert!(server.is_close_sent());
+            assert!(server.send_message(Message::text("late")).await.is_err());
+            drop(server);
+
+            client_task.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_close_echo() {
+        let (mut client, mut server) = socket_pair(WebSocketConfig::default(), None);
+        server
+            .close(Some(CloseFrame::new(CloseCode::GoingAway, "bye")))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.recv_message().await.unwrap(),
+            Some(Message::Close(Some(CloseFrame::new(
+                CloseCode::GoingAway,
+                "bye"
+            ))))
+        );
+        assert_eq!(
+            server.recv_message().await.unwrap(),
+            Some(Message::Close(Some(CloseFrame::new(
+                CloseCode::GoingAway,
+                ""
+            ))))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unmasked_client_frame_rejected() {
+        let (client, server) = duplex(1024);
+        // a server-role socket never masks its frames
+        let mut client = WebSocket::from_raw_socket(client, Role::Server, WebSocketConfig::new());
+        let mut server = WebSocket::from_raw_socket(server, Role::Server, WebSocketConfig::new());
+        client
+            .send_frame(Frame::new(OpCode::Text, "hi"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            server.recv_frame().await,
+            Err(ProtocolError::UnexpectedMasking)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_message_too_large() {
+        let (mut client, mut server) = socket_pair(
+            WebSocketConfig::default()
+                .with_max_message_size(10)
+                .with_max_write_frame_size(4),
+            None,
+        );
+        client
+            .send_message(Message::binary(vec![0; 11]))
+            .await
+            .unwrap();
+        assert!(matches!(
+            server.recv_message().await,
+            Err(ProtocolError::MessageTooLarge { size: 11, max: 10 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_continuation() {
+        let (mut client, mut server) = socket_pair(WebSocketConfig::default(), None);
+        client
+            .send_frame(Frame::new(OpCode::Continue, "oops"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            server.recv_message().await,
+            Err(ProtocolError::UnexpectedContinuation)
+        ));
+    }
+}
@@ -9,3 +9,9 @@ pub mod service;
 mod forward;
 #[doc(inline)]
 pub use forward::ForwardKind;
+
+#[cfg(feature = "tls")]
+mod sni_cert;
+#[cfg(feature = "tls")]
+#[doc(inline)]
+pub use sni_cert::SniCertArg;
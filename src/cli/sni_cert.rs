@@ -0,0 +1,105 @@
+use crate::error::{ErrorContext, OpaqueError};
+use crate::net::tls::{
+    DataEncoding,
+    server::{ServerAuthData, ServerNamePattern, SniMap},
+};
+use rama_utils::str::NonEmptyString;
+use std::{fs, path::PathBuf, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Certificate chain and private key (PEM files) to be served
+/// for a server name (SNI) pattern, parsed from `host=cert.pem,key.pem`.
+///
+/// The host can be a wildcard pattern such as `*.example.com`,
+/// see [`ServerNamePattern`] for more information.
+pub struct SniCertArg {
+    /// pattern matched against the server name (SNI) sent by the client
+    pub pattern: ServerNamePattern,
+    /// path to the PEM-encoded certificate chain
+    pub cert_chain_path: PathBuf,
+    /// path to the PEM-encoded private key
+    pub private_key_path: PathBuf,
+}
+
+impl SniCertArg {
+    /// Load the certificate chain and private key from disk.
+    pub fn load(&self) -> Result<ServerAuthData, OpaqueError> {
+        let cert_chain = fs::read_to_string(&self.cert_chain_path)
+            .with_context(|| format!("read cert chain: {}", self.cert_chain_path.display()))?;
+        let private_key = fs::read_to_string(&self.private_key_path)
+            .with_context(|| format!("read private key: {}", self.private_key_path.display()))?;
+        Ok(ServerAuthData {
+            private_key: DataEncoding::Pem(
+                NonEmptyString::try_from(private_key).context("private key")?,
+            ),
+            cert_chain: DataEncoding::Pem(
+                NonEmptyString::try_from(cert_chain).context("cert chain")?,
+            ),
+            ocsp: None,
+        })
+    }
+
+    /// Load all given certificates into a [`SniMap`],
+    /// using the first certificate as the default one.
+    pub fn try_into_sni_map(
+        args: impl IntoIterator<Item = Self>,
+    ) -> Result<SniMap<ServerAuthData>, OpaqueError> {
+        let mut map = SniMap::new();
+        for arg in args {
+            let data = arg.load()?;
+            if map.is_empty() {
+                map.set_default(data.clone());
+            }
+            map.insert(arg.pattern, data);
+        }
+        Ok(map)
+    }
+}
+
+impl FromStr for SniCertArg {
+    type Err = OpaqueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, paths) = s
+            .split_once('=')
+            .context("expected sni cert in format host=cert.pem,key.pem")?;
+        let (cert_chain_path, private_key_path) = paths
+            .split_once(',')
+            .context("expected sni cert paths in format cert.pem,key.pem")?;
+        Ok(Self {
+            pattern: host.trim().parse()?,
+            cert_chain_path: cert_chain_path.trim().into(),
+            private_key_path: private_key_path.trim().into(),
+        })
+    }
+}
+
+impl TryFrom<&str> for SniCertArg {
+    type Error = OpaqueError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::address::Domain;
+
+    #[test]
+    fn test_sni_cert_arg_parse() {
+        assert_eq!(
+            "*.example.com=certs/example.pem,certs/example.key"
+                .parse::<SniCertArg>()
+                .unwrap(),
+            SniCertArg {
+                pattern: ServerNamePattern::Wildcard(Domain::from_static("example.com")),
+                cert_chain_path: "certs/example.pem".into(),
+                private_key_path: "certs/example.key".into(),
+            }
+        );
+        assert!("example.com=cert.pem".parse::<SniCertArg>().is_err());
+        assert!("cert.pem,key.pem".parse::<SniCertArg>().is_err());
+    }
+}
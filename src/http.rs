@@ -1,7 +1,7 @@
 //! rama http support
 //!
 //! mostly contains re-exports from
-//! `rama-http` and `rama-http-backend`.
+//! `rama-http`, `rama-http-backend` and `rama-ws`.
 
 #[doc(inline)]
 pub use ::rama_http::{
@@ -32,3 +32,7 @@ pub mod layer {
 #[cfg(feature = "http-full")]
 #[doc(inline)]
 pub use ::rama_http_backend::{client, server};
+
+#[cfg(feature = "http-full")]
+#[doc(inline)]
+pub use ::rama_ws as ws;
@@ -48,14 +48,14 @@
 //!
 //! | category | support list |
 //! |-|-|
-//! | ✅ [transports](crate::net::stream) | ✅ [tcp] ⸱ ✅ [udp] ⸱ ✅ [middleware](crate::net::stream::layer) |
-//! | ✅ [http] | ✅ [auto](crate::http::server::service::HttpServer::auto) ⸱ ✅ [http/1.1](crate::http::server::service::HttpServer::http1) ⸱ ✅ [h2](crate::http::server::service::HttpServer::h2) ⸱ 🏗️ h3 <sup>(1)</sup> ⸱ ✅ [middleware](crate::http::layer) |
+//! | ✅ [transports](crate::net::stream) | ✅ [tcp] ⸱ ✅ [udp] ⸱ ✅ [quic] ⸱ ✅ [middleware](crate::net::stream::layer) |
+//! | ✅ [http] | ✅ [auto](crate::http::server::service::HttpServer::auto) ⸱ ✅ [http/1.1](crate::http::server::service::HttpServer::http1) ⸱ ✅ [h2](crate::http::server::service::HttpServer::h2) ⸱ ✅ [h3](crate::http::server::service::HttpServer::h3) ⸱ ✅ [middleware](crate::http::layer) |
 //! | ✅ web server | ✅ [fs](crate::http::service::fs) ⸱ ✅ [redirect](crate::http::service::redirect::Redirect) ⸱ ✅ [router](crate::http::service::web::Router) ⸱ ✅ [dyn router](crate::http::service::web::WebService) ⸱ ✅ [static router](crate::http::service::web::match_service) ⸱ ✅ [handler extractors](crate::http::service::web::extract) ⸱ ✅ [k8s healthcheck](crate::http::service::web::k8s) |
 //! | ✅ [http client](crate::http::client) | ✅ [easy client](crate::http::client::EasyHttpWebClient) ⸱ ✅ [high level API](crate::http::service::client::HttpClientExt) ⸱ ✅ [Proxy Connect](crate::http::client::proxy::layer::HttpProxyConnector) ⸱ ❌ [Chromium Http](https://github.com/plabayo/rama/issues/189) <sup>(3)</sup> |
 //! | ✅ [tls] | ✅ [Rustls](crate::tls::rustls) ⸱ ✅ [BoringSSL](crate::tls::boring) ⸱ ❌ NSS <sup>(3)</sup> |
 //! | ✅ [dns] | ✅ [DNS Resolver][crate::dns::DnsResolver] |
-//! | ✅ [proxy] protocols | ✅ [PROXY protocol](crate::proxy::haproxy) ⸱ ✅ [http proxy](https://github.com/plabayo/rama/blob/main/examples/http_connect_proxy.rs) ⸱ ✅ [https proxy](https://github.com/plabayo/rama/blob/main/examples/https_connect_proxy.rs) ⸱ 🏗️ SOCKS5 <sup>(2)</sup> ⸱ 🏗️ SOCKS5H <sup>(2)</sup> |
-//! | 🏗️ web protocols | 🏗️ Web Sockets <sup>(1)</sup> ⸱ ❌ Web Transport <sup>(3)</sup> ⸱ ❌ gRPC <sup>(3)</sup> |
+//! | ✅ [proxy] protocols | ✅ [PROXY protocol](crate::proxy::haproxy) ⸱ ✅ [http proxy](https://github.com/plabayo/rama/blob/main/examples/http_connect_proxy.rs) ⸱ ✅ [https proxy](https://github.com/plabayo/rama/blob/main/examples/https_connect_proxy.rs) ⸱ ✅ [SOCKS5](crate::proxy::socks5) ⸱ ✅ [SOCKS5H](crate::proxy::socks5) |
+//! | ✅ web protocols | ✅ [Web Sockets](crate::http::ws) ⸱ ❌ Web Transport <sup>(3)</sup> ⸱ ❌ gRPC <sup>(3)</sup> |
 //! | ✅ [async-method trait](https://blog.rust-lang.org/inside-rust/2023/05/03/stabilizing-async-fn-in-trait.html) services | ✅ [Service] ⸱ ✅ [Layer] ⸱ ✅ [context] ⸱ ✅ [dyn dispatch](crate::service::BoxService) ⸱ ✅ [middleware](crate::layer) |
 //! | ✅ [telemetry] | ✅ [tracing](https://tracing.rs/tracing/) ⸱ ✅ [opentelemetry][telemetry::opentelemetry] ⸱ ✅ [http metrics](crate::http::layer::opentelemetry) ⸱ ✅ [transport metrics](crate::net::stream::layer::opentelemetry) |
 //! | ✅ upstream [proxies](proxy) | ✅ [MemoryProxyDB](crate::proxy::MemoryProxyDB) ⸱ ✅ [L4 Username Config] ⸱ ✅ [Proxy Filters](crate::proxy::ProxyFilter) |
@@ -182,10 +182,12 @@
 //! - [`rama-dns`](https://crates.io/crates/rama-dns): DNS support for rama
 //! - [`rama-tcp`](https://crates.io/crates/rama-tcp): TCP support for rama
 //! - [`rama-udp`](https://crates.io/crates/rama-udp): UDP support for rama
+//! - [`rama-quic`](https://crates.io/crates/rama-quic): QUIC support for rama
 //! - [`rama-tls-rustls`](https://crates.io/crates/rama-tls-rustls): [Rustls](https://github.com/rustls/rustls) support for rama
 //! - [`rama-tls`](https://crates.io/crates/rama-tls): TLS support for rama (types, `rustls` and `boring`)
 //! - [`rama-proxy`](https://crates.io/crates/rama-proxy): proxy types and utilities for rama
 //! - [`rama-socks5`](https://crates.io/crates/rama-socks5): SOCKS5 support for rama
+//! - [`rama-ws`](https://crates.io/crates/rama-ws): WebSocket support for rama
 //! - [`rama-haproxy`](https://crates.io/crates/rama-haproxy): rama HaProxy support
 //! - [`rama-ua`](https://crates.io/crates/rama-ua): User-Agent (UA) support for `rama`
 //! - [`rama-http-types`](https://crates.io/crates/rama-http-types): http types and utilities
@@ -373,6 +375,10 @@ pub use ::rama_tcp as tcp;
 #[doc(inline)]
 pub use ::rama_udp as udp;
 
+#[cfg(feature = "quic")]
+#[doc(inline)]
+pub use ::rama_quic as quic;
+
 #[cfg(feature = "telemetry")]
 #[doc(inline)]
 pub use ::rama_core::telemetry;
@@ -399,7 +405,7 @@ pub use ::rama_net as net;
 #[cfg(feature = "http")]
 pub mod http;
 
-#[cfg(any(feature = "proxy", feature = "haproxy"))]
+#[cfg(any(feature = "proxy", feature = "haproxy", feature = "socks5"))]
 pub mod proxy {
     //! rama proxy support
 
@@ -410,6 +416,10 @@ pub mod proxy {
     #[cfg(feature = "haproxy")]
     #[doc(inline)]
     pub use ::rama_haproxy as haproxy;
+
+    #[cfg(feature = "socks5")]
+    #[doc(inline)]
+    pub use ::rama_socks5 as socks5;
 }
 
 #[cfg(feature = "ua")]
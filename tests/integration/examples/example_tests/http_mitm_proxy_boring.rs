@@ -1,7 +1,18 @@
 use super::utils;
 use rama::{
-    Context, Layer,
-    http::{BodyExtractExt, Request, response::Json, server::HttpServer},
+    Context, Layer, Service,
+    http::{
+        Body, BodyExtractExt, Request,
+        client::EasyHttpWebClient,
+        client::proxy::layer::SetProxyAuthHttpHeaderLayer,
+        response::Json,
+        server::HttpServer,
+        service::web::WebService,
+        ws::{
+            WebSocketAcceptor, WebSocketConnector,
+            protocol::{CloseCode, CloseFrame, Message},
+        },
+    },
     net::address::ProxyAddress,
     net::tls::server::SelfSignedData,
     rt::Executor,
@@ -31,6 +42,26 @@ async fn test_http_mitm_proxy() {
             .unwrap();
     });
 
+    tokio::spawn(async {
+        HttpServer::auto(Executor::default())
+            .listen(
+                "127.0.0.1:63008",
+                WebService::default().get("/echo", async |ws: WebSocketAcceptor| {
+                    ws.on_upgrade(async |mut socket| {
+                        while let Ok(Some(message)) = socket.recv_message().await {
+                            if matches!(message, Message::Text(_) | Message::Binary(_))
+                                && socket.send_message(message).await.is_err()
+                            {
+                                return;
+                            }
+                        }
+                    })
+                }),
+            )
+            .await
+            .unwrap();
+    });
+
     let data = TlsAcceptorDataBuilder::new_self_signed(SelfSignedData {
         organisation_name: Some("Example Server Acceptor".to_owned()),
         ..Default::default()
@@ -88,4 +119,32 @@ async fn test_http_mitm_proxy() {
         .unwrap();
     let expected_value = json!({"method":"GET","path":"/foo/bar"});
     assert_eq!(expected_value, result);
+
+    // test websocket relay flow
+    let req = Request::builder()
+        .uri("ws://127.0.0.1:63008/echo")
+        .body(Body::empty())
+        .unwrap();
+    let mut socket = WebSocketConnector::new(
+        SetProxyAuthHttpHeaderLayer::default().into_layer(EasyHttpWebClient::default()),
+    )
+    .serve(ctx, req)
+    .await
+    .unwrap()
+    .into_socket();
+
+    socket.send_message(Message::text("hello")).await.unwrap();
+    assert_eq!(
+        socket.recv_message().await.unwrap(),
+        Some(Message::text("hello"))
+    );
+
+    socket
+        .close(Some(CloseFrame::new(CloseCode::NormalClosure, "")))
+        .await
+        .unwrap();
+    assert!(matches!(
+        socket.recv_message().await.unwrap(),
+        Some(Message::Close(_))
+    ));
 }